@@ -35,6 +35,8 @@ pub struct NetworkConfig {
     pub identity_server: String,
     /// FrogCrypto server URL
     pub frogcrypto_server: String,
+    /// Additional peer document servers, consulted alongside document_server
+    pub peers: Vec<String>,
     /// Request timeout in seconds
     pub timeout_seconds: u32,
 }
@@ -45,6 +47,7 @@ impl Default for NetworkConfig {
             document_server: "https://pod-server.ghost-spica.ts.net/server-new".to_string(),
             identity_server: "https://pod-server.ghost-spica.ts.net/identity-new".to_string(),
             frogcrypto_server: "https://frog-server-q36c.onrender.com".to_string(),
+            peers: Vec::new(),
             timeout_seconds: 30,
         }
     }
@@ -91,6 +94,71 @@ impl Default for LoggingConfig {
     }
 }
 
+/// Feature gates. A disabled feature's commands refuse to run (see
+/// [`FeatureConfig::ensure_enabled`]), so turning one off here is an actual
+/// lockdown rather than just hidden UI.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default = "FeatureConfig::default")]
+pub struct FeatureConfig {
+    /// FrogCrypto commands
+    pub frog: bool,
+    /// Outbound HTTP utilities (URL fetching)
+    pub networking: bool,
+    /// PodNet document publishing, drafts, and notifications
+    pub documents: bool,
+}
+
+impl Default for FeatureConfig {
+    fn default() -> Self {
+        Self {
+            frog: true,
+            networking: true,
+            documents: true,
+        }
+    }
+}
+
+/// A gate a command can sit behind; see [`FeatureConfig`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Feature {
+    Frog,
+    Networking,
+    Documents,
+}
+
+impl Feature {
+    fn name(self) -> &'static str {
+        match self {
+            Feature::Frog => "frog",
+            Feature::Networking => "networking",
+            Feature::Documents => "documents",
+        }
+    }
+}
+
+impl FeatureConfig {
+    fn is_enabled(&self, feature: Feature) -> bool {
+        match feature {
+            Feature::Frog => self.frog,
+            Feature::Networking => self.networking,
+            Feature::Documents => self.documents,
+        }
+    }
+
+    /// Guard for feature-gated commands. The `FeatureDisabled:` prefix is
+    /// stable so the frontend can tell lockdown apart from real failures.
+    pub fn ensure_enabled(&self, feature: Feature) -> Result<(), String> {
+        if self.is_enabled(feature) {
+            Ok(())
+        } else {
+            Err(format!(
+                "FeatureDisabled: the '{}' feature is turned off in the configuration",
+                feature.name()
+            ))
+        }
+    }
+}
+
 /// Main application configuration
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 #[serde(default)]
@@ -103,6 +171,8 @@ pub struct AppConfig {
     pub ui: UiConfig,
     /// Logging configuration
     pub logging: LoggingConfig,
+    /// Feature gates
+    pub features: FeatureConfig,
 }
 
 /// Global configuration instance with thread-safe access
@@ -182,67 +252,43 @@ impl AppConfig {
         Ok(())
     }
 
-    /// Apply a single override using dot notation
+    /// Apply a single override using dot notation. The configuration is
+    /// round-tripped through TOML so any dotted path into it works; the
+    /// value may be a JSON literal (`'["a","b"]'`, `'{"k":1}'`, `42`,
+    /// `true`) for structured fields, while anything that doesn't parse as
+    /// JSON is taken as a plain string.
     fn apply_single_override(&mut self, key_path: &str, value: &str) -> Result<(), String> {
-        let parts: Vec<&str> = key_path.split('.').collect();
-
-        match parts.as_slice() {
-            ["network", "document_server"] => {
-                self.network.document_server = value.to_string();
-            }
-            ["network", "identity_server"] => {
-                self.network.identity_server = value.to_string();
-            }
-            ["network", "frogcrypto_server"] => {
-                self.network.frogcrypto_server = value.to_string();
-            }
-            ["network", "timeout_seconds"] => {
-                self.network.timeout_seconds = value
-                    .parse()
-                    .map_err(|e| format!("Invalid timeout_seconds value '{value}': {e}"))?;
-            }
-            ["database", "path"] => {
-                self.database.path = value.to_string();
-            }
-            ["database", "name"] => {
-                self.database.name = value.to_string();
-            }
-            ["logging", "level"] => {
-                if !["debug", "info", "warn", "error"].contains(&value) {
-                    return Err(format!(
-                        "Invalid logging level '{value}'. Must be one of: debug, info, warn, error"
-                    ));
-                }
-                self.logging.level = value.to_string();
-            }
-            ["logging", "console_output"] => {
-                self.logging.console_output = value
-                    .parse()
-                    .map_err(|e| format!("Invalid console_output value '{value}': {e}"))?;
-            }
-            ["ui", "default_theme"] => {
-                if !["auto", "light", "dark"].contains(&value) {
-                    return Err(format!(
-                        "Invalid default_theme '{value}'. Must be one of: auto, light, dark"
-                    ));
-                }
-                self.ui.default_theme = value.to_string();
-            }
-            ["ui", "default_window_width"] => {
-                self.ui.default_window_width = value
-                    .parse()
-                    .map_err(|e| format!("Invalid default_window_width value '{value}': {e}"))?;
-            }
-            ["ui", "default_window_height"] => {
-                self.ui.default_window_height = value
-                    .parse()
-                    .map_err(|e| format!("Invalid default_window_height value '{value}': {e}"))?;
-            }
-            _ => {
-                return Err(format!("Unknown config path: '{key_path}'"));
-            }
+        let mut root = toml::Value::try_from(&*self)
+            .map_err(|e| format!("Failed to serialize config: {e}"))?;
+
+        let segments: Vec<&str> = key_path.split('.').collect();
+        let (last, parents) = segments
+            .split_last()
+            .ok_or_else(|| format!("Unknown config path: '{key_path}'"))?;
+
+        let mut current = &mut root;
+        for (depth, segment) in parents.iter().enumerate() {
+            current = current
+                .as_table_mut()
+                .and_then(|table| table.get_mut(*segment))
+                .ok_or_else(|| {
+                    format!(
+                        "Unknown config path '{key_path}': no section '{}'",
+                        segments[..=depth].join(".")
+                    )
+                })?;
+        }
+        let table = current.as_table_mut().ok_or_else(|| {
+            format!("Unknown config path '{key_path}': '{}' is not a section", parents.join("."))
+        })?;
+        if !table.contains_key(*last) {
+            return Err(format!("Unknown config path: '{key_path}'"));
         }
+        table.insert((*last).to_string(), parse_override_value(value));
 
+        *self = root
+            .try_into()
+            .map_err(|e| format!("Invalid value for '{key_path}': {e}"))?;
         Ok(())
     }
 
@@ -293,11 +339,26 @@ impl AppConfig {
     }
 }
 
+/// Interpret an override value: JSON literals become the corresponding TOML
+/// value (arrays, tables, numbers, booleans), everything else is a string.
+fn parse_override_value(value: &str) -> toml::Value {
+    serde_json::from_str::<serde_json::Value>(value)
+        .ok()
+        .and_then(|json| toml::Value::try_from(json).ok())
+        .unwrap_or_else(|| toml::Value::String(value.to_string()))
+}
+
 /// Convenience function for accessing configuration
 pub fn config() -> std::sync::RwLockReadGuard<'static, AppConfig> {
     AppConfig::get()
 }
 
+/// Check a feature gate against the global configuration; commands call
+/// this first so a disabled feature cannot be exercised over IPC
+pub fn ensure_feature_enabled(feature: Feature) -> Result<(), String> {
+    config().features.ensure_enabled(feature)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -329,6 +390,70 @@ mod tests {
         assert_eq!(config.database.name, "pod2.db");
     }
 
+    #[test]
+    fn test_apply_overrides_nested_scalar_and_array() {
+        let mut config = AppConfig::default();
+        config
+            .apply_overrides(&[
+                "network.timeout_seconds=45".to_string(),
+                "logging.level=debug".to_string(),
+                r#"network.peers=["https://a.example","https://b.example"]"#.to_string(),
+            ])
+            .unwrap();
+
+        assert_eq!(config.network.timeout_seconds, 45);
+        assert_eq!(config.logging.level, "debug");
+        assert_eq!(
+            config.network.peers,
+            vec!["https://a.example", "https://b.example"]
+        );
+
+        // Plain strings still work even when they would not parse as JSON
+        config
+            .apply_overrides(&["network.document_server=http://localhost:3000".to_string()])
+            .unwrap();
+        assert_eq!(config.network.document_server, "http://localhost:3000");
+    }
+
+    #[test]
+    fn test_apply_overrides_rejects_bad_paths_and_values() {
+        let mut config = AppConfig::default();
+
+        let err = config
+            .apply_overrides(&["network.no_such_key=1".to_string()])
+            .unwrap_err();
+        assert!(err.contains("network.no_such_key"), "{err}");
+
+        let err = config
+            .apply_overrides(&["nowhere.at.all=1".to_string()])
+            .unwrap_err();
+        assert!(err.contains("nowhere.at.all"), "{err}");
+
+        // A structurally valid path with a type-incompatible value is refused
+        let err = config
+            .apply_overrides(&[r#"network.timeout_seconds=["nope"]"#.to_string()])
+            .unwrap_err();
+        assert!(err.contains("network.timeout_seconds"), "{err}");
+
+        // Failed overrides leave the config untouched
+        assert_eq!(config.network.timeout_seconds, 30);
+    }
+
+    #[test]
+    fn test_feature_guard_blocks_disabled_features() {
+        let mut features = FeatureConfig::default();
+        assert!(features.ensure_enabled(Feature::Documents).is_ok());
+
+        features.documents = false;
+        let err = features.ensure_enabled(Feature::Documents).unwrap_err();
+        assert!(err.starts_with("FeatureDisabled:"), "{err}");
+        assert!(err.contains("documents"), "{err}");
+
+        // Other gates are unaffected
+        assert!(features.ensure_enabled(Feature::Frog).is_ok());
+        assert!(features.ensure_enabled(Feature::Networking).is_ok());
+    }
+
     #[test]
     fn test_config_validation() {
         let config = AppConfig::default();
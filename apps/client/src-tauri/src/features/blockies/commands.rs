@@ -30,14 +30,8 @@ pub async fn generate_blockies(public_key: String) -> Result<String, String> {
 pub async fn get_blockies_data(public_key: String) -> Result<Vec<Vec<[u8; 3]>>, String> {
     log::debug!("Getting blockies data for public key: {public_key}");
 
-    use eth_blockies::{Blockies, BlockiesGenerator};
-
-    // Canonicalize the public key for consistent blockies generation
-    let seed = public_key.to_lowercase();
-
-    // Generate 16x16 blockies data using the new API
-    type Icon16<T> = Blockies<16, T>;
-    let blockies_data = Icon16::data(&seed);
+    // Shared derivation keeps the grid in sync with the Node bindings
+    let blockies_data = pod2_blockies::rgb_cells(&public_key);
 
     // Convert to Vec<Vec<[u8; 3]>> format for JSON serialization
     let result: Vec<Vec<[u8; 3]>> = blockies_data
@@ -36,14 +36,8 @@ impl BlockiesGenerator {
     }
 
     fn generate_blockies_png(&self, public_key: &str) -> Result<Vec<u8>> {
-        use eth_blockies::{Blockies, BlockiesGenerator};
-
-        // Canonicalize the public key for consistent blockies generation
-        let seed = public_key.to_lowercase();
-
-        // Generate 16x16 blockies data using the new API
-        type Icon16<T> = Blockies<16, T>;
-        let blockies_data = Icon16::data(&seed);
+        // Shared derivation keeps the grid in sync with the Node bindings
+        let blockies_data = pod2_blockies::rgb_cells(public_key);
 
         // Create PNG image from blockies data
         let png_data = self.create_png_from_blockies(&blockies_data)?;
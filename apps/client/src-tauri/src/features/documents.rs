@@ -12,68 +12,123 @@ use pod2::{
 };
 use pod2_db::store::PodData;
 use podnet_models::{
-    DeleteRequest, Document, DocumentContent, DocumentFile, PublishRequest, ReplyReference,
-    UpvoteRequest,
+    DeleteRequest, Document, DocumentContent, DocumentFile, DocumentReplyTree,
+    InlineDocumentFile, PublishRequest, ReplyReference, UpvoteRequest,
 };
 use reqwest::StatusCode;
 use serde::{Deserialize, Serialize};
 use tauri::State;
 use tokio::sync::Mutex;
 
-use crate::AppState;
+use crate::{
+    config::{ensure_feature_enabled, Feature},
+    AppState,
+};
 
+/// Verification outcome for a single POD component of a document
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct DocumentVerificationResult {
-    pub publish_verified: bool,
-    pub timestamp_verified: bool,
-    pub upvote_count_verified: bool,
-    pub verification_details: HashMap<String, String>,
-    pub verification_errors: Vec<String>,
+pub struct ComponentVerification {
+    pub verified: bool,
+    pub error: Option<String>,
+}
+
+impl ComponentVerification {
+    fn from_result(result: Result<(), Box<dyn std::error::Error>>) -> Self {
+        match result {
+            Ok(()) => ComponentVerification {
+                verified: true,
+                error: None,
+            },
+            Err(e) => ComponentVerification {
+                verified: false,
+                error: Some(e.to_string()),
+            },
+        }
+    }
+}
+
+/// Per-component verification result so users can see *which* proof failed:
+/// the publish MainPod, the server timestamp pod, or the upvote count pod
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DocumentVerification {
+    pub content_pod: ComponentVerification,
+    pub timestamp_pod: ComponentVerification,
+    pub upvote_pod: ComponentVerification,
+    /// Whether the publish proof binds the public key of an identity server
+    /// this client recognizes (currently the one configured during setup)
+    pub identity_trusted: bool,
 }
 
 #[tauri::command]
-pub async fn verify_document_pod(document: Document) -> Result<DocumentVerificationResult, String> {
-    let mut verification_result = DocumentVerificationResult {
-        publish_verified: false,
-        timestamp_verified: false,
-        upvote_count_verified: false,
-        verification_details: HashMap::new(),
-        verification_errors: Vec::new(),
-    };
+pub async fn verify_document_pod(
+    document: Document,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<DocumentVerification, String> {
+    ensure_feature_enabled(Feature::Documents)?;
+
+    let app_state = state.lock().await;
+    let setup_state = pod2_db::store::get_app_setup_state(&app_state.db)
+        .await
+        .map_err(|e| format!("Failed to get app setup state: {e}"))?;
+
+    Ok(verify_document_pods(
+        &document,
+        &trusted_identity_server_keys(&setup_state),
+    ))
+}
 
+/// Public keys of the identity servers this client recognizes: currently just
+/// the server configured during identity setup
+fn trusted_identity_server_keys(setup_state: &pod2_db::store::AppSetupState) -> Vec<Value> {
+    setup_state
+        .identity_server_public_key
+        .as_deref()
+        .and_then(|json| {
+            serde_json::from_str::<pod2::backends::plonky2::primitives::ec::curve::Point>(json)
+                .ok()
+        })
+        .map(Value::from)
+        .into_iter()
+        .collect()
+}
+
+/// The identity-server public key bound into the publish proof's public
+/// statements, when the proof has the expected shape
+fn bound_identity_server_key(document: &Document) -> Option<Value> {
+    match document.pods.pod.get().ok()?.public_statements.get(1)? {
+        pod2::middleware::Statement::Custom(_, args) => args.get(2).cloned(),
+        _ => None,
+    }
+}
+
+fn verify_document_pods(
+    document: &Document,
+    trusted_identity_servers: &[Value],
+) -> DocumentVerification {
     // Get server public key - for now use a placeholder
     // TODO: This should be configurable or fetched from the server
     let server_public_key = "your_server_public_key_here";
 
-    // Use the simplified Document.verify() method
-    match document.verify(server_public_key) {
-        Ok(()) => {
-            // All verification checks passed
-            verification_result.publish_verified = true;
-            verification_result.timestamp_verified = true;
-            verification_result.upvote_count_verified = true;
-
-            verification_result.verification_details.insert(
-                "publish_verification".to_string(),
-                "Identity, document, and content hash verification passed".to_string(),
-            );
-            verification_result.verification_details.insert(
-                "timestamp_verification".to_string(),
-                "Server timestamp signature verified".to_string(),
-            );
-            verification_result.verification_details.insert(
-                "upvote_count_verification".to_string(),
-                "Upvote count cryptographic proof verified".to_string(),
-            );
-        }
-        Err(e) => {
-            verification_result
-                .verification_errors
-                .push(format!("Document verification failed: {e}"));
-        }
+    let content_pod = ComponentVerification::from_result(document.verify_publish_verification());
+    let timestamp_pod = ComponentVerification::from_result(
+        document.verify_timestamp_pod_signature(server_public_key),
+    );
+    let upvote_pod = ComponentVerification::from_result(document.verify_upvote_count_pod());
+
+    // The publish proof only verifies against the identity-server key bound
+    // into it, so once it has verified, trusting the identity comes down to
+    // recognizing that bound key
+    let identity_trusted = content_pod.verified
+        && bound_identity_server_key(document)
+            .map(|key| trusted_identity_servers.contains(&key))
+            .unwrap_or(false);
+
+    DocumentVerification {
+        content_pod,
+        timestamp_pod,
+        upvote_pod,
+        identity_trusted,
     }
-
-    Ok(verification_result)
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -90,6 +145,7 @@ pub async fn upvote_document(
     server_url: String,
     state: State<'_, Mutex<AppState>>,
 ) -> Result<UpvoteResult, String> {
+    ensure_feature_enabled(Feature::Documents)?;
     log::info!("Upvoting document {document_id} on server {server_url}");
 
     // First, get the document to retrieve its content hash
@@ -253,6 +309,7 @@ pub async fn upvote_document(
     let upvote_request = UpvoteRequest {
         username: username.clone(),
         upvote_main_pod,
+        identity_pod_issued_at: podnet_models::identity_pod_issued_at(&identity_pod),
     };
 
     log::info!("Submitting upvote to server...");
@@ -330,7 +387,7 @@ pub struct PublishResult {
 pub async fn publish_document(
     title: String,
     message: Option<String>,
-    file: Option<DocumentFile>,
+    file: Option<InlineDocumentFile>,
     url: Option<String>,
     tags: Vec<String>,
     authors: Vec<String>,
@@ -340,6 +397,7 @@ pub async fn publish_document(
     post_id: Option<i64>,     // Optional post ID for creating revisions (editing documents)
     state: State<'_, Mutex<AppState>>,
 ) -> Result<PublishResult, String> {
+    ensure_feature_enabled(Feature::Documents)?;
     log::info!("Publishing document to server {server_url}");
     log::info!("Post ID for revision: {post_id:?}");
     if let Some(ref reply_ref) = reply_to {
@@ -362,8 +420,10 @@ pub async fn publish_document(
     let mut document_content = DocumentContent {
         message: None,
         file: None,
+        files: Vec::new(),
         url: None,
     };
+    let mut attachments: Vec<Vec<u8>> = Vec::new();
 
     // Process message
     if let Some(msg) = message {
@@ -373,10 +433,18 @@ pub async fn publish_document(
         }
     }
 
-    // Process file
+    // Process file: the content only commits to the attachment hash; the
+    // bytes travel alongside the publish request and are stored as their own
+    // blob server-side
     if let Some(file_data) = file {
-        document_content.file = Some(file_data);
-        log::info!("File added to document");
+        let content_id = DocumentFile::content_id_for(&file_data.content);
+        document_content.files.push(DocumentFile {
+            name: file_data.name,
+            mime: file_data.mime_type,
+            content_id,
+        });
+        attachments.push(file_data.content);
+        log::info!("File added to document (content id {content_id})");
     }
 
     // Process URL
@@ -600,6 +668,8 @@ pub async fn publish_document(
         post_id, // Use provided post_id for revisions, or None for new documents
         username: username.clone(),
         main_pod: publish_main_pod,
+        identity_pod_issued_at: podnet_models::identity_pod_issued_at(&identity_pod),
+        attachments,
     };
 
     log::info!("Sending publish request to server...");
@@ -691,6 +761,7 @@ pub async fn create_draft(
     request: DraftUpdateRequest,
     state: State<'_, Mutex<AppState>>,
 ) -> Result<String, String> {
+    ensure_feature_enabled(Feature::Documents)?;
     let app_state = state.lock().await;
 
     let create_request = pod2_db::store::CreateDraftRequest {
@@ -717,6 +788,7 @@ pub async fn update_draft(
     request: DraftUpdateRequest,
     state: State<'_, Mutex<AppState>>,
 ) -> Result<bool, String> {
+    ensure_feature_enabled(Feature::Documents)?;
     let app_state = state.lock().await;
 
     let update_request = pod2_db::store::UpdateDraftRequest {
@@ -741,6 +813,7 @@ pub async fn update_draft(
 pub async fn list_drafts(
     state: State<'_, Mutex<AppState>>,
 ) -> Result<Vec<pod2_db::store::DraftInfo>, String> {
+    ensure_feature_enabled(Feature::Documents)?;
     let app_state = state.lock().await;
 
     pod2_db::store::list_drafts(&app_state.db)
@@ -753,6 +826,7 @@ pub async fn get_draft(
     draft_id: String,
     state: State<'_, Mutex<AppState>>,
 ) -> Result<Option<pod2_db::store::DraftInfo>, String> {
+    ensure_feature_enabled(Feature::Documents)?;
     let app_state = state.lock().await;
 
     pod2_db::store::get_draft(&app_state.db, &draft_id)
@@ -765,6 +839,7 @@ pub async fn delete_draft(
     draft_id: String,
     state: State<'_, Mutex<AppState>>,
 ) -> Result<bool, String> {
+    ensure_feature_enabled(Feature::Documents)?;
     let app_state = state.lock().await;
 
     pod2_db::store::delete_draft(&app_state.db, &draft_id)
@@ -778,6 +853,7 @@ pub async fn publish_draft(
     server_url: String,
     state: State<'_, Mutex<AppState>>,
 ) -> Result<PublishResult, String> {
+    ensure_feature_enabled(Feature::Documents)?;
     // First get the draft
     let draft = {
         let app_state = state.lock().await;
@@ -793,7 +869,7 @@ pub async fn publish_draft(
             .file_content
             .zip(draft.file_name)
             .zip(draft.file_mime_type)
-            .map(|((content, name), mime_type)| DocumentFile {
+            .map(|((content, name), mime_type)| InlineDocumentFile {
                 name,
                 content,
                 mime_type,
@@ -847,6 +923,7 @@ pub async fn delete_document(
     server_url: String,
     state: State<'_, Mutex<AppState>>,
 ) -> Result<DeleteResult, String> {
+    ensure_feature_enabled(Feature::Documents)?;
     log::info!("Deleting document {document_id} from server {server_url}");
 
     // Get user's identity pod and private key from app state
@@ -1049,6 +1126,7 @@ pub async fn delete_document(
 pub async fn get_current_username(
     state: State<'_, Mutex<AppState>>,
 ) -> Result<Option<String>, String> {
+    ensure_feature_enabled(Feature::Documents)?;
     let app_state = state.lock().await;
 
     let setup_state = pod2_db::store::get_app_setup_state(&app_state.db)
@@ -1061,3 +1139,466 @@ pub async fn get_current_username(
 
     Ok(setup_state.username)
 }
+
+/// Poll the document server for the caller's unread notification count. The
+/// frontend calls this periodically to badge the notification feed.
+#[tauri::command]
+pub async fn get_unread_notification_count(
+    server_url: String,
+    username: String,
+) -> Result<usize, String> {
+    ensure_feature_enabled(Feature::Documents)?;
+    let client = reqwest::Client::new();
+    let response = client
+        .get(format!("{server_url}/notifications"))
+        .query(&[("username", username.as_str()), ("unread_only", "true")])
+        .send()
+        .await
+        .map_err(|e| format!("Failed to fetch notifications: {e}"))?;
+
+    if !response.status().is_success() {
+        return Err(format!(
+            "Notification fetch returned status: {}",
+            response.status()
+        ));
+    }
+
+    let notifications: Vec<serde_json::Value> = response
+        .json()
+        .await
+        .map_err(|e| format!("Failed to parse notifications: {e}"))?;
+
+    Ok(notifications.len())
+}
+
+/// Subscribe to the document server's SSE stream and forward each event to the
+/// frontend as a `document-server-event` Tauri event. The subscription runs
+/// until the connection drops; the frontend resubscribes as needed.
+#[tauri::command]
+pub async fn subscribe_document_events(
+    server_url: String,
+    app: tauri::AppHandle,
+) -> Result<(), String> {
+    ensure_feature_enabled(Feature::Documents)?;
+    use tauri::Emitter;
+
+    log::info!("Subscribing to document events at {server_url}/events");
+
+    let client = reqwest::Client::new();
+    let mut response = client
+        .get(format!("{server_url}/events"))
+        .send()
+        .await
+        .map_err(|e| format!("Failed to connect to event stream: {e}"))?;
+
+    if !response.status().is_success() {
+        return Err(format!(
+            "Event stream returned status: {}",
+            response.status()
+        ));
+    }
+
+    tokio::spawn(async move {
+        let mut buffer = String::new();
+        while let Ok(Some(chunk)) = response.chunk().await {
+            buffer.push_str(&String::from_utf8_lossy(&chunk));
+
+            // SSE messages are separated by a blank line
+            while let Some(boundary) = buffer.find("\n\n") {
+                let message = buffer[..boundary].to_string();
+                buffer.drain(..boundary + 2);
+
+                let data = message
+                    .lines()
+                    .filter_map(|line| line.strip_prefix("data:"))
+                    .map(str::trim_start)
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                if data.is_empty() {
+                    continue;
+                }
+
+                if let Ok(payload) = serde_json::from_str::<serde_json::Value>(&data) {
+                    if let Err(e) = app.emit("document-server-event", &payload) {
+                        log::error!("Failed to forward document server event: {e}");
+                    }
+                }
+            }
+        }
+        log::info!("Document event stream ended");
+    });
+
+    Ok(())
+}
+
+/// Fetch a document's full reply tree and render it as markdown so a thread
+/// can be archived offline. A convenience over the server's reply-tree
+/// endpoint; the rendering happens client-side.
+#[tauri::command]
+pub async fn export_thread(server_url: String, document_id: i64) -> Result<String, String> {
+    ensure_feature_enabled(Feature::Documents)?;
+    let client = reqwest::Client::new();
+    let response = client
+        .get(format!("{server_url}/documents/{document_id}/reply-tree"))
+        .send()
+        .await
+        .map_err(|e| format!("Failed to fetch reply tree: {e}"))?;
+
+    if !response.status().is_success() {
+        return Err(format!(
+            "Reply tree fetch returned status: {}",
+            response.status()
+        ));
+    }
+
+    let tree: DocumentReplyTree = response
+        .json()
+        .await
+        .map_err(|e| format!("Failed to parse reply tree: {e}"))?;
+
+    Ok(render_thread_markdown(&tree))
+}
+
+/// Render a reply tree as markdown: one block per node with title, author,
+/// timestamp and message, each reply level one blockquote deeper (matching
+/// the server's own markdown export). Deleted documents keep their place in
+/// the thread as "[deleted]".
+pub fn render_thread_markdown(tree: &DocumentReplyTree) -> String {
+    let mut out = String::new();
+    render_thread_node(tree, 0, &mut out);
+    out
+}
+
+fn render_thread_node(node: &DocumentReplyTree, depth: usize, out: &mut String) {
+    let quote = "> ".repeat(depth);
+    let meta = &node.document;
+
+    if meta.deleted_at.is_some() {
+        out.push_str(&format!("{quote}*[deleted]*\n{quote}\n"));
+    } else {
+        out.push_str(&format!("{quote}### {}\n", meta.title));
+        out.push_str(&format!(
+            "{quote}*by {} at {}*\n{quote}\n",
+            meta.uploader_id,
+            meta.created_at.as_deref().unwrap_or("unknown time"),
+        ));
+        if let Some(message) = &node.content.message {
+            for line in message.lines() {
+                out.push_str(&format!("{quote}{line}\n"));
+            }
+            out.push_str(&format!("{quote}\n"));
+        }
+    }
+
+    for reply in &node.replies {
+        render_thread_node(reply, depth + 1, out);
+    }
+}
+
+/// Result of patching a freshly arrived reply into a cached reply tree.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum ReplyTreeUpdate {
+    /// The reply was inserted under its parent; the UI can patch in place.
+    Patched { tree: DocumentReplyTree },
+    /// The parent is not part of the cached tree (e.g. pruned behind a stub),
+    /// so the UI needs to refetch the full tree.
+    RefetchRequired,
+}
+
+/// Insert a newly arrived reply into a cached `DocumentReplyTree` without
+/// rebuilding it. Only the affected sibling list is re-sorted (oldest first,
+/// matching the server's ordering), so the UI can patch the tree in place.
+pub fn apply_reply(mut tree: DocumentReplyTree, new_reply: DocumentReplyTree) -> ReplyTreeUpdate {
+    let Some(parent_id) = new_reply.document.reply_to.as_ref().map(|r| r.document_id) else {
+        return ReplyTreeUpdate::RefetchRequired;
+    };
+    let mut pending = Some(new_reply);
+    if insert_reply_at_parent(&mut tree, parent_id, &mut pending) {
+        ReplyTreeUpdate::Patched { tree }
+    } else {
+        ReplyTreeUpdate::RefetchRequired
+    }
+}
+
+fn insert_reply_at_parent(
+    node: &mut DocumentReplyTree,
+    parent_id: i64,
+    pending: &mut Option<DocumentReplyTree>,
+) -> bool {
+    if node.document.id == Some(parent_id) {
+        let reply = pending.take().expect("reply inserted twice");
+        // The event may race a refetch that already contains the reply
+        let duplicate = reply.document.id.is_some()
+            && node
+                .replies
+                .iter()
+                .any(|sibling| sibling.document.id == reply.document.id);
+        if !duplicate {
+            node.replies.push(reply);
+            node.replies.sort_by(|a, b| {
+                (a.document.created_at.as_deref(), a.document.id)
+                    .cmp(&(b.document.created_at.as_deref(), b.document.id))
+            });
+        }
+        return true;
+    }
+    node.replies
+        .iter_mut()
+        .any(|child| insert_reply_at_parent(child, parent_id, pending))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use num_bigint::BigUint;
+    use pod2::backends::plonky2::primitives::ec::schnorr::SecretKey;
+    use podnet_models::{
+        DocumentMetadata, DocumentPods,
+        lazy_pod::LazyDeser,
+        mainpod::publish::{PublishProofParams, prove_publish_verification_with_solver},
+    };
+
+    use super::*;
+
+    // Build a document whose publish MainPod verifies (mock proofs) but whose
+    // timestamp pod has been tampered with
+    fn build_document_with_tampered_timestamp_pod() -> Document {
+        let params = Params::default();
+
+        let user_sk = SecretKey(BigUint::from(11111u64));
+        let identity_server_sk = SecretKey(BigUint::from(22222u64));
+
+        let content_hash = Hash::from_hex(
+            "1234567890abcdef1234567890abcdef1234567890abcdef1234567890abcdef",
+        )
+        .unwrap();
+
+        // Identity pod: signed by the identity server, binds username to user key
+        let mut identity_builder = SignedDictBuilder::new(&params);
+        identity_builder.insert("username", "alice");
+        identity_builder.insert("user_public_key", user_sk.public_key());
+        let identity_pod = identity_builder
+            .sign(&Signer(identity_server_sk))
+            .expect("Failed to sign identity pod");
+
+        // Document pod: signed by the user, carries the publish data dictionary
+        let mut data_map = HashMap::new();
+        data_map.insert(Key::from("content_hash"), Value::from(content_hash));
+        data_map.insert(
+            Key::from("tags"),
+            Value::from(Set::new(5, HashSet::new()).unwrap()),
+        );
+        data_map.insert(
+            Key::from("authors"),
+            Value::from(Set::new(5, HashSet::new()).unwrap()),
+        );
+        data_map.insert(Key::from("reply_to"), Value::from(-1i64));
+        data_map.insert(Key::from("post_id"), Value::from(-1i64));
+        let data = Dictionary::new(6, data_map).unwrap();
+
+        let mut document_builder = SignedDictBuilder::new(&params);
+        document_builder.insert("request_type", "publish");
+        document_builder.insert("data", data);
+        let document_pod = document_builder
+            .sign(&Signer(user_sk))
+            .expect("Failed to sign document pod");
+
+        let main_pod = prove_publish_verification_with_solver(PublishProofParams {
+            identity_pod: &identity_pod,
+            document_pod: &document_pod,
+            use_mock_proofs: true,
+        })
+        .expect("Failed to prove publish verification");
+
+        let metadata = DocumentMetadata {
+            id: Some(1),
+            content_id: content_hash,
+            post_id: 1,
+            revision: 1,
+            created_at: None,
+            uploader_id: "alice".to_string(),
+            upvote_count: 0,
+            tags: HashSet::new(),
+            authors: HashSet::new(),
+            reply_to: None,
+            requested_post_id: Some(-1),
+            title: "Test Document".to_string(),
+            hidden: false,
+            display_name: None,
+            deleted_at: None,
+            content_type: None,
+        };
+
+        let pods = DocumentPods {
+            document_id: 1,
+            pod: LazyDeser::from_value(main_pod).unwrap(),
+            // Tampered: not a valid SignedDict at all
+            timestamp_pod: LazyDeser::from_json_string(r#"{"tampered": true}"#.to_string())
+                .unwrap(),
+            upvote_count_pod: LazyDeser::from_value(None).unwrap(),
+        };
+
+        Document {
+            metadata,
+            pods,
+            content: DocumentContent {
+                message: Some("Test content".to_string()),
+                file: None,
+                files: Vec::new(),
+                url: None,
+            },
+        }
+    }
+
+    // The identity server key the builder above signs with
+    fn known_identity_server_key() -> Value {
+        Value::from(SecretKey(BigUint::from(22222u64)).public_key())
+    }
+
+    #[test]
+    fn test_tampered_timestamp_pod_is_reported_specifically() {
+        let document = build_document_with_tampered_timestamp_pod();
+
+        let result = verify_document_pods(&document, &[known_identity_server_key()]);
+
+        // The intact components verify, and only the tampered one is flagged
+        assert!(result.content_pod.verified);
+        assert!(result.content_pod.error.is_none());
+        assert!(result.identity_trusted);
+        assert!(result.upvote_pod.verified);
+
+        assert!(!result.timestamp_pod.verified);
+        assert!(result.timestamp_pod.error.is_some());
+    }
+
+    #[test]
+    fn test_unrecognized_identity_server_is_not_trusted() {
+        let document = build_document_with_tampered_timestamp_pod();
+
+        // The proof verifies, but it binds a key no recognized server uses
+        let other_server = Value::from(SecretKey(BigUint::from(33333u64)).public_key());
+        let result = verify_document_pods(&document, &[other_server]);
+        assert!(result.content_pod.verified);
+        assert!(!result.identity_trusted);
+
+        // With no recognized servers at all, nothing is trusted
+        assert!(!verify_document_pods(&document, &[]).identity_trusted);
+    }
+
+    fn reply_node(id: i64, created_at: &str, parent: Option<i64>) -> DocumentReplyTree {
+        DocumentReplyTree {
+            document: DocumentMetadata {
+                id: Some(id),
+                content_id: Hash::from_hex(
+                    "1234567890abcdef1234567890abcdef1234567890abcdef1234567890abcdef",
+                )
+                .unwrap(),
+                post_id: 1,
+                revision: 1,
+                created_at: Some(created_at.to_string()),
+                uploader_id: "alice".to_string(),
+                upvote_count: 0,
+                tags: HashSet::new(),
+                authors: HashSet::new(),
+                reply_to: parent.map(|document_id| ReplyReference {
+                    post_id: 1,
+                    document_id,
+                }),
+                requested_post_id: None,
+                title: format!("Document {id}"),
+                hidden: false,
+                display_name: None,
+                deleted_at: None,
+                content_type: None,
+            },
+            content: DocumentContent {
+                message: Some(format!("Content {id}")),
+                file: None,
+                files: Vec::new(),
+                url: None,
+            },
+            replies: Vec::new(),
+            stubs: Vec::new(),
+        }
+    }
+
+    fn reply_ids(node: &DocumentReplyTree) -> Vec<i64> {
+        node.replies
+            .iter()
+            .map(|r| r.document.id.unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_apply_reply_patches_nested_parent_in_order() {
+        let mut root = reply_node(1, "2024-01-01 10:00:00", None);
+        let mut child = reply_node(2, "2024-01-01 11:00:00", Some(1));
+        child.replies.push(reply_node(3, "2024-01-01 11:30:00", Some(2)));
+        root.replies.push(child);
+        root.replies.push(reply_node(4, "2024-01-01 13:00:00", Some(1)));
+
+        // A reply to the nested child lands under it, sorted before its
+        // existing later sibling
+        let update = apply_reply(root, reply_node(5, "2024-01-01 11:15:00", Some(2)));
+        let ReplyTreeUpdate::Patched { tree } = update else {
+            panic!("expected in-place patch");
+        };
+        assert_eq!(reply_ids(&tree), vec![2, 4]);
+        assert_eq!(reply_ids(&tree.replies[0]), vec![5, 3]);
+
+        // Applying the same reply again does not duplicate it
+        let update = apply_reply(tree, reply_node(5, "2024-01-01 11:15:00", Some(2)));
+        let ReplyTreeUpdate::Patched { tree } = update else {
+            panic!("expected in-place patch");
+        };
+        assert_eq!(reply_ids(&tree.replies[0]), vec![5, 3]);
+    }
+
+    #[test]
+    fn test_render_thread_markdown_nests_and_tombstones() {
+        let mut root = reply_node(1, "2024-01-01 10:00:00", None);
+        let mut child = reply_node(2, "2024-01-01 11:00:00", Some(1));
+        let mut deleted = reply_node(3, "2024-01-01 11:30:00", Some(2));
+        deleted.document.deleted_at = Some("2024-01-02 09:00:00".to_string());
+        child.replies.push(deleted);
+        root.replies.push(child);
+        root.replies.push(reply_node(4, "2024-01-01 13:00:00", Some(1)));
+
+        let markdown = render_thread_markdown(&root);
+
+        // Root at depth 0, replies one blockquote level per depth
+        assert!(markdown.contains("### Document 1\n"));
+        assert!(markdown.contains("*by alice at 2024-01-01 10:00:00*\n"));
+        assert!(markdown.contains("Content 1\n"));
+        assert!(markdown.contains("> ### Document 2\n"));
+        assert!(markdown.contains("> Content 2\n"));
+        assert!(markdown.contains("> ### Document 4\n"));
+
+        // The deleted grandchild keeps its slot but exposes no content
+        assert!(markdown.contains("> > *[deleted]*\n"));
+        assert!(!markdown.contains("Document 3"));
+        assert!(!markdown.contains("Content 3"));
+
+        // Reading order: document 2's subtree renders before document 4
+        let pos_2 = markdown.find("Document 2").unwrap();
+        let pos_deleted = markdown.find("[deleted]").unwrap();
+        let pos_4 = markdown.find("Document 4").unwrap();
+        assert!(pos_2 < pos_deleted && pos_deleted < pos_4);
+    }
+
+    #[test]
+    fn test_apply_reply_signals_refetch_when_parent_missing() {
+        let root = reply_node(1, "2024-01-01 10:00:00", None);
+
+        // Parent not present in the cached tree (e.g. pruned behind a stub)
+        let update = apply_reply(root, reply_node(5, "2024-01-01 11:00:00", Some(99)));
+        assert!(matches!(update, ReplyTreeUpdate::RefetchRequired));
+
+        // A document without a reply_to reference cannot be patched in
+        let root = reply_node(1, "2024-01-01 10:00:00", None);
+        let update = apply_reply(root, reply_node(5, "2024-01-01 11:00:00", None));
+        assert!(matches!(update, ReplyTreeUpdate::RefetchRequired));
+    }
+}
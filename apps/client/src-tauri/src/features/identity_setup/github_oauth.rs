@@ -1,7 +1,7 @@
 use anyhow::Result;
 use pod2::middleware::TypedValue;
 use serde::{Deserialize, Serialize};
-use tauri::State;
+use tauri::{AppHandle, Emitter, Manager, State, Url};
 use tokio::sync::Mutex;
 
 use crate::AppState;
@@ -10,6 +10,8 @@ use crate::AppState;
 pub struct GitHubAuthUrlRequest {
     pub public_key: serde_json::Value,
     pub username: String,
+    /// Ask the identity server to attest org memberships in the issued pod
+    pub include_orgs: bool,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -18,6 +20,23 @@ pub struct GitHubAuthUrlResponse {
     pub state: String,
 }
 
+/// The auth-URL request a deep-link completion resumes: which identity
+/// server it targeted, the username entered, and the `state` the server
+/// handed out for it
+#[derive(Debug, Clone)]
+pub struct PendingGitHubOAuth {
+    pub server_url: String,
+    pub username: String,
+    pub state: String,
+}
+
+/// Query parameters carried by an OAuth completion deep link
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OAuthCallbackParams {
+    pub code: String,
+    pub state: String,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct GitHubIdentityRequest {
     pub code: String,
@@ -44,6 +63,7 @@ pub struct GitHubIdentityPodResult {
 pub async fn get_github_auth_url(
     server_url: String,
     username: String,
+    include_orgs: bool,
     state: State<'_, Mutex<AppState>>,
 ) -> Result<GitHubAuthUrlResponse, String> {
     log::info!("Getting GitHub OAuth authorization URL for user: {username}");
@@ -71,6 +91,7 @@ pub async fn get_github_auth_url(
         public_key: serde_json::to_value(public_key)
             .map_err(|e| format!("Failed to serialize public key: {e}"))?,
         username: username.clone(),
+        include_orgs,
     };
 
     let response = client
@@ -93,10 +114,90 @@ pub async fn get_github_auth_url(
         .await
         .map_err(|e| format!("Failed to parse GitHub auth URL response: {e}"))?;
 
+    // Record the flow so a deep-link completion can resume it; the deep
+    // link's state must match the one the server just handed out
+    let mut app_state = state.lock().await;
+    app_state.pending_github_oauth = Some(PendingGitHubOAuth {
+        server_url,
+        username: username.clone(),
+        state: auth_response.state.clone(),
+    });
+    drop(app_state);
+
     log::info!("Successfully obtained GitHub auth URL for user: {username}");
     Ok(auth_response)
 }
 
+/// Parse an OAuth completion deep link of the form
+/// `<scheme>://github-oauth?code=…&state=…`. The scheme is whatever this
+/// build registered, so only the target and query parameters are matched.
+pub fn parse_oauth_deep_link(url: &str) -> Option<OAuthCallbackParams> {
+    let parsed = Url::parse(url).ok()?;
+    let is_github_oauth = parsed.host_str() == Some("github-oauth")
+        || parsed.path().trim_start_matches('/') == "github-oauth";
+    if !is_github_oauth {
+        return None;
+    }
+
+    let mut code = None;
+    let mut state = None;
+    for (key, value) in parsed.query_pairs() {
+        match key.as_ref() {
+            "code" => code = Some(value.into_owned()),
+            "state" => state = Some(value.into_owned()),
+            _ => {}
+        }
+    }
+    Some(OAuthCallbackParams {
+        code: code?,
+        state: state?,
+    })
+}
+
+/// Resolve a deep link against the pending flow: there must be one, and the
+/// state echoed back through the browser must match the state recorded when
+/// the auth URL was requested
+fn completion_for_deep_link(
+    pending: Option<&PendingGitHubOAuth>,
+    params: &OAuthCallbackParams,
+) -> Result<PendingGitHubOAuth, String> {
+    let pending =
+        pending.ok_or_else(|| "Received OAuth deep link with no pending flow".to_string())?;
+    if pending.state != params.state {
+        return Err("OAuth deep link state does not match the pending flow".to_string());
+    }
+    Ok(pending.clone())
+}
+
+/// Complete identity verification for an OAuth deep link using the pending
+/// flow recorded by [`get_github_auth_url`], then notify the frontend
+pub async fn handle_oauth_deep_link(
+    app: AppHandle,
+    params: OAuthCallbackParams,
+) -> Result<(), String> {
+    let state = app.state::<Mutex<AppState>>();
+    let pending = {
+        let mut app_state = state.lock().await;
+        let pending = completion_for_deep_link(app_state.pending_github_oauth.as_ref(), &params)?;
+        // Consume the pending flow only once the deep link matched it
+        app_state.pending_github_oauth = None;
+        pending
+    };
+
+    let result = complete_github_identity_verification(
+        pending.server_url,
+        params.code,
+        params.state,
+        pending.username,
+        state.clone(),
+    )
+    .await?;
+
+    app.emit("github-oauth-completed", &result)
+        .map_err(|e| format!("Failed to notify frontend of OAuth completion: {e}"))?;
+    Ok(())
+}
+
 /// Complete GitHub OAuth identity verification
 #[tauri::command]
 pub async fn complete_github_identity_verification(
@@ -160,15 +261,16 @@ pub async fn complete_github_identity_verification(
         serde_json::from_value(identity_response.identity_pod.clone())
             .map_err(|e| format!("Failed to deserialize identity POD: {e}"))?;
 
-    // Extract GitHub username from the identity POD's github_data dictionary
-    let github_username = identity_pod
-        .get("github_data")
-        .and_then(|v| match v.typed() {
+    // Extract the GitHub username from the identity POD's provider data
+    // dictionary (falling back to the legacy github_data entry for pods
+    // issued by older identity servers)
+    let extract_login = |data_key: &str, login_key: &str| {
+        identity_pod.get(data_key).and_then(|v| match v.typed() {
             TypedValue::String(s) => {
-                // Parse the GitHub data JSON
-                if let Ok(github_data) = serde_json::from_str::<serde_json::Value>(s.as_str()) {
-                    github_data
-                        .get("github_username")
+                // Parse the provider data JSON
+                if let Ok(provider_data) = serde_json::from_str::<serde_json::Value>(s.as_str()) {
+                    provider_data
+                        .get(login_key)
                         .and_then(|u| u.as_str())
                         .map(|u| u.to_string())
                 } else {
@@ -176,7 +278,10 @@ pub async fn complete_github_identity_verification(
                 }
             }
             _ => None,
-        });
+        })
+    };
+    let github_username = extract_login("provider_data", "provider_username")
+        .or_else(|| extract_login("github_data", "github_username"));
 
     // Store identity POD in database as mandatory
     let pod_data = pod2_db::store::PodData::Signed(Box::new(identity_pod.clone().into()));
@@ -224,6 +329,106 @@ pub async fn complete_github_identity_verification(
     })
 }
 
+#[derive(Debug, Serialize)]
+struct GitHubRefreshRequest {
+    refresh_pod: pod2::frontend::SignedDict,
+}
+
+/// Re-issue the GitHub identity POD with the user's current SSH keys and
+/// store the fresh pod, replacing the snapshot taken at issuance
+#[tauri::command]
+pub async fn refresh_github_identity(
+    server_url: String,
+    app_state: State<'_, Mutex<AppState>>,
+) -> Result<GitHubIdentityPodResult, String> {
+    log::info!("Refreshing GitHub identity POD via: {server_url}");
+
+    let state_lock = app_state.lock().await;
+    let private_key = pod2_db::store::get_default_private_key_raw(&state_lock.db)
+        .await
+        .map_err(|e| format!("Failed to load private key: {e}"))?;
+
+    // The refresh request is just a dict signed with the identity's own key
+    let mut builder = pod2::frontend::SignedDictBuilder::new(&pod2::middleware::Params::default());
+    builder.insert("request_type", "refresh");
+    let refresh_pod = builder
+        .sign(&pod2::backends::plonky2::signer::Signer(private_key))
+        .map_err(|e| format!("Failed to sign refresh request: {e}"))?;
+
+    let client = reqwest::Client::new();
+    let response = client
+        .post(format!("{server_url}/identity/refresh"))
+        .json(&GitHubRefreshRequest { refresh_pod })
+        .send()
+        .await
+        .map_err(|e| format!("Failed to request identity refresh: {e}"))?;
+
+    if !response.status().is_success() {
+        let status = response.status();
+        let error_text = response.text().await.unwrap_or_default();
+        return Err(format!("Identity refresh failed: {status} - {error_text}"));
+    }
+
+    let identity_response: GitHubIdentityResponse = response
+        .json()
+        .await
+        .map_err(|e| format!("Failed to parse refreshed identity response: {e}"))?;
+
+    let identity_pod: pod2::frontend::SignedDict =
+        serde_json::from_value(identity_response.identity_pod.clone())
+            .map_err(|e| format!("Failed to deserialize refreshed identity POD: {e}"))?;
+
+    let username = identity_pod
+        .get("username")
+        .and_then(|v| match v.typed() {
+            TypedValue::String(s) => Some(s.to_string()),
+            _ => None,
+        })
+        .ok_or_else(|| "Refreshed identity POD missing username".to_string())?;
+
+    let github_username = identity_pod.get("provider_data").and_then(|v| match v.typed() {
+        TypedValue::String(s) => serde_json::from_str::<serde_json::Value>(s.as_str())
+            .ok()
+            .and_then(|data| {
+                data.get("provider_username")
+                    .and_then(|u| u.as_str())
+                    .map(|u| u.to_string())
+            }),
+        _ => None,
+    });
+
+    // Store the fresh pod and point the identity setup at it
+    let pod_data = pod2_db::store::PodData::Signed(Box::new(identity_pod.clone().into()));
+    let identity_pod_id = pod_data.id();
+
+    pod2_db::store::store_identity_pod(
+        &state_lock.db,
+        &pod_data,
+        "identity",
+        Some("GitHub Identity POD"),
+    )
+    .await
+    .map_err(|e| format!("Failed to store refreshed identity POD: {e}"))?;
+
+    pod2_db::store::update_identity_info(&state_lock.db, &username, &identity_pod_id)
+        .await
+        .map_err(|e| format!("Failed to update identity info: {e}"))?;
+
+    state_lock
+        .trigger_state_sync()
+        .await
+        .map_err(|e| format!("Failed to trigger state sync: {e}"))?;
+
+    log::info!("Successfully refreshed GitHub identity POD for user: {username}");
+
+    Ok(GitHubIdentityPodResult {
+        identity_pod: identity_response.identity_pod,
+        username,
+        github_username,
+        server_id: "github-identity-server".to_string(),
+    })
+}
+
 /// Detect if a server is a GitHub OAuth identity server
 #[tauri::command]
 pub async fn detect_github_oauth_server(
@@ -258,3 +463,75 @@ pub async fn detect_github_oauth_server(
     log::info!("Server GitHub OAuth detection result: {is_github_server}");
     Ok(is_github_server)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pending(state: &str) -> PendingGitHubOAuth {
+        PendingGitHubOAuth {
+            server_url: "http://localhost:3001".to_string(),
+            username: "alice".to_string(),
+            state: state.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_parse_oauth_deep_link_extracts_code_and_state() {
+        let params = parse_oauth_deep_link("podnet://github-oauth?code=abc123&state=xyz789")
+            .expect("deep link should parse");
+        assert_eq!(params.code, "abc123");
+        assert_eq!(params.state, "xyz789");
+    }
+
+    #[test]
+    fn test_parse_oauth_deep_link_accepts_any_scheme_and_decodes() {
+        // The registered scheme varies per build, and query values arrive
+        // percent-encoded from the completion page
+        let params = parse_oauth_deep_link("pod2client://github-oauth?code=a%2Fb&state=s%3D1")
+            .expect("deep link should parse");
+        assert_eq!(params.code, "a/b");
+        assert_eq!(params.state, "s=1");
+    }
+
+    #[test]
+    fn test_parse_oauth_deep_link_rejects_other_targets() {
+        assert!(parse_oauth_deep_link("podnet://some-other-feature?code=a&state=b").is_none());
+        assert!(parse_oauth_deep_link("podnet://github-oauth?code=a").is_none());
+        assert!(parse_oauth_deep_link("podnet://github-oauth?state=b").is_none());
+        assert!(parse_oauth_deep_link("not a url").is_none());
+    }
+
+    #[test]
+    fn test_completion_requires_a_pending_flow() {
+        let params = OAuthCallbackParams {
+            code: "abc".to_string(),
+            state: "xyz".to_string(),
+        };
+        assert!(completion_for_deep_link(None, &params).is_err());
+    }
+
+    #[test]
+    fn test_completion_rejects_mismatched_state() {
+        let params = OAuthCallbackParams {
+            code: "abc".to_string(),
+            state: "forged".to_string(),
+        };
+        assert!(completion_for_deep_link(Some(&pending("xyz")), &params).is_err());
+    }
+
+    #[test]
+    fn test_completion_resumes_the_pending_flow() {
+        let params = OAuthCallbackParams {
+            code: "abc".to_string(),
+            state: "xyz".to_string(),
+        };
+        let resumed = completion_for_deep_link(Some(&pending("xyz")), &params)
+            .expect("matching state should resume the flow");
+        // These are exactly the parameters handed to
+        // complete_github_identity_verification
+        assert_eq!(resumed.server_url, "http://localhost:3001");
+        assert_eq!(resumed.username, "alice");
+        assert_eq!(resumed.state, params.state);
+    }
+}
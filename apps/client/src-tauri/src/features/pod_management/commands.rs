@@ -1,11 +1,13 @@
-use std::sync::Arc;
+use std::{path::Path, sync::Arc};
 
+use anyhow::{bail, Context};
 use pod2::{
     frontend::MainPod,
     lang::pretty_print::PrettyPrint,
     middleware::{CustomPredicateBatch, Predicate},
 };
-use pod2_db::store;
+use pod2_db::{store, Db};
+use serde::{Deserialize, Serialize};
 use tauri::State;
 use tokio::sync::Mutex;
 
@@ -26,6 +28,40 @@ pub async fn trigger_sync(state: State<'_, Mutex<AppState>>) -> Result<(), Strin
     Ok(())
 }
 
+/// List all pods across spaces in the given sort order
+#[tauri::command]
+pub async fn list_all_pods_sorted(
+    state: State<'_, Mutex<AppState>>,
+    sort: store::PodSort,
+) -> Result<Vec<store::PodInfo>, String> {
+    let app_state = state.lock().await;
+    store::list_all_pods_sorted(&app_state.db, sort)
+        .await
+        .map_err(|e| format!("Failed to list pods: {e}"))
+}
+
+/// Get the last-selected space and pod so the frontend can restore the
+/// selection on startup
+#[tauri::command]
+pub async fn get_ui_state(state: State<'_, Mutex<AppState>>) -> Result<store::UiState, String> {
+    let app_state = state.lock().await;
+    store::get_ui_state(&app_state.db)
+        .await
+        .map_err(|e| format!("Failed to get UI state: {e}"))
+}
+
+/// Persist the current selection so it survives a restart
+#[tauri::command]
+pub async fn set_ui_state(
+    state: State<'_, Mutex<AppState>>,
+    ui_state: store::UiState,
+) -> Result<(), String> {
+    let app_state = state.lock().await;
+    store::set_ui_state(&app_state.db, ui_state)
+        .await
+        .map_err(|e| format!("Failed to set UI state: {e}"))
+}
+
 /// List all spaces/folders
 #[tauri::command]
 pub async fn list_spaces(
@@ -85,6 +121,19 @@ pub async fn import_pod(
     Ok(())
 }
 
+/// Return a stored pod's raw serialized JSON, exactly as persisted, so users
+/// can inspect it or attach the offending pod to a bug report
+#[tauri::command]
+pub async fn get_pod_json(
+    state: State<'_, Mutex<AppState>>,
+    pod_id: String,
+) -> Result<Option<String>, String> {
+    let app_state = state.lock().await;
+    store::get_pod_raw_json(&app_state.db, &pod_id)
+        .await
+        .map_err(|e| format!("Failed to get pod JSON: {e}"))
+}
+
 /// Delete a POD from the database
 #[tauri::command]
 pub async fn delete_pod(
@@ -108,22 +157,339 @@ pub async fn delete_pod(
     Ok(())
 }
 
-// /// Debug command to insert ZuKYC sample pods
-// #[tauri::command]
-// pub async fn insert_zukyc_pods(state: State<'_, Mutex<AppState>>) -> Result<(), String> {
-//     use crate::insert_zukyc_pods;
+/// Rename a POD's display label. An empty name clears the custom label so
+/// the UI falls back to its default display name.
+#[tauri::command]
+pub async fn rename_pod(
+    state: State<'_, Mutex<AppState>>,
+    space_id: String,
+    pod_id: String,
+    new_name: String,
+) -> Result<(), String> {
+    let mut app_state = state.lock().await;
+
+    let updated = store::rename_pod(&app_state.db, &space_id, &pod_id, &new_name)
+        .await
+        .map_err(|e| format!("Failed to rename POD: {e}"))?;
+
+    if !updated {
+        return Err("POD not found".to_string());
+    }
+
+    // Trigger state sync to update frontend
+    app_state.trigger_state_sync().await?;
+
+    Ok(())
+}
+
+/// Garbage collect cached content blobs that are no longer referenced by any
+/// draft or pod, returning the number of bytes reclaimed
+#[tauri::command]
+pub async fn gc_storage(state: State<'_, Mutex<AppState>>) -> Result<u64, String> {
+    let app_state = state.lock().await;
 
-//     let mut app_state = state.lock().await;
+    store::gc_orphaned_content(&app_state.db)
+        .await
+        .map_err(|e| format!("Failed to garbage collect storage: {e}"))
+}
 
-//     insert_zukyc_pods(&app_state.db)
-//         .await
-//         .map_err(|e| format!("Failed to insert ZuKYC pods: {e}"))?;
+/// Name of the index file written at the root of an export directory
+const EXPORT_MANIFEST_FILE: &str = "manifest.json";
 
-//     // Trigger state sync to update frontend
-//     app_state.trigger_state_sync().await?;
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportedPodEntry {
+    pub id: String,
+    pub pod_type: String,
+    pub label: Option<String>,
+    pub created_at: String,
+    /// Path of the pod file relative to the export directory
+    pub path: String,
+}
 
-//     Ok(())
-// }
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportedSpaceEntry {
+    pub id: String,
+    pub created_at: String,
+    pub pods: Vec<ExportedPodEntry>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportManifest {
+    pub exported_at: String,
+    pub spaces: Vec<ExportedSpaceEntry>,
+}
+
+/// Dump every pod to `dir` as `<space>/<pod_id>.json` (serialized `PodData`)
+/// plus a `manifest.json` listing spaces and pod metadata. Unlike a single
+/// archive this layout is directly scriptable with external tools. Existing
+/// files are never overwritten unless `overwrite` is set.
+pub async fn export_all_pods(
+    db: &Db,
+    dir: &Path,
+    overwrite: bool,
+) -> anyhow::Result<ExportManifest> {
+    let manifest_path = dir.join(EXPORT_MANIFEST_FILE);
+    if !overwrite && manifest_path.exists() {
+        bail!(
+            "Export directory {} already contains a manifest; pass overwrite to replace it",
+            dir.display()
+        );
+    }
+    std::fs::create_dir_all(dir)
+        .with_context(|| format!("Failed to create {}", dir.display()))?;
+
+    let mut spaces = Vec::new();
+    for space in store::list_spaces(db).await? {
+        // Space ids become directory names; refuse anything that would
+        // escape the export directory
+        if space.id.contains(['/', '\\']) || space.id == ".." {
+            bail!("Space id {:?} is not a valid directory name", space.id);
+        }
+
+        let space_dir = dir.join(&space.id);
+        std::fs::create_dir_all(&space_dir)
+            .with_context(|| format!("Failed to create {}", space_dir.display()))?;
+
+        let mut entries = Vec::new();
+        for pod in store::list_pods(db, &space.id).await? {
+            let relative_path = format!("{}/{}.json", space.id, pod.id);
+            let pod_path = dir.join(&relative_path);
+            if !overwrite && pod_path.exists() {
+                bail!(
+                    "{} already exists; pass overwrite to replace it",
+                    pod_path.display()
+                );
+            }
+
+            let serialized = serde_json::to_vec_pretty(&pod.data)
+                .with_context(|| format!("Failed to serialize pod {}", pod.id))?;
+            std::fs::write(&pod_path, serialized)
+                .with_context(|| format!("Failed to write {}", pod_path.display()))?;
+
+            entries.push(ExportedPodEntry {
+                id: pod.id,
+                pod_type: pod.pod_type,
+                label: pod.label,
+                created_at: pod.created_at,
+                path: relative_path,
+            });
+        }
+
+        spaces.push(ExportedSpaceEntry {
+            id: space.id,
+            created_at: space.created_at,
+            pods: entries,
+        });
+    }
+
+    let manifest = ExportManifest {
+        exported_at: chrono::Utc::now().to_rfc3339(),
+        spaces,
+    };
+    let serialized =
+        serde_json::to_vec_pretty(&manifest).context("Failed to serialize export manifest")?;
+    std::fs::write(&manifest_path, serialized)
+        .with_context(|| format!("Failed to write {}", manifest_path.display()))?;
+
+    Ok(manifest)
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ImportSummary {
+    /// Spaces listed in the manifest (created if they did not already exist)
+    pub spaces: usize,
+    pub imported: usize,
+    /// Pods already present in the target database
+    pub skipped_duplicates: usize,
+    /// Manifest entries whose pod file was missing or unreadable
+    pub missing_files: usize,
+}
+
+/// Re-import a directory written by [`export_all_pods`]: read the manifest,
+/// recreate its spaces, and import every listed pod through the dedup import
+/// path in one transaction. Entries whose file is missing or unparseable are
+/// counted and skipped, and files not listed in the manifest are ignored, so
+/// a partially modified export still imports cleanly.
+pub async fn import_from_directory(db: &Db, dir: &Path) -> anyhow::Result<ImportSummary> {
+    let manifest_path = dir.join(EXPORT_MANIFEST_FILE);
+    let manifest: ExportManifest = serde_json::from_slice(
+        &std::fs::read(&manifest_path)
+            .with_context(|| format!("Failed to read {}", manifest_path.display()))?,
+    )
+    .context("Failed to parse export manifest")?;
+
+    let mut summary = ImportSummary {
+        spaces: manifest.spaces.len(),
+        ..ImportSummary::default()
+    };
+
+    let mut to_import = Vec::new();
+    for space in &manifest.spaces {
+        for entry in &space.pods {
+            let parsed = std::fs::read(dir.join(&entry.path))
+                .ok()
+                .and_then(|bytes| serde_json::from_slice::<store::PodData>(&bytes).ok());
+            match parsed {
+                Some(data) => to_import.push((space.id.clone(), entry.label.clone(), data)),
+                None => {
+                    log::warn!(
+                        "Skipping manifest entry with missing or invalid file: {}",
+                        entry.path
+                    );
+                    summary.missing_files += 1;
+                }
+            }
+        }
+    }
+
+    let spaces: Vec<(String, String)> = manifest
+        .spaces
+        .iter()
+        .map(|s| (s.id.clone(), s.created_at.clone()))
+        .collect();
+    let (imported, skipped) = db
+        .with_transaction(move |tx| {
+            for (id, created_at) in &spaces {
+                store::create_space_if_missing_with_conn(tx, id, created_at)?;
+            }
+            let mut imported = 0;
+            let mut skipped = 0;
+            for (space_id, label, data) in &to_import {
+                store::import_pod_with_conn(tx, data, label.as_deref(), space_id)?;
+                if tx.changes() > 0 {
+                    imported += 1;
+                } else {
+                    skipped += 1;
+                }
+            }
+            Ok((imported, skipped))
+        })
+        .await?;
+    summary.imported = imported;
+    summary.skipped_duplicates = skipped;
+
+    Ok(summary)
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SpaceDiff {
+    /// Pods present only in the first space
+    pub only_in_a: Vec<store::PodInfo>,
+    /// Pods present only in the second space
+    pub only_in_b: Vec<store::PodInfo>,
+    /// Pods present in both spaces; the copy from the first space is returned
+    pub in_both: Vec<store::PodInfo>,
+}
+
+/// Compare two spaces ahead of a merge. Pod ids are content hashes, so two
+/// copies of the same pod match regardless of label or import time. The
+/// spaces usually live in the same database; diffing against another install
+/// works by importing its export into a scratch space first.
+pub async fn compute_space_diff(
+    db: &Db,
+    space_a: &str,
+    space_b: &str,
+) -> anyhow::Result<SpaceDiff> {
+    use std::collections::HashSet;
+
+    let pods_a = store::list_pods(db, space_a).await?;
+    let pods_b = store::list_pods(db, space_b).await?;
+    let ids_a: HashSet<&str> = pods_a.iter().map(|p| p.id.as_str()).collect();
+    let ids_b: HashSet<&str> = pods_b.iter().map(|p| p.id.as_str()).collect();
+
+    let mut diff = SpaceDiff::default();
+    for pod in pods_a {
+        if ids_b.contains(pod.id.as_str()) {
+            diff.in_both.push(pod);
+        } else {
+            diff.only_in_a.push(pod);
+        }
+    }
+    diff.only_in_b = pods_b
+        .into_iter()
+        .filter(|pod| !ids_a.contains(pod.id.as_str()))
+        .collect();
+    Ok(diff)
+}
+
+/// Diff two spaces by content hash for pre-merge review
+#[tauri::command]
+pub async fn diff_spaces(
+    state: State<'_, Mutex<AppState>>,
+    space_a: String,
+    space_b: String,
+) -> Result<SpaceDiff, String> {
+    let app_state = state.lock().await;
+
+    compute_space_diff(&app_state.db, &space_a, &space_b)
+        .await
+        .map_err(|e| format!("Failed to diff spaces: {e}"))
+}
+
+/// Default number of entries returned by [`recent_activity`]
+const RECENT_ACTIVITY_DEFAULT_LIMIT: u32 = 50;
+
+/// The most recent pod mutations (imports, deletions, moves, renames),
+/// newest first
+#[tauri::command]
+pub async fn recent_activity(
+    state: State<'_, Mutex<AppState>>,
+    limit: Option<u32>,
+) -> Result<Vec<store::ActivityEntry>, String> {
+    let app_state = state.lock().await;
+
+    store::list_recent_activity(
+        &app_state.db,
+        limit.unwrap_or(RECENT_ACTIVITY_DEFAULT_LIMIT),
+    )
+    .await
+    .map_err(|e| format!("Failed to list recent activity: {e}"))
+}
+
+/// Import a directory previously written by the bulk export
+#[tauri::command]
+pub async fn import_pods_from_directory(
+    state: State<'_, Mutex<AppState>>,
+    directory: String,
+) -> Result<ImportSummary, String> {
+    let mut app_state = state.lock().await;
+
+    let summary = import_from_directory(&app_state.db, Path::new(&directory))
+        .await
+        .map_err(|e| format!("Failed to import PODs: {e}"))?;
+
+    app_state.trigger_state_sync().await?;
+    Ok(summary)
+}
+
+/// Export every pod to a directory of JSON files for external tooling
+#[tauri::command]
+pub async fn export_all_pods_to_directory(
+    state: State<'_, Mutex<AppState>>,
+    directory: String,
+    overwrite: bool,
+) -> Result<ExportManifest, String> {
+    let app_state = state.lock().await;
+
+    export_all_pods(&app_state.db, Path::new(&directory), overwrite)
+        .await
+        .map_err(|e| format!("Failed to export PODs: {e}"))
+}
+
+/// Debug command to insert ZuKYC sample pods
+#[tauri::command]
+pub async fn insert_zukyc_pods(state: State<'_, Mutex<AppState>>) -> Result<(), String> {
+    let mut app_state = state.lock().await;
+
+    crate::insert_zukyc_pods(&app_state.db)
+        .await
+        .map_err(|e| format!("Failed to insert ZuKYC pods: {e}"))?;
+
+    // Trigger state sync to update frontend
+    app_state.trigger_state_sync().await?;
+
+    Ok(())
+}
 
 /// Return pretty-printed Podlang for custom predicates
 #[tauri::command]
@@ -161,3 +527,268 @@ pub async fn pretty_print_custom_predicates(serialized_main_pod: String) -> Resu
         .collect::<Vec<String>>()
         .join("\n\n"))
 }
+
+#[cfg(test)]
+mod tests {
+    use pod2::{
+        backends::plonky2::{primitives::ec::schnorr::SecretKey, signer::Signer},
+        frontend::SignedDictBuilder,
+        middleware::Params,
+    };
+    use pod2_db::store::PodData;
+
+    use super::*;
+
+    fn signed_pod(note: &str) -> PodData {
+        let mut builder = SignedDictBuilder::new(&Params::default());
+        builder.insert("note", note);
+        let dict = builder
+            .sign(&Signer(SecretKey::new_rand()))
+            .expect("Failed to sign dict");
+        PodData::from(dict)
+    }
+
+    #[tokio::test]
+    async fn test_activity_log_records_each_mutation() {
+        use pod2_db::store::ActivityAction;
+
+        let db = Db::new(None, &pod2_db::MIGRATIONS)
+            .await
+            .expect("Failed to initialize in-memory DB");
+        store::create_space(&db, "personal").await.unwrap();
+        store::create_space(&db, "work").await.unwrap();
+        let pod = signed_pod("tracked");
+        let pod_id = pod.id();
+
+        store::import_pod(&db, &pod, None, "personal").await.unwrap();
+        // No-op mutations add nothing: a duplicate import is ignored, as is
+        // renaming a pod that does not exist
+        store::import_pod(&db, &pod, None, "personal").await.unwrap();
+        assert!(!store::rename_pod(&db, "personal", "missing", "X").await.unwrap());
+        assert!(store::rename_pod(&db, "personal", &pod_id, "Tracked").await.unwrap());
+        assert!(store::move_pod(&db, "personal", &pod_id, "work").await.unwrap());
+        assert!(store::delete_pod(&db, "work", &pod_id).await.unwrap() > 0);
+
+        // Newest first, one entry per effective mutation; the move records
+        // the destination space
+        let entries = store::list_recent_activity(&db, 10).await.unwrap();
+        let observed: Vec<_> = entries
+            .iter()
+            .map(|e| (e.action, e.pod_id.as_str(), e.space.as_str()))
+            .collect();
+        assert_eq!(
+            observed,
+            vec![
+                (ActivityAction::Delete, pod_id.as_str(), "work"),
+                (ActivityAction::Move, pod_id.as_str(), "work"),
+                (ActivityAction::Rename, pod_id.as_str(), "personal"),
+                (ActivityAction::Import, pod_id.as_str(), "personal"),
+            ]
+        );
+
+        // The limit caps the feed from the newest end
+        let limited = store::list_recent_activity(&db, 2).await.unwrap();
+        assert_eq!(limited.len(), 2);
+        assert_eq!(limited[0].action, ActivityAction::Delete);
+    }
+
+    #[tokio::test]
+    async fn test_get_pod_raw_json_returns_stored_serialization() {
+        let db = Db::new(None, &pod2_db::MIGRATIONS)
+            .await
+            .expect("Failed to initialize in-memory DB");
+        store::create_space(&db, "personal").await.unwrap();
+
+        let pod = signed_pod("raw json");
+        store::import_pod(&db, &pod, None, "personal")
+            .await
+            .unwrap();
+
+        // The raw JSON re-parses into exactly the pod that was stored
+        let raw = store::get_pod_raw_json(&db, &pod.id())
+            .await
+            .unwrap()
+            .expect("imported pod has raw JSON");
+        let reparsed: PodData = serde_json::from_str(&raw).unwrap();
+        assert_eq!(reparsed, pod);
+
+        assert!(
+            store::get_pod_raw_json(&db, "missing")
+                .await
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_export_all_pods_writes_manifest_and_pod_files() {
+        let db = Db::new(None, &pod2_db::MIGRATIONS)
+            .await
+            .expect("Failed to initialize in-memory DB");
+
+        store::create_space(&db, "personal").await.unwrap();
+        store::create_space(&db, "work").await.unwrap();
+        let pod_a = signed_pod("a");
+        let pod_b = signed_pod("b");
+        store::import_pod(&db, &pod_a, Some("Pod A"), "personal")
+            .await
+            .unwrap();
+        store::import_pod(&db, &pod_b, None, "work").await.unwrap();
+
+        let dir =
+            std::env::temp_dir().join(format!("pod_export_test_{}", rand::random::<u64>()));
+        let manifest = export_all_pods(&db, &dir, false).await.unwrap();
+
+        // The manifest covers both spaces with the pods they hold
+        assert_eq!(manifest.spaces.len(), 2);
+        let personal = manifest
+            .spaces
+            .iter()
+            .find(|s| s.id == "personal")
+            .expect("personal space in manifest");
+        assert_eq!(personal.pods.len(), 1);
+        assert_eq!(personal.pods[0].id, pod_a.id());
+        assert_eq!(personal.pods[0].label.as_deref(), Some("Pod A"));
+        assert_eq!(
+            personal.pods[0].path,
+            format!("personal/{}.json", pod_a.id())
+        );
+
+        // The manifest on disk round-trips and each pod file deserializes to
+        // the stored PodData
+        let on_disk: ExportManifest =
+            serde_json::from_slice(&std::fs::read(dir.join(EXPORT_MANIFEST_FILE)).unwrap())
+                .unwrap();
+        for space in &on_disk.spaces {
+            for entry in &space.pods {
+                let data: PodData =
+                    serde_json::from_slice(&std::fs::read(dir.join(&entry.path)).unwrap())
+                        .unwrap();
+                assert_eq!(data.id(), entry.id);
+                let stored = store::get_pod(&db, &space.id, &entry.id)
+                    .await
+                    .unwrap()
+                    .expect("exported pod exists in DB");
+                assert_eq!(data, stored.data);
+            }
+        }
+
+        // A second export into the same directory is refused without overwrite
+        let err = export_all_pods(&db, &dir, false).await.unwrap_err();
+        assert!(err.to_string().contains("already contains a manifest"));
+        export_all_pods(&db, &dir, true)
+            .await
+            .expect("overwrite export should succeed");
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[tokio::test]
+    async fn test_space_diff_buckets_by_content_hash() {
+        let db = Db::new(None, &pod2_db::MIGRATIONS)
+            .await
+            .expect("Failed to initialize in-memory DB");
+        store::create_space(&db, "laptop").await.unwrap();
+        store::create_space(&db, "desktop").await.unwrap();
+
+        let shared = signed_pod("shared");
+        let laptop_only = signed_pod("laptop only");
+        let desktop_only = signed_pod("desktop only");
+        store::import_pod(&db, &shared, Some("Shared (laptop)"), "laptop")
+            .await
+            .unwrap();
+        store::import_pod(&db, &laptop_only, None, "laptop")
+            .await
+            .unwrap();
+        store::import_pod(&db, &shared, Some("Shared (desktop)"), "desktop")
+            .await
+            .unwrap();
+        store::import_pod(&db, &desktop_only, None, "desktop")
+            .await
+            .unwrap();
+
+        let diff = compute_space_diff(&db, "laptop", "desktop").await.unwrap();
+        assert_eq!(diff.only_in_a.len(), 1);
+        assert_eq!(diff.only_in_a[0].id, laptop_only.id());
+        assert_eq!(diff.only_in_b.len(), 1);
+        assert_eq!(diff.only_in_b[0].id, desktop_only.id());
+        // The shared pod matches despite different labels; the first space's
+        // copy is the one returned
+        assert_eq!(diff.in_both.len(), 1);
+        assert_eq!(diff.in_both[0].id, shared.id());
+        assert_eq!(diff.in_both[0].label.as_deref(), Some("Shared (laptop)"));
+
+        // Diffing a space against itself puts everything in both buckets' overlap
+        let same = compute_space_diff(&db, "laptop", "laptop").await.unwrap();
+        assert!(same.only_in_a.is_empty());
+        assert!(same.only_in_b.is_empty());
+        assert_eq!(same.in_both.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_directory_export_import_round_trip() {
+        let source = Db::new(None, &pod2_db::MIGRATIONS)
+            .await
+            .expect("Failed to initialize source DB");
+        store::create_space(&source, "personal").await.unwrap();
+        store::create_space(&source, "work").await.unwrap();
+        let pod_a = signed_pod("a");
+        let pod_b = signed_pod("b");
+        let pod_c = signed_pod("c");
+        store::import_pod(&source, &pod_a, Some("Pod A"), "personal")
+            .await
+            .unwrap();
+        store::import_pod(&source, &pod_b, None, "personal")
+            .await
+            .unwrap();
+        store::import_pod(&source, &pod_c, Some("Pod C"), "work")
+            .await
+            .unwrap();
+
+        let dir = std::env::temp_dir().join(format!("pod_import_test_{}", rand::random::<u64>()));
+        export_all_pods(&source, &dir, false).await.unwrap();
+
+        // A stray file in the export directory is ignored; a deleted pod file
+        // is counted and skipped
+        std::fs::write(dir.join("personal").join("stray.txt"), b"not a pod").unwrap();
+        std::fs::remove_file(dir.join(format!("work/{}.json", pod_c.id()))).unwrap();
+
+        let target = Db::new(None, &pod2_db::MIGRATIONS)
+            .await
+            .expect("Failed to initialize target DB");
+        let summary = import_from_directory(&target, &dir).await.unwrap();
+        assert_eq!(summary.spaces, 2);
+        assert_eq!(summary.imported, 2);
+        assert_eq!(summary.skipped_duplicates, 0);
+        assert_eq!(summary.missing_files, 1);
+
+        // The surviving pods arrive with their labels and spaces intact
+        let mut space_ids: Vec<String> = store::list_spaces(&target)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|s| s.id)
+            .collect();
+        space_ids.sort_unstable();
+        assert_eq!(space_ids, ["personal", "work"]);
+        let imported_a = store::get_pod(&target, "personal", &pod_a.id())
+            .await
+            .unwrap()
+            .expect("pod A imported");
+        assert_eq!(imported_a.data, pod_a);
+        assert_eq!(imported_a.label.as_deref(), Some("Pod A"));
+        assert!(
+            store::get_pod(&target, "personal", &pod_b.id())
+                .await
+                .unwrap()
+                .is_some()
+        );
+
+        // Importing the same directory again only skips duplicates
+        let again = import_from_directory(&target, &dir).await.unwrap();
+        assert_eq!(again.imported, 0);
+        assert_eq!(again.skipped_duplicates, 2);
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
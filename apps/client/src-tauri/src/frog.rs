@@ -22,7 +22,10 @@ use serde::{Deserialize, Serialize};
 use tauri::{AppHandle, Emitter, Listener, Manager, State};
 use tokio::sync::Mutex;
 
-use crate::{config::config, AppState};
+use crate::{
+    config::{config, ensure_feature_enabled, Feature},
+    AppState,
+};
 
 fn server_url(path: &str) -> String {
     let domain = &config().network.frogcrypto_server;
@@ -157,6 +160,7 @@ fn frog_data_for(pod: &SignedDict, desc: &SignedDict) -> Option<FrogData> {
 
 #[tauri::command]
 pub async fn list_frogs(state: State<'_, Mutex<AppState>>) -> Result<Vec<FrogPod>, String> {
+    ensure_feature_enabled(Feature::Frog)?;
     let app_state = state.lock().await;
     let frog_pods = frog_pods(&app_state.db).await?;
     let frog_descs = description_pods(&app_state.db).await?;
@@ -184,6 +188,7 @@ fn frogedex_data_for(desc: &SignedDict) -> Option<(i64, String, String)> {
 
 #[tauri::command]
 pub async fn get_frogedex(state: State<'_, Mutex<AppState>>) -> Result<Vec<FrogedexEntry>, String> {
+    ensure_feature_enabled(Feature::Frog)?;
     let app_state = state.lock().await;
     let frog_descs = description_pods(&app_state.db).await?;
     let mut entries: Vec<_> = FROG_RARITIES
@@ -308,6 +313,7 @@ async fn register_frog(app_state: &mut AppState, pod: SignedDict) -> Result<(),
 
 #[tauri::command]
 pub async fn request_frog(state: State<'_, Mutex<AppState>>) -> Result<i64, String> {
+    ensure_feature_enabled(Feature::Frog)?;
     let client = Client::new();
     let mut app_state = state.lock().await;
     let private_key = crate::get_private_key(&app_state.db).await?;
@@ -322,6 +328,7 @@ pub async fn request_frog(state: State<'_, Mutex<AppState>>) -> Result<i64, Stri
 
 #[tauri::command]
 pub async fn fix_frog_descriptions(state: State<'_, Mutex<AppState>>) -> Result<(), String> {
+    ensure_feature_enabled(Feature::Frog)?;
     println!("trying to fix descriptions");
     let app_state = state.lock().await;
     let frog_pods = frog_pods(&app_state.db).await?;
@@ -341,6 +348,7 @@ pub async fn fix_frog_descriptions(state: State<'_, Mutex<AppState>>) -> Result<
 
 #[tauri::command]
 pub async fn request_score(state: State<'_, Mutex<AppState>>) -> Result<serde_json::Value, String> {
+    ensure_feature_enabled(Feature::Frog)?;
     let client = Client::new();
     let app_state = state.lock().await;
     let private_key = crate::get_private_key(&app_state.db).await?;
@@ -367,6 +375,7 @@ pub struct LeaderboardRow {
 pub async fn request_leaderboard(
     _state: State<'_, Mutex<AppState>>,
 ) -> Result<Vec<LeaderboardRow>, String> {
+    ensure_feature_enabled(Feature::Frog)?;
     let client = Client::new();
     client
         .get(server_url("leaderboard"))
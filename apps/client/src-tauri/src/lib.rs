@@ -90,6 +90,7 @@ async fn get_app_config() -> Result<AppConfig, String> {
 /// Tauri command to fetch text content from a URL (for HackMD import)
 #[tauri::command]
 async fn fetch_url_text(url: String) -> Result<String, String> {
+    config::ensure_feature_enabled(config::Feature::Networking)?;
     let client = reqwest::Client::new();
 
     let response = client
@@ -303,6 +304,8 @@ pub struct AppState {
     db: Db,
     state_data: AppStateData,
     app_handle: AppHandle,
+    /// OAuth flow awaiting a deep-link completion, if any
+    pending_github_oauth: Option<identity_setup::PendingGitHubOAuth>,
 }
 
 impl AppState {
@@ -380,21 +383,43 @@ impl AppState {
     }
 }
 
-// pub fn sign_zukyc_pods() -> anyhow::Result<Vec<SignedPod>> {
-//     let params_for_test = Params::default();
-//     let gov_signer = Signer(SecretKey(BigUint::from(1u32)));
-//     let pay_signer = Signer(SecretKey(BigUint::from(2u32)));
-
-//     let (gov_id_builder, pay_stub_builder) = zu_kyc_sign_pod_builders(&params_for_test);
-
-//     let sign_results = [
-//         gov_id_builder.sign(&gov_signer),
-//         pay_stub_builder.sign(&pay_signer),
-//     ];
+pub fn sign_zukyc_pods() -> anyhow::Result<Vec<pod2::frontend::SignedDict>> {
+    use num_bigint::BigUint;
+    use pod2::{
+        backends::plonky2::signer::Signer,
+        examples::{ZU_KYC_SANCTION_LIST, zu_kyc_sign_pod_builders},
+        frontend::SignedDictBuilder,
+        middleware::{Params, containers::Set},
+    };
 
-//     let all_signed: Result<Vec<_>, _> = sign_results.into_iter().collect();
-//     all_signed.map_err(|e| anyhow::anyhow!("Failed to sign Zukyc pods: {}", e))
-// }
+    let params = Params::default();
+    let gov_signer = Signer(SecretKey(BigUint::from(1u32)));
+    let pay_signer = Signer(SecretKey(BigUint::from(2u32)));
+    let sanctions_signer = Signer(SecretKey(BigUint::from(3u32)));
+
+    let (gov_id_builder, pay_stub_builder) = zu_kyc_sign_pod_builders(&params);
+
+    // The example builders only cover the gov and pay pods; the sanctions list
+    // pod is assembled here so the sample set can satisfy
+    // NotContains(sanctions["sanctionList"], ...) requests.
+    let sanctions_values = ZU_KYC_SANCTION_LIST
+        .iter()
+        .map(|s| pod2::middleware::Value::from(*s))
+        .collect();
+    let sanction_set = Set::new(params.max_depth_mt_containers, sanctions_values)
+        .map_err(|e| anyhow::anyhow!("Failed to build sanction set: {e:?}"))?;
+    let mut sanctions_builder = SignedDictBuilder::new(&params);
+    sanctions_builder.insert("sanctionList", sanction_set);
+
+    let sign_results = [
+        gov_id_builder.sign(&gov_signer),
+        pay_stub_builder.sign(&pay_signer),
+        sanctions_builder.sign(&sanctions_signer),
+    ];
+
+    let all_signed: Result<Vec<_>, _> = sign_results.into_iter().collect();
+    all_signed.map_err(|e| anyhow::anyhow!("Failed to sign Zukyc pods: {}", e))
+}
 
 pub async fn setup_default_space(db: &Db) -> anyhow::Result<()> {
     if store::space_exists(db, DEFAULT_SPACE_ID).await? {
@@ -409,33 +434,42 @@ pub async fn setup_default_space(db: &Db) -> anyhow::Result<()> {
     Ok(())
 }
 
-// pub async fn insert_zukyc_pods(db: &Db) -> anyhow::Result<()> {
-//     // Ensure default space exists
-//     if !store::space_exists(db, "zukyc").await? {
-//         store::create_space(db, "zukyc").await?;
-//     }
-
-//     log::info!("Inserting ZuKYC sample pods to default space...");
-
-//     match sign_zukyc_pods() {
-//         Ok(pods) => {
-//             log::info!("All pods signed successfully, importing to DB...");
-//             let pod_names = ["Gov ID", "Pay Stub", "Sanctions List"];
-
-//             for (pod, name) in pods.into_iter().zip(pod_names) {
-//                 let pod_data = PodData::from(pod);
-//                 store::import_pod(db, &pod_data, Some(name), "zukyc").await?;
-//             }
-//             log::info!("Successfully inserted ZuKYC pods to default space.");
-//         }
-//         Err(e) => {
-//             log::error!("Failed to sign one or more pods for ZuKYC insertion: {e}");
-//             return Err(e);
-//         }
-//     }
-
-//     Ok(())
-// }
+const ZUKYC_SPACE_ID: &str = "zukyc";
+
+/// Insert the ZuKYC sample pods (gov ID, pay stub, sanctions list) into their
+/// own space. Idempotent: if the space already holds the samples, re-running
+/// is a no-op instead of importing duplicates.
+pub async fn insert_zukyc_pods(db: &Db) -> anyhow::Result<()> {
+    if store::space_exists(db, ZUKYC_SPACE_ID).await? {
+        if !store::list_pods(db, ZUKYC_SPACE_ID).await?.is_empty() {
+            log::info!("ZuKYC sample pods already present. Skipping insertion.");
+            return Ok(());
+        }
+    } else {
+        store::create_space(db, ZUKYC_SPACE_ID).await?;
+    }
+
+    log::info!("Inserting ZuKYC sample pods...");
+
+    match sign_zukyc_pods() {
+        Ok(pods) => {
+            log::info!("All pods signed successfully, importing to DB...");
+            let pod_names = ["Gov ID", "Pay Stub", "Sanctions List"];
+
+            for (pod, name) in pods.into_iter().zip(pod_names) {
+                let pod_data = store::PodData::from(pod);
+                store::import_pod(db, &pod_data, Some(name), ZUKYC_SPACE_ID).await?;
+            }
+            log::info!("Successfully inserted ZuKYC sample pods.");
+        }
+        Err(e) => {
+            log::error!("Failed to sign one or more pods for ZuKYC insertion: {e}");
+            return Err(e);
+        }
+    }
+
+    Ok(())
+}
 
 async fn init_db(path: &str) -> Result<Db, anyhow::Error> {
     log::info!("Initializing database at: {path}");
@@ -670,6 +704,7 @@ pub fn run() {
                     db,
                     state_data: AppStateData::default(),
                     app_handle,
+                    pending_github_oauth: None,
                 };
                 // Initialize state
                 app_state
@@ -690,6 +725,25 @@ pub fn run() {
                     } else {
                         log::info!("Successfully registered deep-link scheme 'podnet'");
                     }
+
+                    // Route OAuth completion deep links into identity setup
+                    let handle = app.handle().clone();
+                    app.deep_link().on_open_url(move |event| {
+                        for url in event.urls() {
+                            if let Some(params) =
+                                identity_setup::parse_oauth_deep_link(url.as_str())
+                            {
+                                let handle = handle.clone();
+                                tauri::async_runtime::spawn(async move {
+                                    if let Err(e) =
+                                        identity_setup::handle_oauth_deep_link(handle, params).await
+                                    {
+                                        log::error!("OAuth deep-link completion failed: {e}");
+                                    }
+                                });
+                            }
+                        }
+                    });
                 }
             });
             Ok(())
@@ -718,10 +772,20 @@ pub fn run() {
             // POD management commands
             pod_management::get_app_state,
             pod_management::trigger_sync,
+            pod_management::list_all_pods_sorted,
+            pod_management::get_ui_state,
+            pod_management::set_ui_state,
             pod_management::delete_pod,
+            pod_management::rename_pod,
             pod_management::list_spaces,
             pod_management::import_pod,
-           // pod_management::insert_zukyc_pods,
+            pod_management::get_pod_json,
+            pod_management::gc_storage,
+            pod_management::export_all_pods_to_directory,
+            pod_management::import_pods_from_directory,
+            pod_management::diff_spaces,
+            pod_management::recent_activity,
+            pod_management::insert_zukyc_pods,
             pod_management::pretty_print_custom_predicates,
             // Blockies commands
             blockies::commands::generate_blockies,
@@ -737,6 +801,9 @@ pub fn run() {
             documents::publish_document,
             documents::delete_document,
             documents::get_current_username,
+            documents::subscribe_document_events,
+            documents::get_unread_notification_count,
+            documents::export_thread,
             // Draft management commands
             documents::create_draft,
             documents::update_draft,
@@ -753,8 +820,32 @@ pub fn run() {
             // GitHub OAuth identity setup commands
             identity_setup::get_github_auth_url,
             identity_setup::complete_github_identity_verification,
+            identity_setup::refresh_github_identity,
             identity_setup::detect_github_oauth_server,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_insert_zukyc_pods_is_idempotent() {
+        let db = Db::new(None, &pod2_db::MIGRATIONS)
+            .await
+            .expect("Failed to initialize in-memory DB");
+
+        insert_zukyc_pods(&db).await.unwrap();
+        insert_zukyc_pods(&db).await.unwrap();
+
+        // Two runs leave exactly the three sample pods in the zukyc space
+        let pods = store::list_pods(&db, ZUKYC_SPACE_ID).await.unwrap();
+        assert_eq!(pods.len(), 3);
+
+        let mut labels: Vec<_> = pods.iter().filter_map(|p| p.label.as_deref()).collect();
+        labels.sort_unstable();
+        assert_eq!(labels, ["Gov ID", "Pay Stub", "Sanctions List"]);
+    }
+}
@@ -7,7 +7,7 @@ import {
 import { useEffect, useMemo, useRef, useState } from "react";
 import { toast } from "sonner";
 import { isMarkdownContent } from "../../lib/contentUtils";
-import { Document, DocumentFile } from "../../lib/documentApi";
+import { Document, InlineDocumentFile } from "../../lib/documentApi";
 import {
   fileContentToDataUrl,
   fileContentToString,
@@ -31,7 +31,7 @@ import { QuoteRail } from "./QuoteRail";
 interface DocumentContentProps {
   document: Document;
   downloadingFiles: Set<string>;
-  onDownloadFile: (file: DocumentFile) => Promise<void>;
+  onDownloadFile: (file: InlineDocumentFile) => Promise<void>;
   onQuoteText?: (text: string) => Promise<void>;
 }
 
@@ -116,7 +116,7 @@ export function DocumentContent({
     }
   }, [document.content.message, renderedMessageData]);
 
-  const renderFileAttachment = (file: DocumentFile) => {
+  const renderFileAttachment = (file: InlineDocumentFile) => {
     if (!file) return null;
 
     const isImage = isImageFile(file.mime_type);
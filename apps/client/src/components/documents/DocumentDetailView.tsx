@@ -1,4 +1,4 @@
-import { DocumentVerificationResult } from "@/lib/documentApi";
+import { DocumentVerification } from "@/lib/documentApi";
 import { Route } from "@/routes/documents/document/$documentId";
 import { Await } from "@tanstack/react-router";
 import { useRef, useState } from "react";
@@ -27,7 +27,7 @@ export function DocumentDetailView() {
   const { state: appSidebarState } = useSidebar();
 
   const [verificationResult, setVerificationResult] =
-    useState<DocumentVerificationResult | null>(null);
+    useState<DocumentVerification | null>(null);
 
   const {
     isVerifying,
@@ -81,9 +81,10 @@ export function DocumentDetailView() {
 
   const isVerified = Boolean(
     verificationResult &&
-      verificationResult.publish_verified &&
-      verificationResult.timestamp_verified &&
-      verificationResult.upvote_count_verified
+      verificationResult.content_pod.verified &&
+      verificationResult.timestamp_pod.verified &&
+      verificationResult.upvote_pod.verified &&
+      verificationResult.identity_trusted
   );
 
   return (
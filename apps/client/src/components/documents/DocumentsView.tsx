@@ -89,7 +89,7 @@ export function DocumentsView() {
     refetch
   } = useQuery<DocumentMetadata[]>({
     queryKey: documentsQueryKey,
-    queryFn: fetchDocuments
+    queryFn: () => fetchDocuments()
   });
 
   // Extract all unique tags from documents
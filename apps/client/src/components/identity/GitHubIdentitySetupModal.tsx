@@ -35,6 +35,7 @@ import {
 import { Input } from "../ui/input";
 import { Label } from "../ui/label";
 import { Separator } from "../ui/separator";
+import { Switch } from "../ui/switch";
 
 interface GitHubIdentitySetupModalProps {
   open: boolean;
@@ -71,6 +72,7 @@ export function GitHubIdentitySetupModal({
   const [oauthClient, setOauthClient] = useState<GitHubOAuthClient | null>(
     null
   );
+  const [includeOrgs, setIncludeOrgs] = useState(false);
   const [authUrl, setAuthUrl] = useState("");
   const [oauthState, setOauthState] = useState("");
   const [authCode, setAuthCode] = useState("");
@@ -137,7 +139,8 @@ export function GitHubIdentitySetupModal({
       const { invoke } = await import("@tauri-apps/api/core");
       const authResponse = (await invoke("get_github_auth_url", {
         serverUrl,
-        username
+        username,
+        includeOrgs
       })) as { auth_url: string; state: string };
 
       setAuthUrl(authResponse.auth_url);
@@ -336,6 +339,23 @@ export function GitHubIdentitySetupModal({
                 </p>
               </div>
 
+              <div className="flex items-center gap-2">
+                <Switch
+                  id="include-orgs"
+                  checked={includeOrgs}
+                  onCheckedChange={setIncludeOrgs}
+                  disabled={isLoading}
+                />
+                <Label htmlFor="include-orgs">
+                  Attest my GitHub organization memberships
+                </Label>
+              </div>
+              <p className="text-sm text-muted-foreground">
+                Requests the read:org scope so the identity POD can prove
+                which organizations you belong to (e.g. for org-gated
+                communities).
+              </p>
+
               <Button
                 onClick={handleGitHubAuth}
                 disabled={isLoading}
@@ -5,7 +5,7 @@ import { toast } from "sonner";
 import { formatReplyToId } from "../lib/contentUtils";
 import {
   Document,
-  DocumentVerificationResult,
+  DocumentVerification,
   createDraft,
   deleteDocument,
   verifyDocumentPod,
@@ -25,7 +25,7 @@ export interface UseDocumentActionsReturn {
 
 export const useDocumentActions = (
   currentDocument: Document | null,
-  setVerificationResult: (result: DocumentVerificationResult | null) => void
+  setVerificationResult: (result: DocumentVerification | null) => void
 ): UseDocumentActionsReturn => {
   const [isVerifying, setIsVerifying] = useState(false);
   const [verificationError, setVerificationError] = useState<string | null>(
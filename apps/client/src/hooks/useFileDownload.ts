@@ -2,12 +2,12 @@ import { useState } from "react";
 import { save } from "@tauri-apps/plugin-dialog";
 import { writeFile } from "@tauri-apps/plugin-fs";
 import { toast } from "sonner";
-import { DocumentFile } from "../lib/documentApi";
+import { InlineDocumentFile } from "../lib/documentApi";
 import { getFileFilters, ensureFileExtension } from "../lib/fileUtils";
 
 export interface UseFileDownloadReturn {
   downloadingFiles: Set<string>;
-  handleDownloadFile: (file: DocumentFile) => Promise<void>;
+  handleDownloadFile: (file: InlineDocumentFile) => Promise<void>;
 }
 
 export const useFileDownload = (): UseFileDownloadReturn => {
@@ -15,7 +15,7 @@ export const useFileDownload = (): UseFileDownloadReturn => {
     new Set()
   );
 
-  const handleDownloadFile = async (file: DocumentFile) => {
+  const handleDownloadFile = async (file: InlineDocumentFile) => {
     const fileKey = `${file.name}_${file.mime_type}`;
 
     if (downloadingFiles.has(fileKey)) {
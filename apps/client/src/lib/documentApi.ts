@@ -7,20 +7,32 @@ import { Result } from "typescript-result";
 // =============================================================================
 
 /**
- * Document file attachment
+ * Legacy inline file attachment (bytes embedded in the document content);
+ * only present on documents published before hash-referenced attachments
  */
-export interface DocumentFile {
+export interface InlineDocumentFile {
   name: string; // Original filename
   content: number[]; // File bytes
   mime_type: string; // MIME type
 }
 
+/**
+ * File attachment referenced by hash; the bytes are served by
+ * GET /content/:content_id on the PodNet server
+ */
+export interface DocumentFile {
+  name: string; // Original filename
+  mime: string; // MIME type
+  content_id: string; // Hash of the attachment bytes (hex)
+}
+
 /**
  * Document content supporting messages, files, and URLs
  */
 export interface DocumentContent {
   message?: string; // Text message
-  file?: DocumentFile; // File attachment
+  file?: InlineDocumentFile; // Legacy inline file attachment
+  files?: DocumentFile[]; // Attachments referenced by hash
   url?: string; // URL reference
 }
 
@@ -58,8 +70,13 @@ export interface DocumentMetadata {
   reply_to?: ReplyReference; // Post and document IDs this replies to
   requested_post_id?: number; // Original post_id from request
   title: string; // Document title
+  display_name?: string; // Uploader's current username, when renamed since publishing
+  deleted_at?: string; // Tombstone timestamp; set when deleted but kept for its replies
+  content_type?: string; // MIME type derived server-side at publish time
   latest_reply_at?: string; // Most recent reply timestamp in this thread
   latest_reply_by?: string; // Username of most recent reply author
+  pinned?: boolean; // Pinned by an instance operator; sorts above everything else
+  excerpt?: string; // Plain-text content preview, present when requested with include_excerpt
 }
 
 /**
@@ -72,14 +89,22 @@ export interface Document {
 }
 
 /**
- * Verification result for a document
+ * Verification outcome for a single POD component of a document
  */
-export interface DocumentVerificationResult {
-  publish_verified: boolean;
-  timestamp_verified: boolean;
-  upvote_count_verified: boolean;
-  verification_details: Record<string, string>;
-  verification_errors: string[];
+export interface ComponentVerification {
+  verified: boolean;
+  error: string | null;
+}
+
+/**
+ * Per-component verification result for a document, so failures can be
+ * attributed to a specific proof (content pod, timestamp pod, upvote pod)
+ */
+export interface DocumentVerification {
+  content_pod: ComponentVerification;
+  timestamp_pod: ComponentVerification;
+  upvote_pod: ComponentVerification;
+  identity_trusted: boolean;
 }
 
 /**
@@ -157,25 +182,81 @@ async function getDocumentServerUrl(): Promise<string> {
   return config.document_server;
 }
 
+/**
+ * Cached HTTP validators (ETag / Last-Modified) and response bodies, keyed by URL.
+ * Lets us issue conditional requests so unchanged responses come back as cheap 304s.
+ */
+const responseCache = new Map<
+  string,
+  { etag: string | null; lastModified: string | null; body: any }
+>();
+
+/**
+ * Fetch JSON from a URL, sending stored cache validators and reusing the cached
+ * body when the server responds with 304 Not Modified.
+ */
+async function fetchJsonWithValidators(url: string): Promise<any> {
+  const cached = responseCache.get(url);
+  const requestHeaders: Record<string, string> = {};
+  if (cached?.etag) {
+    requestHeaders["If-None-Match"] = cached.etag;
+  }
+  if (cached?.lastModified) {
+    requestHeaders["If-Modified-Since"] = cached.lastModified;
+  }
+
+  const response = await fetch(url, { headers: requestHeaders });
+  if (response.status === 304 && cached) {
+    return cached.body;
+  }
+  if (!response.ok) {
+    throw new Error(`Request to ${url} failed: ${response.statusText}`);
+  }
+
+  const body = await response.json();
+  responseCache.set(url, {
+    etag: response.headers.get("ETag"),
+    lastModified: response.headers.get("Last-Modified"),
+    body
+  });
+  return body;
+}
+
+export type DocumentListSort = "new" | "top" | "active";
+
+export interface DocumentListResponse {
+  sort: DocumentListSort;
+  window_days: number | null;
+  items: DocumentMetadata[];
+  total: number;
+  limit: number;
+  offset: number;
+  has_more: boolean;
+}
+
 /**
  * Fetch all documents from the PodNet server
- * @param serverUrl - Optional server URL (defaults to configuration value)
+ * @param sort - Ordering to request ("new", "top" or "active"; server default is "new")
+ * @param window - Upvote recency window for the "top" sort, e.g. "7d"
  * @returns Array of document metadata
  */
-export async function fetchDocuments(): Promise<DocumentMetadata[]> {
+export async function fetchDocuments(
+  sort?: DocumentListSort,
+  window?: string
+): Promise<DocumentMetadata[]> {
   const serverUrl = await getDocumentServerUrl();
+  const params = new URLSearchParams();
+  if (sort) params.set("sort", sort);
+  if (window) params.set("window", window);
+  const query = params.size > 0 ? `?${params.toString()}` : "";
   try {
     console.log(
-      `[documentApi] Fetching documents from: ${serverUrl}/documents`
+      `[documentApi] Fetching documents from: ${serverUrl}/documents${query}`
     );
-    const response = await fetch(`${serverUrl}/documents`);
-    console.log(
-      `[documentApi] Response status: ${response.status} ${response.statusText}`
+    const response: DocumentListResponse = await fetchJsonWithValidators(
+      `${serverUrl}/documents${query}`
     );
-    if (!response.ok) {
-      throw new Error(`Failed to fetch documents: ${response.statusText}`);
-    }
-    return response.json();
+    return response.items;
   } catch (error) {
     console.error(`[documentApi] Error fetching documents:`, error);
     throw error;
@@ -190,11 +271,7 @@ export async function fetchDocuments(): Promise<DocumentMetadata[]> {
  */
 export async function fetchDocument(id: number): Promise<Document> {
   const serverUrl = await getDocumentServerUrl();
-  const response = await fetch(`${serverUrl}/documents/${id}`);
-  if (!response.ok) {
-    throw new Error(`Failed to fetch document ${id}: ${response.statusText}`);
-  }
-  return response.json();
+  return fetchJsonWithValidators(`${serverUrl}/documents/${id}`);
 }
 
 /**
@@ -241,15 +318,12 @@ export async function fetchPosts(): Promise<any[]> {
  */
 export async function verifyDocumentPod(
   document: Document
-): Promise<DocumentVerificationResult> {
+): Promise<DocumentVerification> {
   try {
     console.log("Calling verifyDocumentPod with:", document);
-    const result = await invoke<DocumentVerificationResult>(
-      "verify_document_pod",
-      {
-        document: document
-      }
-    );
+    const result = await invoke<DocumentVerification>("verify_document_pod", {
+      document: document
+    });
     return result;
   } catch (error) {
     console.error("Failed to verify document POD:", error);
@@ -258,6 +332,36 @@ export async function verifyDocumentPod(
   }
 }
 
+/**
+ * Subscribe to the document server's live event stream. Events are forwarded
+ * by the backend as `document-server-event` Tauri events.
+ */
+export async function subscribeDocumentEvents(): Promise<void> {
+  try {
+    const serverUrl = await getDocumentServerUrl();
+    await invoke("subscribe_document_events", { serverUrl });
+  } catch (error) {
+    throw new Error(`Failed to subscribe to document events: ${error}`);
+  }
+}
+
+/**
+ * Poll the server for the number of unread notifications for a user
+ */
+export async function getUnreadNotificationCount(
+  username: string
+): Promise<number> {
+  try {
+    const serverUrl = await getDocumentServerUrl();
+    return await invoke<number>("get_unread_notification_count", {
+      serverUrl,
+      username
+    });
+  } catch (error) {
+    throw new Error(`Failed to get unread notification count: ${error}`);
+  }
+}
+
 // =============================================================================
 // Draft API
 // =============================================================================
@@ -127,6 +127,20 @@ export async function listSpaces(): Promise<SpaceInfo[]> {
   return invokeCommand<SpaceInfo[]>("list_spaces");
 }
 
+/**
+ * Sort orders for the pod list
+ */
+export type PodSort = "NameAsc" | "NewestImported" | "RecentlyModified";
+
+/**
+ * List all PODs across spaces in the given sort order
+ * @param sort - The sort order to apply
+ * @returns Array of POD information
+ */
+export async function listAllPodsSorted(sort: PodSort): Promise<PodInfo[]> {
+  return invokeCommand<PodInfo[]>("list_all_pods_sorted", { sort });
+}
+
 // =============================================================================
 // State Management
 // =============================================================================
@@ -151,3 +165,25 @@ export async function getAppState(): Promise<AppStateData> {
 export async function triggerSync(): Promise<void> {
   return invokeCommand("trigger_sync");
 }
+
+/**
+ * Last selection the user had open, persisted across launches
+ */
+export interface UiState {
+  selected_space: string | null;
+  selected_pod_id: string | null;
+}
+
+/**
+ * Get the last-selected space and pod, for restoring the selection on startup
+ */
+export async function getUiState(): Promise<UiState> {
+  return invokeCommand<UiState>("get_ui_state");
+}
+
+/**
+ * Persist the current selection so it survives a restart
+ */
+export async function setUiState(uiState: UiState): Promise<void> {
+  return invokeCommand("set_ui_state", { uiState });
+}
@@ -180,25 +180,25 @@ export function extractGitHubInfoFromIdentityPod(identityPod: any): {
 } {
   const username = identityPod.username || "Unknown";
 
-  // Parse GitHub data dictionary
-  let githubData: any = {};
-  if (identityPod.github_data) {
+  // Parse the provider data dictionary (github_data is the legacy entry name
+  // used by older identity servers)
+  const rawData = identityPod.provider_data ?? identityPod.github_data;
+  let providerData: any = {};
+  if (rawData) {
     try {
-      githubData =
-        typeof identityPod.github_data === "string"
-          ? JSON.parse(identityPod.github_data)
-          : identityPod.github_data;
+      providerData = typeof rawData === "string" ? JSON.parse(rawData) : rawData;
     } catch (error) {
-      console.error("Failed to parse GitHub data from identity POD:", error);
+      console.error("Failed to parse provider data from identity POD:", error);
     }
   }
 
   return {
     username: username,
-    github_username: githubData.github_username,
-    github_user_id: githubData.github_user_id,
-    github_public_keys: githubData.github_public_keys,
-    github_email: githubData.github_email,
-    oauth_verified_at: githubData.oauth_verified_at
+    github_username: providerData.provider_username ?? providerData.github_username,
+    github_user_id: providerData.provider_user_id ?? providerData.github_user_id,
+    github_public_keys:
+      providerData.provider_public_keys ?? providerData.github_public_keys,
+    github_email: providerData.provider_email ?? providerData.github_email,
+    oauth_verified_at: providerData.oauth_verified_at
   };
 }
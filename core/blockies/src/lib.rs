@@ -0,0 +1,86 @@
+//! Deterministic blockies seed data for pod avatars.
+//!
+//! Both the desktop client and the Node bindings derive avatars from the
+//! same identifiers (public keys, pod ids); keeping the derivation here
+//! means the two cannot drift apart. Rendering — PNG, canvas, CSS — stays
+//! with each frontend; this crate only produces the grid and palette.
+
+use eth_blockies::{Blockies, BlockiesGenerator};
+use serde::{Deserialize, Serialize};
+
+/// Width and height of the avatar grid, in cells
+pub const GRID_SIZE: usize = 16;
+
+type Icon16<T> = Blockies<GRID_SIZE, T>;
+
+/// Palette-indexed avatar data for one seed string
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlockiesSeed {
+    /// Row-major palette indices, [`GRID_SIZE`] rows of [`GRID_SIZE`] cells
+    pub cells: Vec<Vec<u8>>,
+    /// `#rrggbb` colors in order of first appearance in the grid
+    pub colors: Vec<String>,
+}
+
+/// Raw RGB cells for a seed, for renderers that want pixels directly. The
+/// seed is lowercased so differently-cased renderings of the same key agree.
+pub fn rgb_cells(seed: &str) -> [[(u8, u8, u8); GRID_SIZE]; GRID_SIZE] {
+    Icon16::data(seed.to_lowercase())
+}
+
+/// Palette-indexed cells and colors for a seed; the same input always
+/// produces the same output
+pub fn seed_data(seed: &str) -> BlockiesSeed {
+    let rgb = rgb_cells(seed);
+    let mut colors: Vec<String> = Vec::new();
+    let cells = rgb
+        .iter()
+        .map(|row| {
+            row.iter()
+                .map(|&(r, g, b)| {
+                    let color = format!("#{r:02x}{g:02x}{b:02x}");
+                    match colors.iter().position(|c| c == &color) {
+                        Some(index) => index as u8,
+                        None => {
+                            colors.push(color);
+                            (colors.len() - 1) as u8
+                        }
+                    }
+                })
+                .collect()
+        })
+        .collect();
+    BlockiesSeed { cells, colors }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn seed_data_is_deterministic_and_case_insensitive() {
+        let a = seed_data("PubKey123");
+        let b = seed_data("pubkey123");
+        assert_eq!(a, b);
+        assert_eq!(a, seed_data("PubKey123"));
+        assert_ne!(a, seed_data("a different key"));
+    }
+
+    #[test]
+    fn seed_data_covers_the_grid_with_a_valid_palette() {
+        let seed = seed_data("some-public-key");
+        assert_eq!(seed.cells.len(), GRID_SIZE);
+        // Blockies draw from a three-color palette
+        assert!(!seed.colors.is_empty() && seed.colors.len() <= 3);
+        for row in &seed.cells {
+            assert_eq!(row.len(), GRID_SIZE);
+            for &cell in row {
+                assert!((cell as usize) < seed.colors.len());
+            }
+        }
+        for color in &seed.colors {
+            assert_eq!(color.len(), 7);
+            assert!(color.starts_with('#'));
+        }
+    }
+}
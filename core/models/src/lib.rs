@@ -7,7 +7,7 @@ use lazy_pod::LazyDeser;
 use pod2::{
     backends::plonky2::primitives::ec::curve::Point as PublicKey,
     frontend::{MainPod, SignedDict},
-    middleware::{Hash, Key, Value},
+    middleware::{Hash, Key, Value, hash_values},
 };
 use serde::{Deserialize, Serialize};
 
@@ -17,27 +17,83 @@ pub mod macros;
 // /// Main pod operations and verification utilities
 pub mod mainpod;
 
-/// File attachment within a document
+/// Legacy inline file attachment with the bytes embedded in the document
+/// content itself. Kept so content blobs stored before attachments moved to
+/// [`DocumentContent::files`] keep deserializing (and keep their hash).
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct DocumentFile {
+pub struct InlineDocumentFile {
     pub name: String,      // Original filename
     pub content: Vec<u8>,  // File bytes (base64 encoded in JSON)
     pub mime_type: String, // MIME type
 }
 
+/// File attachment referenced by hash. The bytes are stored as their own blob
+/// in content-addressed storage; the document content (and therefore the hash
+/// the main pod commits to) only carries this reference.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DocumentFile {
+    pub name: String,     // Original filename
+    pub mime: String,     // MIME type
+    pub content_id: Hash, // Hash of the attachment bytes, per [`DocumentFile::content_id_for`]
+}
+
+impl DocumentFile {
+    /// Content id for a blob of attachment bytes. Clients must use exactly
+    /// this hash when building [`DocumentContent::files`], so the server can
+    /// match the uploaded bytes in [`PublishRequest::attachments`] to their
+    /// references.
+    pub fn content_id_for(bytes: &[u8]) -> Hash {
+        hash_values(&[Value::from(bytes.encode_hex::<String>())])
+    }
+}
+
 /// Multi-content document structure supporting messages, files, and URLs
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DocumentContent {
-    pub message: Option<String>,    // Text message
-    pub file: Option<DocumentFile>, // File attachment
-    pub url: Option<String>,        // URL reference
+    pub message: Option<String>, // Text message
+    /// Legacy inline attachment from before [`DocumentContent::files`]; not
+    /// produced for new documents
+    pub file: Option<InlineDocumentFile>,
+    /// Attachments stored separately and referenced by hash
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub files: Vec<DocumentFile>,
+    pub url: Option<String>, // URL reference
+}
+
+/// Best-effort MIME detection from magic bytes, used to derive a document's
+/// content type at publish time instead of trusting the client-declared MIME.
+pub fn sniff_mime(bytes: &[u8]) -> &'static str {
+    match bytes {
+        [0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A, ..] => "image/png",
+        [0xFF, 0xD8, 0xFF, ..] => "image/jpeg",
+        [b'G', b'I', b'F', b'8', ..] => "image/gif",
+        [b'R', b'I', b'F', b'F', _, _, _, _, b'W', b'E', b'B', b'P', ..] => "image/webp",
+        [b'%', b'P', b'D', b'F', ..] => "application/pdf",
+        [0x1F, 0x8B, ..] => "application/gzip",
+        [b'P', b'K', 0x03, 0x04, ..] => "application/zip",
+        _ if std::str::from_utf8(bytes).is_ok() => "text/plain",
+        _ => "application/octet-stream",
+    }
 }
 
 impl DocumentContent {
-    /// Validate that at least one content type is provided
+    /// Validate that exactly one primary content kind (message, inline file,
+    /// or url) is provided. Attachments in `files` accompany the primary
+    /// content and do not count towards the rule.
     pub fn validate(&self) -> Result<(), String> {
-        if self.message.is_none() && self.file.is_none() && self.url.is_none() {
-            return Err("At least one of message, file, or url must be provided".to_string());
+        let provided = [
+            self.message.is_some(),
+            self.file.is_some(),
+            self.url.is_some(),
+        ]
+        .iter()
+        .filter(|set| **set)
+        .count();
+        if provided == 0 {
+            return Err("One of message, file, or url must be provided".to_string());
+        }
+        if provided > 1 {
+            return Err("Only one of message, file, or url may be provided".to_string());
         }
 
         // Validate file size (max 10MB)
@@ -62,6 +118,20 @@ impl DocumentContent {
 
         Ok(())
     }
+
+    /// MIME type of the primary content, derived at publish time: inline file
+    /// bytes are sniffed by magic bytes rather than trusting the declared
+    /// MIME, URLs (whose scheme [`DocumentContent::validate`] already checked)
+    /// report a link type, and messages are markdown.
+    pub fn derived_content_type(&self) -> &'static str {
+        if let Some(file) = &self.file {
+            sniff_mime(&file.content)
+        } else if self.url.is_some() {
+            "text/uri-list"
+        } else {
+            "text/markdown"
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -72,7 +142,7 @@ pub struct Post {
     pub thread_root_post_id: Option<i64>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ReplyReference {
     pub post_id: i64,     // Post ID being replied to
     pub document_id: i64, // Specific document ID being replied to
@@ -94,6 +164,8 @@ pub struct RawDocument {
     pub reply_to: Option<ReplyReference>, // Post and document IDs this document is replying to
     pub requested_post_id: Option<i64>,   // Original post_id from request used in MainPod proof
     pub title: String,                    // Document title
+    pub deleted_at: Option<String>,       // Tombstone timestamp; row kept for its replies
+    pub content_type: Option<String>,     // Derived MIME type of the primary content
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -152,6 +224,23 @@ pub struct DocumentMetadata {
     /// This may be -1 for new documents, while post_id is the actual assigned ID
     pub requested_post_id: Option<i64>,
     pub title: String, // Document title
+    /// Hidden by a moderation action; excluded from lists and feeds but still
+    /// retrievable by direct id so reply threads don't break
+    #[serde(default)]
+    pub hidden: bool,
+    /// Current display name of the uploader when it differs from the
+    /// uploader_id recorded at publish time (the username changed since)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub display_name: Option<String>,
+    /// Tombstone timestamp: the author deleted this document but replies still
+    /// hang off it, so the row is kept with its content blanked out
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deleted_at: Option<String>,
+    /// MIME type of the primary content, derived server-side at publish time
+    /// per [`DocumentContent::derived_content_type`]. None for documents
+    /// published before the type was recorded.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content_type: Option<String>,
 }
 
 /// Extended document metadata for list views, including latest reply information
@@ -163,8 +252,77 @@ pub struct DocumentListItem {
     /// Timestamp of the most recent reply in this thread (or None if no replies)
     pub latest_reply_at: Option<String>,
 
-    /// Username of the author of the most recent reply (None if no replies exist)  
+    /// Username of the author of the most recent reply (None if no replies exist)
     pub latest_reply_by: Option<String>,
+
+    /// Pinned by an instance operator; pinned threads sort above everything else
+    #[serde(default)]
+    pub pinned: bool,
+
+    /// Plain-text preview of the document's message, populated only when the
+    /// client asks for excerpts
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub excerpt: Option<String>,
+}
+
+/// Pagination wrapper shared by the list endpoints. `total` counts every row
+/// matching the filter, so clients can tell whether more pages exist.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PaginatedResponse<T> {
+    pub items: Vec<T>,
+    /// Rows matching the filter across all pages
+    pub total: i64,
+    pub limit: i64,
+    pub offset: i64,
+    /// Whether rows exist past the end of this page
+    pub has_more: bool,
+}
+
+impl<T> PaginatedResponse<T> {
+    pub fn new(items: Vec<T>, total: i64, limit: i64, offset: i64) -> Self {
+        let has_more = offset + items.len() as i64 < total;
+        Self {
+            items,
+            total,
+            limit,
+            offset,
+            has_more,
+        }
+    }
+}
+
+/// Response payload for the document list endpoint. Echoes the sort the
+/// server actually applied so clients can render the active ordering.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DocumentListResponse {
+    /// The effective sort: "new", "top" or "active"
+    pub sort: String,
+
+    /// Upvote window in days applied when sorting by top (None = all time)
+    pub window_days: Option<i64>,
+
+    #[serde(flatten)]
+    pub page: PaginatedResponse<DocumentListItem>,
+}
+
+/// One search hit. The excerpt is the FTS snippet around the match, with
+/// `[match]`/`[/match]` markers, populated only when the client asks for it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchResultItem {
+    #[serde(flatten)]
+    pub metadata: DocumentMetadata,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub excerpt: Option<String>,
+}
+
+/// Placeholder for a reply branch that was collapsed out of a pruned reply tree
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplyTreeStub {
+    pub document_id: i64,
+    pub post_id: i64,
+    /// Number of direct replies beneath the collapsed document
+    pub reply_count: i64,
 }
 
 /// Hierarchical reply tree structure for efficiently representing document replies
@@ -173,6 +331,9 @@ pub struct DocumentReplyTree {
     pub document: DocumentMetadata,
     pub content: DocumentContent,
     pub replies: Vec<DocumentReplyTree>,
+    /// Direct replies excluded by depth/limit pruning, represented as child-count stubs
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub stubs: Vec<ReplyTreeStub>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -370,6 +531,16 @@ pub struct PublishRequest {
     ///
     /// This enables trustless document publishing with verified authorship.
     pub main_pod: MainPod,
+    /// Claimed `issued_at` entry of the (private) identity pod inside the MainPod.
+    /// Only consulted when the identity server has been revoked, to accept pods
+    /// issued before the revocation timestamp.
+    #[serde(default)]
+    pub identity_pod_issued_at: Option<String>,
+    /// Raw bytes for every attachment referenced in `content.files`, matched
+    /// to their [`DocumentFile::content_id`] by hash. The server stores each
+    /// blob individually before the document content itself.
+    #[serde(default)]
+    pub attachments: Vec<Vec<u8>>,
 }
 
 /// Request structure for deleting a document
@@ -403,9 +574,150 @@ pub struct MarkdownResponse {
     pub html: String,
 }
 
+/// Server-enforced content limits, advertised on `GET /` so clients can
+/// pre-validate a publish before building any proofs.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContentLimits {
+    /// Maximum title length in characters
+    pub max_title_length: usize,
+    /// Maximum message length in bytes
+    pub max_message_bytes: usize,
+    /// Maximum file attachment size in bytes
+    pub max_file_bytes: usize,
+    /// Maximum combined size of message, file, and URL in bytes
+    pub max_content_bytes: usize,
+    /// Maximum number of tags per document
+    pub max_tags: usize,
+    /// Maximum tag length in characters
+    pub max_tag_length: usize,
+}
+
+impl Default for ContentLimits {
+    fn default() -> Self {
+        Self {
+            max_title_length: 200,
+            max_message_bytes: 64 * 1024,
+            max_file_bytes: 10 * 1024 * 1024,
+            max_content_bytes: 10 * 1024 * 1024 + 64 * 1024,
+            max_tags: 16,
+            max_tag_length: 64,
+        }
+    }
+}
+
+/// A publish request field that exceeded a [`ContentLimits`] bound
+#[derive(Debug, Clone, Serialize)]
+pub struct ContentLimitViolation {
+    pub field: &'static str,
+    pub message: String,
+    /// True for byte-size violations (HTTP 413); false for structural ones (422)
+    pub oversize: bool,
+}
+
+impl ContentLimits {
+    /// Check the user-controlled parts of a publish request against these
+    /// limits, reporting the first offending field.
+    pub fn validate(
+        &self,
+        title: &str,
+        content: &DocumentContent,
+        tags: &HashSet<String>,
+        attachments: &[Vec<u8>],
+    ) -> Result<(), ContentLimitViolation> {
+        let title_chars = title.chars().count();
+        if title_chars > self.max_title_length {
+            return Err(ContentLimitViolation {
+                field: "title",
+                message: format!(
+                    "title is {title_chars} characters, maximum is {}",
+                    self.max_title_length
+                ),
+                oversize: false,
+            });
+        }
+
+        if tags.len() > self.max_tags {
+            return Err(ContentLimitViolation {
+                field: "tags",
+                message: format!("{} tags given, maximum is {}", tags.len(), self.max_tags),
+                oversize: false,
+            });
+        }
+        for tag in tags {
+            let tag_chars = tag.chars().count();
+            if tag_chars > self.max_tag_length {
+                return Err(ContentLimitViolation {
+                    field: "tags",
+                    message: format!(
+                        "tag {tag:?} is {tag_chars} characters, maximum is {}",
+                        self.max_tag_length
+                    ),
+                    oversize: false,
+                });
+            }
+        }
+
+        let message_bytes = content.message.as_deref().map_or(0, str::len);
+        if message_bytes > self.max_message_bytes {
+            return Err(ContentLimitViolation {
+                field: "content.message",
+                message: format!(
+                    "message is {message_bytes} bytes, maximum is {}",
+                    self.max_message_bytes
+                ),
+                oversize: true,
+            });
+        }
+
+        let file_bytes = content.file.as_ref().map_or(0, |f| f.content.len());
+        if file_bytes > self.max_file_bytes {
+            return Err(ContentLimitViolation {
+                field: "content.file",
+                message: format!(
+                    "file is {file_bytes} bytes, maximum is {}",
+                    self.max_file_bytes
+                ),
+                oversize: true,
+            });
+        }
+
+        for (index, attachment) in attachments.iter().enumerate() {
+            if attachment.len() > self.max_file_bytes {
+                return Err(ContentLimitViolation {
+                    field: "attachments",
+                    message: format!(
+                        "attachment {index} is {} bytes, maximum is {}",
+                        attachment.len(),
+                        self.max_file_bytes
+                    ),
+                    oversize: true,
+                });
+            }
+        }
+
+        let attachment_bytes: usize = attachments.iter().map(Vec::len).sum();
+        let url_bytes = content.url.as_deref().map_or(0, str::len);
+        let total_bytes = message_bytes + file_bytes + attachment_bytes + url_bytes;
+        if total_bytes > self.max_content_bytes {
+            return Err(ContentLimitViolation {
+                field: "content",
+                message: format!(
+                    "content totals {total_bytes} bytes, maximum is {}",
+                    self.max_content_bytes
+                ),
+                oversize: true,
+            });
+        }
+
+        Ok(())
+    }
+}
+
 #[derive(Debug, Serialize)]
 pub struct ServerInfo {
     pub public_key: PublicKey,
+    /// Content limits enforced on publish
+    pub limits: ContentLimits,
 }
 
 #[derive(Debug, Deserialize)]
@@ -430,6 +742,16 @@ pub struct IdentityServer {
     pub challenge_pod: String, // Server's challenge pod as JSON string
     pub identity_pod: String,  // Identity server's response pod as JSON string
     pub created_at: Option<String>,
+    /// When the server was revoked; identity pods issued after this are rejected
+    pub revoked_at: Option<String>,
+    /// Endpoint advertised at registration, polled for liveness (None = not polled)
+    pub base_url: Option<String>,
+    /// When the health poller last got a response from the server
+    pub last_seen_at: Option<String>,
+    /// Outcome of the last health poll: "ok", "key-mismatch" or "unreachable"
+    pub last_status: Option<String>,
+    /// Whether the server last advertised a public key that differs from its registered one
+    pub key_mismatch: bool,
 }
 
 #[derive(Debug, Deserialize)]
@@ -443,9 +765,12 @@ pub struct IdentityServerChallengeRequest {
 pub struct IdentityServerChallengeResponse {
     /// SignedPod containing challenge information from main server:
     /// - challenge: String (random challenge value)
+    /// - nonce: String (single-use value consumed at registration)
+    /// - issued_at: String (ISO timestamp the challenge was created)
     /// - expires_at: String (ISO timestamp when challenge expires)
     /// - identity_server_public_key: Point (public key from request)
     /// - server_id: String (server ID from request)
+    /// - podnet_server_public_key: Point (instance the challenge is bound to)
     /// - _signer: Point (main server's public key, automatically added by SignedPod)
     pub challenge_pod: SignedDict,
 }
@@ -456,9 +781,12 @@ pub struct IdentityServerRegistration {
     ///
     /// server_challenge_pod contains:
     /// - challenge: String (original challenge from server)
+    /// - nonce: String (single-use value; a reused nonce is rejected as a replay)
+    /// - issued_at: String (issuance timestamp, checked against the replay window)
     /// - expires_at: String (expiration timestamp)
     /// - identity_server_public_key: Point (identity server's public key)
     /// - server_id: String (identity server ID)
+    /// - podnet_server_public_key: Point (must match the receiving instance's key)
     /// - _signer: Point (main server's public key)
     ///
     /// identity_response_pod contains:
@@ -467,6 +795,84 @@ pub struct IdentityServerRegistration {
     /// - _signer: Point (identity server's public key, proving control of private key)
     pub server_challenge_pod: SignedDict,
     pub identity_response_pod: SignedDict,
+    /// Publicly reachable endpoint of the identity server, used for health polling
+    #[serde(default)]
+    pub base_url: Option<String>,
+}
+
+/// One key in an identity server's rotation history, with its validity window
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IdentityServerKey {
+    pub public_key: String, // Stored as string in DB
+    pub valid_from: Option<String>,
+    /// `None` for the currently valid key
+    pub valid_until: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct IdentityServerKeyRotation {
+    /// SignedDict proving continuity between the old and new keypair:
+    /// - server_id: String (ID of the rotating server)
+    /// - new_public_key: Point (the key to rotate to)
+    /// - action: String ("rotate")
+    /// - _signer: Point (the server's current, soon-to-be-old public key)
+    pub continuity_pod: SignedDict,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct IdentityServerRevocation {
+    /// SignedDict proving the revocation was authorized by the identity server itself:
+    /// - server_id: String (ID of the server being revoked)
+    /// - action: String ("revoke")
+    /// - _signer: Point (identity server's public key)
+    ///
+    /// May be omitted when the operator revokes with the admin token instead.
+    pub revocation_pod: Option<SignedDict>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct IdentityUpdateRequest {
+    /// Freshly issued identity pod binding a public key to its current
+    /// username:
+    /// - username: String (the new display username)
+    /// - user_public_key: Point (the user's signing key)
+    /// - _signer: Point (a registered identity server's public key)
+    pub identity_pod: SignedDict,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ThreadSubscribeRequest {
+    /// Freshly issued identity pod naming the subscriber, same shape as
+    /// [`IdentityUpdateRequest::identity_pod`]. Optional when the request
+    /// carries an `Authorization: Bearer` API token instead.
+    #[serde(default)]
+    pub identity_pod: Option<SignedDict>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TokenIssueRequest {
+    /// Freshly issued identity pod naming the token's owner, same shape as
+    /// [`IdentityUpdateRequest::identity_pod`]
+    pub identity_pod: SignedDict,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TokenIssueResponse {
+    /// The bearer token itself, returned only at issuance; the server keeps
+    /// a hash of it
+    pub token: String,
+    pub username: String,
+}
+
+/// Notification for a reply to one of a user's documents
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Notification {
+    pub id: Option<i64>,
+    pub username: String, // Recipient (uploader of the document that was replied to)
+    pub document_id: i64, // The reply document that triggered the notification
+    pub actor_username: String, // Author of the reply
+    pub read: bool,
+    pub created_at: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -502,6 +908,94 @@ pub struct UpvoteRequest {
     ///
     /// This enables trustless upvoting with verified user identity.
     pub upvote_main_pod: MainPod,
+    /// Claimed `issued_at` entry of the (private) identity pod inside the MainPod.
+    /// Only consulted when the identity server has been revoked, to accept pods
+    /// issued before the revocation timestamp.
+    #[serde(default)]
+    pub identity_pod_issued_at: Option<String>,
+}
+
+/// A moderation flag raised against a document
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DocumentFlag {
+    pub id: Option<i64>,
+    pub document_id: i64,
+    pub username: String, // Verified username of the reporter
+    pub reason: String,
+    pub created_at: Option<String>,
+    pub resolved_at: Option<String>,
+    /// How the flag was resolved ("dismiss" or "hide"), None while open
+    pub resolution: Option<String>,
+}
+
+/// Aggregated view of the open flags against one document, for the admin queue
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FlaggedDocument {
+    pub document_id: i64,
+    pub title: String,
+    pub flag_count: i64,
+    pub reasons: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FlagRequest {
+    pub document_id: i64, // ID of the document being flagged
+    pub reason: String,   // Human-readable reason for the flag
+    pub username: String, // Expected username from identity verification
+    /// MainPod that cryptographically proves the reporter's identity and that the
+    /// flag targets this document:
+    ///
+    /// Uses the same solver-based approach as upvotes with:
+    /// - identity_verified(username, private: identity_pod)
+    /// - flag_verified(content_hash, private: flag_pod)
+    /// - flag_verification(username, content_hash, identity_server_pk, private: identity_pod, flag_pod)
+    ///
+    /// Public data exposed by main pod:
+    /// - username: String (verified username from identity pod)
+    /// - content_hash: String (verified content hash of flagged document)
+    /// - identity_server_pk: Point (verified identity server public key)
+    ///
+    /// This prevents anonymous flag spam: one verified identity, one flag per document.
+    pub flag_main_pod: MainPod,
+    /// Claimed `issued_at` entry of the (private) identity pod inside the MainPod.
+    /// Only consulted when the identity server has been revoked, to accept pods
+    /// issued before the revocation timestamp.
+    #[serde(default)]
+    pub identity_pod_issued_at: Option<String>,
+}
+
+/// Moderation action taken when resolving a flag
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FlagAction {
+    /// Close the flag without touching the document
+    Dismiss,
+    /// Close the flag and hide the document from lists and feeds
+    Hide,
+}
+
+impl FlagAction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FlagAction::Dismiss => "dismiss",
+            FlagAction::Hide => "hide",
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ResolveFlagRequest {
+    pub action: FlagAction,
+}
+
+/// Extract the `issued_at` entry from an identity pod, used to populate the
+/// `identity_pod_issued_at` claim on publish and upvote requests.
+pub fn identity_pod_issued_at(identity_pod: &SignedDict) -> Option<String> {
+    use pod_utils::ValueExt;
+    identity_pod
+        .get("issued_at")
+        .and_then(|v| v.as_str())
+        .map(String::from)
 }
 
 // /// Shared predicate definitions for publish verification
@@ -543,6 +1037,28 @@ pub fn get_upvote_verification_predicate() -> String {
         "#.to_string()
 }
 
+// /// Shared predicate definitions for flag verification only
+pub fn get_flag_verification_predicate() -> String {
+    r#"
+        identity_verified(username, identity_pod) = AND(
+            Equal(identity_pod["username"], username)
+        )
+
+        flag_verified(content_hash, flag_pod) = AND(
+            Equal(flag_pod["content_hash"], content_hash)
+            Equal(flag_pod["request_type"], "flag")
+        )
+
+        flag_verification(username, content_hash, identity_server_pk, private: identity_pod, flag_pod, flag_pod_signer) = AND(
+            identity_verified(username, identity_pod)
+            flag_verified(content_hash, flag_pod)
+            SignedBy(identity_pod, identity_server_pk)
+            SignedBy(flag_pod, flag_pod_signer)
+            Equal(identity_pod["user_public_key"], flag_pod_signer)
+        )
+        "#.to_string()
+}
+
 // /// Shared predicate definitions for upvote count verification only
 pub fn get_upvote_count_predicate(upvote_batch_id: Hash) -> String {
     format!(
@@ -569,3 +1085,65 @@ pub fn get_upvote_count_predicate(upvote_batch_id: Hash) -> String {
         id = upvote_batch_id.encode_hex::<String>(),
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message_content() -> DocumentContent {
+        DocumentContent {
+            message: Some("hello".to_string()),
+            file: None,
+            files: Vec::new(),
+            url: None,
+        }
+    }
+
+    #[test]
+    fn test_png_file_content_type_is_sniffed() {
+        let png_header = [0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A, 0, 0];
+        let content = DocumentContent {
+            message: None,
+            file: Some(InlineDocumentFile {
+                name: "photo.bin".to_string(),
+                content: png_header.to_vec(),
+                // The declared MIME is ignored; the bytes decide
+                mime_type: "text/plain".to_string(),
+            }),
+            files: Vec::new(),
+            url: None,
+        };
+        content.validate().unwrap();
+        assert_eq!(content.derived_content_type(), "image/png");
+    }
+
+    #[test]
+    fn test_message_and_url_content_types() {
+        assert_eq!(message_content().derived_content_type(), "text/markdown");
+
+        let content = DocumentContent {
+            url: Some("https://example.com".to_string()),
+            ..message_content()
+        };
+        assert_eq!(content.derived_content_type(), "text/uri-list");
+    }
+
+    #[test]
+    fn test_validate_rejects_multiple_primary_contents() {
+        let content = DocumentContent {
+            url: Some("https://example.com".to_string()),
+            ..message_content()
+        };
+        let err = content.validate().unwrap_err();
+        assert!(err.contains("Only one of"));
+    }
+
+    #[test]
+    fn test_validate_rejects_empty_content() {
+        let content = DocumentContent {
+            message: None,
+            ..message_content()
+        };
+        assert!(content.validate().is_err());
+    }
+}
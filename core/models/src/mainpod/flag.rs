@@ -0,0 +1,130 @@
+//! Flag verification MainPod operations
+
+use pod_utils::prover_setup::PodNetProverSetup;
+use pod2::{
+    frontend::{MainPod, SignedDict},
+    lang::parse,
+    middleware::{Hash, Value},
+};
+use pod2_new_solver::{
+    Engine, EngineConfigBuilder, ImmutableEdbBuilder, OpRegistry,
+    build_pod_from_answer_top_level_public,
+};
+
+use super::{MainPodError, MainPodResult};
+use crate::get_flag_verification_predicate;
+
+/// Parameters for solver-based flag verification proof generation
+pub struct FlagProofParamsSolver<'a> {
+    pub identity_pod: &'a SignedDict,
+    pub flag_pod: &'a SignedDict,
+    pub use_mock_proofs: bool,
+}
+
+/// Generate a flag verification MainPod using the pod2 solver
+///
+/// This creates a MainPod that cryptographically proves the reporter's identity
+/// and that the flag pod targets the given content hash, mirroring the upvote
+/// verification flow.
+pub fn prove_flag_verification_with_solver(
+    params: FlagProofParamsSolver,
+) -> MainPodResult<MainPod> {
+    // Extract required values from pods
+    let username = params
+        .identity_pod
+        .get("username")
+        .ok_or(MainPodError::MissingField {
+            pod_type: "Identity",
+            field: "username",
+        })?;
+
+    let content_hash = params
+        .flag_pod
+        .get("content_hash")
+        .ok_or(MainPodError::MissingField {
+            pod_type: "Flag",
+            field: "content_hash",
+        })?;
+
+    let identity_server_pk: Value = params.identity_pod.public_key.into();
+
+    // Start with the flag verification predicate definitions and append REQUEST
+    let mut query = get_flag_verification_predicate();
+
+    query.push_str(&format!(
+        r#"
+
+        REQUEST(
+            flag_verification({username}, {content_hash}, {identity_server_pk})
+        )
+        "#
+    ));
+
+    let pod_params = PodNetProverSetup::get_params();
+    let request = parse(&query, &pod_params, &[])
+        .map_err(|e| MainPodError::ProofGeneration(format!("Parse error: {e:?}")))?;
+
+    let edb = ImmutableEdbBuilder::new()
+        .add_signed_dict(params.identity_pod.clone())
+        .add_signed_dict(params.flag_pod.clone())
+        .build();
+
+    let reg = OpRegistry::default();
+    let config = EngineConfigBuilder::new().recommended(&pod_params).build();
+    let mut engine = Engine::with_config(&reg, &edb, config);
+    engine.load_processed(&request);
+    engine
+        .run()
+        .map_err(|e| MainPodError::ProofGeneration(format!("Solver error: {e:?}")))?;
+
+    let (vd_set, prover) = PodNetProverSetup::create_prover_setup(params.use_mock_proofs)
+        .map_err(MainPodError::ProofGeneration)?;
+
+    let main_pod = build_pod_from_answer_top_level_public(
+        &engine.answers[0],
+        &pod_params,
+        vd_set,
+        |b| b.prove(&*prover).map_err(|e| e.to_string()),
+        &edb,
+    )
+    .map_err(|e| MainPodError::ProofGeneration(format!("Pod build error: {e:?}")))?;
+
+    Ok(main_pod)
+}
+
+/// Verify a flag verification MainPod using the pod2 solver
+///
+/// This verifies that the MainPod contains the expected public statements
+/// and that the content hash and username match the expected values.
+pub fn verify_flag_verification_with_solver(
+    main_pod: &MainPod,
+    expected_username: &str,
+    expected_content_hash: &Hash,
+    expected_identity_server_pk: &Value,
+) -> MainPodResult<()> {
+    // Start with the flag verification predicate definitions and append REQUEST
+    let mut query = get_flag_verification_predicate();
+
+    let username_value = Value::from(expected_username);
+    let content_hash_value = Value::from(*expected_content_hash);
+
+    query.push_str(&format!(
+        r#"
+
+        REQUEST(
+            flag_verification({username_value}, {content_hash_value}, {expected_identity_server_pk})
+        )
+        "#
+    ));
+
+    let pod_params = PodNetProverSetup::get_params();
+    let request = parse(&query, &pod_params, &[])
+        .map_err(|e| MainPodError::ProofGeneration(format!("Parse error: {e:?}")))?
+        .request;
+
+    request
+        .exact_match_pod(&*main_pod.pod)
+        .map_err(|e| MainPodError::Verification(format!("Exact match pod error: {e:?}")))?;
+
+    Ok(())
+}
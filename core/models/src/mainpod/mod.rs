@@ -4,6 +4,7 @@
 //! used in PodNet, eliminating code duplication and providing consistent interfaces.
 
 pub mod delete;
+pub mod flag;
 pub mod publish;
 pub mod upvote;
 //pub mod upvote_count;
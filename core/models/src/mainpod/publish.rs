@@ -1,10 +1,10 @@
 //! Publish verification MainPod operations
 
-use pod_utils::prover_setup::PodNetProverSetup;
+use pod_utils::{ValueExt, prover_setup::PodNetProverSetup};
 use pod2::{
     frontend::{MainPod, SignedDict},
     lang::parse,
-    middleware::{Params, Value, containers::Dictionary},
+    middleware::{Hash, Key, Params, Value, containers::Dictionary},
 };
 use pod2_new_solver::{
     Engine, EngineConfigBuilder, ImmutableEdbBuilder, OpRegistry,
@@ -141,6 +141,30 @@ pub fn verify_publish_verification_with_solver(
     Ok(())
 }
 
+/// Extract the content hash a publish MainPod publicly commits to.
+///
+/// The `publish_verified` statement's second argument is the signed data
+/// dictionary; its `content_hash` entry names the content the prover
+/// committed to at publish time.
+pub fn extract_publish_content_hash(main_pod: &MainPod) -> MainPodResult<Hash> {
+    let (_username, data, _identity_server_pk) = crate::extract_mainpod_args!(
+        main_pod,
+        get_publish_verification_predicate(),
+        "publish_verified",
+        username: as_str,
+        data: as_dictionary,
+        identity_server_pk: as_public_key
+    )?;
+
+    data.get(&Key::from("content_hash"))
+        .ok()
+        .and_then(|v| v.as_hash())
+        .ok_or(MainPodError::MissingField {
+            pod_type: "Publish",
+            field: "content_hash",
+        })
+}
+
 #[cfg(test)]
 mod tests {
     // Add unit tests for publish verification functions
@@ -1042,8 +1042,13 @@ impl<'a> Engine<'a> {
                 }
             }
         }
-        // Use the best choices in a stable order
-        best.into_iter().map(|(_, (_, ch))| ch).collect()
+        // Use the best choices in a stable order; with a shuffle seed, ties
+        // are deterministically permuted within their score class
+        let mut scored: Vec<(i32, Choice)> = best.into_values().collect();
+        if let Some(seed) = self.config.shuffle_seed {
+            shuffle_equal_scores(&mut scored, seed);
+        }
+        scored.into_iter().map(|(_, ch)| ch).collect()
     }
 
     fn enqueue_continuations_for_choices(
@@ -1291,6 +1296,40 @@ fn select_waiters_for_answer(
     let exceeded = budget_left == 0 && cap != u32::MAX;
     (to_deliver, inc, exceeded)
 }
+/// SplitMix64 step; a tiny seedable generator so the shuffle needs no rand
+/// dependency and stays reproducible across platforms.
+#[inline]
+fn splitmix64(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+/// Deterministically permute `scored` within each score class: a choice only
+/// ever swaps places with equally-scored choices, so the relative order of
+/// differently-scored choices is untouched.
+fn shuffle_equal_scores<T>(scored: &mut [(i32, T)], seed: u64) {
+    use std::collections::BTreeSet;
+
+    let mut state = seed;
+    let classes: BTreeSet<i32> = scored.iter().map(|(score, _)| *score).collect();
+    for class in classes {
+        let positions: Vec<usize> = scored
+            .iter()
+            .enumerate()
+            .filter(|(_, (score, _))| *score == class)
+            .map(|(i, _)| i)
+            .collect();
+        // Fisher-Yates over the positions of this class
+        for i in (1..positions.len()).rev() {
+            let j = (splitmix64(&mut state) % (i as u64 + 1)) as usize;
+            scored.swap(positions[i], positions[j]);
+        }
+    }
+}
+
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum SchedulePolicy {
     DepthFirst,
@@ -1309,6 +1348,10 @@ pub struct EngineConfig {
     pub ops_per_pod: usize,
     pub inputs_per_pod: usize,
     pub wall_clock_timeout: Option<Duration>,
+    /// When set, equally-scored choices are explored in a seeded
+    /// pseudo-random order instead of the canonical stable order. The same
+    /// seed always yields the same permutation.
+    pub shuffle_seed: Option<u64>,
 }
 
 #[derive(Clone, Debug, Default)]
@@ -1367,6 +1410,12 @@ impl EngineConfigBuilder {
         self.cfg.wall_clock_timeout = Some(Duration::from_millis(timeout_ms));
         self
     }
+    /// Explore equally-scored choices in a seeded pseudo-random order; see
+    /// [`EngineConfig::shuffle_seed`].
+    pub fn shuffle_seed(mut self, seed: u64) -> Self {
+        self.cfg.shuffle_seed = Some(seed);
+        self
+    }
     /// Apply recommended, bounded defaults and wire limits from Params.
     /// These are conservative, non-tight caps to prevent runaway work in no-solution cases.
     pub fn recommended(mut self, params: &pod2::middleware::Params) -> Self {
@@ -2826,4 +2875,40 @@ mod tests {
             "expected at least one answer proving even(4)"
         );
     }
+
+    #[test]
+    fn shuffle_seed_permutes_ties_reproducibly() {
+        let scored: Vec<(i32, usize)> = vec![(1, 0), (2, 1), (1, 2), (3, 3), (2, 4), (1, 5)];
+
+        // The same seed yields the same permutation across runs
+        let mut first = scored.clone();
+        shuffle_equal_scores(&mut first, 42);
+        let mut second = scored.clone();
+        shuffle_equal_scores(&mut second, 42);
+        assert_eq!(first, second);
+
+        // Choices only move within their score class: the score at every
+        // position is unchanged, and each class keeps the same members
+        for (original, shuffled) in scored.iter().zip(first.iter()) {
+            assert_eq!(original.0, shuffled.0);
+        }
+        let mut members = first.clone();
+        members.sort();
+        let mut expected = scored.clone();
+        expected.sort();
+        assert_eq!(members, expected);
+
+        // Different seeds explore different orders for this input
+        let mut other = scored.clone();
+        shuffle_equal_scores(&mut other, 43);
+        assert_ne!(first, other);
+
+        // Without a seed configured the stable order is untouched
+        let cfg = EngineConfigBuilder::new().build();
+        assert_eq!(cfg.shuffle_seed, None);
+        assert_eq!(
+            EngineConfigBuilder::new().shuffle_seed(7).build().shuffle_seed,
+            Some(7)
+        );
+    }
 }
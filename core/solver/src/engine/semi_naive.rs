@@ -24,6 +24,7 @@ use crate::{
     planner::QueryPlan,
     proof::Proof,
     semantics::materializer::Materializer,
+    SolveEvent,
 };
 
 /// A map from variables in a rule to their concrete values for a given solution.
@@ -93,13 +94,28 @@ impl<M: MetricsSink> SemiNaiveEngine<M> {
     /// 4. Analyzing the final fact set to find a concrete solution that
     ///    satisfies the original query and constructing a proof tree.
     ///
-    /// Note: This implementation finds the *first* solution for any of the
-    /// requested predicates and constructs its proof. It does not yet handle
-    /// requests that require proving multiple top-level statements simultaneously.
+    /// Requests with several top-level statements are handled jointly: the
+    /// planner conjoins them into a single synthetic `_request_goal` rule, so
+    /// every derived `_request_goal` fact is one binding that satisfies all of
+    /// the requested statements at once, including wildcards shared between
+    /// them.
     pub fn execute(
         &mut self,
         plan: &QueryPlan,
         materializer: &Materializer,
+    ) -> Result<(FactStore, ProvenanceStore), SolverError> {
+        self.execute_with_progress(plan, materializer, None)
+    }
+
+    /// Like [`SemiNaiveEngine::execute`], but reports progress through an
+    /// optional callback so long-running solves can drive UI feedback. The
+    /// callback is consulted once per iteration, so passing `None` adds no
+    /// meaningful overhead to the plain `execute` path.
+    pub fn execute_with_progress(
+        &mut self,
+        plan: &QueryPlan,
+        materializer: &Materializer,
+        progress: Option<&mut dyn FnMut(SolveEvent)>,
     ) -> Result<(FactStore, ProvenanceStore), SolverError> {
         // 1.  Evaluate all rules (magic + guarded) together so that recursive
         //     dependencies are handled correctly.
@@ -107,7 +123,7 @@ impl<M: MetricsSink> SemiNaiveEngine<M> {
         combined_rules.extend(plan.guarded_rules.clone());
 
         let (all_facts, prov) =
-            self.evaluate_rules(&combined_rules, materializer, FactStore::new())?;
+            self.evaluate_rules(&combined_rules, materializer, FactStore::new(), progress)?;
 
         Ok((all_facts, prov))
     }
@@ -118,9 +134,12 @@ impl<M: MetricsSink> SemiNaiveEngine<M> {
         provenance: &ProvenanceStore,
         materializer: &Materializer,
     ) -> Result<Proof, SolverError> {
-        // The planner always emits a synthetic predicate `_request_goal`.  The
-        // query is proven if (and only if) at least one fact for that
-        // predicate is derived.
+        // The planner always emits a synthetic predicate `_request_goal` whose
+        // body is the conjunction of every requested statement.  The query is
+        // proven if (and only if) at least one fact for that predicate is
+        // derived, and any such fact is a single consistent binding for all of
+        // the request's wildcards — so reconstructing the proof of one fact
+        // yields a proof covering every requested statement.
 
         let request_pid = all_facts.keys().find(|pid| {
             matches!(pid,
@@ -128,7 +147,11 @@ impl<M: MetricsSink> SemiNaiveEngine<M> {
         }).cloned();
         if let Some(pid) = request_pid {
             if let Some(rel) = all_facts.get(&pid) {
-                if let Some(fact) = rel.iter().next() {
+                // `Relation` is a `HashSet`, so its iteration order varies
+                // between runs. When the request has several solutions, break
+                // the tie by a stable key so repeated solves reconstruct the
+                // same proof.
+                if let Some(fact) = rel.iter().min_by_key(|fact| format!("{:?}", fact.args)) {
                     let recon = ProofReconstructor::new(all_facts, provenance, materializer);
                     let root = recon.build(&pid, fact)?;
                     return Ok(Proof {
@@ -167,6 +190,7 @@ impl<M: MetricsSink> SemiNaiveEngine<M> {
         rules: &[Rule],
         materializer: &Materializer,
         initial_facts: FactStore,
+        mut progress: Option<&mut dyn FnMut(SolveEvent)>,
     ) -> Result<(FactStore, ProvenanceStore), SolverError> {
         let mut all_facts = initial_facts.clone();
         let mut delta_facts = initial_facts;
@@ -195,6 +219,9 @@ impl<M: MetricsSink> SemiNaiveEngine<M> {
         loop {
             iteration_count += 1;
             self.metrics.increment_iterations();
+            if let Some(on_event) = progress.as_deref_mut() {
+                on_event(SolveEvent::IterationStarted);
+            }
 
             log::debug!("=== ITERATION {iteration_count} ===");
             log::debug!(
@@ -214,6 +241,9 @@ impl<M: MetricsSink> SemiNaiveEngine<M> {
 
             let num_new_facts = new_delta.values().map(|rel| rel.len()).sum();
             self.metrics.record_delta_size(num_new_facts);
+            if let Some(on_event) = progress.as_deref_mut() {
+                on_event(SolveEvent::FactsDerived(num_new_facts));
+            }
 
             log::debug!(
                 "New delta facts: {}",
@@ -1367,6 +1397,77 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_joint_request_statements_share_bindings() {
+        let _ = env_logger::builder().is_test(true).try_init();
+        // Two pods both satisfy the first statement, but only pod2 satisfies
+        // the second; the shared wildcard P must bind to pod2 in both.
+        let pod_id1 = pod_id_from_name("pod1");
+        let pod1 = TestPod {
+            id: pod_id1,
+            statements: vec![Statement::equal(
+                AnchoredKey::from((pod_id1, "foo")),
+                Value::from(5),
+            )],
+        };
+
+        let pod_id2 = pod_id_from_name("pod2");
+        let pod2 = TestPod {
+            id: pod_id2,
+            statements: vec![Statement::equal(
+                AnchoredKey::from((pod_id2, "foo")),
+                Value::from(20),
+            )],
+        };
+
+        let pods: Vec<IndexablePod> = vec![
+            IndexablePod::TestPod(Arc::new(pod1)),
+            IndexablePod::TestPod(Arc::new(pod2)),
+        ];
+        let db = Arc::new(FactDB::build(&pods).unwrap());
+        let materializer = Materializer::new(db);
+
+        let podlog = r#"
+            REQUEST(
+                Lt(P["foo"], 100)
+                Equal(P["foo"], 20)
+            )
+        "#;
+        let params = Params::default();
+        let processed = parse(podlog, &params, &[]).unwrap();
+        let request = processed.request;
+
+        let planner = Planner::new();
+        let plan = planner.create_plan(request.templates()).unwrap();
+
+        let mut engine = SemiNaiveEngine::new(NoOpMetrics);
+        let (all_facts, provenance) = engine.execute(&plan, &materializer).unwrap();
+        let proof = engine
+            .reconstruct_proof(&all_facts, &provenance, &materializer)
+            .unwrap();
+
+        assert_eq!(proof.root_nodes.len(), 1);
+        let root = &proof.root_nodes[0];
+
+        // One proof covering both requested statements, as premises of the
+        // synthetic goal.
+        let premises = match &root.justification {
+            Justification::Custom(_, premises) => premises,
+            other => panic!("Expected Custom justification for the goal, got {other:?}"),
+        };
+        assert_eq!(premises.len(), 2, "Expected one premise per request statement");
+
+        // Both premises must refer to the same pod: pod2, the only one that
+        // satisfies the Equal statement.
+        for premise in premises {
+            let ak = match &premise.statement {
+                Statement::Lt(ValueRef::Key(ak), _) | Statement::Equal(ValueRef::Key(ak), _) => ak,
+                other => panic!("Expected Lt/Equal over an anchored key, got {other:?}"),
+            };
+            assert_eq!(ak.pod_id, pod_id2, "Shared wildcard bound inconsistently");
+        }
+    }
+
     #[test]
     fn test_execute_with_proof_reconstruction_custom_predicate() {
         let _ = env_logger::builder().is_test(true).try_init();
@@ -6,6 +6,8 @@ pub enum SolverError {
     Internal(String),
     #[error("Failed to parse datalog: {0}")]
     Parsing(String),
+    #[error("No secret key matching public key {public_key} is available in the solver context")]
+    MissingKey { public_key: String },
 }
 
 impl SolverError {
@@ -14,6 +14,53 @@ use crate::{
 
 type MissingAtom = StatementTmpl;
 
+/// A request statement whose wildcards could bind to more than one distinct
+/// EDB fact. The proof the solver picks among them is arbitrary (if stable),
+/// so the user likely wants to add constraints.
+#[derive(Debug, Clone)]
+pub struct AmbiguousStatement {
+    pub template: StatementTmpl,
+    pub match_count: usize,
+}
+
+pub struct AmbiguityFinder<'a> {
+    materializer: &'a Materializer,
+}
+
+impl<'a> AmbiguityFinder<'a> {
+    pub fn new(materializer: &'a Materializer) -> Self {
+        Self { materializer }
+    }
+
+    /// Returns every native request template that matches more than one
+    /// distinct EDB fact under empty bindings, with the number of matches.
+    ///
+    /// Templates are checked in isolation: a wildcard shared between two
+    /// templates may be constrained by their join even when each template is
+    /// ambiguous on its own, so treat the report as a hint rather than a
+    /// guarantee that the solve is underdetermined.
+    pub fn collect(&self, request: &[StatementTmpl]) -> Vec<AmbiguousStatement> {
+        request
+            .iter()
+            .filter_map(|tmpl| {
+                if !matches!(tmpl.pred, Predicate::Native(_)) {
+                    return None;
+                }
+                let facts = self
+                    .materializer
+                    .materialize_statements(tmpl.pred.clone(), tmpl.args.clone(), &HashMap::new())
+                    .unwrap_or_default();
+                let distinct: HashSet<&[ValueRef]> =
+                    facts.iter().map(|fact| fact.args.as_slice()).collect();
+                (distinct.len() > 1).then(|| AmbiguousStatement {
+                    template: tmpl.clone(),
+                    match_count: distinct.len(),
+                })
+            })
+            .collect()
+    }
+}
+
 pub struct MissingFactFinder<'a> {
     all_facts: &'a FactStore,
     materializer: &'a Materializer,
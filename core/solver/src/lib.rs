@@ -1,17 +1,29 @@
-use std::sync::Arc;
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
 
-use pod2::{backends::plonky2::primitives::ec::schnorr::SecretKey, middleware::StatementTmpl};
+use pod2::{
+    backends::plonky2::primitives::ec::schnorr::SecretKey,
+    lang::{processor::PodlangOutput, LangError},
+    middleware::{
+        CustomPredicate, CustomPredicateBatch, CustomPredicateRef, NativePredicate, Params,
+        PodId, Predicate, Statement, StatementTmpl, StatementTmplArg, TypedValue, Value, ValueRef,
+        Wildcard,
+    },
+};
 
 use crate::{
     db::{FactDB, IndexablePod},
     engine::semi_naive::SemiNaiveEngine,
     error::SolverError,
+    explainer::{AmbiguityFinder, AmbiguousStatement},
     metrics::{
         CounterMetrics, DebugMetrics, MetricsLevel, MetricsReport, MetricsSink, NoOpMetrics,
         TraceMetrics,
     },
     planner::{Planner, QueryPlan},
-    proof::Proof,
+    proof::{Justification, Proof, ProofNode},
     semantics::materializer::Materializer,
 };
 
@@ -33,11 +45,45 @@ pub mod vis;
 pub struct SolverContext<'a> {
     pods: &'a [IndexablePod],
     keys: &'a [SecretKey],
+    batches: &'a [Arc<CustomPredicateBatch>],
 }
 
 impl<'a> SolverContext<'a> {
     pub fn new(pods: &'a [IndexablePod], keys: &'a [SecretKey]) -> Self {
-        Self { pods, keys }
+        Self::with_batches(pods, keys, &[])
+    }
+
+    /// Like [`SolverContext::new`], but also supplies the custom predicate
+    /// batches referenced by the request, so callers don't have to thread
+    /// them separately into `parse`.
+    pub fn with_batches(
+        pods: &'a [IndexablePod],
+        keys: &'a [SecretKey],
+        batches: &'a [Arc<CustomPredicateBatch>],
+    ) -> Self {
+        Self {
+            pods,
+            keys,
+            batches,
+        }
+    }
+
+    /// Parse a Podlang request, resolving custom predicate references against
+    /// the batches held by this context.
+    pub fn parse(&self, input: &str, params: &Params) -> Result<PodlangOutput, LangError> {
+        pod2::lang::parse(input, params, self.batches)
+    }
+
+    /// Returns the ids of pods supplied in this context that `proof` does not
+    /// rely on. Callers can warn about (and then drop) these to keep the pod
+    /// set fed to `prove()` within the `Params` limits.
+    pub fn unused_pod_ids(&self, proof: &Proof) -> Vec<PodId> {
+        let used = proof.used_pod_ids();
+        self.pods
+            .iter()
+            .map(IndexablePod::id)
+            .filter(|id| !used.contains(id))
+            .collect()
     }
 }
 
@@ -51,61 +97,384 @@ pub fn solve(
     context: &SolverContext,
     metrics_level: MetricsLevel,
 ) -> Result<(Proof, MetricsReport), SolverError> {
-    // Common setup logic that is independent of the metrics level.
+    let wrapped_db = build_fact_db(context);
+    let materializer = Materializer::new(wrapped_db.clone());
+    solve_prepared(request, context, metrics_level, &wrapped_db, &materializer)
+}
+
+/// Like [`solve`], but solves several independent requests against the same
+/// pod set, building the `FactDB` and `Materializer` once and reusing them
+/// for every request. Each request gets its own result, so one unprovable
+/// request does not affect the others.
+pub fn solve_batch(
+    requests: &[&[StatementTmpl]],
+    context: &SolverContext,
+    metrics_level: MetricsLevel,
+) -> Vec<Result<(Proof, MetricsReport), SolverError>> {
+    let wrapped_db = build_fact_db(context);
+    let materializer = Materializer::new(wrapped_db.clone());
+    requests
+        .iter()
+        .map(|request| solve_prepared(request, context, metrics_level, &wrapped_db, &materializer))
+        .collect()
+}
+
+/// Indexes the context's pods and secret keys into a [`FactDB`]. This is the
+/// expensive, request-independent part of solver setup.
+fn build_fact_db(context: &SolverContext) -> Arc<FactDB> {
     let mut db = FactDB::build(context.pods).unwrap();
     for key in context.keys {
         db.add_keypair(key.clone());
     }
-    let wrapped_db = Arc::new(db);
-    let materializer = Materializer::new(wrapped_db.clone());
+    Arc::new(db)
+}
+
+/// Solves one request against an already-built [`FactDB`] and materializer.
+fn solve_prepared(
+    request: &[StatementTmpl],
+    context: &SolverContext,
+    metrics_level: MetricsLevel,
+    wrapped_db: &Arc<FactDB>,
+    materializer: &Materializer,
+) -> Result<(Proof, MetricsReport), SolverError> {
+    // Trivial requests are often satisfied verbatim by a single pod's public
+    // statements (e.g. re-checking a MainPod against the request it was built
+    // for). The resulting proof is just a handful of Copy operations, so skip
+    // planning and semi-naive evaluation entirely when a pod matches.
+    if let Some(proof) = try_single_pod_fast_path(request, context, wrapped_db) {
+        let report = match metrics_level {
+            MetricsLevel::None => MetricsReport::None,
+            MetricsLevel::Counters => MetricsReport::Counters(CounterMetrics::default()),
+            MetricsLevel::Debug => MetricsReport::Debug(DebugMetrics::default()),
+            MetricsLevel::Trace => MetricsReport::Trace(TraceMetrics::default()),
+        };
+        return Ok((proof, report));
+    }
+
     let planner = Planner::new();
 
     // Dispatch to the appropriate generic implementation based on the desired
     // metrics level. This allows the compiler to monomorphize the engine's
     // execution path and eliminate the overhead of metrics collection when it
     // is not needed.
-    match metrics_level {
+    let solve_result = match metrics_level {
         MetricsLevel::None => {
             let plan = planner.create_plan(request).unwrap();
-            let (proof, _) = run_solve(plan, materializer, NoOpMetrics)?;
-            Ok((proof, MetricsReport::None))
+            run_solve(plan, materializer, NoOpMetrics)
+                .map(|(proof, _)| (proof, MetricsReport::None))
         }
         MetricsLevel::Counters => {
             let plan = planner.create_plan(request).unwrap();
-            let (proof, metrics) = run_solve(plan, materializer, CounterMetrics::default())?;
-            Ok((proof, MetricsReport::Counters(metrics)))
+            run_solve(plan, materializer, CounterMetrics::default())
+                .map(|(proof, metrics)| (proof, MetricsReport::Counters(metrics)))
         }
         MetricsLevel::Debug => {
             let plan = planner.create_plan(request).unwrap();
-            let (proof, metrics) = run_solve(plan, materializer, DebugMetrics::default())?;
-            Ok((proof, MetricsReport::Debug(metrics)))
+            run_solve(plan, materializer, DebugMetrics::default())
+                .map(|(proof, metrics)| (proof, MetricsReport::Debug(metrics)))
         }
         MetricsLevel::Trace => {
             let mut metrics = TraceMetrics::default();
-            let plan = planner.create_plan_with_metrics(request, &mut metrics)?;
-            let (proof, metrics) = run_solve(plan, materializer, metrics)?;
-            Ok((proof, MetricsReport::Trace(metrics)))
+            planner
+                .create_plan_with_metrics(request, &mut metrics)
+                .and_then(|plan| run_solve(plan, materializer, metrics))
+                .map(|(proof, metrics)| (proof, MetricsReport::Trace(metrics)))
         }
+    };
+    solve_result.map_err(|err| explain_failure(err, request, wrapped_db))
+}
+
+/// Reports request statements that under-constrain their wildcard bindings.
+///
+/// A successful solve picks one grounding for each statement; when several
+/// distinct EDB facts match a template, that pick is arbitrary and the user
+/// may be surprised by which pod the proof ends up using. This runs no proof
+/// search — it only counts matching facts per template — so it is cheap to
+/// call alongside `solve` whenever the caller wants to surface the warning.
+pub fn explain_ambiguity(
+    request: &[StatementTmpl],
+    context: &SolverContext,
+) -> Vec<AmbiguousStatement> {
+    let wrapped_db = build_fact_db(context);
+    let materializer = Materializer::new(wrapped_db);
+    AmbiguityFinder::new(&materializer).collect(request)
+}
+
+/// Upgrades the engine's generic no-proof error to a specific diagnosis when
+/// one applies; other errors pass through untouched.
+fn explain_failure(err: SolverError, request: &[StatementTmpl], db: &FactDB) -> SolverError {
+    match &err {
+        SolverError::Internal(_) => diagnose_missing_key(request, db).unwrap_or(err),
+        _ => err,
     }
 }
 
+/// Explains a failed solve as [`SolverError::MissingKey`] when the request
+/// contains a `PublicKeyOf` goal over a literal public key and the context
+/// holds no matching secret key. Only wildcard secret-key arguments are
+/// considered: a bound secret key comes from the facts themselves, so its
+/// absence from the context proves nothing.
+fn diagnose_missing_key(request: &[StatementTmpl], db: &FactDB) -> Option<SolverError> {
+    request.iter().find_map(|tmpl| {
+        if tmpl.pred != Predicate::Native(NativePredicate::PublicKeyOf) {
+            return None;
+        }
+        let [StatementTmplArg::Literal(pk_val), StatementTmplArg::Wildcard(_)] =
+            tmpl.args.as_slice()
+        else {
+            return None;
+        };
+        let TypedValue::PublicKey(pk) = pk_val.typed() else {
+            return None;
+        };
+        if db.keypairs_iter().any(|sk| sk.public_key() == *pk) {
+            return None;
+        }
+        Some(SolverError::MissingKey {
+            public_key: pk.to_string(),
+        })
+    })
+}
+
 /// The private, generic worker function for the solver.
 ///
 /// This function is monomorphized by the compiler for each concrete `MetricsSink`
 /// type, allowing for zero-cost static dispatch of metrics collection.
 fn run_solve<M: MetricsSink>(
     plan: QueryPlan,
-    materializer: Materializer,
+    materializer: &Materializer,
     metrics: M,
 ) -> Result<(Proof, M), SolverError> {
     let mut engine = SemiNaiveEngine::new(metrics);
 
-    let (all_facts, provenance) = engine.execute(&plan, &materializer)?;
-    let proof = engine.reconstruct_proof(&all_facts, &provenance, &materializer)?;
+    let (all_facts, provenance) = engine.execute(&plan, materializer)?;
+    let proof = engine.reconstruct_proof(&all_facts, &provenance, materializer)?;
 
     Ok((proof, engine.into_metrics()))
 }
 
+/// Attempts to satisfy the entire request from the public statements of one
+/// pod, without planning or running the engine.
+///
+/// The matching is deliberately conservative: every template must unify with a
+/// statement asserted verbatim by the same pod, under a consistent set of
+/// wildcard bindings. Anything that needs derivation — value comparisons,
+/// new entries, statements split across pods — fails the match and falls
+/// through to the full solver.
+fn try_single_pod_fast_path(
+    request: &[StatementTmpl],
+    context: &SolverContext,
+    db: &Arc<FactDB>,
+) -> Option<Proof> {
+    if request.is_empty() {
+        return None;
+    }
+    for pod in context.pods {
+        let statements = pod.pub_statements();
+        let mut bindings = HashMap::new();
+        if let Some(matched) = match_all_templates(request, &statements, &mut bindings)
+            && let Some(proof) = build_copy_proof(request, &matched, &bindings, db.clone())
+        {
+            return Some(proof);
+        }
+    }
+    None
+}
+
+/// Matches every template against some statement of a single pod, threading
+/// wildcard bindings through a backtracking search. Returns the matched
+/// statements in template order.
+fn match_all_templates(
+    templates: &[StatementTmpl],
+    statements: &[Statement],
+    bindings: &mut HashMap<Wildcard, Value>,
+) -> Option<Vec<Statement>> {
+    let Some((tmpl, rest)) = templates.split_first() else {
+        return Some(Vec::new());
+    };
+    for statement in statements {
+        let saved = bindings.clone();
+        if match_template(tmpl, statement, bindings) {
+            if let Some(mut matched) = match_all_templates(rest, statements, bindings) {
+                matched.insert(0, statement.clone());
+                return Some(matched);
+            }
+        }
+        *bindings = saved;
+    }
+    None
+}
+
+fn match_template(
+    tmpl: &StatementTmpl,
+    statement: &Statement,
+    bindings: &mut HashMap<Wildcard, Value>,
+) -> bool {
+    if tmpl.pred != statement.predicate() {
+        return false;
+    }
+    // Custom statements carry plain values rather than value references.
+    if let Statement::Custom(_, values) = statement {
+        return tmpl.args.len() == values.len()
+            && tmpl
+                .args
+                .iter()
+                .zip(values)
+                .all(|(arg, value)| match arg {
+                    StatementTmplArg::Literal(v) => v == value,
+                    StatementTmplArg::Wildcard(w) => bind(bindings, w, value.clone()),
+                    _ => false,
+                });
+    }
+    let Ok(value_refs) = statement
+        .args()
+        .iter()
+        .map(ValueRef::try_from)
+        .collect::<Result<Vec<_>, _>>()
+    else {
+        return false;
+    };
+    tmpl.args.len() == value_refs.len()
+        && tmpl
+            .args
+            .iter()
+            .zip(&value_refs)
+            .all(|(arg, value_ref)| match (arg, value_ref) {
+                (StatementTmplArg::Literal(v), ValueRef::Literal(sv)) => v == sv,
+                (StatementTmplArg::Wildcard(w), ValueRef::Literal(sv)) => {
+                    bind(bindings, w, sv.clone())
+                }
+                (StatementTmplArg::AnchoredKey(pod_wc, key), ValueRef::Key(ak)) => {
+                    *key == ak.key && bind(bindings, pod_wc, Value::from(ak.pod_id.0))
+                }
+                _ => false,
+            })
+}
+
+/// Binds `wildcard` to `value`, or checks consistency if it is already bound.
+fn bind(bindings: &mut HashMap<Wildcard, Value>, wildcard: &Wildcard, value: Value) -> bool {
+    match bindings.get(wildcard) {
+        Some(bound) => *bound == value,
+        None => {
+            bindings.insert(wildcard.clone(), value);
+            true
+        }
+    }
+}
+
+/// Builds a proof whose premises are the matched statements, each justified as
+/// a known fact so `to_operations` emits a Copy for it. The root mirrors the
+/// planner's synthetic `_request_goal` node so proof consumers treat the
+/// copied statements as the request's public premises.
+fn build_copy_proof(
+    request: &[StatementTmpl],
+    matched: &[Statement],
+    bindings: &HashMap<Wildcard, Value>,
+    db: Arc<FactDB>,
+) -> Option<Proof> {
+    let premises: Vec<Arc<ProofNode>> = matched
+        .iter()
+        .map(|statement| {
+            Arc::new(ProofNode {
+                statement: statement.clone(),
+                justification: Justification::Fact,
+            })
+        })
+        .collect();
+
+    let mut head_wildcards: Vec<Wildcard> = request
+        .iter()
+        .flat_map(|tmpl| tmpl.args.iter())
+        .filter_map(|arg| match arg {
+            StatementTmplArg::Wildcard(w) => Some(w.clone()),
+            StatementTmplArg::AnchoredKey(pod_wc, _) => Some(pod_wc.clone()),
+            _ => None,
+        })
+        .collect::<HashSet<_>>()
+        .into_iter()
+        .collect();
+    head_wildcards.sort_by_key(|w| w.index);
+    let head_values: Vec<Value> = head_wildcards
+        .iter()
+        .map(|w| bindings.get(w).cloned())
+        .collect::<Option<_>>()?;
+
+    let params = Params {
+        max_custom_predicate_arity: 20,
+        ..Params::default()
+    };
+    let goal = CustomPredicate::and(
+        &params,
+        "_request_goal".to_string(),
+        request.to_vec(),
+        head_wildcards.len(),
+        head_wildcards.iter().map(|w| w.name.clone()).collect(),
+    )
+    .ok()?;
+    let batch = CustomPredicateBatch::new(&params, "SyntheticRequestBatch".to_string(), vec![goal]);
+    let goal_ref = CustomPredicateRef::new(batch, 0);
+
+    let root = Arc::new(ProofNode {
+        statement: Statement::Custom(goal_ref.clone(), head_values),
+        justification: Justification::Custom(goal_ref, premises),
+    });
+    Some(Proof {
+        root_nodes: vec![root],
+        db,
+    })
+}
+
+/// Progress notifications emitted during a solve, so long-running proof
+/// searches can drive UI feedback instead of appearing stalled.
+#[derive(Debug, Clone)]
+pub enum SolveEvent {
+    /// A new semi-naive evaluation iteration has begun.
+    IterationStarted,
+    /// The number of new facts derived by the iteration that just finished.
+    FactsDerived(usize),
+    /// A requested statement has been proven.
+    GoalSatisfied(Statement),
+}
+
+/// Like [`solve`], but reports progress through `on_event` as the engine runs.
+///
+/// Events are emitted once per evaluation iteration, so the callback adds no
+/// meaningful overhead to the proof search itself. Metrics collection is
+/// disabled; callers that want both metrics and progress should prefer the
+/// tracing entry points.
+pub fn solve_with_progress(
+    request: &[StatementTmpl],
+    context: &SolverContext,
+    mut on_event: impl FnMut(SolveEvent),
+) -> Result<Proof, SolverError> {
+    let wrapped_db = build_fact_db(context);
+    let materializer = Materializer::new(wrapped_db.clone());
+    let planner = Planner::new();
+    let plan = planner.create_plan(request).unwrap();
+
+    let mut engine = SemiNaiveEngine::new(NoOpMetrics);
+    let proof_result =
+        match engine.execute_with_progress(&plan, &materializer, Some(&mut on_event)) {
+            Ok((all_facts, provenance)) => {
+                engine.reconstruct_proof(&all_facts, &provenance, &materializer)
+            }
+            Err(err) => Err(err),
+        };
+    let proof = proof_result.map_err(|err| explain_failure(err, request, &wrapped_db))?;
+
+    // The root node proves the synthetic `_request_goal`; its direct premises
+    // are the statements the caller actually requested.
+    for root in &proof.root_nodes {
+        if let Justification::Custom(_, premises) = &root.justification {
+            for premise in premises {
+                on_event(SolveEvent::GoalSatisfied(premise.statement.clone()));
+            }
+        }
+    }
+
+    Ok(proof)
+}
+
 /// Solve with custom trace configuration.
 pub fn solve_with_tracing(
     request: &[StatementTmpl],
@@ -120,7 +489,7 @@ pub fn solve_with_tracing(
     // Use TraceMetrics with the custom configuration
     let mut metrics = TraceMetrics::new(trace_config);
     let plan = planner.create_plan_with_metrics(request, &mut metrics)?;
-    let (proof, metrics) = run_solve(plan, materializer, metrics)?;
+    let (proof, metrics) = run_solve(plan, &materializer, metrics)?;
     Ok((proof, MetricsReport::Trace(metrics)))
 }
 
@@ -139,10 +508,11 @@ mod tests {
         },
         frontend::{MainPodBuilder, OperationArg},
         lang::parse,
-        middleware::{containers::Set, NativeOperation, OperationType, Params, Value},
+        middleware::{containers::Set, NativeOperation, OperationType, Params, Value, ValueRef},
     };
 
     use super::*;
+    use crate::proof::ProofNode;
 
     #[test]
     fn test_ethdos() {
@@ -180,10 +550,7 @@ mod tests {
             .unwrap()
             .request;
 
-        let context = SolverContext {
-            pods: &[IndexablePod::signed_pod(&alice_attestation)],
-            keys: &[],
-        };
+        let context = SolverContext::new(&[IndexablePod::signed_pod(&alice_attestation)], &[]);
 
         let (result, _metrics) =
             solve(request.templates(), &context, MetricsLevel::Counters).unwrap();
@@ -227,13 +594,13 @@ mod tests {
             .unwrap()
             .request;
 
-        let context = SolverContext {
-            pods: &[
+        let context = SolverContext::new(
+            &[
                 IndexablePod::main_pod(&alice_bob_pod),
                 IndexablePod::signed_pod(&bob_attestation),
             ],
-            keys: &[],
-        };
+            &[],
+        );
         let (result, _metrics) =
             solve(request.templates(), &context, MetricsLevel::Counters).unwrap();
 
@@ -263,123 +630,690 @@ mod tests {
     }
 
     #[test]
-    fn test_zukyc() {
+    fn test_estimate_matches_ethdos_counts() {
         let _ = env_logger::builder().is_test(true).try_init();
-        let params = Params::default();
-
-        let const_18y = ZU_KYC_NOW_MINUS_18Y;
-        let const_1y = ZU_KYC_NOW_MINUS_1Y;
-        let sanctions_values: HashSet<Value> = ZU_KYC_SANCTION_LIST
-            .iter()
-            .map(|s| Value::from(*s))
-            .collect();
-        let sanction_set =
-            Value::from(Set::new(params.max_depth_mt_containers, sanctions_values).unwrap());
+        let params = Params {
+            max_input_pods_public_statements: 8,
+            max_statements: 24,
+            max_public_statements: 8,
+            ..Default::default()
+        };
 
-        let (gov_id, pay_stub) = zu_kyc_sign_pod_builders(&params);
-        let signer = Signer(SecretKey::new_rand());
-        let gov_id = gov_id.sign(&signer).unwrap();
+        let alice = Signer(SecretKey::new_rand());
+        let bob = Signer(SecretKey::new_rand());
 
-        let signer = Signer(SecretKey::new_rand());
-        let pay_stub = pay_stub.sign(&signer).unwrap();
+        let alice_attestation = attest_eth_friend(&params, &alice, bob.public_key());
+        let batch = eth_dos_batch(&params).unwrap();
 
-        let zukyc_request = format!(
+        let req = format!(
             r#"
-        REQUEST(
-            NotContains({sanction_set}, gov["idNumber"])
-            Lt(gov["dateOfBirth"], {const_18y})
-            Equal(pay["startDate"], {const_1y})
-            Equal(gov["socialSecurityNumber"], pay["socialSecurityNumber"])
-            Equal(self["watermark"], 0)
-        )
-        "#
-        );
+      use _, _, _, eth_dos from 0x{}
 
-        let request = parse(&zukyc_request, &params, &[]).unwrap().request;
+      REQUEST(
+          eth_dos({}, {}, Distance)
+      )
+      "#,
+            batch.id().encode_hex::<String>(),
+            alice.public_key(),
+            bob.public_key()
+        );
+        let request = parse(&req, &params, std::slice::from_ref(&batch))
+            .unwrap()
+            .request;
 
-        let pods = [
-            IndexablePod::signed_pod(&gov_id),
-            IndexablePod::signed_pod(&pay_stub),
-        ];
+        let context = SolverContext::new(&[IndexablePod::signed_pod(&alice_attestation)], &[]);
+        let (result, _metrics) =
+            solve(request.templates(), &context, MetricsLevel::Counters).unwrap();
 
-        let context = SolverContext {
-            pods: &pods,
-            keys: &[],
-        };
+        // The estimate reports exactly what to_inputs hands to the builder
+        let estimate = result.estimate();
+        let (pod_ids, ops) = result.to_inputs();
+        assert_eq!(estimate.num_operations, ops.len());
+        assert_eq!(estimate.num_input_pods, pod_ids.len());
+        assert_eq!(
+            estimate.num_public_statements,
+            ops.iter().filter(|(_, public)| *public).count()
+        );
+        assert_eq!(
+            estimate.num_private_statements,
+            estimate.num_operations - estimate.num_public_statements
+        );
+        assert_eq!(
+            estimate.num_public_statements,
+            result.public_statements().len()
+        );
 
-        let (result, _) = solve(request.templates(), &context, MetricsLevel::Counters).unwrap();
+        // The proof fits the limits the estimate was meant to be checked
+        // against (+1 public statement for the builder's own _type entry),
+        // consistent with prove() succeeding below
+        assert!(estimate.num_public_statements + 1 <= params.max_public_statements);
+        assert!(estimate.num_operations + 1 <= params.max_statements);
 
         let prover = MockProver {};
         #[allow(clippy::borrow_interior_mutable_const)]
         let mut builder = MainPodBuilder::new(&params, &MOCK_VD_SET);
-
-        let (pod_ids, ops) = result.to_inputs();
-
         for (op, public) in ops {
             if public {
-                println!("public op: {op:?}");
                 builder.pub_op(op).unwrap();
             } else {
                 builder.priv_op(op).unwrap();
             }
         }
+        builder.add_signed_pod(&alice_attestation);
+        let pod = builder.prove(&prover).unwrap();
+        assert_eq!(
+            pod.pod.pub_statements().len(),
+            estimate.num_public_statements + 1
+        );
+    }
 
-        for pod_id in pod_ids {
-            let pod = pods.iter().find(|p| p.id() == pod_id).unwrap();
-            if let IndexablePod::SignedPod(pod) = pod {
-                builder.add_signed_pod(pod);
+    #[test]
+    fn test_ethdos_batches_via_context() {
+        let _ = env_logger::builder().is_test(true).try_init();
+        let params = Params {
+            max_input_pods_public_statements: 8,
+            max_statements: 24,
+            max_public_statements: 8,
+            ..Default::default()
+        };
+
+        let alice = Signer(SecretKey::new_rand());
+        let bob = Signer(SecretKey::new_rand());
+
+        let alice_attestation = attest_eth_friend(&params, &alice, bob.public_key());
+        let batch = eth_dos_batch(&params).unwrap();
+
+        let req = format!(
+            r#"
+      use _, _, _, eth_dos from 0x{}
+
+      REQUEST(
+          eth_dos({}, {}, Distance)
+      )
+      "#,
+            batch.id().encode_hex::<String>(),
+            alice.public_key(),
+            bob.public_key()
+        );
+
+        // The batch is supplied once via the context and resolved by `parse`.
+        let pods = [IndexablePod::signed_pod(&alice_attestation)];
+        let batches = [batch];
+        let context = SolverContext::with_batches(&pods, &[], &batches);
+        let request = context.parse(&req, &params).unwrap().request;
+
+        let (result, _metrics) =
+            solve(request.templates(), &context, MetricsLevel::Counters).unwrap();
+
+        let prover = MockProver {};
+        #[allow(clippy::borrow_interior_mutable_const)]
+        let mut builder = MainPodBuilder::new(&params, &MOCK_VD_SET);
+
+        let (_pod_ids, ops) = result.to_inputs();
+
+        for (op, public) in ops {
+            if public {
+                builder.pub_op(op).unwrap();
             } else {
-                panic!("Expected signed pod, got {pod:?}");
+                builder.priv_op(op).unwrap();
             }
         }
 
-        let kyc = builder.prove(&prover).unwrap();
-
-        println!("{kyc}");
-    }
-
-    #[test]
-    fn test_public_key_of() {
-        let params = Params::default();
-        let sk = SecretKey::new_rand();
-        let pk = sk.public_key();
-        let request = parse(
-            &format!("REQUEST(PublicKeyOf({}, b))", Value::from(pk)),
-            &params,
-            &[],
-        )
-        .unwrap();
-        let request = request.request;
-        let context = SolverContext::new(&[], &[]);
-        let solve_result = solve(request.templates(), &context, MetricsLevel::Counters);
-        assert!(solve_result.is_err());
+        builder.add_signed_pod(&alice_attestation);
 
-        let sks = vec![sk.clone()];
-        let context = SolverContext::new(&[], &sks);
-        let solve_result = solve(request.templates(), &context, MetricsLevel::Counters);
-        assert!(solve_result.is_ok());
-        let (proof, _) = solve_result.unwrap();
-        let (pod_ids, ops) = proof.to_inputs();
-        assert_eq!(pod_ids.len(), 0);
-        assert_eq!(ops.len(), 1);
-        assert!(matches!(
-            ops[0].0 .0,
-            OperationType::Native(NativeOperation::PublicKeyOf)
-        ));
-        assert!(matches!(
-            ops[0].0.1.as_slice(),
-            [
-                OperationArg::Literal(pk_val),
-                OperationArg::Literal(sk_val)
-            ] if pk_val == &Value::from(pk) && sk_val == &Value::from(sk.clone())
-        ));
+        let alice_bob_pod = builder.prove(&prover).unwrap();
+        let bindings = request.exact_match_pod(&*alice_bob_pod.pod).unwrap();
+        assert_eq!(bindings.get("Distance").unwrap(), &Value::from(1));
     }
 
     #[test]
-    fn test_repeated_statements() {
+    fn test_single_pod_fast_path() {
         let _ = env_logger::builder().is_test(true).try_init();
-        let params = Params::default();
-        let sk = SecretKey::new_rand();
+        let params = Params {
+            max_input_pods_public_statements: 8,
+            max_statements: 24,
+            max_public_statements: 8,
+            ..Default::default()
+        };
+
+        let alice = Signer(SecretKey::new_rand());
+        let bob = Signer(SecretKey::new_rand());
+
+        let alice_attestation = attest_eth_friend(&params, &alice, bob.public_key());
+        let batch = eth_dos_batch(&params).unwrap();
+
+        let req = format!(
+            r#"
+      use _, _, _, eth_dos from 0x{}
+
+      REQUEST(
+          eth_dos({}, {}, Distance)
+      )
+      "#,
+            batch.id().encode_hex::<String>(),
+            alice.public_key(),
+            bob.public_key()
+        );
+
+        let request = parse(&req, &params, std::slice::from_ref(&batch))
+            .unwrap()
+            .request;
+
+        // Solving against the raw attestation needs the engine.
+        let pods = [IndexablePod::signed_pod(&alice_attestation)];
+        let context = SolverContext::new(&pods, &[]);
+        let (result, metrics) =
+            solve(request.templates(), &context, MetricsLevel::Counters).unwrap();
+        let MetricsReport::Counters(counters) = metrics else {
+            panic!("Expected counter metrics");
+        };
+        assert!(counters.fixpoint_iterations > 0);
+
+        let prover = MockProver {};
+        #[allow(clippy::borrow_interior_mutable_const)]
+        let mut builder = MainPodBuilder::new(&params, &MOCK_VD_SET);
+        let (_pod_ids, ops) = result.to_inputs();
+        for (op, public) in ops {
+            if public {
+                builder.pub_op(op).unwrap();
+            } else {
+                builder.priv_op(op).unwrap();
+            }
+        }
+        builder.add_signed_pod(&alice_attestation);
+        let alice_bob_pod = builder.prove(&prover).unwrap();
+
+        // Re-solving the same request against the proven pod is a direct match
+        // of its public statements, so the fast path answers with a pure-Copy
+        // proof and the engine never runs.
+        let pods = [IndexablePod::main_pod(&alice_bob_pod)];
+        let context = SolverContext::new(&pods, &[]);
+        let (proof, metrics) =
+            solve(request.templates(), &context, MetricsLevel::Counters).unwrap();
+        let MetricsReport::Counters(counters) = metrics else {
+            panic!("Expected counter metrics");
+        };
+        assert_eq!(counters.fixpoint_iterations, 0);
+
+        let (pod_ids, ops) = proof.to_inputs();
+        assert_eq!(pod_ids, vec![alice_bob_pod.id()]);
+        assert!(ops.iter().all(|(op, public)| *public
+            && matches!(op.0, OperationType::Native(NativeOperation::CopyStatement))));
+
+        // The copied statements still assemble into a valid recursive MainPod.
+        #[allow(clippy::borrow_interior_mutable_const)]
+        let mut builder = MainPodBuilder::new(&params, &MOCK_VD_SET);
+        for (op, public) in ops {
+            if public {
+                builder.pub_op(op).unwrap();
+            } else {
+                builder.priv_op(op).unwrap();
+            }
+        }
+        builder.add_recursive_pod(alice_bob_pod);
+        let copied_pod = builder.prove(&prover).unwrap();
+        let bindings = request.exact_match_pod(&*copied_pod.pod).unwrap();
+        assert_eq!(bindings.get("Distance").unwrap(), &Value::from(1));
+    }
+
+    #[test]
+    fn test_zukyc() {
+        let _ = env_logger::builder().is_test(true).try_init();
+        let params = Params::default();
+
+        let const_18y = ZU_KYC_NOW_MINUS_18Y;
+        let const_1y = ZU_KYC_NOW_MINUS_1Y;
+        let sanctions_values: HashSet<Value> = ZU_KYC_SANCTION_LIST
+            .iter()
+            .map(|s| Value::from(*s))
+            .collect();
+        let sanction_set =
+            Value::from(Set::new(params.max_depth_mt_containers, sanctions_values).unwrap());
+
+        let (gov_id, pay_stub) = zu_kyc_sign_pod_builders(&params);
+        let signer = Signer(SecretKey::new_rand());
+        let gov_id = gov_id.sign(&signer).unwrap();
+
+        let signer = Signer(SecretKey::new_rand());
+        let pay_stub = pay_stub.sign(&signer).unwrap();
+
+        let zukyc_request = format!(
+            r#"
+        REQUEST(
+            NotContains({sanction_set}, gov["idNumber"])
+            Lt(gov["dateOfBirth"], {const_18y})
+            Equal(pay["startDate"], {const_1y})
+            Equal(gov["socialSecurityNumber"], pay["socialSecurityNumber"])
+            Equal(self["watermark"], 0)
+        )
+        "#
+        );
+
+        let request = parse(&zukyc_request, &params, &[]).unwrap().request;
+
+        let pods = [
+            IndexablePod::signed_pod(&gov_id),
+            IndexablePod::signed_pod(&pay_stub),
+        ];
+
+        let context = SolverContext::new(&pods, &[]);
+
+        let (result, _) = solve(request.templates(), &context, MetricsLevel::Counters).unwrap();
+
+        let prover = MockProver {};
+        #[allow(clippy::borrow_interior_mutable_const)]
+        let mut builder = MainPodBuilder::new(&params, &MOCK_VD_SET);
+
+        let (pod_ids, ops) = result.to_inputs();
+
+        for (op, public) in ops {
+            if public {
+                println!("public op: {op:?}");
+                builder.pub_op(op).unwrap();
+            } else {
+                builder.priv_op(op).unwrap();
+            }
+        }
+
+        for pod_id in pod_ids {
+            let pod = pods.iter().find(|p| p.id() == pod_id).unwrap();
+            if let IndexablePod::SignedPod(pod) = pod {
+                builder.add_signed_pod(pod);
+            } else {
+                panic!("Expected signed pod, got {pod:?}");
+            }
+        }
+
+        let kyc = builder.prove(&prover).unwrap();
+
+        println!("{kyc}");
+    }
+
+    #[test]
+    fn test_zukyc_unused_pods_are_reported() {
+        use pod2::frontend::SignedDictBuilder;
+
+        let _ = env_logger::builder().is_test(true).try_init();
+        let params = Params::default();
+
+        let const_18y = ZU_KYC_NOW_MINUS_18Y;
+        let const_1y = ZU_KYC_NOW_MINUS_1Y;
+        let sanctions_values: HashSet<Value> = ZU_KYC_SANCTION_LIST
+            .iter()
+            .map(|s| Value::from(*s))
+            .collect();
+        let sanction_set =
+            Value::from(Set::new(params.max_depth_mt_containers, sanctions_values).unwrap());
+
+        let (gov_id, pay_stub) = zu_kyc_sign_pod_builders(&params);
+        let signer = Signer(SecretKey::new_rand());
+        let gov_id = gov_id.sign(&signer).unwrap();
+
+        let signer = Signer(SecretKey::new_rand());
+        let pay_stub = pay_stub.sign(&signer).unwrap();
+
+        // A pod that satisfies nothing in the request
+        let mut bystander_builder = SignedDictBuilder::new(&params);
+        bystander_builder.insert("kind", "bystander");
+        let bystander = bystander_builder
+            .sign(&Signer(SecretKey::new_rand()))
+            .unwrap();
+
+        let zukyc_request = format!(
+            r#"
+        REQUEST(
+            NotContains({sanction_set}, gov["idNumber"])
+            Lt(gov["dateOfBirth"], {const_18y})
+            Equal(pay["startDate"], {const_1y})
+            Equal(gov["socialSecurityNumber"], pay["socialSecurityNumber"])
+        )
+        "#
+        );
+
+        let request = parse(&zukyc_request, &params, &[]).unwrap().request;
+
+        let pods = [
+            IndexablePod::signed_pod(&gov_id),
+            IndexablePod::signed_pod(&pay_stub),
+            IndexablePod::signed_pod(&bystander),
+        ];
+
+        let context = SolverContext::new(&pods, &[]);
+
+        let (proof, _) = solve(request.templates(), &context, MetricsLevel::Counters).unwrap();
+
+        let used = proof.used_pod_ids();
+        assert!(used.contains(&gov_id.id()));
+        assert!(used.contains(&pay_stub.id()));
+
+        assert_eq!(context.unused_pod_ids(&proof), vec![bystander.id()]);
+    }
+
+    #[test]
+    fn test_proof_choice_is_deterministic_across_solves() {
+        use pod2::frontend::SignedDictBuilder;
+
+        let _ = env_logger::builder().is_test(true).try_init();
+        let params = Params::default();
+
+        // Three pods satisfy the request equally well, so the engine has a
+        // genuine tie to break
+        let make_pod = |name: &str| {
+            let mut builder = SignedDictBuilder::new(&params);
+            builder.insert("kind", "player");
+            builder.insert("name", name);
+            builder.sign(&Signer(SecretKey::new_rand())).unwrap()
+        };
+        let pod_a = make_pod("alice");
+        let pod_b = make_pod("bob");
+        let pod_c = make_pod("carol");
+
+        let request = parse(
+            r#"
+        REQUEST(
+            Equal(P["kind"], "player")
+        )
+        "#,
+            &params,
+            &[],
+        )
+        .unwrap()
+        .request;
+
+        let pods = [
+            IndexablePod::signed_pod(&pod_a),
+            IndexablePod::signed_pod(&pod_b),
+            IndexablePod::signed_pod(&pod_c),
+        ];
+
+        // Each solve builds its own hash-based fact store, so without stable
+        // tie-breaking the chosen proof would differ between runs
+        let solve_once = || {
+            let context = SolverContext::new(&pods, &[]);
+            let (proof, _) =
+                solve(request.templates(), &context, MetricsLevel::Counters).unwrap();
+            let (pod_ids, ops) = proof.to_inputs();
+            (pod_ids, format!("{ops:?}"))
+        };
+
+        let first = solve_once();
+        assert_eq!(first.0.len(), 1);
+        for _ in 0..5 {
+            assert_eq!(solve_once(), first);
+        }
+    }
+
+    #[test]
+    fn test_not_contains_from_literal_set() {
+        let _ = env_logger::builder().is_test(true).try_init();
+        let params = Params::default();
+
+        // The set is inlined into the request as a literal; no pod asserts
+        // anything about it
+        let request = parse(
+            r#"
+        REQUEST(
+            NotContains(#["G111111", "G222222"], "G333333")
+        )
+        "#,
+            &params,
+            &[],
+        )
+        .unwrap()
+        .request;
+
+        let context = SolverContext::new(&[], &[]);
+        let (result, _) = solve(request.templates(), &context, MetricsLevel::Counters).unwrap();
+
+        // The statement derives from the literals alone, with no input pods
+        let (pod_ids, ops) = result.to_inputs();
+        assert!(pod_ids.is_empty());
+
+        let expected_set = Value::from(
+            Set::new(
+                params.max_depth_mt_containers,
+                ["G111111", "G222222"].iter().map(|s| Value::from(*s)).collect(),
+            )
+            .unwrap(),
+        );
+
+        let not_contains: Vec<_> = ops
+            .iter()
+            .filter(|(op, _)| {
+                matches!(
+                    op.0,
+                    OperationType::Native(NativeOperation::NotContainsFromEntries)
+                )
+            })
+            .collect();
+        assert_eq!(not_contains.len(), 1);
+        let (op, public) = not_contains[0];
+        assert!(*public);
+        match (&op.1[0], &op.1[1]) {
+            (OperationArg::Literal(set), OperationArg::Literal(value)) => {
+                assert_eq!(set, &expected_set);
+                assert_eq!(value, &Value::from("G333333"));
+            }
+            other => panic!("expected literal operation args, got {other:?}"),
+        }
+
+        // The ops assemble into a MainPod without any input pods
+        let prover = MockProver {};
+        #[allow(clippy::borrow_interior_mutable_const)]
+        let mut builder = MainPodBuilder::new(&params, &MOCK_VD_SET);
+        for (op, public) in ops {
+            if public {
+                builder.pub_op(op).unwrap();
+            } else {
+                builder.priv_op(op).unwrap();
+            }
+        }
+        let pod = builder.prove(&prover).unwrap();
+        request.exact_match_pod(&*pod.pod).unwrap();
+    }
+
+    #[test]
+    fn test_public_statements_match_mainpod() {
+        let _ = env_logger::builder().is_test(true).try_init();
+        let params = Params::default();
+
+        let const_18y = ZU_KYC_NOW_MINUS_18Y;
+        let const_1y = ZU_KYC_NOW_MINUS_1Y;
+        let sanctions_values: HashSet<Value> = ZU_KYC_SANCTION_LIST
+            .iter()
+            .map(|s| Value::from(*s))
+            .collect();
+        let sanction_set =
+            Value::from(Set::new(params.max_depth_mt_containers, sanctions_values).unwrap());
+
+        let (gov_id, pay_stub) = zu_kyc_sign_pod_builders(&params);
+        let signer = Signer(SecretKey::new_rand());
+        let gov_id = gov_id.sign(&signer).unwrap();
+
+        let signer = Signer(SecretKey::new_rand());
+        let pay_stub = pay_stub.sign(&signer).unwrap();
+
+        let zukyc_request = format!(
+            r#"
+        REQUEST(
+            NotContains({sanction_set}, gov["idNumber"])
+            Lt(gov["dateOfBirth"], {const_18y})
+            Equal(pay["startDate"], {const_1y})
+            Equal(gov["socialSecurityNumber"], pay["socialSecurityNumber"])
+            Equal(self["watermark"], 0)
+        )
+        "#
+        );
+
+        let request = parse(&zukyc_request, &params, &[]).unwrap().request;
+
+        let pods = [
+            IndexablePod::signed_pod(&gov_id),
+            IndexablePod::signed_pod(&pay_stub),
+        ];
+        let context = SolverContext::new(&pods, &[]);
+
+        let (result, _) = solve(request.templates(), &context, MetricsLevel::Counters).unwrap();
+
+        // One public statement per requested statement, available before proving
+        let public_statements = result.public_statements();
+        assert_eq!(public_statements.len(), request.templates().len());
+
+        let prover = MockProver {};
+        #[allow(clippy::borrow_interior_mutable_const)]
+        let mut builder = MainPodBuilder::new(&params, &MOCK_VD_SET);
+
+        let (pod_ids, ops) = result.to_inputs();
+        for (op, public) in ops {
+            if public {
+                builder.pub_op(op).unwrap();
+            } else {
+                builder.priv_op(op).unwrap();
+            }
+        }
+        for pod_id in pod_ids {
+            let pod = pods.iter().find(|p| p.id() == pod_id).unwrap();
+            if let IndexablePod::SignedPod(pod) = pod {
+                builder.add_signed_pod(pod);
+            } else {
+                panic!("Expected signed pod, got {pod:?}");
+            }
+        }
+        let kyc = builder.prove(&prover).unwrap();
+
+        // The prediction matches the built pod exactly, modulo the _type
+        // statement the builder adds on its own.
+        for statement in &public_statements {
+            assert!(
+                kyc.public_statements.contains(statement),
+                "predicted public statement missing from pod: {statement}"
+            );
+        }
+        assert_eq!(kyc.public_statements.len(), public_statements.len() + 1);
+    }
+
+    #[test]
+    fn test_zukyc_progress_events() {
+        let _ = env_logger::builder().is_test(true).try_init();
+        let params = Params::default();
+
+        let const_18y = ZU_KYC_NOW_MINUS_18Y;
+        let const_1y = ZU_KYC_NOW_MINUS_1Y;
+        let sanctions_values: HashSet<Value> = ZU_KYC_SANCTION_LIST
+            .iter()
+            .map(|s| Value::from(*s))
+            .collect();
+        let sanction_set =
+            Value::from(Set::new(params.max_depth_mt_containers, sanctions_values).unwrap());
+
+        let (gov_id, pay_stub) = zu_kyc_sign_pod_builders(&params);
+        let signer = Signer(SecretKey::new_rand());
+        let gov_id = gov_id.sign(&signer).unwrap();
+
+        let signer = Signer(SecretKey::new_rand());
+        let pay_stub = pay_stub.sign(&signer).unwrap();
+
+        let zukyc_request = format!(
+            r#"
+        REQUEST(
+            NotContains({sanction_set}, gov["idNumber"])
+            Lt(gov["dateOfBirth"], {const_18y})
+            Equal(pay["startDate"], {const_1y})
+            Equal(gov["socialSecurityNumber"], pay["socialSecurityNumber"])
+            Equal(self["watermark"], 0)
+        )
+        "#
+        );
+
+        let request = parse(&zukyc_request, &params, &[]).unwrap().request;
+
+        let pods = [
+            IndexablePod::signed_pod(&gov_id),
+            IndexablePod::signed_pod(&pay_stub),
+        ];
+        let context = SolverContext::new(&pods, &[]);
+
+        let mut events = Vec::new();
+        let proof = solve_with_progress(request.templates(), &context, |event| {
+            events.push(event);
+        })
+        .unwrap();
+        assert!(!proof.root_nodes.is_empty());
+
+        let iterations = events
+            .iter()
+            .filter(|e| matches!(e, SolveEvent::IterationStarted))
+            .count();
+        let deltas = events
+            .iter()
+            .filter(|e| matches!(e, SolveEvent::FactsDerived(_)))
+            .count();
+        let goals = events
+            .iter()
+            .filter(|e| matches!(e, SolveEvent::GoalSatisfied(_)))
+            .count();
+
+        assert!(iterations > 0);
+        assert_eq!(iterations, deltas);
+        // One goal event per requested statement
+        assert_eq!(goals, request.templates().len());
+    }
+
+    #[test]
+    fn test_public_key_of() {
+        let params = Params::default();
+        let sk = SecretKey::new_rand();
+        let pk = sk.public_key();
+        let request = parse(
+            &format!("REQUEST(PublicKeyOf({}, b))", Value::from(pk)),
+            &params,
+            &[],
+        )
+        .unwrap();
+        let request = request.request;
+        let context = SolverContext::new(&[], &[]);
+        let solve_result = solve(request.templates(), &context, MetricsLevel::Counters);
+        // Without the secret key the failure names the key that is missing
+        assert!(matches!(
+            solve_result,
+            Err(SolverError::MissingKey { ref public_key }) if *public_key == pk.to_string()
+        ));
+
+        // A context holding the wrong key fails the same way
+        let wrong_sks = vec![SecretKey::new_rand()];
+        let context = SolverContext::new(&[], &wrong_sks);
+        let solve_result = solve(request.templates(), &context, MetricsLevel::Counters);
+        assert!(matches!(
+            solve_result,
+            Err(SolverError::MissingKey { .. })
+        ));
+
+        let sks = vec![sk.clone()];
+        let context = SolverContext::new(&[], &sks);
+        let solve_result = solve(request.templates(), &context, MetricsLevel::Counters);
+        assert!(solve_result.is_ok());
+        let (proof, _) = solve_result.unwrap();
+        let (pod_ids, ops) = proof.to_inputs();
+        assert_eq!(pod_ids.len(), 0);
+        assert_eq!(ops.len(), 1);
+        assert!(matches!(
+            ops[0].0 .0,
+            OperationType::Native(NativeOperation::PublicKeyOf)
+        ));
+        assert!(matches!(
+            ops[0].0.1.as_slice(),
+            [
+                OperationArg::Literal(pk_val),
+                OperationArg::Literal(sk_val)
+            ] if pk_val == &Value::from(pk) && sk_val == &Value::from(sk.clone())
+        ));
+    }
+
+    #[test]
+    fn test_repeated_statements() {
+        let _ = env_logger::builder().is_test(true).try_init();
+        let params = Params::default();
+        let sk = SecretKey::new_rand();
         let pk = Value::from(sk.public_key());
         let request = parse(
             &format!(
@@ -423,4 +1357,205 @@ REQUEST(
         assert_eq!(pod.public_statements.len(), 3); // Including the _type statement
         println!("{pod}");
     }
+
+    #[test]
+    fn test_minimize_drops_redundant_operations() {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        // A proof that derives the same statement via two distinct nodes
+        let statement = Statement::Equal(
+            ValueRef::Literal(Value::from(1)),
+            ValueRef::Literal(Value::from(1)),
+        );
+        let make_node = || {
+            Arc::new(ProofNode {
+                statement: statement.clone(),
+                justification: Justification::ValueComparison(NativeOperation::EqualFromEntries),
+            })
+        };
+        let proof = Proof {
+            root_nodes: vec![make_node(), make_node()],
+            db: Arc::new(FactDB::build(&[]).unwrap()),
+        };
+
+        let minimized = proof.minimize();
+        assert_eq!(proof.walk_post_order().len(), 2);
+        assert_eq!(minimized.walk_post_order().len(), 1);
+        // The deduplicated operation list is unchanged, so nothing the proof
+        // asserts was lost
+        assert_eq!(minimized.to_operations().len(), proof.to_operations().len());
+
+        // The repeated-statements scenario still proves after minimization
+        let params = Params::default();
+        let sk = SecretKey::new_rand();
+        let pk = Value::from(sk.public_key());
+        let request = parse(
+            &format!(
+                r#"
+owned_public_key(pk, pod_id, private: sk) = AND(
+    PublicKeyOf(pk, sk)
+    Equal(pod_id, SELF)
+)
+
+REQUEST(
+    PublicKeyOf({pk}, sk)
+    owned_public_key({pk}, SELF)
+)
+            "#
+            ),
+            &params,
+            &[],
+        )
+        .unwrap()
+        .request;
+        let sks = vec![sk.clone()];
+        let context = SolverContext::new(&[], &sks);
+        let (proof, _) = solve(request.templates(), &context, MetricsLevel::Counters).unwrap();
+
+        let minimized = proof.minimize();
+        assert!(minimized.walk_post_order().len() <= proof.walk_post_order().len());
+
+        let (_pod_ids, ops) = minimized.to_inputs();
+        let mut builder = MainPodBuilder::new(&params, &MOCK_VD_SET);
+        for (op, public) in ops {
+            if public {
+                builder.pub_op(op).unwrap();
+            } else {
+                builder.priv_op(op).unwrap();
+            }
+        }
+        let pod = builder.prove(&MockProver {}).unwrap();
+        assert_eq!(pod.public_statements.len(), 3); // Including the _type statement
+    }
+
+    #[test]
+    fn test_explain_ambiguity_reports_loose_templates() {
+        use pod2::frontend::SignedDictBuilder;
+
+        let _ = env_logger::builder().is_test(true).try_init();
+        let params = Params::default();
+
+        // Three pods carry the same loosely-matched entry, but only one of
+        // them is named "alice"
+        let make_pod = |name: &str| {
+            let mut builder = SignedDictBuilder::new(&params);
+            builder.insert("kind", "player");
+            builder.insert("name", name);
+            builder.sign(&Signer(SecretKey::new_rand())).unwrap()
+        };
+        let pod_a = make_pod("alice");
+        let pod_b = make_pod("bob");
+        let pod_c = make_pod("carol");
+
+        let pods = [
+            IndexablePod::signed_pod(&pod_a),
+            IndexablePod::signed_pod(&pod_b),
+            IndexablePod::signed_pod(&pod_c),
+        ];
+        let context = SolverContext::new(&pods, &[]);
+
+        // `Equal(P["kind"], "player")` leaves P free to bind to any of the
+        // three pods, so the request is ambiguous even though it solves
+        let request = parse(
+            r#"
+        REQUEST(
+            Equal(P["kind"], "player")
+        )
+        "#,
+            &params,
+            &[],
+        )
+        .unwrap()
+        .request;
+        assert!(solve(request.templates(), &context, MetricsLevel::None).is_ok());
+
+        let ambiguous = explain_ambiguity(request.templates(), &context);
+        assert_eq!(ambiguous.len(), 1);
+        assert_eq!(ambiguous[0].template, request.templates()[0]);
+        assert_eq!(ambiguous[0].match_count, 3);
+
+        // Pinning the name matches exactly one fact, so that template is not
+        // reported. The kind template still is — templates are checked in
+        // isolation, so the join with the name constraint does not clear it.
+        let request = parse(
+            r#"
+        REQUEST(
+            Equal(P["kind"], "player")
+            Equal(P["name"], "alice")
+        )
+        "#,
+            &params,
+            &[],
+        )
+        .unwrap()
+        .request;
+        let ambiguous = explain_ambiguity(request.templates(), &context);
+        assert_eq!(ambiguous.len(), 1);
+        assert_eq!(ambiguous[0].template, request.templates()[0]);
+    }
+
+    #[test]
+    fn test_solve_batch_matches_individual_solves() {
+        let _ = env_logger::builder().is_test(true).try_init();
+        let params = Params {
+            max_input_pods_public_statements: 8,
+            max_statements: 24,
+            max_public_statements: 8,
+            ..Default::default()
+        };
+
+        let alice = Signer(SecretKey::new_rand());
+        let bob = Signer(SecretKey::new_rand());
+        let charlie = Signer(SecretKey::new_rand());
+
+        let alice_attestation = attest_eth_friend(&params, &alice, bob.public_key());
+        let bob_attestation = attest_eth_friend(&params, &bob, charlie.public_key());
+        let batch = eth_dos_batch(&params).unwrap();
+
+        let make_request = |src: &Signer, dst: &Signer| {
+            let req = format!(
+                r#"
+      use _, _, _, eth_dos from 0x{}
+
+      REQUEST(
+          eth_dos({}, {}, Distance)
+      )
+      "#,
+                batch.id().encode_hex::<String>(),
+                src.public_key(),
+                dst.public_key()
+            );
+            parse(&req, &params, std::slice::from_ref(&batch))
+                .unwrap()
+                .request
+        };
+        let first = make_request(&alice, &bob);
+        let second = make_request(&bob, &charlie);
+
+        let pods = [
+            IndexablePod::signed_pod(&alice_attestation),
+            IndexablePod::signed_pod(&bob_attestation),
+        ];
+        let context = SolverContext::new(&pods, &[]);
+
+        let results = solve_batch(
+            &[first.templates(), second.templates()],
+            &context,
+            MetricsLevel::Counters,
+        );
+        assert_eq!(results.len(), 2);
+
+        // Each batched proof relies on the same pods and produces the same
+        // operations as solving its request on its own.
+        for (request, result) in [&first, &second].iter().zip(&results) {
+            let (batch_proof, _) = result.as_ref().unwrap();
+            let (individual_proof, _) =
+                solve(request.templates(), &context, MetricsLevel::Counters).unwrap();
+            assert_eq!(batch_proof.used_pod_ids(), individual_proof.used_pod_ids());
+            assert_eq!(
+                batch_proof.to_inputs().1.len(),
+                individual_proof.to_inputs().1.len()
+            );
+        }
+    }
 }
@@ -34,8 +34,8 @@
 //! - Use meaningful prefixes in log messages to provide context
 
 use std::{
-    collections::HashMap,
-    fmt::{Display, Formatter, Result as FmtResult},
+    collections::{HashMap, HashSet},
+    fmt::{Display, Formatter, Result as FmtResult, Write},
 };
 
 use pod2::{
@@ -49,6 +49,7 @@ use pod2::{
 use crate::{
     engine::semi_naive::{Fact, FactStore},
     ir::{Atom, PredicateIdentifier, Rule},
+    planner::QueryPlan,
 };
 
 /// Pretty-print a Hash, showing only the first 8 characters
@@ -121,6 +122,58 @@ pub fn format_rule(rule: &Rule) -> String {
     }
 }
 
+/// Render a `QueryPlan` as a Graphviz DOT dependency graph.
+///
+/// Predicates become nodes and every rule contributes edges from its body
+/// predicates to its head predicate, so recursion through the plan shows up
+/// as a cycle. Magic predicates introduced by the magic-set transformation
+/// are drawn as dashed ellipses to set them apart from the original program.
+/// This complements the proof exporter in [`crate::vis`].
+pub fn plan_to_dot(plan: &QueryPlan) -> String {
+    fn escape(s: &str) -> String {
+        s.replace('\\', "\\\\").replace('"', "\\\"")
+    }
+
+    let mut dot = String::new();
+    writeln!(&mut dot, "digraph QueryPlan {{").unwrap();
+    writeln!(&mut dot, "  rankdir=LR;").unwrap();
+    writeln!(&mut dot, "  node [shape=box];").unwrap();
+
+    let mut node_ids: HashMap<String, String> = HashMap::new();
+    let mut edges: HashSet<(String, String)> = HashSet::new();
+    let mut counter = 0usize;
+
+    let mut node_id = |pred: &PredicateIdentifier, dot: &mut String| {
+        let label = format_predicate_identifier(pred);
+        if let Some(id) = node_ids.get(&label) {
+            return id.clone();
+        }
+        let id = format!("pred_{counter}");
+        counter += 1;
+        let style = if matches!(pred, PredicateIdentifier::Magic { .. }) {
+            ", shape=ellipse, style=dashed"
+        } else {
+            ""
+        };
+        writeln!(dot, "  {} [label=\"{}\"{}];", id, escape(&label), style).unwrap();
+        node_ids.insert(label, id.clone());
+        id
+    };
+
+    for rule in plan.magic_rules.iter().chain(&plan.guarded_rules) {
+        let head_id = node_id(&rule.head.predicate, &mut dot);
+        for literal in &rule.body {
+            let body_id = node_id(&literal.predicate, &mut dot);
+            if edges.insert((body_id.clone(), head_id.clone())) {
+                writeln!(&mut dot, "  {body_id} -> {head_id};").unwrap();
+            }
+        }
+    }
+
+    writeln!(&mut dot, "}}").unwrap();
+    dot
+}
+
 /// Pretty-print a StatementTmpl
 pub fn format_statement_template(stmt: &StatementTmpl) -> String {
     let pred_name = match &stmt.pred {
@@ -430,6 +483,51 @@ mod tests {
         assert_eq!(pretty_wildcard.to_string(), "test_var");
     }
 
+    #[test]
+    fn test_plan_to_dot_contains_nodes_and_edges() {
+        use pod2::{lang::parse, middleware::Params};
+
+        use crate::planner::Planner;
+
+        fn node_id_for(dot: &str, label_fragment: &str) -> String {
+            dot.lines()
+                .find(|l| {
+                    l.contains("[label=") && l.contains(label_fragment) && !l.contains("magic_")
+                })
+                .and_then(|l| l.trim().split_whitespace().next())
+                .map(str::to_string)
+                .unwrap_or_else(|| panic!("no node labelled with {label_fragment}"))
+        }
+
+        let podlog = r#"
+            is_large(P) = AND(
+                Lt(10, P["foo"])
+            )
+            REQUEST(
+                is_large(SomePod)
+            )
+        "#;
+        let params = Params::default();
+        let processed = parse(podlog, &params, &[]).unwrap();
+        let plan = Planner::new()
+            .create_plan(processed.request.templates())
+            .unwrap();
+
+        let dot = plan_to_dot(&plan);
+        assert!(dot.starts_with("digraph QueryPlan {"));
+        assert!(dot.trim_end().ends_with('}'));
+
+        // Both predicates of the is_large rule appear as nodes, and the
+        // dependency edge runs from the body predicate to the head.
+        let head_id = node_id_for(&dot, "is_large");
+        let body_id = node_id_for(&dot, "Lt");
+        assert!(dot.contains(&format!("{body_id} -> {head_id};")));
+
+        // Magic predicates are present and visually distinguished.
+        assert!(dot.contains("magic_"));
+        assert!(dot.contains("style=dashed"));
+    }
+
     #[test]
     fn test_pretty_iteration_summary() {
         let summary = PrettyIterationSummary {
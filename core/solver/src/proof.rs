@@ -265,6 +265,31 @@ impl Proof {
         result
     }
 
+    /// Returns the statements that end up in the MainPod's public section when
+    /// the proof's operations are fed to a `MainPodBuilder`: the conclusions
+    /// of the direct premises of the synthetic `_request_goal` root, in the
+    /// order `to_operations` emits the corresponding public operations. Lets
+    /// callers display the public statements before running `prove()`; note
+    /// that the builder appends a `_type` statement of its own on top.
+    pub fn public_statements(&self) -> Vec<Statement> {
+        let mut public_nodes: HashSet<*const ProofNode> = HashSet::new();
+        for root in &self.root_nodes {
+            if let Justification::Custom(_, premises) = &root.justification {
+                for p in premises {
+                    public_nodes.insert(Arc::as_ptr(p));
+                }
+            }
+        }
+
+        let mut seen: HashSet<Statement> = HashSet::new();
+        self.walk_post_order()
+            .into_iter()
+            .filter(|node| public_nodes.contains(&Arc::as_ptr(node)))
+            .filter(|node| seen.insert(node.statement.clone()))
+            .map(|node| node.statement.clone())
+            .collect()
+    }
+
     fn post_order_traverse(
         node: &Arc<ProofNode>,
         visited: &mut HashSet<*const ProofNode>,
@@ -350,6 +375,104 @@ impl Proof {
 
         (pod_cover, ops_with_flag)
     }
+
+    /// Returns the ids of the input PODs this proof actually relies on: the
+    /// minimal cover selected by [`Proof::to_inputs`].
+    pub fn used_pod_ids(&self) -> HashSet<PodId> {
+        self.to_inputs().0.into_iter().collect()
+    }
+
+    /// Estimates the size of the MainPod this proof will build, computed from
+    /// [`Proof::to_inputs`]: every operation produces one statement, public or
+    /// private according to its flag. Comparing the estimate against `Params`
+    /// (e.g. `max_statements`, `max_public_statements`) lets callers warn
+    /// before `prove()` fails late; note that `MainPodBuilder` appends a
+    /// `_type` statement of its own on top of the public count.
+    pub fn estimate(&self) -> ProofEstimate {
+        let (pod_ids, ops) = self.to_inputs();
+        let num_public_statements = ops.iter().filter(|(_, public)| *public).count();
+        ProofEstimate {
+            num_public_statements,
+            num_private_statements: ops.len() - num_public_statements,
+            num_input_pods: pod_ids.len(),
+            num_operations: ops.len(),
+        }
+    }
+
+    /// Returns a structurally minimized copy of the proof.
+    ///
+    /// Reconstruction can produce several distinct `ProofNode`s for the same
+    /// derivation (e.g. the same `Contains` proven once as a requested
+    /// statement and again inside a custom predicate body). Because traversal
+    /// deduplicates by pointer identity, each copy is walked separately and
+    /// emits its own operation, inflating the MainPod towards the `Params`
+    /// limits. Minimization interns structurally identical subtrees into a
+    /// single shared node and rebuilds the tree from the root statements, so
+    /// every derivation is emitted once and premises no public statement
+    /// depends on are dropped. The proven statements are unchanged.
+    pub fn minimize(&self) -> Proof {
+        let mut interned: HashMap<String, Arc<ProofNode>> = HashMap::new();
+        let root_nodes = self
+            .root_nodes
+            .iter()
+            .map(|root| Self::intern_node(root, &mut interned).0)
+            .collect();
+        Proof {
+            root_nodes,
+            db: self.db.clone(),
+        }
+    }
+
+    /// Rebuilds `node` bottom-up, sharing one `Arc` per structural key. The
+    /// key covers the statement, the justification, and the premise keys, so
+    /// two nodes merge only when their entire derivations coincide.
+    fn intern_node(
+        node: &Arc<ProofNode>,
+        interned: &mut HashMap<String, Arc<ProofNode>>,
+    ) -> (Arc<ProofNode>, String) {
+        let (justification, justification_key) = match &node.justification {
+            Justification::Custom(cpr, premises) => {
+                let mut new_premises = Vec::with_capacity(premises.len());
+                let mut premise_keys = Vec::with_capacity(premises.len());
+                for premise in premises {
+                    let (new_premise, key) = Self::intern_node(premise, interned);
+                    new_premises.push(new_premise);
+                    premise_keys.push(key);
+                }
+                (
+                    Justification::Custom(cpr.clone(), new_premises),
+                    format!("custom:{}:{}:{premise_keys:?}", cpr.batch.id(), cpr.index),
+                )
+            }
+            Justification::Fact => (Justification::Fact, "fact".to_string()),
+            Justification::NewEntry => (Justification::NewEntry, "new_entry".to_string()),
+            Justification::ValueComparison(op) => {
+                (Justification::ValueComparison(*op), format!("cmp:{op:?}"))
+            }
+            Justification::Special(op) => (Justification::Special(*op), format!("special:{op:?}")),
+        };
+        let key = format!("{:?}|{justification_key}", node.statement);
+        let shared = interned
+            .entry(key.clone())
+            .or_insert_with(|| {
+                Arc::new(ProofNode {
+                    statement: node.statement.clone(),
+                    justification,
+                })
+            })
+            .clone();
+        (shared, key)
+    }
+}
+
+/// Size estimate of the MainPod a [`Proof`] will build; see
+/// [`Proof::estimate`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ProofEstimate {
+    pub num_public_statements: usize,
+    pub num_private_statements: usize,
+    pub num_input_pods: usize,
+    pub num_operations: usize,
 }
 
 /// Returns the set of PodIds that assert the given statement, if any.
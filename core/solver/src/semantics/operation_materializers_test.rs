@@ -7,8 +7,9 @@ mod tests {
     use pod2::{
         backends::plonky2::primitives::ec::schnorr::SecretKey,
         middleware::{
-            containers::Dictionary, hash_str, AnchoredKey, Key, NativeOperation, NativePredicate,
-            Params, PodId, Value, ValueRef, SELF,
+            containers::{Array, Dictionary, Set},
+            hash_str, AnchoredKey, Key, NativeOperation, NativePredicate, Params, PodId, Value,
+            ValueRef, SELF,
         },
     };
 
@@ -261,6 +262,84 @@ mod tests {
         assert_eq!(fact.args[2], val_ref_int(42));
     }
 
+    fn test_array() -> ValueRef {
+        let params = Params::default();
+        let arr = Array::new(
+            params.max_depth_mt_containers,
+            vec![val_int(10), val_int(20), val_int(30)],
+        )
+        .unwrap();
+        ValueRef::from(Value::new(arr.into()))
+    }
+
+    #[test]
+    fn test_contains_from_entries_array_index_all_bound() {
+        let db = create_test_db();
+        let materializer = OperationMaterializer::ContainsFromEntries;
+        let array_value_ref = test_array();
+
+        // The element at index 1 is 20
+        let args = vec![
+            Some(array_value_ref.clone()),
+            Some(val_ref_int(1)),
+            Some(val_ref_int(20)),
+        ];
+        let result = materializer.materialize(&args, &db, NativePredicate::Contains);
+
+        assert!(result.is_some());
+        let fact = result.unwrap();
+        assert!(matches!(fact.source, FactSource::Native(_)));
+        assert_eq!(fact.args[0], array_value_ref);
+        assert_eq!(fact.args[1], val_ref_int(1));
+        assert_eq!(fact.args[2], val_ref_int(20));
+
+        // Wrong value at that index does not materialize
+        let args = vec![
+            Some(array_value_ref.clone()),
+            Some(val_ref_int(1)),
+            Some(val_ref_int(30)),
+        ];
+        assert!(materializer.materialize(&args, &db, NativePredicate::Contains).is_none());
+
+        // Out-of-range index does not materialize
+        let args = vec![
+            Some(array_value_ref),
+            Some(val_ref_int(3)),
+            Some(val_ref_int(10)),
+        ];
+        assert!(materializer.materialize(&args, &db, NativePredicate::Contains).is_none());
+    }
+
+    #[test]
+    fn test_contains_from_entries_array_index_unbound_value() {
+        let db = create_test_db();
+        let materializer = OperationMaterializer::ContainsFromEntries;
+        let array_value_ref = test_array();
+
+        // "value" arg is unbound; the element at index 1 is deduced
+        let args = vec![Some(array_value_ref.clone()), Some(val_ref_int(1)), None];
+        let result = materializer.materialize(&args, &db, NativePredicate::Contains);
+
+        assert!(result.is_some());
+        let fact = result.unwrap();
+        assert_eq!(fact.args[0], array_value_ref);
+        assert_eq!(fact.args[1], val_ref_int(1));
+        assert_eq!(fact.args[2], val_ref_int(20));
+    }
+
+    #[test]
+    fn test_contains_from_entries_array_unbound_index() {
+        let db = create_test_db();
+        let materializer = OperationMaterializer::ContainsFromEntries;
+
+        // With the index unbound there is nothing to deduce; the engine must
+        // suspend until another rule binds it
+        let args = vec![Some(test_array()), None, Some(val_ref_int(20))];
+        let result = materializer.materialize(&args, &db, NativePredicate::Contains);
+
+        assert!(result.is_none());
+    }
+
     #[test]
     fn test_not_contains_from_entries_wrong_argument_count() {
         let db = create_test_db();
@@ -284,6 +363,39 @@ mod tests {
         assert!(result.is_none());
     }
 
+    #[test]
+    fn test_not_contains_from_entries_literal_set() {
+        let db = create_test_db();
+        let materializer = OperationMaterializer::NotContainsFromEntries;
+        let params = Params::default();
+
+        let set = Set::new(
+            params.max_depth_mt_containers,
+            [val_str("apple"), val_str("banana")].into_iter().collect(),
+        )
+        .unwrap();
+        let set_ref = ValueRef::Literal(Value::from(set));
+
+        // An absent value materializes directly from the literals; no pod
+        // needs to assert the statement
+        let args = vec![Some(set_ref.clone()), Some(val_ref_str("cherry"))];
+        let result = materializer.materialize(&args, &db, NativePredicate::NotContains);
+
+        assert!(result.is_some());
+        let fact = result.unwrap();
+        assert!(matches!(
+            fact.source,
+            FactSource::Native(NativeOperation::NotContainsFromEntries)
+        ));
+        assert_eq!(fact.args, vec![set_ref.clone(), val_ref_str("cherry")]);
+
+        // A contained value does not materialize
+        let args = vec![Some(set_ref), Some(val_ref_str("apple"))];
+        let result = materializer.materialize(&args, &db, NativePredicate::NotContains);
+
+        assert!(result.is_none());
+    }
+
     // ================================================================================================
     // Tests for Arithmetic/Computation Operations
     // ================================================================================================
@@ -7,6 +7,7 @@ use pod2::{
 };
 
 pub mod prover_setup;
+pub mod serialization;
 
 /// Utility trait for extracting typed values from pod2::middleware::Value
 pub trait ValueExt {
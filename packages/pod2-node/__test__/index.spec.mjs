@@ -1,8 +1,279 @@
 import test from 'ava'
-import { MainPod } from '../index.js'
+import {
+  blockiesSeed,
+  MainPod,
+  PodRequest,
+  SignedPod,
+  SignedPodBuilder,
+  Signer,
+  solve,
+  verifyBatch,
+} from '../index.js'
 import serializedMainPod from './mainpod.json' assert { type: 'json' }
 
 test('deserialize main pod', (t) => {
   const mainPod = MainPod.deserialize(JSON.stringify(serializedMainPod))
   t.is(mainPod.verify(), true)
 })
+
+test('deserialize throws a catchable error on malformed JSON', (t) => {
+  const error = t.throws(() => MainPod.deserialize('not json at all'))
+  t.is(error.code, 'POD_DESERIALIZE_ERROR')
+  t.true(error.message.length > 0)
+})
+
+test('deserialize throws a catchable error on JSON that is not a main pod', (t) => {
+  const error = t.throws(() => MainPod.deserialize('{"unexpected": true}'))
+  t.is(error.code, 'POD_DESERIALIZE_ERROR')
+})
+
+test('verifyDetailed does not throw for a valid pod', (t) => {
+  const mainPod = MainPod.deserialize(JSON.stringify(serializedMainPod))
+  t.notThrows(() => mainPod.verifyDetailed())
+  t.truthy(mainPod.publicStatements())
+})
+
+test('SignedPod.deserialize throws a catchable error on malformed JSON', (t) => {
+  const error = t.throws(() => SignedPod.deserialize('not a signed pod'))
+  t.is(error.code, 'POD_DESERIALIZE_ERROR')
+  t.true(error.message.length > 0)
+})
+
+test('PodRequest.parse reports line and column on bad Podlang', (t) => {
+  const error = t.throws(() => PodRequest.parse('REQUEST('))
+  t.is(error.code, 'POD_REQUEST_PARSE_ERROR')
+  t.regex(error.message, /line \d+, column \d+/)
+})
+
+test('PodRequest exposes its templates for debugging', (t) => {
+  const request = PodRequest.parse('REQUEST(Equal(x["score"], 1))')
+  const templates = request.templatesJson()
+  t.truthy(templates)
+  t.is(JSON.parse(JSON.stringify(templates)).length, 1)
+})
+
+test('matchPod returns null when the pod does not satisfy the request', (t) => {
+  const mainPod = MainPod.deserialize(JSON.stringify(serializedMainPod))
+  const request = PodRequest.parse('REQUEST(Equal(x["no_such_key"], 12345))')
+  t.is(request.matchPod(mainPod), null)
+})
+
+test('matchPod extracts bindings from a satisfying pod', async (t) => {
+  const builder = new SignedPodBuilder()
+  builder.insert('score', 42)
+  const signed = builder.sign(Signer.fromSecretKeyHexUnsafe('0x3f91a2c5d8e7'))
+
+  // Prove the request from the signed pod, then check the proof against it
+  const source = 'REQUEST(Equal(x["score"], 42))'
+  const podJson = await solve(source, [signed.serialize()], { buildMock: true })
+  const pod = MainPod.deserialize(JSON.stringify(podJson))
+
+  const bindings = PodRequest.parse(source).matchPod(pod)
+  t.truthy(bindings)
+  t.true(Object.keys(bindings).includes('x'))
+})
+
+test('MainPod round-trips through JSON and compact bytes', (t) => {
+  const pod = MainPod.deserialize(JSON.stringify(serializedMainPod))
+  const bytes = pod.toBytes()
+  // The CBOR form is what makes network transfer cheap; it must beat JSON
+  t.true(bytes.length < pod.serialize().length)
+
+  const restored = MainPod.fromBytes(bytes)
+  t.is(restored.id(), pod.id())
+  t.is(restored.verify(), true)
+  t.deepEqual(JSON.parse(restored.serialize()), JSON.parse(pod.serialize()))
+})
+
+test('fromBytes rejects truncated and empty buffers', (t) => {
+  const bytes = MainPod.deserialize(JSON.stringify(serializedMainPod)).toBytes()
+  const truncated = t.throws(() => MainPod.fromBytes(bytes.subarray(0, bytes.length >> 1)))
+  t.is(truncated.code, 'POD_DESERIALIZE_ERROR')
+  const empty = t.throws(() => MainPod.fromBytes(Buffer.alloc(0)))
+  t.is(empty.code, 'POD_DESERIALIZE_ERROR')
+})
+
+test('verifyAsync resolves like verify', async (t) => {
+  const mainPod = MainPod.deserialize(JSON.stringify(serializedMainPod))
+  t.is(await mainPod.verifyAsync(), true)
+})
+
+test('verifyBatch preserves order and isolates bad entries', async (t) => {
+  const good = JSON.stringify(serializedMainPod)
+  const results = await verifyBatch([good, 'not a pod', good], 2)
+  t.is(results.length, 3)
+  t.deepEqual(results.map((r) => r.ok), [true, false, true])
+  t.truthy(results[1].error)
+  t.is(results[0].error, undefined)
+})
+
+test('verifyBatch keeps the event loop responsive', async (t) => {
+  const pods = Array.from({ length: 200 }, () => JSON.stringify(serializedMainPod))
+  let ticks = 0
+  const timer = setInterval(() => {
+    ticks += 1
+  }, 5)
+  // Hold the event loop open past a few timer intervals so a blocked loop
+  // would show up as zero ticks
+  const [results] = await Promise.all([
+    verifyBatch(pods),
+    new Promise((resolve) => setTimeout(resolve, 25)),
+  ])
+  clearInterval(timer)
+  t.is(results.length, 200)
+  t.true(results.every((r) => r.ok))
+  t.true(ticks > 0)
+})
+
+test('solve throws a coded error on bad request source', (t) => {
+  const error = t.throws(() => solve('REQUEST(', []))
+  t.is(error.code, 'POD_REQUEST_PARSE_ERROR')
+})
+
+test('solve throws a coded error on a pod that deserializes as neither kind', (t) => {
+  const error = t.throws(() => solve('REQUEST(Equal(x["a"], 1))', ['{"nope": true}']))
+  t.is(error.code, 'POD_DESERIALIZE_ERROR')
+})
+
+test('solve rejects with a coded reason when unsatisfiable', async (t) => {
+  const error = await t.throwsAsync(() => solve('REQUEST(Equal(x["missing"], 1))', []))
+  t.regex(error.message, /^POD_UNSATISFIABLE/)
+})
+
+test('solve answers a request from signed pods built in-process', async (t) => {
+  const gov = new SignedPodBuilder()
+  gov.insert('idNumber', 'G-123')
+  gov.insert('age', 25)
+  const govPod = gov.sign(Signer.fromSecretKeyHexUnsafe('0x1d4e7a'))
+
+  const pay = new SignedPodBuilder()
+  pay.insert('startDate', 1000)
+  const payPod = pay.sign(Signer.fromSecretKeyHexUnsafe('0x2b5f8c'))
+
+  const source = 'REQUEST(Equal(gov["age"], 25), Equal(pay["startDate"], 1000))'
+  const pods = [govPod.serialize(), payPod.serialize()]
+
+  const summary = await solve(source, pods)
+  t.true(summary.statements.length > 0)
+  t.true(Array.isArray(summary.inputPods))
+  t.true(summary.operationCount >= 0)
+
+  const podJson = await solve(source, pods, { buildMock: true })
+  const pod = MainPod.deserialize(JSON.stringify(podJson))
+  t.is(pod.verify(), true)
+  t.truthy(PodRequest.parse(source).matchPod(pod))
+})
+
+test('blockiesSeed is deterministic, case-insensitive, and well-formed', (t) => {
+  const seed = blockiesSeed('Some-Public-Key')
+  t.deepEqual(seed, blockiesSeed('some-public-key'))
+
+  t.is(seed.cells.length, 16)
+  t.true(seed.colors.length > 0 && seed.colors.length <= 3)
+  for (const color of seed.colors) {
+    t.regex(color, /^#[0-9a-f]{6}$/)
+  }
+  for (const row of seed.cells) {
+    t.is(row.length, 16)
+    t.true(row.every((cell) => cell >= 0 && cell < seed.colors.length))
+  }
+
+  t.notDeepEqual(seed, blockiesSeed('a different key'))
+})
+
+test('pods expose hash accessors and avatar seeds', (t) => {
+  const pod = MainPod.deserialize(JSON.stringify(serializedMainPod))
+  t.regex(pod.statementsHash(), /^[0-9a-f]+$/)
+  t.deepEqual(pod.blockiesSeed(), blockiesSeed(pod.id()))
+
+  const signer = Signer.fromSecretKeyHexUnsafe('0x2a6b35c8d1e94f07b3a25c8d1e94f07b')
+  const builder = new SignedPodBuilder()
+  builder.insert('k', 1)
+  const signed = builder.sign(signer)
+  t.is(signed.commitment(), signed.id())
+  t.deepEqual(signed.blockiesSeed(), blockiesSeed(signed.signer()))
+})
+
+test('avatar seeds are stable for a fixed signing key', (t) => {
+  // A fixed secret key pins the public key, and with it the avatar; the
+  // derivation itself lives in pod2-blockies, the same crate the desktop
+  // client renders from, so agreement here is agreement with the client.
+  const signer = Signer.fromSecretKeyHexUnsafe('0x6c2e9b4f81d3a750')
+  const builder = new SignedPodBuilder()
+  builder.insert('k', 1)
+  const seed = builder.sign(signer).blockiesSeed()
+
+  t.deepEqual(seed, blockiesSeed(signer.publicKey()))
+  const again = new SignedPodBuilder()
+  again.insert('k', 2)
+  t.deepEqual(again.sign(signer).blockiesSeed(), seed)
+})
+
+test('build and sign a pod in Node, then verify it through the Rust decoders', (t) => {
+  const signer = Signer.fromSecretKeyHexUnsafe('0x2a6b35c8d1e94f07b3a25c8d1e94f07b')
+  const builder = new SignedPodBuilder()
+  builder.insert('name', 'alice')
+  builder.insert('age', 42)
+  builder.insert('admin', true)
+  builder.insert('scores', [1, 2, 3])
+  builder.insert('profile', { city: 'zuzalu', rank: 7 })
+  builder.insert('tags', { $set: ['b', 'a'] })
+  builder.insert('big', { $int: '9007199254740993' })
+
+  const pod = builder.sign(signer)
+  t.is(pod.verify(), true)
+  t.is(pod.signer(), signer.publicKey())
+  t.is(pod.get('name'), 'alice')
+  t.is(pod.get('age'), 42)
+  t.is(pod.get('admin'), true)
+  t.deepEqual(pod.get('scores'), [1, 2, 3])
+  t.deepEqual(pod.get('profile'), { city: 'zuzalu', rank: 7 })
+  t.deepEqual(pod.get('tags'), ['a', 'b'])
+  t.is(pod.get('big'), '9007199254740993')
+
+  // Cross-language check: both Rust-side decoders accept what Node signed
+  const viaJson = SignedPod.deserialize(pod.serialize())
+  t.is(viaJson.verify(), true)
+  t.is(viaJson.id(), pod.id())
+  const viaBytes = SignedPod.fromBytes(pod.toBytes())
+  t.is(viaBytes.verify(), true)
+})
+
+test('insert rejects floats, null, and malformed markers', (t) => {
+  const builder = new SignedPodBuilder()
+  for (const bad of [1.5, null, { $int: 'not digits' }, { $set: 'not an array' }]) {
+    const error = t.throws(() => builder.insert('k', bad))
+    t.is(error.code, 'POD_VALUE_ERROR')
+    t.true(error.message.includes("'k'"))
+  }
+})
+
+test('Signer.fromSecretKeyHexUnsafe rejects unusable keys', (t) => {
+  t.is(t.throws(() => Signer.fromSecretKeyHexUnsafe('not hex')).code, 'POD_SIGN_ERROR')
+  t.is(t.throws(() => Signer.fromSecretKeyHexUnsafe('0x0')).code, 'POD_SIGN_ERROR')
+})
+
+test('deserialize and inspect a signed pod', (t) => {
+  const builder = new SignedPodBuilder()
+  builder.insert('kind', 'attestation')
+  builder.insert('issued', 1700000000)
+  builder.insert('subjects', ['alice', 'bob'])
+  const signed = builder.sign(Signer.fromSecretKeyHexUnsafe('0x517b3c8d1e94f07b'))
+
+  const pod = SignedPod.deserialize(signed.serialize())
+  t.is(pod.verify(), true)
+  t.notThrows(() => pod.verifyDetailed())
+  t.regex(pod.id(), /^[0-9a-f]+$/)
+  t.truthy(pod.signer())
+
+  const entries = pod.entries()
+  t.is(typeof entries, 'object')
+  for (const key of Object.keys(entries)) {
+    t.deepEqual(pod.get(key), entries[key])
+  }
+  t.is(pod.get('no-such-key'), null)
+
+  const restored = SignedPod.fromBytes(pod.toBytes())
+  t.is(restored.id(), pod.id())
+  t.is(restored.verify(), true)
+})
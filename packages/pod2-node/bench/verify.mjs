@@ -0,0 +1,51 @@
+// Compare synchronous verify() against verifyAsync()/verifyBatch() on a
+// large batch, and report how starved the event loop was in each case.
+// Run with: node bench/verify.mjs [podCount]
+import { readFileSync } from 'node:fs'
+
+import { MainPod, verifyBatch } from '../index.js'
+
+const podCount = Number(process.argv[2] ?? 500)
+const serialized = readFileSync(new URL('../__test__/mainpod.json', import.meta.url), 'utf8')
+const pods = Array.from({ length: podCount }, () => serialized)
+
+// Measures the worst gap between 1ms timer ticks while `run` executes: a
+// blocked event loop shows up as one gap close to the full runtime.
+async function measure(name, run) {
+  let lastTick = process.hrtime.bigint()
+  let worstGapMs = 0
+  const timer = setInterval(() => {
+    const now = process.hrtime.bigint()
+    worstGapMs = Math.max(worstGapMs, Number(now - lastTick) / 1e6)
+    lastTick = now
+  }, 1)
+
+  const start = process.hrtime.bigint()
+  await run()
+  const totalMs = Number(process.hrtime.bigint() - start) / 1e6
+
+  clearInterval(timer)
+  console.log(
+    `${name.padEnd(24)} total ${totalMs.toFixed(1)}ms, worst event-loop gap ${worstGapMs.toFixed(1)}ms`
+  )
+}
+
+await measure('verify (sync loop)', async () => {
+  for (const pod of pods) {
+    MainPod.deserialize(pod).verify()
+  }
+})
+
+await measure('verifyAsync (serial)', async () => {
+  for (const pod of pods) {
+    await MainPod.deserialize(pod).verifyAsync()
+  }
+})
+
+await measure('verifyBatch', async () => {
+  await verifyBatch(pods)
+})
+
+await measure('verifyBatch (2 threads)', async () => {
+  await verifyBatch(pods, 2)
+})
@@ -6,5 +6,174 @@
 export declare class MainPod {
   static deserialize(serializedPod: string): MainPod
   verify(): boolean
+  /**
+   * Like `verify`, but throws an error with code POD_VERIFY_ERROR and the
+   * underlying failure reason instead of returning false
+   */
+  verifyDetailed(): void
   publicStatements(): JsonValue
+  /**
+   * Like `verify`, but runs on the libuv thread pool so verifying many
+   * pods doesn't block the JS event loop
+   */
+  verifyAsync(): Promise<boolean>
+  /** Hex id of the pod (its statements hash) */
+  id(): string
+  /**
+   * Hex hash of the pod's public statements — the value other pods commit
+   * to when they reference this one
+   */
+  statementsHash(): string
+  /** Avatar seed data derived from the pod id, matching `blockiesSeed` */
+  blockiesSeed(): BlockiesSeed
+  /** The canonical JSON serialization, as accepted by `deserialize` */
+  serialize(): string
+  /**
+   * The same pod as `serialize` in version-prefixed CBOR, at roughly half
+   * the JSON size; `pod_utils::serialization` is the Rust side of the
+   * format, so Rust services can decode these buffers directly
+   */
+  toBytes(): Buffer
+  /**
+   * Decode a buffer produced by `toBytes` (or by the Rust helper).
+   * Truncated buffers and unknown format versions are rejected.
+   */
+  static fromBytes(bytes: Buffer): MainPod
+}
+/** Palette-indexed avatar grid; see `blockiesSeed` */
+export interface BlockiesSeed {
+  /** Row-major palette indices into `colors` */
+  cells: Array<Array<number>>
+  /** `#rrggbb` colors in first-appearance order */
+  colors: Array<string>
+}
+/**
+ * Avatar seed data for an arbitrary identifier (e.g. a signer public key).
+ * The derivation is shared with the desktop client, so both render the
+ * same avatar for the same input; rendering itself stays in JS.
+ */
+export declare function blockiesSeed(seed: string): BlockiesSeed
+export interface VerifyBatchEntry {
+  ok: boolean
+  /** Why deserialization or verification failed, when `ok` is false */
+  error?: string
+}
+/**
+ * Deserialize and verify many pods on a rayon pool, off the JS thread.
+ * Results preserve input order; a pod that fails to deserialize or verify
+ * yields `{ ok: false, error }` instead of rejecting the whole batch.
+ * `concurrency` caps the worker threads (default: rayon's global pool).
+ */
+export declare function verifyBatch(serializedPods: Array<string>, concurrency?: number | undefined | null): Promise<Array<VerifyBatchEntry>>
+export interface SolveOptions {
+  /**
+   * Build and return a mock-proved MainPod (JSON) instead of the
+   * operation summary. Mock proofs are for testing only.
+   */
+  buildMock?: boolean
+}
+/**
+ * Parse a Podlang request and solve it against the provided serialized pods
+ * (each entry a SignedPod or MainPod JSON). The search runs on the libuv
+ * thread pool so long solves don't block the JS event loop. Parse and
+ * deserialize failures throw coded errors synchronously; async failures
+ * reject with a "CODE: message" reason (POD_SOLVE_ERROR, POD_UNSATISFIABLE).
+ */
+export declare function solve(requestSource: string, pods: Array<string>, options?: SolveOptions | undefined | null): Promise<JsonValue>
+export declare class PodRequest {
+  /**
+   * Parse Podlang source into a request. `customBatches` are Podlang
+   * sources for custom predicate batches the request refers to, as
+   * produced by `CustomPredicateBatch::to_podlang_string`; earlier batches
+   * are in scope when later ones are parsed.
+   */
+  static parse(source: string, customBatches?: Array<string> | undefined | null): PodRequest
+  /**
+   * Check a MainPod against this request. Returns the wildcard bindings
+   * when the pod's public statements satisfy the request exactly, or null
+   * when they don't.
+   */
+  matchPod(mainPod: MainPod): JsonValue | null
+  /**
+   * The request's statement templates in pod2's JSON encoding, for
+   * debugging and logging
+   */
+  templatesJson(): JsonValue
+}
+export declare class Signer {
+  /**
+   * Build a signer from a hex-encoded secret key (optionally 0x-prefixed).
+   * "Unsafe" refers to key handling, not memory safety: handing a raw key
+   * to JS code is only appropriate for trusted automation, and sourcing the
+   * hex from somewhere secret is the caller's responsibility.
+   */
+  static fromSecretKeyHexUnsafe(secretKeyHex: string): Signer
+  /** The corresponding public key as a string, matching `SignedPod.signer` */
+  publicKey(): string
+}
+/**
+ * Accumulates entries for a SignedPod. JS values are converted to pod2
+ * values on `insert`: strings, booleans, and safe integers map directly,
+ * arrays and plain objects become pod2 arrays and dictionaries, and two
+ * markers cover what JSON cannot express — `{ "$set": [...] }` for sets and
+ * `{ "$int": "decimal string" }` for integers beyond
+ * Number.MAX_SAFE_INTEGER (BigInts do not survive the JSON bridge, so pass
+ * them as marker strings).
+ */
+export declare class SignedPodBuilder {
+  constructor()
+  /**
+   * Insert an entry, replacing any previous value for the key. Throws an
+   * error with code POD_VALUE_ERROR for values with no pod2 equivalent:
+   * floats, null, malformed markers, and over-deep nesting.
+   */
+  insert(key: string, value: JsonValue): void
+  /**
+   * Sign the collected entries, producing a SignedPod that verifies like
+   * any other
+   */
+  sign(signer: Signer): SignedPod
+}
+export declare class SignedPod {
+  static deserialize(serializedPod: string): SignedPod
+  verify(): boolean
+  /**
+   * Like `verify`, but throws an error with code POD_VERIFY_ERROR and the
+   * underlying failure reason instead of returning false
+   */
+  verifyDetailed(): void
+  /** Hex commitment of the signed dictionary */
+  id(): string
+  /**
+   * The merkle commitment the signature covers; identical to `id`, named
+   * for symmetry with `MainPod.statementsHash`
+   */
+  commitment(): string
+  /** The signer's public key as a string */
+  signer(): string
+  /**
+   * Avatar seed data derived from the signer's public key, the same
+   * derivation the desktop client uses for identity avatars
+   */
+  blockiesSeed(): BlockiesSeed
+  /**
+   * All entries as a plain object of key to JSON value, containers
+   * converted recursively. Pass `largeIntsAsStrings: false` to get lossy
+   * Numbers for ints beyond Number.MAX_SAFE_INTEGER instead of strings.
+   */
+  entries(largeIntsAsStrings?: boolean | undefined | null): JsonValue
+  /** A single entry converted like `entries`, or null when absent */
+  get(key: string, largeIntsAsStrings?: boolean | undefined | null): JsonValue | null
+  /** The canonical JSON serialization, as accepted by `deserialize` */
+  serialize(): string
+  /**
+   * Version-prefixed CBOR encoding of the signed dictionary, sharing the
+   * wire format with `pod_utils::serialization` on the Rust side
+   */
+  toBytes(): Buffer
+  /**
+   * Decode a buffer produced by `toBytes` (or by the Rust helper).
+   * Truncated buffers and unknown format versions are rejected.
+   */
+  static fromBytes(bytes: Buffer): SignedPod
 }
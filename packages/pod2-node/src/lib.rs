@@ -3,9 +3,99 @@
 #[macro_use]
 extern crate napi_derive;
 
-use pod2::frontend::MainPod as Pod2MainPod;
+use std::{
+  collections::{HashMap, HashSet},
+  sync::Arc,
+};
+
+use hex::ToHex;
+use napi::{
+  bindgen_prelude::{AsyncTask, Buffer},
+  Env, Error, Result, Task,
+};
+use num_bigint::BigUint;
+use pod_utils::serialization;
+use pod2::{
+  backends::plonky2::{
+    mock::mainpod::MockProver, primitives::ec::schnorr::SecretKey, signer::Signer as PodSigner,
+  },
+  examples::MOCK_VD_SET,
+  frontend::{MainPod as Pod2MainPod, SignedDict, SignedDictBuilder},
+  lang::{self, parser, processor::PodlangOutput, LangError},
+  middleware::{
+    containers::{Array, Dictionary, Set},
+    CustomPredicateBatch, Key, Params, TypedValue, Value,
+  },
+};
+use pod2_new_solver::{
+  build_pod_from_answer_top_level_public, edb::ImmutableEdbBuilder, engine::Engine,
+  ConstraintStore, EngineConfigBuilder, OpRegistry, OpTag,
+};
+use rayon::prelude::*;
 use serde_json::Value as JsonValue;
 
+// Stable `code` values carried by thrown JS errors, so callers can branch on
+// the failure kind without parsing messages.
+const POD_DESERIALIZE_ERROR: &str = "POD_DESERIALIZE_ERROR";
+const POD_VERIFY_ERROR: &str = "POD_VERIFY_ERROR";
+const POD_SERIALIZE_ERROR: &str = "POD_SERIALIZE_ERROR";
+const POD_REQUEST_PARSE_ERROR: &str = "POD_REQUEST_PARSE_ERROR";
+const POD_VALUE_ERROR: &str = "POD_VALUE_ERROR";
+const POD_SIGN_ERROR: &str = "POD_SIGN_ERROR";
+// Async task rejections cannot carry a custom `code` property, so these are
+// prefixed onto the rejection reason as "CODE: message" instead.
+const POD_SOLVE_ERROR: &str = "POD_SOLVE_ERROR";
+const POD_UNSATISFIABLE: &str = "POD_UNSATISFIABLE";
+
+fn pod_error(code: &str, err: impl std::fmt::Display) -> Error<String> {
+  Error::new(code.to_string(), err.to_string())
+}
+
+/// Palette-indexed avatar grid; see `blockiesSeed`
+#[napi(object)]
+pub struct BlockiesSeed {
+  /// Row-major palette indices into `colors`
+  pub cells: Vec<Vec<u8>>,
+  /// `#rrggbb` colors in first-appearance order
+  pub colors: Vec<String>,
+}
+
+impl From<pod2_blockies::BlockiesSeed> for BlockiesSeed {
+  fn from(seed: pod2_blockies::BlockiesSeed) -> Self {
+    BlockiesSeed {
+      cells: seed.cells,
+      colors: seed.colors,
+    }
+  }
+}
+
+/// Avatar seed data for an arbitrary identifier (e.g. a signer public key).
+/// The derivation is shared with the desktop client, so both render the
+/// same avatar for the same input; rendering itself stays in JS.
+#[napi]
+pub fn blockies_seed(seed: String) -> BlockiesSeed {
+  pod2_blockies::seed_data(&seed).into()
+}
+
+/// Format a Podlang failure, pointing at the offending line and column when
+/// the parser reports one
+fn lang_error_message(err: &LangError) -> String {
+  match err {
+    LangError::Parse(parse_error) => {
+      let parser::ParseError::Pest(pest_error) = &**parse_error;
+      let (line, column) = match pest_error.line_col {
+        pest::error::LineColLocation::Pos((l, c)) => (l, c),
+        pest::error::LineColLocation::Span((l, c), _) => (l, c),
+      };
+      format!(
+        "line {line}, column {column}: {}",
+        pest_error.variant.message()
+      )
+    }
+    other => other.to_string(),
+  }
+}
+
 #[napi]
 #[allow(unused)]
 pub struct MainPod {
@@ -15,9 +105,10 @@ pub struct MainPod {
 #[napi]
 impl MainPod {
   #[napi(factory)]
-  pub fn deserialize(serialized_pod: String) -> Self {
-    let main_pod: Pod2MainPod = serde_json::from_str(serialized_pod.as_str()).unwrap();
-    MainPod { inner: main_pod }
+  pub fn deserialize(serialized_pod: String) -> Result<Self, String> {
+    let main_pod: Pod2MainPod = serde_json::from_str(serialized_pod.as_str())
+      .map_err(|e| pod_error(POD_DESERIALIZE_ERROR, e))?;
+    Ok(MainPod { inner: main_pod })
   }
 
   #[napi]
@@ -25,8 +116,669 @@ impl MainPod {
     self.inner.pod.verify().is_ok()
   }
 
+  /// Like `verify`, but throws an error with code POD_VERIFY_ERROR and the
+  /// underlying failure reason instead of returning false
+  #[napi]
+  pub fn verify_detailed(&self) -> Result<(), String> {
+    self
+      .inner
+      .pod
+      .verify()
+      .map_err(|e| pod_error(POD_VERIFY_ERROR, e))
+  }
+
+  #[napi]
+  pub fn public_statements(&self) -> Result<JsonValue, String> {
+    serde_json::to_value(self.inner.pod.pub_statements())
+      .map_err(|e| pod_error(POD_SERIALIZE_ERROR, e))
+  }
+
+  /// Like `verify`, but runs on the libuv thread pool so verifying many
+  /// pods doesn't block the JS event loop
+  #[napi(ts_return_type = "Promise<boolean>")]
+  pub fn verify_async(&self) -> AsyncTask<VerifyTask> {
+    AsyncTask::new(VerifyTask {
+      pod: self.inner.clone(),
+    })
+  }
+
+  /// Hex id of the pod (its statements hash)
+  #[napi]
+  pub fn id(&self) -> String {
+    self.inner.id().0.encode_hex()
+  }
+
+  /// Hex hash of the pod's public statements — the value other pods commit
+  /// to when they reference this one
+  #[napi]
+  pub fn statements_hash(&self) -> String {
+    self.inner.statements_hash().encode_hex()
+  }
+
+  /// Avatar seed data derived from the pod id, matching `blockiesSeed`
+  #[napi]
+  pub fn blockies_seed(&self) -> BlockiesSeed {
+    pod2_blockies::seed_data(&self.id()).into()
+  }
+
+  /// The canonical JSON serialization, as accepted by `deserialize`
+  #[napi]
+  pub fn serialize(&self) -> Result<String, String> {
+    serde_json::to_string(&self.inner).map_err(|e| pod_error(POD_SERIALIZE_ERROR, e))
+  }
+
+  /// The same pod as `serialize` in version-prefixed CBOR, at roughly half
+  /// the JSON size; `pod_utils::serialization` is the Rust side of the
+  /// format, so Rust services can decode these buffers directly
+  #[napi]
+  pub fn to_bytes(&self) -> Result<Buffer, String> {
+    serialization::to_bytes(&self.inner)
+      .map(Buffer::from)
+      .map_err(|e| pod_error(POD_SERIALIZE_ERROR, e))
+  }
+
+  /// Decode a buffer produced by `toBytes` (or by the Rust helper).
+  /// Truncated buffers and unknown format versions are rejected.
+  #[napi(factory)]
+  pub fn from_bytes(bytes: Buffer) -> Result<Self, String> {
+    let main_pod: Pod2MainPod =
+      serialization::from_bytes(bytes.as_ref()).map_err(|e| pod_error(POD_DESERIALIZE_ERROR, e))?;
+    Ok(MainPod { inner: main_pod })
+  }
+}
+
+pub struct VerifyTask {
+  pod: Pod2MainPod,
+}
+
+impl Task for VerifyTask {
+  type Output = bool;
+  type JsValue = bool;
+
+  fn compute(&mut self) -> Result<Self::Output> {
+    Ok(self.pod.pod.verify().is_ok())
+  }
+
+  fn resolve(&mut self, _env: Env, output: Self::Output) -> Result<Self::JsValue> {
+    Ok(output)
+  }
+}
+
+#[napi(object)]
+pub struct VerifyBatchEntry {
+  pub ok: bool,
+  /// Why deserialization or verification failed, when `ok` is false
+  pub error: Option<String>,
+}
+
+pub struct VerifyBatchTask {
+  pods: Vec<String>,
+  concurrency: Option<u32>,
+}
+
+impl Task for VerifyBatchTask {
+  type Output = Vec<VerifyBatchEntry>;
+  type JsValue = Vec<VerifyBatchEntry>;
+
+  fn compute(&mut self) -> Result<Self::Output> {
+    let verify_one = |serialized: &String| match serde_json::from_str::<Pod2MainPod>(serialized) {
+      Err(e) => VerifyBatchEntry {
+        ok: false,
+        error: Some(e.to_string()),
+      },
+      Ok(pod) => match pod.pod.verify() {
+        Ok(()) => VerifyBatchEntry {
+          ok: true,
+          error: None,
+        },
+        Err(e) => VerifyBatchEntry {
+          ok: false,
+          error: Some(e.to_string()),
+        },
+      },
+    };
+
+    let run = || self.pods.par_iter().map(verify_one).collect();
+    match self.concurrency {
+      Some(threads) => rayon::ThreadPoolBuilder::new()
+        .num_threads(threads as usize)
+        .build()
+        .map_err(|e| Error::from_reason(e.to_string()))?
+        .install(run),
+      None => run(),
+    }
+  }
+
+  fn resolve(&mut self, _env: Env, output: Self::Output) -> Result<Self::JsValue> {
+    Ok(output)
+  }
+}
+
+/// Deserialize and verify many pods on a rayon pool, off the JS thread.
+/// Results preserve input order; a pod that fails to deserialize or verify
+/// yields `{ ok: false, error }` instead of rejecting the whole batch.
+/// `concurrency` caps the worker threads (default: rayon's global pool).
+#[napi(ts_return_type = "Promise<Array<VerifyBatchEntry>>")]
+pub fn verify_batch(
+  serialized_pods: Vec<String>,
+  concurrency: Option<u32>,
+) -> AsyncTask<VerifyBatchTask> {
+  AsyncTask::new(VerifyBatchTask {
+    pods: serialized_pods,
+    concurrency,
+  })
+}
+
+/// A parsed Podlang REQUEST. Lets a service receiving a MainPod check that
+/// the pod proves exactly the requested statements and read back the
+/// wildcard bindings.
+#[napi]
+pub struct PodRequest {
+  inner: pod2::frontend::PodRequest,
+}
+
+#[napi]
+impl PodRequest {
+  /// Parse Podlang source into a request. `customBatches` are Podlang
+  /// sources for custom predicate batches the request refers to, as
+  /// produced by `CustomPredicateBatch::to_podlang_string`; earlier batches
+  /// are in scope when later ones are parsed.
+  #[napi(factory)]
+  pub fn parse(source: String, custom_batches: Option<Vec<String>>) -> Result<Self, String> {
+    let params = Params::default();
+    let mut batches: Vec<Arc<CustomPredicateBatch>> = Vec::new();
+    for batch_source in custom_batches.unwrap_or_default() {
+      let parsed = lang::parse(&batch_source, &params, &batches)
+        .map_err(|e| pod_error(POD_REQUEST_PARSE_ERROR, lang_error_message(&e)))?;
+      batches.push(parsed.custom_batch);
+    }
+    let output = lang::parse(&source, &params, &batches)
+      .map_err(|e| pod_error(POD_REQUEST_PARSE_ERROR, lang_error_message(&e)))?;
+    Ok(PodRequest {
+      inner: output.request,
+    })
+  }
+
+  /// Check a MainPod against this request. Returns the wildcard bindings
+  /// when the pod's public statements satisfy the request exactly, or null
+  /// when they don't.
   #[napi]
-  pub fn public_statements(&self) -> JsonValue {
-    serde_json::to_value(self.inner.pod.pub_statements()).unwrap()
+  pub fn match_pod(&self, main_pod: &MainPod) -> Option<JsonValue> {
+    self
+      .inner
+      .exact_match_pod(&*main_pod.inner.pod)
+      .ok()
+      .map(|bindings| {
+        JsonValue::Object(
+          bindings
+            .iter()
+            .map(|(k, v)| (k.to_string(), value_to_js(v, true)))
+            .collect(),
+        )
+      })
+  }
+
+  /// The request's statement templates in pod2's JSON encoding, for
+  /// debugging and logging
+  #[napi]
+  pub fn templates_json(&self) -> Result<JsonValue, String> {
+    serde_json::to_value(self.inner.templates()).map_err(|e| pod_error(POD_SERIALIZE_ERROR, e))
+  }
+}
+
+/// Largest integer a JS Number can hold exactly
+const MAX_SAFE_INTEGER: u64 = 9_007_199_254_740_991;
+
+/// Convert a pod2 value into plain JSON, recursing into containers. Ints
+/// beyond Number.MAX_SAFE_INTEGER become decimal strings when
+/// `large_ints_as_strings` is set, so no precision is lost crossing into JS.
+fn value_to_js(value: &Value, large_ints_as_strings: bool) -> JsonValue {
+  match value.typed() {
+    TypedValue::String(s) => JsonValue::String(s.clone()),
+    TypedValue::Bool(b) => JsonValue::Bool(*b),
+    TypedValue::Int(i) => {
+      if large_ints_as_strings && i.unsigned_abs() > MAX_SAFE_INTEGER {
+        JsonValue::String(i.to_string())
+      } else {
+        JsonValue::from(*i)
+      }
+    }
+    TypedValue::PublicKey(pk) => JsonValue::String(pk.to_string()),
+    TypedValue::Dictionary(dict) => JsonValue::Object(
+      dict
+        .kvs()
+        .iter()
+        .map(|(k, v)| (k.name().to_string(), value_to_js(v, large_ints_as_strings)))
+        .collect(),
+    ),
+    TypedValue::Array(array) => JsonValue::Array(
+      array
+        .array()
+        .iter()
+        .map(|v| value_to_js(v, large_ints_as_strings))
+        .collect(),
+    ),
+    TypedValue::Set(set) => {
+      // Sets have no inherent order; sort the converted items so entries()
+      // output is stable across calls
+      let mut items: Vec<JsonValue> = set
+        .set()
+        .iter()
+        .map(|v| value_to_js(v, large_ints_as_strings))
+        .collect();
+      items.sort_by_key(|v| v.to_string());
+      JsonValue::Array(items)
+    }
+    // Raw values and any future variants keep pod2's own JSON encoding
+    other => serde_json::to_value(other).unwrap_or(JsonValue::Null),
   }
 }
+
+// Markers for JS values JSON cannot express directly; see SignedPodBuilder
+const SET_MARKER: &str = "$set";
+const INT_MARKER: &str = "$int";
+
+/// Convert a JS value into a pod2 value, recursing into containers. Floats
+/// and nulls have no pod2 representation and are rejected; nesting beyond
+/// the params' merkle depth is refused up front so the error names the
+/// problem instead of surfacing as a failed tree build.
+fn js_to_value(
+  json: &JsonValue,
+  params: &Params,
+  depth: usize,
+) -> std::result::Result<Value, String> {
+  let max_depth = params.max_depth_mt_containers;
+  if depth >= max_depth {
+    return Err(format!("containers nested deeper than {max_depth} levels"));
+  }
+  match json {
+    JsonValue::Null => Err("null has no pod2 representation".to_string()),
+    JsonValue::Bool(b) => Ok(Value::from(*b)),
+    JsonValue::Number(n) => n
+      .as_i64()
+      .map(Value::from)
+      .ok_or_else(|| format!("{n} is not an integer; floats are not supported")),
+    JsonValue::String(s) => Ok(Value::from(s.as_str())),
+    JsonValue::Array(items) => {
+      let converted = items
+        .iter()
+        .map(|item| js_to_value(item, params, depth + 1))
+        .collect::<std::result::Result<Vec<Value>, String>>()?;
+      Array::new(max_depth, converted)
+        .map(Value::from)
+        .map_err(|e| format!("{e:?}"))
+    }
+    JsonValue::Object(map) => {
+      if let Some(marker) = map.get(SET_MARKER) {
+        if map.len() != 1 {
+          return Err(format!("\"{SET_MARKER}\" cannot be mixed with other keys"));
+        }
+        let JsonValue::Array(items) = marker else {
+          return Err(format!("\"{SET_MARKER}\" must hold an array of elements"));
+        };
+        let converted = items
+          .iter()
+          .map(|item| js_to_value(item, params, depth + 1))
+          .collect::<std::result::Result<HashSet<Value>, String>>()?;
+        return Set::new(max_depth, converted)
+          .map(Value::from)
+          .map_err(|e| format!("{e:?}"));
+      }
+      if let Some(marker) = map.get(INT_MARKER) {
+        if map.len() != 1 {
+          return Err(format!("\"{INT_MARKER}\" cannot be mixed with other keys"));
+        }
+        let JsonValue::String(digits) = marker else {
+          return Err(format!("\"{INT_MARKER}\" must hold a decimal string"));
+        };
+        return digits
+          .parse::<i64>()
+          .map(Value::from)
+          .map_err(|_| format!("\"{INT_MARKER}\" value '{digits}' is not a decimal i64"));
+      }
+      let kvs = map
+        .iter()
+        .map(|(k, v)| Ok((Key::from(k.as_str()), js_to_value(v, params, depth + 1)?)))
+        .collect::<std::result::Result<HashMap<Key, Value>, String>>()?;
+      Dictionary::new(max_depth, kvs)
+        .map(Value::from)
+        .map_err(|e| format!("{e:?}"))
+    }
+  }
+}
+
+/// Schnorr signer for producing SignedPods from Node (e.g. a bot issuing
+/// attestations). The secret key lives only in native memory and cannot be
+/// read back out once passed in.
+#[napi]
+pub struct Signer {
+  inner: PodSigner,
+}
+
+#[napi]
+impl Signer {
+  /// Build a signer from a hex-encoded secret key (optionally 0x-prefixed).
+  /// "Unsafe" refers to key handling, not memory safety: handing a raw key
+  /// to JS code is only appropriate for trusted automation, and sourcing the
+  /// hex from somewhere secret is the caller's responsibility.
+  #[napi(factory)]
+  pub fn from_secret_key_hex_unsafe(secret_key_hex: String) -> Result<Self, String> {
+    let digits = secret_key_hex.trim().trim_start_matches("0x");
+    let key = BigUint::parse_bytes(digits.as_bytes(), 16)
+      .ok_or_else(|| pod_error(POD_SIGN_ERROR, "secret key is not valid hex"))?;
+    if key.bits() == 0 {
+      return Err(pod_error(POD_SIGN_ERROR, "secret key must be non-zero"));
+    }
+    Ok(Signer {
+      inner: PodSigner(SecretKey(key)),
+    })
+  }
+
+  /// The corresponding public key as a string, matching `SignedPod.signer`
+  #[napi]
+  pub fn public_key(&self) -> String {
+    self.inner.0.public_key().to_string()
+  }
+}
+
+/// Accumulates entries for a SignedPod. JS values are converted to pod2
+/// values on `insert`: strings, booleans, and safe integers map directly,
+/// arrays and plain objects become pod2 arrays and dictionaries, and two
+/// markers cover what JSON cannot express — `{ "$set": [...] }` for sets and
+/// `{ "$int": "decimal string" }` for integers beyond
+/// Number.MAX_SAFE_INTEGER (BigInts do not survive the JSON bridge, so pass
+/// them as marker strings).
+#[napi]
+pub struct SignedPodBuilder {
+  params: Params,
+  builder: SignedDictBuilder,
+}
+
+impl Default for SignedPodBuilder {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+#[napi]
+impl SignedPodBuilder {
+  #[napi(constructor)]
+  pub fn new() -> Self {
+    let params = Params::default();
+    let builder = SignedDictBuilder::new(&params);
+    SignedPodBuilder { params, builder }
+  }
+
+  /// Insert an entry, replacing any previous value for the key. Throws an
+  /// error with code POD_VALUE_ERROR for values with no pod2 equivalent:
+  /// floats, null, malformed markers, and over-deep nesting.
+  #[napi]
+  pub fn insert(&mut self, key: String, value: JsonValue) -> Result<(), String> {
+    let converted = js_to_value(&value, &self.params, 0)
+      .map_err(|e| pod_error(POD_VALUE_ERROR, format!("entry '{key}': {e}")))?;
+    self.builder.insert(key.as_str(), converted);
+    Ok(())
+  }
+
+  /// Sign the collected entries, producing a SignedPod that verifies like
+  /// any other
+  #[napi]
+  pub fn sign(&self, signer: &Signer) -> Result<SignedPod, String> {
+    let signed_dict = self
+      .builder
+      .sign(&signer.inner)
+      .map_err(|e| pod_error(POD_SIGN_ERROR, e))?;
+    Ok(SignedPod { inner: signed_dict })
+  }
+}
+
+#[napi]
+pub struct SignedPod {
+  inner: SignedDict,
+}
+
+#[napi]
+impl SignedPod {
+  #[napi(factory)]
+  pub fn deserialize(serialized_pod: String) -> Result<Self, String> {
+    let signed_dict: SignedDict = serde_json::from_str(serialized_pod.as_str())
+      .map_err(|e| pod_error(POD_DESERIALIZE_ERROR, e))?;
+    Ok(SignedPod { inner: signed_dict })
+  }
+
+  #[napi]
+  pub fn verify(&self) -> bool {
+    self.inner.verify().is_ok()
+  }
+
+  /// Like `verify`, but throws an error with code POD_VERIFY_ERROR and the
+  /// underlying failure reason instead of returning false
+  #[napi]
+  pub fn verify_detailed(&self) -> Result<(), String> {
+    self
+      .inner
+      .verify()
+      .map_err(|e| pod_error(POD_VERIFY_ERROR, e))
+  }
+
+  /// Hex commitment of the signed dictionary
+  #[napi]
+  pub fn id(&self) -> String {
+    self.inner.dict.commitment().encode_hex()
+  }
+
+  /// The merkle commitment the signature covers; identical to `id`, named
+  /// for symmetry with `MainPod.statementsHash`
+  #[napi]
+  pub fn commitment(&self) -> String {
+    self.id()
+  }
+
+  /// The signer's public key as a string
+  #[napi]
+  pub fn signer(&self) -> String {
+    self.inner.public_key.to_string()
+  }
+
+  /// Avatar seed data derived from the signer's public key, the same
+  /// derivation the desktop client uses for identity avatars
+  #[napi]
+  pub fn blockies_seed(&self) -> BlockiesSeed {
+    pod2_blockies::seed_data(&self.signer()).into()
+  }
+
+  /// All entries as a plain object of key to JSON value, containers
+  /// converted recursively. Pass `largeIntsAsStrings: false` to get lossy
+  /// Numbers for ints beyond Number.MAX_SAFE_INTEGER instead of strings.
+  #[napi]
+  pub fn entries(&self, large_ints_as_strings: Option<bool>) -> JsonValue {
+    let as_strings = large_ints_as_strings.unwrap_or(true);
+    JsonValue::Object(
+      self
+        .inner
+        .dict
+        .kvs()
+        .iter()
+        .map(|(k, v)| (k.name().to_string(), value_to_js(v, as_strings)))
+        .collect(),
+    )
+  }
+
+  /// A single entry converted like `entries`, or null when absent
+  #[napi]
+  pub fn get(&self, key: String, large_ints_as_strings: Option<bool>) -> Option<JsonValue> {
+    self
+      .inner
+      .get(key.as_str())
+      .map(|v| value_to_js(v, large_ints_as_strings.unwrap_or(true)))
+  }
+
+  /// The canonical JSON serialization, as accepted by `deserialize`
+  #[napi]
+  pub fn serialize(&self) -> Result<String, String> {
+    serde_json::to_string(&self.inner).map_err(|e| pod_error(POD_SERIALIZE_ERROR, e))
+  }
+
+  /// Version-prefixed CBOR encoding of the signed dictionary, sharing the
+  /// wire format with `pod_utils::serialization` on the Rust side
+  #[napi]
+  pub fn to_bytes(&self) -> Result<Buffer, String> {
+    serialization::to_bytes(&self.inner)
+      .map(Buffer::from)
+      .map_err(|e| pod_error(POD_SERIALIZE_ERROR, e))
+  }
+
+  /// Decode a buffer produced by `toBytes` (or by the Rust helper).
+  /// Truncated buffers and unknown format versions are rejected.
+  #[napi(factory)]
+  pub fn from_bytes(bytes: Buffer) -> Result<Self, String> {
+    let signed_dict: SignedDict =
+      serialization::from_bytes(bytes.as_ref()).map_err(|e| pod_error(POD_DESERIALIZE_ERROR, e))?;
+    Ok(SignedPod { inner: signed_dict })
+  }
+}
+
+#[napi(object)]
+pub struct SolveOptions {
+  /// Build and return a mock-proved MainPod (JSON) instead of the
+  /// operation summary. Mock proofs are for testing only.
+  pub build_mock: Option<bool>,
+}
+
+/// How a solved statement is obtained, without the premise trees
+fn op_tag_json(tag: &OpTag) -> JsonValue {
+  match tag {
+    OpTag::CopyStatement { source } => serde_json::json!({
+      "op": "copy_statement",
+      "sourcePod": source.0.encode_hex::<String>(),
+    }),
+    OpTag::FromLiterals => serde_json::json!({ "op": "from_literals" }),
+    OpTag::Derived { .. } => serde_json::json!({ "op": "derived" }),
+    OpTag::CustomDeduction { .. } => serde_json::json!({ "op": "custom_deduction" }),
+    OpTag::GeneratedContains { .. } => serde_json::json!({ "op": "generated_contains" }),
+    OpTag::GeneratedPublicKeyOf { .. } => serde_json::json!({ "op": "generated_public_key_of" }),
+  }
+}
+
+/// Summarize a solved answer: the statements to prove, how each one is
+/// obtained, and which input pods the proof relies on.
+fn answer_to_js(answer: &ConstraintStore) -> std::result::Result<JsonValue, serde_json::Error> {
+  let statements = answer
+    .premises
+    .iter()
+    .map(|(statement, tag)| {
+      let mut entry = op_tag_json(tag);
+      if let JsonValue::Object(map) = &mut entry {
+        map.insert("statement".to_string(), serde_json::to_value(statement)?);
+      }
+      Ok(entry)
+    })
+    .collect::<std::result::Result<Vec<_>, serde_json::Error>>()?;
+
+  Ok(serde_json::json!({
+    "statements": statements,
+    "inputPods": answer
+      .required_pods()
+      .iter()
+      .map(|pod_ref| pod_ref.0.encode_hex::<String>())
+      .collect::<Vec<String>>(),
+    "operationCount": answer.operation_count,
+  }))
+}
+
+pub struct SolveTask {
+  processed: PodlangOutput,
+  signed_dicts: Vec<SignedDict>,
+  main_pods: Vec<Pod2MainPod>,
+  build_mock: bool,
+}
+
+impl Task for SolveTask {
+  type Output = JsonValue;
+  type JsValue = JsonValue;
+
+  fn compute(&mut self) -> Result<Self::Output> {
+    let params = Params::default();
+    let mut edb_builder = ImmutableEdbBuilder::new();
+    for dict in &self.signed_dicts {
+      edb_builder = edb_builder.add_signed_dict(dict.clone());
+    }
+    for pod in &self.main_pods {
+      edb_builder = edb_builder.add_main_pod(pod);
+    }
+    let edb = edb_builder.build();
+
+    let registry = OpRegistry::default();
+    let config = EngineConfigBuilder::new().from_params(&params).build();
+    let mut engine = Engine::with_config(&registry, &edb, config);
+    engine.load_processed(&self.processed);
+    engine
+      .run()
+      .map_err(|e| Error::from_reason(format!("{POD_SOLVE_ERROR}: {e}")))?;
+
+    let Some(answer) = engine.answers.first() else {
+      return Err(Error::from_reason(format!(
+        "{POD_UNSATISFIABLE}: the request has no solution with the provided pods"
+      )));
+    };
+
+    if self.build_mock {
+      #[allow(clippy::borrow_interior_mutable_const)]
+      let pod = build_pod_from_answer_top_level_public(
+        answer,
+        &params,
+        &MOCK_VD_SET,
+        |b| b.prove(&MockProver {}).map_err(|e| e.to_string()),
+        &edb,
+      )
+      .map_err(|e| Error::from_reason(format!("{POD_SOLVE_ERROR}: {e}")))?;
+      serde_json::to_value(&pod)
+        .map_err(|e| Error::from_reason(format!("{POD_SERIALIZE_ERROR}: {e}")))
+    } else {
+      answer_to_js(answer).map_err(|e| Error::from_reason(format!("{POD_SERIALIZE_ERROR}: {e}")))
+    }
+  }
+
+  fn resolve(&mut self, _env: Env, output: Self::Output) -> Result<Self::JsValue> {
+    Ok(output)
+  }
+}
+
+/// Parse a Podlang request and solve it against the provided serialized pods
+/// (each entry a SignedPod or MainPod JSON). The search runs on the libuv
+/// thread pool so long solves don't block the JS event loop. Parse and
+/// deserialize failures throw coded errors synchronously; async failures
+/// reject with a "CODE: message" reason (POD_SOLVE_ERROR, POD_UNSATISFIABLE).
+#[napi(ts_return_type = "Promise<JsonValue>")]
+pub fn solve(
+  request_source: String,
+  pods: Vec<String>,
+  options: Option<SolveOptions>,
+) -> Result<AsyncTask<SolveTask>, String> {
+  let params = Params::default();
+  let processed = lang::parse(&request_source, &params, &[])
+    .map_err(|e| pod_error(POD_REQUEST_PARSE_ERROR, lang_error_message(&e)))?;
+
+  let mut signed_dicts = Vec::new();
+  let mut main_pods = Vec::new();
+  for (index, serialized) in pods.iter().enumerate() {
+    if let Ok(dict) = serde_json::from_str::<SignedDict>(serialized) {
+      signed_dicts.push(dict);
+    } else {
+      let main_pod = serde_json::from_str::<Pod2MainPod>(serialized).map_err(|e| {
+        pod_error(
+          POD_DESERIALIZE_ERROR,
+          format!("pod {index} is neither a SignedPod nor a MainPod: {e}"),
+        )
+      })?;
+      main_pods.push(main_pod);
+    }
+  }
+
+  Ok(AsyncTask::new(SolveTask {
+    processed,
+    signed_dicts,
+    main_pods,
+    build_mock: options.and_then(|o| o.build_mock).unwrap_or(false),
+  }))
+}
@@ -390,6 +390,7 @@ export interface PodInfo {
   label?: string | null;
   pod_type: string;
   space: string;
+  updated_at: string;
 }
 export interface SignedDict {
   dict: Dictionary;
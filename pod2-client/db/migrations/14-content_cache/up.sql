@@ -0,0 +1,11 @@
+-- Local cache of content blobs staged for drafts or fetched for pods.
+-- Each entry records the draft and/or pod that references it so orphaned
+-- blobs can be garbage collected once the referencing row is gone.
+
+CREATE TABLE content_cache (
+    hash TEXT PRIMARY KEY,
+    content BLOB NOT NULL,
+    draft_id TEXT,
+    pod_id TEXT,
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP
+);
@@ -0,0 +1 @@
+DROP TABLE ui_state;
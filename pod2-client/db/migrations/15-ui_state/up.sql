@@ -0,0 +1,9 @@
+-- Last selection the user had open (space and pod), restored by the
+-- frontend on startup. Single-row table keyed by id = 1.
+
+CREATE TABLE ui_state (
+    id INTEGER PRIMARY KEY CHECK (id = 1),
+    selected_space TEXT,
+    selected_pod_id TEXT,
+    updated_at DATETIME DEFAULT CURRENT_TIMESTAMP
+);
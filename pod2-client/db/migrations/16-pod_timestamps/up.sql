@@ -0,0 +1,5 @@
+-- Track when a pod was last renamed or moved, alongside the existing
+-- import-time created_at. Existing rows default to their import time.
+
+ALTER TABLE pods ADD COLUMN updated_at DATETIME;
+UPDATE pods SET updated_at = created_at;
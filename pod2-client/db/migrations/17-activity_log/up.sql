@@ -0,0 +1,10 @@
+-- Unified feed of pod mutations (imports, deletions, moves, renames) for
+-- the client's recent-activity view.
+
+CREATE TABLE activity_log (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    action TEXT NOT NULL,
+    pod_id TEXT NOT NULL,
+    space TEXT NOT NULL,
+    created_at DATETIME NOT NULL
+);
@@ -0,0 +1,4 @@
+-- Soft-delete tombstones for pods: a set deleted_at hides the pod from
+-- listings and search until store::purge_deleted removes the row for good.
+
+ALTER TABLE pods ADD COLUMN deleted_at DATETIME;
@@ -70,6 +70,32 @@ impl Db {
     pub fn pool(&self) -> &ConnectionPool {
         &self.pool
     }
+
+    /// Runs `f` inside a single SQLite transaction, committing on `Ok` and
+    /// rolling back on `Err`. The closure is synchronous because the
+    /// transaction cannot outlive one pooled-connection interaction; compose
+    /// multi-statement operations with the `*_with_conn` helpers in [`store`].
+    pub async fn with_transaction<R, F>(&self, f: F) -> Result<R>
+    where
+        F: FnOnce(&rusqlite::Transaction) -> Result<R> + Send + 'static,
+        R: Send + 'static,
+    {
+        let conn = self
+            .pool
+            .get()
+            .await
+            .context("Failed to get DB connection")?;
+
+        conn.interact(move |conn| -> Result<R> {
+            let tx = conn.transaction()?;
+            let result = f(&tx)?;
+            tx.commit().context("Failed to commit transaction")?;
+            Ok(result)
+        })
+        .await
+        .map_err(|e| anyhow::anyhow!("InteractError: {e}"))
+        .context("DB interaction failed for with_transaction")?
+    }
 }
 
 #[cfg(test)]
@@ -179,4 +205,580 @@ mod tests {
             "'spaces' table should persist in file DB",
         );
     }
+
+    #[tokio::test]
+    async fn test_rename_pod_persists_across_reopen() {
+        use pod2::{
+            backends::plonky2::{primitives::ec::schnorr::SecretKey, signer::Signer},
+            frontend::SignedDictBuilder,
+            middleware::Params,
+        };
+
+        let temp_file = NamedTempFile::new().unwrap();
+        let path_str = temp_file.path().to_str().unwrap();
+
+        let mut builder = SignedDictBuilder::new(&Params::default());
+        builder.insert("note", "hello");
+        let dict = builder
+            .sign(&Signer(SecretKey::new_rand()))
+            .expect("Failed to sign dict");
+        let pod_data = store::PodData::from(dict);
+        let pod_id = pod_data.id();
+
+        {
+            let db = Db::new(Some(path_str), &MIGRATIONS)
+                .await
+                .expect("Failed to initialize file DB");
+            store::create_space(&db, "test-space").await.unwrap();
+            store::import_pod(&db, &pod_data, Some("original"), "test-space")
+                .await
+                .unwrap();
+
+            assert!(
+                store::rename_pod(&db, "test-space", &pod_id, "renamed")
+                    .await
+                    .unwrap()
+            );
+            // Renaming an unknown pod updates nothing
+            assert!(
+                !store::rename_pod(&db, "test-space", "missing", "x")
+                    .await
+                    .unwrap()
+            );
+        }
+
+        // The new label survives a reopen of the database file
+        let db = Db::new(Some(path_str), &MIGRATIONS)
+            .await
+            .expect("Failed to reopen file DB");
+        let pod = store::get_pod(&db, "test-space", &pod_id)
+            .await
+            .unwrap()
+            .expect("pod should persist");
+        assert_eq!(pod.label.as_deref(), Some("renamed"));
+
+        // An empty name clears the custom label
+        assert!(
+            store::rename_pod(&db, "test-space", &pod_id, "   ")
+                .await
+                .unwrap()
+        );
+        let pod = store::get_pod(&db, "test-space", &pod_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(pod.label, None);
+    }
+
+    #[tokio::test]
+    async fn test_gc_orphaned_content() {
+        let db = Db::new(None, &MIGRATIONS)
+            .await
+            .expect("Failed to initialize in-memory DB");
+
+        let draft_id = store::create_draft(
+            &db,
+            store::CreateDraftRequest {
+                title: "Draft with attachment".to_string(),
+                content_type: "file".to_string(),
+                message: None,
+                file_name: Some("attachment.bin".to_string()),
+                file_content: Some(vec![1, 2, 3]),
+                file_mime_type: Some("application/octet-stream".to_string()),
+                url: None,
+                tags: vec![],
+                authors: vec![],
+                reply_to: None,
+            },
+        )
+        .await
+        .expect("Failed to create draft");
+
+        store::cache_content(&db, "referenced", vec![0u8; 16], Some(draft_id), None)
+            .await
+            .expect("Failed to cache referenced blob");
+        store::cache_content(&db, "orphaned", vec![0u8; 64], Some("gone".to_string()), None)
+            .await
+            .expect("Failed to cache orphaned blob");
+
+        let reclaimed = store::gc_orphaned_content(&db)
+            .await
+            .expect("GC should succeed");
+        assert_eq!(reclaimed, 64, "only the orphaned blob should be reclaimed");
+
+        let conn = db.pool().get().await.expect("Failed to get connection");
+        let remaining: Vec<String> = conn
+            .interact(|conn| -> Result<Vec<String>, rusqlite::Error> {
+                let mut stmt = conn.prepare("SELECT hash FROM content_cache")?;
+                let hashes = stmt
+                    .query_map([], |row| row.get(0))?
+                    .collect::<Result<Vec<String>, _>>()?;
+                Ok(hashes)
+            })
+            .await
+            .expect("Interaction failed")
+            .expect("Query failed");
+        assert_eq!(remaining, vec!["referenced".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_with_transaction_rolls_back_on_error() {
+        let db = Db::new(None, &MIGRATIONS)
+            .await
+            .expect("Failed to initialize in-memory DB");
+
+        let result = db
+            .with_transaction(|tx| -> Result<()> {
+                tx.execute(
+                    "INSERT INTO spaces (id, created_at) VALUES ('tx-a', '2025-01-01T00:00:00Z')",
+                    [],
+                )?;
+                tx.execute(
+                    "INSERT INTO spaces (id, created_at) VALUES ('tx-b', '2025-01-01T00:00:00Z')",
+                    [],
+                )?;
+                anyhow::bail!("abort after both inserts")
+            })
+            .await;
+        assert!(result.is_err());
+
+        // Neither insert survived the rollback
+        assert!(!store::space_exists(&db, "tx-a").await.unwrap());
+        assert!(!store::space_exists(&db, "tx-b").await.unwrap());
+
+        // A successful closure commits
+        db.with_transaction(|tx| {
+            tx.execute(
+                "INSERT INTO spaces (id, created_at) VALUES ('tx-a', '2025-01-01T00:00:00Z')",
+                [],
+            )?;
+            Ok(())
+        })
+        .await
+        .expect("Transaction should commit");
+        assert!(store::space_exists(&db, "tx-a").await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_ui_state_round_trips() {
+        let db = Db::new(None, &MIGRATIONS)
+            .await
+            .expect("Failed to initialize in-memory DB");
+
+        // Nothing stored yet: selection is empty
+        let initial = store::get_ui_state(&db).await.unwrap();
+        assert_eq!(initial, store::UiState::default());
+
+        let selection = store::UiState {
+            selected_space: Some("work".to_string()),
+            selected_pod_id: Some("abc123".to_string()),
+        };
+        store::set_ui_state(&db, selection.clone()).await.unwrap();
+        assert_eq!(store::get_ui_state(&db).await.unwrap(), selection);
+
+        // A later write replaces the single stored selection
+        let cleared = store::UiState {
+            selected_space: Some("personal".to_string()),
+            selected_pod_id: None,
+        };
+        store::set_ui_state(&db, cleared.clone()).await.unwrap();
+        assert_eq!(store::get_ui_state(&db).await.unwrap(), cleared);
+    }
+
+    #[tokio::test]
+    async fn test_list_all_pods_sorted() {
+        use pod2::{
+            backends::plonky2::{primitives::ec::schnorr::SecretKey, signer::Signer},
+            frontend::SignedDictBuilder,
+            middleware::Params,
+        };
+
+        let db = Db::new(None, &MIGRATIONS)
+            .await
+            .expect("Failed to initialize in-memory DB");
+        store::create_space(&db, "test-space").await.unwrap();
+
+        let signed_pod_data = |note: &str| {
+            let mut builder = SignedDictBuilder::new(&Params::default());
+            builder.insert("note", note);
+            let dict = builder
+                .sign(&Signer(SecretKey::new_rand()))
+                .expect("Failed to sign dict");
+            store::PodData::from(dict)
+        };
+
+        // Import in name order, then backdate so import order differs from it
+        let mut ids = Vec::new();
+        for (label, imported_at) in [
+            ("Apple", "2024-01-01 00:00:00"),
+            ("banana", "2024-02-01 00:00:00"),
+            ("cherry", "2024-03-01 00:00:00"),
+        ] {
+            let pod_data = signed_pod_data(label);
+            let pod_id = pod_data.id();
+            store::import_pod(&db, &pod_data, Some(label), "test-space")
+                .await
+                .unwrap();
+            let conn = db.pool().get().await.unwrap();
+            let id = pod_id.clone();
+            conn.interact(move |conn| {
+                conn.execute(
+                    "UPDATE pods SET created_at = ?1, updated_at = ?1 WHERE id = ?2",
+                    rusqlite::params![imported_at, id],
+                )
+            })
+            .await
+            .expect("DB interaction failed")
+            .unwrap();
+            ids.push(pod_id);
+        }
+
+        let labels = |pods: Vec<store::PodInfo>| {
+            pods.into_iter()
+                .map(|p| p.label.unwrap())
+                .collect::<Vec<_>>()
+        };
+
+        // Name order is case-insensitive
+        let by_name = store::list_all_pods_sorted(&db, store::PodSort::NameAsc)
+            .await
+            .unwrap();
+        assert_eq!(labels(by_name), ["Apple", "banana", "cherry"]);
+
+        let by_import = store::list_all_pods_sorted(&db, store::PodSort::NewestImported)
+            .await
+            .unwrap();
+        assert_eq!(labels(by_import), ["cherry", "banana", "Apple"]);
+
+        // Renaming bumps updated_at, so the renamed pod sorts first by modification
+        assert!(
+            store::rename_pod(&db, "test-space", &ids[0], "Apple v2")
+                .await
+                .unwrap()
+        );
+        let by_modified = store::list_all_pods_sorted(&db, store::PodSort::RecentlyModified)
+            .await
+            .unwrap();
+        assert_eq!(labels(by_modified), ["Apple v2", "cherry", "banana"]);
+    }
+
+    #[tokio::test]
+    async fn test_prepared_statement_cache() {
+        use pod2::{
+            backends::plonky2::{primitives::ec::schnorr::SecretKey, signer::Signer},
+            frontend::SignedDictBuilder,
+            middleware::Params,
+        };
+
+        let db = Db::new(None, &MIGRATIONS)
+            .await
+            .expect("Failed to initialize in-memory DB");
+        store::create_space(&db, "test-space").await.unwrap();
+
+        for note in ["a", "b", "c"] {
+            let mut builder = SignedDictBuilder::new(&Params::default());
+            builder.insert("note", note);
+            let dict = builder
+                .sign(&Signer(SecretKey::new_rand()))
+                .expect("Failed to sign dict");
+            store::import_pod(&db, &store::PodData::from(dict), Some(note), "test-space")
+                .await
+                .unwrap();
+        }
+
+        // Concurrent reads through the cached statements stay consistent
+        let (count, by_type, pods) = tokio::join!(
+            store::count_all_pods(&db),
+            store::count_pods_by_type(&db),
+            store::list_all_pods(&db),
+        );
+        assert_eq!(count.unwrap(), 3);
+        assert_eq!(by_type.unwrap(), (3, 0));
+        assert_eq!(pods.unwrap().len(), 3);
+
+        // Reuse check via sqlite's per-statement run counter: a freshly
+        // prepared statement starts at zero runs, so the counter only
+        // accumulates when `prepare_cached` hands back the same statement.
+        const ITERATIONS: usize = 50;
+        const SQL: &str = "SELECT id, pod_type, data, label, created_at, \
+             COALESCE(updated_at, created_at), space FROM pods ORDER BY created_at DESC";
+        let conn = db.pool().get().await.unwrap();
+        let (uncached_runs, cached_runs) = conn
+            .interact(|conn| {
+                let mut uncached_runs = 0;
+                for _ in 0..ITERATIONS {
+                    let mut stmt = conn.prepare(SQL)?;
+                    let rows = stmt.query_map([], |row| row.get::<_, String>(0))?.count();
+                    assert_eq!(rows, 3);
+                    uncached_runs += stmt.get_status(rusqlite::StatementStatus::Run) as usize;
+                }
+
+                for _ in 0..ITERATIONS {
+                    let mut stmt = conn.prepare_cached(SQL)?;
+                    let rows = stmt.query_map([], |row| row.get::<_, String>(0))?.count();
+                    assert_eq!(rows, 3);
+                }
+                let stmt = conn.prepare_cached(SQL)?;
+                let cached_runs = stmt.get_status(rusqlite::StatementStatus::Run) as usize;
+                Ok::<_, rusqlite::Error>((uncached_runs, cached_runs))
+            })
+            .await
+            .expect("DB interaction failed")
+            .unwrap();
+
+        // `prepare` parses anew each call, so no statement ever sees more
+        // than its own single run; the cached statement has seen all of them.
+        assert_eq!(uncached_runs, ITERATIONS);
+        assert_eq!(cached_runs, ITERATIONS);
+    }
+
+    #[tokio::test]
+    async fn test_count_pods_matching_agrees_with_search() {
+        use pod2::{
+            backends::plonky2::{primitives::ec::schnorr::SecretKey, signer::Signer},
+            frontend::SignedDictBuilder,
+            middleware::Params,
+        };
+
+        let db = Db::new(None, &MIGRATIONS)
+            .await
+            .expect("Failed to initialize in-memory DB");
+        store::create_space(&db, "test-space").await.unwrap();
+
+        let signed = |key: &str, value: &str| {
+            let mut builder = SignedDictBuilder::new(&Params::default());
+            builder.insert(key, value);
+            store::PodData::from(builder.sign(&Signer(SecretKey::new_rand())).unwrap())
+        };
+
+        store::import_pod(
+            &db,
+            &signed("note", "alpha contents"),
+            Some("alpha-label"),
+            "test-space",
+        )
+        .await
+        .unwrap();
+        store::import_pod(&db, &signed("note", "beta contents"), None, "test-space")
+            .await
+            .unwrap();
+        store::import_pod(&db, &signed("other", "gamma"), None, "test-space")
+            .await
+            .unwrap();
+
+        // The count always equals what an unpaginated search returns
+        for query in ["alpha", "contents", "gamma", "no-such-pod"] {
+            let results = store::search_pods(&db, query, "test-space").await.unwrap();
+            let count = store::count_pods_matching(&db, query, "test-space")
+                .await
+                .unwrap();
+            assert_eq!(count as usize, results.len(), "query {query:?}");
+        }
+
+        // Labels are matched as well as serialized data
+        assert_eq!(
+            store::count_pods_matching(&db, "alpha-label", "test-space")
+                .await
+                .unwrap(),
+            1
+        );
+        // Other spaces don't contribute to the count
+        assert_eq!(
+            store::count_pods_matching(&db, "contents", "another-space")
+                .await
+                .unwrap(),
+            0
+        );
+
+        // LIKE wildcards in the query are literals, not patterns
+        store::import_pod(&db, &signed("note", "sale 100% off"), None, "test-space")
+            .await
+            .unwrap();
+        store::import_pod(&db, &signed("note", "sale 100x off"), None, "test-space")
+            .await
+            .unwrap();
+        assert_eq!(
+            store::count_pods_matching(&db, "100%", "test-space")
+                .await
+                .unwrap(),
+            1
+        );
+        assert_eq!(
+            store::count_pods_matching(&db, "100_", "test-space")
+                .await
+                .unwrap(),
+            0
+        );
+    }
+
+    #[tokio::test]
+    async fn test_soft_delete_hides_pods_and_purge_respects_retention() {
+        use std::time::Duration;
+
+        use pod2::{
+            backends::plonky2::{primitives::ec::schnorr::SecretKey, signer::Signer},
+            frontend::SignedDictBuilder,
+            middleware::Params,
+        };
+
+        let db = Db::new(None, &MIGRATIONS)
+            .await
+            .expect("Failed to initialize in-memory DB");
+        store::create_space(&db, "test-space").await.unwrap();
+
+        let signed = |value: &str| {
+            let mut builder = SignedDictBuilder::new(&Params::default());
+            builder.insert("note", value);
+            store::PodData::from(builder.sign(&Signer(SecretKey::new_rand())).unwrap())
+        };
+
+        let keep = signed("keep");
+        let tombstone = signed("tombstone");
+        let keep_id = keep.id();
+        let tombstone_id = tombstone.id();
+        store::import_pod(&db, &keep, None, "test-space").await.unwrap();
+        store::import_pod(&db, &tombstone, None, "test-space")
+            .await
+            .unwrap();
+
+        assert!(
+            store::soft_delete_pod(&db, "test-space", &tombstone_id)
+                .await
+                .unwrap()
+        );
+        // Tombstoning the same pod again is a no-op
+        assert!(
+            !store::soft_delete_pod(&db, "test-space", &tombstone_id)
+                .await
+                .unwrap()
+        );
+
+        // Hidden from listings, search and counts, but still directly readable
+        let listed = store::list_pods(&db, "test-space").await.unwrap();
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].id, keep_id);
+        assert_eq!(store::count_all_pods(&db).await.unwrap(), 1);
+        assert_eq!(
+            store::count_pods_matching(&db, "tombstone", "test-space")
+                .await
+                .unwrap(),
+            0
+        );
+        assert!(
+            store::get_pod(&db, "test-space", &tombstone_id)
+                .await
+                .unwrap()
+                .is_some()
+        );
+
+        // A fresh tombstone is inside the retention window
+        assert_eq!(store::purge_deleted(&db, Duration::from_secs(3600)).await.unwrap(), 0);
+
+        // Backdate the tombstone past the window; now it gets reclaimed
+        let conn = db.pool().get().await.unwrap();
+        let backdated_id = tombstone_id.clone();
+        conn.interact(move |conn| {
+            conn.execute(
+                "UPDATE pods SET deleted_at = datetime('now', '-2 hours') WHERE id = ?1",
+                [&backdated_id],
+            )
+        })
+        .await
+        .unwrap()
+        .unwrap();
+
+        assert_eq!(store::purge_deleted(&db, Duration::from_secs(3600)).await.unwrap(), 1);
+        assert!(
+            store::get_pod(&db, "test-space", &tombstone_id)
+                .await
+                .unwrap()
+                .is_none()
+        );
+        assert_eq!(store::count_all_pods(&db).await.unwrap(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_replace_pod_preserves_metadata_and_updates_content() {
+        use pod2::{
+            backends::plonky2::{primitives::ec::schnorr::SecretKey, signer::Signer},
+            frontend::SignedDictBuilder,
+            middleware::Params,
+        };
+
+        let db = Db::new(None, &MIGRATIONS)
+            .await
+            .expect("Failed to initialize in-memory DB");
+        store::create_space(&db, "test-space").await.unwrap();
+
+        let signed = |value: &str| {
+            let mut builder = SignedDictBuilder::new(&Params::default());
+            builder.insert("revision", value);
+            store::PodData::from(builder.sign(&Signer(SecretKey::new_rand())).unwrap())
+        };
+
+        let original = signed("v1");
+        let original_id = original.id();
+        store::import_pod(&db, &original, Some("My Pod"), "test-space")
+            .await
+            .unwrap();
+
+        // Mark the row as a favorite so we can see the flag survive
+        let conn = db.pool().get().await.unwrap();
+        let flagged_id = original_id.clone();
+        conn.interact(move |conn| {
+            conn.execute(
+                "UPDATE pods SET is_mandatory = TRUE WHERE id = ?1",
+                [&flagged_id],
+            )
+        })
+        .await
+        .unwrap()
+        .unwrap();
+
+        let revised = signed("v2");
+        let revised_id = revised.id();
+        assert_ne!(original_id, revised_id);
+        assert!(
+            store::replace_pod(&db, "test-space", &original_id, &revised)
+                .await
+                .unwrap()
+        );
+
+        // The old id is gone; the row now carries the new pod's content
+        assert!(
+            store::get_pod(&db, "test-space", &original_id)
+                .await
+                .unwrap()
+                .is_none()
+        );
+        let replaced = store::get_pod(&db, "test-space", &revised_id)
+            .await
+            .unwrap()
+            .expect("replaced pod should be present under its new id");
+        assert_eq!(replaced.label.as_deref(), Some("My Pod"));
+        assert_eq!(replaced.data.id(), revised_id);
+
+        let conn = db.pool().get().await.unwrap();
+        let check_id = revised_id.clone();
+        let still_favorite: bool = conn
+            .interact(move |conn| {
+                conn.query_row(
+                    "SELECT is_mandatory FROM pods WHERE id = ?1",
+                    [&check_id],
+                    |row| row.get(0),
+                )
+            })
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(still_favorite);
+
+        // Replacing a pod that is not there reports false
+        assert!(
+            !store::replace_pod(&db, "test-space", &original_id, &signed("v3"))
+                .await
+                .unwrap()
+        );
+        assert_eq!(store::count_all_pods(&db).await.unwrap(), 1);
+    }
 }
@@ -88,8 +88,64 @@ pub struct PodInfo {
     pub pod_type: String,
     pub data: PodData,
     pub label: Option<String>,
+    /// Import time
     pub created_at: String,
+    /// Last rename or move
+    pub updated_at: String,
+    pub space: String,
+}
+
+/// Sort orders for [`list_all_pods_sorted`]
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PodSort {
+    NameAsc,
+    NewestImported,
+    RecentlyModified,
+}
+
+/// Pod mutations recorded in the activity log
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ActivityAction {
+    Import,
+    Delete,
+    Move,
+    Rename,
+    Replace,
+}
+
+impl ActivityAction {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Import => "import",
+            Self::Delete => "delete",
+            Self::Move => "move",
+            Self::Rename => "rename",
+            Self::Replace => "replace",
+        }
+    }
+
+    fn parse(s: &str) -> Option<Self> {
+        match s {
+            "import" => Some(Self::Import),
+            "delete" => Some(Self::Delete),
+            "move" => Some(Self::Move),
+            "rename" => Some(Self::Rename),
+            "replace" => Some(Self::Replace),
+            _ => None,
+        }
+    }
+}
+
+/// One entry in the unified recent-activity feed. For moves, `space` is the
+/// destination space.
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq)]
+pub struct ActivityEntry {
+    pub id: i64,
+    pub action: ActivityAction,
+    pub pod_id: String,
     pub space: String,
+    pub created_at: String,
 }
 
 pub async fn create_space(db: &Db, id: &str) -> Result<()> {
@@ -115,6 +171,22 @@ pub async fn create_space(db: &Db, id: &str) -> Result<()> {
     Ok(())
 }
 
+/// Synchronous variant of [`create_space`] for callers composing several
+/// writes atomically via [`Db::with_transaction`]. An existing space with the
+/// same id is left untouched.
+pub fn create_space_if_missing_with_conn(
+    conn: &rusqlite::Connection,
+    id: &str,
+    created_at: &str,
+) -> Result<()> {
+    conn.execute(
+        "INSERT OR IGNORE INTO spaces (id, created_at) VALUES (?1, ?2)",
+        rusqlite::params![id, created_at],
+    )
+    .context("Failed to insert space")?;
+    Ok(())
+}
+
 pub async fn list_spaces(db: &Db) -> Result<Vec<SpaceInfo>> {
     let conn = db
         .pool()
@@ -177,8 +249,31 @@ pub async fn delete_space(db: &Db, id: &str) -> Result<usize> {
 
 // --- Pod Queries ---
 
-pub async fn import_pod(
-    db: &Db,
+/// Append an entry to the activity log; called by every mutating pod
+/// operation within its own write
+fn log_activity(
+    conn: &rusqlite::Connection,
+    action: ActivityAction,
+    pod_id: &str,
+    space_id: &str,
+) -> Result<(), rusqlite::Error> {
+    conn.execute(
+        "INSERT INTO activity_log (action, pod_id, space, created_at) VALUES (?1, ?2, ?3, ?4)",
+        rusqlite::params![
+            action.as_str(),
+            pod_id,
+            space_id,
+            Utc::now().to_rfc3339()
+        ],
+    )?;
+    Ok(())
+}
+
+/// Synchronous variant of [`import_pod`] for callers composing several writes
+/// atomically via [`Db::with_transaction`]. A `rusqlite::Transaction` derefs
+/// to `Connection`, so it can be passed directly.
+pub fn import_pod_with_conn(
+    conn: &rusqlite::Connection,
     data: &PodData,
     label: Option<&str>,
     space_id: &str,
@@ -187,29 +282,45 @@ pub async fn import_pod(
     let data_blob =
         serde_json::to_vec(data).context("Failed to serialize PodData enum for storage")?;
 
+    let inserted = conn
+        .execute(
+            "INSERT OR IGNORE INTO pods (id, pod_type, data, label, created_at, updated_at, space) VALUES (?1, ?2, ?3, ?4, ?5, ?5, ?6)",
+            rusqlite::params![
+                data.id(),
+                data.type_str(),
+                data_blob,
+                label,
+                now,
+                space_id
+            ],
+        )
+        .context("Failed to insert pod")?;
+    if inserted > 0 {
+        log_activity(conn, ActivityAction::Import, &data.id(), space_id)
+            .context("Failed to log pod import")?;
+    }
+
+    Ok(())
+}
+
+pub async fn import_pod(
+    db: &Db,
+    data: &PodData,
+    label: Option<&str>,
+    space_id: &str,
+) -> Result<()> {
     let conn = db
         .pool()
         .get()
         .await
         .context("Failed to get DB connection")?;
 
+    let data_clone = data.clone();
     let label_clone = label.map(|s| s.to_string());
     let space_id_clone = space_id.to_string();
-    let type_str = data.type_str();
-    let id = data.id();
 
     conn.interact(move |conn| {
-        conn.execute(
-            "INSERT OR IGNORE INTO pods (id, pod_type, data, label, created_at, space) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
-            rusqlite::params![
-                id,
-                type_str,
-                data_blob,
-                label_clone,
-                now,
-                space_id_clone
-            ],
-        )
+        import_pod_with_conn(conn, &data_clone, label_clone.as_deref(), &space_id_clone)
     })
     .await
     .map_err(|e| anyhow::anyhow!("InteractError: {e}"))
@@ -230,7 +341,7 @@ pub async fn get_pod(db: &Db, space_id: &str, pod_id: &str) -> Result<Option<Pod
     let pod_info_result = conn
         .interact(move |conn| {
             let mut stmt = conn.prepare(
-                "SELECT id, pod_type, data, label, created_at, space FROM pods WHERE space = ?1 AND id = ?2",
+                "SELECT id, pod_type, data, label, created_at, COALESCE(updated_at, created_at), space FROM pods WHERE space = ?1 AND id = ?2",
             )?;
             let result = stmt.query_row([&space_id_clone, &pod_id_clone], |row| {
                 let data_blob: Vec<u8> = row.get(2)?;
@@ -248,7 +359,8 @@ pub async fn get_pod(db: &Db, space_id: &str, pod_id: &str) -> Result<Option<Pod
                     data: pod_data,
                     label: row.get(3)?,
                     created_at: row.get(4)?,
-                    space: row.get(5)?,
+                    updated_at: row.get(5)?,
+                    space: row.get(6)?,
                 })
             });
 
@@ -291,7 +403,7 @@ async fn list_pods_filtered(
             match pod_type_filter_clone {
                 Some(pod_type) => {
                     let mut stmt = conn.prepare(
-                        "SELECT id, pod_type, data, label, created_at, space FROM pods WHERE space = ?1 AND pod_type = ?2"
+                        "SELECT id, pod_type, data, label, created_at, COALESCE(updated_at, created_at), space FROM pods WHERE space = ?1 AND pod_type = ?2 AND deleted_at IS NULL"
                     )?;
                     let pod_iter = stmt.query_map([&space_id_clone, &pod_type], |row| {
                         let data_blob: Vec<u8> = row.get(2)?;
@@ -308,14 +420,15 @@ async fn list_pods_filtered(
                             data: pod_data,
                             label: row.get(3)?,
                             created_at: row.get(4)?,
-                            space: row.get(5)?,
+                            updated_at: row.get(5)?,
+                            space: row.get(6)?,
                         })
                     })?;
                     pod_iter.collect::<Result<Vec<_>, _>>()
                 },
                 None => {
                     let mut stmt = conn.prepare(
-                        "SELECT id, pod_type, data, label, created_at, space FROM pods WHERE space = ?1"
+                        "SELECT id, pod_type, data, label, created_at, COALESCE(updated_at, created_at), space FROM pods WHERE space = ?1 AND deleted_at IS NULL"
                     )?;
                     let pod_iter = stmt.query_map([&space_id_clone], |row| {
                         let data_blob: Vec<u8> = row.get(2)?;
@@ -332,7 +445,8 @@ async fn list_pods_filtered(
                             data: pod_data,
                             label: row.get(3)?,
                             created_at: row.get(4)?,
-                            space: row.get(5)?,
+                            updated_at: row.get(5)?,
+                            space: row.get(6)?,
                         })
                     })?;
                     pod_iter.collect::<Result<Vec<_>, _>>()
@@ -345,6 +459,120 @@ async fn list_pods_filtered(
     Ok(pods)
 }
 
+/// Match predicate shared by [`search_pods`] and [`count_pods_matching`]:
+/// a case-insensitive substring match over a pod's label and serialized
+/// data. Keeping one clause guarantees the count agrees with the items.
+const POD_MATCH_CLAUSE: &str =
+    "space = ?1 AND deleted_at IS NULL AND (label LIKE '%' || ?2 || '%' ESCAPE '\\' OR CAST(data AS TEXT) LIKE '%' || ?2 || '%' ESCAPE '\\')";
+
+/// Escape LIKE wildcards so a user's query only matches literally
+fn escape_like(query: &str) -> String {
+    query
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_")
+}
+
+/// Pods in a space whose label or serialized data contains `query`
+pub async fn search_pods(db: &Db, query: &str, space_id: &str) -> Result<Vec<PodInfo>> {
+    let conn = db
+        .pool()
+        .get()
+        .await
+        .context("Failed to get DB connection")?;
+    let space_id_clone = space_id.to_string();
+    let query_clone = escape_like(query);
+
+    let pods = conn
+        .interact(move |conn| {
+            let mut stmt = conn.prepare_cached(&format!(
+                "SELECT id, pod_type, data, label, created_at, COALESCE(updated_at, created_at), space
+                 FROM pods WHERE {POD_MATCH_CLAUSE}"
+            ))?;
+            let pod_iter = stmt.query_map([&space_id_clone, &query_clone], |row| {
+                let data_blob: Vec<u8> = row.get(2)?;
+                let pod_data: PodData = serde_json::from_slice(&data_blob).map_err(|e| {
+                    rusqlite::Error::FromSqlConversionFailure(
+                        2,
+                        rusqlite::types::Type::Blob,
+                        Box::new(e),
+                    )
+                })?;
+                Ok(PodInfo {
+                    id: row.get(0)?,
+                    pod_type: row.get(1)?,
+                    data: pod_data,
+                    label: row.get(3)?,
+                    created_at: row.get(4)?,
+                    updated_at: row.get(5)?,
+                    space: row.get(6)?,
+                })
+            })?;
+            pod_iter.collect::<Result<Vec<_>, _>>()
+        })
+        .await
+        .map_err(|e| anyhow::anyhow!("InteractError: {e}"))
+        .context("DB interaction failed for search_pods")??;
+    Ok(pods)
+}
+
+/// Number of pods [`search_pods`] would return for the same query, without
+/// materializing them; for "N results" summaries in the UI
+pub async fn count_pods_matching(db: &Db, query: &str, space_id: &str) -> Result<u32> {
+    let conn = db
+        .pool()
+        .get()
+        .await
+        .context("Failed to get DB connection")?;
+    let space_id_clone = space_id.to_string();
+    let query_clone = escape_like(query);
+
+    conn.interact(move |conn| {
+        let count: i64 = conn
+            .prepare_cached(&format!(
+                "SELECT COUNT(*) FROM pods WHERE {POD_MATCH_CLAUSE}"
+            ))?
+            .query_row([&space_id_clone, &query_clone], |row| row.get(0))?;
+        Ok(count as u32)
+    })
+    .await
+    .map_err(|e| anyhow::anyhow!("InteractError: {e}"))
+    .context("DB interaction failed for count_pods_matching")?
+}
+
+/// Returns the exact serialized JSON stored for a pod, without the usual
+/// typed round-trip. Pod ids are content hashes, so any space holding the id
+/// stores the same serialization. Useful for inspecting a pod or attaching it
+/// verbatim to a bug report.
+pub async fn get_pod_raw_json(db: &Db, pod_id: &str) -> Result<Option<String>> {
+    let conn = db
+        .pool()
+        .get()
+        .await
+        .context("Failed to get DB connection")?;
+    let pod_id_clone = pod_id.to_string();
+
+    let raw = conn
+        .interact(move |conn| {
+            let result = conn.query_row(
+                "SELECT data FROM pods WHERE id = ?1 LIMIT 1",
+                [&pod_id_clone],
+                |row| row.get::<_, Vec<u8>>(0),
+            );
+            match result {
+                Ok(blob) => Ok(Some(blob)),
+                Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+                Err(e) => Err(e),
+            }
+        })
+        .await
+        .map_err(|e| anyhow::anyhow!("InteractError: {e}"))
+        .context("DB interaction failed for get_pod_raw_json")??;
+
+    raw.map(|blob| String::from_utf8(blob).context("Stored pod data is not valid UTF-8"))
+        .transpose()
+}
+
 pub async fn delete_pod(db: &Db, space_id: &str, pod_id: &str) -> Result<usize> {
     let conn = db
         .pool()
@@ -373,10 +601,14 @@ pub async fn delete_pod(db: &Db, space_id: &str, pod_id: &str) -> Result<usize>
                 }
                 Ok(false) => {
                     // Pod is not mandatory, proceed with deletion
-                    conn.execute(
+                    let deleted = conn.execute(
                         "DELETE FROM pods WHERE space = ?1 AND id = ?2",
-                        [space_id_clone, pod_id_clone],
-                    )
+                        [&space_id_clone, &pod_id_clone],
+                    )?;
+                    if deleted > 0 {
+                        log_activity(conn, ActivityAction::Delete, &pod_id_clone, &space_id_clone)?;
+                    }
+                    Ok(deleted)
                 }
                 Err(rusqlite::Error::QueryReturnedNoRows) => {
                     // Pod doesn't exist, return 0 rows deleted
@@ -391,6 +623,218 @@ pub async fn delete_pod(db: &Db, space_id: &str, pod_id: &str) -> Result<usize>
     Ok(rows_deleted)
 }
 
+/// Tombstone a pod instead of removing it: sets `deleted_at`, which hides
+/// the pod from listings, search and counts until [`purge_deleted`] reclaims
+/// the row. Mandatory pods cannot be soft-deleted. Returns whether a pod was
+/// actually tombstoned.
+pub async fn soft_delete_pod(db: &Db, space_id: &str, pod_id: &str) -> Result<bool> {
+    let conn = db
+        .pool()
+        .get()
+        .await
+        .context("Failed to get DB connection")?;
+    let space_id_clone = space_id.to_string();
+    let pod_id_clone = pod_id.to_string();
+
+    let tombstoned = conn
+        .interact(move |conn| {
+            let updated = conn.execute(
+                "UPDATE pods SET deleted_at = CURRENT_TIMESTAMP
+                 WHERE space = ?1 AND id = ?2 AND deleted_at IS NULL
+                 AND NOT COALESCE(is_mandatory, 0)",
+                [&space_id_clone, &pod_id_clone],
+            )?;
+            if updated > 0 {
+                log_activity(conn, ActivityAction::Delete, &pod_id_clone, &space_id_clone)?;
+            }
+            Ok::<_, rusqlite::Error>(updated > 0)
+        })
+        .await
+        .map_err(|e| anyhow::anyhow!("InteractError: {e}"))
+        .context("DB interaction failed for soft_delete_pod")??;
+    Ok(tombstoned)
+}
+
+/// Permanently remove soft-deleted pods whose tombstone is older than the
+/// retention window. Returns how many rows were purged.
+pub async fn purge_deleted(db: &Db, older_than: std::time::Duration) -> Result<u64> {
+    let conn = db
+        .pool()
+        .get()
+        .await
+        .context("Failed to get DB connection")?;
+    let retention_seconds = older_than.as_secs() as i64;
+
+    let purged = conn
+        .interact(move |conn| {
+            conn.execute(
+                "DELETE FROM pods WHERE deleted_at IS NOT NULL
+                 AND deleted_at <= datetime('now', '-' || ?1 || ' seconds')",
+                [retention_seconds],
+            )
+        })
+        .await
+        .map_err(|e| anyhow::anyhow!("InteractError: {e}"))
+        .context("DB interaction failed for purge_deleted")??;
+
+    if purged > 0 {
+        log::info!("Purged {purged} soft-deleted pods past the retention window");
+    }
+    Ok(purged as u64)
+}
+
+/// Set or clear a pod's display name. An empty (or whitespace-only) name
+/// clears the custom label so the UI falls back to its default. Returns
+/// whether a pod was actually updated.
+pub async fn rename_pod(db: &Db, space_id: &str, pod_id: &str, new_name: &str) -> Result<bool> {
+    let conn = db
+        .pool()
+        .get()
+        .await
+        .context("Failed to get DB connection")?;
+    let space_id_clone = space_id.to_string();
+    let pod_id_clone = pod_id.to_string();
+    let label = {
+        let trimmed = new_name.trim();
+        (!trimmed.is_empty()).then(|| trimmed.to_string())
+    };
+
+    let now = Utc::now().to_rfc3339();
+    let rows_updated = conn
+        .interact(move |conn| {
+            let updated = conn.execute(
+                "UPDATE pods SET label = ?1, updated_at = ?4 WHERE space = ?2 AND id = ?3",
+                rusqlite::params![label, space_id_clone, pod_id_clone, now],
+            )?;
+            if updated > 0 {
+                log_activity(conn, ActivityAction::Rename, &pod_id_clone, &space_id_clone)?;
+            }
+            Ok::<_, rusqlite::Error>(updated)
+        })
+        .await
+        .map_err(|e| anyhow::anyhow!("InteractError: {e}"))
+        .context("DB interaction failed for rename_pod")??;
+    Ok(rows_updated > 0)
+}
+
+/// Swap a stored pod for a re-signed revision in one transaction, keeping
+/// the row's label, favorite flag, space, and creation time while the id,
+/// type and content follow the new pod. Returns `false` when `old_pod_id`
+/// is not present (or is tombstoned) in the space.
+pub async fn replace_pod(
+    db: &Db,
+    space_id: &str,
+    old_pod_id: &str,
+    new_data: &PodData,
+) -> Result<bool> {
+    let space_id_clone = space_id.to_string();
+    let old_pod_id_clone = old_pod_id.to_string();
+    let new_data_clone = new_data.clone();
+
+    db.with_transaction(move |tx| {
+        let data_blob = serde_json::to_vec(&new_data_clone)
+            .context("Failed to serialize PodData enum for storage")?;
+        let now = Utc::now().to_rfc3339();
+        let updated = tx.execute(
+            "UPDATE pods SET id = ?1, pod_type = ?2, data = ?3, updated_at = ?4
+             WHERE space = ?5 AND id = ?6 AND deleted_at IS NULL",
+            rusqlite::params![
+                new_data_clone.id(),
+                new_data_clone.type_str(),
+                data_blob,
+                now,
+                space_id_clone,
+                old_pod_id_clone
+            ],
+        )?;
+        if updated > 0 {
+            log_activity(
+                tx,
+                ActivityAction::Replace,
+                &new_data_clone.id(),
+                &space_id_clone,
+            )?;
+        }
+        Ok(updated > 0)
+    })
+    .await
+    .context("DB interaction failed for replace_pod")
+}
+
+/// Move a pod to another space. A no-op (returning `false`) when the pod does
+/// not exist or the destination already holds a copy of it. Returns whether a
+/// pod was actually moved.
+pub async fn move_pod(
+    db: &Db,
+    space_id: &str,
+    pod_id: &str,
+    new_space_id: &str,
+) -> Result<bool> {
+    let conn = db
+        .pool()
+        .get()
+        .await
+        .context("Failed to get DB connection")?;
+    let space_id_clone = space_id.to_string();
+    let pod_id_clone = pod_id.to_string();
+    let new_space_id_clone = new_space_id.to_string();
+
+    let now = Utc::now().to_rfc3339();
+    let rows_updated = conn
+        .interact(move |conn| {
+            let moved = conn.execute(
+                "UPDATE OR IGNORE pods SET space = ?1, updated_at = ?2 WHERE space = ?3 AND id = ?4",
+                rusqlite::params![new_space_id_clone, now, space_id_clone, pod_id_clone],
+            )?;
+            if moved > 0 {
+                log_activity(conn, ActivityAction::Move, &pod_id_clone, &new_space_id_clone)?;
+            }
+            Ok::<_, rusqlite::Error>(moved)
+        })
+        .await
+        .map_err(|e| anyhow::anyhow!("InteractError: {e}"))
+        .context("DB interaction failed for move_pod")??;
+    Ok(rows_updated > 0)
+}
+
+/// The most recent activity log entries, newest first
+pub async fn list_recent_activity(db: &Db, limit: u32) -> Result<Vec<ActivityEntry>> {
+    let conn = db
+        .pool()
+        .get()
+        .await
+        .context("Failed to get DB connection")?;
+
+    let entries = conn
+        .interact(move |conn| {
+            let mut stmt = conn.prepare(
+                "SELECT id, action, pod_id, space, created_at FROM activity_log ORDER BY id DESC LIMIT ?1",
+            )?;
+            let rows = stmt.query_map([limit], |row| {
+                let action_str: String = row.get(1)?;
+                let action = ActivityAction::parse(&action_str).ok_or_else(|| {
+                    rusqlite::Error::FromSqlConversionFailure(
+                        1,
+                        rusqlite::types::Type::Text,
+                        format!("unknown activity action: {action_str}").into(),
+                    )
+                })?;
+                Ok(ActivityEntry {
+                    id: row.get(0)?,
+                    action,
+                    pod_id: row.get(2)?,
+                    space: row.get(3)?,
+                    created_at: row.get(4)?,
+                })
+            })?;
+            rows.collect::<Result<Vec<_>, _>>()
+        })
+        .await
+        .map_err(|e| anyhow::anyhow!("InteractError: {e}"))
+        .context("DB interaction failed for list_recent_activity")??;
+    Ok(entries)
+}
+
 pub async fn count_all_pods(db: &Db) -> Result<u32> {
     let conn = db
         .pool()
@@ -399,7 +843,9 @@ pub async fn count_all_pods(db: &Db) -> Result<u32> {
         .context("Failed to get DB connection")?;
 
     conn.interact(move |conn| {
-        let count: i64 = conn.query_row("SELECT COUNT(*) FROM pods", [], |row| row.get(0))?;
+        let count: i64 = conn
+            .prepare_cached("SELECT COUNT(*) FROM pods WHERE deleted_at IS NULL")?
+            .query_row([], |row| row.get(0))?;
         Ok(count as u32)
     })
     .await
@@ -416,16 +862,10 @@ pub async fn count_pods_by_type(db: &Db) -> Result<(u32, u32)> {
 
     let counts = conn
         .interact(move |conn| {
-            let signed_count: i64 = conn.query_row(
-                "SELECT COUNT(*) FROM pods WHERE pod_type = 'signed'",
-                [],
-                |row| row.get(0),
-            )?;
-            let main_count: i64 = conn.query_row(
-                "SELECT COUNT(*) FROM pods WHERE pod_type = 'main'",
-                [],
-                |row| row.get(0),
-            )?;
+            let mut stmt = conn
+                .prepare_cached("SELECT COUNT(*) FROM pods WHERE pod_type = ?1 AND deleted_at IS NULL")?;
+            let signed_count: i64 = stmt.query_row(["signed"], |row| row.get(0))?;
+            let main_count: i64 = stmt.query_row(["main"], |row| row.get(0))?;
             Ok::<_, rusqlite::Error>((signed_count as u32, main_count as u32))
         })
         .await
@@ -931,7 +1371,7 @@ pub async fn import_pod_and_add_to_inbox(
 
         // First, import the POD
         tx.execute(
-            "INSERT INTO pods (id, data, created_at, space, pod_type) VALUES (?1, ?2, ?3, ?4, ?5)",
+            "INSERT INTO pods (id, data, created_at, updated_at, space, pod_type) VALUES (?1, ?2, ?3, ?3, ?4, ?5)",
             rusqlite::params![&pod_id_clone, &data_blob_clone, &now_clone, &space_id_clone, &pod_type_clone],
         )?;
 
@@ -969,8 +1409,8 @@ pub async fn list_all_pods(db: &Db) -> Result<Vec<PodInfo>> {
 
     let pods = conn
         .interact(move |conn| {
-            let mut stmt = conn.prepare(
-                "SELECT id, pod_type, data, label, created_at, space FROM pods ORDER BY created_at DESC"
+            let mut stmt = conn.prepare_cached(
+                "SELECT id, pod_type, data, label, created_at, COALESCE(updated_at, created_at), space FROM pods WHERE deleted_at IS NULL ORDER BY created_at DESC"
             )?;
             let pod_iter = stmt.query_map([], |row| {
                 let data_blob: Vec<u8> = row.get(2)?;
@@ -987,7 +1427,8 @@ pub async fn list_all_pods(db: &Db) -> Result<Vec<PodInfo>> {
                     data: pod_data,
                     label: row.get(3)?,
                     created_at: row.get(4)?,
-                    space: row.get(5)?,
+                    updated_at: row.get(5)?,
+                    space: row.get(6)?,
                 })
             })?;
             pod_iter.collect::<Result<Vec<_>, _>>()
@@ -999,6 +1440,53 @@ pub async fn list_all_pods(db: &Db) -> Result<Vec<PodInfo>> {
     Ok(pods)
 }
 
+/// List every pod across all spaces in the given sort order
+pub async fn list_all_pods_sorted(db: &Db, sort: PodSort) -> Result<Vec<PodInfo>> {
+    let conn = db
+        .pool()
+        .get()
+        .await
+        .context("Failed to get DB connection")?;
+
+    let order_by = match sort {
+        PodSort::NameAsc => "COALESCE(label, id) COLLATE NOCASE ASC",
+        PodSort::NewestImported => "created_at DESC",
+        PodSort::RecentlyModified => "COALESCE(updated_at, created_at) DESC",
+    };
+
+    let pods = conn
+        .interact(move |conn| {
+            let mut stmt = conn.prepare_cached(&format!(
+                "SELECT id, pod_type, data, label, created_at, COALESCE(updated_at, created_at), space FROM pods WHERE deleted_at IS NULL ORDER BY {order_by}"
+            ))?;
+            let pod_iter = stmt.query_map([], |row| {
+                let data_blob: Vec<u8> = row.get(2)?;
+                let pod_data: PodData = serde_json::from_slice(&data_blob).map_err(|e| {
+                    rusqlite::Error::FromSqlConversionFailure(
+                        2,
+                        rusqlite::types::Type::Blob,
+                        Box::new(e),
+                    )
+                })?;
+                Ok(PodInfo {
+                    id: row.get(0)?,
+                    pod_type: row.get(1)?,
+                    data: pod_data,
+                    label: row.get(3)?,
+                    created_at: row.get(4)?,
+                    updated_at: row.get(5)?,
+                    space: row.get(6)?,
+                })
+            })?;
+            pod_iter.collect::<Result<Vec<_>, _>>()
+        })
+        .await
+        .map_err(|e| anyhow::anyhow!("InteractError: {e}"))
+        .context("DB interaction failed for list_all_pods_sorted")??;
+
+    Ok(pods)
+}
+
 // --- Identity Setup Functions ---
 
 #[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
@@ -1194,7 +1682,7 @@ pub async fn store_identity_pod(
 
     conn.interact(move |conn| {
         conn.execute(
-            "INSERT INTO pods (id, data, created_at, space, pod_type, label, is_mandatory) VALUES (?1, ?2, ?3, ?4, ?5, ?6, TRUE)",
+            "INSERT INTO pods (id, data, created_at, updated_at, space, pod_type, label, is_mandatory) VALUES (?1, ?2, ?3, ?3, ?4, ?5, ?6, TRUE)",
             rusqlite::params![&pod_id_clone, &data_blob_clone, &now, &space_id_clone, &pod_type_clone, &label_clone],
         )
     })
@@ -1560,3 +2048,138 @@ pub async fn delete_draft(db: &Db, draft_id: &str) -> Result<bool> {
 
     Ok(rows_affected > 0)
 }
+
+// --- Content Cache API ---
+
+/// Insert or replace a cached content blob, recording the draft and/or pod
+/// that references it
+pub async fn cache_content(
+    db: &Db,
+    hash: &str,
+    content: Vec<u8>,
+    draft_id: Option<String>,
+    pod_id: Option<String>,
+) -> Result<()> {
+    let conn = db
+        .pool()
+        .get()
+        .await
+        .context("Failed to get DB connection")?;
+
+    let hash_owned = hash.to_string();
+    conn.interact(move |conn| {
+        conn.execute(
+            "INSERT OR REPLACE INTO content_cache (hash, content, draft_id, pod_id) VALUES (?1, ?2, ?3, ?4)",
+            rusqlite::params![hash_owned, content, draft_id, pod_id],
+        )
+    })
+    .await
+    .map_err(|e| anyhow::anyhow!("InteractError: {e}"))
+    .context("DB interaction failed for cache_content")??;
+
+    Ok(())
+}
+
+/// Delete cached content blobs whose referencing draft and pod no longer
+/// exist, returning the number of bytes reclaimed. Blobs still referenced by
+/// a live draft or pod are never deleted.
+pub async fn gc_orphaned_content(db: &Db) -> Result<u64> {
+    let conn = db
+        .pool()
+        .get()
+        .await
+        .context("Failed to get DB connection")?;
+
+    const ORPHAN_CONDITION: &str = "(draft_id IS NULL OR draft_id NOT IN (SELECT id FROM drafts))
+         AND (pod_id IS NULL OR pod_id NOT IN (SELECT id FROM pods))";
+
+    let bytes_reclaimed = conn
+        .interact(move |conn| -> Result<u64, rusqlite::Error> {
+            let tx = conn.transaction()?;
+            let bytes: u64 = tx.query_row(
+                &format!(
+                    "SELECT COALESCE(SUM(LENGTH(content)), 0) FROM content_cache WHERE {ORPHAN_CONDITION}"
+                ),
+                [],
+                |row| row.get(0),
+            )?;
+            tx.execute(
+                &format!("DELETE FROM content_cache WHERE {ORPHAN_CONDITION}"),
+                [],
+            )?;
+            tx.commit()?;
+            Ok(bytes)
+        })
+        .await
+        .map_err(|e| anyhow::anyhow!("InteractError: {e}"))
+        .context("DB interaction failed for gc_orphaned_content")??;
+
+    if bytes_reclaimed > 0 {
+        log::info!("Garbage collected {bytes_reclaimed} bytes of orphaned cached content");
+    }
+    Ok(bytes_reclaimed)
+}
+
+// --- UI State API ---
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq, Default)]
+pub struct UiState {
+    pub selected_space: Option<String>,
+    pub selected_pod_id: Option<String>,
+}
+
+/// Get the last-selected space and pod, so the frontend can restore the
+/// user's selection on startup
+pub async fn get_ui_state(db: &Db) -> Result<UiState> {
+    let conn = db
+        .pool()
+        .get()
+        .await
+        .context("Failed to get DB connection")?;
+
+    let ui_state = conn
+        .interact(|conn| {
+            let result = conn.query_row(
+                "SELECT selected_space, selected_pod_id FROM ui_state WHERE id = 1",
+                [],
+                |row| {
+                    Ok(UiState {
+                        selected_space: row.get(0)?,
+                        selected_pod_id: row.get(1)?,
+                    })
+                },
+            );
+
+            match result {
+                Ok(state) => Ok(state),
+                Err(rusqlite::Error::QueryReturnedNoRows) => Ok(UiState::default()),
+                Err(e) => Err(anyhow::anyhow!("Database error: {e}")),
+            }
+        })
+        .await
+        .map_err(|e| anyhow::anyhow!("InteractError: {e}"))
+        .context("DB interaction failed for get_ui_state")??;
+
+    Ok(ui_state)
+}
+
+/// Persist the current selection so it survives a restart
+pub async fn set_ui_state(db: &Db, ui_state: UiState) -> Result<()> {
+    let conn = db
+        .pool()
+        .get()
+        .await
+        .context("Failed to get DB connection")?;
+
+    conn.interact(move |conn| {
+        conn.execute(
+            "INSERT OR REPLACE INTO ui_state (id, selected_space, selected_pod_id) VALUES (1, ?1, ?2)",
+            rusqlite::params![ui_state.selected_space, ui_state.selected_pod_id],
+        )
+    })
+    .await
+    .map_err(|e| anyhow::anyhow!("InteractError: {e}"))
+    .context("DB interaction failed for set_ui_state")??;
+
+    Ok(())
+}
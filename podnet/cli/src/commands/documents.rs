@@ -111,7 +111,9 @@ pub async fn list_documents(server_url: &str) -> Result<(), Box<dyn std::error::
     if response.status().is_success() {
         let documents: serde_json::Value = response.json().await?;
 
-        if let Some(documents_array) = documents.as_array() {
+        // The list endpoint wraps the items so it can echo the effective sort
+        // and pagination metadata
+        if let Some(documents_array) = documents.get("items").and_then(|v| v.as_array()) {
             if documents_array.is_empty() {
                 println!("No documents found.");
                 return Ok(());
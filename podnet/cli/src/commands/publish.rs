@@ -33,7 +33,7 @@ pub async fn publish_content(
     keypair_file: &str,
     title: &str,
     message: Option<&String>,
-    file_path: Option<&String>,
+    file_paths: &[String],
     url: Option<&String>,
     format_override: Option<&String>,
     server_url: &str,
@@ -50,8 +50,10 @@ pub async fn publish_content(
     let mut document_content = DocumentContent {
         message: None,
         file: None,
+        files: Vec::new(),
         url: None,
     };
+    let mut attachments: Vec<Vec<u8>> = Vec::new();
 
     // Process message
     if let Some(msg) = message {
@@ -73,8 +75,9 @@ pub async fn publish_content(
         println!("Message added to document");
     }
 
-    // Process file
-    if let Some(file_path_str) = file_path {
+    // Process files: each is stored server-side as its own blob, so the
+    // document content only commits to the attachment hashes
+    for file_path_str in file_paths {
         println!("Reading file: {file_path_str}");
         let file_content = std::fs::read(file_path_str)?;
         let file_name = std::path::Path::new(file_path_str)
@@ -84,7 +87,7 @@ pub async fn publish_content(
             .to_string();
 
         // Detect MIME type based on file extension
-        let mime_type = match std::path::Path::new(file_path_str)
+        let mime = match std::path::Path::new(file_path_str)
             .extension()
             .and_then(|ext| ext.to_str())
         {
@@ -98,12 +101,14 @@ pub async fn publish_content(
         }
         .to_string();
 
-        document_content.file = Some(DocumentFile {
+        let content_id = DocumentFile::content_id_for(&file_content);
+        document_content.files.push(DocumentFile {
             name: file_name,
-            content: file_content,
-            mime_type,
+            mime,
+            content_id,
         });
-        println!("File added to document");
+        attachments.push(file_content);
+        println!("File added to document (content id {content_id})");
     }
 
     // Process URL
@@ -306,6 +311,8 @@ pub async fn publish_content(
         post_id: post_id_num,
         username: username.clone(),
         main_pod,
+        identity_pod_issued_at: podnet_models::identity_pod_issued_at(&identity_pod),
+        attachments,
     };
     println!("Main pod is: {}", &publish_request.main_pod);
 
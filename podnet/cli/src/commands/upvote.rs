@@ -124,6 +124,7 @@ pub async fn upvote_document(
     let upvote_request = UpvoteRequest {
         username: username.clone(),
         upvote_main_pod: main_pod,
+        identity_pod_issued_at: podnet_models::identity_pod_issued_at(&identity_pod),
     };
 
     println!("Submitting upvote to server...");
@@ -311,10 +311,11 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                         .long("message")
                         .value_name("MESSAGE"),
                     Arg::new("file")
-                        .help("File to attach")
+                        .help("File to attach (repeatable)")
                         .short('f')
                         .long("file")
-                        .value_name("FILE_PATH"),
+                        .value_name("FILE_PATH")
+                        .action(clap::ArgAction::Append),
                     Arg::new("url")
                         .help("URL to reference")
                         .short('u')
@@ -402,7 +403,10 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             let keypair_file = sub_matches.get_one::<String>("keypair").unwrap();
             let title = sub_matches.get_one::<String>("title").unwrap();
             let message = sub_matches.get_one::<String>("message");
-            let file_path = sub_matches.get_one::<String>("file");
+            let file_paths: Vec<String> = sub_matches
+                .get_many::<String>("file")
+                .map(|paths| paths.cloned().collect())
+                .unwrap_or_default();
             let url = sub_matches.get_one::<String>("url");
             let format_override = sub_matches.get_one::<String>("format");
             let server = config::CliConfig::load().server_url;
@@ -414,7 +418,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             let reply_to = sub_matches.get_one::<String>("reply_to");
 
             // Validate that at least one content type is provided
-            if message.is_none() && file_path.is_none() && url.is_none() {
+            if message.is_none() && file_paths.is_empty() && url.is_none() {
                 return Err("At least one of --message, --file, or --url must be provided".into());
             }
 
@@ -422,7 +426,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 keypair_file,
                 title,
                 message,
-                file_path,
+                &file_paths,
                 url,
                 format_override,
                 &server,
@@ -552,7 +556,7 @@ async fn view_post_in_browser(
             .map_err(|e| format!("Failed to parse document: {e}"))?;
 
         // Render the raw DocumentContent to HTML on the client side
-        let html_content = render_document_content_to_html(&document.content);
+        let html_content = render_document_content_to_html(&document.content, server_url);
 
         let content_id = document.metadata.content_id;
         let created_at = document
@@ -624,7 +628,7 @@ async fn view_post_in_browser(
                                         reply_doc_value,
                                     )
                                 {
-                                    render_document_content_to_html(&reply_doc.content)
+                                    render_document_content_to_html(&reply_doc.content, server_url)
                                 } else {
                                     "Error parsing reply document".to_string()
                                 }
@@ -781,7 +785,7 @@ async fn view_post_in_browser(
     Ok(())
 }
 
-fn render_document_content_to_html(content: &DocumentContent) -> String {
+fn render_document_content_to_html(content: &DocumentContent, server_url: &str) -> String {
     let mut html_parts = Vec::new();
 
     // Render message content with format detection
@@ -906,6 +910,39 @@ fn render_document_content_to_html(content: &DocumentContent) -> String {
         html_parts.push(file_html);
     }
 
+    // Render hash-referenced attachments, served by the content endpoint
+    for file in &content.files {
+        let content_url = format!(
+            "{}/content/{}",
+            server_url,
+            file.content_id.encode_hex::<String>()
+        );
+        let file_html = if file.mime.starts_with("image/") {
+            format!(
+                r#"<div class="file-attachment" style="margin: 20px 0; padding: 15px; background-color: #f8f9fa; border-left: 4px solid #28a745; border-radius: 5px;">
+                    <h4 style="margin: 0 0 10px 0; color: #155724;">📎 Image: {}</h4>
+                    <p style="margin: 5px 0; color: #666;">
+                        <strong>Type:</strong> {}
+                    </p>
+                    <img src="{}" alt="{}" style="max-width: 100%; height: auto; border: 1px solid #ddd; border-radius: 3px;" />
+                </div>"#,
+                file.name, file.mime, content_url, file.name
+            )
+        } else {
+            format!(
+                r#"<div class="file-attachment" style="margin: 20px 0; padding: 15px; background-color: #f8f9fa; border-left: 4px solid #28a745; border-radius: 5px;">
+                    <h4 style="margin: 0 0 10px 0; color: #155724;">📎 File: {}</h4>
+                    <p style="margin: 5px 0; color: #666;">
+                        <strong>Type:</strong> {}
+                    </p>
+                    <p style="margin: 5px 0;"><a href="{}">Download</a></p>
+                </div>"#,
+                file.name, file.mime, content_url
+            )
+        };
+        html_parts.push(file_html);
+    }
+
     // Render URL content
     if let Some(ref url) = content.url {
         let url_html = format!(
@@ -0,0 +1,14 @@
+-- Users registered through an OAuth provider. A provider account may only
+-- back one identity, enforced per provider since user ids are only unique
+-- within a provider.
+CREATE TABLE users (
+    public_key_json TEXT PRIMARY KEY,
+    username TEXT NOT NULL,
+    provider TEXT NOT NULL,
+    provider_username TEXT NOT NULL,
+    provider_user_id INTEGER NOT NULL,
+    provider_public_keys TEXT NOT NULL,
+    oauth_verified_at TEXT NOT NULL,
+    issued_at TEXT NOT NULL,
+    UNIQUE (provider, provider_user_id)
+);
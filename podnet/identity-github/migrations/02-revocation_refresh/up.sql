@@ -0,0 +1,2 @@
+ALTER TABLE users ADD COLUMN revoked_at TEXT;
+ALTER TABLE users ADD COLUMN refreshed_at TEXT;
@@ -0,0 +1,11 @@
+-- Pending OAuth flows: the state handed out with the authorization URL must
+-- come back on completion, unexpired and at most once. States only live for
+-- minutes, so rebuilding the table on upgrade just discards pending flows.
+DROP TABLE IF EXISTS oauth_states;
+CREATE TABLE oauth_states (
+    state TEXT PRIMARY KEY,
+    public_key_json TEXT NOT NULL,
+    username TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    include_orgs INTEGER NOT NULL DEFAULT 0
+);
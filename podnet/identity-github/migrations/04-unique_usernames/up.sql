@@ -0,0 +1,14 @@
+-- Usernames become unique, compared case-insensitively. Existing duplicates
+-- keep the oldest row's name; later rows are renamed with a '-<rowid>' suffix
+-- and the name they originally registered under is kept in username_alias so
+-- reverse lookups still find them.
+ALTER TABLE users ADD COLUMN username_alias TEXT;
+
+UPDATE users SET
+    username_alias = username,
+    username = username || '-' || rowid
+WHERE rowid NOT IN (
+    SELECT MIN(rowid) FROM users GROUP BY lower(username)
+);
+
+CREATE UNIQUE INDEX idx_users_username ON users (username COLLATE NOCASE);
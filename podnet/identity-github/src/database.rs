@@ -1,109 +1,957 @@
-use anyhow::Result;
+//! SQLite storage for issued identities and pending OAuth flows.
+//!
+//! The schema lives in the embedded `migrations/` directory and is applied at
+//! startup via `rusqlite_migration`. Connections come from a `deadpool-sqlite`
+//! pool, so concurrent handlers don't serialize on a single mutex; every
+//! public function here takes the pool and runs its queries on a pooled
+//! connection.
+
+use anyhow::{Context, Result};
 use chrono::{DateTime, Utc};
+use deadpool_sqlite::{Config, Object, Pool, Runtime};
+use include_dir::{Dir, include_dir};
+use lazy_static::lazy_static;
 use pod2::backends::plonky2::primitives::ec::curve::Point as PublicKey;
-use rusqlite::{Connection, params};
+use rusqlite::{Connection, OptionalExtension, params};
+use rusqlite_migration::Migrations;
+use serde::{Deserialize, Serialize};
+
+lazy_static! {
+    static ref MIGRATIONS_DIR: Dir<'static> = include_dir!("$CARGO_MANIFEST_DIR/migrations");
+    pub static ref MIGRATIONS: Migrations<'static> =
+        Migrations::from_directory(&MIGRATIONS_DIR).unwrap();
+}
+
+pub type DbPool = Pool;
 
-pub fn initialize_database(db_path: &str) -> Result<Connection> {
-    tracing::info!("Initializing GitHub identity database at: {}", db_path);
+pub async fn initialize_database(db_path: &str) -> Result<DbPool> {
+    // ":memory:" would give every pooled connection its own empty database;
+    // use a uniquely named shared-cache in-memory database instead so the
+    // whole pool (and thus each test) sees one schema.
+    let db_path = if db_path == ":memory:" {
+        format!(
+            "file:identity-{}?mode=memory&cache=shared",
+            rand::random::<u64>()
+        )
+    } else {
+        db_path.to_string()
+    };
+    tracing::info!("Initializing OAuth identity database at: {}", db_path);
+
+    let pool = Config::new(db_path).create_pool(Runtime::Tokio1)?;
+    let conn = pool
+        .get()
+        .await
+        .context("Failed to get connection for migrations")?;
+    conn.interact(|conn| -> Result<()> {
+        baseline_existing_database(conn)?;
+        MIGRATIONS.to_latest(conn)?;
+        Ok(())
+    })
+    .await
+    .map_err(|e| anyhow::anyhow!("InteractError during migration: {e}"))??;
 
-    let conn = Connection::open(db_path)?;
+    tracing::info!("✓ OAuth identity database initialized successfully");
+    Ok(pool)
+}
 
-    // Create the users table with GitHub-specific fields
-    conn.execute(
-        "CREATE TABLE IF NOT EXISTS users (
-            public_key_json TEXT PRIMARY KEY,
-            username TEXT NOT NULL,
-            github_username TEXT NOT NULL,
-            github_user_id INTEGER UNIQUE NOT NULL,
-            github_public_keys TEXT NOT NULL,
-            oauth_verified_at TEXT NOT NULL,
-            issued_at TEXT NOT NULL
-        )",
-        [],
-    )?;
+/// Databases created before the move to `rusqlite_migration` carry the schema
+/// but no migration version. Detect them by the shape of the users table and
+/// set `user_version` so migrations pick up from the right point instead of
+/// re-running `CREATE TABLE`s against existing tables.
+fn baseline_existing_database(conn: &Connection) -> Result<()> {
+    let current_version: i64 = conn.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+    if current_version != 0 {
+        return Ok(());
+    }
+
+    let users_sql: String = conn
+        .query_row(
+            "SELECT sql FROM sqlite_master WHERE name = 'users' AND type = 'table'",
+            [],
+            |row| row.get(0),
+        )
+        .optional()?
+        .unwrap_or_default();
+    if users_sql.is_empty() {
+        // Fresh database: run every migration.
+        return Ok(());
+    }
+
+    // Legacy databases always have the base users table; the revocation and
+    // refresh columns may have been added by the old hand-rolled migration.
+    // The oauth_states migration rebuilds its table either way.
+    let version = if users_sql.contains("refreshed_at") { 2 } else { 1 };
+    tracing::info!("Detected pre-migration database; baselining to version {version}");
+    conn.execute_batch(&format!("PRAGMA user_version = {version}"))?;
+    Ok(())
+}
+
+async fn get_conn(pool: &DbPool) -> Result<Object> {
+    pool.get().await.context("Failed to get DB connection")
+}
 
-    tracing::info!("✓ GitHub identity database initialized successfully");
-    Ok(conn)
+fn interact_error(e: deadpool_sqlite::InteractError) -> anyhow::Error {
+    anyhow::anyhow!("InteractError: {e}")
 }
 
-pub fn insert_user_mapping(
-    conn: &Connection,
+/// A pending OAuth flow, recorded when the authorization URL was handed out.
+pub struct OAuthStateRecord {
+    pub public_key_json: String,
+    pub username: String,
+    pub created_at: String,
+    /// Whether the flow requested the extra org-membership scope
+    pub include_orgs: bool,
+}
+
+pub async fn insert_oauth_state(
+    pool: &DbPool,
+    state: &str,
+    public_key: &PublicKey,
+    username: &str,
+    created_at: DateTime<Utc>,
+    include_orgs: bool,
+) -> Result<()> {
+    let state = state.to_string();
+    let public_key_json = serde_json::to_string(public_key)?;
+    let username = username.to_string();
+
+    let conn = get_conn(pool).await?;
+    conn.interact(move |conn| {
+        conn.execute(
+            "INSERT OR REPLACE INTO oauth_states
+                (state, public_key_json, username, created_at, include_orgs)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                state,
+                public_key_json,
+                username,
+                created_at.to_rfc3339(),
+                include_orgs
+            ],
+        )
+    })
+    .await
+    .map_err(interact_error)??;
+    Ok(())
+}
+
+/// Look up a pending OAuth state and delete it in the same transaction, making
+/// every issued state single-use. Returns None for unknown or already-consumed
+/// states.
+pub async fn consume_oauth_state(pool: &DbPool, state: &str) -> Result<Option<OAuthStateRecord>> {
+    let state = state.to_string();
+
+    let conn = get_conn(pool).await?;
+    conn.interact(move |conn| -> rusqlite::Result<Option<OAuthStateRecord>> {
+        let tx = conn.transaction()?;
+        let record = tx
+            .query_row(
+                "SELECT public_key_json, username, created_at, include_orgs
+                 FROM oauth_states WHERE state = ?1",
+                params![state],
+                |row| {
+                    Ok(OAuthStateRecord {
+                        public_key_json: row.get(0)?,
+                        username: row.get(1)?,
+                        created_at: row.get(2)?,
+                        include_orgs: row.get(3)?,
+                    })
+                },
+            )
+            .optional()?;
+        if record.is_some() {
+            tx.execute("DELETE FROM oauth_states WHERE state = ?1", params![state])?;
+        }
+        tx.commit()?;
+        Ok(record)
+    })
+    .await
+    .map_err(interact_error)?
+    .map_err(Into::into)
+}
+
+#[allow(clippy::too_many_arguments)]
+pub async fn insert_user_mapping(
+    pool: &DbPool,
     public_key: &PublicKey,
     username: &str,
-    github_username: &str,
-    github_user_id: i64,
-    github_public_keys: &[String],
+    provider: &str,
+    provider_username: &str,
+    provider_user_id: i64,
+    provider_public_keys: &[String],
     oauth_verified_at: DateTime<Utc>,
+    username_alias: Option<&str>,
 ) -> Result<()> {
     let public_key_json = serde_json::to_string(public_key)?;
-    let github_public_keys_json = serde_json::to_string(github_public_keys)?;
+    let provider_public_keys_json = serde_json::to_string(provider_public_keys)?;
+    let username = username.to_string();
+    let username_alias = username_alias.map(str::to_string);
+    let provider = provider.to_string();
+    let provider_username = provider_username.to_string();
     let issued_at = Utc::now();
 
-    conn.execute(
-        "INSERT OR REPLACE INTO users (
-            public_key_json, 
-            username, 
-            github_username, 
-            github_user_id, 
-            github_public_keys, 
-            oauth_verified_at, 
-            issued_at
-        ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
-        params![
+    let conn = get_conn(pool).await?;
+    let log_username = username.clone();
+    let log_provider = provider.clone();
+    let log_provider_username = provider_username.clone();
+    let log_public_key_json = public_key_json.clone();
+    conn.interact(move |conn| {
+        conn.execute(
+            "INSERT OR REPLACE INTO users (
+                public_key_json,
+                username,
+                username_alias,
+                provider,
+                provider_username,
+                provider_user_id,
+                provider_public_keys,
+                oauth_verified_at,
+                issued_at
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            params![
+                public_key_json,
+                username,
+                username_alias,
+                provider,
+                provider_username,
+                provider_user_id,
+                provider_public_keys_json,
+                oauth_verified_at.to_rfc3339(),
+                issued_at.to_rfc3339()
+            ],
+        )
+    })
+    .await
+    .map_err(interact_error)??;
+
+    tracing::info!(
+        "✓ Stored user mapping: {} ({}:{}) -> {}",
+        log_username,
+        log_provider,
+        log_provider_username,
+        log_public_key_json
+    );
+    Ok(())
+}
+
+/// A user's stored identity mapping as relevant to lookups: the username and,
+/// when the identity has been revoked, the revocation timestamp.
+pub struct UserStatus {
+    pub username: String,
+    pub revoked_at: Option<String>,
+}
+
+pub async fn get_user_status_by_public_key(
+    pool: &DbPool,
+    public_key: &PublicKey,
+) -> Result<Option<UserStatus>> {
+    let public_key_json = serde_json::to_string(public_key)?;
+
+    let conn = get_conn(pool).await?;
+    conn.interact(move |conn| {
+        conn.query_row(
+            "SELECT username, revoked_at FROM users WHERE public_key_json = ?1",
+            params![public_key_json],
+            |row| {
+                Ok(UserStatus {
+                    username: row.get(0)?,
+                    revoked_at: row.get(1)?,
+                })
+            },
+        )
+        .optional()
+    })
+    .await
+    .map_err(interact_error)?
+    .map_err(Into::into)
+}
+
+/// Case-insensitive check whether `username` is held by a row other than the
+/// one for `public_key`, so a user re-registering under their own name is not
+/// counted as a conflict. The unique index guarantees at most one holder.
+pub async fn username_taken_by_other(
+    pool: &DbPool,
+    username: &str,
+    public_key: &PublicKey,
+) -> Result<bool> {
+    let username = username.to_string();
+    let public_key_json = serde_json::to_string(public_key)?;
+
+    let conn = get_conn(pool).await?;
+    conn.interact(move |conn| {
+        let holder: Option<String> = conn
+            .query_row(
+                "SELECT public_key_json FROM users WHERE username = ?1 COLLATE NOCASE",
+                params![username],
+                |row| row.get(0),
+            )
+            .optional()?;
+        Ok::<_, rusqlite::Error>(matches!(holder, Some(owner) if owner != public_key_json))
+    })
+    .await
+    .map_err(interact_error)?
+    .map_err(Into::into)
+}
+
+/// First free `<base>-<n>` name, offered alongside the 409 when `base` is
+/// already taken.
+pub async fn suggest_username(pool: &DbPool, base: &str) -> Result<String> {
+    let base = base.to_string();
+
+    let conn = get_conn(pool).await?;
+    conn.interact(move |conn| {
+        let mut stmt =
+            conn.prepare("SELECT 1 FROM users WHERE username = ?1 COLLATE NOCASE")?;
+        let mut n = 2u32;
+        loop {
+            let candidate = format!("{base}-{n}");
+            if !stmt.exists(params![candidate])? {
+                return Ok::<_, rusqlite::Error>(candidate);
+            }
+            n += 1;
+        }
+    })
+    .await
+    .map_err(interact_error)?
+    .map_err(Into::into)
+}
+
+/// One identity bound to a looked-up username: the key it belongs to, the
+/// name it is currently issued under, and its revocation time if revoked.
+pub struct UsernameBinding {
+    pub public_key_json: String,
+    pub username: String,
+    pub revoked_at: Option<String>,
+}
+
+/// Public keys bound to a username, matched case-insensitively against both
+/// the current name and any alias assigned when uniqueness was backfilled.
+pub async fn lookup_bindings_by_username(
+    pool: &DbPool,
+    username: &str,
+) -> Result<Vec<UsernameBinding>> {
+    let username = username.to_string();
+
+    let conn = get_conn(pool).await?;
+    conn.interact(move |conn| {
+        let mut stmt = conn.prepare(
+            "SELECT public_key_json, username, revoked_at FROM users
+             WHERE username = ?1 COLLATE NOCASE OR username_alias = ?1 COLLATE NOCASE
+             ORDER BY issued_at",
+        )?;
+        let bindings = stmt
+            .query_map(params![username], |row| {
+                Ok(UsernameBinding {
+                    public_key_json: row.get(0)?,
+                    username: row.get(1)?,
+                    revoked_at: row.get(2)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok::<_, rusqlite::Error>(bindings)
+    })
+    .await
+    .map_err(interact_error)?
+    .map_err(Into::into)
+}
+
+/// Version of the `/admin/export` dump format; imports reject other versions.
+pub const EXPORT_VERSION: u32 = 1;
+
+/// One user mapping as it appears in an export dump: every stored column,
+/// with the public key kept as its JSON string so a dump survives key types
+/// this build can't parse.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExportedUser {
+    pub public_key_json: String,
+    pub username: String,
+    pub username_alias: Option<String>,
+    pub provider: String,
+    pub provider_username: String,
+    pub provider_user_id: i64,
+    pub provider_public_keys: Vec<String>,
+    pub oauth_verified_at: String,
+    pub issued_at: String,
+    pub revoked_at: Option<String>,
+    pub refreshed_at: Option<String>,
+}
+
+/// One page of users for the streaming export, ordered by rowid; pass the
+/// last returned rowid back in to fetch the next page.
+pub async fn export_users_page(
+    pool: &DbPool,
+    after_rowid: i64,
+    limit: usize,
+) -> Result<Vec<(i64, ExportedUser)>> {
+    let conn = get_conn(pool).await?;
+    conn.interact(move |conn| {
+        let mut stmt = conn.prepare(
+            "SELECT rowid, public_key_json, username, username_alias, provider,
+                    provider_username, provider_user_id, provider_public_keys,
+                    oauth_verified_at, issued_at, revoked_at, refreshed_at
+             FROM users WHERE rowid > ?1 ORDER BY rowid LIMIT ?2",
+        )?;
+        let rows = stmt
+            .query_map(params![after_rowid, limit as i64], |row| {
+                Ok((
+                    row.get::<_, i64>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, String>(2)?,
+                    row.get::<_, Option<String>>(3)?,
+                    row.get::<_, String>(4)?,
+                    row.get::<_, String>(5)?,
+                    row.get::<_, i64>(6)?,
+                    row.get::<_, String>(7)?,
+                    row.get::<_, String>(8)?,
+                    row.get::<_, String>(9)?,
+                    row.get::<_, Option<String>>(10)?,
+                    row.get::<_, Option<String>>(11)?,
+                ))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok::<_, rusqlite::Error>(rows)
+    })
+    .await
+    .map_err(interact_error)??
+    .into_iter()
+    .map(|row| {
+        let (
+            rowid,
             public_key_json,
             username,
-            github_username,
-            github_user_id,
-            github_public_keys_json,
-            oauth_verified_at.to_rfc3339(),
-            issued_at.to_rfc3339()
-        ],
-    )?;
+            username_alias,
+            provider,
+            provider_username,
+            provider_user_id,
+            provider_public_keys,
+            oauth_verified_at,
+            issued_at,
+            revoked_at,
+            refreshed_at,
+        ) = row;
+        let provider_public_keys = serde_json::from_str(&provider_public_keys)
+            .context("Stored provider_public_keys is not valid JSON")?;
+        Ok((
+            rowid,
+            ExportedUser {
+                public_key_json,
+                username,
+                username_alias,
+                provider,
+                provider_username,
+                provider_user_id,
+                provider_public_keys,
+                oauth_verified_at,
+                issued_at,
+                revoked_at,
+                refreshed_at,
+            },
+        ))
+    })
+    .collect()
+}
 
-    tracing::info!(
-        "✓ Stored GitHub user mapping: {} ({}) -> {}",
+/// What an import did with one dump row; rows matching an existing mapping
+/// byte-for-byte are skipped so re-importing a dump is idempotent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImportOutcome {
+    Inserted,
+    Updated,
+    Skipped,
+}
+
+/// Apply one dump row, keyed by (provider, provider_user_id): insert when
+/// absent, update in place when the stored mapping differs, skip when it
+/// already matches.
+pub async fn import_user(pool: &DbPool, user: ExportedUser) -> Result<ImportOutcome> {
+    let provider_public_keys_json = serde_json::to_string(&user.provider_public_keys)?;
+
+    let conn = get_conn(pool).await?;
+    conn.interact(move |conn| {
+        let tx = conn.transaction()?;
+        let existing = tx
+            .query_row(
+                "SELECT public_key_json, username, username_alias, provider_username,
+                        provider_public_keys, oauth_verified_at, issued_at, revoked_at,
+                        refreshed_at
+                 FROM users WHERE provider = ?1 AND provider_user_id = ?2",
+                params![user.provider, user.provider_user_id],
+                |row| {
+                    Ok((
+                        row.get::<_, String>(0)?,
+                        row.get::<_, String>(1)?,
+                        row.get::<_, Option<String>>(2)?,
+                        row.get::<_, String>(3)?,
+                        row.get::<_, String>(4)?,
+                        row.get::<_, String>(5)?,
+                        row.get::<_, String>(6)?,
+                        row.get::<_, Option<String>>(7)?,
+                        row.get::<_, Option<String>>(8)?,
+                    ))
+                },
+            )
+            .optional()?;
+
+        let outcome = match existing {
+            None => {
+                tx.execute(
+                    "INSERT INTO users (
+                        public_key_json, username, username_alias, provider,
+                        provider_username, provider_user_id, provider_public_keys,
+                        oauth_verified_at, issued_at, revoked_at, refreshed_at
+                    ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+                    params![
+                        user.public_key_json,
+                        user.username,
+                        user.username_alias,
+                        user.provider,
+                        user.provider_username,
+                        user.provider_user_id,
+                        provider_public_keys_json,
+                        user.oauth_verified_at,
+                        user.issued_at,
+                        user.revoked_at,
+                        user.refreshed_at
+                    ],
+                )?;
+                ImportOutcome::Inserted
+            }
+            Some(existing)
+                if existing
+                    == (
+                        user.public_key_json.clone(),
+                        user.username.clone(),
+                        user.username_alias.clone(),
+                        user.provider_username.clone(),
+                        provider_public_keys_json.clone(),
+                        user.oauth_verified_at.clone(),
+                        user.issued_at.clone(),
+                        user.revoked_at.clone(),
+                        user.refreshed_at.clone(),
+                    ) =>
+            {
+                ImportOutcome::Skipped
+            }
+            Some(_) => {
+                tx.execute(
+                    "UPDATE users SET
+                        public_key_json = ?1, username = ?2, username_alias = ?3,
+                        provider_username = ?4, provider_public_keys = ?5,
+                        oauth_verified_at = ?6, issued_at = ?7, revoked_at = ?8,
+                        refreshed_at = ?9
+                     WHERE provider = ?10 AND provider_user_id = ?11",
+                    params![
+                        user.public_key_json,
+                        user.username,
+                        user.username_alias,
+                        user.provider_username,
+                        provider_public_keys_json,
+                        user.oauth_verified_at,
+                        user.issued_at,
+                        user.revoked_at,
+                        user.refreshed_at,
+                        user.provider,
+                        user.provider_user_id
+                    ],
+                )?;
+                ImportOutcome::Updated
+            }
+        };
+        tx.commit()?;
+        Ok::<_, rusqlite::Error>(outcome)
+    })
+    .await
+    .map_err(interact_error)?
+    .map_err(Into::into)
+}
+
+/// A user's full stored mapping, as needed by the refresh flow.
+pub struct UserRecord {
+    pub username: String,
+    pub provider: String,
+    pub provider_username: String,
+    pub provider_user_id: i64,
+    pub provider_public_keys: Vec<String>,
+    pub oauth_verified_at: String,
+    pub revoked_at: Option<String>,
+    pub refreshed_at: Option<String>,
+}
+
+pub async fn get_user_by_public_key(
+    pool: &DbPool,
+    public_key: &PublicKey,
+) -> Result<Option<UserRecord>> {
+    let public_key_json = serde_json::to_string(public_key)?;
+
+    let conn = get_conn(pool).await?;
+    let row = conn
+        .interact(move |conn| {
+            conn.query_row(
+                "SELECT username, provider, provider_username, provider_user_id,
+                        provider_public_keys, oauth_verified_at, revoked_at, refreshed_at
+                 FROM users WHERE public_key_json = ?1",
+                params![public_key_json],
+                |row| {
+                    Ok((
+                        row.get::<_, String>(0)?,
+                        row.get::<_, String>(1)?,
+                        row.get::<_, String>(2)?,
+                        row.get::<_, i64>(3)?,
+                        row.get::<_, String>(4)?,
+                        row.get::<_, String>(5)?,
+                        row.get::<_, Option<String>>(6)?,
+                        row.get::<_, Option<String>>(7)?,
+                    ))
+                },
+            )
+            .optional()
+        })
+        .await
+        .map_err(interact_error)??;
+
+    let Some((
         username,
-        github_username,
-        public_key_json
-    );
+        provider,
+        provider_username,
+        provider_user_id,
+        provider_public_keys_json,
+        oauth_verified_at,
+        revoked_at,
+        refreshed_at,
+    )) = row
+    else {
+        return Ok(None);
+    };
+    Ok(Some(UserRecord {
+        username,
+        provider,
+        provider_username,
+        provider_user_id,
+        provider_public_keys: serde_json::from_str(&provider_public_keys_json)?,
+        oauth_verified_at,
+        revoked_at,
+        refreshed_at,
+    }))
+}
+
+/// Replace the stored SSH keys for an identity after a refresh, recording
+/// when the refresh happened for rate limiting.
+pub async fn update_user_public_keys(
+    pool: &DbPool,
+    public_key: &PublicKey,
+    provider_public_keys: &[String],
+    refreshed_at: DateTime<Utc>,
+) -> Result<()> {
+    let public_key_json = serde_json::to_string(public_key)?;
+    let provider_public_keys_json = serde_json::to_string(provider_public_keys)?;
+
+    let conn = get_conn(pool).await?;
+    let log_public_key_json = public_key_json.clone();
+    conn.interact(move |conn| {
+        conn.execute(
+            "UPDATE users SET provider_public_keys = ?1, refreshed_at = ?2
+             WHERE public_key_json = ?3",
+            params![
+                provider_public_keys_json,
+                refreshed_at.to_rfc3339(),
+                public_key_json
+            ],
+        )
+    })
+    .await
+    .map_err(interact_error)??;
+
+    tracing::info!("✓ Updated stored SSH keys for: {}", log_public_key_json);
     Ok(())
 }
 
-pub fn get_username_by_public_key(
-    conn: &Connection,
+/// Mark the identity behind `public_key` revoked. Returns false when no
+/// mapping exists for the key.
+pub async fn revoke_user_by_public_key(
+    pool: &DbPool,
     public_key: &PublicKey,
-) -> Result<Option<String>> {
+    revoked_at: DateTime<Utc>,
+) -> Result<bool> {
     let public_key_json = serde_json::to_string(public_key)?;
 
-    let mut stmt = conn.prepare("SELECT username FROM users WHERE public_key_json = ?1")?;
-    let mut rows = stmt.query(params![public_key_json])?;
+    let conn = get_conn(pool).await?;
+    let log_public_key_json = public_key_json.clone();
+    let updated_rows = conn
+        .interact(move |conn| {
+            conn.execute(
+                "UPDATE users SET revoked_at = ?1
+                 WHERE public_key_json = ?2 AND revoked_at IS NULL",
+                params![revoked_at.to_rfc3339(), public_key_json],
+            )
+        })
+        .await
+        .map_err(interact_error)??;
 
-    if let Some(row) = rows.next()? {
-        let username: String = row.get(0)?;
-        Ok(Some(username))
-    } else {
-        Ok(None)
+    if updated_rows > 0 {
+        tracing::info!("✓ Revoked identity for public key: {}", log_public_key_json);
     }
+    Ok(updated_rows > 0)
 }
 
-pub fn user_exists_by_github_id(conn: &Connection, github_user_id: i64) -> Result<bool> {
-    let mut stmt = conn.prepare("SELECT 1 FROM users WHERE github_user_id = ?1")?;
-    let mut rows = stmt.query(params![github_user_id])?;
-    Ok(rows.next()?.is_some())
+/// Mark the identity backed by a provider account revoked, for key-loss cases
+/// where the user re-authenticates with the provider instead of signing.
+/// Returns the public key JSON of the revoked mapping, if one existed.
+pub async fn revoke_user_by_provider_id(
+    pool: &DbPool,
+    provider: &str,
+    provider_user_id: i64,
+    revoked_at: DateTime<Utc>,
+) -> Result<Option<String>> {
+    let provider = provider.to_string();
+    let log_provider = provider.clone();
+
+    let conn = get_conn(pool).await?;
+    let revoked = conn
+        .interact(move |conn| -> rusqlite::Result<Option<String>> {
+            let tx = conn.transaction()?;
+            let public_key_json = tx
+                .query_row(
+                    "SELECT public_key_json FROM users
+                     WHERE provider = ?1 AND provider_user_id = ?2 AND revoked_at IS NULL",
+                    params![provider, provider_user_id],
+                    |row| row.get::<_, String>(0),
+                )
+                .optional()?;
+            if let Some(public_key_json) = &public_key_json {
+                tx.execute(
+                    "UPDATE users SET revoked_at = ?1 WHERE public_key_json = ?2",
+                    params![revoked_at.to_rfc3339(), public_key_json],
+                )?;
+            }
+            tx.commit()?;
+            Ok(public_key_json)
+        })
+        .await
+        .map_err(interact_error)??;
+
+    if revoked.is_some() {
+        tracing::info!(
+            "✓ Revoked identity for provider account {}:{}",
+            log_provider,
+            provider_user_id
+        );
+    }
+    Ok(revoked)
+}
+
+/// A revoked identity mapping, as served by the public revocation list.
+pub struct RevokedUser {
+    pub public_key_json: String,
+    pub revoked_at: String,
+}
+
+pub async fn list_revoked_users(pool: &DbPool) -> Result<Vec<RevokedUser>> {
+    let conn = get_conn(pool).await?;
+    conn.interact(|conn| {
+        let mut stmt = conn.prepare(
+            "SELECT public_key_json, revoked_at FROM users
+             WHERE revoked_at IS NOT NULL ORDER BY revoked_at",
+        )?;
+        let revoked = stmt
+            .query_map([], |row| {
+                Ok(RevokedUser {
+                    public_key_json: row.get(0)?,
+                    revoked_at: row.get(1)?,
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(revoked)
+    })
+    .await
+    .map_err(interact_error)?
 }
 
-pub fn delete_user_by_github_id(conn: &Connection, github_user_id: i64) -> Result<()> {
-    let deleted_rows = conn.execute(
-        "DELETE FROM users WHERE github_user_id = ?1",
-        params![github_user_id],
-    )?;
+pub async fn user_exists_by_provider_id(
+    pool: &DbPool,
+    provider: &str,
+    provider_user_id: i64,
+) -> Result<bool> {
+    let provider = provider.to_string();
+
+    let conn = get_conn(pool).await?;
+    let exists = conn
+        .interact(move |conn| {
+            conn.query_row(
+                "SELECT 1 FROM users WHERE provider = ?1 AND provider_user_id = ?2",
+                params![provider, provider_user_id],
+                |_| Ok(()),
+            )
+            .optional()
+        })
+        .await
+        .map_err(interact_error)??;
+    Ok(exists.is_some())
+}
+
+pub async fn delete_user_by_provider_id(
+    pool: &DbPool,
+    provider: &str,
+    provider_user_id: i64,
+) -> Result<()> {
+    let provider = provider.to_string();
+    let log_provider = provider.clone();
+
+    let conn = get_conn(pool).await?;
+    let deleted_rows = conn
+        .interact(move |conn| {
+            conn.execute(
+                "DELETE FROM users WHERE provider = ?1 AND provider_user_id = ?2",
+                params![provider, provider_user_id],
+            )
+        })
+        .await
+        .map_err(interact_error)??;
 
     if deleted_rows > 0 {
         tracing::info!(
-            "✓ Deleted existing GitHub user record (ID: {})",
-            github_user_id
+            "✓ Deleted existing user record ({}:{})",
+            log_provider,
+            provider_user_id
         );
     }
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_legacy_database_is_baselined_and_upgraded() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = dir.path().join("legacy.db");
+        let db_path = db_path.to_str().unwrap().to_string();
+
+        // Shape a database the way the old hand-written CREATE TABLE calls
+        // left it: full users schema, oauth_states without include_orgs, and
+        // no migration version recorded
+        {
+            let conn = Connection::open(&db_path).unwrap();
+            conn.execute_batch(
+                "CREATE TABLE users (
+                    public_key_json TEXT PRIMARY KEY,
+                    username TEXT NOT NULL,
+                    provider TEXT NOT NULL,
+                    provider_username TEXT NOT NULL,
+                    provider_user_id INTEGER NOT NULL,
+                    provider_public_keys TEXT NOT NULL,
+                    oauth_verified_at TEXT NOT NULL,
+                    issued_at TEXT NOT NULL,
+                    revoked_at TEXT,
+                    refreshed_at TEXT,
+                    UNIQUE (provider, provider_user_id)
+                );
+                CREATE TABLE oauth_states (
+                    state TEXT PRIMARY KEY,
+                    public_key_json TEXT NOT NULL,
+                    username TEXT NOT NULL,
+                    created_at TEXT NOT NULL
+                );
+                INSERT INTO users VALUES
+                    ('pk-json', 'Alice', 'github', 'octocat', 42, '[]',
+                     '2025-01-01T00:00:00Z', '2025-01-01T00:00:00Z', NULL, NULL),
+                    ('pk-json-2', 'alice', 'github', 'octodog', 43, '[]',
+                     '2025-02-01T00:00:00Z', '2025-02-01T00:00:00Z', NULL, NULL);",
+            )
+            .unwrap();
+        }
+
+        let pool = initialize_database(&db_path).await.unwrap();
+
+        // The legacy row survives, the schema is versioned, and the rebuilt
+        // oauth_states table carries the include_orgs column
+        let conn = pool.get().await.unwrap();
+        conn.interact(|conn| {
+            let version: i64 = conn
+                .query_row("PRAGMA user_version", [], |row| row.get(0))
+                .unwrap();
+            assert_eq!(version, 4);
+
+            let username: String = conn
+                .query_row(
+                    "SELECT username FROM users WHERE public_key_json = 'pk-json'",
+                    [],
+                    |row| row.get(0),
+                )
+                .unwrap();
+            assert_eq!(username, "Alice");
+
+            // The duplicate (case-insensitively) loses the name to the older
+            // row: it gets a suffixed replacement and keeps the original as
+            // an alias
+            let (renamed, alias): (String, Option<String>) = conn
+                .query_row(
+                    "SELECT username, username_alias FROM users
+                     WHERE public_key_json = 'pk-json-2'",
+                    [],
+                    |row| Ok((row.get(0)?, row.get(1)?)),
+                )
+                .unwrap();
+            assert!(renamed.starts_with("alice-"), "got {renamed:?}");
+            assert_eq!(alias.as_deref(), Some("alice"));
+
+            let has_include_orgs: i64 = conn
+                .query_row(
+                    "SELECT COUNT(*) FROM pragma_table_info('oauth_states')
+                     WHERE name = 'include_orgs'",
+                    [],
+                    |row| row.get(0),
+                )
+                .unwrap();
+            assert_eq!(has_include_orgs, 1);
+        })
+        .await
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_older_legacy_database_gains_revocation_columns() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = dir.path().join("older.db");
+        let db_path = db_path.to_str().unwrap().to_string();
+
+        // A database from before revocation support lacks the revoked_at and
+        // refreshed_at columns entirely
+        {
+            let conn = Connection::open(&db_path).unwrap();
+            conn.execute_batch(
+                "CREATE TABLE users (
+                    public_key_json TEXT PRIMARY KEY,
+                    username TEXT NOT NULL,
+                    provider TEXT NOT NULL,
+                    provider_username TEXT NOT NULL,
+                    provider_user_id INTEGER NOT NULL,
+                    provider_public_keys TEXT NOT NULL,
+                    oauth_verified_at TEXT NOT NULL,
+                    issued_at TEXT NOT NULL,
+                    UNIQUE (provider, provider_user_id)
+                );",
+            )
+            .unwrap();
+        }
+
+        let pool = initialize_database(&db_path).await.unwrap();
+
+        let conn = pool.get().await.unwrap();
+        conn.interact(|conn| {
+            let has_refreshed_at: i64 = conn
+                .query_row(
+                    "SELECT COUNT(*) FROM pragma_table_info('users')
+                     WHERE name = 'refreshed_at'",
+                    [],
+                    |row| row.get(0),
+                )
+                .unwrap();
+            assert_eq!(has_refreshed_at, 1);
+        })
+        .await
+        .unwrap();
+    }
+}
@@ -1,3 +1,5 @@
+use std::collections::HashSet;
+
 use anyhow::Result;
 use chrono::{DateTime, Utc};
 use pod2::{
@@ -6,11 +8,19 @@ use pod2::{
         signer::Signer,
     },
     frontend::{SignedDict, SignedDictBuilder},
-    middleware::Params,
+    middleware::{Params, Value, containers::Set},
 };
 use serde::{Deserialize, Serialize};
 
-use crate::github::GitHubUser;
+use crate::{
+    database::ExportedUser, policy::AccountPolicy, providers::ProviderUser,
+    registration::RegistrationStatus,
+};
+
+/// Upper bound on org logins attested in one identity pod. The set lives in a
+/// fixed-depth container, so the attestation cannot grow without limit; users
+/// in more orgs than this get the lexicographically first ones.
+pub const MAX_ATTESTED_ORGS: usize = 32;
 
 #[derive(Debug, Serialize)]
 pub struct IdentityResponse {
@@ -21,6 +31,8 @@ pub struct IdentityResponse {
 pub struct ServerInfo {
     pub server_id: String,
     pub public_key: PublicKey,
+    /// Current state of this server's registration with the podnet server
+    pub registration: RegistrationStatus,
 }
 
 #[derive(Debug, Deserialize)]
@@ -31,44 +43,169 @@ pub struct UsernameLookupRequest {
 #[derive(Debug, Serialize)]
 pub struct UsernameLookupResponse {
     pub username: String,
+    /// RFC 3339 revocation time; present when the identity has been revoked
+    pub revoked_at: Option<String>,
+}
+
+/// 409 body returned when the requested username is already bound to a
+/// different key, including a free alternative the client can offer.
+#[derive(Debug, Serialize)]
+pub struct UsernameConflict {
+    pub error: String,
+    pub suggested_username: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PublicKeyLookupRequest {
+    pub username: String,
+}
+
+/// One identity matched by a reverse lookup. `username` is the name the pod
+/// was actually issued under, which differs from the queried name when the
+/// match was via a pre-uniqueness alias.
+#[derive(Debug, Serialize)]
+pub struct PublicKeyLookupEntry {
+    pub public_key: PublicKey,
+    pub username: String,
+    pub revoked_at: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PublicKeyLookupResponse {
+    pub bindings: Vec<PublicKeyLookupEntry>,
+}
+
+/// Dump submitted to `POST /admin/import`; the same shape `GET /admin/export`
+/// streams out, so a dump can be piped from one server into another.
+#[derive(Debug, Deserialize)]
+pub struct ImportRequest {
+    pub version: u32,
+    pub users: Vec<ExportedUser>,
+}
+
+/// Per-row import result, keyed the way the import itself is keyed.
+#[derive(Debug, Serialize)]
+pub struct ImportRowResult {
+    pub provider: String,
+    pub provider_user_id: i64,
+    pub username: String,
+    /// `inserted`, `updated`, `skipped`, or `rejected`
+    pub outcome: String,
+    /// Why a row was rejected; absent otherwise
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ImportResponse {
+    pub version: u32,
+    pub results: Vec<ImportRowResult>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RevokeResponse {
+    pub public_key: PublicKey,
+    pub revoked_at: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RevocationEntry {
+    pub public_key: PublicKey,
+    pub revoked_at: String,
 }
 
+/// Public revocation list, for verifiers (e.g. the podnet server) to consult
+/// and cache when deciding whether to still accept an identity pod.
+#[derive(Debug, Serialize)]
+pub struct RevocationListResponse {
+    pub revocations: Vec<RevocationEntry>,
+}
+
+#[allow(clippy::too_many_arguments)]
 pub fn create_identity_pod(
     server_id: &str,
     server_secret_key: &SecretKey,
     public_key: &PublicKey,
     username: &str,
-    github_user: &GitHubUser,
-    github_public_keys: &[String],
+    provider: &str,
+    provider_user: &ProviderUser,
+    provider_public_keys: &[String],
     oauth_verified_at: DateTime<Utc>,
+    policy: &AccountPolicy,
+    refreshed_at: Option<DateTime<Utc>>,
+    attested_orgs: Option<&[String]>,
 ) -> Result<SignedDict> {
     let params = Params::default();
     let mut identity_builder = SignedDictBuilder::new(&params);
 
-    // Core identity fields (minimal in main pod)
+    // Core identity fields (minimal in main pod); the provider name and user
+    // id are top-level entries so downstream verifiers can tell accounts from
+    // different providers apart without parsing the data blob
     identity_builder.insert("username", username);
     identity_builder.insert("user_public_key", *public_key);
     identity_builder.insert("identity_server_id", server_id);
     identity_builder.insert("issued_at", Utc::now().to_rfc3339().as_str());
+    identity_builder.insert("oauth_provider", provider);
+    identity_builder.insert("provider_user_id", provider_user.id);
+
+    // Pods re-issued because the provider SSH keys changed carry the refresh
+    // time alongside the original verification time
+    if let Some(refreshed_at) = refreshed_at {
+        identity_builder.insert("refreshed_at", refreshed_at.to_rfc3339().as_str());
+    }
+
+    // Attest the account-quality requirements that were actually applied so
+    // relying parties can see the policy this pod was issued under
+    if let Some(min_days) = policy.min_account_age_days {
+        identity_builder.insert("policy_min_account_age_days", min_days);
+    }
+    if let Some(min_repos) = policy.min_public_repos {
+        identity_builder.insert("policy_min_public_repos", min_repos);
+    }
+    if let Some(min_followers) = policy.min_followers {
+        identity_builder.insert("policy_min_followers", min_followers);
+    }
+    if policy.require_ssh_key {
+        identity_builder.insert("policy_require_ssh_key", true);
+    }
+
+    // Attest org memberships as a set entry (e.g. `github_orgs`) so POD
+    // requests can express membership via Contains without a provider round;
+    // the same container depth as the rest of the stack keeps the entry
+    // usable in existing circuits
+    if let Some(orgs) = attested_orgs {
+        let mut orgs: Vec<&String> = orgs.iter().collect();
+        orgs.sort();
+        orgs.dedup();
+        orgs.truncate(MAX_ATTESTED_ORGS);
+        let orgs_set = Set::new(
+            5,
+            orgs.into_iter()
+                .map(|org| Value::from(org.clone()))
+                .collect::<HashSet<_>>(),
+        )
+        .map_err(|e| anyhow::anyhow!("Failed to create org set: {e:?}"))?;
+        identity_builder.insert(format!("{provider}_orgs").as_str(), Value::from(orgs_set));
+    }
 
-    // Create GitHub data dictionary (similar to document pod structure)
-    let mut github_data = std::collections::HashMap::new();
-    github_data.insert(
-        "github_username".to_string(),
-        serde_json::Value::String(github_user.login.clone()),
+    // Create provider data dictionary (similar to document pod structure)
+    let mut provider_data = std::collections::HashMap::new();
+    provider_data.insert(
+        "provider_username".to_string(),
+        serde_json::Value::String(provider_user.login.clone()),
     );
-    github_data.insert(
-        "github_user_id".to_string(),
-        serde_json::Value::Number(github_user.id.into()),
+    provider_data.insert(
+        "provider_user_id".to_string(),
+        serde_json::Value::Number(provider_user.id.into()),
     );
-    github_data.insert(
+    provider_data.insert(
         "oauth_verified_at".to_string(),
         serde_json::Value::String(oauth_verified_at.to_rfc3339()),
     );
-    github_data.insert(
-        "github_public_keys".to_string(),
+    provider_data.insert(
+        "provider_public_keys".to_string(),
         serde_json::Value::Array(
-            github_public_keys
+            provider_public_keys
                 .iter()
                 .map(|k| serde_json::Value::String(k.clone()))
                 .collect(),
@@ -76,25 +213,26 @@ pub fn create_identity_pod(
     );
 
     // Add email if available
-    if let Some(email) = &github_user.email {
-        github_data.insert(
-            "github_email".to_string(),
+    if let Some(email) = &provider_user.email {
+        provider_data.insert(
+            "provider_email".to_string(),
             serde_json::Value::String(email.clone()),
         );
     }
 
-    // Store GitHub data as a dictionary field
-    let github_data_json = serde_json::to_string(&github_data)?;
-    identity_builder.insert("github_data", github_data_json.as_str());
+    // Store provider data as a dictionary field
+    let provider_data_json = serde_json::to_string(&provider_data)?;
+    identity_builder.insert("provider_data", provider_data_json.as_str());
 
     // Sign the identity pod with the identity server's key
     let server_signer = Signer(SecretKey(server_secret_key.0.clone()));
     let identity_pod = identity_builder.sign(&server_signer)?;
 
     tracing::info!(
-        "Identity pod issued for user: {} (GitHub: {})",
+        "Identity pod issued for user: {} ({}: {})",
         username,
-        github_user.login
+        provider,
+        provider_user.login
     );
 
     Ok(identity_pod)
@@ -0,0 +1,317 @@
+//! Persistence of the server's Schnorr keypair.
+//!
+//! The keypair file is plain JSON by default. When
+//! `IDENTITY_KEYPAIR_PASSPHRASE` is set the secret key is encrypted with
+//! Argon2id + ChaCha20-Poly1305 and the file stores the ciphertext alongside
+//! the KDF parameters; legacy plaintext files are re-written encrypted the
+//! first time they are loaded with a passphrase. Decryption failures abort
+//! startup — silently generating a fresh keypair would orphan every identity
+//! pod this server ever signed.
+
+use std::fs;
+
+use anyhow::{Context, Result, bail};
+use argon2::Argon2;
+use chacha20poly1305::{
+    ChaCha20Poly1305, Nonce,
+    aead::{Aead, KeyInit},
+};
+use pod2::backends::plonky2::primitives::ec::{curve::Point as PublicKey, schnorr::SecretKey};
+use serde::{Deserialize, Serialize};
+
+pub const SERVER_ID: &str = "github-identity-server";
+
+// Keypair persistence models
+#[derive(Debug, Serialize, Deserialize)]
+pub struct IdentityServerKeypair {
+    pub server_id: String,
+    pub secret_key: String, // hex encoded
+    pub public_key: PublicKey,
+    pub created_at: String,
+}
+
+/// The secret key under passphrase encryption, with everything needed to
+/// decrypt it again: the KDF parameters and salt, and the AEAD nonce.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EncryptedSecretKey {
+    pub kdf: String,
+    pub salt: String, // hex encoded
+    pub m_cost: u32,
+    pub t_cost: u32,
+    pub p_cost: u32,
+    pub cipher: String,
+    pub nonce: String,      // hex encoded
+    pub ciphertext: String, // hex encoded
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EncryptedIdentityServerKeypair {
+    pub version: u32,
+    pub server_id: String,
+    pub encrypted_secret_key: EncryptedSecretKey,
+    pub public_key: PublicKey,
+    pub created_at: String,
+}
+
+/// Either file format; the encrypted variant is tried first since it is the
+/// more specific shape (a plaintext file has no `encrypted_secret_key`).
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(untagged)]
+enum KeypairFile {
+    Encrypted(EncryptedIdentityServerKeypair),
+    Plain(IdentityServerKeypair),
+}
+
+fn derive_key(passphrase: &str, encrypted: &EncryptedSecretKey) -> Result<[u8; 32]> {
+    if encrypted.kdf != "argon2id" {
+        bail!("Unsupported keypair KDF: {}", encrypted.kdf);
+    }
+    if encrypted.cipher != "chacha20poly1305" {
+        bail!("Unsupported keypair cipher: {}", encrypted.cipher);
+    }
+    let salt = hex::decode(&encrypted.salt).context("Keypair salt is not valid hex")?;
+    let params = argon2::Params::new(encrypted.m_cost, encrypted.t_cost, encrypted.p_cost, None)
+        .map_err(|e| anyhow::anyhow!("Invalid keypair KDF parameters: {e}"))?;
+    let argon2 = Argon2::new(argon2::Algorithm::Argon2id, argon2::Version::V0x13, params);
+    let mut key = [0u8; 32];
+    argon2
+        .hash_password_into(passphrase.as_bytes(), &salt, &mut key)
+        .map_err(|e| anyhow::anyhow!("Key derivation failed: {e}"))?;
+    Ok(key)
+}
+
+fn encrypt_secret_key(secret_key: &SecretKey, passphrase: &str) -> Result<EncryptedSecretKey> {
+    let params = argon2::Params::default();
+    let mut encrypted = EncryptedSecretKey {
+        kdf: "argon2id".to_string(),
+        salt: hex::encode(rand::random::<[u8; 16]>()),
+        m_cost: params.m_cost(),
+        t_cost: params.t_cost(),
+        p_cost: params.p_cost(),
+        cipher: "chacha20poly1305".to_string(),
+        nonce: hex::encode(rand::random::<[u8; 12]>()),
+        ciphertext: String::new(),
+    };
+    let key = derive_key(passphrase, &encrypted)?;
+    let cipher = ChaCha20Poly1305::new(&key.into());
+    let nonce_bytes = hex::decode(&encrypted.nonce).expect("nonce was hex-encoded above");
+    let ciphertext = cipher
+        .encrypt(
+            Nonce::from_slice(&nonce_bytes),
+            secret_key.0.to_bytes_le().as_slice(),
+        )
+        .map_err(|e| anyhow::anyhow!("Failed to encrypt secret key: {e}"))?;
+    encrypted.ciphertext = hex::encode(ciphertext);
+    Ok(encrypted)
+}
+
+fn decrypt_secret_key(encrypted: &EncryptedSecretKey, passphrase: &str) -> Result<SecretKey> {
+    let key = derive_key(passphrase, encrypted)?;
+    let cipher = ChaCha20Poly1305::new(&key.into());
+    let nonce = hex::decode(&encrypted.nonce).context("Keypair nonce is not valid hex")?;
+    let ciphertext =
+        hex::decode(&encrypted.ciphertext).context("Keypair ciphertext is not valid hex")?;
+    let secret_key_bytes = cipher
+        .decrypt(Nonce::from_slice(&nonce), ciphertext.as_slice())
+        .map_err(|_| {
+            anyhow::anyhow!(
+                "Failed to decrypt keypair: wrong IDENTITY_KEYPAIR_PASSPHRASE or corrupted file"
+            )
+        })?;
+    let secret_key_bigint = num_bigint::BigUint::from_bytes_le(&secret_key_bytes);
+    Ok(SecretKey(secret_key_bigint))
+}
+
+fn write_keypair(
+    keypair_file: &str,
+    server_id: &str,
+    secret_key: &SecretKey,
+    public_key: PublicKey,
+    created_at: &str,
+    passphrase: Option<&str>,
+) -> Result<()> {
+    let json = match passphrase {
+        Some(passphrase) => serde_json::to_string_pretty(&EncryptedIdentityServerKeypair {
+            version: 2,
+            server_id: server_id.to_string(),
+            encrypted_secret_key: encrypt_secret_key(secret_key, passphrase)?,
+            public_key,
+            created_at: created_at.to_string(),
+        })?,
+        None => serde_json::to_string_pretty(&IdentityServerKeypair {
+            server_id: server_id.to_string(),
+            secret_key: hex::encode(secret_key.0.to_bytes_le()),
+            public_key,
+            created_at: created_at.to_string(),
+        })?,
+    };
+    fs::write(keypair_file, json)?;
+    Ok(())
+}
+
+pub fn load_or_create_keypair(
+    keypair_file: &str,
+    passphrase: Option<&str>,
+) -> Result<(String, SecretKey, PublicKey)> {
+    let server_id = SERVER_ID.to_string();
+
+    if fs::metadata(keypair_file).is_ok() {
+        tracing::info!("Loading existing keypair from: {}", keypair_file);
+        let keypair_json = fs::read_to_string(keypair_file)?;
+        let keypair: KeypairFile =
+            serde_json::from_str(&keypair_json).context("Unrecognized keypair file format")?;
+        let was_plaintext = matches!(keypair, KeypairFile::Plain(_));
+
+        let (file_server_id, secret_key, public_key, created_at) = match keypair {
+            KeypairFile::Plain(keypair) => {
+                let secret_key_bytes = hex::decode(&keypair.secret_key)?;
+                let secret_key_bigint = num_bigint::BigUint::from_bytes_le(&secret_key_bytes);
+                let secret_key = SecretKey(secret_key_bigint);
+                (
+                    keypair.server_id,
+                    secret_key,
+                    keypair.public_key,
+                    keypair.created_at,
+                )
+            }
+            KeypairFile::Encrypted(keypair) => {
+                let Some(passphrase) = passphrase else {
+                    bail!(
+                        "Keypair file {keypair_file} is encrypted; \
+                         set IDENTITY_KEYPAIR_PASSPHRASE to load it"
+                    );
+                };
+                let secret_key = decrypt_secret_key(&keypair.encrypted_secret_key, passphrase)?;
+                (
+                    keypair.server_id,
+                    secret_key,
+                    keypair.public_key,
+                    keypair.created_at,
+                )
+            }
+        };
+
+        // Verify server_id matches
+        if file_server_id != server_id {
+            bail!("Keypair server_id mismatch: expected {server_id}, found {file_server_id}");
+        }
+
+        // Verify public key matches
+        if secret_key.public_key() != public_key {
+            bail!("Keypair public key mismatch");
+        }
+
+        // One-time migration: a plaintext file loaded with a passphrase is
+        // re-written encrypted so the secret stops living on disk in the clear
+        if was_plaintext && passphrase.is_some() {
+            tracing::info!("Encrypting previously plaintext keypair file");
+            write_keypair(
+                keypair_file,
+                &server_id,
+                &secret_key,
+                public_key,
+                &created_at,
+                passphrase,
+            )?;
+        }
+
+        tracing::info!("✓ Keypair loaded successfully");
+        tracing::info!("Created at: {}", created_at);
+
+        Ok((server_id, secret_key, public_key))
+    } else {
+        tracing::info!("Creating new keypair and saving to: {}", keypair_file);
+
+        // Generate new keypair
+        let secret_key = SecretKey::new_rand();
+        let public_key = secret_key.public_key();
+
+        write_keypair(
+            keypair_file,
+            &server_id,
+            &secret_key,
+            public_key,
+            &chrono::Utc::now().to_rfc3339(),
+            passphrase,
+        )?;
+
+        tracing::info!(
+            "✓ New keypair created and saved{}",
+            if passphrase.is_some() {
+                " (encrypted)"
+            } else {
+                ""
+            }
+        );
+
+        Ok((server_id, secret_key, public_key))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keypair_path(dir: &tempfile::TempDir) -> String {
+        dir.path().join("keypair.json").to_str().unwrap().to_string()
+    }
+
+    #[test]
+    fn test_encrypted_keypair_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = keypair_path(&dir);
+
+        let (_, secret_key, public_key) =
+            load_or_create_keypair(&path, Some("hunter2")).unwrap();
+
+        // The file carries ciphertext, not the hex secret
+        let json: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(json["version"], 2);
+        assert!(json.get("secret_key").is_none());
+        let secret_hex = hex::encode(secret_key.0.to_bytes_le());
+        assert!(!fs::read_to_string(&path).unwrap().contains(&secret_hex));
+
+        // Loading with the right passphrase recovers the same keypair
+        let (_, loaded_secret, loaded_public) =
+            load_or_create_keypair(&path, Some("hunter2")).unwrap();
+        assert_eq!(loaded_secret.0, secret_key.0);
+        assert_eq!(loaded_public, public_key);
+    }
+
+    #[test]
+    fn test_wrong_passphrase_is_a_hard_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = keypair_path(&dir);
+        load_or_create_keypair(&path, Some("hunter2")).unwrap();
+
+        // Neither a wrong passphrase nor a missing one may fall through to
+        // generating a fresh keypair
+        let err = load_or_create_keypair(&path, Some("wrong")).unwrap_err();
+        assert!(err.to_string().contains("Failed to decrypt"), "{err}");
+        let err = load_or_create_keypair(&path, None).unwrap_err();
+        assert!(err.to_string().contains("is encrypted"), "{err}");
+    }
+
+    #[test]
+    fn test_plaintext_keypair_is_migrated_when_passphrase_appears() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = keypair_path(&dir);
+
+        // Start plaintext, as existing deployments are
+        let (_, secret_key, public_key) = load_or_create_keypair(&path, None).unwrap();
+        assert!(fs::read_to_string(&path).unwrap().contains("secret_key"));
+
+        // First load with a passphrase re-writes the file encrypted...
+        let (_, migrated_secret, _) = load_or_create_keypair(&path, Some("hunter2")).unwrap();
+        assert_eq!(migrated_secret.0, secret_key.0);
+        let secret_hex = hex::encode(secret_key.0.to_bytes_le());
+        assert!(!fs::read_to_string(&path).unwrap().contains(&secret_hex));
+
+        // ...and subsequent loads require it
+        let (_, loaded_secret, loaded_public) =
+            load_or_create_keypair(&path, Some("hunter2")).unwrap();
+        assert_eq!(loaded_secret.0, secret_key.0);
+        assert_eq!(loaded_public, public_key);
+    }
+}
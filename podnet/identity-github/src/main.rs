@@ -1,63 +1,79 @@
 use std::{
     fs,
-    sync::{Arc, Mutex},
+    sync::{Arc, RwLock},
 };
 
 use axum::{
     Router,
-    extract::{Query, State},
-    http::StatusCode,
-    response::{Json, Redirect},
+    body::Body,
+    extract::{Path, Query, State},
+    http::{HeaderMap, HeaderValue, Method, StatusCode, header},
+    response::{IntoResponse, Json, Redirect, Response},
     routing::{get, post},
 };
 use chrono::Utc;
-use pod2::backends::plonky2::primitives::ec::{curve::Point as PublicKey, schnorr::SecretKey};
-use rusqlite::Connection;
+use pod_utils::ValueExt;
+use pod2::{
+    backends::plonky2::primitives::ec::{curve::Point as PublicKey, schnorr::SecretKey},
+    frontend::SignedDict,
+};
 use serde::{Deserialize, Serialize};
-use tower_http::cors::CorsLayer;
+use tower_http::cors::{AllowOrigin, CorsLayer};
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
 mod database;
-mod github;
 mod identity;
+mod keypair;
+mod policy;
+mod providers;
 mod registration;
 
 use database::{
-    delete_user_by_github_id, get_username_by_public_key, initialize_database, insert_user_mapping,
-    user_exists_by_github_id,
+    DbPool, EXPORT_VERSION, ImportOutcome, consume_oauth_state, delete_user_by_provider_id,
+    export_users_page, get_user_by_public_key, get_user_status_by_public_key, import_user,
+    initialize_database, insert_oauth_state, insert_user_mapping, list_revoked_users,
+    lookup_bindings_by_username, revoke_user_by_provider_id, revoke_user_by_public_key,
+    suggest_username, update_user_public_keys, user_exists_by_provider_id,
+    username_taken_by_other,
 };
-use github::{GitHubOAuthClient, GitHubOAuthConfig, OAuthCallbackQuery, parse_oauth_state};
 use identity::{
-    IdentityResponse, ServerInfo, UsernameLookupRequest, UsernameLookupResponse,
+    IdentityResponse, ImportRequest, ImportResponse, ImportRowResult, PublicKeyLookupEntry,
+    PublicKeyLookupRequest, PublicKeyLookupResponse, RevocationEntry, RevocationListResponse,
+    RevokeResponse, ServerInfo, UsernameConflict, UsernameLookupRequest, UsernameLookupResponse,
     create_identity_pod,
 };
-use registration::register_with_podnet_server;
+use keypair::load_or_create_keypair;
+use policy::{AccountPolicy, PolicyRejection};
+use providers::{
+    GitHubProvider, GitLabProvider, OAuthCallbackQuery, OAuthProvider, OAuthProviderConfig,
+    Provider, ProviderRegistry, ProviderUser,
+};
+use registration::{RegistrationSchedule, RegistrationStatus, spawn_registration_task};
 
 // Server state
-pub struct GitHubIdentityServerState {
+#[derive(Clone)]
+pub struct IdentityServerState {
     pub server_id: String,
     pub server_secret_key: Arc<SecretKey>,
     pub server_public_key: PublicKey,
-    pub db_conn: Arc<Mutex<Connection>>,
-    pub oauth_client: GitHubOAuthClient,
-}
-
-impl Clone for GitHubIdentityServerState {
-    fn clone(&self) -> Self {
-        Self {
-            server_id: self.server_id.clone(),
-            server_secret_key: Arc::clone(&self.server_secret_key),
-            server_public_key: self.server_public_key,
-            db_conn: Arc::clone(&self.db_conn),
-            oauth_client: GitHubOAuthClient::new(GitHubOAuthConfig {
-                client_id: std::env::var("GITHUB_CLIENT_ID").expect("GITHUB_CLIENT_ID must be set"),
-                client_secret: std::env::var("GITHUB_CLIENT_SECRET")
-                    .expect("GITHUB_CLIENT_SECRET must be set"),
-                redirect_uri: std::env::var("GITHUB_REDIRECT_URI")
-                    .expect("GITHUB_REDIRECT_URI must be set"),
-            })
-            .expect("Failed to create OAuth client"),
-        }
+    pub db_pool: DbPool,
+    pub providers: Arc<ProviderRegistry>,
+    pub policy: AccountPolicy,
+    pub registration_status: Arc<RwLock<RegistrationStatus>>,
+    /// When set, issuance rejects usernames already bound to another key;
+    /// otherwise conflicting requests are issued under a suffixed name
+    pub require_unique_usernames: bool,
+    /// Token required by the /admin endpoints; with none configured they
+    /// respond 404 as if they didn't exist
+    pub admin_token: Option<String>,
+}
+
+impl IdentityServerState {
+    fn provider(&self, name: &str) -> Result<&Provider, StatusCode> {
+        self.providers.get(name).ok_or_else(|| {
+            tracing::warn!("Unknown OAuth provider requested: {}", name);
+            StatusCode::NOT_FOUND
+        })
     }
 }
 
@@ -66,6 +82,9 @@ impl Clone for GitHubIdentityServerState {
 pub struct AuthUrlRequest {
     pub public_key: PublicKey,
     pub username: String, // Full name provided by user
+    /// Request the extra scope needed to attest org memberships in the pod
+    #[serde(default)]
+    pub include_orgs: bool,
 }
 
 #[derive(Debug, Serialize)]
@@ -78,46 +97,98 @@ pub struct AuthUrlResponse {
 pub struct IdentityRequest {
     pub code: String,
     pub state: String,
-    pub username: String,            // Full name provided by user
-    pub challenge_signature: String, // User signs challenge containing GitHub info + their name
+    pub username: String, // Full name provided by user
+    #[serde(default = "default_provider")]
+    pub provider: String, // OAuth provider name; defaults to github for older clients
+    pub challenge_signature: String, // User signs challenge containing provider info + their name
 }
 
-// Keypair persistence models
-#[derive(Debug, Serialize, Deserialize)]
-pub struct IdentityServerKeypair {
-    pub server_id: String,
-    pub secret_key: String, // hex encoded
-    pub public_key: PublicKey,
-    pub created_at: String,
+fn default_provider() -> String {
+    "github".to_string()
+}
+
+/// Minimum time between SSH-key refreshes for one identity. The provider's
+/// key endpoint is unauthenticated and cheap to hit, but each refresh signs a
+/// fresh pod; this keeps a stolen refresh request from becoming a signing oracle.
+const REFRESH_MIN_INTERVAL_SECONDS: i64 = 3600;
+
+/// How long an issued OAuth state stays redeemable. Long enough to complete
+/// the provider round trip by hand, short enough that a leaked state goes
+/// stale before it is worth replaying.
+const OAUTH_STATE_MAX_AGE_SECONDS: i64 = 600;
+
+/// Re-issue an identity pod with the user's current provider SSH keys. A
+/// signature from the identity's own key suffices: the provider account
+/// mapping is already on record, so no OAuth round is needed.
+#[derive(Debug, Deserialize)]
+pub struct RefreshRequest {
+    /// A dict signed by the identity's key with `request_type = "refresh"`
+    pub refresh_pod: SignedDict,
+}
+
+/// Revocation is authenticated either by a request signed with the identity's
+/// own key, or — for key-loss cases — by a fresh OAuth round proving control
+/// of the provider account behind the identity.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum RevokeRequest {
+    Signed {
+        /// A dict signed by the identity's key with `request_type = "revoke"`
+        revocation_pod: SignedDict,
+    },
+    OAuth {
+        provider: String,
+        code: String,
+    },
 }
 
 // Root endpoint
-async fn root(State(state): State<GitHubIdentityServerState>) -> Json<ServerInfo> {
+async fn root(State(state): State<IdentityServerState>) -> Json<ServerInfo> {
     Json(ServerInfo {
         server_id: state.server_id.clone(),
         public_key: state.server_public_key,
+        registration: state.registration_status.read().unwrap().clone(),
     })
 }
 
-// Step 1: Get GitHub OAuth authorization URL
+// Step 1: Get OAuth authorization URL for the requested provider
 async fn get_auth_url(
-    State(state): State<GitHubIdentityServerState>,
+    State(state): State<IdentityServerState>,
+    Path(provider_name): Path<String>,
     Json(payload): Json<AuthUrlRequest>,
 ) -> Result<Json<AuthUrlResponse>, StatusCode> {
     tracing::info!(
-        "Authorization URL requested for user: {} with public key: {}",
+        "Authorization URL requested for user: {} via {} with public key: {}",
         payload.username,
+        provider_name,
         payload.public_key
     );
 
-    let (auth_url, csrf_token) = state
-        .oauth_client
-        .get_authorization_url(&payload.public_key)
+    let provider = state.provider(&provider_name)?;
+
+    let (auth_url, csrf_token) = provider
+        .authorization_url(&payload.public_key, payload.include_orgs)
         .map_err(|e| {
             tracing::error!("Failed to generate authorization URL: {}", e);
             StatusCode::INTERNAL_SERVER_ERROR
         })?;
 
+    // Record the issued state so completion can validate against what this
+    // server handed out, rather than trusting the round-tripped string
+    insert_oauth_state(
+        &state.db_pool,
+        csrf_token.secret(),
+        &payload.public_key,
+        &payload.username,
+        Utc::now(),
+        payload.include_orgs,
+    )
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to store OAuth state: {}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
     tracing::info!("Generated authorization URL for user: {}", payload.username);
 
     Ok(Json(AuthUrlResponse {
@@ -150,34 +221,49 @@ async fn oauth_complete_page(
 ) -> Result<axum::response::Html<String>, StatusCode> {
     tracing::info!("OAuth completion page requested with code: {}", params.code);
 
-    // Return a simple HTML page that displays the authorization code
-    // The user can copy this code back to the client application
+    // Hand the code and state straight back to the client via its registered
+    // custom URL scheme; the copy UI below stays as the fallback for builds
+    // that haven't registered the scheme. The scheme is configurable so
+    // differently-branded client builds can register their own.
+    let client_scheme =
+        std::env::var("IDENTITY_CLIENT_URI_SCHEME").unwrap_or_else(|_| "podnet".to_string());
+    let query = url::form_urlencoded::Serializer::new(String::new())
+        .append_pair("code", &params.code)
+        .append_pair("state", &params.state)
+        .finish();
+    let deep_link = format!("{client_scheme}://github-oauth?{query}");
+    let code = &params.code;
+
     let html = format!(
         r#"
         <!DOCTYPE html>
         <html>
         <head>
-            <title>GitHub OAuth Complete</title>
+            <title>OAuth Complete</title>
             <style>
                 body {{ font-family: Arial, sans-serif; margin: 40px; }}
                 .container {{ max-width: 600px; margin: 0 auto; }}
                 .code {{ background: #f5f5f5; padding: 15px; border-radius: 5px; font-family: monospace; word-break: break-all; }}
                 .copy-btn {{ background: #0366d6; color: white; border: none; padding: 10px 20px; border-radius: 5px; cursor: pointer; margin-top: 10px; }}
                 .copy-btn:hover {{ background: #0256cc; }}
+                .open-btn {{ display: inline-block; background: #2da44e; color: white; text-decoration: none; padding: 10px 20px; border-radius: 5px; margin-top: 10px; }}
+                .open-btn:hover {{ background: #2c974b; }}
             </style>
         </head>
         <body>
             <div class="container">
-                <h1>🎉 GitHub Authorization Complete!</h1>
-                <p>Your GitHub authorization was successful. Please copy the authorization code below and paste it into the POD2 client:</p>
-                
-                <div class="code" id="authCode">{}</div>
-                
+                <h1>🎉 Authorization Complete!</h1>
+                <p>Your authorization was successful. The POD2 client should open automatically to finish your identity setup.</p>
+
+                <a class="open-btn" id="openClient" href="{deep_link}">🚀 Open POD2 Client</a>
+
+                <p>If the client doesn't open, copy the authorization code below and paste it into the POD2 client instead:</p>
+
+                <div class="code" id="authCode">{code}</div>
+
                 <button class="copy-btn" onclick="copyCode()">📋 Copy Authorization Code</button>
-                
-                <p><small>You can now return to the POD2 client and paste this code to complete your identity setup.</small></p>
             </div>
-            
+
             <script>
                 function copyCode() {{
                     const code = document.getElementById('authCode').textContent;
@@ -189,87 +275,229 @@ async fn oauth_complete_page(
                         }}, 2000);
                     }});
                 }}
+                // Try the handoff automatically; if nothing handles the
+                // scheme the browser stays on this page and the copy UI
+                // still works
+                window.location.href = document.getElementById('openClient').href;
             </script>
         </body>
         </html>
-        "#,
-        params.code
+        "#
     );
 
     Ok(axum::response::Html(html))
 }
 
+// Identity issuance failures: plain status codes for infrastructure errors,
+// a structured 403 body when the account fails the issuance policy
+#[derive(Debug)]
+enum IssueIdentityError {
+    Status(StatusCode),
+    PolicyRejected(PolicyRejection),
+    UsernameTaken(UsernameConflict),
+}
+
+impl From<StatusCode> for IssueIdentityError {
+    fn from(status: StatusCode) -> Self {
+        Self::Status(status)
+    }
+}
+
+impl From<PolicyRejection> for IssueIdentityError {
+    fn from(rejection: PolicyRejection) -> Self {
+        Self::PolicyRejected(rejection)
+    }
+}
+
+impl axum::response::IntoResponse for IssueIdentityError {
+    fn into_response(self) -> axum::response::Response {
+        match self {
+            Self::Status(status) => status.into_response(),
+            Self::PolicyRejected(rejection) => {
+                (StatusCode::FORBIDDEN, Json(rejection)).into_response()
+            }
+            Self::UsernameTaken(conflict) => {
+                (StatusCode::CONFLICT, Json(conflict)).into_response()
+            }
+        }
+    }
+}
+
 // Step 3: Complete identity verification and issue POD
 async fn issue_identity(
-    State(state): State<GitHubIdentityServerState>,
+    State(state): State<IdentityServerState>,
     Json(payload): Json<IdentityRequest>,
-) -> Result<Json<IdentityResponse>, StatusCode> {
-    tracing::info!("Processing GitHub identity request");
+) -> Result<Json<IdentityResponse>, IssueIdentityError> {
+    tracing::info!("Processing {} identity request", payload.provider);
 
-    // Parse the public key from state
-    let public_key = parse_oauth_state(&payload.state).map_err(|e| {
-        tracing::error!("Failed to parse OAuth state: {}", e);
-        StatusCode::BAD_REQUEST
-    })?;
+    let provider = state.provider(&payload.provider)?;
 
-    // Exchange authorization code for access token
-    let access_token = state
-        .oauth_client
-        .exchange_code(oauth2::AuthorizationCode::new(payload.code))
+    // The presented state must be one this server handed out. Consuming it
+    // here makes every state single-use, even when a later check fails.
+    let state_record = consume_oauth_state(&state.db_pool, &payload.state)
         .await
         .map_err(|e| {
-            tracing::error!("Failed to exchange OAuth code: {}", e);
+            tracing::error!("Database error consuming OAuth state: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .ok_or_else(|| {
+            tracing::warn!("Unknown or already consumed OAuth state presented");
             StatusCode::BAD_REQUEST
         })?;
 
-    // Get GitHub user info
-    let github_user = state
-        .oauth_client
-        .get_user_info(&access_token)
+    let state_created_at = chrono::DateTime::parse_from_rfc3339(&state_record.created_at)
+        .map_err(|e| {
+            tracing::error!("Stored OAuth state has invalid created_at: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+    if (Utc::now() - state_created_at.with_timezone(&Utc)).num_seconds()
+        > OAUTH_STATE_MAX_AGE_SECONDS
+    {
+        tracing::warn!("Expired OAuth state presented");
+        return Err(StatusCode::BAD_REQUEST.into());
+    }
+
+    if state_record.username != payload.username {
+        tracing::warn!(
+            "OAuth state issued for username {:?} presented with username {:?}",
+            state_record.username,
+            payload.username
+        );
+        return Err(StatusCode::BAD_REQUEST.into());
+    }
+
+    // The public key comes from the stored record, not from whatever the
+    // client embedded in the state string
+    let public_key: PublicKey = serde_json::from_str(&state_record.public_key_json)
+        .map_err(|e| {
+            tracing::error!("Stored OAuth state has invalid public key: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    // Exchange authorization code for access token
+    let access_token = provider
+        .exchange_code(oauth2::AuthorizationCode::new(payload.code))
         .await
         .map_err(|e| {
-            tracing::error!("Failed to get GitHub user info: {}", e);
+            tracing::error!("Failed to exchange OAuth code: {}", e);
             StatusCode::BAD_REQUEST
         })?;
 
-    // Check if this GitHub user already has an identity and remove it if so
-    {
-        let conn = state.db_conn.lock().unwrap();
-        if user_exists_by_github_id(&conn, github_user.id).map_err(|e| {
-            tracing::error!("Database error checking GitHub user: {}", e);
+    // Get provider user info
+    let provider_user = provider.user_info(&access_token).await.map_err(|e| {
+        tracing::error!("Failed to get {} user info: {}", provider.name(), e);
+        StatusCode::BAD_REQUEST
+    })?;
+
+    // Check if this provider account already has an identity and remove it if so
+    if user_exists_by_provider_id(&state.db_pool, provider.name(), provider_user.id)
+        .await
+        .map_err(|e| {
+            tracing::error!("Database error checking provider user: {}", e);
             StatusCode::INTERNAL_SERVER_ERROR
-        })? {
-            tracing::info!(
-                "GitHub user {} already has an identity, removing old record",
-                github_user.login
-            );
-            delete_user_by_github_id(&conn, github_user.id).map_err(|e| {
-                tracing::error!("Failed to delete existing GitHub user record: {}", e);
+        })?
+    {
+        tracing::info!(
+            "{} user {} already has an identity, removing old record",
+            provider.name(),
+            provider_user.login
+        );
+        delete_user_by_provider_id(&state.db_pool, provider.name(), provider_user.id)
+            .await
+            .map_err(|e| {
+                tracing::error!("Failed to delete existing provider user record: {}", e);
                 StatusCode::INTERNAL_SERVER_ERROR
             })?;
-        }
     }
 
-    // Get SSH keys from GitHub
-    let github_public_keys = state
-        .oauth_client
-        .get_ssh_keys(&github_user.login)
+    // Usernames are unique (case-insensitively) across identities. In strict
+    // mode a taken name is rejected with a free alternative; otherwise the
+    // pod is issued under the alternative and the requested name is kept as
+    // an alias, mirroring what the migration backfill did for old duplicates
+    let mut issued_username = payload.username.clone();
+    let mut username_alias = None;
+    if username_taken_by_other(&state.db_pool, &payload.username, &public_key)
         .await
         .map_err(|e| {
-            tracing::error!("Failed to get GitHub SSH keys: {}", e);
-            StatusCode::BAD_REQUEST
-        })?;
+            tracing::error!("Database error checking username availability: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+    {
+        let suggestion = suggest_username(&state.db_pool, &payload.username)
+            .await
+            .map_err(|e| {
+                tracing::error!("Database error suggesting username: {}", e);
+                StatusCode::INTERNAL_SERVER_ERROR
+            })?;
+        if state.require_unique_usernames {
+            tracing::warn!(
+                "Username {:?} already taken, rejecting (suggesting {:?})",
+                payload.username,
+                suggestion
+            );
+            return Err(IssueIdentityError::UsernameTaken(UsernameConflict {
+                error: format!("Username '{}' is already taken", payload.username),
+                suggested_username: suggestion,
+            }));
+        }
+        tracing::info!(
+            "Username {:?} already taken, issuing as {:?}",
+            payload.username,
+            suggestion
+        );
+        username_alias = Some(payload.username.clone());
+        issued_username = suggestion;
+    }
+
+    // Get SSH keys from the provider
+    let provider_public_keys = provider.public_keys(&provider_user.login).await.map_err(|e| {
+        tracing::error!("Failed to get {} SSH keys: {}", provider.name(), e);
+        StatusCode::BAD_REQUEST
+    })?;
 
     tracing::info!(
-        "Retrieved {} SSH keys for GitHub user: {}",
-        github_public_keys.len(),
-        github_user.login
+        "Retrieved {} SSH keys for {} user: {}",
+        provider_public_keys.len(),
+        provider.name(),
+        provider_user.login
     );
 
+    // Only fetch org memberships when the flow asked for them up front: the
+    // authorization URL only carried the org scope in that case
+    let attested_orgs = if state_record.include_orgs {
+        let orgs = provider.org_memberships(&access_token).await.map_err(|e| {
+            tracing::error!("Failed to get {} org memberships: {}", provider.name(), e);
+            StatusCode::BAD_REQUEST
+        })?;
+        tracing::info!(
+            "Attesting {} org memberships for {} user: {}",
+            orgs.len(),
+            provider.name(),
+            provider_user.login
+        );
+        Some(orgs)
+    } else {
+        None
+    };
+
+    // Enforce account-quality requirements before issuing
+    state
+        .policy
+        .evaluate(&provider_user, &provider_public_keys, Utc::now())
+        .map_err(|rejection| {
+            tracing::warn!(
+                "Rejecting identity request for {} user {}: failed requirements {:?}",
+                provider.name(),
+                provider_user.login,
+                rejection.failed_requirements
+            );
+            rejection
+        })?;
+
     // TODO: Verify challenge signature from user
     // For now, we'll proceed without signature verification
     // In production, you'd want to verify that the user signed a challenge
-    // containing their GitHub info and provided username
+    // containing their provider info and provided username
 
     let oauth_verified_at = Utc::now();
 
@@ -278,10 +506,14 @@ async fn issue_identity(
         &state.server_id,
         &state.server_secret_key,
         &public_key,
-        &payload.username,
-        &github_user,
-        &github_public_keys,
+        &issued_username,
+        provider.name(),
+        &provider_user,
+        &provider_public_keys,
         oauth_verified_at,
+        &state.policy,
+        None,
+        attested_orgs.as_deref(),
     )
     .map_err(|e| {
         tracing::error!("Failed to create identity POD: {}", e);
@@ -289,27 +521,28 @@ async fn issue_identity(
     })?;
 
     // Store user mapping in database
-    {
-        let conn = state.db_conn.lock().unwrap();
-        insert_user_mapping(
-            &conn,
-            &public_key,
-            &payload.username,
-            &github_user.login,
-            github_user.id,
-            &github_public_keys,
-            oauth_verified_at,
-        )
-        .map_err(|e| {
-            tracing::error!("Failed to store user mapping: {}", e);
-            StatusCode::INTERNAL_SERVER_ERROR
-        })?;
-    }
+    insert_user_mapping(
+        &state.db_pool,
+        &public_key,
+        &issued_username,
+        provider.name(),
+        &provider_user.login,
+        provider_user.id,
+        &provider_public_keys,
+        oauth_verified_at,
+        username_alias.as_deref(),
+    )
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to store user mapping: {}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
 
     tracing::info!(
-        "✓ GitHub identity POD issued for user: {} (GitHub: {})",
-        payload.username,
-        github_user.login
+        "✓ Identity POD issued for user: {} ({}: {})",
+        issued_username,
+        provider.name(),
+        provider_user.login
     );
 
     Ok(Json(IdentityResponse { identity_pod }))
@@ -317,17 +550,22 @@ async fn issue_identity(
 
 // Username lookup handler (for compatibility)
 async fn lookup_username_by_public_key(
-    State(state): State<GitHubIdentityServerState>,
+    State(state): State<IdentityServerState>,
     Query(params): Query<UsernameLookupRequest>,
 ) -> Result<Json<UsernameLookupResponse>, StatusCode> {
     tracing::info!("Looking up username for public key: {}", params.public_key);
 
-    let conn = state.db_conn.lock().unwrap();
-
-    match get_username_by_public_key(&conn, &params.public_key) {
-        Ok(Some(username)) => {
-            tracing::info!("✓ Found username: {}", username);
-            Ok(Json(UsernameLookupResponse { username }))
+    match get_user_status_by_public_key(&state.db_pool, &params.public_key).await {
+        Ok(Some(status)) => {
+            tracing::info!(
+                "✓ Found username: {} (revoked: {})",
+                status.username,
+                status.revoked_at.is_some()
+            );
+            Ok(Json(UsernameLookupResponse {
+                username: status.username,
+                revoked_at: status.revoked_at,
+            }))
         }
         Ok(None) => {
             tracing::info!("Username not found for public key: {}", params.public_key);
@@ -340,61 +578,517 @@ async fn lookup_username_by_public_key(
     }
 }
 
-// Keypair management functions
-fn load_or_create_keypair(keypair_file: &str) -> anyhow::Result<(String, SecretKey, PublicKey)> {
-    let server_id = "github-identity-server".to_string();
+// Reverse lookup: the key(s) bound to a username, matched case-insensitively
+// against current names and pre-uniqueness aliases. The podnet server uses
+// this to check uploader_id/username consistency.
+async fn lookup_public_keys_by_username(
+    State(state): State<IdentityServerState>,
+    Query(params): Query<PublicKeyLookupRequest>,
+) -> Result<Json<PublicKeyLookupResponse>, StatusCode> {
+    tracing::info!("Looking up public keys for username: {}", params.username);
+
+    let bindings = lookup_bindings_by_username(&state.db_pool, &params.username)
+        .await
+        .map_err(|e| {
+            tracing::error!("Database error during reverse lookup: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+    if bindings.is_empty() {
+        return Err(StatusCode::NOT_FOUND);
+    }
 
-    if fs::metadata(keypair_file).is_ok() {
-        tracing::info!("Loading existing keypair from: {}", keypair_file);
-        let keypair_json = fs::read_to_string(keypair_file)?;
-        let keypair: IdentityServerKeypair = serde_json::from_str(&keypair_json)?;
+    let bindings = bindings
+        .into_iter()
+        .map(|binding| {
+            let public_key = serde_json::from_str(&binding.public_key_json).map_err(|e| {
+                tracing::error!("Stored user mapping has invalid public key: {}", e);
+                StatusCode::INTERNAL_SERVER_ERROR
+            })?;
+            Ok(PublicKeyLookupEntry {
+                public_key,
+                username: binding.username,
+                revoked_at: binding.revoked_at,
+            })
+        })
+        .collect::<Result<Vec<_>, StatusCode>>()?;
 
-        // Verify server_id matches
-        if keypair.server_id != server_id {
-            return Err(anyhow::anyhow!(
-                "Keypair server_id mismatch: expected {}, found {}",
-                server_id,
-                keypair.server_id
-            ));
+    Ok(Json(PublicKeyLookupResponse { bindings }))
+}
+
+/// Guard for the /admin endpoints: compares the X-Admin-Token header against
+/// the configured token. With no token configured the endpoints answer 404,
+/// so an unconfigured server doesn't advertise an admin surface at all.
+fn check_admin_token(expected: Option<&str>, headers: &HeaderMap) -> Result<(), StatusCode> {
+    let Some(expected) = expected else {
+        tracing::warn!("Admin endpoint requested but IDENTITY_ADMIN_TOKEN is not set");
+        return Err(StatusCode::NOT_FOUND);
+    };
+    match headers.get("x-admin-token").and_then(|v| v.to_str().ok()) {
+        Some(provided) if provided == expected => Ok(()),
+        Some(_) => {
+            tracing::warn!("Admin endpoint requested with an invalid admin token");
+            Err(StatusCode::UNAUTHORIZED)
+        }
+        None => {
+            tracing::warn!("Admin endpoint requested without an X-Admin-Token header");
+            Err(StatusCode::UNAUTHORIZED)
         }
+    }
+}
+
+/// How many user rows each database round-trip contributes to the export
+const EXPORT_PAGE_SIZE: usize = 256;
+
+// Stream a versioned JSON dump of every user mapping. The body is produced a
+// page at a time so exporting a large user base never materializes the whole
+// table in memory; a database failure mid-stream truncates the body, leaving
+// invalid JSON that the importer will refuse.
+async fn export_user_mappings(
+    State(state): State<IdentityServerState>,
+    headers: HeaderMap,
+) -> Result<Response, StatusCode> {
+    check_admin_token(state.admin_token.as_deref(), &headers)?;
+    tracing::info!("Exporting user mappings");
+
+    let pool = state.db_pool.clone();
+    let (tx, rx) = tokio::sync::mpsc::channel::<Result<String, std::convert::Infallible>>(4);
+    tokio::spawn(async move {
+        let opening = format!("{{\"version\":{EXPORT_VERSION},\"users\":[");
+        if tx.send(Ok(opening)).await.is_err() {
+            return;
+        }
+        let mut after_rowid = 0;
+        let mut first = true;
+        loop {
+            let page = match export_users_page(&pool, after_rowid, EXPORT_PAGE_SIZE).await {
+                Ok(page) => page,
+                Err(e) => {
+                    tracing::error!("Export aborted mid-stream: {}", e);
+                    return;
+                }
+            };
+            if page.is_empty() {
+                break;
+            }
+            for (rowid, user) in page {
+                after_rowid = rowid;
+                let row = match serde_json::to_string(&user) {
+                    Ok(row) => row,
+                    Err(e) => {
+                        tracing::error!("Export aborted mid-stream: {}", e);
+                        return;
+                    }
+                };
+                let separator = if first { "" } else { "," };
+                first = false;
+                if tx.send(Ok(format!("{separator}{row}"))).await.is_err() {
+                    return;
+                }
+            }
+        }
+        let _ = tx.send(Ok("]}".to_string())).await;
+    });
+
+    let body = Body::from_stream(tokio_stream::wrappers::ReceiverStream::new(rx));
+    Ok(([(header::CONTENT_TYPE, "application/json")], body).into_response())
+}
+
+// Apply a dump produced by /admin/export. Rows are keyed by
+// (provider, provider_user_id) and applied idempotently: absent rows are
+// inserted, differing rows updated in place, matching rows skipped. A row
+// whose public key doesn't parse is rejected without aborting the rest
+async fn import_user_mappings(
+    State(state): State<IdentityServerState>,
+    headers: HeaderMap,
+    Json(payload): Json<ImportRequest>,
+) -> Result<Json<ImportResponse>, StatusCode> {
+    check_admin_token(state.admin_token.as_deref(), &headers)?;
+    if payload.version != EXPORT_VERSION {
+        tracing::warn!(
+            "Rejecting import with dump version {} (this server speaks version {})",
+            payload.version,
+            EXPORT_VERSION
+        );
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    tracing::info!("Importing {} user mappings", payload.users.len());
+
+    let mut results = Vec::with_capacity(payload.users.len());
+    for user in payload.users {
+        let (provider, provider_user_id, username) = (
+            user.provider.clone(),
+            user.provider_user_id,
+            user.username.clone(),
+        );
+        let (outcome, detail) =
+            if let Err(e) = serde_json::from_str::<PublicKey>(&user.public_key_json) {
+                ("rejected", Some(format!("public key does not parse: {e}")))
+            } else {
+                match import_user(&state.db_pool, user).await {
+                    Ok(ImportOutcome::Inserted) => ("inserted", None),
+                    Ok(ImportOutcome::Updated) => ("updated", None),
+                    Ok(ImportOutcome::Skipped) => ("skipped", None),
+                    Err(e) => ("rejected", Some(e.to_string())),
+                }
+            };
+        results.push(ImportRowResult {
+            provider,
+            provider_user_id,
+            username,
+            outcome: outcome.to_string(),
+            detail,
+        });
+    }
+
+    Ok(Json(ImportResponse {
+        version: EXPORT_VERSION,
+        results,
+    }))
+}
+
+// Revoke an issued identity; see RevokeRequest for the two authentication
+// paths. Revocation is permanent for the issued pod, but the user may
+// re-register afterwards and receive a fresh pod with a new issuance time
+async fn revoke_identity(
+    State(state): State<IdentityServerState>,
+    Json(payload): Json<RevokeRequest>,
+) -> Result<Json<RevokeResponse>, StatusCode> {
+    let revoked_at = Utc::now();
+
+    let public_key = match payload {
+        RevokeRequest::Signed { revocation_pod } => {
+            revocation_pod.verify().map_err(|e| {
+                tracing::warn!("Revocation pod failed verification: {}", e);
+                StatusCode::UNAUTHORIZED
+            })?;
 
-        // Decode secret key
-        let secret_key_bytes = hex::decode(&keypair.secret_key)?;
-        let secret_key_bigint = num_bigint::BigUint::from_bytes_le(&secret_key_bytes);
-        let secret_key = SecretKey(secret_key_bigint);
+            if revocation_pod.get("request_type").and_then(|v| v.as_str()) != Some("revoke") {
+                tracing::warn!("Revocation pod missing request_type = revoke");
+                return Err(StatusCode::BAD_REQUEST);
+            }
+
+            // The key that signed the request is the identity being revoked
+            let public_key = revocation_pod.public_key;
+            let revoked = revoke_user_by_public_key(&state.db_pool, &public_key, revoked_at)
+                .await
+                .map_err(|e| {
+                    tracing::error!("Database error revoking identity: {}", e);
+                    StatusCode::INTERNAL_SERVER_ERROR
+                })?;
+            if !revoked {
+                tracing::warn!("Revocation requested for unknown identity: {}", public_key);
+                return Err(StatusCode::NOT_FOUND);
+            }
+            public_key
+        }
+        RevokeRequest::OAuth { provider, code } => {
+            let provider = state.provider(&provider)?;
+
+            let access_token = provider
+                .exchange_code(oauth2::AuthorizationCode::new(code))
+                .await
+                .map_err(|e| {
+                    tracing::error!("Failed to exchange OAuth code for revocation: {}", e);
+                    StatusCode::BAD_REQUEST
+                })?;
+            let provider_user = provider.user_info(&access_token).await.map_err(|e| {
+                tracing::error!("Failed to get {} user info: {}", provider.name(), e);
+                StatusCode::BAD_REQUEST
+            })?;
 
-        // Verify public key matches
-        let expected_public_key = secret_key.public_key();
-        if expected_public_key != keypair.public_key {
-            return Err(anyhow::anyhow!("Keypair public key mismatch"));
+            let public_key_json = revoke_user_by_provider_id(
+                &state.db_pool,
+                provider.name(),
+                provider_user.id,
+                revoked_at,
+            )
+            .await
+            .map_err(|e| {
+                tracing::error!("Database error revoking identity: {}", e);
+                StatusCode::INTERNAL_SERVER_ERROR
+            })?
+            .ok_or_else(|| {
+                tracing::warn!(
+                    "Revocation requested for {} account {} with no active identity",
+                    provider.name(),
+                    provider_user.login
+                );
+                StatusCode::NOT_FOUND
+            })?;
+            serde_json::from_str(&public_key_json).map_err(|e| {
+                tracing::error!("Failed to parse stored public key: {}", e);
+                StatusCode::INTERNAL_SERVER_ERROR
+            })?
         }
+    };
 
-        tracing::info!("✓ Keypair loaded successfully");
-        tracing::info!("Created at: {}", keypair.created_at);
+    tracing::info!("✓ Identity revoked for public key: {}", public_key);
+    Ok(Json(RevokeResponse {
+        public_key,
+        revoked_at: revoked_at.to_rfc3339(),
+    }))
+}
+
+// Re-fetch the user's SSH keys from their provider and issue a fresh
+// identity pod carrying them, updating the stored mapping
+async fn refresh_identity(
+    State(state): State<IdentityServerState>,
+    Json(payload): Json<RefreshRequest>,
+) -> Result<Json<IdentityResponse>, StatusCode> {
+    payload.refresh_pod.verify().map_err(|e| {
+        tracing::warn!("Refresh pod failed verification: {}", e);
+        StatusCode::UNAUTHORIZED
+    })?;
+
+    if payload.refresh_pod.get("request_type").and_then(|v| v.as_str()) != Some("refresh") {
+        tracing::warn!("Refresh pod missing request_type = refresh");
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let public_key = payload.refresh_pod.public_key;
+    let user = get_user_by_public_key(&state.db_pool, &public_key)
+        .await
+        .map_err(|e| {
+            tracing::error!("Database error loading user for refresh: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .ok_or_else(|| {
+            tracing::warn!("Refresh requested for unknown identity: {}", public_key);
+            StatusCode::NOT_FOUND
+        })?;
+
+    if user.revoked_at.is_some() {
+        tracing::warn!("Refresh requested for revoked identity: {}", public_key);
+        return Err(StatusCode::FORBIDDEN);
+    }
 
-        Ok((server_id, secret_key, keypair.public_key))
+    // Rate limit per user: the last refresh must be old enough
+    if let Some(last_refresh) = user
+        .refreshed_at
+        .as_deref()
+        .and_then(|raw| chrono::DateTime::parse_from_rfc3339(raw).ok())
+        && (Utc::now() - last_refresh.with_timezone(&Utc)).num_seconds()
+            < REFRESH_MIN_INTERVAL_SECONDS
+    {
+        tracing::warn!("Refresh rate limit hit for identity: {}", public_key);
+        return Err(StatusCode::TOO_MANY_REQUESTS);
+    }
+
+    let provider = state.provider(&user.provider)?;
+    let fresh_keys = provider
+        .public_keys(&user.provider_username)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to re-fetch {} SSH keys: {}", provider.name(), e);
+            StatusCode::BAD_GATEWAY
+        })?;
+
+    if fresh_keys == user.provider_public_keys {
+        tracing::info!(
+            "SSH keys unchanged for {} user {}; re-issuing anyway",
+            provider.name(),
+            user.provider_username
+        );
     } else {
-        tracing::info!("Creating new keypair and saving to: {}", keypair_file);
+        tracing::info!(
+            "SSH keys changed for {} user {} ({} -> {} keys)",
+            provider.name(),
+            user.provider_username,
+            user.provider_public_keys.len(),
+            fresh_keys.len()
+        );
+    }
 
-        // Generate new keypair
-        let secret_key = SecretKey::new_rand();
-        let public_key = secret_key.public_key();
+    // Rebuild the provider view from the stored mapping: only the key set is
+    // re-fetched, everything else keeps its issuance-time value
+    let provider_user = ProviderUser {
+        id: user.provider_user_id,
+        login: user.provider_username.clone(),
+        name: None,
+        email: None,
+        created_at: None,
+        public_repos: None,
+        followers: None,
+    };
+    let oauth_verified_at = chrono::DateTime::parse_from_rfc3339(&user.oauth_verified_at)
+        .map_err(|e| {
+            tracing::error!("Stored oauth_verified_at is not RFC 3339: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .with_timezone(&Utc);
 
-        // Save keypair to file
-        let keypair = IdentityServerKeypair {
-            server_id: server_id.clone(),
-            secret_key: hex::encode(secret_key.0.to_bytes_le()),
-            public_key,
-            created_at: chrono::Utc::now().to_rfc3339(),
+    let refreshed_at = Utc::now();
+    let identity_pod = create_identity_pod(
+        &state.server_id,
+        &state.server_secret_key,
+        &public_key,
+        &user.username,
+        provider.name(),
+        &provider_user,
+        &fresh_keys,
+        oauth_verified_at,
+        &state.policy,
+        Some(refreshed_at),
+        // No OAuth token at refresh time, so org memberships cannot be
+        // re-verified; users who want them attested re-run the full flow
+        None,
+    )
+    .map_err(|e| {
+        tracing::error!("Failed to create refreshed identity POD: {}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    update_user_public_keys(&state.db_pool, &public_key, &fresh_keys, refreshed_at)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to update stored SSH keys: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    tracing::info!(
+        "✓ Identity POD refreshed for user: {} ({}: {})",
+        user.username,
+        provider.name(),
+        user.provider_username
+    );
+    Ok(Json(IdentityResponse { identity_pod }))
+}
+
+// Public revocation list for verifiers to consult and cache
+async fn list_revocations(
+    State(state): State<IdentityServerState>,
+) -> Result<Json<RevocationListResponse>, StatusCode> {
+    let revoked = list_revoked_users(&state.db_pool).await.map_err(|e| {
+        tracing::error!("Database error listing revocations: {}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    let revocations = revoked
+        .into_iter()
+        .map(|user| {
+            let public_key = serde_json::from_str(&user.public_key_json).map_err(|e| {
+                tracing::error!("Failed to parse stored public key: {}", e);
+                StatusCode::INTERNAL_SERVER_ERROR
+            })?;
+            Ok(RevocationEntry {
+                public_key,
+                revoked_at: user.revoked_at,
+            })
+        })
+        .collect::<Result<Vec<_>, StatusCode>>()?;
+
+    Ok(Json(RevocationListResponse { revocations }))
+}
+
+fn provider_config_from_env(prefix: &str) -> Option<OAuthProviderConfig> {
+    let client_id = std::env::var(format!("{prefix}_CLIENT_ID")).ok()?;
+    let client_secret = std::env::var(format!("{prefix}_CLIENT_SECRET")).ok()?;
+    let redirect_uri = std::env::var(format!("{prefix}_REDIRECT_URI")).ok()?;
+
+    Some(OAuthProviderConfig {
+        client_id,
+        client_secret,
+        redirect_uri,
+    })
+}
+
+fn provider_registry_from_env() -> anyhow::Result<ProviderRegistry> {
+    let mut registry = ProviderRegistry::new();
+
+    if let Some(config) = provider_config_from_env("GITHUB") {
+        tracing::info!("GitHub OAuth Client ID: {}", config.client_id);
+        tracing::info!("GitHub Redirect URI: {}", config.redirect_uri);
+        registry.register(Provider::GitHub(GitHubProvider::new(config)?));
+    }
+
+    if let Some(config) = provider_config_from_env("GITLAB") {
+        tracing::info!("GitLab OAuth Client ID: {}", config.client_id);
+        tracing::info!("GitLab Redirect URI: {}", config.redirect_uri);
+        let provider = match std::env::var("GITLAB_BASE_URL") {
+            Ok(base_url) => GitLabProvider::with_base_url(config, &base_url)?,
+            Err(_) => GitLabProvider::new(config)?,
         };
+        registry.register(Provider::GitLab(provider));
+    }
+
+    if registry.is_empty() {
+        return Err(anyhow::anyhow!(
+            "No OAuth providers configured: set GITHUB_CLIENT_ID/GITHUB_CLIENT_SECRET/GITHUB_REDIRECT_URI and/or the GITLAB_ equivalents"
+        ));
+    }
+
+    Ok(registry)
+}
 
-        let keypair_json = serde_json::to_string_pretty(&keypair)?;
-        fs::write(keypair_file, keypair_json)?;
+/// How long browsers may cache preflight responses
+const PREFLIGHT_MAX_AGE: std::time::Duration = std::time::Duration::from_secs(3600);
 
-        tracing::info!("✓ New keypair created and saved");
+/// Build the CORS layer from the `ALLOWED_ORIGINS` env var, a comma-separated
+/// list of origins browsers may call this server from. When unset the server
+/// keeps the historical permissive behaviour, with a warning: an identity
+/// endpoint open to every origin invites cross-site abuse in production.
+fn build_cors_layer() -> CorsLayer {
+    cors_layer_for_origins(std::env::var("ALLOWED_ORIGINS").ok().as_deref())
+}
+
+fn cors_layer_for_origins(allowed_origins: Option<&str>) -> CorsLayer {
+    let Some(allowed_origins) = allowed_origins else {
+        tracing::warn!(
+            "ALLOWED_ORIGINS not set; allowing requests from any origin. Set a \
+             comma-separated origin list to restrict browser access."
+        );
+        return CorsLayer::permissive();
+    };
+
+    let origins: Vec<HeaderValue> = allowed_origins
+        .split(',')
+        .map(str::trim)
+        .filter(|origin| !origin.is_empty())
+        .filter_map(|origin| match origin.parse() {
+            Ok(value) => Some(value),
+            Err(_) => {
+                tracing::warn!("Ignoring invalid CORS origin: {origin}");
+                None
+            }
+        })
+        .collect();
+
+    // The identity endpoints POST JSON bodies, which triggers preflights, so
+    // the content-type header must be allowed explicitly
+    CorsLayer::new()
+        .allow_origin(AllowOrigin::list(origins))
+        .allow_methods([Method::GET, Method::POST])
+        .allow_headers([header::CONTENT_TYPE])
+        .max_age(PREFLIGHT_MAX_AGE)
+}
+
+/// Completes on SIGINT or SIGTERM, letting axum stop accepting connections
+/// and drain in-flight requests before the process (and its pooled SQLite
+/// connections) go away.
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        tokio::signal::ctrl_c()
+            .await
+            .expect("failed to install Ctrl+C handler");
+    };
 
-        Ok((server_id, secret_key, public_key))
+    #[cfg(unix)]
+    let terminate = async {
+        tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+            .expect("failed to install SIGTERM handler")
+            .recv()
+            .await;
+    };
+
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {},
+        _ = terminate => {},
     }
+
+    tracing::info!("Shutdown signal received, draining in-flight requests");
 }
 
 #[tokio::main]
@@ -408,74 +1102,91 @@ async fn main() -> anyhow::Result<()> {
         .with(tracing_subscriber::fmt::layer())
         .init();
 
-    tracing::info!("Starting PodNet GitHub Identity Server...");
+    tracing::info!("Starting PodNet OAuth Identity Server...");
 
-    // Load environment variables
-    let github_client_id = std::env::var("GITHUB_CLIENT_ID")
-        .expect("GITHUB_CLIENT_ID environment variable must be set");
-    let github_client_secret = std::env::var("GITHUB_CLIENT_SECRET")
-        .expect("GITHUB_CLIENT_SECRET environment variable must be set");
-    let github_redirect_uri = std::env::var("GITHUB_REDIRECT_URI")
-        .expect("GITHUB_REDIRECT_URI environment variable must be set");
+    // Register every provider whose OAuth credentials are configured
+    let providers = provider_registry_from_env()?;
+    tracing::info!("Configured OAuth providers: {:?}", providers.names());
 
-    tracing::info!("GitHub OAuth Client ID: {}", github_client_id);
-    tracing::info!("GitHub Redirect URI: {}", github_redirect_uri);
+    let policy = AccountPolicy::from_env();
+    tracing::info!("Account issuance policy: {:?}", policy);
 
     // Load or create server keypair
     let keypair_file = std::env::var("IDENTITY_KEYPAIR_FILE")
         .unwrap_or_else(|_| "github-identity-server-keypair.json".to_string());
     tracing::info!("Using keypair file: {}", keypair_file);
 
-    let (server_id, server_secret_key, server_public_key) = load_or_create_keypair(&keypair_file)?;
+    // With a passphrase set, the secret key is stored encrypted (and a legacy
+    // plaintext file is re-encrypted on first load)
+    let keypair_passphrase = std::env::var("IDENTITY_KEYPAIR_PASSPHRASE").ok();
+    let (server_id, server_secret_key, server_public_key) =
+        load_or_create_keypair(&keypair_file, keypair_passphrase.as_deref())?;
 
-    tracing::info!("GitHub Identity Server ID: {}", server_id);
+    tracing::info!("Identity Server ID: {}", server_id);
     tracing::info!("Server Public Key: {}", server_public_key);
 
-    // Initialize OAuth client
-    let oauth_config = GitHubOAuthConfig {
-        client_id: github_client_id,
-        client_secret: github_client_secret,
-        redirect_uri: github_redirect_uri,
-    };
-    let oauth_client = GitHubOAuthClient::new(oauth_config)?;
-
-    // Attempt to register with podnet-server
     let podnet_server_url =
         std::env::var("PODNET_SERVER_URL").unwrap_or_else(|_| "http://localhost:3000".to_string());
 
-    tracing::info!("Attempting to register with podnet-server...");
-    if let Err(e) =
-        register_with_podnet_server(&server_id, &server_secret_key, &podnet_server_url).await
-    {
-        tracing::warn!("Failed to register with podnet-server: {}", e);
-        tracing::warn!("Identity server will continue running, but won't be registered.");
-        tracing::warn!("Issued identity pods may not be accepted by podnet-server.");
-    }
+    // Register with podnet-server in the background, retrying with backoff
+    // until it succeeds and re-registering periodically as a heartbeat. The
+    // current status is reported on GET /.
+    let server_secret_key = Arc::new(server_secret_key);
+    let registration_status = Arc::new(RwLock::new(RegistrationStatus::default()));
+    tracing::info!("Registering with podnet-server in the background...");
+    spawn_registration_task(
+        server_id.clone(),
+        server_secret_key.clone(),
+        podnet_server_url,
+        RegistrationSchedule::default(),
+        registration_status.clone(),
+    );
 
     // Initialize database
     let db_path = std::env::var("IDENTITY_DATABASE_PATH")
         .unwrap_or_else(|_| "github-identity-users.db".to_string());
     tracing::info!("Using database file: {}", db_path);
 
-    let db_conn = initialize_database(&db_path)?;
-    let db_conn = Arc::new(Mutex::new(db_conn));
+    let db_pool = initialize_database(&db_path).await?;
 
-    let state = GitHubIdentityServerState {
+    // Uniqueness is enforced unless explicitly disabled; with it disabled,
+    // conflicting registrations get a suffixed name instead of a 409
+    let require_unique_usernames = std::env::var("IDENTITY_REQUIRE_UNIQUE_USERNAMES")
+        .map(|v| v.to_lowercase() != "false")
+        .unwrap_or(true);
+
+    // The /admin endpoints only exist when a token is configured
+    let admin_token = std::env::var("IDENTITY_ADMIN_TOKEN").ok();
+    if admin_token.is_none() {
+        tracing::info!("IDENTITY_ADMIN_TOKEN not set; /admin endpoints disabled");
+    }
+
+    let state = IdentityServerState {
         server_id: server_id.clone(),
-        server_secret_key: Arc::new(server_secret_key),
+        server_secret_key,
         server_public_key,
-        db_conn,
-        oauth_client,
+        db_pool,
+        providers: Arc::new(providers),
+        policy,
+        registration_status,
+        require_unique_usernames,
+        admin_token,
     };
 
     let app = Router::new()
         .route("/", get(root))
-        .route("/auth/github", post(get_auth_url))
-        .route("/auth/github/callback", get(oauth_callback))
+        .route("/auth/:provider", post(get_auth_url))
+        .route("/auth/:provider/callback", get(oauth_callback))
         .route("/identity/complete", get(oauth_complete_page))
         .route("/identity", post(issue_identity))
+        .route("/identity/refresh", post(refresh_identity))
+        .route("/identity/revoke", post(revoke_identity))
+        .route("/revocations", get(list_revocations))
         .route("/lookup", get(lookup_username_by_public_key))
-        .layer(CorsLayer::permissive())
+        .route("/lookup/by-username", get(lookup_public_keys_by_username))
+        .route("/admin/export", get(export_user_mappings))
+        .route("/admin/import", post(import_user_mappings))
+        .layer(build_cors_layer())
         .with_state(state);
 
     // Configure server port
@@ -491,17 +1202,1100 @@ async fn main() -> anyhow::Result<()> {
     tracing::info!("Binding to {}...", bind_addr);
     let listener = tokio::net::TcpListener::bind(&bind_addr).await?;
     tracing::info!(
-        "GitHub Identity server running on http://localhost:{}",
+        "Identity server running on http://localhost:{}",
         port
     );
     tracing::info!("Available endpoints:");
-    tracing::info!("  GET  /                      - Server info");
-    tracing::info!("  POST /auth/github           - Get GitHub OAuth authorization URL");
-    tracing::info!("  GET  /auth/github/callback  - Handle OAuth callback");
-    tracing::info!("  GET  /identity/complete     - OAuth completion page with authorization code");
-    tracing::info!("  POST /identity              - Complete identity verification and get POD");
-    tracing::info!("  GET  /lookup                - Look up username by public key");
-
-    axum::serve(listener, app).await?;
+    tracing::info!("  GET  /                         - Server info");
+    tracing::info!("  POST /auth/:provider           - Get OAuth authorization URL");
+    tracing::info!("  GET  /auth/:provider/callback  - Handle OAuth callback");
+    tracing::info!(
+        "  GET  /identity/complete        - OAuth completion page with authorization code"
+    );
+    tracing::info!("  POST /identity                 - Complete identity verification and get POD");
+    tracing::info!("  POST /identity/refresh         - Re-issue a pod with current SSH keys");
+    tracing::info!("  POST /identity/revoke          - Revoke an issued identity");
+    tracing::info!("  GET  /revocations              - List revoked identities");
+    tracing::info!("  GET  /lookup                   - Look up username by public key");
+    tracing::info!("  GET  /lookup/by-username       - Look up public key(s) by username");
+    tracing::info!("  GET  /admin/export             - Stream a dump of all user mappings");
+    tracing::info!("  POST /admin/import             - Apply a previously exported dump");
+
+    axum::serve(listener, app)
+        .with_graceful_shutdown(shutdown_signal())
+        .await?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use pod2::{backends::plonky2::signer::Signer, frontend::SignedDictBuilder, middleware::Params};
+    use serde_json::json;
+
+    use super::*;
+
+    async fn test_state(providers: ProviderRegistry) -> IdentityServerState {
+        let db_pool = initialize_database(":memory:").await.unwrap();
+        let server_secret_key = SecretKey::new_rand();
+        IdentityServerState {
+            server_id: "test-identity-server".to_string(),
+            server_public_key: server_secret_key.public_key(),
+            server_secret_key: Arc::new(server_secret_key),
+            db_pool,
+            providers: Arc::new(providers),
+            policy: AccountPolicy::default(),
+            registration_status: Arc::new(RwLock::new(RegistrationStatus::default())),
+            require_unique_usernames: true,
+            admin_token: Some("test-admin-token".to_string()),
+        }
+    }
+
+    async fn serve(app: Router) -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+        format!("http://{addr}")
+    }
+
+    fn mock_github() -> Router {
+        Router::new()
+            .route(
+                "/login/oauth/access_token",
+                post(|| async {
+                    Json(json!({
+                        "access_token": "test-access-token",
+                        "token_type": "bearer",
+                        "scope": ""
+                    }))
+                }),
+            )
+            .route(
+                "/user",
+                get(|| async {
+                    Json(json!({
+                        "id": 42,
+                        "login": "octocat",
+                        "name": "Octo Cat",
+                        "email": null
+                    }))
+                }),
+            )
+            .route(
+                "/octocat.keys",
+                get(|| async { "ssh-ed25519 AAAAkey1\n" }),
+            )
+            .route(
+                "/user/orgs",
+                get(|| async {
+                    Json(json!([
+                        {"login": "0xparc"},
+                        {"login": "zk-guild"}
+                    ]))
+                }),
+            )
+    }
+
+    fn github_registry_at(base_url: &str) -> ProviderRegistry {
+        let config = OAuthProviderConfig {
+            client_id: "test-client-id".to_string(),
+            client_secret: "test-client-secret".to_string(),
+            redirect_uri: "http://localhost:3001/auth/github/callback".to_string(),
+        };
+        let mut registry = ProviderRegistry::new();
+        registry.register(Provider::GitHub(
+            GitHubProvider::with_base_urls(config, base_url, base_url).unwrap(),
+        ));
+        registry
+    }
+
+    async fn github_registry() -> ProviderRegistry {
+        let base_url = serve(mock_github()).await;
+        github_registry_at(&base_url)
+    }
+
+    async fn insert_test_user(state: &IdentityServerState, public_key: &PublicKey) {
+        insert_user_mapping(
+            &state.db_pool,
+            public_key,
+            "Alice",
+            "github",
+            "octocat",
+            42,
+            &["ssh-ed25519 AAAAkey1".to_string()],
+            Utc::now(),
+            None,
+        )
+        .await
+        .unwrap();
+    }
+
+    fn revocation_pod(secret_key: SecretKey) -> SignedDict {
+        let mut builder = SignedDictBuilder::new(&Params::default());
+        builder.insert("request_type", "revoke");
+        builder.sign(&Signer(secret_key)).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_signed_revocation_marks_identity_revoked() {
+        let state = test_state(ProviderRegistry::new()).await;
+        let user_sk = SecretKey::new_rand();
+        let user_pk = user_sk.public_key();
+        insert_test_user(&state, &user_pk).await;
+
+        let response = revoke_identity(
+            State(state.clone()),
+            Json(RevokeRequest::Signed {
+                revocation_pod: revocation_pod(user_sk),
+            }),
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.public_key, user_pk);
+
+        // The lookup now reports the revocation...
+        let status = get_user_status_by_public_key(&state.db_pool, &user_pk)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(status.username, "Alice");
+        assert_eq!(status.revoked_at.as_deref(), Some(response.revoked_at.as_str()));
+
+        // ...and so does the public revocation list
+        let revocations = list_revocations(State(state.clone())).await.unwrap();
+        assert_eq!(revocations.revocations.len(), 1);
+        assert_eq!(revocations.revocations[0].public_key, user_pk);
+    }
+
+    #[tokio::test]
+    async fn test_signed_revocation_rejects_bad_requests() {
+        let state = test_state(ProviderRegistry::new()).await;
+        let user_sk = SecretKey::new_rand();
+        insert_test_user(&state, &user_sk.public_key()).await;
+
+        // A pod without the revoke marker must not revoke anything
+        let mut builder = SignedDictBuilder::new(&Params::default());
+        builder.insert("request_type", "publish");
+        let wrong_type = builder.sign(&Signer(user_sk)).unwrap();
+        let status = revoke_identity(
+            State(state.clone()),
+            Json(RevokeRequest::Signed {
+                revocation_pod: wrong_type,
+            }),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        // A valid pod from a key with no identity finds nothing to revoke
+        let status = revoke_identity(
+            State(state.clone()),
+            Json(RevokeRequest::Signed {
+                revocation_pod: revocation_pod(SecretKey::new_rand()),
+            }),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(status, StatusCode::NOT_FOUND);
+
+        let revocations = list_revocations(State(state)).await.unwrap();
+        assert!(revocations.revocations.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_oauth_revocation_covers_key_loss() {
+        let state = test_state(github_registry().await).await;
+        let user_pk = SecretKey::new_rand().public_key();
+        insert_test_user(&state, &user_pk).await;
+
+        // No signature: the fresh OAuth round alone authenticates the request
+        let response = revoke_identity(
+            State(state.clone()),
+            Json(RevokeRequest::OAuth {
+                provider: "github".to_string(),
+                code: "test-code".to_string(),
+            }),
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.public_key, user_pk);
+
+        // A second round finds no active identity for the provider account
+        let status = revoke_identity(
+            State(state),
+            Json(RevokeRequest::OAuth {
+                provider: "github".to_string(),
+                code: "test-code".to_string(),
+            }),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_reregistration_after_revocation_issues_fresh_pod() {
+        let state = test_state(github_registry().await).await;
+        let user_sk = SecretKey::new_rand();
+        let user_pk = user_sk.public_key();
+
+        let issue_request = || IdentityRequest {
+            code: "test-code".to_string(),
+            state: "test-oauth-state".to_string(),
+            username: "Alice".to_string(),
+            provider: "github".to_string(),
+            challenge_signature: String::new(),
+        };
+
+        // Each issuance consumes its state, so stage a fresh one per attempt
+        let stage_state = || async {
+            insert_oauth_state(
+                &state.db_pool,
+                "test-oauth-state",
+                &user_pk,
+                "Alice",
+                Utc::now(),
+                false,
+            )
+            .await
+            .unwrap();
+        };
+
+        stage_state().await;
+        let first = issue_identity(State(state.clone()), Json(issue_request()))
+            .await
+            .unwrap();
+        let first_issued_at = first.identity_pod.get("issued_at").cloned().unwrap();
+
+        revoke_identity(
+            State(state.clone()),
+            Json(RevokeRequest::Signed {
+                revocation_pod: revocation_pod(user_sk),
+            }),
+        )
+        .await
+        .unwrap();
+
+        // Re-registering clears the revocation and issues a fresh pod with a
+        // new issuance timestamp
+        stage_state().await;
+        let second = issue_identity(State(state.clone()), Json(issue_request()))
+            .await
+            .unwrap();
+        second.identity_pod.verify().unwrap();
+        let second_issued_at = second.identity_pod.get("issued_at").cloned().unwrap();
+        assert_ne!(first_issued_at, second_issued_at);
+
+        let status = get_user_status_by_public_key(&state.db_pool, &user_pk)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(status.revoked_at.is_none());
+    }
+
+    fn issue_request_with_state(state_token: &str, username: &str) -> IdentityRequest {
+        IdentityRequest {
+            code: "test-code".to_string(),
+            state: state_token.to_string(),
+            username: username.to_string(),
+            provider: "github".to_string(),
+            challenge_signature: String::new(),
+        }
+    }
+
+    fn assert_bad_request(err: IssueIdentityError) {
+        match err {
+            IssueIdentityError::Status(status) => assert_eq!(status, StatusCode::BAD_REQUEST),
+            other => panic!("expected a 400 status, got {other:?}"),
+        }
+    }
+
+    /// A username holder on a different provider account than the mocked
+    /// GitHub user, so issuance doesn't clear it as a re-registration
+    async fn insert_username_holder(state: &IdentityServerState, username: &str) -> PublicKey {
+        let holder_pk = SecretKey::new_rand().public_key();
+        insert_user_mapping(
+            &state.db_pool,
+            &holder_pk,
+            username,
+            "github",
+            "someone-else",
+            7,
+            &["ssh-ed25519 AAAAother".to_string()],
+            Utc::now(),
+            None,
+        )
+        .await
+        .unwrap();
+        holder_pk
+    }
+
+    #[tokio::test]
+    async fn test_issue_identity_rejects_taken_username_case_insensitively() {
+        let state = test_state(github_registry().await).await;
+        insert_username_holder(&state, "Alice").await;
+
+        let user_pk = SecretKey::new_rand().public_key();
+        insert_oauth_state(&state.db_pool, "token", &user_pk, "alice", Utc::now(), false)
+            .await
+            .unwrap();
+
+        // "alice" differs from the stored "Alice" only by case; the conflict
+        // response offers the first free suffixed name
+        let err = issue_identity(
+            State(state.clone()),
+            Json(issue_request_with_state("token", "alice")),
+        )
+        .await
+        .unwrap_err();
+        match err {
+            IssueIdentityError::UsernameTaken(conflict) => {
+                assert_eq!(conflict.suggested_username, "alice-2");
+            }
+            other => panic!("expected a username conflict, got {other:?}"),
+        }
+
+        // The holder keeps the name; nothing was issued for the new key
+        assert!(
+            get_user_status_by_public_key(&state.db_pool, &user_pk)
+                .await
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_relaxed_uniqueness_issues_under_suffixed_alias() {
+        let mut state = test_state(github_registry().await).await;
+        state.require_unique_usernames = false;
+        insert_username_holder(&state, "Alice").await;
+
+        let user_pk = SecretKey::new_rand().public_key();
+        insert_oauth_state(&state.db_pool, "token", &user_pk, "Alice", Utc::now(), false)
+            .await
+            .unwrap();
+
+        let response = issue_identity(
+            State(state.clone()),
+            Json(issue_request_with_state("token", "Alice")),
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            response.identity_pod.get("username").and_then(|v| v.as_str()),
+            Some("Alice-2")
+        );
+
+        // The requested name survives as an alias, so a reverse lookup by
+        // "alice" finds both identities
+        let lookup = lookup_public_keys_by_username(
+            State(state),
+            Query(PublicKeyLookupRequest {
+                username: "alice".to_string(),
+            }),
+        )
+        .await
+        .unwrap();
+        let usernames: Vec<&str> = lookup
+            .bindings
+            .iter()
+            .map(|entry| entry.username.as_str())
+            .collect();
+        assert_eq!(usernames, vec!["Alice", "Alice-2"]);
+    }
+
+    #[tokio::test]
+    async fn test_lookup_by_username_returns_bound_key() {
+        let state = test_state(ProviderRegistry::new()).await;
+        let user_pk = SecretKey::new_rand().public_key();
+        insert_test_user(&state, &user_pk).await;
+
+        let lookup = lookup_public_keys_by_username(
+            State(state.clone()),
+            Query(PublicKeyLookupRequest {
+                username: "alice".to_string(),
+            }),
+        )
+        .await
+        .unwrap();
+        assert_eq!(lookup.bindings.len(), 1);
+        assert_eq!(lookup.bindings[0].public_key, user_pk);
+        assert!(lookup.bindings[0].revoked_at.is_none());
+
+        let status = lookup_public_keys_by_username(
+            State(state),
+            Query(PublicKeyLookupRequest {
+                username: "nobody".to_string(),
+            }),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+
+    fn admin_headers() -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert("x-admin-token", HeaderValue::from_static("test-admin-token"));
+        headers
+    }
+
+    async fn insert_extra_user(
+        state: &IdentityServerState,
+        public_key: &PublicKey,
+        username: &str,
+        login: &str,
+        provider_user_id: i64,
+        username_alias: Option<&str>,
+    ) {
+        insert_user_mapping(
+            &state.db_pool,
+            public_key,
+            username,
+            "github",
+            login,
+            provider_user_id,
+            &[format!("ssh-ed25519 AAAA{login}")],
+            Utc::now(),
+            username_alias,
+        )
+        .await
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_admin_endpoints_require_token() {
+        let state = test_state(ProviderRegistry::new()).await;
+
+        let status = export_user_mappings(State(state.clone()), HeaderMap::new())
+            .await
+            .unwrap_err();
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+        let mut wrong = HeaderMap::new();
+        wrong.insert("x-admin-token", HeaderValue::from_static("not-the-token"));
+        let status = export_user_mappings(State(state.clone()), wrong)
+            .await
+            .unwrap_err();
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+        // With no token configured the endpoints pretend not to exist, even
+        // for a caller presenting some token
+        let mut unconfigured = state;
+        unconfigured.admin_token = None;
+        let status = export_user_mappings(State(unconfigured), admin_headers())
+            .await
+            .unwrap_err();
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+
+    async fn export_dump(state: &IdentityServerState) -> ImportRequest {
+        let response = export_user_mappings(State(state.clone()), admin_headers())
+            .await
+            .unwrap();
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        serde_json::from_slice(&bytes).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_export_import_round_trip() {
+        let source = test_state(ProviderRegistry::new()).await;
+        let alice_pk = SecretKey::new_rand().public_key();
+        insert_test_user(&source, &alice_pk).await;
+        let bob_pk = SecretKey::new_rand().public_key();
+        insert_extra_user(&source, &bob_pk, "Bob-2", "octodog", 43, Some("Bob")).await;
+        let carol_pk = SecretKey::new_rand().public_key();
+        insert_extra_user(&source, &carol_pk, "Carol", "octofox", 44, None).await;
+        revoke_user_by_public_key(&source.db_pool, &carol_pk, Utc::now())
+            .await
+            .unwrap();
+
+        let dump = export_dump(&source).await;
+        assert_eq!(dump.version, EXPORT_VERSION);
+        assert_eq!(dump.users.len(), 3);
+
+        let target = test_state(ProviderRegistry::new()).await;
+        let report = import_user_mappings(
+            State(target.clone()),
+            admin_headers(),
+            Json(ImportRequest {
+                version: dump.version,
+                users: dump.users,
+            }),
+        )
+        .await
+        .unwrap();
+        assert!(report.results.iter().all(|r| r.outcome == "inserted"));
+
+        // The imported database answers lookups exactly like the source
+        for pk in [&alice_pk, &bob_pk, &carol_pk] {
+            let original = get_user_by_public_key(&source.db_pool, pk)
+                .await
+                .unwrap()
+                .unwrap();
+            let imported = get_user_by_public_key(&target.db_pool, pk)
+                .await
+                .unwrap()
+                .unwrap();
+            assert_eq!(imported.username, original.username);
+            assert_eq!(imported.provider_user_id, original.provider_user_id);
+            assert_eq!(imported.provider_public_keys, original.provider_public_keys);
+            assert_eq!(imported.revoked_at, original.revoked_at);
+        }
+        let bindings = lookup_bindings_by_username(&target.db_pool, "bob")
+            .await
+            .unwrap();
+        assert_eq!(bindings.len(), 1);
+        assert_eq!(bindings[0].username, "Bob-2");
+
+        // Re-importing the same dump is a no-op
+        let dump = export_dump(&source).await;
+        let report = import_user_mappings(
+            State(target.clone()),
+            admin_headers(),
+            Json(ImportRequest {
+                version: dump.version,
+                users: dump.users,
+            }),
+        )
+        .await
+        .unwrap();
+        assert!(report.results.iter().all(|r| r.outcome == "skipped"));
+
+        // A row that changed upstream updates the existing mapping in place
+        let mut dump = export_dump(&source).await;
+        let alice_row = dump
+            .users
+            .iter_mut()
+            .find(|u| u.provider_user_id == 42)
+            .unwrap();
+        alice_row.username = "Alice Renamed".to_string();
+        let report = import_user_mappings(State(target.clone()), admin_headers(), Json(dump))
+            .await
+            .unwrap();
+        let outcomes: Vec<_> = report.results.iter().map(|r| r.outcome.as_str()).collect();
+        assert_eq!(outcomes.iter().filter(|o| **o == "updated").count(), 1);
+        assert_eq!(outcomes.iter().filter(|o| **o == "skipped").count(), 2);
+        let renamed = get_user_by_public_key(&target.db_pool, &alice_pk)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(renamed.username, "Alice Renamed");
+    }
+
+    #[tokio::test]
+    async fn test_import_validates_version_and_rows() {
+        let state = test_state(ProviderRegistry::new()).await;
+
+        let status = import_user_mappings(
+            State(state.clone()),
+            admin_headers(),
+            Json(ImportRequest {
+                version: EXPORT_VERSION + 1,
+                users: vec![],
+            }),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        let bad_row = database::ExportedUser {
+            public_key_json: "not a public key".to_string(),
+            username: "Mallory".to_string(),
+            username_alias: None,
+            provider: "github".to_string(),
+            provider_username: "mallory".to_string(),
+            provider_user_id: 666,
+            provider_public_keys: vec![],
+            oauth_verified_at: Utc::now().to_rfc3339(),
+            issued_at: Utc::now().to_rfc3339(),
+            revoked_at: None,
+            refreshed_at: None,
+        };
+        let report = import_user_mappings(
+            State(state.clone()),
+            admin_headers(),
+            Json(ImportRequest {
+                version: EXPORT_VERSION,
+                users: vec![bad_row],
+            }),
+        )
+        .await
+        .unwrap();
+        assert_eq!(report.results.len(), 1);
+        assert_eq!(report.results[0].outcome, "rejected");
+        assert!(report.results[0].detail.is_some());
+        assert!(
+            lookup_bindings_by_username(&state.db_pool, "Mallory")
+                .await
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_issue_identity_rejects_expired_state() {
+        let state = test_state(github_registry().await).await;
+        let user_pk = SecretKey::new_rand().public_key();
+        insert_oauth_state(
+            &state.db_pool,
+            "stale-state",
+            &user_pk,
+            "Alice",
+            Utc::now() - chrono::Duration::seconds(OAUTH_STATE_MAX_AGE_SECONDS + 1),
+            false,
+        )
+        .await
+        .unwrap();
+
+        let err = issue_identity(
+            State(state),
+            Json(issue_request_with_state("stale-state", "Alice")),
+        )
+        .await
+        .unwrap_err();
+        assert_bad_request(err);
+    }
+
+    #[tokio::test]
+    async fn test_issue_identity_rejects_reused_state() {
+        let state = test_state(github_registry().await).await;
+        let user_pk = SecretKey::new_rand().public_key();
+        insert_oauth_state(&state.db_pool, "one-shot-state", &user_pk, "Alice", Utc::now(), false)
+            .await
+            .unwrap();
+
+        issue_identity(
+            State(state.clone()),
+            Json(issue_request_with_state("one-shot-state", "Alice")),
+        )
+        .await
+        .unwrap();
+
+        // The state was consumed by the first issuance
+        let err = issue_identity(
+            State(state),
+            Json(issue_request_with_state("one-shot-state", "Alice")),
+        )
+        .await
+        .unwrap_err();
+        assert_bad_request(err);
+    }
+
+    #[tokio::test]
+    async fn test_issue_identity_attests_org_memberships() {
+        use pod2::{lang::parse, middleware::Value};
+        use pod2_new_solver::{Engine, EngineConfigBuilder, ImmutableEdbBuilder, OpRegistry};
+
+        let state = test_state(github_registry().await).await;
+        let server_pk = Value::from(state.server_public_key);
+        let user_pk = SecretKey::new_rand().public_key();
+        insert_oauth_state(&state.db_pool, "org-state", &user_pk, "Alice", Utc::now(), true)
+            .await
+            .unwrap();
+
+        let response = issue_identity(
+            State(state),
+            Json(issue_request_with_state("org-state", "Alice")),
+        )
+        .await
+        .unwrap();
+        let pod = response.identity_pod.clone();
+        pod.verify().unwrap();
+        assert!(pod.get("github_orgs").is_some());
+
+        // A gated-community request phrased against the attested set solves
+        let query = format!(
+            r#"
+            github_org_member(username, org, identity_server_pk, private: identity_pod) = AND(
+                Equal(identity_pod["username"], username)
+                SetContains(identity_pod["github_orgs"], org)
+                SignedBy(identity_pod, identity_server_pk)
+            )
+
+            REQUEST(
+                github_org_member("Alice", "0xparc", {server_pk})
+            )
+            "#
+        );
+        let params = Params::default();
+        let request = parse(&query, &params, &[]).unwrap();
+        let edb = ImmutableEdbBuilder::new().add_signed_dict(pod).build();
+        let reg = OpRegistry::default();
+        let config = EngineConfigBuilder::new().from_params(&params).build();
+        let mut engine = Engine::with_config(&reg, &edb, config);
+        engine.load_processed(&request);
+        engine.run().unwrap();
+        assert!(!engine.answers.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_issue_identity_without_org_scope_omits_entry() {
+        let state = test_state(github_registry().await).await;
+        let user_pk = SecretKey::new_rand().public_key();
+        insert_oauth_state(&state.db_pool, "plain-state", &user_pk, "Alice", Utc::now(), false)
+            .await
+            .unwrap();
+
+        let response = issue_identity(
+            State(state),
+            Json(issue_request_with_state("plain-state", "Alice")),
+        )
+        .await
+        .unwrap();
+        assert!(response.identity_pod.get("github_orgs").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_issue_identity_rejects_username_mismatch() {
+        let state = test_state(github_registry().await).await;
+        let user_pk = SecretKey::new_rand().public_key();
+        insert_oauth_state(&state.db_pool, "alice-state", &user_pk, "Alice", Utc::now(), false)
+            .await
+            .unwrap();
+
+        let err = issue_identity(
+            State(state),
+            Json(issue_request_with_state("alice-state", "Mallory")),
+        )
+        .await
+        .unwrap_err();
+        assert_bad_request(err);
+    }
+
+    #[tokio::test]
+    async fn test_refresh_reissues_pod_with_current_keys() {
+        // The provider now reports a different key set than the stored one
+        let mock = Router::new().route(
+            "/octocat.keys",
+            get(|| async { "ssh-ed25519 AAAAnewkey\n" }),
+        );
+        let base_url = serve(mock).await;
+        let state = test_state(github_registry_at(&base_url)).await;
+
+        let user_sk = SecretKey::new_rand();
+        let user_pk = user_sk.public_key();
+        insert_test_user(&state, &user_pk).await;
+
+        let mut builder = SignedDictBuilder::new(&Params::default());
+        builder.insert("request_type", "refresh");
+        let refresh_pod = builder.sign(&Signer(user_sk)).unwrap();
+
+        let response = refresh_identity(
+            State(state.clone()),
+            Json(RefreshRequest {
+                refresh_pod: refresh_pod.clone(),
+            }),
+        )
+        .await
+        .unwrap();
+
+        // The fresh pod carries the new keys and a refresh timestamp
+        let pod = &response.identity_pod;
+        pod.verify().unwrap();
+        assert!(pod.get("refreshed_at").is_some());
+        let provider_data: serde_json::Value = serde_json::from_str(
+            pod.get("provider_data").and_then(|v| v.as_str()).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            provider_data["provider_public_keys"],
+            json!(["ssh-ed25519 AAAAnewkey"])
+        );
+
+        // The stored mapping was updated to match
+        let user = get_user_by_public_key(&state.db_pool, &user_pk)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            user.provider_public_keys,
+            vec!["ssh-ed25519 AAAAnewkey".to_string()]
+        );
+        assert!(user.refreshed_at.is_some());
+
+        // An immediate second refresh is rate limited
+        let status = refresh_identity(State(state), Json(RefreshRequest { refresh_pod }))
+            .await
+            .unwrap_err();
+        assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
+    }
+
+    #[tokio::test]
+    async fn test_refresh_rejects_unknown_identity() {
+        let state = test_state(ProviderRegistry::new()).await;
+
+        let mut builder = SignedDictBuilder::new(&Params::default());
+        builder.insert("request_type", "refresh");
+        let refresh_pod = builder.sign(&Signer(SecretKey::new_rand())).unwrap();
+
+        let status = refresh_identity(State(state), Json(RefreshRequest { refresh_pod }))
+            .await
+            .unwrap_err();
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_registration_task_records_failures() {
+        use std::time::Duration;
+
+        let mock_podnet = Router::new().route(
+            "/identity/challenge",
+            post(|| async { StatusCode::SERVICE_UNAVAILABLE }),
+        );
+        let podnet_url = serve(mock_podnet).await;
+
+        let status = Arc::new(RwLock::new(RegistrationStatus::default()));
+        spawn_registration_task(
+            "test-identity-server".to_string(),
+            Arc::new(SecretKey::new_rand()),
+            podnet_url,
+            RegistrationSchedule {
+                initial_retry_delay: Duration::from_millis(10),
+                max_retry_delay: Duration::from_millis(50),
+                heartbeat_interval: Duration::from_millis(50),
+            },
+            status.clone(),
+        );
+
+        let deadline = tokio::time::Instant::now() + Duration::from_secs(10);
+        loop {
+            {
+                let status = status.read().unwrap();
+                if status.last_error.is_some() {
+                    assert!(status.last_attempt.is_some());
+                    assert!(status.registered_at.is_none());
+                    break;
+                }
+            }
+            assert!(
+                tokio::time::Instant::now() < deadline,
+                "no failed attempt was recorded"
+            );
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+    }
+
+    #[tokio::test]
+    async fn test_registration_task_retries_until_podnet_accepts() {
+        use std::{
+            sync::atomic::{AtomicUsize, Ordering},
+            time::Duration,
+        };
+
+        let attempts = Arc::new(AtomicUsize::new(0));
+        let podnet_sk = SecretKey::new_rand();
+        let podnet_pk = podnet_sk.public_key();
+
+        // Podnet rejects the first two attempts, then runs the challenge flow
+        let challenge_attempts = attempts.clone();
+        let mock_podnet = Router::new()
+            .route(
+                "/identity/challenge",
+                post(move || {
+                    let attempts = challenge_attempts.clone();
+                    let podnet_sk = SecretKey(podnet_sk.0.clone());
+                    async move {
+                        if attempts.fetch_add(1, Ordering::SeqCst) < 2 {
+                            return Err(StatusCode::SERVICE_UNAVAILABLE);
+                        }
+                        let mut builder = SignedDictBuilder::new(&Params::default());
+                        builder.insert("challenge", "test-challenge");
+                        builder.insert("nonce", "test-nonce");
+                        builder.insert("issued_at", Utc::now().to_rfc3339().as_str());
+                        let challenge_pod = builder.sign(&Signer(podnet_sk)).unwrap();
+                        Ok(Json(json!({ "challenge_pod": challenge_pod })))
+                    }
+                }),
+            )
+            .route(
+                "/identity/register",
+                post(move || async move { Json(json!({ "public_key": podnet_pk })) }),
+            );
+        let podnet_url = serve(mock_podnet).await;
+
+        let status = Arc::new(RwLock::new(RegistrationStatus::default()));
+        spawn_registration_task(
+            "test-identity-server".to_string(),
+            Arc::new(SecretKey::new_rand()),
+            podnet_url,
+            RegistrationSchedule {
+                initial_retry_delay: Duration::from_millis(10),
+                max_retry_delay: Duration::from_millis(50),
+                heartbeat_interval: Duration::from_millis(50),
+            },
+            status.clone(),
+        );
+
+        // Backoff keeps retrying until podnet accepts; success clears the
+        // error from the failed attempts
+        let deadline = tokio::time::Instant::now() + Duration::from_secs(10);
+        loop {
+            {
+                let status = status.read().unwrap();
+                if status.registered_at.is_some() {
+                    assert!(status.last_attempt.is_some());
+                    assert!(status.last_error.is_none());
+                    break;
+                }
+            }
+            assert!(
+                tokio::time::Instant::now() < deadline,
+                "registration never succeeded"
+            );
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+        let settled = attempts.load(Ordering::SeqCst);
+        assert!(settled >= 3);
+
+        // After success the heartbeat keeps re-registering
+        let heartbeat_deadline = tokio::time::Instant::now() + Duration::from_secs(10);
+        while attempts.load(Ordering::SeqCst) == settled {
+            assert!(
+                tokio::time::Instant::now() < heartbeat_deadline,
+                "no heartbeat re-registration happened"
+            );
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_lookups_share_the_pool() {
+        let state = test_state(ProviderRegistry::new()).await;
+        let user_pk = SecretKey::new_rand().public_key();
+        insert_test_user(&state, &user_pk).await;
+
+        // With pooled connections these run in parallel instead of queueing
+        // on one mutex; every lookup must still see the stored mapping
+        let lookups = (0..16).map(|_| {
+            let state = state.clone();
+            tokio::spawn(async move {
+                lookup_username_by_public_key(
+                    State(state),
+                    Query(UsernameLookupRequest {
+                        public_key: user_pk,
+                    }),
+                )
+                .await
+            })
+        });
+        for lookup in lookups.collect::<Vec<_>>() {
+            let response = lookup.await.unwrap().unwrap();
+            assert_eq!(response.username, "Alice");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_graceful_shutdown_drains_in_flight_requests() {
+        use std::time::Duration;
+
+        let app = Router::new().route(
+            "/slow",
+            get(|| async {
+                tokio::time::sleep(Duration::from_millis(200)).await;
+                "done"
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (shutdown_tx, shutdown_rx) = tokio::sync::oneshot::channel::<()>();
+        let server = tokio::spawn(async move {
+            axum::serve(listener, app)
+                .with_graceful_shutdown(async {
+                    shutdown_rx.await.ok();
+                })
+                .await
+                .unwrap();
+        });
+
+        let request =
+            tokio::spawn(
+                async move { reqwest::get(format!("http://{addr}/slow")).await.unwrap() },
+            );
+        // Give the request time to reach the handler before signalling
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        shutdown_tx.send(()).unwrap();
+
+        // The in-flight request still completes normally
+        let response = request.await.unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::OK);
+        assert_eq!(response.text().await.unwrap(), "done");
+
+        // ... and the server exits once it has drained
+        tokio::time::timeout(Duration::from_secs(5), server)
+            .await
+            .expect("server should stop after draining")
+            .unwrap();
+
+        // New connections are refused after shutdown
+        assert!(reqwest::get(format!("http://{addr}/slow")).await.is_err());
+    }
+
+    async fn preflight(
+        allowed_origins: Option<&str>,
+        origin: &str,
+    ) -> axum::http::Response<axum::body::Body> {
+        use tower::ServiceExt;
+
+        let app = Router::new()
+            .route("/lookup", get(|| async { "ok" }))
+            .layer(cors_layer_for_origins(allowed_origins));
+        app.oneshot(
+            axum::http::Request::builder()
+                .method("OPTIONS")
+                .uri("/lookup")
+                .header("Origin", origin)
+                .header("Access-Control-Request-Method", "GET")
+                .body(axum::body::Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_cors_allows_listed_origin() {
+        let response = preflight(
+            Some("https://app.example.com, https://other.example.com"),
+            "https://app.example.com",
+        )
+        .await;
+        assert_eq!(
+            response
+                .headers()
+                .get("access-control-allow-origin")
+                .and_then(|v| v.to_str().ok()),
+            Some("https://app.example.com")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_cors_rejects_unlisted_origin() {
+        let response = preflight(
+            Some("https://app.example.com"),
+            "https://evil.example.com",
+        )
+        .await;
+        // Without the allow-origin header the browser blocks the response
+        assert!(
+            response
+                .headers()
+                .get("access-control-allow-origin")
+                .is_none()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_cors_stays_permissive_when_unconfigured() {
+        let response = preflight(None, "https://anywhere.example.com").await;
+        assert_eq!(
+            response
+                .headers()
+                .get("access-control-allow-origin")
+                .and_then(|v| v.to_str().ok()),
+            Some("*")
+        );
+    }
+}
@@ -0,0 +1,274 @@
+//! Account-quality requirements applied before issuing identity pods.
+//!
+//! Throwaway provider accounts would otherwise defeat the sybil-resistance
+//! purpose of OAuth-backed identities. Each requirement is individually
+//! toggleable via environment variables and the applied thresholds are
+//! attested in the issued pod so relying parties can see the issuance policy.
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use crate::providers::ProviderUser;
+
+#[derive(Debug, Clone, Default)]
+pub struct AccountPolicy {
+    pub min_account_age_days: Option<i64>,
+    pub min_public_repos: Option<i64>,
+    pub min_followers: Option<i64>,
+    pub require_ssh_key: bool,
+}
+
+/// One requirement the account failed to meet. `actual` is `None` when the
+/// provider did not report the datum at all (enabled checks fail closed).
+#[derive(Debug, Serialize)]
+pub struct RequirementFailure {
+    pub requirement: &'static str,
+    pub required: i64,
+    pub actual: Option<i64>,
+}
+
+/// Structured 403 body listing every unmet requirement.
+#[derive(Debug, Serialize)]
+pub struct PolicyRejection {
+    pub error: &'static str,
+    pub failed_requirements: Vec<RequirementFailure>,
+}
+
+fn env_threshold(name: &str) -> Option<i64> {
+    let raw = std::env::var(name).ok()?;
+    match raw.parse() {
+        Ok(value) => Some(value),
+        Err(_) => {
+            tracing::warn!("Ignoring invalid {}: {}", name, raw);
+            None
+        }
+    }
+}
+
+impl AccountPolicy {
+    pub fn from_env() -> Self {
+        Self {
+            min_account_age_days: env_threshold("IDENTITY_MIN_ACCOUNT_AGE_DAYS"),
+            min_public_repos: env_threshold("IDENTITY_MIN_PUBLIC_REPOS"),
+            min_followers: env_threshold("IDENTITY_MIN_FOLLOWERS"),
+            require_ssh_key: std::env::var("IDENTITY_REQUIRE_SSH_KEY")
+                .map(|v| v == "1" || v.eq_ignore_ascii_case("true"))
+                .unwrap_or(false),
+        }
+    }
+
+    pub fn evaluate(
+        &self,
+        user: &ProviderUser,
+        public_keys: &[String],
+        now: DateTime<Utc>,
+    ) -> Result<(), PolicyRejection> {
+        let mut failed_requirements = Vec::new();
+
+        if let Some(min_days) = self.min_account_age_days {
+            let age_days = user
+                .created_at
+                .as_deref()
+                .and_then(|raw| DateTime::parse_from_rfc3339(raw).ok())
+                .map(|created| (now - created.with_timezone(&Utc)).num_days());
+            if age_days.is_none_or(|age| age < min_days) {
+                failed_requirements.push(RequirementFailure {
+                    requirement: "min_account_age_days",
+                    required: min_days,
+                    actual: age_days,
+                });
+            }
+        }
+
+        if let Some(min_repos) = self.min_public_repos {
+            if user.public_repos.is_none_or(|repos| repos < min_repos) {
+                failed_requirements.push(RequirementFailure {
+                    requirement: "min_public_repos",
+                    required: min_repos,
+                    actual: user.public_repos,
+                });
+            }
+        }
+
+        if let Some(min_followers) = self.min_followers {
+            if user.followers.is_none_or(|followers| followers < min_followers) {
+                failed_requirements.push(RequirementFailure {
+                    requirement: "min_followers",
+                    required: min_followers,
+                    actual: user.followers,
+                });
+            }
+        }
+
+        if self.require_ssh_key && public_keys.is_empty() {
+            failed_requirements.push(RequirementFailure {
+                requirement: "ssh_key",
+                required: 1,
+                actual: Some(0),
+            });
+        }
+
+        if failed_requirements.is_empty() {
+            Ok(())
+        } else {
+            Err(PolicyRejection {
+                error: "account does not meet identity issuance requirements",
+                failed_requirements,
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+
+    use super::*;
+
+    fn user(created_days_ago: i64, public_repos: i64, followers: i64) -> ProviderUser {
+        ProviderUser {
+            id: 1,
+            login: "tester".to_string(),
+            name: None,
+            email: None,
+            created_at: Some((Utc::now() - Duration::days(created_days_ago)).to_rfc3339()),
+            public_repos: Some(public_repos),
+            followers: Some(followers),
+        }
+    }
+
+    fn failed(rejection: PolicyRejection) -> Vec<&'static str> {
+        rejection
+            .failed_requirements
+            .iter()
+            .map(|f| f.requirement)
+            .collect()
+    }
+
+    #[test]
+    fn test_disabled_policy_accepts_anything() {
+        let policy = AccountPolicy::default();
+        let bare_user = ProviderUser {
+            id: 1,
+            login: "fresh".to_string(),
+            name: None,
+            email: None,
+            created_at: None,
+            public_repos: None,
+            followers: None,
+        };
+        assert!(policy.evaluate(&bare_user, &[], Utc::now()).is_ok());
+    }
+
+    #[test]
+    fn test_account_age_threshold() {
+        let policy = AccountPolicy {
+            min_account_age_days: Some(30),
+            ..Default::default()
+        };
+
+        assert!(policy.evaluate(&user(31, 0, 0), &[], Utc::now()).is_ok());
+
+        let rejection = policy
+            .evaluate(&user(29, 0, 0), &[], Utc::now())
+            .unwrap_err();
+        assert_eq!(failed(rejection), vec!["min_account_age_days"]);
+    }
+
+    #[test]
+    fn test_repo_and_follower_thresholds_are_independent() {
+        let policy = AccountPolicy {
+            min_public_repos: Some(5),
+            min_followers: Some(10),
+            ..Default::default()
+        };
+
+        assert!(policy.evaluate(&user(0, 5, 10), &[], Utc::now()).is_ok());
+
+        let rejection = policy
+            .evaluate(&user(0, 4, 10), &[], Utc::now())
+            .unwrap_err();
+        assert_eq!(failed(rejection), vec!["min_public_repos"]);
+
+        let rejection = policy
+            .evaluate(&user(0, 5, 9), &[], Utc::now())
+            .unwrap_err();
+        assert_eq!(failed(rejection), vec!["min_followers"]);
+    }
+
+    #[test]
+    fn test_ssh_key_requirement() {
+        let policy = AccountPolicy {
+            require_ssh_key: true,
+            ..Default::default()
+        };
+
+        let keys = vec!["ssh-ed25519 AAAAkey".to_string()];
+        assert!(policy.evaluate(&user(0, 0, 0), &keys, Utc::now()).is_ok());
+
+        let rejection = policy.evaluate(&user(0, 0, 0), &[], Utc::now()).unwrap_err();
+        assert_eq!(failed(rejection), vec!["ssh_key"]);
+    }
+
+    #[test]
+    fn test_enabled_checks_fail_closed_on_missing_data() {
+        let policy = AccountPolicy {
+            min_account_age_days: Some(30),
+            min_public_repos: Some(1),
+            ..Default::default()
+        };
+        let bare_user = ProviderUser {
+            id: 1,
+            login: "opaque".to_string(),
+            name: None,
+            email: None,
+            created_at: None,
+            public_repos: None,
+            followers: None,
+        };
+
+        let rejection = policy.evaluate(&bare_user, &[], Utc::now()).unwrap_err();
+        assert_eq!(
+            failed(rejection),
+            vec!["min_account_age_days", "min_public_repos"]
+        );
+    }
+
+    #[test]
+    fn test_applied_thresholds_attested_in_pod() {
+        use pod2::{backends::plonky2::primitives::ec::schnorr::SecretKey, middleware::Value};
+
+        use crate::identity::create_identity_pod;
+
+        let policy = AccountPolicy {
+            min_account_age_days: Some(30),
+            require_ssh_key: true,
+            ..Default::default()
+        };
+
+        let server_sk = SecretKey::new_rand();
+        let user_pk = SecretKey::new_rand().public_key();
+        let pod = create_identity_pod(
+            "test-identity-server",
+            &server_sk,
+            &user_pk,
+            "Alice",
+            "github",
+            &user(90, 3, 3),
+            &["ssh-ed25519 AAAAkey".to_string()],
+            Utc::now(),
+            &policy,
+            None,
+            None,
+        )
+        .unwrap();
+
+        assert_eq!(
+            pod.get("policy_min_account_age_days"),
+            Some(&Value::from(30i64))
+        );
+        assert_eq!(pod.get("policy_require_ssh_key"), Some(&Value::from(true)));
+        assert!(pod.get("policy_min_public_repos").is_none());
+        assert!(pod.get("policy_min_followers").is_none());
+    }
+}
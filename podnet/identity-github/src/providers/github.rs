@@ -0,0 +1,185 @@
+use anyhow::{Result, anyhow};
+use oauth2::{
+    AuthUrl, AuthorizationCode, ClientId, ClientSecret, CsrfToken, RedirectUrl, Scope,
+    TokenResponse, TokenUrl, basic::BasicClient, reqwest::async_http_client,
+};
+use pod2::backends::plonky2::primitives::ec::curve::Point as PublicKey;
+use reqwest::Client;
+use serde::Deserialize;
+use url::Url;
+
+use super::{OAuthProvider, OAuthProviderConfig, ProviderUser};
+
+const GITHUB_WEB_BASE: &str = "https://github.com";
+const GITHUB_API_BASE: &str = "https://api.github.com";
+const USER_AGENT: &str = "pod2-identity-github/1.0";
+
+#[derive(Debug, Deserialize)]
+struct GitHubUser {
+    id: i64,
+    login: String,
+    name: Option<String>,
+    email: Option<String>,
+    created_at: Option<String>,
+    public_repos: Option<i64>,
+    followers: Option<i64>,
+}
+
+pub struct GitHubProvider {
+    client: BasicClient,
+    http_client: Client,
+    web_base: String,
+    api_base: String,
+}
+
+impl GitHubProvider {
+    pub fn new(config: OAuthProviderConfig) -> Result<Self> {
+        Self::with_base_urls(config, GITHUB_WEB_BASE, GITHUB_API_BASE)
+    }
+
+    /// Construct against non-default hosts (GitHub Enterprise, or a mock
+    /// server in tests). `web_base` serves the OAuth and `.keys` endpoints,
+    /// `api_base` the REST API.
+    pub fn with_base_urls(
+        config: OAuthProviderConfig,
+        web_base: &str,
+        api_base: &str,
+    ) -> Result<Self> {
+        let client = BasicClient::new(
+            ClientId::new(config.client_id),
+            Some(ClientSecret::new(config.client_secret)),
+            AuthUrl::new(format!("{web_base}/login/oauth/authorize"))?,
+            Some(TokenUrl::new(format!(
+                "{web_base}/login/oauth/access_token"
+            ))?),
+        )
+        .set_redirect_uri(RedirectUrl::new(config.redirect_uri)?);
+
+        Ok(Self {
+            client,
+            http_client: Client::new(),
+            web_base: web_base.to_string(),
+            api_base: api_base.to_string(),
+        })
+    }
+}
+
+impl OAuthProvider for GitHubProvider {
+    fn name(&self) -> &'static str {
+        "github"
+    }
+
+    fn authorization_url(
+        &self,
+        public_key: &PublicKey,
+        include_orgs: bool,
+    ) -> Result<(Url, CsrfToken)> {
+        // Use the public key as state to associate OAuth flow with user
+        let public_key_json = serde_json::to_string(public_key)?;
+        let csrf_token = CsrfToken::new(public_key_json);
+
+        let mut request = self
+            .client
+            .authorize_url(|| csrf_token.clone())
+            .add_scope(Scope::new("user:email".to_string()));
+        if include_orgs {
+            // read:org also surfaces concealed memberships the user has
+            // agreed to share with the OAuth app
+            request = request.add_scope(Scope::new("read:org".to_string()));
+        }
+        let (auth_url, _) = request.url();
+
+        Ok((auth_url, csrf_token))
+    }
+
+    async fn exchange_code(&self, code: AuthorizationCode) -> Result<String> {
+        let token_result = self
+            .client
+            .exchange_code(code)
+            .request_async(async_http_client)
+            .await?;
+
+        Ok(token_result.access_token().secret().clone())
+    }
+
+    async fn user_info(&self, access_token: &str) -> Result<ProviderUser> {
+        let response = self
+            .http_client
+            .get(format!("{}/user", self.api_base))
+            .bearer_auth(access_token)
+            .header("User-Agent", USER_AGENT)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            return Err(anyhow!(
+                "Failed to get GitHub user info: {}",
+                response.status()
+            ));
+        }
+
+        let user: GitHubUser = response.json().await?;
+        Ok(ProviderUser {
+            id: user.id,
+            login: user.login,
+            name: user.name,
+            email: user.email,
+            created_at: user.created_at,
+            public_repos: user.public_repos,
+            followers: user.followers,
+        })
+    }
+
+    async fn public_keys(&self, login: &str) -> Result<Vec<String>> {
+        let url = format!("{}/{login}.keys", self.web_base);
+
+        let response = self
+            .http_client
+            .get(&url)
+            .header("User-Agent", USER_AGENT)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            return Err(anyhow!(
+                "Failed to get SSH keys for {}: {}",
+                login,
+                response.status()
+            ));
+        }
+
+        let keys_text = response.text().await?;
+        let keys: Vec<String> = keys_text
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| line.trim().to_string())
+            .collect();
+
+        Ok(keys)
+    }
+
+    async fn org_memberships(&self, access_token: &str) -> Result<Vec<String>> {
+        #[derive(Deserialize)]
+        struct GitHubOrg {
+            login: String,
+        }
+
+        let response = self
+            .http_client
+            .get(format!("{}/user/orgs", self.api_base))
+            .bearer_auth(access_token)
+            .header("User-Agent", USER_AGENT)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            return Err(anyhow!(
+                "Failed to get GitHub org memberships: {}",
+                response.status()
+            ));
+        }
+
+        let orgs: Vec<GitHubOrg> = response.json().await?;
+        Ok(orgs.into_iter().map(|org| org.login).collect())
+    }
+}
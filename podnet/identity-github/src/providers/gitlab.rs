@@ -0,0 +1,174 @@
+use anyhow::{Result, anyhow};
+use oauth2::{
+    AuthUrl, AuthorizationCode, ClientId, ClientSecret, CsrfToken, RedirectUrl, Scope,
+    TokenResponse, TokenUrl, basic::BasicClient, reqwest::async_http_client,
+};
+use pod2::backends::plonky2::primitives::ec::curve::Point as PublicKey;
+use reqwest::Client;
+use serde::Deserialize;
+use url::Url;
+
+use super::{OAuthProvider, OAuthProviderConfig, ProviderUser};
+
+const GITLAB_BASE: &str = "https://gitlab.com";
+const USER_AGENT: &str = "pod2-identity-github/1.0";
+
+#[derive(Debug, Deserialize)]
+struct GitLabUser {
+    id: i64,
+    username: String,
+    name: Option<String>,
+    public_email: Option<String>,
+    created_at: Option<String>,
+    followers: Option<i64>,
+}
+
+pub struct GitLabProvider {
+    client: BasicClient,
+    http_client: Client,
+    base_url: String,
+}
+
+impl GitLabProvider {
+    pub fn new(config: OAuthProviderConfig) -> Result<Self> {
+        Self::with_base_url(config, GITLAB_BASE)
+    }
+
+    /// Construct against a non-default host (self-hosted GitLab, or a mock
+    /// server in tests).
+    pub fn with_base_url(config: OAuthProviderConfig, base_url: &str) -> Result<Self> {
+        let client = BasicClient::new(
+            ClientId::new(config.client_id),
+            Some(ClientSecret::new(config.client_secret)),
+            AuthUrl::new(format!("{base_url}/oauth/authorize"))?,
+            Some(TokenUrl::new(format!("{base_url}/oauth/token"))?),
+        )
+        .set_redirect_uri(RedirectUrl::new(config.redirect_uri)?);
+
+        Ok(Self {
+            client,
+            http_client: Client::new(),
+            base_url: base_url.to_string(),
+        })
+    }
+}
+
+impl OAuthProvider for GitLabProvider {
+    fn name(&self) -> &'static str {
+        "gitlab"
+    }
+
+    fn authorization_url(
+        &self,
+        public_key: &PublicKey,
+        include_orgs: bool,
+    ) -> Result<(Url, CsrfToken)> {
+        // Use the public key as state to associate OAuth flow with user
+        let public_key_json = serde_json::to_string(public_key)?;
+        let csrf_token = CsrfToken::new(public_key_json);
+
+        let mut request = self
+            .client
+            .authorize_url(|| csrf_token.clone())
+            .add_scope(Scope::new("read_user".to_string()));
+        if include_orgs {
+            // Group listings need API read access, not just the profile scope
+            request = request.add_scope(Scope::new("read_api".to_string()));
+        }
+        let (auth_url, _) = request.url();
+
+        Ok((auth_url, csrf_token))
+    }
+
+    async fn exchange_code(&self, code: AuthorizationCode) -> Result<String> {
+        let token_result = self
+            .client
+            .exchange_code(code)
+            .request_async(async_http_client)
+            .await?;
+
+        Ok(token_result.access_token().secret().clone())
+    }
+
+    async fn user_info(&self, access_token: &str) -> Result<ProviderUser> {
+        let response = self
+            .http_client
+            .get(format!("{}/api/v4/user", self.base_url))
+            .bearer_auth(access_token)
+            .header("User-Agent", USER_AGENT)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            return Err(anyhow!(
+                "Failed to get GitLab user info: {}",
+                response.status()
+            ));
+        }
+
+        let user: GitLabUser = response.json().await?;
+        Ok(ProviderUser {
+            id: user.id,
+            login: user.username,
+            name: user.name,
+            email: user.public_email.filter(|e| !e.is_empty()),
+            created_at: user.created_at,
+            // GitLab's /user payload does not report a project count
+            public_repos: None,
+            followers: user.followers,
+        })
+    }
+
+    async fn public_keys(&self, login: &str) -> Result<Vec<String>> {
+        let url = format!("{}/{login}.keys", self.base_url);
+
+        let response = self
+            .http_client
+            .get(&url)
+            .header("User-Agent", USER_AGENT)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            return Err(anyhow!(
+                "Failed to get SSH keys for {}: {}",
+                login,
+                response.status()
+            ));
+        }
+
+        let keys_text = response.text().await?;
+        let keys: Vec<String> = keys_text
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| line.trim().to_string())
+            .collect();
+
+        Ok(keys)
+    }
+
+    async fn org_memberships(&self, access_token: &str) -> Result<Vec<String>> {
+        #[derive(Deserialize)]
+        struct GitLabGroup {
+            full_path: String,
+        }
+
+        let response = self
+            .http_client
+            .get(format!("{}/api/v4/groups", self.base_url))
+            .bearer_auth(access_token)
+            .header("User-Agent", USER_AGENT)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            return Err(anyhow!(
+                "Failed to get GitLab group memberships: {}",
+                response.status()
+            ));
+        }
+
+        let groups: Vec<GitLabGroup> = response.json().await?;
+        Ok(groups.into_iter().map(|group| group.full_path).collect())
+    }
+}
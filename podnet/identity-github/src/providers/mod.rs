@@ -0,0 +1,368 @@
+//! OAuth provider abstraction.
+//!
+//! Each supported provider (GitHub, GitLab, ...) implements [`OAuthProvider`]
+//! so the rest of the server never talks to a provider-specific API directly.
+//! Providers are registered by name in a [`ProviderRegistry`] and selected per
+//! request via the `/auth/:provider` path segment.
+
+use anyhow::Result;
+use oauth2::{AuthorizationCode, CsrfToken};
+use pod2::backends::plonky2::primitives::ec::curve::Point as PublicKey;
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+mod github;
+mod gitlab;
+
+pub use github::GitHubProvider;
+pub use gitlab::GitLabProvider;
+
+/// Provider-agnostic view of an authenticated OAuth user.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ProviderUser {
+    /// Stable numeric user id assigned by the provider.
+    pub id: i64,
+    /// Provider login (e.g. GitHub/GitLab username).
+    pub login: String,
+    /// Display name, if the user has set one.
+    pub name: Option<String>,
+    /// Public email, if the provider exposes one.
+    pub email: Option<String>,
+    /// RFC 3339 account creation time, if the provider reports it.
+    pub created_at: Option<String>,
+    /// Public repository/project count, if the provider reports it.
+    pub public_repos: Option<i64>,
+    /// Follower count, if the provider reports it.
+    pub followers: Option<i64>,
+}
+
+/// OAuth application credentials, shared shape across providers.
+#[derive(Debug, Clone)]
+pub struct OAuthProviderConfig {
+    pub client_id: String,
+    pub client_secret: String,
+    pub redirect_uri: String,
+}
+
+/// The operations the identity server needs from an OAuth provider.
+pub trait OAuthProvider {
+    /// Stable provider name, used in routes, identity pods and the database.
+    fn name(&self) -> &'static str;
+
+    /// Build the authorization URL for the user's OAuth flow, binding the
+    /// user's POD public key into the CSRF state. When `include_orgs` is set
+    /// the URL requests the extra scope needed to read org memberships.
+    fn authorization_url(
+        &self,
+        public_key: &PublicKey,
+        include_orgs: bool,
+    ) -> Result<(Url, CsrfToken)>;
+
+    /// Exchange an authorization code for an access token.
+    async fn exchange_code(&self, code: AuthorizationCode) -> Result<String>;
+
+    /// Fetch the authenticated user's profile.
+    async fn user_info(&self, access_token: &str) -> Result<ProviderUser>;
+
+    /// Fetch the user's public SSH keys.
+    async fn public_keys(&self, login: &str) -> Result<Vec<String>>;
+
+    /// Fetch the logins of the organizations (GitLab: groups) the
+    /// authenticated user belongs to. Requires the org scope requested via
+    /// `include_orgs` to see anything beyond public memberships.
+    async fn org_memberships(&self, access_token: &str) -> Result<Vec<String>>;
+}
+
+/// A configured provider, dispatching [`OAuthProvider`] calls to the concrete
+/// implementation.
+pub enum Provider {
+    GitHub(GitHubProvider),
+    GitLab(GitLabProvider),
+}
+
+impl OAuthProvider for Provider {
+    fn name(&self) -> &'static str {
+        match self {
+            Provider::GitHub(p) => p.name(),
+            Provider::GitLab(p) => p.name(),
+        }
+    }
+
+    fn authorization_url(
+        &self,
+        public_key: &PublicKey,
+        include_orgs: bool,
+    ) -> Result<(Url, CsrfToken)> {
+        match self {
+            Provider::GitHub(p) => p.authorization_url(public_key, include_orgs),
+            Provider::GitLab(p) => p.authorization_url(public_key, include_orgs),
+        }
+    }
+
+    async fn exchange_code(&self, code: AuthorizationCode) -> Result<String> {
+        match self {
+            Provider::GitHub(p) => p.exchange_code(code).await,
+            Provider::GitLab(p) => p.exchange_code(code).await,
+        }
+    }
+
+    async fn user_info(&self, access_token: &str) -> Result<ProviderUser> {
+        match self {
+            Provider::GitHub(p) => p.user_info(access_token).await,
+            Provider::GitLab(p) => p.user_info(access_token).await,
+        }
+    }
+
+    async fn public_keys(&self, login: &str) -> Result<Vec<String>> {
+        match self {
+            Provider::GitHub(p) => p.public_keys(login).await,
+            Provider::GitLab(p) => p.public_keys(login).await,
+        }
+    }
+
+    async fn org_memberships(&self, access_token: &str) -> Result<Vec<String>> {
+        match self {
+            Provider::GitHub(p) => p.org_memberships(access_token).await,
+            Provider::GitLab(p) => p.org_memberships(access_token).await,
+        }
+    }
+}
+
+/// Providers available to this server instance, keyed by name.
+#[derive(Default)]
+pub struct ProviderRegistry {
+    providers: Vec<Provider>,
+}
+
+impl ProviderRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn register(&mut self, provider: Provider) {
+        self.providers.push(provider);
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Provider> {
+        self.providers.iter().find(|p| p.name() == name)
+    }
+
+    pub fn names(&self) -> Vec<&'static str> {
+        self.providers.iter().map(|p| p.name()).collect()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.providers.is_empty()
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct OAuthCallbackQuery {
+    pub code: String,
+    pub state: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{
+        Json, Router,
+        routing::{get, post},
+    };
+    use pod_utils::ValueExt;
+    use pod2::{backends::plonky2::primitives::ec::schnorr::SecretKey, middleware::Value};
+    use serde_json::json;
+
+    use super::*;
+    use crate::{identity::create_identity_pod, policy::AccountPolicy};
+
+    async fn serve(app: Router) -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+        format!("http://{addr}")
+    }
+
+    fn test_config() -> OAuthProviderConfig {
+        OAuthProviderConfig {
+            client_id: "test-client-id".to_string(),
+            client_secret: "test-client-secret".to_string(),
+            redirect_uri: "http://localhost:3001/auth/callback".to_string(),
+        }
+    }
+
+    fn token_response() -> Json<serde_json::Value> {
+        Json(json!({
+            "access_token": "test-access-token",
+            "token_type": "bearer",
+            "scope": ""
+        }))
+    }
+
+    #[tokio::test]
+    async fn test_github_provider_issues_pod_with_provider_entries() {
+        let mock = Router::new()
+            .route("/login/oauth/access_token", post(|| async { token_response() }))
+            .route(
+                "/user",
+                get(|| async {
+                    Json(json!({
+                        "id": 42,
+                        "login": "octocat",
+                        "name": "Octo Cat",
+                        "email": null
+                    }))
+                }),
+            )
+            .route(
+                "/octocat.keys",
+                get(|| async { "ssh-ed25519 AAAAkey1\nssh-ed25519 AAAAkey2\n" }),
+            )
+            .route(
+                "/user/orgs",
+                get(|| async { Json(json!([{"login": "0xparc"}, {"login": "zk-guild"}])) }),
+            );
+        let base_url = serve(mock).await;
+
+        let provider =
+            GitHubProvider::with_base_urls(test_config(), &base_url, &base_url).unwrap();
+        assert_eq!(provider.name(), "github");
+
+        let (auth_url, _) = provider
+            .authorization_url(&SecretKey::new_rand().public_key(), false)
+            .unwrap();
+        assert!(auth_url.as_str().starts_with(&base_url));
+        assert!(!auth_url.as_str().contains("read%3Aorg"));
+
+        // Asking for org attestation adds the read:org scope
+        let (orgs_url, _) = provider
+            .authorization_url(&SecretKey::new_rand().public_key(), true)
+            .unwrap();
+        assert!(orgs_url.as_str().contains("read%3Aorg"));
+
+        let token = provider
+            .exchange_code(AuthorizationCode::new("test-code".to_string()))
+            .await
+            .unwrap();
+        assert_eq!(token, "test-access-token");
+
+        let user = provider.user_info(&token).await.unwrap();
+        assert_eq!(user.id, 42);
+        assert_eq!(user.login, "octocat");
+        assert_eq!(user.name.as_deref(), Some("Octo Cat"));
+
+        let keys = provider.public_keys(&user.login).await.unwrap();
+        assert_eq!(keys.len(), 2);
+
+        let orgs = provider.org_memberships(&token).await.unwrap();
+        assert_eq!(orgs, vec!["0xparc".to_string(), "zk-guild".to_string()]);
+
+        let server_sk = SecretKey::new_rand();
+        let user_pk = SecretKey::new_rand().public_key();
+        let pod = create_identity_pod(
+            "test-identity-server",
+            &server_sk,
+            &user_pk,
+            "Alice",
+            provider.name(),
+            &user,
+            &keys,
+            chrono::Utc::now(),
+            &AccountPolicy::default(),
+            None,
+            None,
+        )
+        .unwrap();
+
+        pod.verify().unwrap();
+        assert_eq!(pod.get("oauth_provider"), Some(&Value::from("github")));
+        assert_eq!(pod.get("provider_user_id"), Some(&Value::from(42i64)));
+        let provider_data: serde_json::Value = serde_json::from_str(
+            pod.get("provider_data").and_then(|v| v.as_str()).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(provider_data["provider_username"], "octocat");
+        assert_eq!(provider_data["provider_user_id"], 42);
+    }
+
+    #[tokio::test]
+    async fn test_gitlab_provider_issues_pod_with_provider_entries() {
+        let mock = Router::new()
+            .route("/oauth/token", post(|| async { token_response() }))
+            .route(
+                "/api/v4/user",
+                get(|| async {
+                    Json(json!({
+                        "id": 7,
+                        "username": "gitfox",
+                        "name": "Git Fox",
+                        "public_email": "fox@example.com"
+                    }))
+                }),
+            )
+            .route("/gitfox.keys", get(|| async { "ssh-rsa AAAAkey1\n" }))
+            .route(
+                "/api/v4/groups",
+                get(|| async { Json(json!([{"full_path": "pod-collective"}])) }),
+            );
+        let base_url = serve(mock).await;
+
+        let provider = GitLabProvider::with_base_url(test_config(), &base_url).unwrap();
+        assert_eq!(provider.name(), "gitlab");
+
+        let token = provider
+            .exchange_code(AuthorizationCode::new("test-code".to_string()))
+            .await
+            .unwrap();
+        let user = provider.user_info(&token).await.unwrap();
+        assert_eq!(user.id, 7);
+        assert_eq!(user.login, "gitfox");
+        assert_eq!(user.email.as_deref(), Some("fox@example.com"));
+
+        let keys = provider.public_keys(&user.login).await.unwrap();
+        assert_eq!(keys, vec!["ssh-rsa AAAAkey1".to_string()]);
+
+        let groups = provider.org_memberships(&token).await.unwrap();
+        assert_eq!(groups, vec!["pod-collective".to_string()]);
+
+        let server_sk = SecretKey::new_rand();
+        let user_pk = SecretKey::new_rand().public_key();
+        let pod = create_identity_pod(
+            "test-identity-server",
+            &server_sk,
+            &user_pk,
+            "Felix",
+            provider.name(),
+            &user,
+            &keys,
+            chrono::Utc::now(),
+            &AccountPolicy::default(),
+            None,
+            None,
+        )
+        .unwrap();
+
+        pod.verify().unwrap();
+        assert_eq!(pod.get("oauth_provider"), Some(&Value::from("gitlab")));
+        assert_eq!(pod.get("provider_user_id"), Some(&Value::from(7i64)));
+        let provider_data: serde_json::Value = serde_json::from_str(
+            pod.get("provider_data").and_then(|v| v.as_str()).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(provider_data["provider_username"], "gitfox");
+        assert_eq!(provider_data["provider_email"], "fox@example.com");
+    }
+
+    #[tokio::test]
+    async fn test_registry_selects_provider_by_name() {
+        let mut registry = ProviderRegistry::new();
+        registry.register(Provider::GitHub(GitHubProvider::new(test_config()).unwrap()));
+        registry.register(Provider::GitLab(GitLabProvider::new(test_config()).unwrap()));
+
+        assert_eq!(registry.get("github").map(|p| p.name()), Some("github"));
+        assert_eq!(registry.get("gitlab").map(|p| p.name()), Some("gitlab"));
+        assert!(registry.get("bitbucket").is_none());
+        assert_eq!(registry.names(), vec!["github", "gitlab"]);
+    }
+}
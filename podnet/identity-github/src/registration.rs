@@ -1,4 +1,10 @@
+use std::{
+    sync::{Arc, RwLock},
+    time::Duration,
+};
+
 use anyhow::Result;
+use chrono::Utc;
 use pod_utils::ValueExt;
 use pod2::{
     backends::plonky2::{
@@ -34,6 +40,88 @@ pub struct PodNetServerInfo {
     pub public_key: PublicKey,
 }
 
+/// Live view of this server's registration with the podnet server, as
+/// maintained by [`spawn_registration_task`] and reported on `GET /`.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct RegistrationStatus {
+    /// RFC 3339 time of the last successful registration; absent until the
+    /// first success
+    pub registered_at: Option<String>,
+    /// RFC 3339 time of the most recent attempt
+    pub last_attempt: Option<String>,
+    /// Error from the most recent attempt; cleared by a success
+    pub last_error: Option<String>,
+}
+
+/// Timing for [`spawn_registration_task`].
+#[derive(Debug, Clone)]
+pub struct RegistrationSchedule {
+    /// Delay before retrying a failed registration; doubles per consecutive
+    /// failure
+    pub initial_retry_delay: Duration,
+    /// Cap on the backoff delay
+    pub max_retry_delay: Duration,
+    /// How often a registered server re-registers, so podnet's health view of
+    /// this identity server stays fresh
+    pub heartbeat_interval: Duration,
+}
+
+impl Default for RegistrationSchedule {
+    fn default() -> Self {
+        Self {
+            initial_retry_delay: Duration::from_secs(1),
+            max_retry_delay: Duration::from_secs(5 * 60),
+            heartbeat_interval: Duration::from_secs(5 * 60),
+        }
+    }
+}
+
+/// Keeps this server registered with the podnet server: retries with
+/// exponential backoff until registration succeeds, then re-registers every
+/// heartbeat interval. The outcome of every attempt is recorded in `status`.
+pub fn spawn_registration_task(
+    server_id: String,
+    secret_key: Arc<SecretKey>,
+    podnet_server_url: String,
+    schedule: RegistrationSchedule,
+    status: Arc<RwLock<RegistrationStatus>>,
+) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut retry_delay = schedule.initial_retry_delay;
+        loop {
+            let result =
+                register_with_podnet_server(&server_id, &secret_key, &podnet_server_url).await;
+            let now = Utc::now().to_rfc3339();
+            {
+                let mut status = status.write().unwrap();
+                status.last_attempt = Some(now.clone());
+                match &result {
+                    Ok(()) => {
+                        status.registered_at = Some(now);
+                        status.last_error = None;
+                    }
+                    Err(e) => status.last_error = Some(e.to_string()),
+                }
+            }
+            match result {
+                Ok(()) => {
+                    retry_delay = schedule.initial_retry_delay;
+                    tokio::time::sleep(schedule.heartbeat_interval).await;
+                }
+                Err(e) => {
+                    tracing::warn!(
+                        "Registration with podnet-server failed (retrying in {:?}): {}",
+                        retry_delay,
+                        e
+                    );
+                    tokio::time::sleep(retry_delay).await;
+                    retry_delay = (retry_delay * 2).min(schedule.max_retry_delay);
+                }
+            }
+        }
+    })
+}
+
 pub async fn register_with_podnet_server(
     server_id: &str,
     secret_key: &SecretKey,
@@ -83,7 +171,20 @@ pub async fn register_with_podnet_server(
         .and_then(|v| v.as_str())
         .ok_or_else(|| anyhow::anyhow!("Server challenge pod missing challenge"))?;
 
-    tracing::info!("Challenge received: {}", challenge);
+    // The server rejects challenges without a nonce and issuance timestamp;
+    // failing here gives a clearer error than a rejected registration
+    let nonce = challenge_response
+        .challenge_pod
+        .get("nonce")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| anyhow::anyhow!("Server challenge pod missing nonce"))?;
+    let issued_at = challenge_response
+        .challenge_pod
+        .get("issued_at")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| anyhow::anyhow!("Server challenge pod missing issued_at"))?;
+
+    tracing::info!("Challenge received: {} (nonce {}, issued at {})", challenge, nonce, issued_at);
 
     // Step 3: Create identity server's response pod
     let params = Params::default();
@@ -12,6 +12,13 @@ pub struct IdentityServerConfig {
     pub keypair_file: String,
     /// URL of the podnet server to register with
     pub podnet_server_url: String,
+    /// Publicly reachable URL of this server, advertised to the podnet server
+    /// so it can health-poll us (None = not advertised)
+    pub public_url: Option<String>,
+    /// Browser origins allowed by CORS; a literal "*" allows any origin
+    pub cors_allowed_origins: Vec<String>,
+    /// Whether cross-origin requests may carry credentials (ignored with "*" origins)
+    pub cors_allow_credentials: bool,
 }
 
 impl Default for IdentityServerConfig {
@@ -22,6 +29,9 @@ impl Default for IdentityServerConfig {
             database_path: "identity-users.db".to_string(),
             keypair_file: "identity-server-keypair.json".to_string(),
             podnet_server_url: "http://localhost:3000".to_string(),
+            public_url: None,
+            cors_allowed_origins: vec!["*".to_string()],
+            cors_allow_credentials: false,
         }
     }
 }
@@ -45,12 +55,32 @@ impl IdentityServerConfig {
         let podnet_server_url =
             env::var("PODNET_SERVER_URL").unwrap_or_else(|_| "http://localhost:3000".to_string());
 
+        let public_url = env::var("IDENTITY_PUBLIC_URL").ok().filter(|u| !u.is_empty());
+
+        let cors_allowed_origins = env::var("IDENTITY_CORS_ALLOWED_ORIGINS")
+            .ok()
+            .map(|v| {
+                v.split(',')
+                    .map(|origin| origin.trim().to_string())
+                    .filter(|origin| !origin.is_empty())
+                    .collect::<Vec<_>>()
+            })
+            .filter(|origins| !origins.is_empty())
+            .unwrap_or_else(|| vec!["*".to_string()]);
+
+        let cors_allow_credentials = env::var("IDENTITY_CORS_ALLOW_CREDENTIALS")
+            .map(|v| v.parse().unwrap_or(false))
+            .unwrap_or(false);
+
         Self {
             port,
             host,
             database_path,
             keypair_file,
             podnet_server_url,
+            public_url,
+            cors_allowed_origins,
+            cors_allow_credentials,
         }
     }
 
@@ -63,6 +93,15 @@ impl IdentityServerConfig {
         tracing::info!("  Database path: {}", config.database_path);
         tracing::info!("  Keypair file: {}", config.keypair_file);
         tracing::info!("  PodNet server URL: {}", config.podnet_server_url);
+        match &config.public_url {
+            Some(url) => tracing::info!("  Public URL: {}", url),
+            None => tracing::info!("  Public URL: not set (IDENTITY_PUBLIC_URL); won't be health-polled"),
+        }
+        tracing::info!(
+            "  CORS: origins={:?}, credentials={}",
+            config.cors_allowed_origins,
+            config.cors_allow_credentials
+        );
         config
     }
 }
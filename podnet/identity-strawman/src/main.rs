@@ -24,14 +24,21 @@ use pod2::{
 use rand::Rng;
 use rusqlite::{Connection, params};
 use serde::{Deserialize, Serialize};
-use tower_http::cors::CorsLayer;
+use tower_http::cors::{AllowOrigin, CorsLayer};
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
+// The server's current keypair; replaced in place on rotation
+pub struct ServerKeypair {
+    pub secret_key: SecretKey,
+    pub public_key: PublicKey,
+}
+
 // Identity server state
 pub struct IdentityServerState {
     pub server_id: String,
-    pub server_secret_key: Arc<SecretKey>,
-    pub server_public_key: PublicKey,
+    pub keypair: Arc<Mutex<ServerKeypair>>,
+    pub keypair_file: String,
+    pub podnet_server_url: String,
     pub db_conn: Arc<Mutex<Connection>>,
 }
 
@@ -39,8 +46,9 @@ impl Clone for IdentityServerState {
     fn clone(&self) -> Self {
         Self {
             server_id: self.server_id.clone(),
-            server_secret_key: Arc::clone(&self.server_secret_key),
-            server_public_key: self.server_public_key,
+            keypair: Arc::clone(&self.keypair),
+            keypair_file: self.keypair_file.clone(),
+            podnet_server_url: self.podnet_server_url.clone(),
             db_conn: Arc::clone(&self.db_conn),
         }
     }
@@ -115,6 +123,8 @@ pub struct IdentityServerChallengeResponse {
 pub struct IdentityServerRegistrationRequest {
     pub server_challenge_pod: SignedDict,
     pub identity_response_pod: SignedDict,
+    /// Where the podnet server can reach us for health polling
+    pub base_url: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -142,11 +152,36 @@ pub struct UsernameLookupResponse {
     pub username: String,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct PublicKeyLookupRequest {
+    pub username: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct UsernameMatch {
+    pub username: String,
+    pub public_key: PublicKey,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PrefixLookupRequest {
+    pub q: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PrefixLookupResponse {
+    pub matches: Vec<UsernameMatch>,
+}
+
+/// Maximum number of usernames returned by a prefix search
+const MAX_PREFIX_MATCHES: usize = 10;
+
 // Root endpoint
 async fn root(State(state): State<IdentityServerState>) -> Json<ServerInfo> {
+    let public_key = state.keypair.lock().unwrap().public_key;
     Json(ServerInfo {
         server_id: state.server_id.clone(),
-        public_key: state.server_public_key,
+        public_key,
     })
 }
 
@@ -187,7 +222,10 @@ async fn request_user_challenge(
     challenge_builder.insert("username", payload.username.as_str());
 
     // Sign with identity server's private key
-    let identity_signer = Signer(SecretKey(state.server_secret_key.0.clone()));
+    let identity_signer = {
+        let keypair = state.keypair.lock().unwrap();
+        Signer(SecretKey(keypair.secret_key.0.clone()))
+    };
     let challenge_pod = challenge_builder.sign(&identity_signer).map_err(|e| {
         tracing::error!("Failed to sign user challenge pod: {e}");
         StatusCode::INTERNAL_SERVER_ERROR
@@ -215,7 +253,7 @@ async fn issue_identity(
     })?;
 
     // 2. Verify challenge pod was signed by this identity server
-    let identity_server_public_key = state.server_public_key;
+    let identity_server_public_key = state.keypair.lock().unwrap().public_key;
     let challenge_signer = payload.server_challenge_pod.public_key;
 
     if challenge_signer != identity_server_public_key {
@@ -317,6 +355,23 @@ async fn issue_identity(
 
     tracing::info!("✓ All verifications passed for user: {}", username);
 
+    // Refuse to issue an identity pod for a username already bound to a
+    // different public key, before anything is signed
+    {
+        let conn = state.db_conn.lock().unwrap();
+        match username_taken_by_other(&conn, username, user_public_key) {
+            Ok(true) => {
+                tracing::warn!("Username {username} is already bound to another public key");
+                return Err(StatusCode::CONFLICT);
+            }
+            Ok(false) => {}
+            Err(e) => {
+                tracing::error!("Failed to check username availability: {}", e);
+                return Err(StatusCode::INTERNAL_SERVER_ERROR);
+            }
+        }
+    }
+
     // Create identity pod using SignedDictBuilder
     let params = Params::default();
     let mut identity_builder = SignedDictBuilder::new(&params);
@@ -327,7 +382,10 @@ async fn issue_identity(
     identity_builder.insert("issued_at", chrono::Utc::now().to_rfc3339().as_str());
 
     // Sign the identity pod with the identity server's key
-    let server_signer = Signer(SecretKey(state.server_secret_key.0.clone()));
+    let server_signer = {
+        let keypair = state.keypair.lock().unwrap();
+        Signer(SecretKey(keypair.secret_key.0.clone()))
+    };
     let identity_pod = identity_builder.sign(&server_signer).map_err(|e| {
         tracing::error!("Failed to sign identity pod: {e}");
         StatusCode::INTERNAL_SERVER_ERROR
@@ -372,11 +430,178 @@ async fn lookup_username_by_public_key(
     }
 }
 
+// Reverse lookup handler: public key by (case-insensitive) username
+async fn lookup_public_key_by_username_handler(
+    State(state): State<IdentityServerState>,
+    Query(params): Query<PublicKeyLookupRequest>,
+) -> Result<Json<UsernameMatch>, StatusCode> {
+    tracing::info!("Looking up public key for username: {}", params.username);
+
+    let conn = state.db_conn.lock().unwrap();
+
+    match lookup_public_key_by_username(&conn, &params.username) {
+        Ok(Some(found)) => {
+            tracing::info!("✓ Found public key for username: {}", found.username);
+            Ok(Json(found))
+        }
+        Ok(None) => {
+            tracing::info!("No user found for username: {}", params.username);
+            Err(StatusCode::NOT_FOUND)
+        }
+        Err(e) => {
+            tracing::error!("Database error during public key lookup: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+// Prefix search handler for username autocomplete
+async fn lookup_usernames_by_prefix_handler(
+    State(state): State<IdentityServerState>,
+    Query(params): Query<PrefixLookupRequest>,
+) -> Result<Json<PrefixLookupResponse>, StatusCode> {
+    tracing::info!("Prefix search for usernames starting with: {}", params.q);
+
+    let conn = state.db_conn.lock().unwrap();
+
+    match lookup_usernames_by_prefix(&conn, &params.q, MAX_PREFIX_MATCHES) {
+        Ok(matches) => {
+            tracing::info!("✓ Prefix search returned {} matches", matches.len());
+            Ok(Json(PrefixLookupResponse { matches }))
+        }
+        Err(e) => {
+            tracing::error!("Database error during prefix search: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+// Key rotation models (mirrors the podnet-server API)
+#[derive(Debug, Serialize)]
+pub struct KeyRotationRequest {
+    pub continuity_pod: SignedDict,
+}
+
+// Admin endpoint: rotate the server keypair. Generates a fresh key, signs a
+// continuity pod with the old key, registers the rotation with the podnet
+// server, then persists and adopts the new key. Like the rest of this
+// strawman, the endpoint is unauthenticated.
+async fn rotate_keypair(
+    State(state): State<IdentityServerState>,
+) -> Result<Json<ServerInfo>, StatusCode> {
+    tracing::info!("Rotating identity server keypair");
+
+    let old_secret_key = {
+        let keypair = state.keypair.lock().unwrap();
+        SecretKey(keypair.secret_key.0.clone())
+    };
+    let new_secret_key = SecretKey::new_rand();
+    let new_public_key = new_secret_key.public_key();
+
+    // Sign the continuity pod with the old key
+    let params = Params::default();
+    let mut continuity_builder = SignedDictBuilder::new(&params);
+    continuity_builder.insert("server_id", state.server_id.as_str());
+    continuity_builder.insert("new_public_key", new_public_key);
+    continuity_builder.insert("action", "rotate");
+
+    let old_signer = Signer(old_secret_key);
+    let continuity_pod = continuity_builder.sign(&old_signer).map_err(|e| {
+        tracing::error!("Failed to sign continuity pod: {e}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    // Register the rotation with the podnet server before adopting the new
+    // key, so we never end up with a key the podnet server doesn't know
+    let client = reqwest::Client::new();
+    let response = client
+        .post(format!(
+            "{}/identity-servers/{}/rotate",
+            state.podnet_server_url, state.server_id
+        ))
+        .header("Content-Type", "application/json")
+        .json(&KeyRotationRequest { continuity_pod })
+        .send()
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to reach podnet-server for rotation: {e}");
+            StatusCode::BAD_GATEWAY
+        })?;
+
+    if !response.status().is_success() {
+        tracing::error!(
+            "podnet-server rejected key rotation with status: {}",
+            response.status()
+        );
+        return Err(StatusCode::BAD_GATEWAY);
+    }
+
+    if let Err(e) = save_keypair(
+        &state.keypair_file,
+        &state.server_id,
+        &new_secret_key,
+        new_public_key,
+    ) {
+        tracing::error!("Failed to persist rotated keypair: {e}");
+        return Err(StatusCode::INTERNAL_SERVER_ERROR);
+    }
+
+    {
+        let mut keypair = state.keypair.lock().unwrap();
+        *keypair = ServerKeypair {
+            secret_key: new_secret_key,
+            public_key: new_public_key,
+        };
+    }
+
+    tracing::info!("✓ Keypair rotated; new public key: {new_public_key}");
+    Ok(Json(ServerInfo {
+        server_id: state.server_id.clone(),
+        public_key: new_public_key,
+    }))
+}
+
+/// Build the CORS layer from the configured policy; a literal "*" origin
+/// keeps the permissive default. Mirrors the podnet server's policy handling.
+fn build_cors_layer(config: &config::IdentityServerConfig) -> CorsLayer {
+    use axum::http::{HeaderValue, Method, header};
+
+    if config.cors_allowed_origins.iter().any(|origin| origin == "*") {
+        if config.cors_allow_credentials {
+            tracing::warn!(
+                "IDENTITY_CORS_ALLOW_CREDENTIALS is ignored with wildcard CORS origins"
+            );
+        }
+        return CorsLayer::permissive();
+    }
+
+    let origins: Vec<HeaderValue> = config
+        .cors_allowed_origins
+        .iter()
+        .filter_map(|origin| match origin.parse() {
+            Ok(value) => Some(value),
+            Err(_) => {
+                tracing::warn!("Ignoring invalid CORS origin: {origin}");
+                None
+            }
+        })
+        .collect();
+
+    // Challenge/issue requests POST JSON, which triggers a preflight
+    CorsLayer::new()
+        .allow_origin(AllowOrigin::list(origins))
+        .allow_methods([Method::GET, Method::POST])
+        .allow_headers([header::CONTENT_TYPE])
+        .allow_credentials(config.cors_allow_credentials)
+        .max_age(std::time::Duration::from_secs(3600))
+}
+
 // Register this identity server with the podnet-server
 async fn register_with_podnet_server(
     server_id: &str,
     secret_key: &SecretKey,
     podnet_server_url: &str,
+    public_url: Option<&str>,
 ) -> Result<(), Box<dyn std::error::Error>> {
     tracing::info!("Registering with podnet-server at: {}", podnet_server_url);
 
@@ -436,6 +661,7 @@ async fn register_with_podnet_server(
     let registration_request = IdentityServerRegistrationRequest {
         server_challenge_pod: challenge_response.challenge_pod,
         identity_response_pod,
+        base_url: public_url.map(String::from),
     };
 
     let registration_response = client
@@ -505,16 +731,7 @@ fn load_or_create_keypair(keypair_file: &str) -> anyhow::Result<(String, SecretK
         let secret_key = SecretKey::new_rand();
         let public_key = secret_key.public_key();
 
-        // Save keypair to file
-        let keypair = IdentityServerKeypair {
-            server_id: server_id.clone(),
-            secret_key: hex::encode(secret_key.0.to_bytes_le()),
-            public_key,
-            created_at: chrono::Utc::now().to_rfc3339(),
-        };
-
-        let keypair_json = serde_json::to_string_pretty(&keypair)?;
-        fs::write(keypair_file, keypair_json)?;
+        save_keypair(keypair_file, &server_id, &secret_key, public_key)?;
 
         tracing::info!("✓ New keypair created and saved");
 
@@ -522,6 +739,24 @@ fn load_or_create_keypair(keypair_file: &str) -> anyhow::Result<(String, SecretK
     }
 }
 
+fn save_keypair(
+    keypair_file: &str,
+    server_id: &str,
+    secret_key: &SecretKey,
+    public_key: PublicKey,
+) -> anyhow::Result<()> {
+    let keypair = IdentityServerKeypair {
+        server_id: server_id.to_string(),
+        secret_key: hex::encode(secret_key.0.to_bytes_le()),
+        public_key,
+        created_at: chrono::Utc::now().to_rfc3339(),
+    };
+
+    let keypair_json = serde_json::to_string_pretty(&keypair)?;
+    fs::write(keypair_file, keypair_json)?;
+    Ok(())
+}
+
 // Database initialization function
 fn initialize_database(db_path: &str) -> anyhow::Result<Connection> {
     tracing::info!("Initializing database at: {}", db_path);
@@ -533,16 +768,53 @@ fn initialize_database(db_path: &str) -> anyhow::Result<Connection> {
         "CREATE TABLE IF NOT EXISTS users (
             public_key_json TEXT PRIMARY KEY,
             username TEXT NOT NULL,
+            username_normalized TEXT NOT NULL DEFAULT '',
             issued_at TEXT NOT NULL
         )",
         [],
     )?;
 
+    // Upgrade databases created before the normalized column existed
+    let _ = conn.execute(
+        "ALTER TABLE users ADD COLUMN username_normalized TEXT NOT NULL DEFAULT ''",
+        [],
+    );
+    conn.execute(
+        "UPDATE users SET username_normalized = lower(username) WHERE username_normalized = ''",
+        [],
+    )?;
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_users_username_normalized
+            ON users (username_normalized)",
+        [],
+    )?;
+
     tracing::info!("✓ Database initialized successfully");
     Ok(conn)
 }
 
 // Database operations
+
+/// Whether `username` is already bound (case-insensitively) to a public key
+/// other than `public_key`.
+fn username_taken_by_other(
+    conn: &Connection,
+    username: &str,
+    public_key: &PublicKey,
+) -> anyhow::Result<bool> {
+    let public_key_json = serde_json::to_string(public_key)?;
+    let mut stmt = conn.prepare(
+        "SELECT COUNT(*) FROM users WHERE username_normalized = lower(?1) AND public_key_json != ?2",
+    )?;
+    let count: i64 = stmt.query_row(params![username, public_key_json], |row| row.get(0))?;
+    Ok(count > 0)
+}
+
+/// Store the username mapping for a public key.
+///
+/// Rows are keyed by public key, so a user re-registering under a new username
+/// replaces their old mapping and frees the previous name. Callers must first
+/// reject usernames held by a different key (see [`username_taken_by_other`]).
 fn insert_user_mapping(
     conn: &Connection,
     public_key: &PublicKey,
@@ -552,8 +824,14 @@ fn insert_user_mapping(
     let issued_at = chrono::Utc::now().to_rfc3339();
 
     conn.execute(
-        "INSERT OR REPLACE INTO users (public_key_json, username, issued_at) VALUES (?1, ?2, ?3)",
-        params![public_key_json, username, issued_at],
+        "INSERT OR REPLACE INTO users (public_key_json, username, username_normalized, issued_at)
+            VALUES (?1, ?2, ?3, ?4)",
+        params![
+            public_key_json,
+            username,
+            username.to_lowercase(),
+            issued_at
+        ],
     )?;
 
     tracing::info!(
@@ -581,6 +859,86 @@ fn get_username_by_public_key(
     }
 }
 
+fn lookup_public_key_by_username(
+    conn: &Connection,
+    username: &str,
+) -> anyhow::Result<Option<UsernameMatch>> {
+    let mut stmt = conn.prepare(
+        "SELECT username, public_key_json FROM users WHERE username_normalized = lower(?1)",
+    )?;
+    let mut rows = stmt.query(params![username])?;
+
+    if let Some(row) = rows.next()? {
+        let username: String = row.get(0)?;
+        let public_key_json: String = row.get(1)?;
+        let public_key: PublicKey = serde_json::from_str(&public_key_json)?;
+        Ok(Some(UsernameMatch {
+            username,
+            public_key,
+        }))
+    } else {
+        Ok(None)
+    }
+}
+
+fn lookup_usernames_by_prefix(
+    conn: &Connection,
+    prefix: &str,
+    limit: usize,
+) -> anyhow::Result<Vec<UsernameMatch>> {
+    // Escape LIKE wildcards so the query is a literal prefix match
+    let escaped = prefix
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_");
+    let mut stmt = conn.prepare(
+        "SELECT username, public_key_json FROM users
+            WHERE username_normalized LIKE lower(?1) || '%' ESCAPE '\\'
+            ORDER BY username_normalized LIMIT ?2",
+    )?;
+    let mut rows = stmt.query(params![escaped, limit as i64])?;
+
+    let mut matches = Vec::new();
+    while let Some(row) = rows.next()? {
+        let username: String = row.get(0)?;
+        let public_key_json: String = row.get(1)?;
+        let public_key: PublicKey = serde_json::from_str(&public_key_json)?;
+        matches.push(UsernameMatch {
+            username,
+            public_key,
+        });
+    }
+    Ok(matches)
+}
+
+/// Waits for SIGINT or SIGTERM; used as axum's graceful-shutdown trigger so
+/// in-flight requests finish and SQLite is never killed mid-transaction.
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        tokio::signal::ctrl_c()
+            .await
+            .expect("failed to install Ctrl+C handler");
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+            .expect("failed to install SIGTERM handler")
+            .recv()
+            .await;
+    };
+
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {},
+        _ = terminate => {},
+    }
+
+    tracing::info!("Shutdown signal received, draining in-flight requests");
+}
+
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
     tracing_subscriber::registry()
@@ -608,8 +966,13 @@ async fn main() -> anyhow::Result<()> {
 
     // Attempt to register with podnet-server
     tracing::info!("Attempting to register with podnet-server...");
-    if let Err(e) =
-        register_with_podnet_server(&server_id, &server_secret_key, &config.podnet_server_url).await
+    if let Err(e) = register_with_podnet_server(
+        &server_id,
+        &server_secret_key,
+        &config.podnet_server_url,
+        config.public_url.as_deref(),
+    )
+    .await
     {
         tracing::warn!("Failed to register with podnet-server: {}", e);
         tracing::warn!("Identity server will continue running, but won't be registered.");
@@ -624,8 +987,12 @@ async fn main() -> anyhow::Result<()> {
 
     let state = IdentityServerState {
         server_id: server_id.clone(),
-        server_secret_key: Arc::new(server_secret_key),
-        server_public_key,
+        keypair: Arc::new(Mutex::new(ServerKeypair {
+            secret_key: server_secret_key,
+            public_key: server_public_key,
+        })),
+        keypair_file: config.keypair_file.clone(),
+        podnet_server_url: config.podnet_server_url.clone(),
         db_conn,
     };
 
@@ -634,7 +1001,13 @@ async fn main() -> anyhow::Result<()> {
         .route("/user/challenge", post(request_user_challenge))
         .route("/identity", post(issue_identity))
         .route("/lookup", get(lookup_username_by_public_key))
-        .layer(CorsLayer::permissive())
+        .route(
+            "/lookup-username",
+            get(lookup_public_key_by_username_handler),
+        )
+        .route("/lookup-prefix", get(lookup_usernames_by_prefix_handler))
+        .route("/admin/rotate-keypair", post(rotate_keypair))
+        .layer(build_cors_layer(&config))
         .with_state(state);
 
     let bind_addr = format!("{}:{}", config.host, config.port);
@@ -652,7 +1025,96 @@ async fn main() -> anyhow::Result<()> {
     tracing::info!(
         "  GET  /lookup          - Look up username by public key (query param: public_key)"
     );
+    tracing::info!(
+        "  GET  /lookup-username - Look up public key by username (query param: username)"
+    );
+    tracing::info!("  GET  /lookup-prefix   - Search usernames by prefix (query param: q)");
+    tracing::info!("  POST /admin/rotate-keypair - Rotate the server keypair");
 
-    axum::serve(listener, app).await?;
+    axum::serve(listener, app)
+        .with_graceful_shutdown(shutdown_signal())
+        .await?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_db_with_users(usernames: &[&str]) -> (Connection, Vec<PublicKey>) {
+        let conn = initialize_database(":memory:").unwrap();
+        let mut public_keys = Vec::new();
+        for username in usernames {
+            let public_key = SecretKey::new_rand().public_key();
+            insert_user_mapping(&conn, &public_key, username).unwrap();
+            public_keys.push(public_key);
+        }
+        (conn, public_keys)
+    }
+
+    #[test]
+    fn test_lookup_public_key_by_username_exact_and_case_insensitive() {
+        let (conn, public_keys) = test_db_with_users(&["Alice", "bob"]);
+
+        let exact = lookup_public_key_by_username(&conn, "Alice")
+            .unwrap()
+            .unwrap();
+        assert_eq!(exact.username, "Alice");
+        assert_eq!(exact.public_key, public_keys[0]);
+
+        let insensitive = lookup_public_key_by_username(&conn, "aLiCe")
+            .unwrap()
+            .unwrap();
+        assert_eq!(insensitive.username, "Alice");
+        assert_eq!(insensitive.public_key, public_keys[0]);
+
+        assert!(
+            lookup_public_key_by_username(&conn, "carol")
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_username_cannot_be_shared_across_public_keys() {
+        let (conn, public_keys) = test_db_with_users(&["Alice"]);
+        let other_key = SecretKey::new_rand().public_key();
+
+        // A different key cannot take the name, regardless of case
+        assert!(username_taken_by_other(&conn, "Alice", &other_key).unwrap());
+        assert!(username_taken_by_other(&conn, "aLiCe", &other_key).unwrap());
+
+        // The original key may re-register its own name
+        assert!(!username_taken_by_other(&conn, "Alice", &public_keys[0]).unwrap());
+
+        // Re-binding the original key to a new name frees the old one
+        insert_user_mapping(&conn, &public_keys[0], "Alicia").unwrap();
+        assert!(!username_taken_by_other(&conn, "Alice", &other_key).unwrap());
+        assert!(username_taken_by_other(&conn, "Alicia", &other_key).unwrap());
+    }
+
+    #[test]
+    fn test_lookup_usernames_by_prefix() {
+        let (conn, _) = test_db_with_users(&["Alice", "alfred", "bob"]);
+
+        let matches = lookup_usernames_by_prefix(&conn, "AL", MAX_PREFIX_MATCHES).unwrap();
+        let usernames: Vec<&str> = matches.iter().map(|m| m.username.as_str()).collect();
+        assert_eq!(usernames, vec!["alfred", "Alice"]);
+
+        let limited = lookup_usernames_by_prefix(&conn, "al", 1).unwrap();
+        assert_eq!(limited.len(), 1);
+
+        assert!(
+            lookup_usernames_by_prefix(&conn, "zz", MAX_PREFIX_MATCHES)
+                .unwrap()
+                .is_empty()
+        );
+
+        // LIKE wildcards in the query are treated literally
+        assert!(
+            lookup_usernames_by_prefix(&conn, "%", MAX_PREFIX_MATCHES)
+                .unwrap()
+                .is_empty()
+        );
+    }
+}
@@ -1,5 +1,7 @@
 use std::env;
 
+use podnet_models::ContentLimits;
+
 #[derive(Debug, Clone)]
 pub struct ServerConfig {
     /// Whether to use mock proofs instead of real ZK proofs for faster development
@@ -12,6 +14,39 @@ pub struct ServerConfig {
     pub database_path: String,
     /// Path to the content storage directory
     pub content_storage_path: String,
+    /// Maximum publishes per user per hour
+    pub publish_per_hour: u32,
+    /// Maximum upvotes per user per hour
+    pub upvote_per_hour: u32,
+    /// Maximum identity requests per client IP per hour
+    pub identity_per_hour: u32,
+    /// Whether to garbage collect orphaned content blobs at startup
+    pub gc_on_startup: bool,
+    /// Whether retrieved documents are re-hashed against their content id,
+    /// so disk corruption is detected instead of served
+    pub verify_content_on_read: bool,
+    /// Token required by the admin endpoints; they are disabled when unset
+    pub admin_token: Option<String>,
+    /// Auto-hide a document once it has this many unresolved flags (None = never)
+    pub flag_auto_hide_threshold: Option<u32>,
+    /// Maximum depth of a reply chain; replies that would exceed it are rejected
+    pub max_reply_depth: u32,
+    /// Seconds between identity server health polls; 0 disables polling
+    pub identity_poll_interval_secs: u64,
+    /// Maximum age of a registration challenge before it is rejected as stale
+    pub challenge_max_age_secs: u64,
+    /// Directory snapshots are written into by `/admin/backup`
+    pub backup_dir: String,
+    /// Seconds between scheduled backups; 0 disables the scheduler
+    pub backup_interval_secs: u64,
+    /// Browser origins allowed by CORS; a literal "*" allows any origin
+    pub cors_allowed_origins: Vec<String>,
+    /// Methods browsers may use cross-origin
+    pub cors_allowed_methods: Vec<String>,
+    /// Whether cross-origin requests may carry credentials (ignored with "*" origins)
+    pub cors_allow_credentials: bool,
+    /// Size limits enforced on published content
+    pub content_limits: ContentLimits,
 }
 
 impl Default for ServerConfig {
@@ -22,6 +57,22 @@ impl Default for ServerConfig {
             host: "0.0.0.0".to_string(), // Bind to all interfaces for deployment
             database_path: "app.db".to_string(),
             content_storage_path: "content".to_string(),
+            publish_per_hour: 10,
+            upvote_per_hour: 60,
+            identity_per_hour: 30,
+            gc_on_startup: false,
+            verify_content_on_read: true,
+            admin_token: None,
+            flag_auto_hide_threshold: None,
+            max_reply_depth: 50,
+            identity_poll_interval_secs: 300,
+            challenge_max_age_secs: 300,
+            backup_dir: "backups".to_string(),
+            backup_interval_secs: 0,
+            cors_allowed_origins: vec!["*".to_string()],
+            cors_allowed_methods: ["GET", "POST", "DELETE"].map(String::from).to_vec(),
+            cors_allow_credentials: false,
+            content_limits: ContentLimits::default(),
         }
     }
 }
@@ -46,12 +97,122 @@ impl ServerConfig {
         let content_storage_path =
             env::var("PODNET_CONTENT_STORAGE_PATH").unwrap_or_else(|_| "content".to_string());
 
+        let defaults = Self::default();
+        let publish_per_hour = env::var("PODNET_PUBLISH_PER_HOUR")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(defaults.publish_per_hour);
+
+        let upvote_per_hour = env::var("PODNET_UPVOTE_PER_HOUR")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(defaults.upvote_per_hour);
+
+        let identity_per_hour = env::var("PODNET_IDENTITY_PER_HOUR")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(defaults.identity_per_hour);
+
+        let gc_on_startup = env::var("PODNET_GC_ON_STARTUP")
+            .map(|v| v.parse().unwrap_or(false))
+            .unwrap_or(false);
+
+        let verify_content_on_read = env::var("PODNET_VERIFY_CONTENT_ON_READ")
+            .map(|v| v.parse().unwrap_or(true))
+            .unwrap_or(true);
+
+        let admin_token = env::var("PODNET_ADMIN_TOKEN").ok().filter(|t| !t.is_empty());
+
+        let flag_auto_hide_threshold = env::var("PODNET_FLAG_AUTO_HIDE_THRESHOLD")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .filter(|&t: &u32| t > 0);
+
+        let max_reply_depth = env::var("PODNET_MAX_REPLY_DEPTH")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(defaults.max_reply_depth);
+
+        let identity_poll_interval_secs = env::var("PODNET_IDENTITY_POLL_INTERVAL_SECS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(defaults.identity_poll_interval_secs);
+
+        let challenge_max_age_secs = env::var("PODNET_CHALLENGE_MAX_AGE_SECS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(defaults.challenge_max_age_secs);
+
+        let backup_dir =
+            env::var("PODNET_BACKUP_DIR").unwrap_or_else(|_| defaults.backup_dir.clone());
+
+        let backup_interval_secs = env::var("PODNET_BACKUP_INTERVAL_SECS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(defaults.backup_interval_secs);
+
+        fn csv(name: &str, defaults: Vec<String>) -> Vec<String> {
+            env::var(name)
+                .ok()
+                .map(|v| {
+                    v.split(',')
+                        .map(|entry| entry.trim().to_string())
+                        .filter(|entry| !entry.is_empty())
+                        .collect::<Vec<_>>()
+                })
+                .filter(|entries| !entries.is_empty())
+                .unwrap_or(defaults)
+        }
+        let cors_allowed_origins = csv(
+            "PODNET_CORS_ALLOWED_ORIGINS",
+            defaults.cors_allowed_origins.clone(),
+        );
+        let cors_allowed_methods = csv(
+            "PODNET_CORS_ALLOWED_METHODS",
+            defaults.cors_allowed_methods.clone(),
+        );
+        let cors_allow_credentials = env::var("PODNET_CORS_ALLOW_CREDENTIALS")
+            .map(|v| v.parse().unwrap_or(false))
+            .unwrap_or(false);
+
+        fn limit(name: &str, default: usize) -> usize {
+            env::var(name)
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(default)
+        }
+        let limit_defaults = ContentLimits::default();
+        let content_limits = ContentLimits {
+            max_title_length: limit("PODNET_MAX_TITLE_LENGTH", limit_defaults.max_title_length),
+            max_message_bytes: limit("PODNET_MAX_MESSAGE_BYTES", limit_defaults.max_message_bytes),
+            max_file_bytes: limit("PODNET_MAX_FILE_BYTES", limit_defaults.max_file_bytes),
+            max_content_bytes: limit("PODNET_MAX_CONTENT_BYTES", limit_defaults.max_content_bytes),
+            max_tags: limit("PODNET_MAX_TAGS", limit_defaults.max_tags),
+            max_tag_length: limit("PODNET_MAX_TAG_LENGTH", limit_defaults.max_tag_length),
+        };
+
         Self {
             mock_proofs,
             port,
             host,
             database_path,
             content_storage_path,
+            publish_per_hour,
+            upvote_per_hour,
+            identity_per_hour,
+            gc_on_startup,
+            verify_content_on_read,
+            admin_token,
+            flag_auto_hide_threshold,
+            max_reply_depth,
+            identity_poll_interval_secs,
+            challenge_max_age_secs,
+            backup_dir,
+            backup_interval_secs,
+            cors_allowed_origins,
+            cors_allowed_methods,
+            cors_allow_credentials,
+            content_limits,
         }
     }
 
@@ -64,6 +225,57 @@ impl ServerConfig {
         tracing::info!("  Port: {}", config.port);
         tracing::info!("  Database path: {}", config.database_path);
         tracing::info!("  Content storage path: {}", config.content_storage_path);
+        tracing::info!(
+            "  Rate limits per hour: publish={}, upvote={}, identity={}",
+            config.publish_per_hour,
+            config.upvote_per_hour,
+            config.identity_per_hour
+        );
+        tracing::info!("  GC on startup: {}", config.gc_on_startup);
+        tracing::info!(
+            "  Admin endpoints: {}",
+            if config.admin_token.is_some() {
+                "enabled"
+            } else {
+                "disabled (PODNET_ADMIN_TOKEN unset)"
+            }
+        );
+        tracing::info!(
+            "  Content limits: title={}, message={}B, file={}B, total={}B, tags={}x{}",
+            config.content_limits.max_title_length,
+            config.content_limits.max_message_bytes,
+            config.content_limits.max_file_bytes,
+            config.content_limits.max_content_bytes,
+            config.content_limits.max_tags,
+            config.content_limits.max_tag_length
+        );
+        match config.flag_auto_hide_threshold {
+            Some(threshold) => tracing::info!("  Flag auto-hide threshold: {}", threshold),
+            None => tracing::info!("  Flag auto-hide: disabled"),
+        }
+        tracing::info!("  Max reply depth: {}", config.max_reply_depth);
+        match config.identity_poll_interval_secs {
+            0 => tracing::info!("  Identity server health polling: disabled"),
+            secs => tracing::info!("  Identity server health poll interval: {}s", secs),
+        }
+        tracing::info!(
+            "  Registration challenge max age: {}s",
+            config.challenge_max_age_secs
+        );
+        match config.backup_interval_secs {
+            0 => tracing::info!("  Scheduled backups: disabled (dir: {})", config.backup_dir),
+            secs => tracing::info!(
+                "  Scheduled backups: every {}s into {}",
+                secs,
+                config.backup_dir
+            ),
+        }
+        tracing::info!(
+            "  CORS: origins={:?}, methods={:?}, credentials={}",
+            config.cors_allowed_origins,
+            config.cors_allowed_methods,
+            config.cors_allow_credentials
+        );
         config
     }
 }
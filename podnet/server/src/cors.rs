@@ -0,0 +1,151 @@
+//! CORS policy built from [`ServerConfig`].
+//!
+//! The default configuration keeps the historical permissive behaviour (any
+//! origin, no credentials). Deployments that want to restrict browser access
+//! list explicit origins instead, and the layer then only echoes those.
+
+use axum::http::{HeaderValue, Method, header};
+use tower_http::cors::{AllowOrigin, CorsLayer};
+
+use crate::config::ServerConfig;
+
+/// How long browsers may cache preflight responses
+const PREFLIGHT_MAX_AGE: std::time::Duration = std::time::Duration::from_secs(3600);
+
+/// Build the CORS layer for the router from the configured policy. A literal
+/// "*" origin keeps the permissive behaviour; credentials are refused in that
+/// mode because browsers reject wildcard origins on credentialed requests.
+pub fn build_cors_layer(config: &ServerConfig) -> CorsLayer {
+    if config.cors_allowed_origins.iter().any(|origin| origin == "*") {
+        if config.cors_allow_credentials {
+            tracing::warn!(
+                "PODNET_CORS_ALLOW_CREDENTIALS is ignored with wildcard CORS origins"
+            );
+        }
+        return CorsLayer::permissive();
+    }
+
+    let origins: Vec<HeaderValue> = config
+        .cors_allowed_origins
+        .iter()
+        .filter_map(|origin| match origin.parse() {
+            Ok(value) => Some(value),
+            Err(_) => {
+                tracing::warn!("Ignoring invalid CORS origin: {origin}");
+                None
+            }
+        })
+        .collect();
+    let methods: Vec<Method> = config
+        .cors_allowed_methods
+        .iter()
+        .filter_map(|method| match method.parse() {
+            Ok(method) => Some(method),
+            Err(_) => {
+                tracing::warn!("Ignoring invalid CORS method: {method}");
+                None
+            }
+        })
+        .collect();
+
+    // Publishing and upvoting POST JSON bodies and the admin endpoints send a
+    // bearer token, both of which trigger preflights, so those headers must be
+    // allowed explicitly. The SSE endpoint sends no custom headers but
+    // benefits from the cached preflight like everything else.
+    CorsLayer::new()
+        .allow_origin(AllowOrigin::list(origins))
+        .allow_methods(methods)
+        .allow_headers([header::CONTENT_TYPE, header::AUTHORIZATION])
+        .allow_credentials(config.cors_allow_credentials)
+        .max_age(PREFLIGHT_MAX_AGE)
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{
+        Router,
+        body::Body,
+        http::{Request, Response},
+        routing::get,
+    };
+    use tower::ServiceExt;
+
+    use super::*;
+
+    fn restrictive_config() -> ServerConfig {
+        ServerConfig {
+            cors_allowed_origins: vec!["https://app.example.com".to_string()],
+            ..ServerConfig::default()
+        }
+    }
+
+    async fn preflight(config: &ServerConfig, origin: &str) -> Response<Body> {
+        let app = Router::new()
+            .route("/documents", get(|| async { "ok" }))
+            .layer(build_cors_layer(config));
+        app.oneshot(
+            Request::builder()
+                .method("OPTIONS")
+                .uri("/documents")
+                .header("Origin", origin)
+                .header("Access-Control-Request-Method", "GET")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_listed_origin_is_allowed() {
+        let response = preflight(&restrictive_config(), "https://app.example.com").await;
+        assert_eq!(
+            response
+                .headers()
+                .get("access-control-allow-origin")
+                .and_then(|v| v.to_str().ok()),
+            Some("https://app.example.com")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_unlisted_origin_is_rejected() {
+        let response = preflight(&restrictive_config(), "https://evil.example.com").await;
+        // Without the allow-origin header the browser blocks the response
+        assert!(
+            response
+                .headers()
+                .get("access-control-allow-origin")
+                .is_none()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_wildcard_keeps_permissive_behaviour() {
+        let response = preflight(&ServerConfig::default(), "https://anywhere.example.com").await;
+        assert_eq!(
+            response
+                .headers()
+                .get("access-control-allow-origin")
+                .and_then(|v| v.to_str().ok()),
+            Some("*")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_disallowed_method_is_not_offered() {
+        let config = ServerConfig {
+            cors_allowed_methods: vec!["GET".to_string()],
+            ..restrictive_config()
+        };
+        let response = preflight(&config, "https://app.example.com").await;
+        let methods = response
+            .headers()
+            .get("access-control-allow-methods")
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or_default()
+            .to_string();
+        assert!(methods.contains("GET"));
+        assert!(!methods.contains("DELETE"));
+    }
+}
@@ -168,5 +168,185 @@ lazy_static! {
 
             Ok(())
         }),
+        M::up(
+            "CREATE TABLE IF NOT EXISTS notifications (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                username TEXT NOT NULL,
+                document_id INTEGER NOT NULL,
+                actor_username TEXT NOT NULL,
+                read INTEGER NOT NULL DEFAULT 0,
+                created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+                FOREIGN KEY (document_id) REFERENCES documents (id)
+            );
+            CREATE INDEX IF NOT EXISTS idx_notifications_username ON notifications(username);"
+        ),
+        M::up("ALTER TABLE identity_servers ADD COLUMN revoked_at DATETIME;"),
+        M::up(
+            "CREATE TABLE IF NOT EXISTS identity_server_keys (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                server_id TEXT NOT NULL,
+                public_key TEXT NOT NULL,
+                valid_from DATETIME DEFAULT CURRENT_TIMESTAMP,
+                valid_until DATETIME,
+                FOREIGN KEY (server_id) REFERENCES identity_servers (server_id)
+            );
+            INSERT INTO identity_server_keys (server_id, public_key, valid_from)
+                SELECT server_id, public_key, created_at FROM identity_servers;"
+        ),
+        M::up(
+            "ALTER TABLE documents ADD COLUMN hidden INTEGER NOT NULL DEFAULT 0;
+            CREATE TABLE IF NOT EXISTS document_flags (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                document_id INTEGER NOT NULL,
+                username TEXT NOT NULL,
+                reason TEXT NOT NULL,
+                created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+                resolved_at DATETIME,
+                resolution TEXT,
+                FOREIGN KEY (document_id) REFERENCES documents (id),
+                UNIQUE (document_id, username)
+            );
+            CREATE TABLE IF NOT EXISTS moderation_audit (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                flag_id INTEGER NOT NULL,
+                document_id INTEGER NOT NULL,
+                action TEXT NOT NULL,
+                admin_token_id TEXT NOT NULL,
+                created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+                FOREIGN KEY (flag_id) REFERENCES document_flags (id)
+            );"
+        ),
+        M::up(
+            "ALTER TABLE documents ADD COLUMN upvote_count_pod_count INTEGER NOT NULL DEFAULT 0;
+            CREATE TABLE IF NOT EXISTS upvote_count_jobs (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                document_id INTEGER NOT NULL,
+                requested_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+                status TEXT NOT NULL DEFAULT 'pending',
+                attempts INTEGER NOT NULL DEFAULT 0,
+                not_before DATETIME,
+                last_error TEXT,
+                completed_at DATETIME,
+                FOREIGN KEY (document_id) REFERENCES documents (id)
+            );
+            CREATE UNIQUE INDEX IF NOT EXISTS idx_upvote_count_jobs_pending
+                ON upvote_count_jobs(document_id) WHERE status = 'pending';"
+        ),
+        // Indices for the list-view queries: the latest-reply subselects scan
+        // posts by thread root and documents by (post, creation time), and the
+        // batched upvote counts group by document_id.
+        M::up(
+            "CREATE INDEX IF NOT EXISTS idx_posts_thread_root_post_id
+                ON posts(thread_root_post_id);
+            CREATE INDEX IF NOT EXISTS idx_documents_post_id_created_at
+                ON documents(post_id, created_at);
+            CREATE INDEX IF NOT EXISTS idx_upvotes_document_id
+                ON upvotes(document_id);"
+        ),
+        // The windowed variant of the top sort filters upvotes by recency
+        // before aggregating.
+        M::up(
+            "CREATE INDEX IF NOT EXISTS idx_upvotes_created_at
+                ON upvotes(created_at);"
+        ),
+        // Operator-pinned announcement posts; set = pinned to the top of the
+        // document list, NULL = normal ordering.
+        M::up("ALTER TABLE posts ADD COLUMN pinned_at DATETIME;"),
+        // Metadata for attachment blobs referenced from document content:
+        // name and MIME type for serving them, and liveness for the content GC.
+        M::up(
+            "CREATE TABLE attachments (
+                content_id TEXT PRIMARY KEY,
+                name TEXT NOT NULL,
+                mime TEXT NOT NULL,
+                created_at DATETIME DEFAULT CURRENT_TIMESTAMP
+            );"
+        ),
+        // Reply-chain depth, maintained on insert so the publish handler can
+        // enforce max_reply_depth without walking parent_post_id. Backfilled
+        // for existing threads by walking down from the roots.
+        M::up(
+            "ALTER TABLE posts ADD COLUMN depth INTEGER NOT NULL DEFAULT 0;
+            WITH RECURSIVE post_depth(id, depth) AS (
+                SELECT id, 0 FROM posts WHERE parent_post_id IS NULL
+                UNION ALL
+                SELECT p.id, pd.depth + 1
+                    FROM posts p JOIN post_depth pd ON p.parent_post_id = pd.id
+            )
+            UPDATE posts
+                SET depth = (SELECT depth FROM post_depth WHERE post_depth.id = posts.id);"
+        ),
+        // Full-text index over document titles and messages, maintained by
+        // create_document/delete_document. Messages of pre-existing documents
+        // live in content storage, so only their titles are backfilled.
+        M::up(
+            "CREATE VIRTUAL TABLE documents_fts USING fts5(title, message);
+            INSERT INTO documents_fts(rowid, title, message)
+                SELECT id, title, '' FROM documents;"
+        ),
+        // Append-only log of usernames an identity server has bound to a
+        // public key. The newest row per key is the current display name;
+        // earlier rows double as the audit trail for renames.
+        M::up(
+            "CREATE TABLE user_aliases (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                public_key TEXT NOT NULL,
+                username TEXT NOT NULL,
+                created_at DATETIME DEFAULT CURRENT_TIMESTAMP
+            );
+            CREATE INDEX idx_user_aliases_public_key ON user_aliases(public_key);
+            CREATE INDEX idx_user_aliases_username ON user_aliases(username);"
+        ),
+        // Health-poll state for registered identity servers: the endpoint they
+        // advertised at registration and what the poller last saw there.
+        M::up(
+            "ALTER TABLE identity_servers ADD COLUMN base_url TEXT;
+            ALTER TABLE identity_servers ADD COLUMN last_seen_at DATETIME;
+            ALTER TABLE identity_servers ADD COLUMN last_status TEXT;
+            ALTER TABLE identity_servers ADD COLUMN key_mismatch INTEGER NOT NULL DEFAULT 0;"
+        ),
+        // Thread subscriptions: who gets a notification row when a reply lands
+        // anywhere in a thread, keyed by the thread root document.
+        M::up(
+            "CREATE TABLE thread_subscriptions (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                username TEXT NOT NULL,
+                thread_root_id INTEGER NOT NULL,
+                created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+                FOREIGN KEY (thread_root_id) REFERENCES documents (id),
+                UNIQUE (username, thread_root_id)
+            );
+            CREATE INDEX idx_thread_subscriptions_root ON thread_subscriptions(thread_root_id);"
+        ),
+        // Tombstone marker for documents deleted while replies still hang off
+        // them: the row survives with its content blanked so the thread keeps
+        // its shape.
+        M::up("ALTER TABLE documents ADD COLUMN deleted_at DATETIME;"),
+        // Nonces of registration challenges that have already been presented,
+        // so a captured challenge/response exchange cannot be replayed.
+        M::up(
+            "CREATE TABLE consumed_challenge_nonces (
+                nonce TEXT PRIMARY KEY,
+                consumed_at DATETIME DEFAULT CURRENT_TIMESTAMP
+            );"
+        ),
+        // Content-hash lookups (GET /documents/by-content/:hash) scan by
+        // content_id, which was unindexed.
+        M::up("CREATE INDEX IF NOT EXISTS idx_documents_content_id ON documents(content_id);"),
+        // MIME type of the primary content, derived server-side at publish
+        // time; NULL for documents published before the type was recorded.
+        M::up("ALTER TABLE documents ADD COLUMN content_type TEXT;"),
+        // Long-lived bearer tokens for programmatic clients (bots, CI),
+        // issued against a verified identity pod. Only a hash of the token
+        // is stored; revocation keeps the row as an audit record.
+        M::up(
+            "CREATE TABLE tokens (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                token_hash TEXT NOT NULL UNIQUE,
+                username TEXT NOT NULL,
+                created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+                revoked_at DATETIME
+            );"
+        ),
     ]);
 }
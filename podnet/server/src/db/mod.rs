@@ -1,20 +1,125 @@
-use std::{collections::HashSet, sync::Mutex};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Mutex,
+};
 
 use hex::{FromHex, ToHex};
 use pod2::{frontend::MainPod, middleware::Hash};
 use podnet_models::{
-    Document, DocumentContent, DocumentListItem, DocumentMetadata, DocumentPods, DocumentReplyTree,
-    IdentityServer, Post, RawDocument, ReplyReference, Upvote, lazy_pod::LazyDeser,
+    Document, DocumentContent, DocumentFlag, DocumentListItem, DocumentMetadata, DocumentPods,
+    DocumentReplyTree, FlaggedDocument, IdentityServer, IdentityServerKey, Notification, Post,
+    RawDocument, ReplyReference, Upvote,
+    lazy_pod::LazyDeser,
 };
 use rusqlite::{Connection, OptionalExtension, Result};
+use serde::Serialize;
 
 pub mod migrations;
 
+/// Pruning parameters for reply tree construction. `None` values mean unlimited,
+/// which preserves the full-tree behavior of `get_reply_tree_for_document`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ReplyTreePruning {
+    /// Maximum reply depth below the requested document
+    pub depth: Option<usize>,
+    /// Maximum number of replies included per node; the rest become stubs
+    pub limit_per_node: Option<usize>,
+}
+
+/// Reply tree slice with metadata only; each node's content id lives in its
+/// metadata. Built entirely from SQL, so content can be fetched from storage
+/// afterwards — concurrently, in the handlers — instead of one blocking read
+/// per node while assembling the tree.
+#[derive(Debug, Clone)]
+pub struct ReplyTreeSkeleton {
+    pub document: DocumentMetadata,
+    pub replies: Vec<ReplyTreeSkeleton>,
+    pub stubs: Vec<podnet_models::ReplyTreeStub>,
+}
+
+/// Ordering applied to the top-level document list. Implemented in SQL so it
+/// composes with any future pagination of the list query.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DocumentSort {
+    /// Latest root revision first (creation order)
+    #[default]
+    New,
+    /// Most upvoted first, optionally restricted to a recent window
+    Top,
+    /// Most recently replied-to thread first
+    Active,
+}
+
+impl DocumentSort {
+    /// The query-parameter spelling of this sort, echoed back in responses.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DocumentSort::New => "new",
+            DocumentSort::Top => "top",
+            DocumentSort::Active => "active",
+        }
+    }
+}
+
+/// Row counts and freshness data for the operator stats endpoint
+#[derive(Debug, Clone)]
+pub struct DatabaseStats {
+    pub document_count: i64,
+    pub post_count: i64,
+    pub upvote_count: i64,
+    pub identity_server_count: i64,
+    /// Documents whose upvote count pod has not been generated yet
+    pub upvote_count_pod_backlog: i64,
+    pub last_publish_at: Option<String>,
+}
+
+/// One row of the moderation audit trail: which admin token hid which
+/// document, via which flag
+#[derive(Debug, Clone)]
+pub struct ModerationAuditEntry {
+    pub id: i64,
+    pub flag_id: i64,
+    pub document_id: i64,
+    pub action: String,
+    pub admin_token_id: String,
+    pub created_at: Option<String>,
+}
+
+/// One queued recount of a document's upvote count pod. At most one `pending`
+/// job exists per document, so a burst of upvotes coalesces into a single
+/// proving pass.
+#[derive(Debug, Clone, Serialize)]
+pub struct UpvoteCountJob {
+    pub id: i64,
+    pub document_id: i64,
+    pub requested_at: Option<String>,
+    pub status: String,
+    pub attempts: i64,
+    pub not_before: Option<String>,
+    pub last_error: Option<String>,
+    pub completed_at: Option<String>,
+}
+
+/// SQLite's default host-parameter limit is 999; batched IN queries chunk
+/// their id lists to stay comfortably below it.
+const SQL_PARAM_CHUNK: usize = 500;
+
 pub struct Database {
     conn: Mutex<Connection>,
+    /// Connection acquisitions, one per query batch. Tests use this to assert
+    /// that list endpoints issue a bounded number of queries.
+    #[cfg(test)]
+    pub(crate) query_count: std::sync::atomic::AtomicUsize,
 }
 
 impl Database {
+    fn conn(&self) -> std::sync::MutexGuard<'_, Connection> {
+        #[cfg(test)]
+        self.query_count
+            .fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+        self.conn.lock().unwrap()
+    }
+
     pub async fn new(db_path: &str) -> anyhow::Result<Self> {
         let db_path = db_path.to_string();
         tokio::task::spawn_blocking(move || {
@@ -49,6 +154,8 @@ impl Database {
 
             let db = Database {
                 conn: Mutex::new(conn),
+                #[cfg(test)]
+                query_count: std::sync::atomic::AtomicUsize::new(0),
             };
             Ok(db)
         })
@@ -62,23 +169,38 @@ impl Database {
         thread_root_post_id: Option<i64>,
         reply_to_document_id: Option<i64>,
     ) -> Result<()> {
-        let conn = self.conn.lock().unwrap();
+        let conn = self.conn();
         conn.execute(
-            "UPDATE posts SET parent_post_id = ?1, thread_root_post_id = ?2, reply_to_document_id = ?3 WHERE id = ?4",
+            "UPDATE posts SET parent_post_id = ?1, thread_root_post_id = ?2, reply_to_document_id = ?3,
+                depth = CASE
+                    WHEN ?1 IS NULL THEN 0
+                    ELSE (SELECT depth + 1 FROM posts WHERE id = ?1)
+                END
+             WHERE id = ?4",
             rusqlite::params![parent_post_id, thread_root_post_id, reply_to_document_id, post_id],
         )?;
         Ok(())
     }
 
+    pub fn get_post_depth(&self, post_id: i64) -> Result<Option<i64>> {
+        let conn = self.conn();
+        let depth = conn
+            .query_row("SELECT depth FROM posts WHERE id = ?1", [post_id], |row| {
+                row.get(0)
+            })
+            .optional()?;
+        Ok(depth)
+    }
+
     // Post methods
     pub fn create_post(&self) -> Result<i64> {
-        let conn = self.conn.lock().unwrap();
+        let conn = self.conn();
         conn.execute("INSERT INTO posts DEFAULT VALUES", [])?;
         Ok(conn.last_insert_rowid())
     }
 
     pub fn get_post(&self, id: i64) -> Result<Option<Post>> {
-        let conn = self.conn.lock().unwrap();
+        let conn = self.conn();
         let mut stmt = conn.prepare(
             "SELECT id, created_at, last_edited_at, thread_root_post_id FROM posts WHERE id = ?1",
         )?;
@@ -97,8 +219,35 @@ impl Database {
         Ok(post)
     }
 
+    /// Pin or unpin a post in the document list. Returns false when the post
+    /// does not exist.
+    pub fn set_post_pinned(&self, post_id: i64, pinned: bool) -> Result<bool> {
+        let conn = self.conn();
+        let updated = if pinned {
+            conn.execute(
+                "UPDATE posts SET pinned_at = CURRENT_TIMESTAMP WHERE id = ?1",
+                [post_id],
+            )?
+        } else {
+            conn.execute("UPDATE posts SET pinned_at = NULL WHERE id = ?1", [post_id])?
+        };
+        Ok(updated > 0)
+    }
+
+    /// Fingerprint of the current pin state, folded into the document list
+    /// ETag so pinning and unpinning invalidate cached lists.
+    pub fn get_pin_fingerprint(&self) -> Result<String> {
+        let conn = self.conn();
+        let fingerprint = conn.query_row(
+            "SELECT COUNT(pinned_at) || '-' || COALESCE(MAX(pinned_at), '') FROM posts",
+            [],
+            |row| row.get(0),
+        )?;
+        Ok(fingerprint)
+    }
+
     pub fn get_all_posts(&self) -> Result<Vec<Post>> {
-        let conn = self.conn.lock().unwrap();
+        let conn = self.conn();
         let mut stmt = conn.prepare(
             "SELECT id, created_at, last_edited_at, thread_root_post_id FROM posts ORDER BY last_edited_at DESC",
         )?;
@@ -118,7 +267,7 @@ impl Database {
     }
 
     pub fn get_most_recent_modification_time(&self) -> Result<Option<String>> {
-        let conn = self.conn.lock().unwrap();
+        let conn = self.conn();
         let result = conn.query_row("SELECT MAX(last_edited_at) FROM posts", [], |row| {
             row.get::<_, Option<String>>(0)
         });
@@ -130,8 +279,77 @@ impl Database {
         }
     }
 
+    pub fn get_document_count(&self) -> Result<i64> {
+        let conn = self.conn();
+        conn.query_row("SELECT COUNT(*) FROM documents", [], |row| row.get(0))
+    }
+
+    /// Trivial connectivity check for the readiness probe.
+    pub fn ping(&self) -> Result<()> {
+        let conn = self.conn();
+        conn.query_row("SELECT 1", [], |row| row.get::<_, i64>(0))?;
+        Ok(())
+    }
+
+    /// Gather all operator stats in a single lock acquisition
+    pub fn get_stats(&self) -> Result<DatabaseStats> {
+        let conn = self.conn();
+        let count = |sql: &str| conn.query_row(sql, [], |row| row.get::<_, i64>(0));
+
+        Ok(DatabaseStats {
+            document_count: count("SELECT COUNT(*) FROM documents")?,
+            post_count: count("SELECT COUNT(*) FROM posts")?,
+            upvote_count: count("SELECT COUNT(*) FROM upvotes")?,
+            identity_server_count: count("SELECT COUNT(*) FROM identity_servers")?,
+            upvote_count_pod_backlog: count(
+                "SELECT COUNT(*) FROM documents WHERE upvote_count_pod IS NULL",
+            )?,
+            last_publish_at: conn.query_row("SELECT MAX(created_at) FROM documents", [], |row| {
+                row.get::<_, Option<String>>(0)
+            })?,
+        })
+    }
+
+    /// Content hashes still referenced by at least one document row, plus
+    /// every recorded attachment blob
+    pub fn get_referenced_content_ids(&self) -> Result<HashSet<String>> {
+        let conn = self.conn();
+        let mut stmt = conn.prepare(
+            "SELECT DISTINCT content_id FROM documents
+             UNION SELECT content_id FROM attachments",
+        )?;
+        let ids = stmt
+            .query_map([], |row| row.get::<_, String>(0))?
+            .collect::<Result<HashSet<_>, _>>()?;
+        Ok(ids)
+    }
+
+    /// Record name and MIME type for an attachment blob. Blobs are
+    /// content-addressed, so re-publishing the same bytes is a no-op.
+    pub fn record_attachment(&self, content_id: &str, name: &str, mime: &str) -> Result<()> {
+        let conn = self.conn();
+        conn.execute(
+            "INSERT OR IGNORE INTO attachments (content_id, name, mime) VALUES (?1, ?2, ?3)",
+            rusqlite::params![content_id, name, mime],
+        )?;
+        Ok(())
+    }
+
+    /// Name and MIME type recorded for an attachment blob, if any
+    pub fn get_attachment(&self, content_id: &str) -> Result<Option<(String, String)>> {
+        let conn = self.conn();
+        let result = conn
+            .query_row(
+                "SELECT name, mime FROM attachments WHERE content_id = ?1",
+                [content_id],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()?;
+        Ok(result)
+    }
+
     pub fn update_post_last_edited(&self, post_id: i64) -> Result<()> {
-        let conn = self.conn.lock().unwrap();
+        let conn = self.conn();
         conn.execute(
             "UPDATE posts SET last_edited_at = CURRENT_TIMESTAMP WHERE id = ?1",
             [post_id],
@@ -152,9 +370,10 @@ impl Database {
         reply_to: Option<ReplyReference>,
         requested_post_id: Option<i64>,
         title: &str,
-        storage: &crate::storage::ContentAddressedStorage,
+        content: &DocumentContent,
+        auto_subscribe: bool,
     ) -> Result<Document> {
-        let mut conn = self.conn.lock().unwrap();
+        let mut conn = self.conn();
         let tx = conn.transaction()?;
 
         // Get the next revision number for this post
@@ -206,9 +425,12 @@ impl Database {
             None
         };
 
+        // The stored type is derived from the content, never from the client
+        let content_type = content.derived_content_type();
+
         // Insert document with empty timestamp_pod and null upvote_count_pod initially
         tx.execute(
-            "INSERT INTO documents (content_id, post_id, revision, pod, timestamp_pod, uploader_id, upvote_count_pod, tags, authors, reply_to, requested_post_id, title, thread_root_id) VALUES (?1, ?2, ?3, ?4, '', ?5, NULL, ?6, ?7, ?8, ?9, ?10, ?11)",
+            "INSERT INTO documents (content_id, post_id, revision, pod, timestamp_pod, uploader_id, upvote_count_pod, tags, authors, reply_to, requested_post_id, title, thread_root_id, content_type) VALUES (?1, ?2, ?3, ?4, '', ?5, NULL, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
             rusqlite::params![
                 content_id_string,
                 post_id,
@@ -221,6 +443,7 @@ impl Database {
                 requested_post_id,
                 title,
                 thread_root_id, // Option<i64> -> NULL for roots, parent thread id for replies
+                content_type,
             ],
         )?;
 
@@ -246,6 +469,10 @@ impl Database {
                 "UPDATE documents SET thread_root_id = ?1 WHERE id = ?1",
                 [document_id],
             )?;
+            // Thread-root authors follow their own threads unless they opted out
+            if auto_subscribe {
+                Self::insert_thread_subscription(&tx, uploader_id, document_id)?;
+            }
         }
 
         // Update the post's last_edited_at timestamp
@@ -254,25 +481,26 @@ impl Database {
             [post_id],
         )?;
 
-        tx.commit()?;
+        // Notify the parent document's uploader about the reply, then the
+        // thread's subscribers (minus anyone already notified above)
+        if let Some(ref reply_ref) = reply_to {
+            Self::insert_reply_notification(&tx, reply_ref, uploader_id, document_id)?;
+            if let Some(root_id) = thread_root_id {
+                Self::insert_subscription_notifications(&tx, root_id, uploader_id, document_id)?;
+            }
+        }
 
-        // Retrieve content from storage
-        let content = storage
-            .retrieve_document_content(content_id)
-            .map_err(|_| {
-                rusqlite::Error::InvalidColumnType(
-                    0,
-                    "content".to_string(),
-                    rusqlite::types::Type::Text,
-                )
-            })?
-            .ok_or_else(|| {
-                rusqlite::Error::InvalidColumnType(
-                    0,
-                    "content".to_string(),
-                    rusqlite::types::Type::Text,
-                )
-            })?;
+        // Index the title and message for full-text search
+        tx.execute(
+            "INSERT INTO documents_fts(rowid, title, message) VALUES (?1, ?2, ?3)",
+            rusqlite::params![
+                document_id,
+                title,
+                content.message.as_deref().unwrap_or("")
+            ],
+        )?;
+
+        tx.commit()?;
 
         // Get upvote count (will be 0 for new document)
         let upvote_count = 0;
@@ -291,6 +519,10 @@ impl Database {
             reply_to,
             requested_post_id,
             title: title.to_string(),
+            hidden: false,
+            display_name: None,
+            deleted_at: None,
+            content_type: Some(content_type.to_string()),
         };
 
         // Create the pods
@@ -322,14 +554,14 @@ impl Database {
         Ok(Document {
             metadata,
             pods,
-            content,
+            content: content.clone(),
         })
     }
 
     pub fn get_raw_document(&self, id: i64) -> Result<Option<RawDocument>> {
-        let conn = self.conn.lock().unwrap();
+        let conn = self.conn();
         let mut stmt = conn.prepare(
-            "SELECT id, content_id, post_id, revision, created_at, pod, timestamp_pod, uploader_id, upvote_count_pod, tags, authors, reply_to, requested_post_id, title FROM documents WHERE id = ?1"
+            "SELECT id, content_id, post_id, revision, created_at, pod, timestamp_pod, uploader_id, upvote_count_pod, tags, authors, reply_to, requested_post_id, title, deleted_at, content_type FROM documents WHERE id = ?1"
         )?;
 
         let document = stmt
@@ -357,6 +589,8 @@ impl Database {
                     reply_to,
                     requested_post_id: row.get(12)?,
                     title: row.get(13)?,
+                    deleted_at: row.get(14)?,
+                    content_type: row.get(15)?,
                 })
             })
             .optional()?;
@@ -365,9 +599,9 @@ impl Database {
     }
 
     pub fn get_documents_by_post_id(&self, post_id: i64) -> Result<Vec<RawDocument>> {
-        let conn = self.conn.lock().unwrap();
+        let conn = self.conn();
         let mut stmt = conn.prepare(
-            "SELECT id, content_id, post_id, revision, created_at, pod, timestamp_pod, uploader_id, upvote_count_pod, tags, authors, reply_to, requested_post_id, title
+            "SELECT id, content_id, post_id, revision, created_at, pod, timestamp_pod, uploader_id, upvote_count_pod, tags, authors, reply_to, requested_post_id, title, deleted_at, content_type
              FROM documents WHERE post_id = ?1 ORDER BY revision DESC",
         )?;
 
@@ -396,6 +630,8 @@ impl Database {
                     reply_to,
                     requested_post_id: row.get(12)?,
                     title: row.get(13)?,
+                    deleted_at: row.get(14)?,
+                    content_type: row.get(15)?,
                 })
             })?
             .collect::<Result<Vec<_>, _>>()?;
@@ -404,9 +640,9 @@ impl Database {
     }
 
     pub fn get_latest_document_by_post_id(&self, post_id: i64) -> Result<Option<RawDocument>> {
-        let conn = self.conn.lock().unwrap();
+        let conn = self.conn();
         let mut stmt = conn.prepare(
-            "SELECT id, content_id, post_id, revision, created_at, pod, timestamp_pod, uploader_id, upvote_count_pod, tags, authors, reply_to, requested_post_id, title
+            "SELECT id, content_id, post_id, revision, created_at, pod, timestamp_pod, uploader_id, upvote_count_pod, tags, authors, reply_to, requested_post_id, title, deleted_at, content_type
              FROM documents WHERE post_id = ?1 ORDER BY revision DESC LIMIT 1",
         )?;
 
@@ -435,6 +671,8 @@ impl Database {
                     reply_to,
                     requested_post_id: row.get(12)?,
                     title: row.get(13)?,
+                    deleted_at: row.get(14)?,
+                    content_type: row.get(15)?,
                 })
             })
             .optional()?;
@@ -443,9 +681,9 @@ impl Database {
     }
 
     pub fn get_all_documents(&self) -> Result<Vec<RawDocument>> {
-        let conn = self.conn.lock().unwrap();
+        let conn = self.conn();
         let mut stmt = conn.prepare(
-            "SELECT id, content_id, post_id, revision, created_at, pod, timestamp_pod, uploader_id, upvote_count_pod, tags, authors, reply_to, requested_post_id, title
+            "SELECT id, content_id, post_id, revision, created_at, pod, timestamp_pod, uploader_id, upvote_count_pod, tags, authors, reply_to, requested_post_id, title, deleted_at, content_type
              FROM documents ORDER BY created_at DESC",
         )?;
 
@@ -474,6 +712,62 @@ impl Database {
                     reply_to,
                     requested_post_id: row.get(12)?,
                     title: row.get(13)?,
+                    deleted_at: row.get(14)?,
+                    content_type: row.get(15)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(documents)
+    }
+
+    /// Latest-revision top-level documents (no reply_to), newest first,
+    /// optionally filtered by tag. Used to build syndication feeds.
+    pub fn get_recent_top_level_documents(
+        &self,
+        limit: usize,
+        tag: Option<&str>,
+    ) -> Result<Vec<RawDocument>> {
+        let conn = self.conn();
+        let mut stmt = conn.prepare(
+            "SELECT id, content_id, post_id, revision, created_at, pod, timestamp_pod, uploader_id, upvote_count_pod, tags, authors, reply_to, requested_post_id, title, deleted_at, content_type
+             FROM documents d
+             WHERE d.reply_to IS NULL
+               AND d.hidden = 0
+               AND d.deleted_at IS NULL
+               AND d.revision = (SELECT MAX(x.revision) FROM documents x WHERE x.post_id = d.post_id AND x.reply_to IS NULL)
+               AND (?1 IS NULL OR EXISTS (SELECT 1 FROM json_each(d.tags) WHERE json_each.value = ?1))
+             ORDER BY d.created_at DESC, d.id DESC
+             LIMIT ?2",
+        )?;
+
+        let documents = stmt
+            .query_map(rusqlite::params![tag, limit as i64], |row| {
+                let tags_json: String = row.get(9)?;
+                let tags: HashSet<String> = serde_json::from_str(&tags_json).unwrap_or_default();
+                let authors_json: String = row.get(10)?;
+                let authors: HashSet<String> =
+                    serde_json::from_str(&authors_json).unwrap_or_default();
+                let reply_to_json: Option<String> = row.get(11)?;
+                let reply_to: Option<ReplyReference> =
+                    reply_to_json.and_then(|json| serde_json::from_str(&json).ok());
+                Ok(RawDocument {
+                    id: Some(row.get(0)?),
+                    content_id: row.get(1)?,
+                    post_id: row.get(2)?,
+                    revision: row.get(3)?,
+                    created_at: Some(row.get(4)?),
+                    pod: row.get(5)?,
+                    timestamp_pod: row.get(6)?,
+                    uploader_id: row.get(7)?,
+                    upvote_count_pod: row.get(8)?,
+                    tags,
+                    authors,
+                    reply_to,
+                    requested_post_id: row.get(12)?,
+                    title: row.get(13)?,
+                    deleted_at: row.get(14)?,
+                    content_type: row.get(15)?,
                 })
             })?
             .collect::<Result<Vec<_>, _>>()?;
@@ -489,18 +783,81 @@ impl Database {
         challenge_pod: &str,
         identity_pod: &str,
     ) -> Result<i64> {
-        let conn = self.conn.lock().unwrap();
+        let conn = self.conn();
         conn.execute(
             "INSERT INTO identity_servers (server_id, public_key, challenge_pod, identity_pod) VALUES (?1, ?2, ?3, ?4)",
             [server_id, public_key, challenge_pod, identity_pod],
         )?;
-        Ok(conn.last_insert_rowid())
+        let id = conn.last_insert_rowid();
+        conn.execute(
+            "INSERT INTO identity_server_keys (server_id, public_key) VALUES (?1, ?2)",
+            [server_id, public_key],
+        )?;
+        Ok(id)
+    }
+
+    /// Mark a registration challenge nonce as used. Returns false when the
+    /// nonce was already consumed, i.e. the challenge is being replayed.
+    pub fn consume_challenge_nonce(&self, nonce: &str) -> Result<bool> {
+        let conn = self.conn();
+        let inserted = conn.execute(
+            "INSERT OR IGNORE INTO consumed_challenge_nonces (nonce) VALUES (?1)",
+            [nonce],
+        )?;
+        Ok(inserted > 0)
+    }
+
+    /// Rotate an identity server to a new public key: close the current key's
+    /// validity window, record the new key, and make it the server's current
+    /// key. Returns false if the server is unknown or revoked.
+    pub fn rotate_identity_server_key(
+        &self,
+        server_id: &str,
+        new_public_key: &str,
+    ) -> Result<bool> {
+        let conn = self.conn();
+        let updated = conn.execute(
+            "UPDATE identity_servers SET public_key = ?1 WHERE server_id = ?2 AND revoked_at IS NULL",
+            [new_public_key, server_id],
+        )?;
+        if updated == 0 {
+            return Ok(false);
+        }
+        conn.execute(
+            "UPDATE identity_server_keys SET valid_until = CURRENT_TIMESTAMP WHERE server_id = ?1 AND valid_until IS NULL",
+            [server_id],
+        )?;
+        conn.execute(
+            "INSERT INTO identity_server_keys (server_id, public_key) VALUES (?1, ?2)",
+            [server_id, new_public_key],
+        )?;
+        Ok(true)
+    }
+
+    /// Full key history for an identity server, oldest first
+    pub fn get_identity_server_keys(&self, server_id: &str) -> Result<Vec<IdentityServerKey>> {
+        let conn = self.conn();
+        let mut stmt = conn.prepare(
+            "SELECT public_key, valid_from, valid_until FROM identity_server_keys WHERE server_id = ?1 ORDER BY id ASC",
+        )?;
+
+        let keys = stmt
+            .query_map([server_id], |row| {
+                Ok(IdentityServerKey {
+                    public_key: row.get(0)?,
+                    valid_from: row.get(1)?,
+                    valid_until: row.get(2)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(keys)
     }
 
     pub fn get_identity_server_by_id(&self, server_id: &str) -> Result<Option<IdentityServer>> {
-        let conn = self.conn.lock().unwrap();
+        let conn = self.conn();
         let mut stmt = conn.prepare(
-            "SELECT id, server_id, public_key, challenge_pod, identity_pod, created_at FROM identity_servers WHERE server_id = ?1",
+            "SELECT id, server_id, public_key, challenge_pod, identity_pod, created_at, revoked_at, base_url, last_seen_at, last_status, key_mismatch FROM identity_servers WHERE server_id = ?1",
         )?;
 
         let identity_server = stmt
@@ -512,6 +869,11 @@ impl Database {
                     challenge_pod: row.get(3)?,
                     identity_pod: row.get(4)?,
                     created_at: Some(row.get(5)?),
+                    revoked_at: row.get(6)?,
+                    base_url: row.get(7)?,
+                    last_seen_at: row.get(8)?,
+                    last_status: row.get(9)?,
+                    key_mismatch: row.get(10)?,
                 })
             })
             .optional()?;
@@ -519,13 +881,57 @@ impl Database {
         Ok(identity_server)
     }
 
+    /// Record the endpoint an identity server advertised at registration so
+    /// the health poller can reach it.
+    pub fn set_identity_server_base_url(&self, server_id: &str, base_url: &str) -> Result<()> {
+        let conn = self.conn();
+        conn.execute(
+            "UPDATE identity_servers SET base_url = ?1 WHERE server_id = ?2",
+            [base_url, server_id],
+        )?;
+        Ok(())
+    }
+
+    /// Store the outcome of a health poll. `last_seen_at` only advances when
+    /// the server actually responded, and the key-mismatch flag is left
+    /// untouched when the poll could not compare keys (server unreachable).
+    pub fn record_identity_server_health(
+        &self,
+        server_id: &str,
+        status: &str,
+        reachable: bool,
+        key_mismatch: Option<bool>,
+    ) -> Result<()> {
+        let conn = self.conn();
+        conn.execute(
+            "UPDATE identity_servers SET
+                last_status = ?2,
+                last_seen_at = CASE WHEN ?3 THEN CURRENT_TIMESTAMP ELSE last_seen_at END,
+                key_mismatch = COALESCE(?4, key_mismatch)
+            WHERE server_id = ?1",
+            rusqlite::params![server_id, status, reachable, key_mismatch],
+        )?;
+        Ok(())
+    }
+
+    /// Mark an identity server as revoked. Returns false if it does not exist
+    /// or was already revoked.
+    pub fn revoke_identity_server(&self, server_id: &str) -> Result<bool> {
+        let conn = self.conn();
+        let updated = conn.execute(
+            "UPDATE identity_servers SET revoked_at = CURRENT_TIMESTAMP WHERE server_id = ?1 AND revoked_at IS NULL",
+            [server_id],
+        )?;
+        Ok(updated > 0)
+    }
+
     pub fn get_identity_server_by_public_key(
         &self,
         public_key: &str,
     ) -> Result<Option<IdentityServer>> {
-        let conn = self.conn.lock().unwrap();
+        let conn = self.conn();
         let mut stmt = conn.prepare(
-            "SELECT id, server_id, public_key, challenge_pod, identity_pod, created_at FROM identity_servers WHERE public_key = ?1",
+            "SELECT id, server_id, public_key, challenge_pod, identity_pod, created_at, revoked_at, base_url, last_seen_at, last_status, key_mismatch FROM identity_servers WHERE public_key = ?1",
         )?;
 
         let identity_server = stmt
@@ -537,6 +943,11 @@ impl Database {
                     challenge_pod: row.get(3)?,
                     identity_pod: row.get(4)?,
                     created_at: Some(row.get(5)?),
+                    revoked_at: row.get(6)?,
+                    base_url: row.get(7)?,
+                    last_seen_at: row.get(8)?,
+                    last_status: row.get(9)?,
+                    key_mismatch: row.get(10)?,
                 })
             })
             .optional()?;
@@ -545,9 +956,9 @@ impl Database {
     }
 
     pub fn get_all_identity_servers(&self) -> Result<Vec<IdentityServer>> {
-        let conn = self.conn.lock().unwrap();
+        let conn = self.conn();
         let mut stmt = conn.prepare(
-            "SELECT id, server_id, public_key, challenge_pod, identity_pod, created_at FROM identity_servers ORDER BY created_at DESC",
+            "SELECT id, server_id, public_key, challenge_pod, identity_pod, created_at, revoked_at, base_url, last_seen_at, last_status, key_mismatch FROM identity_servers ORDER BY created_at DESC",
         )?;
 
         let identity_servers = stmt
@@ -559,6 +970,11 @@ impl Database {
                     challenge_pod: row.get(3)?,
                     identity_pod: row.get(4)?,
                     created_at: Some(row.get(5)?),
+                    revoked_at: row.get(6)?,
+                    base_url: row.get(7)?,
+                    last_seen_at: row.get(8)?,
+                    last_status: row.get(9)?,
+                    key_mismatch: row.get(10)?,
                 })
             })?
             .collect::<Result<Vec<_>, _>>()?;
@@ -568,7 +984,7 @@ impl Database {
 
     // Upvote methods
     pub fn create_upvote(&self, document_id: i64, username: &str, pod_json: &str) -> Result<i64> {
-        let conn = self.conn.lock().unwrap();
+        let conn = self.conn();
         conn.execute(
             "INSERT INTO upvotes (document_id, username, pod_json) VALUES (?1, ?2, ?3)",
             [&document_id.to_string(), username, pod_json],
@@ -577,7 +993,7 @@ impl Database {
     }
 
     pub fn get_upvote_count(&self, document_id: i64) -> Result<i64> {
-        let conn = self.conn.lock().unwrap();
+        let conn = self.conn();
         let count = conn.query_row(
             "SELECT COUNT(*) FROM upvotes WHERE document_id = ?1",
             [document_id],
@@ -586,10 +1002,43 @@ impl Database {
         Ok(count)
     }
 
+    /// Total number of upvotes across all documents. Folded into the list
+    /// ETag, since upvote counts (and the `top` ordering) change the list
+    /// body without touching any document row.
+    pub fn get_total_upvote_count(&self) -> Result<i64> {
+        let conn = self.conn();
+        let count = conn.query_row("SELECT COUNT(*) FROM upvotes", [], |row| row.get(0))?;
+        Ok(count)
+    }
+
+    // Batched upvote counts: one GROUP BY query per chunk of ids instead of
+    // one COUNT per document. Documents without upvotes are absent from the map.
+    pub fn get_upvote_counts(
+        &self,
+        document_ids: &[i64],
+    ) -> Result<std::collections::HashMap<i64, i64>> {
+        let conn = self.conn();
+        let mut counts = std::collections::HashMap::new();
+        for chunk in document_ids.chunks(SQL_PARAM_CHUNK) {
+            let placeholders = vec!["?"; chunk.len()].join(",");
+            let mut stmt = conn.prepare(&format!(
+                "SELECT document_id, COUNT(*) FROM upvotes WHERE document_id IN ({placeholders}) GROUP BY document_id"
+            ))?;
+            let rows = stmt.query_map(rusqlite::params_from_iter(chunk.iter()), |row| {
+                Ok((row.get::<_, i64>(0)?, row.get::<_, i64>(1)?))
+            })?;
+            for row in rows {
+                let (document_id, count) = row?;
+                counts.insert(document_id, count);
+            }
+        }
+        Ok(counts)
+    }
+
     pub fn get_upvotes_by_document_id(&self, document_id: i64) -> Result<Vec<Upvote>> {
-        let conn = self.conn.lock().unwrap();
+        let conn = self.conn();
         let mut stmt = conn.prepare(
-            "SELECT id, document_id, username, pod_json, created_at FROM upvotes WHERE document_id = ?1",
+            "SELECT id, document_id, username, pod_json, created_at FROM upvotes WHERE document_id = ?1 ORDER BY id",
         )?;
 
         let upvotes = stmt
@@ -607,131 +1056,656 @@ impl Database {
         Ok(upvotes)
     }
 
-    // Helper method to convert RawDocument to DocumentMetadata (without PODs)
-    pub fn raw_document_to_metadata(&self, raw_doc: RawDocument) -> Result<DocumentMetadata> {
-        // Get upvote count
-        let upvote_count = raw_doc
-            .id
-            .map(|id| self.get_upvote_count(id).unwrap_or(0))
-            .unwrap_or(0);
-
-        let content_id = Hash::from_hex(raw_doc.content_id).map_err(|_| {
-            rusqlite::Error::InvalidColumnType(
-                0,
-                "content".to_string(),
-                rusqlite::types::Type::Text,
-            )
-        })?;
+    // Upvote count job queue methods
 
-        Ok(DocumentMetadata {
-            id: raw_doc.id,
-            content_id,
-            post_id: raw_doc.post_id,
-            revision: raw_doc.revision,
-            created_at: raw_doc.created_at,
-            uploader_id: raw_doc.uploader_id,
-            upvote_count,
-            tags: raw_doc.tags,
-            authors: raw_doc.authors,
-            reply_to: raw_doc.reply_to,
-            requested_post_id: raw_doc.requested_post_id,
-            title: raw_doc.title,
-        })
+    /// Queue a recount of a document's upvote count pod. Returns `false` when
+    /// a pending job for the document already exists (the recount coalesces
+    /// into it).
+    pub fn enqueue_upvote_recount(&self, document_id: i64) -> Result<bool> {
+        let conn = self.conn();
+        let inserted = conn.execute(
+            "INSERT OR IGNORE INTO upvote_count_jobs (document_id) VALUES (?1)",
+            [document_id],
+        )?;
+        Ok(inserted > 0)
     }
 
-    // Helper method to convert RawDocument to DocumentPods
-    pub fn raw_document_to_pods(&self, raw_doc: RawDocument) -> Result<DocumentPods> {
-        let document_id = raw_doc.id.ok_or_else(|| {
-            rusqlite::Error::InvalidColumnType(0, "id".to_string(), rusqlite::types::Type::Integer)
-        })?;
-
-        // Create lazy pod wrappers instead of deserializing immediately
-        let pod = LazyDeser::from_json_string(raw_doc.pod).map_err(|_| {
-            rusqlite::Error::InvalidColumnType(0, "pod".to_string(), rusqlite::types::Type::Text)
-        })?;
-        let timestamp_pod = LazyDeser::from_json_string(raw_doc.timestamp_pod).map_err(|_| {
-            rusqlite::Error::InvalidColumnType(
-                0,
-                "timestamp_pod".to_string(),
-                rusqlite::types::Type::Text,
+    /// Claim the oldest runnable pending job, marking it running and counting
+    /// the attempt. Jobs whose `not_before` lies in the future (retry backoff)
+    /// are skipped.
+    pub fn claim_next_upvote_recount_job(&self) -> Result<Option<UpvoteCountJob>> {
+        let conn = self.conn();
+        let job = conn
+            .query_row(
+                "SELECT id, document_id, requested_at, status, attempts, not_before, last_error, completed_at
+                 FROM upvote_count_jobs
+                 WHERE status = 'pending' AND (not_before IS NULL OR not_before <= CURRENT_TIMESTAMP)
+                 ORDER BY id LIMIT 1",
+                [],
+                Self::row_to_upvote_count_job,
             )
-        })?;
-
-        // For optional MainPod, we need to create the JSON for Option<MainPod>
-        let upvote_count_pod_json =
-            serde_json::to_string(&raw_doc.upvote_count_pod).map_err(|_| {
-                rusqlite::Error::InvalidColumnType(
-                    0,
-                    "upvote_count_pod".to_string(),
-                    rusqlite::types::Type::Text,
-                )
-            })?;
-        let upvote_count_pod =
-            LazyDeser::from_json_string(upvote_count_pod_json).map_err(|_| {
-                rusqlite::Error::InvalidColumnType(
-                    0,
-                    "upvote_count_pod".to_string(),
-                    rusqlite::types::Type::Text,
-                )
-            })?;
+            .optional()?;
 
-        Ok(DocumentPods {
-            document_id,
-            pod,
-            timestamp_pod,
-            upvote_count_pod,
-        })
+        let Some(mut job) = job else {
+            return Ok(None);
+        };
+        conn.execute(
+            "UPDATE upvote_count_jobs SET status = 'running', attempts = attempts + 1 WHERE id = ?1",
+            [job.id],
+        )?;
+        job.status = "running".to_string();
+        job.attempts += 1;
+        Ok(Some(job))
     }
 
-    // Get document metadata only (no content)
-    pub fn get_document_metadata(&self, id: i64) -> Result<Option<DocumentMetadata>> {
-        match self.get_raw_document(id)? {
-            Some(raw_doc) => Ok(Some(self.raw_document_to_metadata(raw_doc)?)),
-            None => Ok(None),
-        }
+    pub fn complete_upvote_recount_job(&self, job_id: i64) -> Result<()> {
+        let conn = self.conn();
+        conn.execute(
+            "UPDATE upvote_count_jobs SET status = 'done', completed_at = CURRENT_TIMESTAMP WHERE id = ?1",
+            [job_id],
+        )?;
+        Ok(())
     }
 
-    // Get document with content from storage
-    pub fn get_document(
+    /// Record a failed attempt. With `retry_in_secs` the job returns to the
+    /// pending state after the backoff delay — unless a newer pending job for
+    /// the same document has been queued in the meantime, in which case this
+    /// one is superseded. Without a retry delay the job is failed for good.
+    pub fn fail_upvote_recount_job(
         &self,
-        id: i64,
-        storage: &crate::storage::ContentAddressedStorage,
-    ) -> Result<Option<Document>> {
+        job_id: i64,
+        error: &str,
+        retry_in_secs: Option<i64>,
+    ) -> Result<()> {
+        let conn = self.conn();
+        if let Some(secs) = retry_in_secs {
+            let superseded: bool = conn.query_row(
+                "SELECT EXISTS(
+                    SELECT 1 FROM upvote_count_jobs
+                    WHERE status = 'pending'
+                      AND document_id = (SELECT document_id FROM upvote_count_jobs WHERE id = ?1)
+                 )",
+                [job_id],
+                |row| row.get(0),
+            )?;
+            if !superseded {
+                conn.execute(
+                    "UPDATE upvote_count_jobs
+                     SET status = 'pending', last_error = ?2,
+                         not_before = datetime('now', '+' || ?3 || ' seconds')
+                     WHERE id = ?1",
+                    [&job_id.to_string(), error, &secs.to_string()],
+                )?;
+                return Ok(());
+            }
+            conn.execute(
+                "UPDATE upvote_count_jobs
+                 SET status = 'superseded', last_error = ?2, completed_at = CURRENT_TIMESTAMP
+                 WHERE id = ?1",
+                [&job_id.to_string(), error],
+            )?;
+            return Ok(());
+        }
+        conn.execute(
+            "UPDATE upvote_count_jobs
+             SET status = 'failed', last_error = ?2, completed_at = CURRENT_TIMESTAMP
+             WHERE id = ?1",
+            [&job_id.to_string(), error],
+        )?;
+        Ok(())
+    }
+
+    /// Most recent jobs first, for the admin queue view.
+    pub fn get_upvote_recount_jobs(&self, limit: i64) -> Result<Vec<UpvoteCountJob>> {
+        let conn = self.conn();
+        let mut stmt = conn.prepare(
+            "SELECT id, document_id, requested_at, status, attempts, not_before, last_error, completed_at
+             FROM upvote_count_jobs ORDER BY id DESC LIMIT ?1",
+        )?;
+        let jobs = stmt
+            .query_map([limit], Self::row_to_upvote_count_job)?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(jobs)
+    }
+
+    fn row_to_upvote_count_job(row: &rusqlite::Row<'_>) -> Result<UpvoteCountJob> {
+        Ok(UpvoteCountJob {
+            id: row.get(0)?,
+            document_id: row.get(1)?,
+            requested_at: row.get(2)?,
+            status: row.get(3)?,
+            attempts: row.get(4)?,
+            not_before: row.get(5)?,
+            last_error: row.get(6)?,
+            completed_at: row.get(7)?,
+        })
+    }
+
+    // Notification methods
+
+    // Insert a notification for the uploader of the document being replied to.
+    // Users are not notified about their own replies.
+    fn insert_reply_notification(
+        conn: &Connection,
+        reply_to: &ReplyReference,
+        replier_username: &str,
+        reply_document_id: i64,
+    ) -> Result<()> {
+        let parent_uploader: Option<String> = conn
+            .query_row(
+                "SELECT uploader_id FROM documents WHERE id = ?1",
+                [reply_to.document_id],
+                |row| row.get(0),
+            )
+            .optional()?;
+
+        if let Some(parent_uploader) = parent_uploader
+            && parent_uploader != replier_username
+        {
+            conn.execute(
+                "INSERT INTO notifications (username, document_id, actor_username) VALUES (?1, ?2, ?3)",
+                rusqlite::params![parent_uploader, reply_document_id, replier_username],
+            )?;
+        }
+
+        Ok(())
+    }
+
+    // Notify every subscriber of the thread about a new reply, skipping the
+    // replier themselves and anyone who already holds a notification for this
+    // reply (the parent uploader, notified by `insert_reply_notification`).
+    fn insert_subscription_notifications(
+        conn: &Connection,
+        thread_root_id: i64,
+        replier_username: &str,
+        reply_document_id: i64,
+    ) -> Result<()> {
+        conn.execute(
+            "INSERT INTO notifications (username, document_id, actor_username)
+             SELECT username, ?2, ?3 FROM thread_subscriptions
+             WHERE thread_root_id = ?1 AND username != ?3
+               AND username NOT IN (SELECT username FROM notifications WHERE document_id = ?2)",
+            rusqlite::params![thread_root_id, reply_document_id, replier_username],
+        )?;
+        Ok(())
+    }
+
+    fn insert_thread_subscription(
+        conn: &Connection,
+        username: &str,
+        thread_root_id: i64,
+    ) -> Result<bool> {
+        let rows = conn.execute(
+            "INSERT OR IGNORE INTO thread_subscriptions (username, thread_root_id) VALUES (?1, ?2)",
+            rusqlite::params![username, thread_root_id],
+        )?;
+        Ok(rows > 0)
+    }
+
+    /// Subscribe a user to a thread. Returns false when the subscription
+    /// already existed.
+    pub fn subscribe_to_thread(&self, username: &str, thread_root_id: i64) -> Result<bool> {
+        let conn = self.conn();
+        Self::insert_thread_subscription(&conn, username, thread_root_id)
+    }
+
+    /// Remove a user's thread subscription. Returns false when there was none.
+    pub fn unsubscribe_from_thread(&self, username: &str, thread_root_id: i64) -> Result<bool> {
+        let conn = self.conn();
+        let rows = conn.execute(
+            "DELETE FROM thread_subscriptions WHERE username = ?1 AND thread_root_id = ?2",
+            rusqlite::params![username, thread_root_id],
+        )?;
+        Ok(rows > 0)
+    }
+
+    pub fn get_thread_subscribers(&self, thread_root_id: i64) -> Result<Vec<String>> {
+        let conn = self.conn();
+        let mut stmt = conn.prepare(
+            "SELECT username FROM thread_subscriptions WHERE thread_root_id = ?1 ORDER BY username",
+        )?;
+        let subscribers = stmt
+            .query_map([thread_root_id], |row| row.get(0))?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(subscribers)
+    }
+
+    /// Record a newly issued API token; only the token's hash is stored
+    pub fn create_token(&self, token_hash: &str, username: &str) -> Result<i64> {
+        let conn = self.conn();
+        conn.execute(
+            "INSERT INTO tokens (token_hash, username) VALUES (?1, ?2)",
+            rusqlite::params![token_hash, username],
+        )?;
+        Ok(conn.last_insert_rowid())
+    }
+
+    /// Resolve an API token hash to its username, ignoring revoked tokens
+    pub fn get_token_username(&self, token_hash: &str) -> Result<Option<String>> {
+        let conn = self.conn();
+        conn.query_row(
+            "SELECT username FROM tokens WHERE token_hash = ?1 AND revoked_at IS NULL",
+            [token_hash],
+            |row| row.get(0),
+        )
+        .optional()
+    }
+
+    /// Revoke an API token. Returns false when the token was unknown or
+    /// already revoked.
+    pub fn revoke_token(&self, token_hash: &str) -> Result<bool> {
+        let conn = self.conn();
+        let rows = conn.execute(
+            "UPDATE tokens SET revoked_at = CURRENT_TIMESTAMP
+             WHERE token_hash = ?1 AND revoked_at IS NULL",
+            [token_hash],
+        )?;
+        Ok(rows > 0)
+    }
+
+    /// Resolve the thread root of any document in a thread
+    pub fn get_thread_root_id(&self, document_id: i64) -> Result<Option<i64>> {
+        let conn = self.conn();
+        conn.query_row(
+            "SELECT thread_root_id FROM documents WHERE id = ?1",
+            [document_id],
+            |row| row.get(0),
+        )
+        .optional()
+    }
+
+    pub fn get_notifications(&self, username: &str, unread_only: bool) -> Result<Vec<Notification>> {
+        let conn = self.conn();
+        let sql = if unread_only {
+            "SELECT id, username, document_id, actor_username, read, created_at
+             FROM notifications WHERE username = ?1 AND read = 0 ORDER BY created_at DESC"
+        } else {
+            "SELECT id, username, document_id, actor_username, read, created_at
+             FROM notifications WHERE username = ?1 ORDER BY created_at DESC"
+        };
+        let mut stmt = conn.prepare(sql)?;
+
+        let notifications = stmt
+            .query_map([username], |row| {
+                Ok(Notification {
+                    id: Some(row.get(0)?),
+                    username: row.get(1)?,
+                    document_id: row.get(2)?,
+                    actor_username: row.get(3)?,
+                    read: row.get::<_, i64>(4)? != 0,
+                    created_at: Some(row.get(5)?),
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(notifications)
+    }
+
+    pub fn mark_notification_read(&self, id: i64) -> Result<()> {
+        let conn = self.conn();
+        conn.execute("UPDATE notifications SET read = 1 WHERE id = ?1", [id])?;
+        Ok(())
+    }
+
+    // Moderation flag methods
+    pub fn user_has_flagged(&self, document_id: i64, username: &str) -> Result<bool> {
+        let conn = self.conn();
+        let count: i64 = conn.query_row(
+            "SELECT COUNT(*) FROM document_flags WHERE document_id = ?1 AND username = ?2",
+            rusqlite::params![document_id, username],
+            |row| row.get(0),
+        )?;
+        Ok(count > 0)
+    }
+
+    pub fn create_flag(&self, document_id: i64, username: &str, reason: &str) -> Result<i64> {
+        let conn = self.conn();
+        conn.execute(
+            "INSERT INTO document_flags (document_id, username, reason) VALUES (?1, ?2, ?3)",
+            rusqlite::params![document_id, username, reason],
+        )?;
+        Ok(conn.last_insert_rowid())
+    }
+
+    pub fn get_flag(&self, flag_id: i64) -> Result<Option<DocumentFlag>> {
+        let conn = self.conn();
+        let flag = conn
+            .query_row(
+                "SELECT id, document_id, username, reason, created_at, resolved_at, resolution
+                 FROM document_flags WHERE id = ?1",
+                [flag_id],
+                |row| {
+                    Ok(DocumentFlag {
+                        id: Some(row.get(0)?),
+                        document_id: row.get(1)?,
+                        username: row.get(2)?,
+                        reason: row.get(3)?,
+                        created_at: row.get(4)?,
+                        resolved_at: row.get(5)?,
+                        resolution: row.get(6)?,
+                    })
+                },
+            )
+            .optional()?;
+        Ok(flag)
+    }
+
+    /// Number of flags on a document that no admin has resolved yet
+    pub fn count_unresolved_flags(&self, document_id: i64) -> Result<i64> {
+        let conn = self.conn();
+        let count = conn.query_row(
+            "SELECT COUNT(*) FROM document_flags WHERE document_id = ?1 AND resolved_at IS NULL",
+            [document_id],
+            |row| row.get(0),
+        )?;
+        Ok(count)
+    }
+
+    /// Documents with open flags, with per-document counts and reasons
+    pub fn get_open_flagged_documents(&self) -> Result<Vec<FlaggedDocument>> {
+        let conn = self.conn();
+        let mut stmt = conn.prepare(
+            "SELECT f.document_id, d.title, f.reason
+             FROM document_flags f
+             JOIN documents d ON d.id = f.document_id
+             WHERE f.resolved_at IS NULL
+             ORDER BY f.document_id ASC, f.id ASC",
+        )?;
+        let rows: Vec<(i64, String, String)> = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut result: Vec<FlaggedDocument> = Vec::new();
+        for (document_id, title, reason) in rows {
+            match result.last_mut() {
+                Some(entry) if entry.document_id == document_id => {
+                    entry.flag_count += 1;
+                    entry.reasons.push(reason);
+                }
+                _ => result.push(FlaggedDocument {
+                    document_id,
+                    title,
+                    flag_count: 1,
+                    reasons: vec![reason],
+                }),
+            }
+        }
+        Ok(result)
+    }
+
+    /// Mark a flag resolved. Returns false if the flag was already resolved
+    /// (or does not exist).
+    pub fn resolve_flag(&self, flag_id: i64, resolution: &str) -> Result<bool> {
+        let conn = self.conn();
+        let updated = conn.execute(
+            "UPDATE document_flags SET resolved_at = CURRENT_TIMESTAMP, resolution = ?2
+             WHERE id = ?1 AND resolved_at IS NULL",
+            rusqlite::params![flag_id, resolution],
+        )?;
+        Ok(updated > 0)
+    }
+
+    pub fn set_document_hidden(&self, document_id: i64, hidden: bool) -> Result<()> {
+        let conn = self.conn();
+        conn.execute(
+            "UPDATE documents SET hidden = ?2 WHERE id = ?1",
+            rusqlite::params![document_id, hidden as i64],
+        )?;
+        Ok(())
+    }
+
+    pub fn document_is_hidden(&self, document_id: i64) -> Result<bool> {
+        let conn = self.conn();
+        let hidden: Option<i64> = conn
+            .query_row(
+                "SELECT hidden FROM documents WHERE id = ?1",
+                [document_id],
+                |row| row.get(0),
+            )
+            .optional()?;
+        Ok(hidden.unwrap_or(0) != 0)
+    }
+
+    // Batched hidden lookup companion to get_upvote_counts, for converting
+    // whole document lists without a per-row query.
+    pub fn get_hidden_document_ids(&self, document_ids: &[i64]) -> Result<HashSet<i64>> {
+        let conn = self.conn();
+        let mut hidden_ids = HashSet::new();
+        for chunk in document_ids.chunks(SQL_PARAM_CHUNK) {
+            let placeholders = vec!["?"; chunk.len()].join(",");
+            let mut stmt = conn.prepare(&format!(
+                "SELECT id FROM documents WHERE hidden != 0 AND id IN ({placeholders})"
+            ))?;
+            let rows = stmt.query_map(rusqlite::params_from_iter(chunk.iter()), |row| {
+                row.get::<_, i64>(0)
+            })?;
+            for row in rows {
+                hidden_ids.insert(row?);
+            }
+        }
+        Ok(hidden_ids)
+    }
+
+    pub fn record_moderation_action(
+        &self,
+        flag_id: i64,
+        document_id: i64,
+        action: &str,
+        admin_token_id: &str,
+    ) -> Result<i64> {
+        let conn = self.conn();
+        conn.execute(
+            "INSERT INTO moderation_audit (flag_id, document_id, action, admin_token_id)
+             VALUES (?1, ?2, ?3, ?4)",
+            rusqlite::params![flag_id, document_id, action, admin_token_id],
+        )?;
+        Ok(conn.last_insert_rowid())
+    }
+
+    pub fn get_moderation_audit_for_document(
+        &self,
+        document_id: i64,
+    ) -> Result<Vec<ModerationAuditEntry>> {
+        let conn = self.conn();
+        let mut stmt = conn.prepare(
+            "SELECT id, flag_id, document_id, action, admin_token_id, created_at
+             FROM moderation_audit WHERE document_id = ?1 ORDER BY id ASC",
+        )?;
+        let entries = stmt
+            .query_map([document_id], |row| {
+                Ok(ModerationAuditEntry {
+                    id: row.get(0)?,
+                    flag_id: row.get(1)?,
+                    document_id: row.get(2)?,
+                    action: row.get(3)?,
+                    admin_token_id: row.get(4)?,
+                    created_at: row.get(5)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(entries)
+    }
+
+    // Helper method to convert RawDocument to DocumentMetadata (without PODs)
+    pub fn raw_document_to_metadata(&self, raw_doc: RawDocument) -> Result<DocumentMetadata> {
+        // Get upvote count
+        let upvote_count = raw_doc
+            .id
+            .map(|id| self.get_upvote_count(id).unwrap_or(0))
+            .unwrap_or(0);
+
+        let hidden = raw_doc
+            .id
+            .map(|id| self.document_is_hidden(id).unwrap_or(false))
+            .unwrap_or(false);
+
+        let mut metadata = Self::metadata_from_parts(raw_doc, upvote_count, hidden)?;
+        self.fill_display_name(&mut metadata)?;
+        Ok(metadata)
+    }
+
+    // Attach the uploader's current display name when a rename is recorded
+    // in the alias table
+    fn fill_display_name(&self, metadata: &mut DocumentMetadata) -> Result<()> {
+        let display = self.resolve_display_name(&metadata.uploader_id)?;
+        if display != metadata.uploader_id {
+            metadata.display_name = Some(display);
+        }
+        Ok(())
+    }
+
+    // Convert a whole batch of raw documents, resolving upvote counts and
+    // hidden flags with a bounded number of queries instead of two per row.
+    pub fn raw_documents_to_metadata(
+        &self,
+        raw_docs: Vec<RawDocument>,
+    ) -> Result<Vec<DocumentMetadata>> {
+        let ids: Vec<i64> = raw_docs.iter().filter_map(|d| d.id).collect();
+        let upvote_counts = self.get_upvote_counts(&ids)?;
+        let hidden_ids = self.get_hidden_document_ids(&ids)?;
+
+        // Resolve all distinct uploaders' display names in a single query
+        let uploaders: HashSet<String> =
+            raw_docs.iter().map(|d| d.uploader_id.clone()).collect();
+        let display_names = self.resolve_display_names(&uploaders)?;
+
+        raw_docs
+            .into_iter()
+            .map(|raw_doc| {
+                let upvote_count = raw_doc
+                    .id
+                    .and_then(|id| upvote_counts.get(&id).copied())
+                    .unwrap_or(0);
+                let hidden = raw_doc.id.is_some_and(|id| hidden_ids.contains(&id));
+                let mut metadata = Self::metadata_from_parts(raw_doc, upvote_count, hidden)?;
+                metadata.display_name = display_names
+                    .get(&metadata.uploader_id)
+                    .filter(|display| **display != metadata.uploader_id)
+                    .cloned();
+                Ok(metadata)
+            })
+            .collect()
+    }
+
+    fn metadata_from_parts(
+        raw_doc: RawDocument,
+        upvote_count: i64,
+        hidden: bool,
+    ) -> Result<DocumentMetadata> {
+        let content_id = Hash::from_hex(raw_doc.content_id).map_err(|_| {
+            rusqlite::Error::InvalidColumnType(
+                0,
+                "content".to_string(),
+                rusqlite::types::Type::Text,
+            )
+        })?;
+
+        Ok(DocumentMetadata {
+            id: raw_doc.id,
+            content_id,
+            post_id: raw_doc.post_id,
+            revision: raw_doc.revision,
+            created_at: raw_doc.created_at,
+            uploader_id: raw_doc.uploader_id,
+            upvote_count,
+            tags: raw_doc.tags,
+            authors: raw_doc.authors,
+            reply_to: raw_doc.reply_to,
+            requested_post_id: raw_doc.requested_post_id,
+            title: raw_doc.title,
+            hidden,
+            display_name: None,
+            deleted_at: raw_doc.deleted_at,
+            content_type: raw_doc.content_type,
+        })
+    }
+
+    // Helper method to convert RawDocument to DocumentPods
+    pub fn raw_document_to_pods(&self, raw_doc: RawDocument) -> Result<DocumentPods> {
+        let document_id = raw_doc.id.ok_or_else(|| {
+            rusqlite::Error::InvalidColumnType(0, "id".to_string(), rusqlite::types::Type::Integer)
+        })?;
+
+        // Create lazy pod wrappers instead of deserializing immediately
+        let pod = LazyDeser::from_json_string(raw_doc.pod).map_err(|_| {
+            rusqlite::Error::InvalidColumnType(0, "pod".to_string(), rusqlite::types::Type::Text)
+        })?;
+        let timestamp_pod = LazyDeser::from_json_string(raw_doc.timestamp_pod).map_err(|_| {
+            rusqlite::Error::InvalidColumnType(
+                0,
+                "timestamp_pod".to_string(),
+                rusqlite::types::Type::Text,
+            )
+        })?;
+
+        // For optional MainPod, we need to create the JSON for Option<MainPod>
+        let upvote_count_pod_json =
+            serde_json::to_string(&raw_doc.upvote_count_pod).map_err(|_| {
+                rusqlite::Error::InvalidColumnType(
+                    0,
+                    "upvote_count_pod".to_string(),
+                    rusqlite::types::Type::Text,
+                )
+            })?;
+        let upvote_count_pod =
+            LazyDeser::from_json_string(upvote_count_pod_json).map_err(|_| {
+                rusqlite::Error::InvalidColumnType(
+                    0,
+                    "upvote_count_pod".to_string(),
+                    rusqlite::types::Type::Text,
+                )
+            })?;
+
+        Ok(DocumentPods {
+            document_id,
+            pod,
+            timestamp_pod,
+            upvote_count_pod,
+        })
+    }
+
+    // Get document metadata only (no content)
+    pub fn get_document_metadata(&self, id: i64) -> Result<Option<DocumentMetadata>> {
+        match self.get_raw_document(id)? {
+            Some(raw_doc) => Ok(Some(self.raw_document_to_metadata(raw_doc)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Look up a document by the hash of its content. Content ids are not
+    /// unique across documents (the same content can be re-published), so the
+    /// earliest surviving document wins to keep deep links stable.
+    pub fn get_document_by_content_id(
+        &self,
+        content_id: &Hash,
+    ) -> Result<Option<DocumentMetadata>> {
+        let content_id_string: String = content_id.encode_hex();
+        let id: Option<i64> = {
+            let conn = self.conn();
+            conn.query_row(
+                "SELECT id FROM documents
+                 WHERE content_id = ?1 AND deleted_at IS NULL
+                 ORDER BY id LIMIT 1",
+                [content_id_string],
+                |row| row.get(0),
+            )
+            .optional()?
+        };
+        match id {
+            Some(id) => self.get_document_metadata(id),
+            None => Ok(None),
+        }
+    }
+
+    // Get document metadata and pods without touching content storage. The
+    // content id lives in the returned metadata; callers fetch the content
+    // from storage themselves, off the database lock.
+    pub fn get_document_record(&self, id: i64) -> Result<Option<(DocumentMetadata, DocumentPods)>> {
         match self.get_raw_document(id)? {
             Some(raw_doc) => {
                 let metadata = self.raw_document_to_metadata(raw_doc.clone())?;
-                let pods = self.raw_document_to_pods(raw_doc.clone())?;
-                let content_hash = Hash::from_hex(raw_doc.content_id).map_err(|_| {
-                    rusqlite::Error::InvalidColumnType(
-                        0,
-                        "content_id".to_string(),
-                        rusqlite::types::Type::Text,
-                    )
-                })?;
-
-                // Retrieve content from storage
-                let content = storage
-                    .retrieve_document_content(&content_hash)
-                    .map_err(|_| {
-                        rusqlite::Error::InvalidColumnType(
-                            0,
-                            "content".to_string(),
-                            rusqlite::types::Type::Text,
-                        )
-                    })?
-                    .ok_or_else(|| {
-                        rusqlite::Error::InvalidColumnType(
-                            0,
-                            "content".to_string(),
-                            rusqlite::types::Type::Text,
-                        )
-                    })?;
-
-                Ok(Some(Document {
-                    metadata,
-                    pods,
-                    content,
-                }))
+                let pods = self.raw_document_to_pods(raw_doc)?;
+                Ok(Some((metadata, pods)))
             }
             None => Ok(None),
         }
@@ -740,17 +1714,34 @@ impl Database {
     // Get all documents metadata only
     pub fn get_all_documents_metadata(&self) -> Result<Vec<DocumentMetadata>> {
         let raw_documents = self.get_all_documents()?;
-        let mut documents_metadata = Vec::new();
-
-        for raw_doc in raw_documents {
-            documents_metadata.push(self.raw_document_to_metadata(raw_doc)?);
-        }
-
-        Ok(documents_metadata)
+        self.raw_documents_to_metadata(raw_documents)
     }
 
     // Get top-level documents with latest reply information for list views
-    pub fn get_top_level_documents_with_latest_reply(&self) -> Result<Vec<DocumentListItem>> {
+    /// Total thread roots the document list pages over, using the same filter
+    /// as [`Self::get_top_level_documents_with_latest_reply`]
+    pub fn get_top_level_document_count(&self) -> Result<i64> {
+        let conn = self.conn();
+        conn.query_row(
+            "SELECT COUNT(*) FROM posts p
+             JOIN documents d ON d.post_id = p.id AND d.revision = (
+                SELECT MAX(x.revision) FROM documents x WHERE x.post_id = p.id AND (x.reply_to IS NULL)
+             )
+             WHERE p.parent_post_id IS NULL AND d.hidden = 0 AND d.deleted_at IS NULL",
+            [],
+            |row| row.get(0),
+        )
+    }
+
+    /// One page of the document list. A negative `limit` disables paging
+    /// (SQLite treats `LIMIT -1` as unbounded).
+    pub fn get_top_level_documents_with_latest_reply(
+        &self,
+        sort: DocumentSort,
+        upvote_window_days: Option<i64>,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<DocumentListItem>> {
         // Query latest document per root post, capturing both new-model (post-based) and old-model (doc-based) latest reply
         type Row = (
             RawDocument,
@@ -758,92 +1749,139 @@ impl Database {
             Option<String>,
             Option<String>,
             Option<String>,
+            bool,
         );
 
-        let rows: Vec<Row> = {
-            let conn = self.conn.lock().unwrap();
-            let mut stmt = conn.prepare(
-                "SELECT 
-                    d.id, d.content_id, d.post_id, d.revision, d.created_at, d.pod, d.timestamp_pod,
-                    d.uploader_id, d.upvote_count_pod, d.tags, d.authors, d.reply_to, d.requested_post_id, d.title,
-                    -- New-model latest reply across descendant posts in this thread
-                    (
-                        SELECT MAX(r.created_at) FROM documents r
-                        WHERE r.post_id IN (
-                            SELECT c.id FROM posts c WHERE c.thread_root_post_id = p.id AND c.parent_post_id IS NOT NULL
-                        )
-                    ) AS latest_reply_at_new,
-                    (
-                        SELECT r.uploader_id FROM documents r
-                        WHERE r.post_id IN (
-                            SELECT c.id FROM posts c WHERE c.thread_root_post_id = p.id AND c.parent_post_id IS NOT NULL
-                        )
-                        ORDER BY r.created_at DESC LIMIT 1
-                    ) AS latest_reply_by_new,
-                    -- Old-model latest reply within the same post using document-level reply_to
-                    (
-                        SELECT MAX(rr.created_at) FROM documents rr WHERE rr.post_id = p.id AND rr.reply_to IS NOT NULL
-                    ) AS latest_reply_at_old,
-                    (
-                        SELECT rr.uploader_id FROM documents rr WHERE rr.post_id = p.id AND rr.reply_to IS NOT NULL
-                        ORDER BY rr.created_at DESC LIMIT 1
-                    ) AS latest_reply_by_old
-                 FROM posts p
-                 JOIN documents d ON d.post_id = p.id AND d.revision = (
-                    SELECT MAX(x.revision) FROM documents x WHERE x.post_id = p.id AND (x.reply_to IS NULL)
-                 )
-                 WHERE p.parent_post_id IS NULL
-                 ORDER BY d.created_at DESC",
-            )?;
+        fn map_row(row: &rusqlite::Row) -> rusqlite::Result<Row> {
+            // Parse fields for latest root document
+            let tags_json: String = row.get(9)?;
+            let tags: HashSet<String> = serde_json::from_str(&tags_json).unwrap_or_default();
+            let authors_json: String = row.get(10)?;
+            let authors: HashSet<String> = serde_json::from_str(&authors_json).unwrap_or_default();
+            let reply_to_json: Option<String> = row.get(11)?;
+            let reply_to: Option<ReplyReference> =
+                reply_to_json.and_then(|json| serde_json::from_str(&json).ok());
+
+            let raw_doc = RawDocument {
+                id: Some(row.get(0)?),
+                content_id: row.get(1)?,
+                post_id: row.get(2)?,
+                revision: row.get(3)?,
+                created_at: Some(row.get(4)?),
+                pod: row.get(5)?,
+                timestamp_pod: row.get(6)?,
+                uploader_id: row.get(7)?,
+                upvote_count_pod: row.get(8)?,
+                tags,
+                authors,
+                reply_to,
+                requested_post_id: row.get(12)?,
+                title: row.get(13)?,
+                deleted_at: row.get(14)?,
+                content_type: row.get(15)?,
+            };
 
-            stmt.query_map([], |row| {
-                // Parse fields for latest root document
-                let tags_json: String = row.get(9)?;
-                let tags: HashSet<String> = serde_json::from_str(&tags_json).unwrap_or_default();
-                let authors_json: String = row.get(10)?;
-                let authors: HashSet<String> =
-                    serde_json::from_str(&authors_json).unwrap_or_default();
-                let reply_to_json: Option<String> = row.get(11)?;
-                let reply_to: Option<ReplyReference> =
-                    reply_to_json.and_then(|json| serde_json::from_str(&json).ok());
+            let latest_reply_at_new: Option<String> = row.get(16)?;
+            let latest_reply_by_new: Option<String> = row.get(17)?;
+            let latest_reply_at_old: Option<String> = row.get(18)?;
+            let latest_reply_by_old: Option<String> = row.get(19)?;
+            let pinned_at: Option<String> = row.get(20)?;
+
+            Ok((
+                raw_doc,
+                latest_reply_at_new,
+                latest_reply_by_new,
+                latest_reply_at_old,
+                latest_reply_by_old,
+                pinned_at.is_some(),
+            ))
+        }
 
-                let raw_doc = RawDocument {
-                    id: Some(row.get(0)?),
-                    content_id: row.get(1)?,
-                    post_id: row.get(2)?,
-                    revision: row.get(3)?,
-                    created_at: Some(row.get(4)?),
-                    pod: row.get(5)?,
-                    timestamp_pod: row.get(6)?,
-                    uploader_id: row.get(7)?,
-                    upvote_count_pod: row.get(8)?,
-                    tags,
-                    authors,
-                    reply_to,
-                    requested_post_id: row.get(12)?,
-                    title: row.get(13)?,
-                };
-
-                let latest_reply_at_new: Option<String> = row.get(14)?;
-                let latest_reply_by_new: Option<String> = row.get(15)?;
-                let latest_reply_at_old: Option<String> = row.get(16)?;
-                let latest_reply_by_old: Option<String> = row.get(17)?;
-
-                Ok((
-                    raw_doc,
-                    latest_reply_at_new,
-                    latest_reply_by_new,
-                    latest_reply_at_old,
-                    latest_reply_by_old,
-                ))
-            })?
-            .collect::<Result<Vec<_>, _>>()?
+        // Ordering happens in SQL so it stays correct once the list is
+        // paginated. `top` joins the (optionally windowed) upvote aggregate;
+        // `active` orders by the newer of the two latest-reply models, falling
+        // back to the document's own creation time for threads with no replies.
+        let (upvote_join, order_by) = match sort {
+            DocumentSort::New => ("", "d.created_at DESC"),
+            DocumentSort::Top => (
+                "LEFT JOIN (
+                    SELECT document_id, COUNT(*) AS upvote_count FROM upvotes
+                    WHERE ?1 IS NULL OR created_at >= datetime('now', '-' || ?1 || ' days')
+                    GROUP BY document_id
+                 ) u ON u.document_id = d.id",
+                "COALESCE(u.upvote_count, 0) DESC, d.created_at DESC",
+            ),
+            DocumentSort::Active => (
+                "",
+                "COALESCE(NULLIF(MAX(COALESCE(latest_reply_at_new, ''), COALESCE(latest_reply_at_old, '')), ''), d.created_at) DESC",
+            ),
         };
 
-        // Now, outside of the DB lock, convert and choose latest between models
+        let query = format!(
+            "SELECT
+                d.id, d.content_id, d.post_id, d.revision, d.created_at, d.pod, d.timestamp_pod,
+                d.uploader_id, d.upvote_count_pod, d.tags, d.authors, d.reply_to, d.requested_post_id, d.title, d.deleted_at, d.content_type,
+                -- New-model latest reply across descendant posts in this thread
+                (
+                    SELECT MAX(r.created_at) FROM documents r
+                    WHERE r.post_id IN (
+                        SELECT c.id FROM posts c WHERE c.thread_root_post_id = p.id AND c.parent_post_id IS NOT NULL
+                    )
+                ) AS latest_reply_at_new,
+                (
+                    SELECT r.uploader_id FROM documents r
+                    WHERE r.post_id IN (
+                        SELECT c.id FROM posts c WHERE c.thread_root_post_id = p.id AND c.parent_post_id IS NOT NULL
+                    )
+                    ORDER BY r.created_at DESC LIMIT 1
+                ) AS latest_reply_by_new,
+                -- Old-model latest reply within the same post using document-level reply_to
+                (
+                    SELECT MAX(rr.created_at) FROM documents rr WHERE rr.post_id = p.id AND rr.reply_to IS NOT NULL
+                ) AS latest_reply_at_old,
+                (
+                    SELECT rr.uploader_id FROM documents rr WHERE rr.post_id = p.id AND rr.reply_to IS NOT NULL
+                    ORDER BY rr.created_at DESC LIMIT 1
+                ) AS latest_reply_by_old,
+                p.pinned_at
+             FROM posts p
+             JOIN documents d ON d.post_id = p.id AND d.revision = (
+                SELECT MAX(x.revision) FROM documents x WHERE x.post_id = p.id AND (x.reply_to IS NULL)
+             )
+             {upvote_join}
+             WHERE p.parent_post_id IS NULL AND d.hidden = 0 AND d.deleted_at IS NULL
+             ORDER BY (p.pinned_at IS NOT NULL) DESC, p.pinned_at DESC, {order_by}
+             LIMIT {limit} OFFSET {offset}"
+        );
+
+        let rows: Vec<Row> = {
+            let conn = self.conn();
+            let mut stmt = conn.prepare(&query)?;
+            let mapped = if sort == DocumentSort::Top {
+                stmt.query_map(rusqlite::params![upvote_window_days], map_row)?
+                    .collect::<Result<Vec<_>, _>>()?
+            } else {
+                stmt.query_map([], map_row)?
+                    .collect::<Result<Vec<_>, _>>()?
+            };
+            mapped
+        };
+
+        // Now, outside of the DB lock, convert and choose latest between models.
+        // Upvote counts and hidden flags are resolved in one batch for the
+        // whole page rather than per row.
+        let (raw_docs, reply_infos): (Vec<_>, Vec<_>) = rows
+            .into_iter()
+            .map(|(raw_doc, at_new, by_new, at_old, by_old, pinned)| {
+                (raw_doc, (at_new, by_new, at_old, by_old, pinned))
+            })
+            .unzip();
+        let metadatas = self.raw_documents_to_metadata(raw_docs)?;
+
         let mut result = Vec::new();
-        for (raw_doc, at_new, by_new, at_old, by_old) in rows {
-            let metadata = self.raw_document_to_metadata(raw_doc)?;
+        for (metadata, (at_new, by_new, at_old, by_old, pinned)) in
+            metadatas.into_iter().zip(reply_infos)
+        {
             let (latest_reply_at, latest_reply_by) = match (at_new.as_ref(), at_old.as_ref()) {
                 (Some(a), Some(b)) => {
                     if a >= b {
@@ -861,6 +1899,8 @@ impl Database {
                 metadata,
                 latest_reply_at,
                 latest_reply_by,
+                pinned,
+                excerpt: None,
             });
         }
 
@@ -870,59 +1910,244 @@ impl Database {
     // Get documents by post ID (metadata only)
     pub fn get_documents_metadata_by_post_id(&self, post_id: i64) -> Result<Vec<DocumentMetadata>> {
         let raw_documents = self.get_documents_by_post_id(post_id)?;
-        let mut documents_metadata = Vec::new();
-
-        for raw_doc in raw_documents {
-            documents_metadata.push(self.raw_document_to_metadata(raw_doc)?);
-        }
-
-        Ok(documents_metadata)
+        self.raw_documents_to_metadata(raw_documents)
     }
 
     pub fn user_has_upvoted(&self, document_id: i64, username: &str) -> Result<bool> {
-        let conn = self.conn.lock().unwrap();
+        // Upvotes recorded under a previous username of the same key still
+        // count, so a rename cannot be used to upvote twice
+        let aliases = self.username_alias_group(username)?;
+
+        let conn = self.conn();
+        let placeholders = vec!["?"; aliases.len()].join(", ");
+        let mut params: Vec<&dyn rusqlite::ToSql> = vec![&document_id];
+        params.extend(aliases.iter().map(|name| name as &dyn rusqlite::ToSql));
         let count: i64 = conn.query_row(
-            "SELECT COUNT(*) FROM upvotes WHERE document_id = ?1 AND username = ?2",
-            [&document_id.to_string(), username],
+            &format!(
+                "SELECT COUNT(*) FROM upvotes WHERE document_id = ? AND username IN ({placeholders})"
+            ),
+            rusqlite::params_from_iter(params),
             |row| row.get(0),
         )?;
         Ok(count > 0)
     }
 
-    /// Delete a document and return the uploader username for verification
+    /// Record that a verified identity pod bound `public_key` to `username`.
+    /// Returns the previously-current username for that key when this is a
+    /// rename, None when the binding is new or unchanged.
+    pub fn record_user_identity(&self, public_key: &str, username: &str) -> Result<Option<String>> {
+        let conn = self.conn();
+        let current: Option<String> = conn
+            .query_row(
+                "SELECT username FROM user_aliases WHERE public_key = ?1 ORDER BY id DESC LIMIT 1",
+                [public_key],
+                |row| row.get(0),
+            )
+            .optional()?;
+        if current.as_deref() == Some(username) {
+            return Ok(None);
+        }
+
+        conn.execute(
+            "INSERT INTO user_aliases (public_key, username) VALUES (?1, ?2)",
+            [public_key, username],
+        )?;
+        Ok(current)
+    }
+
+    /// All usernames that have been bound to the same public key as
+    /// `username`, including `username` itself. Unknown usernames are their
+    /// own group of one.
+    pub fn username_alias_group(&self, username: &str) -> Result<Vec<String>> {
+        let conn = self.conn();
+        let mut stmt = conn.prepare(
+            "SELECT DISTINCT username FROM user_aliases
+             WHERE public_key IN (SELECT public_key FROM user_aliases WHERE username = ?1)",
+        )?;
+        let mut names = stmt
+            .query_map([username], |row| row.get(0))?
+            .collect::<Result<Vec<String>, _>>()?;
+        if !names.iter().any(|name| name == username) {
+            names.push(username.to_string());
+        }
+        Ok(names)
+    }
+
+    /// Batched companion to [`Self::resolve_display_name`] for list
+    /// conversions; usernames with no recorded alias are absent from the map.
+    fn resolve_display_names(
+        &self,
+        usernames: &HashSet<String>,
+    ) -> Result<HashMap<String, String>> {
+        if usernames.is_empty() {
+            return Ok(HashMap::new());
+        }
+        let conn = self.conn();
+        let placeholders = vec!["?"; usernames.len()].join(", ");
+        let mut stmt = conn.prepare(&format!(
+            "SELECT DISTINCT a1.username,
+                    (SELECT a2.username FROM user_aliases a2
+                      WHERE a2.public_key = a1.public_key
+                      ORDER BY a2.id DESC LIMIT 1)
+               FROM user_aliases a1
+              WHERE a1.username IN ({placeholders})"
+        ))?;
+        let rows = stmt.query_map(rusqlite::params_from_iter(usernames.iter()), |row| {
+            Ok((row.get(0)?, row.get(1)?))
+        })?;
+        rows.collect()
+    }
+
+    /// Current display name for a (possibly historical) username: the newest
+    /// username bound to the same public key. Returns the input unchanged
+    /// when no alias is recorded.
+    pub fn resolve_display_name(&self, username: &str) -> Result<String> {
+        let conn = self.conn();
+        let name: Option<String> = conn
+            .query_row(
+                "SELECT username FROM user_aliases
+                 WHERE public_key IN (SELECT public_key FROM user_aliases WHERE username = ?1)
+                 ORDER BY id DESC LIMIT 1",
+                [username],
+                |row| row.get(0),
+            )
+            .optional()?;
+        Ok(name.unwrap_or_else(|| username.to_string()))
+    }
+
+    /// Delete a document and return the uploader username for verification.
+    ///
+    /// Documents that still have descendants (replies through the posts
+    /// hierarchy or the old document-level reply_to model) are tombstoned
+    /// instead of removed: the row stays so the reply tree keeps its shape,
+    /// but content and pods are blanked and `deleted_at` is set. Only
+    /// documents with no descendants are hard-deleted.
     pub fn delete_document(&self, document_id: i64) -> Result<String> {
-        let conn = self.conn.lock().unwrap();
+        let conn = self.conn();
 
         // First get the document to verify it exists and get uploader info
-        let uploader_id: String = conn.query_row(
-            "SELECT uploader_id FROM documents WHERE id = ?1",
-            [&document_id.to_string()],
-            |row| row.get(0),
+        let (uploader_id, post_id): (String, i64) = conn.query_row(
+            "SELECT uploader_id, post_id FROM documents WHERE id = ?1 AND deleted_at IS NULL",
+            [document_id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
         )?;
 
-        // Delete the document
-        let deleted_rows = conn.execute(
-            "DELETE FROM documents WHERE id = ?1",
-            [&document_id.to_string()],
+        // Drop the document from the full-text index either way
+        conn.execute("DELETE FROM documents_fts WHERE rowid = ?1", [document_id])?;
+
+        let has_descendants: bool = conn.query_row(
+            "SELECT EXISTS (SELECT 1 FROM posts WHERE parent_post_id = ?1)
+                 OR EXISTS (SELECT 1 FROM documents WHERE json_extract(reply_to, '$.document_id') = ?2)",
+            rusqlite::params![post_id, document_id],
+            |row| row.get(0),
         )?;
 
-        if deleted_rows == 0 {
-            return Err(rusqlite::Error::QueryReturnedNoRows);
+        if has_descendants {
+            // Tombstone: keep the row so replies stay attached, blank out
+            // everything else. The zeroed content_id never resolves in storage.
+            conn.execute(
+                "UPDATE documents SET
+                     content_id = '0000000000000000000000000000000000000000000000000000000000000000',
+                     pod = '', timestamp_pod = '', upvote_count_pod = NULL,
+                     tags = '[]', authors = '[]', title = '',
+                     deleted_at = CURRENT_TIMESTAMP
+                 WHERE id = ?1",
+                [document_id],
+            )?;
+        } else {
+            conn.execute("DELETE FROM documents WHERE id = ?1", [document_id])?;
         }
 
         // Also delete associated upvotes
-        conn.execute(
-            "DELETE FROM upvotes WHERE document_id = ?1",
-            [&document_id.to_string()],
-        )?;
+        conn.execute("DELETE FROM upvotes WHERE document_id = ?1", [document_id])?;
 
-        tracing::info!("Deleted document {document_id} and associated upvotes");
+        if has_descendants {
+            tracing::info!("Tombstoned document {document_id} (has replies) and deleted upvotes");
+        } else {
+            tracing::info!("Deleted document {document_id} and associated upvotes");
+        }
         Ok(uploader_id)
     }
 
+    /// Full-text search over document titles and messages, best match first.
+    /// Deleted documents drop out of the index; hidden documents are filtered
+    /// out here.
+    pub fn search_documents(
+        &self,
+        query: &str,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<DocumentMetadata>> {
+        let ids: Vec<i64> = {
+            let conn = self.conn();
+            let mut stmt = conn.prepare(
+                "SELECT f.rowid FROM documents_fts f
+                 JOIN documents d ON d.id = f.rowid
+                 WHERE documents_fts MATCH ?1 AND d.hidden = 0
+                 ORDER BY rank LIMIT ?2 OFFSET ?3",
+            )?;
+            let rows =
+                stmt.query_map(rusqlite::params![query, limit, offset], |row| row.get(0))?;
+            rows.collect::<Result<Vec<i64>, _>>()?
+        };
+
+        let mut results = Vec::with_capacity(ids.len());
+        for id in ids {
+            if let Some(metadata) = self.get_document_metadata(id)? {
+                results.push(metadata);
+            }
+        }
+        Ok(results)
+    }
+
+    /// Like [`Self::search_documents`], but pairs each hit with the FTS
+    /// snippet around the match, wrapped in `[match]`/`[/match]` markers
+    pub fn search_documents_with_snippets(
+        &self,
+        query: &str,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<(DocumentMetadata, String)>> {
+        let rows: Vec<(i64, String)> = {
+            let conn = self.conn();
+            let mut stmt = conn.prepare(
+                "SELECT f.rowid, snippet(documents_fts, -1, '[match]', '[/match]', '…', 16)
+                 FROM documents_fts f
+                 JOIN documents d ON d.id = f.rowid
+                 WHERE documents_fts MATCH ?1 AND d.hidden = 0
+                 ORDER BY rank LIMIT ?2 OFFSET ?3",
+            )?;
+            let rows = stmt.query_map(rusqlite::params![query, limit, offset], |row| {
+                Ok((row.get(0)?, row.get(1)?))
+            })?;
+            rows.collect::<Result<Vec<_>, _>>()?
+        };
+
+        let mut results = Vec::with_capacity(rows.len());
+        for (id, snippet) in rows {
+            if let Some(metadata) = self.get_document_metadata(id)? {
+                results.push((metadata, snippet));
+            }
+        }
+        Ok(results)
+    }
+
+    /// Total matches for a search query, using the same filter as
+    /// [`Self::search_documents`]
+    pub fn count_search_documents(&self, query: &str) -> Result<i64> {
+        let conn = self.conn();
+        conn.query_row(
+            "SELECT COUNT(*) FROM documents_fts f
+             JOIN documents d ON d.id = f.rowid
+             WHERE documents_fts MATCH ?1 AND d.hidden = 0",
+            [query],
+            |row| row.get(0),
+        )
+    }
+
     /// Delete all documents in a post. Returns number of deleted documents.
     pub fn delete_documents_by_post_id(&self, post_id: i64) -> Result<usize> {
-        let conn = self.conn.lock().unwrap();
+        let conn = self.conn();
 
         // Delete upvotes for documents in this post
         conn.execute(
@@ -930,6 +2155,12 @@ impl Database {
             [post_id],
         )?;
 
+        // Drop the documents from the full-text index
+        conn.execute(
+            "DELETE FROM documents_fts WHERE rowid IN (SELECT id FROM documents WHERE post_id = ?1)",
+            [post_id],
+        )?;
+
         // Delete documents in this post
         let deleted = conn.execute("DELETE FROM documents WHERE post_id = ?1", [post_id])?;
 
@@ -938,7 +2169,7 @@ impl Database {
 
     /// Get uploader username for a document
     pub fn get_document_uploader(&self, document_id: i64) -> Result<Option<String>> {
-        let conn = self.conn.lock().unwrap();
+        let conn = self.conn();
         let result = conn.query_row(
             "SELECT uploader_id FROM documents WHERE id = ?1",
             [&document_id.to_string()],
@@ -952,17 +2183,38 @@ impl Database {
         }
     }
 
-    pub fn update_upvote_count_pod(&self, document_id: i64, upvote_count_pod: &str) -> Result<()> {
-        let conn = self.conn.lock().unwrap();
+    pub fn update_upvote_count_pod(
+        &self,
+        document_id: i64,
+        upvote_count_pod: &str,
+        proven_count: i64,
+    ) -> Result<()> {
+        let conn = self.conn();
         conn.execute(
-            "UPDATE documents SET upvote_count_pod = ?1 WHERE id = ?2",
-            [upvote_count_pod, &document_id.to_string()],
+            "UPDATE documents SET upvote_count_pod = ?1, upvote_count_pod_count = ?2 WHERE id = ?3",
+            [
+                upvote_count_pod,
+                &proven_count.to_string(),
+                &document_id.to_string(),
+            ],
         )?;
         Ok(())
     }
 
+    /// The upvote count the stored upvote count pod proves; 0 when only the
+    /// base case pod (or no pod) exists.
+    pub fn get_upvote_count_pod_count(&self, document_id: i64) -> Result<i64> {
+        let conn = self.conn();
+        let count = conn.query_row(
+            "SELECT upvote_count_pod_count FROM documents WHERE id = ?1",
+            [document_id],
+            |row| row.get(0),
+        )?;
+        Ok(count)
+    }
+
     pub fn get_upvote_count_pod(&self, document_id: i64) -> Result<Option<String>> {
-        let conn = self.conn.lock().unwrap();
+        let conn = self.conn();
         let result = conn.query_row(
             "SELECT upvote_count_pod FROM documents WHERE id = ?1",
             [document_id],
@@ -978,9 +2230,9 @@ impl Database {
 
     // Get documents that reply to a specific document
     pub fn get_replies_to_document(&self, document_id: i64) -> Result<Vec<RawDocument>> {
-        let conn = self.conn.lock().unwrap();
+        let conn = self.conn();
         let mut stmt = conn.prepare(
-            "SELECT id, content_id, post_id, revision, created_at, pod, timestamp_pod, uploader_id, upvote_count_pod, tags, authors, reply_to, requested_post_id, title
+            "SELECT id, content_id, post_id, revision, created_at, pod, timestamp_pod, uploader_id, upvote_count_pod, tags, authors, reply_to, requested_post_id, title, deleted_at, content_type
              FROM documents WHERE json_extract(reply_to, '$.document_id') = ?1 ORDER BY created_at ASC",
         )?;
 
@@ -1009,19 +2261,86 @@ impl Database {
                     reply_to,
                     requested_post_id: row.get(12)?,
                     title: row.get(13)?,
+                    deleted_at: row.get(14)?,
+                    content_type: row.get(15)?,
                 })
             })?
             .collect::<Result<Vec<_>, _>>()?;
 
-        Ok(documents)
+        Ok(documents)
+    }
+
+    // Get complete reply tree for a specific document using posts table hierarchy
+    pub fn get_reply_tree_for_document(
+        &self,
+        document_id: i64,
+        storage: &crate::storage::ContentAddressedStorage,
+    ) -> Result<Option<DocumentReplyTree>> {
+        self.get_reply_tree_for_document_pruned(document_id, ReplyTreePruning::default(), storage)
+    }
+
+    // Synchronous convenience that builds the skeleton and hydrates its content
+    // in one call. Request handlers use get_reply_tree_skeleton_pruned instead
+    // and fetch content concurrently off the request thread.
+    pub fn get_reply_tree_for_document_pruned(
+        &self,
+        document_id: i64,
+        pruning: ReplyTreePruning,
+        storage: &crate::storage::ContentAddressedStorage,
+    ) -> Result<Option<DocumentReplyTree>> {
+        match self.get_reply_tree_skeleton_pruned(document_id, pruning)? {
+            Some(skeleton) => Ok(Some(Self::hydrate_reply_tree(skeleton, storage)?)),
+            None => Ok(None),
+        }
+    }
+
+    // Fetch content for every node in a skeleton, sequentially. Error mapping
+    // matches the historical inline fetches during tree construction.
+    fn hydrate_reply_tree(
+        skeleton: ReplyTreeSkeleton,
+        storage: &crate::storage::ContentAddressedStorage,
+    ) -> Result<DocumentReplyTree> {
+        // Tombstoned nodes have no content in storage; render a placeholder so
+        // their replies stay reachable
+        let content = if skeleton.document.deleted_at.is_some() {
+            DocumentContent {
+                message: Some("[deleted by author]".to_string()),
+                file: None,
+                files: Vec::new(),
+                url: None,
+            }
+        } else {
+            storage
+                .retrieve_document_content(&skeleton.document.content_id)
+                .map_err(|_| rusqlite::Error::InvalidPath("storage error".into()))?
+                .ok_or_else(|| {
+                    rusqlite::Error::InvalidPath("content not found in storage".into())
+                })?
+        };
+
+        let replies = skeleton
+            .replies
+            .into_iter()
+            .map(|reply| Self::hydrate_reply_tree(reply, storage))
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(DocumentReplyTree {
+            document: skeleton.document,
+            content,
+            replies,
+            stubs: skeleton.stubs,
+        })
     }
 
-    // Get complete reply tree for a specific document using posts table hierarchy
-    pub fn get_reply_tree_for_document(
+    // Get a depth/limit-pruned, metadata-only slice of the reply tree for a
+    // specific document. Branches cut off by the pruning parameters are returned
+    // as child-count stubs. No storage I/O happens here, so the connection lock
+    // is never held across filesystem reads.
+    pub fn get_reply_tree_skeleton_pruned(
         &self,
         document_id: i64,
-        storage: &crate::storage::ContentAddressedStorage,
-    ) -> Result<Option<DocumentReplyTree>> {
+        pruning: ReplyTreePruning,
+    ) -> Result<Option<ReplyTreeSkeleton>> {
         // First get the post_id for this document
         let document_post_id = match self.get_document_post_id(document_id)? {
             Some(post_id) => post_id,
@@ -1053,13 +2372,13 @@ impl Database {
             all_thread_documents,
             posts_hierarchy,
             document_id,
-            storage,
+            pruning,
         )
     }
 
     // Helper method to get post_id for a document
     pub fn get_document_post_id(&self, document_id: i64) -> Result<Option<i64>> {
-        let conn = self.conn.lock().unwrap();
+        let conn = self.conn();
         conn.query_row(
             "SELECT post_id FROM documents WHERE id = ?1",
             [document_id],
@@ -1070,7 +2389,7 @@ impl Database {
 
     // Helper method to get thread_root_post_id for a post
     pub fn get_post_thread_root_id(&self, post_id: i64) -> Result<Option<i64>> {
-        let conn = self.conn.lock().unwrap();
+        let conn = self.conn();
         let result = conn
             .query_row(
                 "SELECT thread_root_post_id FROM posts WHERE id = ?1",
@@ -1085,7 +2404,7 @@ impl Database {
 
     // Helper method to get thread_root_id for a document (kept for compatibility)
     pub fn get_document_thread_root_id(&self, document_id: i64) -> Result<Option<i64>> {
-        let conn = self.conn.lock().unwrap();
+        let conn = self.conn();
         conn.query_row(
             "SELECT thread_root_id FROM documents WHERE id = ?1",
             [document_id],
@@ -1099,12 +2418,12 @@ impl Database {
         &self,
         thread_root_post_id: i64,
     ) -> Result<Vec<RawDocument>> {
-        let conn = self.conn.lock().unwrap();
+        let conn = self.conn();
 
         // Get all documents for all posts in this thread using posts table hierarchy
         let mut stmt = conn.prepare(
             "SELECT d.id, d.content_id, d.post_id, d.revision, d.created_at, d.pod, d.timestamp_pod, 
-                    d.uploader_id, d.upvote_count_pod, d.tags, d.authors, d.reply_to, d.requested_post_id, d.title
+                    d.uploader_id, d.upvote_count_pod, d.tags, d.authors, d.reply_to, d.requested_post_id, d.title, d.deleted_at, d.content_type
              FROM posts p
              JOIN documents d ON p.id = d.post_id
              WHERE p.thread_root_post_id = ?1 OR p.id = ?1
@@ -1137,6 +2456,8 @@ impl Database {
                     reply_to,
                     requested_post_id: row.get(12)?,
                     title: row.get(13)?,
+                    deleted_at: row.get(14)?,
+                    content_type: row.get(15)?,
                 })
             })?
             .collect::<Result<Vec<_>, _>>()?;
@@ -1149,7 +2470,7 @@ impl Database {
         &self,
         thread_root_post_id: i64,
     ) -> Result<std::collections::HashMap<i64, Option<i64>>> {
-        let conn = self.conn.lock().unwrap();
+        let conn = self.conn();
 
         // Get post_id -> parent_post_id mapping for all posts in the thread
         let mut stmt = conn.prepare(
@@ -1171,9 +2492,9 @@ impl Database {
 
     // Helper method to get all documents in a thread (original method, kept for compatibility)
     pub fn get_documents_by_thread_root_id(&self, thread_root_id: i64) -> Result<Vec<RawDocument>> {
-        let conn = self.conn.lock().unwrap();
+        let conn = self.conn();
         let mut stmt = conn.prepare(
-            "SELECT id, content_id, post_id, revision, created_at, pod, timestamp_pod, uploader_id, upvote_count_pod, tags, authors, reply_to, requested_post_id, title, thread_root_id
+            "SELECT id, content_id, post_id, revision, created_at, pod, timestamp_pod, uploader_id, upvote_count_pod, tags, authors, reply_to, requested_post_id, title, deleted_at, thread_root_id, content_type
              FROM documents WHERE thread_root_id = ?1 ORDER BY created_at ASC",
         )?;
 
@@ -1203,6 +2524,8 @@ impl Database {
                     reply_to,
                     requested_post_id: row.get(12)?,
                     title: row.get(13)?,
+                    deleted_at: row.get(14)?,
+                    content_type: row.get(16)?,
                 })
             })?
             .collect::<Result<Vec<_>, _>>()?;
@@ -1216,45 +2539,29 @@ impl Database {
         raw_documents: Vec<RawDocument>,
         posts_hierarchy: std::collections::HashMap<i64, Option<i64>>,
         requested_document_id: i64,
-        storage: &crate::storage::ContentAddressedStorage,
-    ) -> Result<Option<DocumentReplyTree>> {
+        pruning: ReplyTreePruning,
+    ) -> Result<Option<ReplyTreeSkeleton>> {
         use std::collections::HashMap;
 
         if raw_documents.is_empty() {
             return Ok(None);
         }
 
-        // Create mappings for building the tree
+        // Create mappings for building the tree. Only metadata is assembled here;
+        // pruned branches never appear in the skeleton, so they cost no content fetch.
         let mut document_map: HashMap<i64, DocumentMetadata> = HashMap::new();
-        let mut content_map: HashMap<i64, DocumentContent> = HashMap::new();
         let mut post_to_documents: HashMap<i64, Vec<i64>> = HashMap::new();
 
-        // Process all documents
-        for raw_doc in &raw_documents {
-            let doc_id = raw_doc.id.unwrap_or(-1);
-            let metadata = self.raw_document_to_metadata(raw_doc.clone())?;
-
-            // Fetch content from storage
-            let content_hash = Hash::from_hex(raw_doc.content_id.clone()).map_err(|_| {
-                rusqlite::Error::InvalidColumnType(
-                    0,
-                    "content_id".to_string(),
-                    rusqlite::types::Type::Text,
-                )
-            })?;
-            let content = storage
-                .retrieve_document_content(&content_hash)
-                .map_err(|_| rusqlite::Error::InvalidPath("storage error".into()))?
-                .ok_or_else(|| {
-                    rusqlite::Error::InvalidPath("content not found in storage".into())
-                })?;
-
-            document_map.insert(doc_id, metadata);
-            content_map.insert(doc_id, content);
+        // Process all documents; upvote counts and hidden flags for the whole
+        // thread are resolved in one batch
+        let metadatas = self.raw_documents_to_metadata(raw_documents.clone())?;
+        for metadata in metadatas {
+            let doc_id = metadata.id.unwrap_or(-1);
             post_to_documents
-                .entry(raw_doc.post_id)
+                .entry(metadata.post_id)
                 .or_default()
                 .push(doc_id);
+            document_map.insert(doc_id, metadata);
         }
 
         // Choose representative document for each post (prefer requested document, then latest revision)
@@ -1296,46 +2603,75 @@ impl Database {
             return Ok(None);
         }
 
-        // Recursively build the tree starting from the requested document
+        // Recursively build the tree starting from the requested document, collapsing
+        // branches beyond the pruning limits into child-count stubs
         fn build_tree_node(
             document_id: i64,
+            depth: usize,
             document_map: &HashMap<i64, DocumentMetadata>,
-            content_map: &HashMap<i64, DocumentContent>,
             children_map: &HashMap<i64, Vec<i64>>,
-        ) -> Option<DocumentReplyTree> {
+            pruning: ReplyTreePruning,
+        ) -> Option<ReplyTreeSkeleton> {
             let document = document_map.get(&document_id)?.clone();
-            let content = content_map.get(&document_id)?.clone();
-            let child_ids = children_map.get(&document_id).cloned().unwrap_or_default();
+
+            // Sort children by creation time (with id as tie-breaker) so pruning is
+            // deterministic and replies come out in chronological order
+            let mut child_ids = children_map.get(&document_id).cloned().unwrap_or_default();
+            child_ids.sort_by_key(|child_id| {
+                (
+                    document_map
+                        .get(child_id)
+                        .and_then(|m| m.created_at.clone()),
+                    *child_id,
+                )
+            });
+
+            let included = match pruning.depth {
+                Some(max_depth) if depth >= max_depth => 0,
+                _ => pruning
+                    .limit_per_node
+                    .unwrap_or(child_ids.len())
+                    .min(child_ids.len()),
+            };
 
             let mut replies = Vec::new();
-            for child_id in child_ids {
+            for child_id in &child_ids[..included] {
                 if let Some(child_tree) =
-                    build_tree_node(child_id, document_map, content_map, children_map)
+                    build_tree_node(*child_id, depth + 1, document_map, children_map, pruning)
                 {
                     replies.push(child_tree);
                 }
             }
 
-            // Sort replies by creation time
-            replies.sort_by(|a, b| {
-                a.document
-                    .created_at
-                    .as_ref()
-                    .cmp(&b.document.created_at.as_ref())
-            });
+            let stubs = child_ids[included..]
+                .iter()
+                .filter_map(|child_id| {
+                    document_map
+                        .get(child_id)
+                        .map(|metadata| podnet_models::ReplyTreeStub {
+                            document_id: *child_id,
+                            post_id: metadata.post_id,
+                            reply_count: children_map
+                                .get(child_id)
+                                .map(|children| children.len() as i64)
+                                .unwrap_or(0),
+                        })
+                })
+                .collect();
 
-            Some(DocumentReplyTree {
+            Some(ReplyTreeSkeleton {
                 document,
-                content,
                 replies,
+                stubs,
             })
         }
 
         Ok(build_tree_node(
             requested_document_id,
+            0,
             &document_map,
-            &content_map,
             &children_map,
+            pruning,
         ))
     }
 }
@@ -1377,6 +2713,7 @@ pub mod tests {
         let content = DocumentContent {
             message: Some(format!("Test content for {title}")),
             file: None,
+            files: Vec::new(),
             url: None,
         };
         let content_hash = storage
@@ -1463,6 +2800,382 @@ pub mod tests {
         }
     }
 
+    // Insert a document with its own post and proper thread links in the posts table,
+    // so the posts-hierarchy-based reply tree builder can see it. Returns (post_id, document_id).
+    pub fn insert_threaded_document(
+        db: &Database,
+        storage: &crate::storage::ContentAddressedStorage,
+        title: &str,
+        parent: Option<(i64, i64)>, // (parent_post_id, parent_document_id)
+    ) -> (i64, i64) {
+        let post_id = db.create_post().unwrap();
+        if let Some((parent_post_id, parent_document_id)) = parent {
+            let thread_root = db
+                .get_post_thread_root_id(parent_post_id)
+                .unwrap()
+                .unwrap_or(parent_post_id);
+            db.set_post_thread_links(
+                post_id,
+                Some(parent_post_id),
+                Some(thread_root),
+                Some(parent_document_id),
+            )
+            .unwrap();
+        } else {
+            db.set_post_thread_links(post_id, None, Some(post_id), None)
+                .unwrap();
+        }
+
+        let content = DocumentContent {
+            message: Some(format!("Test content for {title}")),
+            file: None,
+            files: Vec::new(),
+            url: None,
+        };
+        let content_hash = storage
+            .store_document_content(&content)
+            .expect("Failed to store test content")
+            .encode_hex::<String>();
+
+        let reply_to_json = parent.map(|(parent_post_id, parent_document_id)| {
+            serde_json::to_string(&ReplyReference {
+                post_id: parent_post_id,
+                document_id: parent_document_id,
+            })
+            .unwrap()
+        });
+
+        let conn = db.conn.lock().unwrap();
+        conn.execute(
+            "INSERT INTO documents (content_id, post_id, revision, pod, timestamp_pod, uploader_id, upvote_count_pod, tags, authors, reply_to, requested_post_id, title)
+             VALUES (?1, ?2, 1, '{}', '{}', 'test_user', NULL, '[]', '[]', ?3, NULL, ?4)",
+            rusqlite::params![content_hash, post_id, reply_to_json, title],
+        )
+        .unwrap();
+        let document_id = conn.last_insert_rowid();
+
+        // Mirror create_document's full-text indexing
+        conn.execute(
+            "INSERT INTO documents_fts(rowid, title, message) VALUES (?1, ?2, ?3)",
+            rusqlite::params![document_id, title, content.message.as_deref().unwrap_or("")],
+        )
+        .unwrap();
+
+        (post_id, document_id)
+    }
+
+    #[test]
+    fn test_reply_notifications() {
+        let db = create_test_database();
+        let storage = create_test_storage();
+
+        // Alice's post and Bob's reply to it
+        let (root_post, root_doc) = insert_threaded_document(&db, &storage, "Alice's Post", None);
+        let (_, reply_doc) =
+            insert_threaded_document(&db, &storage, "Bob's Reply", Some((root_post, root_doc)));
+        {
+            let conn = db.conn.lock().unwrap();
+            conn.execute(
+                "UPDATE documents SET uploader_id = 'alice' WHERE id = ?1",
+                [root_doc],
+            )
+            .unwrap();
+            conn.execute(
+                "UPDATE documents SET uploader_id = 'bob' WHERE id = ?1",
+                [reply_doc],
+            )
+            .unwrap();
+
+            // Bob replying to Alice notifies Alice
+            Database::insert_reply_notification(
+                &conn,
+                &ReplyReference {
+                    post_id: root_post,
+                    document_id: root_doc,
+                },
+                "bob",
+                reply_doc,
+            )
+            .unwrap();
+
+            // Alice following up on her own post does not notify anyone
+            Database::insert_reply_notification(
+                &conn,
+                &ReplyReference {
+                    post_id: root_post,
+                    document_id: root_doc,
+                },
+                "alice",
+                reply_doc,
+            )
+            .unwrap();
+        }
+
+        let alice_notifications = db.get_notifications("alice", false).unwrap();
+        assert_eq!(alice_notifications.len(), 1);
+        let notification = &alice_notifications[0];
+        assert_eq!(notification.actor_username, "bob");
+        assert_eq!(notification.document_id, reply_doc);
+        assert!(!notification.read);
+
+        // Bob never gets notified about his own reply
+        assert!(db.get_notifications("bob", false).unwrap().is_empty());
+
+        // Marking the notification read removes it from the unread view
+        db.mark_notification_read(notification.id.unwrap()).unwrap();
+        assert!(db.get_notifications("alice", true).unwrap().is_empty());
+        assert_eq!(db.get_notifications("alice", false).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_thread_subscription_notifications() {
+        let db = create_test_database();
+        let storage = create_test_storage();
+
+        let (root_post, root_doc) = insert_threaded_document(&db, &storage, "Root", None);
+        let (_, first_reply) =
+            insert_threaded_document(&db, &storage, "First Reply", Some((root_post, root_doc)));
+        let (_, second_reply) =
+            insert_threaded_document(&db, &storage, "Second Reply", Some((root_post, root_doc)));
+        let (_, third_reply) =
+            insert_threaded_document(&db, &storage, "Third Reply", Some((root_post, root_doc)));
+
+        // Any document in the thread resolves to its root; unknown ids to None
+        assert_eq!(db.get_thread_root_id(root_doc).unwrap(), Some(root_doc));
+        assert_eq!(db.get_thread_root_id(first_reply).unwrap(), Some(root_doc));
+        assert_eq!(db.get_thread_root_id(99999).unwrap(), None);
+
+        // Auto-subscribe as create_document does for thread roots; repeats are no-ops
+        {
+            let conn = db.conn.lock().unwrap();
+            conn.execute(
+                "UPDATE documents SET uploader_id = 'alice' WHERE id = ?1",
+                [root_doc],
+            )
+            .unwrap();
+            assert!(Database::insert_thread_subscription(&conn, "alice", root_doc).unwrap());
+            assert!(!Database::insert_thread_subscription(&conn, "alice", root_doc).unwrap());
+        }
+        assert!(db.subscribe_to_thread("bob", root_doc).unwrap());
+        assert_eq!(db.get_thread_subscribers(root_doc).unwrap(), ["alice", "bob"]);
+
+        // Carol replies: the parent uploader is notified directly, subscribers
+        // through their subscription, and nobody twice
+        {
+            let conn = db.conn.lock().unwrap();
+            let reply_ref = ReplyReference {
+                post_id: root_post,
+                document_id: root_doc,
+            };
+            Database::insert_reply_notification(&conn, &reply_ref, "carol", first_reply).unwrap();
+            Database::insert_subscription_notifications(&conn, root_doc, "carol", first_reply)
+                .unwrap();
+        }
+        assert_eq!(db.get_notifications("alice", true).unwrap().len(), 1);
+        let bob_notifications = db.get_notifications("bob", true).unwrap();
+        assert_eq!(bob_notifications.len(), 1);
+        assert_eq!(bob_notifications[0].actor_username, "carol");
+        assert_eq!(bob_notifications[0].document_id, first_reply);
+        assert!(db.get_notifications("carol", false).unwrap().is_empty());
+
+        // A subscriber replying to the thread is not notified about it
+        {
+            let conn = db.conn.lock().unwrap();
+            let reply_ref = ReplyReference {
+                post_id: root_post,
+                document_id: root_doc,
+            };
+            Database::insert_reply_notification(&conn, &reply_ref, "bob", second_reply).unwrap();
+            Database::insert_subscription_notifications(&conn, root_doc, "bob", second_reply)
+                .unwrap();
+        }
+        assert_eq!(db.get_notifications("alice", true).unwrap().len(), 2);
+        assert_eq!(db.get_notifications("bob", true).unwrap().len(), 1);
+
+        // After unsubscribing, further replies no longer reach Bob
+        assert!(db.unsubscribe_from_thread("bob", root_doc).unwrap());
+        assert!(!db.unsubscribe_from_thread("bob", root_doc).unwrap());
+        {
+            let conn = db.conn.lock().unwrap();
+            Database::insert_subscription_notifications(&conn, root_doc, "carol", third_reply)
+                .unwrap();
+        }
+        assert_eq!(db.get_notifications("bob", true).unwrap().len(), 1);
+        assert_eq!(db.get_notifications("alice", true).unwrap().len(), 3);
+
+        // Marking read clears the unread feed one row at a time
+        let unread = db.get_notifications("alice", true).unwrap();
+        db.mark_notification_read(unread[0].id.unwrap()).unwrap();
+        assert_eq!(db.get_notifications("alice", true).unwrap().len(), 2);
+        assert_eq!(db.get_notifications("alice", false).unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_pruned_reply_tree_stub_counts() {
+        let db = create_test_database();
+        let storage = create_test_storage();
+
+        // Build a thread with 120 replies: 40 direct replies to the root,
+        // each of which has 2 children of its own
+        let (root_post, root_doc) =
+            insert_threaded_document(&db, &storage, "Thread Root", None);
+        for i in 0..40 {
+            let (reply_post, reply_doc) = insert_threaded_document(
+                &db,
+                &storage,
+                &format!("Reply {i}"),
+                Some((root_post, root_doc)),
+            );
+            for j in 0..2 {
+                insert_threaded_document(
+                    &db,
+                    &storage,
+                    &format!("Reply {i}.{j}"),
+                    Some((reply_post, reply_doc)),
+                );
+            }
+        }
+
+        let pruning = ReplyTreePruning {
+            depth: Some(1),
+            limit_per_node: Some(15),
+        };
+        let tree = db
+            .get_reply_tree_for_document_pruned(root_doc, pruning, &storage)
+            .unwrap()
+            .expect("tree should exist");
+
+        // Only 15 of the 40 direct replies are included; the other 25 become stubs
+        assert_eq!(tree.replies.len(), 15);
+        assert_eq!(tree.stubs.len(), 25);
+        for stub in &tree.stubs {
+            assert_eq!(stub.reply_count, 2);
+        }
+
+        // Included replies are at the depth limit, so their children are all stubs
+        for reply in &tree.replies {
+            assert_eq!(reply.replies.len(), 0);
+            assert_eq!(reply.stubs.len(), 2);
+            for stub in &reply.stubs {
+                assert_eq!(stub.reply_count, 0);
+            }
+        }
+    }
+
+    #[test]
+    fn test_expand_stub_returns_children() {
+        let db = create_test_database();
+        let storage = create_test_storage();
+
+        let (root_post, root_doc) =
+            insert_threaded_document(&db, &storage, "Thread Root", None);
+        let (reply_post, reply_doc) =
+            insert_threaded_document(&db, &storage, "Reply", Some((root_post, root_doc)));
+        for j in 0..3 {
+            insert_threaded_document(
+                &db,
+                &storage,
+                &format!("Child {j}"),
+                Some((reply_post, reply_doc)),
+            );
+        }
+
+        // Prune so the reply appears only as a stub
+        let pruning = ReplyTreePruning {
+            depth: Some(0),
+            limit_per_node: None,
+        };
+        let tree = db
+            .get_reply_tree_for_document_pruned(root_doc, pruning, &storage)
+            .unwrap()
+            .unwrap();
+        assert_eq!(tree.replies.len(), 0);
+        assert_eq!(tree.stubs.len(), 1);
+        let stub = &tree.stubs[0];
+        assert_eq!(stub.document_id, reply_doc);
+        assert_eq!(stub.reply_count, 3);
+
+        // Expanding the stub (one level deep) returns its children
+        let expanded = db
+            .get_reply_tree_for_document_pruned(
+                stub.document_id,
+                ReplyTreePruning {
+                    depth: Some(1),
+                    limit_per_node: None,
+                },
+                &storage,
+            )
+            .unwrap()
+            .unwrap();
+        assert_eq!(expanded.document.title, "Reply");
+        assert_eq!(expanded.replies.len(), 3);
+        assert_eq!(expanded.stubs.len(), 0);
+    }
+
+    #[test]
+    fn test_tombstoned_document_preserves_reply_tree() {
+        let db = create_test_database();
+        let storage = create_test_storage();
+
+        // root -> child -> grandchild, each on its own post
+        let (root_post, root_doc) = insert_threaded_document(&db, &storage, "Root", None);
+        let (child_post, child_doc) =
+            insert_threaded_document(&db, &storage, "Child", Some((root_post, root_doc)));
+        let (_, grandchild_doc) =
+            insert_threaded_document(&db, &storage, "Grandchild", Some((child_post, child_doc)));
+
+        // Deleting the mid-thread document tombstones it instead of removing it
+        let uploader = db.delete_document(child_doc).unwrap();
+        assert_eq!(uploader, "test_user");
+        let child_metadata = db
+            .get_document_metadata(child_doc)
+            .unwrap()
+            .expect("tombstoned row should survive");
+        assert!(child_metadata.deleted_at.is_some());
+        assert_eq!(child_metadata.title, "");
+
+        // The grandchild stays reachable through the placeholder node
+        let tree = db
+            .get_reply_tree_for_document(root_doc, &storage)
+            .unwrap()
+            .expect("tree should exist");
+        assert_eq!(tree.replies.len(), 1);
+        let placeholder = &tree.replies[0];
+        assert!(placeholder.document.deleted_at.is_some());
+        assert_eq!(
+            placeholder.content.message,
+            Some("[deleted by author]".to_string())
+        );
+        assert_eq!(placeholder.replies.len(), 1);
+        assert_eq!(placeholder.replies[0].document.title, "Grandchild");
+        assert_eq!(placeholder.replies[0].document.id, Some(grandchild_doc));
+
+        // A tombstone cannot be deleted a second time
+        assert!(matches!(
+            db.delete_document(child_doc),
+            Err(rusqlite::Error::QueryReturnedNoRows)
+        ));
+
+        // A leaf with no descendants is hard-deleted as before
+        db.delete_document(grandchild_doc).unwrap();
+        assert!(db.get_document_metadata(grandchild_doc).unwrap().is_none());
+
+        // Tombstoned roots drop out of the document list
+        let (other_root_post, other_root_doc) =
+            insert_threaded_document(&db, &storage, "Doomed Root", None);
+        insert_threaded_document(&db, &storage, "Reply", Some((other_root_post, other_root_doc)));
+        db.delete_document(other_root_doc).unwrap();
+
+        let listed: Vec<Option<i64>> = db
+            .get_top_level_documents_with_latest_reply(DocumentSort::New, None, -1, 0)
+            .unwrap()
+            .into_iter()
+            .map(|item| item.metadata.id)
+            .collect();
+        assert!(listed.contains(&Some(root_doc)));
+        assert!(!listed.contains(&Some(other_root_doc)));
+    }
+
     #[test]
     fn test_single_document_no_replies() {
         let db = create_test_database();
@@ -1480,4 +3193,318 @@ pub mod tests {
         );
         assert_eq!(tree.replies.len(), 0);
     }
+
+    #[test]
+    fn test_get_document_by_content_id() {
+        let db = create_test_database();
+        let storage = create_test_storage();
+        let doc_id = insert_dummy_document(&db, &storage, "Content addressed", None);
+
+        // Recompute the hash of the content insert_dummy_document stored
+        let content = DocumentContent {
+            message: Some("Test content for Content addressed".to_string()),
+            file: None,
+            files: Vec::new(),
+            url: None,
+        };
+        let content_id =
+            crate::storage::ContentAddressedStorage::hash_document_content(&content).unwrap();
+
+        let metadata = db.get_document_by_content_id(&content_id).unwrap().unwrap();
+        assert_eq!(metadata.id, Some(doc_id));
+        assert_eq!(metadata.content_id, content_id);
+
+        // A hash nothing was published under finds nothing
+        let unpublished = DocumentContent {
+            message: Some("never published".to_string()),
+            file: None,
+            files: Vec::new(),
+            url: None,
+        };
+        let unpublished_id =
+            crate::storage::ContentAddressedStorage::hash_document_content(&unpublished).unwrap();
+        assert!(
+            db.get_document_by_content_id(&unpublished_id)
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_document_list_issues_bounded_queries() {
+        use std::sync::atomic::Ordering;
+
+        let db = create_test_database();
+        let storage = create_test_storage();
+        for i in 0..500 {
+            insert_dummy_document(&db, &storage, &format!("Document {i}"), None);
+        }
+
+        // Converting the whole list resolves upvote counts and hidden flags in
+        // batches, so the query count stays flat instead of growing per row
+        db.query_count.store(0, Ordering::Relaxed);
+        let documents = db.get_all_documents_metadata().unwrap();
+        assert_eq!(documents.len(), 500);
+        let queries = db.query_count.load(Ordering::Relaxed);
+        assert!(
+            queries <= 4,
+            "listing 500 documents took {queries} query batches"
+        );
+
+        db.query_count.store(0, Ordering::Relaxed);
+        let list = db
+            .get_top_level_documents_with_latest_reply(DocumentSort::New, None, -1, 0)
+            .unwrap();
+        assert!(!list.is_empty());
+        let queries = db.query_count.load(Ordering::Relaxed);
+        assert!(
+            queries <= 4,
+            "top-level listing took {queries} query batches"
+        );
+    }
+
+    #[test]
+    fn test_document_list_sort_orders() {
+        let db = create_test_database();
+        let storage = create_test_storage();
+
+        let (post_a, doc_a) = insert_threaded_document(&db, &storage, "Doc A", None);
+        let (_post_b, doc_b) = insert_threaded_document(&db, &storage, "Doc B", None);
+        let (_post_c, doc_c) = insert_threaded_document(&db, &storage, "Doc C", None);
+
+        // Distinct creation times so orderings don't tie within one second
+        {
+            let conn = db.conn.lock().unwrap();
+            for (doc_id, age) in [(doc_a, "-3 hours"), (doc_b, "-2 hours"), (doc_c, "-1 hour")] {
+                conn.execute(
+                    "UPDATE documents SET created_at = datetime('now', ?1) WHERE id = ?2",
+                    rusqlite::params![age, doc_id],
+                )
+                .unwrap();
+            }
+        }
+
+        // A reply lands on A just now, making it the most recently active thread
+        insert_threaded_document(&db, &storage, "Reply to A", Some((post_a, doc_a)));
+
+        // B leads the all-time upvote count, but one of its upvotes is a month
+        // old; C's single upvote is recent
+        db.create_upvote(doc_b, "recent_b", "{}").unwrap();
+        db.create_upvote(doc_b, "old_b", "{}").unwrap();
+        db.create_upvote(doc_c, "recent_c", "{}").unwrap();
+        {
+            let conn = db.conn.lock().unwrap();
+            conn.execute(
+                "UPDATE upvotes SET created_at = datetime('now', '-30 days') WHERE username = 'old_b'",
+                [],
+            )
+            .unwrap();
+        }
+
+        let titles = |sort: DocumentSort, window: Option<i64>| -> Vec<String> {
+            db.get_top_level_documents_with_latest_reply(sort, window, -1, 0)
+                .unwrap()
+                .into_iter()
+                .map(|item| item.metadata.title)
+                .collect()
+        };
+
+        assert_eq!(titles(DocumentSort::New, None), ["Doc C", "Doc B", "Doc A"]);
+        assert_eq!(
+            titles(DocumentSort::Active, None),
+            ["Doc A", "Doc C", "Doc B"]
+        );
+        assert_eq!(titles(DocumentSort::Top, None), ["Doc B", "Doc C", "Doc A"]);
+        // Inside a seven-day window B's old upvote no longer counts; the
+        // resulting 1-1 tie breaks on document recency
+        assert_eq!(
+            titles(DocumentSort::Top, Some(7)),
+            ["Doc C", "Doc B", "Doc A"]
+        );
+    }
+
+    #[test]
+    fn test_pinned_posts_sort_first() {
+        let db = create_test_database();
+        let storage = create_test_storage();
+
+        let (post_a, _doc_a) = insert_threaded_document(&db, &storage, "Doc A", None);
+        let (post_b, _doc_b) = insert_threaded_document(&db, &storage, "Doc B", None);
+        let (_post_c, _doc_c) = insert_threaded_document(&db, &storage, "Doc C", None);
+
+        assert!(db.set_post_pinned(post_a, true).unwrap());
+        // A later pin sorts above an earlier one; distinct timestamps so the
+        // pinned_at ordering doesn't tie within one second
+        assert!(db.set_post_pinned(post_b, true).unwrap());
+        {
+            let conn = db.conn.lock().unwrap();
+            conn.execute(
+                "UPDATE posts SET pinned_at = datetime('now', '-1 hour') WHERE id = ?1",
+                [post_a],
+            )
+            .unwrap();
+        }
+        assert!(!db.set_post_pinned(9999, true).unwrap());
+
+        let list = db
+            .get_top_level_documents_with_latest_reply(DocumentSort::New, None, -1, 0)
+            .unwrap();
+        let summary: Vec<(String, bool)> = list
+            .into_iter()
+            .map(|item| (item.metadata.title, item.pinned))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                ("Doc B".to_string(), true),
+                ("Doc A".to_string(), true),
+                ("Doc C".to_string(), false),
+            ]
+        );
+
+        // Pins only reorder the list view; the flat metadata listing that
+        // backs search and the tag feeds is untouched
+        assert_eq!(db.get_all_documents_metadata().unwrap().len(), 3);
+
+        // Unpinning restores the normal sort
+        assert!(db.set_post_pinned(post_a, false).unwrap());
+        assert!(db.set_post_pinned(post_b, false).unwrap());
+        let list = db
+            .get_top_level_documents_with_latest_reply(DocumentSort::New, None, -1, 0)
+            .unwrap();
+        assert!(list.iter().all(|item| !item.pinned));
+    }
+
+    #[test]
+    fn test_post_depth_maintained_on_insert() {
+        let db = create_test_database();
+        let storage = create_test_storage();
+
+        let (root_post, root_doc) = insert_threaded_document(&db, &storage, "Root", None);
+        let (reply_post, reply_doc) =
+            insert_threaded_document(&db, &storage, "Reply", Some((root_post, root_doc)));
+        let (nested_post, _) =
+            insert_threaded_document(&db, &storage, "Nested", Some((reply_post, reply_doc)));
+
+        assert_eq!(db.get_post_depth(root_post).unwrap(), Some(0));
+        assert_eq!(db.get_post_depth(reply_post).unwrap(), Some(1));
+        assert_eq!(db.get_post_depth(nested_post).unwrap(), Some(2));
+        assert_eq!(db.get_post_depth(9999).unwrap(), None);
+    }
+
+    #[test]
+    fn test_depth_backfill_on_legacy_database() {
+        let mut conn = rusqlite::Connection::open_in_memory().unwrap();
+        // Bring the schema to the version just before the depth migration, so
+        // the posts below are shaped like rows from a pre-depth database
+        migrations::MIGRATIONS.to_version(&mut conn, 16).unwrap();
+
+        conn.execute_batch(
+            "INSERT INTO posts (id) VALUES (1);
+             INSERT INTO posts (id, parent_post_id, thread_root_post_id) VALUES (2, 1, 1);
+             INSERT INTO posts (id, parent_post_id, thread_root_post_id) VALUES (3, 2, 1);
+             INSERT INTO posts (id, parent_post_id, thread_root_post_id) VALUES (4, 1, 1);
+             INSERT INTO posts (id) VALUES (5);",
+        )
+        .unwrap();
+
+        migrations::MIGRATIONS.to_latest(&mut conn).unwrap();
+
+        let depth = |id: i64| -> i64 {
+            conn.query_row("SELECT depth FROM posts WHERE id = ?1", [id], |row| {
+                row.get(0)
+            })
+            .unwrap()
+        };
+        assert_eq!(depth(1), 0);
+        assert_eq!(depth(2), 1);
+        assert_eq!(depth(3), 2);
+        assert_eq!(depth(4), 1);
+        assert_eq!(depth(5), 0);
+    }
+
+    #[test]
+    fn test_search_documents() {
+        let db = create_test_database();
+        let storage = create_test_storage();
+
+        let (_, quantum_doc) =
+            insert_threaded_document(&db, &storage, "Quantum computing intro", None);
+        insert_threaded_document(&db, &storage, "Gardening tips", None);
+
+        // A keyword search returns the matching document and not the other one
+        let results = db.search_documents("quantum", 10, 0).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].id, Some(quantum_doc));
+        assert_eq!(results[0].title, "Quantum computing intro");
+
+        // The message text is indexed too; helper messages contain the title
+        let results = db.search_documents("intro", 10, 0).unwrap();
+        assert_eq!(results.len(), 1);
+
+        assert!(db.search_documents("zeppelin", 10, 0).unwrap().is_empty());
+
+        // Hidden documents are excluded from results
+        db.set_document_hidden(quantum_doc, true).unwrap();
+        assert!(db.search_documents("quantum", 10, 0).unwrap().is_empty());
+        db.set_document_hidden(quantum_doc, false).unwrap();
+
+        // Deleted documents drop out of the index
+        db.delete_document(quantum_doc).unwrap();
+        assert!(db.search_documents("quantum", 10, 0).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_username_alias_resolution() {
+        let db = create_test_database();
+        let storage = create_test_storage();
+
+        let (_, doc) = insert_threaded_document(&db, &storage, "Alice's Post", None);
+        {
+            let conn = db.conn.lock().unwrap();
+            conn.execute(
+                "UPDATE documents SET uploader_id = 'alice' WHERE id = ?1",
+                [doc],
+            )
+            .unwrap();
+        }
+        db.create_upvote(doc, "alice", "{}").unwrap();
+
+        // First sighting of the key just records the binding
+        assert_eq!(db.record_user_identity("pk-1", "alice").unwrap(), None);
+        let metadata = db.get_document_metadata(doc).unwrap().unwrap();
+        assert_eq!(metadata.display_name, None);
+
+        // Re-issuing the identity with a new username is a rename
+        assert_eq!(
+            db.record_user_identity("pk-1", "wonderland").unwrap(),
+            Some("alice".to_string())
+        );
+
+        // Historical documents keep uploader_id but expose the current name
+        let metadata = db.get_document_metadata(doc).unwrap().unwrap();
+        assert_eq!(metadata.uploader_id, "alice");
+        assert_eq!(metadata.display_name, Some("wonderland".to_string()));
+
+        // Upvote dedup sees through the rename in both directions
+        assert!(db.user_has_upvoted(doc, "wonderland").unwrap());
+        assert!(db.user_has_upvoted(doc, "alice").unwrap());
+        assert!(!db.user_has_upvoted(doc, "bob").unwrap());
+
+        // Resolution follows the alias chain across repeated renames
+        assert_eq!(
+            db.record_user_identity("pk-1", "mad-hatter").unwrap(),
+            Some("wonderland".to_string())
+        );
+        assert_eq!(db.resolve_display_name("alice").unwrap(), "mad-hatter");
+        assert_eq!(db.resolve_display_name("wonderland").unwrap(), "mad-hatter");
+        // Unknown usernames resolve to themselves
+        assert_eq!(db.resolve_display_name("bob").unwrap(), "bob");
+
+        // Re-submitting the current name is a no-op
+        assert_eq!(db.record_user_identity("pk-1", "mad-hatter").unwrap(), None);
+        let mut group = db.username_alias_group("mad-hatter").unwrap();
+        group.sort();
+        assert_eq!(group, vec!["alice", "mad-hatter", "wonderland"]);
+    }
 }
@@ -0,0 +1,560 @@
+//! Administrative maintenance endpoints.
+
+use std::{
+    sync::Arc,
+    time::{Duration, SystemTime},
+};
+
+use axum::{
+    extract::State,
+    http::{HeaderMap, StatusCode, header},
+    response::{IntoResponse, Json, Response},
+};
+use serde::{Deserialize, Serialize};
+
+/// How recent a blob may be before GC refuses to touch it. Content is stored
+/// before its documents row is inserted, so very young unreferenced blobs may
+/// belong to an in-flight publish.
+pub const GC_GRACE_PERIOD: Duration = Duration::from_secs(300);
+
+#[derive(Debug, Default, Serialize)]
+pub struct GcReport {
+    pub deleted_blobs: usize,
+    pub bytes_freed: u64,
+    pub skipped_recent: usize,
+}
+
+/// Delete content blobs that no documents row references.
+///
+/// Blobs are listed before the referenced set is queried, so a blob stored
+/// after the listing is never a deletion candidate; blobs stored just before
+/// the listing whose row has not landed yet are protected by `grace_period`.
+pub fn run_content_gc(
+    db: &crate::db::Database,
+    storage: &crate::storage::ContentAddressedStorage,
+    grace_period: Duration,
+) -> anyhow::Result<GcReport> {
+    let blobs = storage.list_blobs()?;
+    let referenced = db.get_referenced_content_ids()?;
+    let now = SystemTime::now();
+
+    let mut report = GcReport::default();
+    for blob in blobs {
+        if referenced.contains(&blob.hash) {
+            continue;
+        }
+        let age = now.duration_since(blob.modified).unwrap_or(Duration::ZERO);
+        if age < grace_period {
+            report.skipped_recent += 1;
+            continue;
+        }
+        storage.delete(&blob.hash)?;
+        report.deleted_blobs += 1;
+        report.bytes_freed += blob.size;
+    }
+
+    tracing::info!(
+        "Content GC: deleted {} blobs ({} bytes), skipped {} recent blobs",
+        report.deleted_blobs,
+        report.bytes_freed,
+        report.skipped_recent
+    );
+    Ok(report)
+}
+
+/// Pages copied per backup step before the source read lock is released,
+/// letting the serving connection write between steps.
+const BACKUP_PAGES_PER_STEP: std::os::raw::c_int = 64;
+/// Pause between backup steps
+const BACKUP_STEP_PAUSE: Duration = Duration::from_millis(25);
+
+/// One content blob in the snapshot manifest. Blobs are immutable and
+/// content-addressed, so hash plus size is enough to copy and verify them.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BlobManifestEntry {
+    pub hash: String,
+    pub size: u64,
+}
+
+/// The `manifest.json` written alongside the database snapshot
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BlobManifest {
+    pub created_at: String,
+    pub blob_count: usize,
+    pub total_bytes: u64,
+    pub blobs: Vec<BlobManifestEntry>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BackupReport {
+    pub snapshot_path: String,
+    pub database_file: String,
+    pub database_bytes: u64,
+    pub blob_count: usize,
+    pub blob_bytes: u64,
+}
+
+/// Take a consistent snapshot: an SQLite online backup of the database plus a
+/// manifest of the content blobs, written to a fresh timestamped directory
+/// under `backup_dir`.
+///
+/// The database is copied through a dedicated read-only connection using
+/// SQLite's backup API in small steps, so the serving connection's mutex is
+/// never held and writes proceed while the copy runs. The blobs themselves are
+/// immutable, so the manifest plus an rsync-style copy of the content
+/// directory reproduces the store.
+pub fn run_backup(
+    database_path: &str,
+    storage: &crate::storage::ContentAddressedStorage,
+    backup_dir: &std::path::Path,
+) -> anyhow::Result<BackupReport> {
+    if database_path == ":memory:" {
+        anyhow::bail!("Cannot back up an in-memory database");
+    }
+
+    let created_at = chrono::Utc::now();
+    let snapshot_path = backup_dir.join(format!(
+        "snapshot-{}",
+        created_at.format("%Y%m%dT%H%M%SZ")
+    ));
+    std::fs::create_dir_all(&snapshot_path)?;
+
+    let database_file = std::path::Path::new(database_path)
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| "app.db".to_string());
+    let backup_db_path = snapshot_path.join(&database_file);
+    let src = rusqlite::Connection::open_with_flags(
+        database_path,
+        rusqlite::OpenFlags::SQLITE_OPEN_READ_ONLY,
+    )?;
+    let mut dst = rusqlite::Connection::open(&backup_db_path)?;
+    rusqlite::backup::Backup::new(&src, &mut dst)?.run_to_completion(
+        BACKUP_PAGES_PER_STEP,
+        BACKUP_STEP_PAUSE,
+        None,
+    )?;
+    let database_bytes = std::fs::metadata(&backup_db_path)?.len();
+
+    let blobs = storage.list_blobs()?;
+    let manifest = BlobManifest {
+        created_at: created_at.to_rfc3339(),
+        blob_count: blobs.len(),
+        total_bytes: blobs.iter().map(|b| b.size).sum(),
+        blobs: blobs
+            .into_iter()
+            .map(|b| BlobManifestEntry {
+                hash: b.hash,
+                size: b.size,
+            })
+            .collect(),
+    };
+    std::fs::write(
+        snapshot_path.join("manifest.json"),
+        serde_json::to_string_pretty(&manifest)?,
+    )?;
+
+    let report = BackupReport {
+        snapshot_path: snapshot_path.to_string_lossy().to_string(),
+        database_file,
+        database_bytes,
+        blob_count: manifest.blob_count,
+        blob_bytes: manifest.total_bytes,
+    };
+    tracing::info!(
+        "Backup complete: {} ({} bytes of database, {} blobs totalling {} bytes in manifest)",
+        report.snapshot_path,
+        report.database_bytes,
+        report.blob_count,
+        report.blob_bytes
+    );
+    Ok(report)
+}
+
+pub async fn create_backup(
+    State(state): State<Arc<crate::AppState>>,
+    headers: HeaderMap,
+) -> Result<Json<BackupReport>, StatusCode> {
+    check_admin_token(state.config.admin_token.as_deref(), &headers)?;
+    let database_path = state.config.database_path.clone();
+    let storage = (*state.storage).clone();
+    let backup_dir = std::path::PathBuf::from(&state.config.backup_dir);
+    let report =
+        tokio::task::spawn_blocking(move || run_backup(&database_path, &storage, &backup_dir))
+            .await
+            .map_err(|e| {
+                tracing::error!("Backup task panicked: {e}");
+                StatusCode::INTERNAL_SERVER_ERROR
+            })?
+            .map_err(|e| {
+                tracing::error!("Backup failed: {e}");
+                StatusCode::INTERNAL_SERVER_ERROR
+            })?;
+    Ok(Json(report))
+}
+
+/// Background worker that takes a snapshot every `backup_interval_secs`
+pub async fn backup_worker(state: Arc<crate::AppState>) {
+    let interval_secs = state.config.backup_interval_secs;
+    if interval_secs == 0 {
+        return;
+    }
+    loop {
+        tokio::time::sleep(Duration::from_secs(interval_secs)).await;
+        let database_path = state.config.database_path.clone();
+        let storage = (*state.storage).clone();
+        let backup_dir = std::path::PathBuf::from(&state.config.backup_dir);
+        let result =
+            tokio::task::spawn_blocking(move || run_backup(&database_path, &storage, &backup_dir))
+                .await;
+        match result {
+            Ok(Ok(_)) => {}
+            Ok(Err(e)) => tracing::error!("Scheduled backup failed: {e}"),
+            Err(e) => tracing::error!("Scheduled backup task panicked: {e}"),
+        }
+    }
+}
+
+/// Require the `X-Admin-Token` header to match the configured token. Admin
+/// endpoints are disabled entirely when no token is configured.
+pub(crate) fn check_admin_token(
+    expected: Option<&str>,
+    headers: &HeaderMap,
+) -> Result<(), StatusCode> {
+    let Some(expected) = expected else {
+        return Err(StatusCode::NOT_FOUND);
+    };
+    let provided = headers
+        .get("x-admin-token")
+        .and_then(|v| v.to_str().ok())
+        .ok_or(StatusCode::UNAUTHORIZED)?;
+    if provided == expected {
+        Ok(())
+    } else {
+        Err(StatusCode::UNAUTHORIZED)
+    }
+}
+
+/// Short stable identifier for an admin token, safe to record in audit rows
+/// without persisting the token itself.
+pub(crate) fn admin_token_id(token: &str) -> String {
+    use hex::ToHex;
+    let digest: String = pod2::middleware::hash_str(token).encode_hex();
+    digest[..16].to_string()
+}
+
+pub async fn gc_content(
+    State(state): State<Arc<crate::AppState>>,
+    headers: HeaderMap,
+) -> Result<Json<GcReport>, StatusCode> {
+    check_admin_token(state.config.admin_token.as_deref(), &headers)?;
+    let report = run_content_gc(&state.db, &state.storage, GC_GRACE_PERIOD).map_err(|e| {
+        tracing::error!("Content GC failed: {e}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    Ok(Json(report))
+}
+
+/// How many recent upvote recount jobs `/admin/jobs` returns.
+const JOB_LIST_LIMIT: i64 = 100;
+
+pub async fn get_jobs(
+    State(state): State<Arc<crate::AppState>>,
+    headers: HeaderMap,
+) -> Result<Json<Vec<crate::db::UpvoteCountJob>>, StatusCode> {
+    check_admin_token(state.config.admin_token.as_deref(), &headers)?;
+    let jobs = state
+        .db
+        .get_upvote_recount_jobs(JOB_LIST_LIMIT)
+        .map_err(|e| {
+            tracing::error!("Failed to list upvote recount jobs: {e}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+    Ok(Json(jobs))
+}
+
+#[derive(Debug, Serialize)]
+pub struct ServerStats {
+    pub documents: i64,
+    pub posts: i64,
+    pub upvotes: i64,
+    pub identity_servers: i64,
+    pub upvote_count_pod_backlog: i64,
+    pub last_publish_at: Option<String>,
+    pub database_file_bytes: u64,
+    pub storage_blobs: usize,
+    pub storage_bytes: u64,
+}
+
+pub(crate) fn collect_server_stats(
+    db: &crate::db::Database,
+    storage: &crate::storage::ContentAddressedStorage,
+    database_path: &str,
+) -> anyhow::Result<ServerStats> {
+    let db_stats = db.get_stats()?;
+    let storage_stats = storage.stats()?;
+    // An in-memory or not-yet-flushed database simply reports zero bytes
+    let database_file_bytes = std::fs::metadata(database_path)
+        .map(|m| m.len())
+        .unwrap_or(0);
+
+    Ok(ServerStats {
+        documents: db_stats.document_count,
+        posts: db_stats.post_count,
+        upvotes: db_stats.upvote_count,
+        identity_servers: db_stats.identity_server_count,
+        upvote_count_pod_backlog: db_stats.upvote_count_pod_backlog,
+        last_publish_at: db_stats.last_publish_at,
+        database_file_bytes,
+        storage_blobs: storage_stats.blob_count,
+        storage_bytes: storage_stats.total_bytes,
+    })
+}
+
+pub async fn get_stats(
+    State(state): State<Arc<crate::AppState>>,
+    headers: HeaderMap,
+) -> Result<Json<ServerStats>, StatusCode> {
+    check_admin_token(state.config.admin_token.as_deref(), &headers)?;
+    let stats = collect_server_stats(&state.db, &state.storage, &state.config.database_path)
+        .map_err(|e| {
+            tracing::error!("Failed to collect server stats: {e}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+    Ok(Json(stats))
+}
+
+pub(crate) fn render_prometheus(stats: &ServerStats) -> String {
+    let mut out = String::new();
+    let mut gauge = |name: &str, help: &str, value: String| {
+        out.push_str(&format!(
+            "# HELP {name} {help}\n# TYPE {name} gauge\n{name} {value}\n"
+        ));
+    };
+    gauge(
+        "podnet_documents_total",
+        "Number of document revisions",
+        stats.documents.to_string(),
+    );
+    gauge(
+        "podnet_posts_total",
+        "Number of posts",
+        stats.posts.to_string(),
+    );
+    gauge(
+        "podnet_upvotes_total",
+        "Number of upvotes",
+        stats.upvotes.to_string(),
+    );
+    gauge(
+        "podnet_identity_servers_total",
+        "Number of registered identity servers",
+        stats.identity_servers.to_string(),
+    );
+    gauge(
+        "podnet_upvote_count_pod_backlog",
+        "Documents without an upvote count pod",
+        stats.upvote_count_pod_backlog.to_string(),
+    );
+    gauge(
+        "podnet_database_file_bytes",
+        "Size of the SQLite database file",
+        stats.database_file_bytes.to_string(),
+    );
+    gauge(
+        "podnet_storage_blobs",
+        "Number of content blobs on disk",
+        stats.storage_blobs.to_string(),
+    );
+    gauge(
+        "podnet_storage_bytes",
+        "Total size of content blobs on disk",
+        stats.storage_bytes.to_string(),
+    );
+    out
+}
+
+pub async fn get_metrics(
+    State(state): State<Arc<crate::AppState>>,
+    headers: HeaderMap,
+) -> Result<Response, StatusCode> {
+    check_admin_token(state.config.admin_token.as_deref(), &headers)?;
+    let stats = collect_server_stats(&state.db, &state.storage, &state.config.database_path)
+        .map_err(|e| {
+            tracing::error!("Failed to collect server stats: {e}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+    Ok((
+        [(
+            header::CONTENT_TYPE,
+            "text/plain; version=0.0.4; charset=utf-8",
+        )],
+        render_prometheus(&stats),
+    )
+        .into_response())
+}
+
+#[cfg(test)]
+mod tests {
+    use hex::ToHex;
+
+    use super::*;
+    use crate::{
+        db::{Database, tests::insert_dummy_document},
+        storage::ContentAddressedStorage,
+    };
+
+    #[tokio::test]
+    async fn test_gc_keeps_shared_and_referenced_blobs() {
+        let db = Database::new(":memory:").await.unwrap();
+        // The GC test needs its own storage directory so it cannot reclaim
+        // blobs belonging to concurrently running tests
+        let storage_path =
+            std::env::temp_dir().join(format!("podnet_gc_test_{}", rand::random::<u64>()));
+        let storage = ContentAddressedStorage::new(storage_path.to_str().unwrap()).unwrap();
+
+        // Two documents with identical content share one blob; a third owns its own
+        let shared_a = insert_dummy_document(&db, &storage, "Shared", None);
+        let _shared_b = insert_dummy_document(&db, &storage, "Shared", None);
+        let orphan = insert_dummy_document(&db, &storage, "Orphan", None);
+
+        let shared_hash: String = db
+            .get_document_metadata(shared_a)
+            .unwrap()
+            .unwrap()
+            .content_id
+            .encode_hex();
+        let orphan_hash: String = db
+            .get_document_metadata(orphan)
+            .unwrap()
+            .unwrap()
+            .content_id
+            .encode_hex();
+
+        // Delete one copy of the shared document and the orphan's only document
+        db.delete_document(shared_a).unwrap();
+        db.delete_document(orphan).unwrap();
+
+        // A fresh blob inside the grace period is left alone
+        let cautious = run_content_gc(&db, &storage, GC_GRACE_PERIOD).unwrap();
+        assert_eq!(cautious.deleted_blobs, 0);
+        assert_eq!(cautious.skipped_recent, 1);
+        assert!(storage.exists(&orphan_hash));
+
+        // With no grace period only the truly orphaned blob is removed
+        let report = run_content_gc(&db, &storage, Duration::ZERO).unwrap();
+        assert_eq!(report.deleted_blobs, 1);
+        assert!(report.bytes_freed > 0);
+        assert!(!storage.exists(&orphan_hash));
+        assert!(storage.exists(&shared_hash));
+
+        let _ = std::fs::remove_dir_all(storage_path);
+    }
+
+    #[tokio::test]
+    async fn test_backup_snapshot_is_consistent() {
+        let suffix = rand::random::<u64>();
+        let db_path = std::env::temp_dir().join(format!("podnet_backup_test_{suffix}.db"));
+        let storage_path = std::env::temp_dir().join(format!("podnet_backup_test_{suffix}_blobs"));
+        let backup_dir = std::env::temp_dir().join(format!("podnet_backup_test_{suffix}_out"));
+
+        let db = Database::new(db_path.to_str().unwrap()).await.unwrap();
+        let storage = ContentAddressedStorage::new(storage_path.to_str().unwrap()).unwrap();
+        insert_dummy_document(&db, &storage, "First", None);
+        insert_dummy_document(&db, &storage, "Second", None);
+
+        let report = run_backup(db_path.to_str().unwrap(), &storage, &backup_dir).unwrap();
+        assert!(report.database_bytes > 0);
+        assert_eq!(report.blob_count, 2);
+
+        // The backup database opens independently with the same row counts
+        let snapshot = std::path::Path::new(&report.snapshot_path);
+        let backup_db = rusqlite::Connection::open(snapshot.join(&report.database_file)).unwrap();
+        let documents: i64 = backup_db
+            .query_row("SELECT COUNT(*) FROM documents", [], |row| row.get(0))
+            .unwrap();
+        let posts: i64 = backup_db
+            .query_row("SELECT COUNT(*) FROM posts", [], |row| row.get(0))
+            .unwrap();
+        let stats = db.get_stats().unwrap();
+        assert_eq!(documents, stats.document_count);
+        assert_eq!(posts, stats.post_count);
+
+        // The manifest lists every blob on disk with its size
+        let manifest: BlobManifest =
+            serde_json::from_str(&std::fs::read_to_string(snapshot.join("manifest.json")).unwrap())
+                .unwrap();
+        assert_eq!(manifest.blob_count, 2);
+        for blob in &manifest.blobs {
+            assert!(storage.exists(&blob.hash));
+            assert!(blob.size > 0);
+        }
+
+        let _ = std::fs::remove_file(&db_path);
+        let _ = std::fs::remove_dir_all(storage_path);
+        let _ = std::fs::remove_dir_all(backup_dir);
+    }
+
+    #[test]
+    fn test_admin_token_enforcement() {
+        let mut headers = HeaderMap::new();
+
+        // No token configured: admin endpoints do not exist
+        assert_eq!(
+            check_admin_token(None, &headers),
+            Err(StatusCode::NOT_FOUND)
+        );
+
+        // Token configured but missing or wrong in the request
+        assert_eq!(
+            check_admin_token(Some("secret"), &headers),
+            Err(StatusCode::UNAUTHORIZED)
+        );
+        headers.insert("x-admin-token", "wrong".parse().unwrap());
+        assert_eq!(
+            check_admin_token(Some("secret"), &headers),
+            Err(StatusCode::UNAUTHORIZED)
+        );
+
+        headers.insert("x-admin-token", "secret".parse().unwrap());
+        assert_eq!(check_admin_token(Some("secret"), &headers), Ok(()));
+    }
+
+    #[tokio::test]
+    async fn test_server_stats_shape() {
+        let db = Database::new(":memory:").await.unwrap();
+        let storage_path =
+            std::env::temp_dir().join(format!("podnet_stats_test_{}", rand::random::<u64>()));
+        let storage = ContentAddressedStorage::new(storage_path.to_str().unwrap()).unwrap();
+
+        insert_dummy_document(&db, &storage, "Stats", None);
+
+        let stats = collect_server_stats(&db, &storage, ":memory:").unwrap();
+        assert_eq!(stats.documents, 1);
+        assert_eq!(stats.storage_blobs, 1);
+        assert!(stats.storage_bytes > 0);
+        assert_eq!(stats.upvote_count_pod_backlog, 1);
+        assert!(stats.last_publish_at.is_some());
+
+        let json = serde_json::to_value(&stats).unwrap();
+        for field in [
+            "documents",
+            "posts",
+            "upvotes",
+            "identity_servers",
+            "upvote_count_pod_backlog",
+            "last_publish_at",
+            "database_file_bytes",
+            "storage_blobs",
+            "storage_bytes",
+        ] {
+            assert!(json.get(field).is_some(), "missing field: {field}");
+        }
+
+        let metrics = render_prometheus(&stats);
+        assert!(metrics.contains("podnet_documents_total 1\n"));
+        assert!(metrics.contains("# TYPE podnet_storage_bytes gauge"));
+
+        let _ = std::fs::remove_dir_all(storage_path);
+    }
+}
@@ -1,125 +1,355 @@
 use std::{collections::HashMap, sync::Arc};
 
 use axum::{
-    extract::{Path, State},
-    http::{HeaderMap, StatusCode},
+    extract::{Path, Query, State},
+    http::{HeaderMap, HeaderValue, StatusCode, header},
     response::{IntoResponse, Json, Response},
 };
+use hex::{FromHex, ToHex};
 use pod2::middleware::{
     Key, Value,
     containers::{Dictionary, Set},
 };
 use podnet_models::{
-    DeleteRequest, Document, DocumentMetadata, PublishRequest,
+    DeleteRequest, Document, DocumentContent, DocumentListItem, DocumentMetadata,
+    DocumentReplyTree, PublishRequest, SearchResultItem,
     mainpod::{
         delete::verify_delete_verification_with_solver,
-        publish::verify_publish_verification_with_solver,
+        publish::{extract_publish_content_hash, verify_publish_verification_with_solver},
     },
 };
 
+use crate::db::{DocumentSort, ReplyTreePruning, ReplyTreeSkeleton};
+
+// Convert a SQLite timestamp to an HTTP date suitable for the Last-Modified header
+fn sqlite_timestamp_to_http_date(sqlite_timestamp: &str) -> Option<String> {
+    chrono::NaiveDateTime::parse_from_str(sqlite_timestamp, "%Y-%m-%d %H:%M:%S")
+        .ok()
+        .map(|naive| {
+            chrono::DateTime::<chrono::Utc>::from_naive_utc_and_offset(naive, chrono::Utc)
+                .to_rfc2822()
+        })
+}
+
+// Build the cache validator headers (ETag, Last-Modified, Cache-Control) shared by
+// the conditional GET handlers
+fn cache_validator_headers(etag: &str, last_modified: Option<&str>) -> HeaderMap {
+    let mut response_headers = HeaderMap::new();
+    if let Ok(value) = HeaderValue::from_str(etag) {
+        response_headers.insert(header::ETAG, value);
+    }
+    if let Some(http_date) = last_modified.and_then(sqlite_timestamp_to_http_date)
+        && let Ok(value) = HeaderValue::from_str(&http_date)
+    {
+        response_headers.insert(header::LAST_MODIFIED, value);
+    }
+    if let Ok(value) = HeaderValue::from_str("public, max-age=0, must-revalidate") {
+        response_headers.insert(header::CACHE_CONTROL, value);
+    }
+    response_headers
+}
+
+// Check the conditional request headers against the current validators.
+// If-None-Match takes precedence over If-Modified-Since, per RFC 9110.
+fn request_is_not_modified(
+    request_headers: &HeaderMap,
+    etag: &str,
+    last_modified: Option<&str>,
+) -> bool {
+    if let Some(if_none_match) = request_headers
+        .get(header::IF_NONE_MATCH)
+        .and_then(|v| v.to_str().ok())
+    {
+        return if_none_match
+            .split(',')
+            .any(|candidate| candidate.trim() == etag || candidate.trim() == "*");
+    }
+
+    if let Some(if_modified_since) = request_headers
+        .get(header::IF_MODIFIED_SINCE)
+        .and_then(|v| v.to_str().ok())
+        && let Some(last_modified_str) = last_modified
+        && let Ok(last_modified_time) =
+            chrono::NaiveDateTime::parse_from_str(last_modified_str, "%Y-%m-%d %H:%M:%S")
+        && let Ok(client_time) = chrono::DateTime::parse_from_rfc2822(if_modified_since)
+    {
+        let last_modified_utc =
+            chrono::DateTime::<chrono::Utc>::from_naive_utc_and_offset(last_modified_time, chrono::Utc);
+        return last_modified_utc <= client_time.with_timezone(&chrono::Utc);
+    }
+
+    false
+}
+
+#[derive(Debug, Default, serde::Deserialize)]
+pub struct DocumentListQuery {
+    /// Requested ordering: "new" (default), "top" or "active"
+    pub sort: Option<String>,
+    /// Recency window for the top sort, e.g. "7d" (all time if omitted)
+    pub window: Option<String>,
+    /// Page size (default 50, capped at 100)
+    pub limit: Option<i64>,
+    /// Rows to skip before the page starts (default 0)
+    pub offset: Option<i64>,
+    /// Attach a plain-text content excerpt to each item (default false)
+    pub include_excerpt: Option<bool>,
+}
+
+/// Maximum excerpt length for list views, in characters
+const LIST_EXCERPT_MAX_CHARS: usize = 200;
+
+// Fetch content for every item of a page concurrently and attach a plain-text
+// excerpt of each message. An unreadable content blob costs that item its
+// excerpt, not the whole page.
+async fn attach_excerpts(items: &mut [DocumentListItem], state: &Arc<crate::AppState>) {
+    let fetches: Vec<_> = items
+        .iter()
+        .map(|item| {
+            let storage = state.storage.clone();
+            let content_id = item.metadata.content_id;
+            tokio::spawn(async move { storage.retrieve_document_content_async(content_id).await })
+        })
+        .collect();
+
+    for (item, fetch) in items.iter_mut().zip(fetches) {
+        item.excerpt = match fetch.await {
+            Ok(Ok(Some(content))) => content
+                .message
+                .map(|message| super::feed::plain_text_excerpt(&message, LIST_EXCERPT_MAX_CHARS)),
+            Ok(Ok(None)) => None,
+            Ok(Err(e)) => {
+                tracing::warn!(
+                    "Failed to read content for excerpt of document {:?}: {e}",
+                    item.metadata.id
+                );
+                None
+            }
+            Err(e) => {
+                tracing::warn!("Excerpt fetch task failed: {e}");
+                None
+            }
+        };
+    }
+}
+
+/// Parse a window like "7d" (or a bare day count) into days
+fn parse_window_days(window: &str) -> Option<i64> {
+    let days: i64 = window.strip_suffix('d').unwrap_or(window).parse().ok()?;
+    (days > 0).then_some(days)
+}
+
 pub async fn get_documents(
+    Query(query): Query<DocumentListQuery>,
     State(state): State<Arc<crate::AppState>>,
     headers: HeaderMap,
 ) -> Result<Response, StatusCode> {
-    /*    // Extract conditional request header
-        let if_modified_since = headers.get(header::IF_MODIFIED_SINCE);
+    let sort = match query.sort.as_deref() {
+        None | Some("new") => DocumentSort::New,
+        Some("top") => DocumentSort::Top,
+        Some("active") => DocumentSort::Active,
+        Some(other) => {
+            tracing::debug!("Rejecting unknown document list sort: {other}");
+            return Err(StatusCode::BAD_REQUEST);
+        }
+    };
+    // The window only restricts the upvote aggregate, so it is meaningless
+    // outside the top sort
+    let window_days = match (sort, query.window.as_deref()) {
+        (DocumentSort::Top, Some(window)) => {
+            Some(parse_window_days(window).ok_or(StatusCode::BAD_REQUEST)?)
+        }
+        _ => None,
+    };
 
-        // Get the most recent modification time for cache validation
-        let last_modified = state
-            .db
-            .get_most_recent_modification_time()
-            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    // Derive validators from the most recent modification time plus document
+    // and upvote totals, so the ETag changes even when several documents land
+    // within the same timestamp second, and when an upvote reorders the top
+    // sort (or bumps a count) without touching any document row
+    let last_modified = state
+        .db
+        .get_most_recent_modification_time()
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let document_count = state
+        .db
+        .get_document_count()
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let upvote_count = state
+        .db
+        .get_total_upvote_count()
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    // Pinning reorders the list without touching document or upvote rows
+    let pin_fingerprint = state
+        .db
+        .get_pin_fingerprint()
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
 
-        let mut headers = HeaderMap::new();
+    let etag = format!(
+        "\"{}-{document_count}-{upvote_count}-{}\"",
+        last_modified.as_deref().unwrap_or("0").replace(' ', "T"),
+        pin_fingerprint.replace(' ', "T")
+    );
+    let response_headers = cache_validator_headers(&etag, last_modified.as_deref());
 
-        if let Some(last_modified_str) = &last_modified {
-            // Convert SQLite timestamp to HTTP date format
-            if let Ok(last_modified_time) =
-                chrono::NaiveDateTime::parse_from_str(last_modified_str, "%Y-%m-%d %H:%M:%S")
-            {
-                let last_modified_utc: chrono::DateTime<chrono::Utc> =
-                    chrono::DateTime::from_naive_utc_and_offset(last_modified_time, chrono::Utc);
-                let http_date = last_modified_utc.to_rfc2822();
+    if request_is_not_modified(&headers, &etag, last_modified.as_deref()) {
+        return Ok((StatusCode::NOT_MODIFIED, response_headers).into_response());
+    }
 
-                // Set Last-Modified header in proper HTTP date format
-                if let Ok(header_value) = HeaderValue::from_str(&http_date) {
-                    headers.insert("last-modified", header_value);
-                }
-            }
+    let limit = query.limit.unwrap_or(50).clamp(1, 100);
+    let offset = query.offset.unwrap_or(0).max(0);
 
-            // Check If-Modified-Since header (time-based conditional request)
-            if let Some(if_modified_since_value) = if_modified_since
-                && let Ok(if_modified_since_str) = if_modified_since_value.to_str()
-            {
-                // Parse the last_modified timestamp from SQLite format
-                if let Ok(last_modified_time) =
-                    chrono::NaiveDateTime::parse_from_str(last_modified_str, "%Y-%m-%d %H:%M:%S")
-                {
-                    let last_modified_utc: chrono::DateTime<chrono::Utc> =
-                        chrono::DateTime::from_naive_utc_and_offset(last_modified_time, chrono::Utc);
-
-                    // Parse the client's If-Modified-Since header
-                    if let Ok(client_time) = chrono::DateTime::parse_from_rfc2822(if_modified_since_str)
-                    {
-                        let client_time_utc = client_time.with_timezone(&chrono::Utc);
-
-                        // Compare timestamps - if content hasn't been modified since client's timestamp
-                        let is_modified = last_modified_utc > client_time_utc;
-
-                        if !is_modified {
-                            // Content not modified, return 304 Not Modified
-                            return Ok(Response::builder()
-                                .status(StatusCode::NOT_MODIFIED)
-                                .body(axum::body::Body::empty())
-                                .unwrap());
-                        }
-                    }
-                }
-            }
-        }
-    */
-    // Content has been modified or no conditional headers, return full response
-    // Fetch only top-level documents with latest reply info
-    let documents_list = state
+    // Fetch one page of top-level documents with latest reply info, plus the
+    // total over the same filter so clients can tell whether more pages exist
+    let mut documents = state
         .db
-        .get_top_level_documents_with_latest_reply()
+        .get_top_level_documents_with_latest_reply(sort, window_days, limit, offset)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let total = state
+        .db
+        .get_top_level_document_count()
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
 
-    // Set Cache-Control to allow caching but require validation
-    /*   if let Ok(cache_control) = HeaderValue::from_str("public, max-age=0, must-revalidate") {
-        headers.insert("cache-control", cache_control);
-    } */
+    if query.include_excerpt.unwrap_or(false) {
+        attach_excerpts(&mut documents, &state).await;
+    }
 
-    Ok((headers, Json(documents_list)).into_response())
+    let response = podnet_models::DocumentListResponse {
+        sort: sort.as_str().to_string(),
+        window_days,
+        page: podnet_models::PaginatedResponse::new(documents, total, limit, offset),
+    };
+    Ok((response_headers, Json(response)).into_response())
 }
 
 async fn get_document_from_db(
     document_id: i64,
     state: Arc<crate::AppState>,
 ) -> Result<Document, StatusCode> {
-    let document = state
+    let (metadata, pods) = state
         .db
-        .get_document(document_id, &state.storage)
+        .get_document_record(document_id)
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
         .ok_or(StatusCode::NOT_FOUND)?;
 
-    Ok(document)
+    // Content lives on disk; fetch it off the async executor once the
+    // database work is done
+    let content = state
+        .storage
+        .retrieve_document_content_async(metadata.content_id)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to retrieve content for document {document_id}: {e}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .ok_or_else(|| {
+            tracing::error!("Content missing from storage for document {document_id}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    Ok(Document {
+        metadata,
+        pods,
+        content,
+    })
 }
 
 pub async fn get_document_by_id(
     Path(id): Path<i64>,
     State(state): State<Arc<crate::AppState>>,
-) -> Result<Json<Document>, StatusCode> {
+    headers: HeaderMap,
+) -> Result<Response, StatusCode> {
     let document = get_document_from_db(id, state).await?;
-    Ok(Json(document))
+
+    // A document revision is immutable, so its creation time is a stable validator
+    let created_at = document.metadata.created_at.clone();
+    let etag = format!(
+        "\"doc-{id}-{}\"",
+        created_at.as_deref().unwrap_or("0").replace(' ', "T")
+    );
+    let response_headers = cache_validator_headers(&etag, created_at.as_deref());
+
+    if request_is_not_modified(&headers, &etag, created_at.as_deref()) {
+        return Ok((StatusCode::NOT_MODIFIED, response_headers).into_response());
+    }
+
+    Ok((response_headers, Json(document)).into_response())
+}
+
+// Content-addressed lookup: resolve a content hash to the document metadata
+// that carries it, for deduplication checks and deep links
+pub async fn get_document_by_content_id(
+    Path(hash): Path<String>,
+    State(state): State<Arc<crate::AppState>>,
+) -> Result<Json<DocumentMetadata>, StatusCode> {
+    let content_id = pod2::middleware::Hash::from_hex(&hash).map_err(|e| {
+        tracing::error!("Invalid content hash {hash}: {e}");
+        StatusCode::BAD_REQUEST
+    })?;
+
+    let metadata = state
+        .db
+        .get_document_by_content_id(&content_id)
+        .map_err(|e| {
+            tracing::error!("Database error looking up document by content id: {e}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    Ok(Json(metadata))
+}
+
+fn default_true() -> bool {
+    true
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct PublishQuery {
+    /// Whether thread-root authors are auto-subscribed to replies in their
+    /// thread; pass `?subscribe=false` to opt out
+    #[serde(default = "default_true")]
+    pub subscribe: bool,
 }
 
 pub async fn publish_document(
     State(state): State<Arc<crate::AppState>>,
+    Query(query): Query<PublishQuery>,
     Json(payload): Json<PublishRequest>,
-) -> Result<Json<Document>, StatusCode> {
+) -> Result<Response, StatusCode> {
     tracing::info!("Starting document publish with main pod verification");
 
+    // Enforce content limits first: these checks are cheap and run before any
+    // pod verification or storage write, so an over-limit publish costs the
+    // server nothing
+    if let Err(violation) =
+        state
+            .config
+            .content_limits
+            .validate(
+                &payload.title,
+                &payload.content,
+                &payload.tags,
+                &payload.attachments,
+            )
+    {
+        tracing::warn!(
+            "Publish rejected by content limits ({}): {}",
+            violation.field,
+            violation.message
+        );
+        let status = if violation.oversize {
+            StatusCode::PAYLOAD_TOO_LARGE
+        } else {
+            StatusCode::UNPROCESSABLE_ENTITY
+        };
+        return Ok((
+            status,
+            Json(serde_json::json!({
+                "field": violation.field,
+                "error": violation.message,
+            })),
+        )
+            .into_response());
+    }
+
     // Validate the document content
     payload.content.validate().map_err(|e| {
         tracing::error!("Document content validation failed: {e}");
@@ -127,10 +357,57 @@ pub async fn publish_document(
     })?;
     tracing::info!("✓ Document content validated");
 
+    // Every attachment reference in the content must come with exactly the
+    // bytes it commits to, and every uploaded blob must be referenced
+    let uploaded_blobs: HashMap<String, &[u8]> = payload
+        .attachments
+        .iter()
+        .map(|bytes| {
+            (
+                podnet_models::DocumentFile::content_id_for(bytes).encode_hex(),
+                bytes.as_slice(),
+            )
+        })
+        .collect();
+    let referenced_blobs: std::collections::HashSet<String> = payload
+        .content
+        .files
+        .iter()
+        .map(|file| file.content_id.encode_hex())
+        .collect();
+    if let Some(missing) = referenced_blobs
+        .iter()
+        .find(|hash| !uploaded_blobs.contains_key(*hash))
+    {
+        tracing::warn!("Publish references attachment {missing} but its bytes were not uploaded");
+        return Ok((
+            StatusCode::UNPROCESSABLE_ENTITY,
+            Json(serde_json::json!({
+                "field": "attachments",
+                "error": format!("no uploaded bytes hash to referenced attachment {missing}"),
+            })),
+        )
+            .into_response());
+    }
+    if let Some(stray) = uploaded_blobs
+        .keys()
+        .find(|hash| !referenced_blobs.contains(*hash))
+    {
+        tracing::warn!("Publish uploaded attachment {stray} that content.files does not reference");
+        return Ok((
+            StatusCode::UNPROCESSABLE_ENTITY,
+            Json(serde_json::json!({
+                "field": "attachments",
+                "error": format!("uploaded attachment {stray} is not referenced by content.files"),
+            })),
+        )
+            .into_response());
+    }
+
     // Validate reply content restrictions
     if payload.reply_to.is_some() {
         // Replies can only be messages, not files or URLs
-        if payload.content.file.is_some() {
+        if payload.content.file.is_some() || !payload.content.files.is_empty() {
             tracing::error!("Replies cannot contain file attachments");
             return Err(StatusCode::BAD_REQUEST);
         }
@@ -162,11 +439,54 @@ pub async fn publish_document(
     })?;
     tracing::info!("✓ Main pod proof verified");
 
+    // The pod must commit to the exact content being published: a valid pod
+    // proven over different content cannot be attached to this upload. This
+    // runs before any storage write, so a mismatched publish costs nothing
+    let claimed_content_hash = extract_publish_content_hash(&payload.main_pod).map_err(|e| {
+        tracing::error!("Failed to extract content hash from main pod: {e}");
+        StatusCode::BAD_REQUEST
+    })?;
+    let payload_content_hash =
+        crate::storage::ContentAddressedStorage::hash_document_content(&payload.content).map_err(
+            |e| {
+                tracing::error!("Failed to hash document content: {e}");
+                StatusCode::INTERNAL_SERVER_ERROR
+            },
+        )?;
+    if claimed_content_hash != payload_content_hash {
+        tracing::error!(
+            "Main pod commits to content hash {claimed_content_hash} but uploaded content hashes to {payload_content_hash}"
+        );
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    tracing::info!("✓ Main pod commits to the uploaded content hash");
+
+    // Store attachment blobs before the content that references them, so a
+    // crash in between only leaves orphans for the GC, never dangling
+    // references
+    for file in &payload.content.files {
+        let hash_hex: String = file.content_id.encode_hex();
+        let bytes = uploaded_blobs[&hash_hex].to_vec();
+        state.storage.store_bytes_async(bytes).await.map_err(|e| {
+            tracing::error!("Failed to store attachment {hash_hex}: {e}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+        state
+            .db
+            .record_attachment(&hash_hex, &file.name, &file.mime)
+            .map_err(|e| {
+                tracing::error!("Failed to record attachment {hash_hex}: {e}");
+                StatusCode::INTERNAL_SERVER_ERROR
+            })?;
+        tracing::info!("Attachment stored: {} ({hash_hex})", file.name);
+    }
+
     // Store the content first to get its hash for verification
     tracing::info!("Storing content in content-addressed storage");
     let stored_content_hash = state
         .storage
-        .store_document_content(&payload.content)
+        .store_document_content_async(payload.content.clone())
+        .await
         .map_err(|e| {
             tracing::error!("Failed to store content: {e}");
             StatusCode::INTERNAL_SERVER_ERROR
@@ -253,42 +573,60 @@ pub async fn publish_document(
     let mut verification_succeeded = false;
     let mut identity_server_pk = None;
 
-    for identity_server in &identity_servers {
-        // Parse the identity server public key from database
-        let server_pk: pod2::backends::plonky2::primitives::ec::curve::Point =
-            serde_json::from_str(&identity_server.public_key).map_err(|e| {
-                tracing::error!("Failed to parse identity server public key: {e}");
-                StatusCode::INTERNAL_SERVER_ERROR
-            })?;
-
-        let server_pk_value = Value::from(server_pk);
+    'servers: for identity_server in &identity_servers {
+        // Skip revoked servers unless the identity pod predates the revocation
+        if !super::registration::identity_server_accepts_pod(
+            identity_server,
+            payload.identity_pod_issued_at.as_deref(),
+        ) {
+            tracing::info!(
+                "Skipping revoked identity server: {}",
+                identity_server.server_id
+            );
+            continue;
+        }
 
-        // Try verification with this identity server
-        tracing::info!(
-            "Trying verification with identity server: {}",
-            identity_server.server_id
-        );
-        match verify_publish_verification_with_solver(
-            &payload.main_pod,
-            &payload.username,
-            &expected_data,
-            &server_pk_value,
+        // Try every key that could have signed the identity pod (the current
+        // key, plus rotated-out keys valid at the claimed issuance time)
+        for key_json in super::registration::candidate_verification_keys(
+            &state.db,
+            identity_server,
+            payload.identity_pod_issued_at.as_deref(),
         ) {
-            Ok(_) => {
-                tracing::info!(
-                    "✓ Solver verification succeeded with identity server: {}",
-                    identity_server.server_id
-                );
-                verification_succeeded = true;
-                identity_server_pk = Some(server_pk);
-                break;
-            }
-            Err(_) => {
-                tracing::debug!(
-                    "Verification failed with identity server: {}",
-                    identity_server.server_id
-                );
-                continue;
+            let server_pk: pod2::backends::plonky2::primitives::ec::curve::Point =
+                serde_json::from_str(&key_json).map_err(|e| {
+                    tracing::error!("Failed to parse identity server public key: {e}");
+                    StatusCode::INTERNAL_SERVER_ERROR
+                })?;
+
+            let server_pk_value = Value::from(server_pk);
+
+            // Try verification with this identity server
+            tracing::info!(
+                "Trying verification with identity server: {}",
+                identity_server.server_id
+            );
+            match verify_publish_verification_with_solver(
+                &payload.main_pod,
+                &payload.username,
+                &expected_data,
+                &server_pk_value,
+            ) {
+                Ok(_) => {
+                    tracing::info!(
+                        "✓ Solver verification succeeded with identity server: {}",
+                        identity_server.server_id
+                    );
+                    verification_succeeded = true;
+                    identity_server_pk = Some(server_pk);
+                    break 'servers;
+                }
+                Err(_) => {
+                    tracing::debug!(
+                        "Verification failed with identity server: {}",
+                        identity_server.server_id
+                    );
+                }
             }
         }
     }
@@ -305,6 +643,15 @@ pub async fn publish_document(
         payload.username
     );
 
+    // Rate limit by verified username now that we trust the claimed identity
+    if let Err(retry_after) = state.rate_limiters.publish.check(&payload.username) {
+        tracing::warn!(
+            "Publish rate limit exceeded for user {}, retry after {retry_after}s",
+            payload.username
+        );
+        return Ok(crate::rate_limit::too_many_requests(retry_after));
+    }
+
     // Use the data from the request for further processing
     let uploader_username = &payload.username;
     let post_id = payload.post_id.unwrap_or(-1);
@@ -346,6 +693,35 @@ pub async fn publish_document(
             );
             return Err(StatusCode::BAD_REQUEST);
         }
+        // Enforce the reply depth limit before creating the reply post
+        let parent_depth = state
+            .db
+            .get_post_depth(target_doc.post_id)
+            .map_err(|e| {
+                tracing::error!("Failed to read depth of post {}: {e}", target_doc.post_id);
+                StatusCode::INTERNAL_SERVER_ERROR
+            })?
+            .ok_or(StatusCode::NOT_FOUND)?;
+        let max_reply_depth = i64::from(state.config.max_reply_depth);
+        if parent_depth + 1 > max_reply_depth {
+            tracing::warn!(
+                "Rejecting reply to post {}: depth {} exceeds max_reply_depth {}",
+                target_doc.post_id,
+                parent_depth + 1,
+                max_reply_depth
+            );
+            return Ok((
+                StatusCode::UNPROCESSABLE_ENTITY,
+                Json(serde_json::json!({
+                    "field": "reply_to",
+                    "error": format!(
+                        "reply would be at depth {}, maximum reply depth is {max_reply_depth}",
+                        parent_depth + 1
+                    ),
+                })),
+            )
+                .into_response());
+        }
         // Create a new post for the reply
         let new_post_id = state.db.create_post().map_err(|e| {
             tracing::error!("Failed to create reply post: {e}");
@@ -466,7 +842,8 @@ pub async fn publish_document(
             payload.reply_to.clone(),
             Some(post_id), // Store original requested post_id for verification
             &payload.title,
-            &state.storage,
+            &payload.content,
+            query.subscribe,
         )
         .map_err(|e| {
             tracing::error!("Failed to create document: {e}");
@@ -474,6 +851,16 @@ pub async fn publish_document(
         })?;
     tracing::info!("Document created with ID: {:?}", document.metadata.id);
 
+    if let Some(document_id) = document.metadata.id {
+        super::events::emit_document_created(
+            &state,
+            final_post_id,
+            document_id,
+            &payload.title,
+            payload.reply_to.clone(),
+        );
+    }
+
     // // Spawn background task to generate base case upvote count pod
     if let Some(document_id) = document.metadata.id {
         let state_clone = state.clone();
@@ -495,7 +882,7 @@ pub async fn publish_document(
     }
 
     // tracing::info!("Document publish completed successfully using main pod verification");
-    Ok(Json(document))
+    Ok(Json(document).into_response())
 }
 
 pub async fn get_document_replies(
@@ -519,19 +906,403 @@ pub async fn get_document_replies(
     Ok(Json(replies))
 }
 
+#[derive(Debug, Default, serde::Deserialize)]
+pub struct SearchQuery {
+    /// Full-text query over document titles and messages
+    pub q: String,
+    /// Maximum number of results (default 20, capped at 100)
+    pub limit: Option<i64>,
+    /// Results to skip before the page starts (default 0)
+    pub offset: Option<i64>,
+    /// Attach the FTS snippet around the match to each hit (default false)
+    pub include_excerpt: Option<bool>,
+}
+
+pub async fn search_documents(
+    Query(query): Query<SearchQuery>,
+    State(state): State<Arc<crate::AppState>>,
+) -> Result<Json<podnet_models::PaginatedResponse<SearchResultItem>>, StatusCode> {
+    let q = query.q.trim();
+    if q.is_empty() {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    let limit = query.limit.unwrap_or(20).clamp(1, 100);
+    let offset = query.offset.unwrap_or(0).max(0);
+
+    // FTS reports malformed match expressions as query errors
+    let map_search_err = |e: rusqlite::Error| {
+        tracing::warn!("Search for {q:?} failed: {e}");
+        StatusCode::BAD_REQUEST
+    };
+    let results: Vec<SearchResultItem> = if query.include_excerpt.unwrap_or(false) {
+        state
+            .db
+            .search_documents_with_snippets(q, limit, offset)
+            .map_err(map_search_err)?
+            .into_iter()
+            .map(|(metadata, snippet)| SearchResultItem {
+                metadata,
+                excerpt: Some(snippet),
+            })
+            .collect()
+    } else {
+        state
+            .db
+            .search_documents(q, limit, offset)
+            .map_err(map_search_err)?
+            .into_iter()
+            .map(|metadata| SearchResultItem {
+                metadata,
+                excerpt: None,
+            })
+            .collect()
+    };
+    let total = state.db.count_search_documents(q).map_err(|e| {
+        tracing::warn!("Search count for {q:?} failed: {e}");
+        StatusCode::BAD_REQUEST
+    })?;
+    tracing::info!("Search for {q:?} matched {total} documents");
+    Ok(Json(podnet_models::PaginatedResponse::new(
+        results, total, limit, offset,
+    )))
+}
+
+#[derive(Debug, Default, serde::Deserialize)]
+pub struct ReplyTreeQuery {
+    /// Maximum reply depth below the requested document (unlimited if omitted)
+    pub depth: Option<usize>,
+    /// Maximum number of replies per node; excess replies become stubs
+    pub limit_per_node: Option<usize>,
+}
+
+// Fetch content for every node of a reply tree skeleton concurrently, then
+// assemble the hydrated tree. Each distinct content id is read from storage
+// at most once, on the blocking pool.
+async fn hydrate_reply_tree(
+    skeleton: ReplyTreeSkeleton,
+    state: &Arc<crate::AppState>,
+) -> Result<DocumentReplyTree, StatusCode> {
+    fn collect_content_ids(
+        skeleton: &ReplyTreeSkeleton,
+        ids: &mut HashMap<String, pod2::middleware::Hash>,
+    ) {
+        ids.entry(skeleton.document.content_id.encode_hex())
+            .or_insert(skeleton.document.content_id);
+        for reply in &skeleton.replies {
+            collect_content_ids(reply, ids);
+        }
+    }
+
+    fn attach_content(
+        skeleton: ReplyTreeSkeleton,
+        contents: &HashMap<String, DocumentContent>,
+    ) -> Option<DocumentReplyTree> {
+        let content = contents
+            .get(&skeleton.document.content_id.encode_hex::<String>())?
+            .clone();
+        let replies = skeleton
+            .replies
+            .into_iter()
+            .map(|reply| attach_content(reply, contents))
+            .collect::<Option<Vec<_>>>()?;
+        Some(DocumentReplyTree {
+            document: skeleton.document,
+            content,
+            replies,
+            stubs: skeleton.stubs,
+        })
+    }
+
+    let mut ids = HashMap::new();
+    collect_content_ids(&skeleton, &mut ids);
+
+    let fetches: Vec<_> = ids
+        .into_iter()
+        .map(|(hash_hex, content_id)| {
+            let storage = state.storage.clone();
+            tokio::spawn(async move {
+                (
+                    hash_hex,
+                    storage.retrieve_document_content_async(content_id).await,
+                )
+            })
+        })
+        .collect();
+
+    let mut contents = HashMap::new();
+    for fetch in fetches {
+        let (hash_hex, result) = fetch.await.map_err(|e| {
+            tracing::error!("Reply tree content fetch task failed: {e}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+        let content = result
+            .map_err(|e| {
+                tracing::error!("Failed to retrieve reply tree content {hash_hex}: {e}");
+                StatusCode::INTERNAL_SERVER_ERROR
+            })?
+            .ok_or_else(|| {
+                tracing::error!("Reply tree content {hash_hex} missing from storage");
+                StatusCode::INTERNAL_SERVER_ERROR
+            })?;
+        contents.insert(hash_hex, content);
+    }
+
+    attach_content(skeleton, &contents).ok_or(StatusCode::INTERNAL_SERVER_ERROR)
+}
+
 pub async fn get_document_reply_tree(
     Path(id): Path<i64>,
+    Query(params): Query<ReplyTreeQuery>,
+    State(state): State<Arc<crate::AppState>>,
+) -> Result<Json<DocumentReplyTree>, StatusCode> {
+    let pruning = ReplyTreePruning {
+        depth: params.depth,
+        limit_per_node: params.limit_per_node,
+    };
+
+    let skeleton = state
+        .db
+        .get_reply_tree_skeleton_pruned(id, pruning)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    let reply_tree = hydrate_reply_tree(skeleton, &state).await?;
+
+    Ok(Json(reply_tree))
+}
+
+// Expand a collapsed stub node from a pruned reply tree. Returns the subtree rooted
+// at the stub document, one level deep by default.
+pub async fn expand_reply_tree_node(
+    Path(id): Path<i64>,
+    Query(params): Query<ReplyTreeQuery>,
     State(state): State<Arc<crate::AppState>>,
-) -> Result<Json<podnet_models::DocumentReplyTree>, StatusCode> {
-    let reply_tree = state
+) -> Result<Json<DocumentReplyTree>, StatusCode> {
+    let pruning = ReplyTreePruning {
+        depth: Some(params.depth.unwrap_or(1)),
+        limit_per_node: params.limit_per_node,
+    };
+
+    let skeleton = state
         .db
-        .get_reply_tree_for_document(id, &state.storage)
+        .get_reply_tree_skeleton_pruned(id, pruning)
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
         .ok_or(StatusCode::NOT_FOUND)?;
 
+    let reply_tree = hydrate_reply_tree(skeleton, &state).await?;
+
     Ok(Json(reply_tree))
 }
 
+#[derive(Debug, Default, serde::Deserialize)]
+pub struct ThreadExportQuery {
+    /// Export format: "json" (default) or "markdown"
+    pub format: Option<String>,
+}
+
+// Render one markdown block per tree node in reading order, so a large thread
+// can be streamed chunk by chunk instead of concatenated into one string.
+// Reply depth is expressed with nested blockquotes; file attachments are
+// referenced by content hash rather than inlined.
+fn render_markdown_chunks(tree: &DocumentReplyTree, depth: usize, chunks: &mut Vec<String>) {
+    let quote = "> ".repeat(depth);
+    let meta = &tree.document;
+    let mut block = String::new();
+
+    block.push_str(&format!("{quote}### {}\n", meta.title));
+    block.push_str(&format!(
+        "{quote}*by {} at {} — {} upvote{}*\n{quote}\n",
+        meta.uploader_id,
+        meta.created_at.as_deref().unwrap_or("unknown time"),
+        meta.upvote_count,
+        if meta.upvote_count == 1 { "" } else { "s" },
+    ));
+
+    if let Some(message) = &tree.content.message {
+        for line in message.lines() {
+            block.push_str(&format!("{quote}{line}\n"));
+        }
+        block.push_str(&format!("{quote}\n"));
+    }
+    if let Some(file) = &tree.content.file {
+        block.push_str(&format!(
+            "{quote}Attachment: `{}` ({}, content {})\n{quote}\n",
+            file.name,
+            file.mime_type,
+            meta.content_id.encode_hex::<String>(),
+        ));
+    }
+    for file in &tree.content.files {
+        block.push_str(&format!(
+            "{quote}Attachment: `{}` ({}, content {})\n{quote}\n",
+            file.name,
+            file.mime,
+            file.content_id.encode_hex::<String>(),
+        ));
+    }
+    if let Some(url) = &tree.content.url {
+        block.push_str(&format!("{quote}URL: <{url}>\n{quote}\n"));
+    }
+
+    chunks.push(block);
+
+    for reply in &tree.replies {
+        render_markdown_chunks(reply, depth + 1, chunks);
+    }
+    for stub in &tree.stubs {
+        chunks.push(format!(
+            "{quote}> *({} more replies under document {})*\n\n",
+            stub.reply_count, stub.document_id
+        ));
+    }
+}
+
+fn attachment_headers(content_type: &str, filename: &str) -> Result<HeaderMap, StatusCode> {
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        header::CONTENT_TYPE,
+        HeaderValue::from_str(content_type).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?,
+    );
+    headers.insert(
+        header::CONTENT_DISPOSITION,
+        HeaderValue::from_str(&format!("attachment; filename=\"{filename}\""))
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?,
+    );
+    Ok(headers)
+}
+
+// Export a full thread as a downloadable archive: either the reply tree JSON
+// as served by /documents/:id/reply-tree, or a markdown transcript
+pub async fn export_document_thread(
+    Path(id): Path<i64>,
+    Query(query): Query<ThreadExportQuery>,
+    State(state): State<Arc<crate::AppState>>,
+) -> Result<Response, StatusCode> {
+    let skeleton = state
+        .db
+        .get_reply_tree_skeleton_pruned(id, ReplyTreePruning::default())
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+    let tree = hydrate_reply_tree(skeleton, &state).await?;
+
+    match query.format.as_deref() {
+        None | Some("json") => {
+            let body = serde_json::to_vec(&tree).map_err(|e| {
+                tracing::error!("Failed to serialize thread export for document {id}: {e}");
+                StatusCode::INTERNAL_SERVER_ERROR
+            })?;
+            let headers = attachment_headers("application/json", &format!("thread-{id}.json"))?;
+            Ok((headers, body).into_response())
+        }
+        Some("markdown") => {
+            let mut chunks = Vec::new();
+            render_markdown_chunks(&tree, 0, &mut chunks);
+            let body = axum::body::Body::from_stream(tokio_stream::iter(
+                chunks.into_iter().map(Ok::<_, std::convert::Infallible>),
+            ));
+            let headers =
+                attachment_headers("text/markdown; charset=utf-8", &format!("thread-{id}.md"))?;
+            Ok((headers, body).into_response())
+        }
+        Some(other) => {
+            tracing::debug!("Rejected unknown thread export format: {other}");
+            Err(StatusCode::BAD_REQUEST)
+        }
+    }
+}
+
+/// Parse a single-range `Range` header ("bytes=a-b", "bytes=a-" or "bytes=-n")
+/// into an inclusive byte span within a blob of `len` bytes. Multipart ranges
+/// are not supported; a suffix longer than the blob covers the whole blob.
+fn parse_byte_range(header: &str, len: u64) -> Option<(u64, u64)> {
+    let spec = header.strip_prefix("bytes=")?;
+    if spec.contains(',') {
+        return None;
+    }
+    let (start, end) = spec.split_once('-')?;
+    let range = match (start.is_empty(), end.is_empty()) {
+        (false, false) => (start.parse().ok()?, end.parse().ok()?),
+        (false, true) => (start.parse().ok()?, len.checked_sub(1)?),
+        (true, false) => {
+            let suffix: u64 = end.parse().ok()?;
+            if suffix == 0 {
+                return None;
+            }
+            (len.saturating_sub(suffix), len.checked_sub(1)?)
+        }
+        (true, true) => return None,
+    };
+    (range.0 <= range.1 && range.1 < len).then_some(range)
+}
+
+// Serve a raw content blob by hash, primarily for document attachments. The
+// content type comes from the attachment metadata recorded at publish time;
+// single byte ranges are honored so media can be streamed.
+pub async fn get_content(
+    Path(hash): Path<String>,
+    State(state): State<Arc<crate::AppState>>,
+    headers: HeaderMap,
+) -> Result<Response, StatusCode> {
+    // The hash doubles as a storage path component, so accept nothing but a
+    // full hex digest
+    if hash.len() != 64 || !hash.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let bytes = state
+        .storage
+        .retrieve_bytes_async(hash.clone())
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to read content blob {hash}: {e}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    let mime = state
+        .db
+        .get_attachment(&hash)
+        .map_err(|e| {
+            tracing::error!("Failed to look up attachment metadata for {hash}: {e}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .map(|(_, mime)| mime)
+        .unwrap_or_else(|| "application/octet-stream".to_string());
+
+    let mut response_headers = HeaderMap::new();
+    response_headers.insert(
+        header::CONTENT_TYPE,
+        HeaderValue::from_str(&mime)
+            .unwrap_or_else(|_| HeaderValue::from_static("application/octet-stream")),
+    );
+    response_headers.insert(header::ACCEPT_RANGES, HeaderValue::from_static("bytes"));
+    // Content-addressed blobs never change under their hash
+    response_headers.insert(
+        header::CACHE_CONTROL,
+        HeaderValue::from_static("public, max-age=31536000, immutable"),
+    );
+
+    let total = bytes.len() as u64;
+    let Some(range_header) = headers.get(header::RANGE).and_then(|v| v.to_str().ok()) else {
+        return Ok((response_headers, bytes).into_response());
+    };
+
+    let Some((start, end)) = parse_byte_range(range_header, total) else {
+        tracing::debug!("Unsatisfiable range {range_header:?} for {total}-byte blob {hash}");
+        if let Ok(value) = HeaderValue::from_str(&format!("bytes */{total}")) {
+            response_headers.insert(header::CONTENT_RANGE, value);
+        }
+        return Ok((StatusCode::RANGE_NOT_SATISFIABLE, response_headers).into_response());
+    };
+    response_headers.insert(
+        header::CONTENT_RANGE,
+        HeaderValue::from_str(&format!("bytes {start}-{end}/{total}"))
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?,
+    );
+    let slice = bytes[start as usize..=end as usize].to_vec();
+    Ok((StatusCode::PARTIAL_CONTENT, response_headers, slice).into_response())
+}
+
 pub async fn delete_document(
     Path(id): Path<i64>,
     State(state): State<Arc<crate::AppState>>,
@@ -558,28 +1329,29 @@ pub async fn delete_document(
     })?;
     tracing::info!("✓ Main pod proof verified");
 
-    // Check if document exists and get uploader info
+    // Check if document exists and get uploader info. Deletion only needs the
+    // metadata and pods, so the content blob is never read here.
     tracing::info!("Checking document exists and getting uploader info");
-    let document = state
+    let (metadata, pods) = state
         .db
-        .get_document(id, &state.storage)
+        .get_document_record(id)
         .map_err(|e| {
             tracing::error!("Database error retrieving document {id}: {e}");
             StatusCode::INTERNAL_SERVER_ERROR
         })?
         .ok_or(StatusCode::NOT_FOUND)?;
-    tracing::info!(
-        "Document {} found, uploader: {}",
-        id,
-        document.metadata.uploader_id
-    );
+    if metadata.deleted_at.is_some() {
+        tracing::warn!("Document {id} is already tombstoned");
+        return Err(StatusCode::NOT_FOUND);
+    }
+    tracing::info!("Document {} found, uploader: {}", id, metadata.uploader_id);
 
     // Verify username matches document uploader
-    if payload.username != document.metadata.uploader_id {
+    if payload.username != metadata.uploader_id {
         tracing::error!(
             "Username mismatch: requester '{}' vs document uploader '{}'",
             payload.username,
-            document.metadata.uploader_id
+            metadata.uploader_id
         );
         return Err(StatusCode::FORBIDDEN);
     }
@@ -600,15 +1372,14 @@ pub async fn delete_document(
     // Try verification with each registered identity server until one succeeds
     let mut verification_succeeded = false;
 
-    let timestamp_pod = document
-        .pods
+    let timestamp_pod = pods
         .timestamp_pod
         .get()
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
     tracing::info!("Got timestamp pod for document deletion verification: {timestamp_pod}");
 
     // Extract the original data from the publish MainPod
-    let publish_main_pod = document.pods.pod.get().map_err(|e| {
+    let publish_main_pod = pods.pod.get().map_err(|e| {
         tracing::error!("Failed to get publish MainPod: {e}");
         StatusCode::INTERNAL_SERVER_ERROR
     })?;
@@ -624,42 +1395,54 @@ pub async fn delete_document(
     };
     tracing::info!("✓ Original document data extracted from publish MainPod");
 
-    for identity_server in &identity_servers {
-        // Parse the identity server public key from database
-        let server_pk: pod2::backends::plonky2::primitives::ec::curve::Point =
-            serde_json::from_str(&identity_server.public_key).map_err(|e| {
-                tracing::error!("Failed to parse identity server public key: {e}");
-                StatusCode::INTERNAL_SERVER_ERROR
-            })?;
-
-        let server_pk_value = Value::from(server_pk);
+    'servers: for identity_server in &identity_servers {
+        // Deletion requests carry no issued-at claim, so revoked servers are
+        // rejected outright and only current keys are tried
+        if !super::registration::identity_server_accepts_pod(identity_server, None) {
+            tracing::info!(
+                "Skipping revoked identity server: {}",
+                identity_server.server_id
+            );
+            continue;
+        }
 
-        // Try verification with this identity server
-        tracing::info!(
-            "Trying deletion verification with identity server: {}",
-            identity_server.server_id
-        );
-        match verify_delete_verification_with_solver(
-            &payload.main_pod,
-            &payload.username,
-            original_data,
-            &server_pk_value,
-            timestamp_pod,
-        ) {
-            Ok(_) => {
-                tracing::info!(
-                    "✓ Solver verification succeeded with identity server: {}",
-                    identity_server.server_id
-                );
-                verification_succeeded = true;
-                break;
-            }
-            Err(_) => {
-                tracing::debug!(
-                    "Verification failed with identity server: {}",
-                    identity_server.server_id
-                );
-                continue;
+        for key_json in
+            super::registration::candidate_verification_keys(&state.db, identity_server, None)
+        {
+            let server_pk: pod2::backends::plonky2::primitives::ec::curve::Point =
+                serde_json::from_str(&key_json).map_err(|e| {
+                    tracing::error!("Failed to parse identity server public key: {e}");
+                    StatusCode::INTERNAL_SERVER_ERROR
+                })?;
+
+            let server_pk_value = Value::from(server_pk);
+
+            // Try verification with this identity server
+            tracing::info!(
+                "Trying deletion verification with identity server: {}",
+                identity_server.server_id
+            );
+            match verify_delete_verification_with_solver(
+                &payload.main_pod,
+                &payload.username,
+                original_data,
+                &server_pk_value,
+                timestamp_pod,
+            ) {
+                Ok(_) => {
+                    tracing::info!(
+                        "✓ Solver verification succeeded with identity server: {}",
+                        identity_server.server_id
+                    );
+                    verification_succeeded = true;
+                    break 'servers;
+                }
+                Err(_) => {
+                    tracing::debug!(
+                        "Verification failed with identity server: {}",
+                        identity_server.server_id
+                    );
+                }
             }
         }
     }
@@ -678,22 +1461,24 @@ pub async fn delete_document(
     // Delete all documents in this post (temporary behavior)
     tracing::info!(
         "Deleting all documents in post {} (requested by delete of document {})",
-        document.metadata.post_id,
+        metadata.post_id,
         id
     );
-    let deleted_uploader = document.metadata.uploader_id.clone();
+    let deleted_uploader = metadata.uploader_id.clone();
     let _deleted_count = state
         .db
-        .delete_documents_by_post_id(document.metadata.post_id)
+        .delete_documents_by_post_id(metadata.post_id)
         .map_err(|e| {
             tracing::error!(
                 "Failed to delete documents for post {}: {}",
-                document.metadata.post_id,
+                metadata.post_id,
                 e
             );
             StatusCode::INTERNAL_SERVER_ERROR
         })?;
 
+    super::events::emit_post_deleted(&state, metadata.post_id);
+
     tracing::info!("Document deletion completed successfully for document {id}");
 
     Ok(Json(serde_json::json!({
@@ -706,33 +1491,192 @@ pub async fn delete_document(
 
 #[cfg(test)]
 mod tests {
-    use std::sync::Arc;
-
     use axum::{extract::Path, http::StatusCode};
 
     use super::*;
-    use crate::db::Database;
+    use crate::handlers::tests::create_mock_app_state;
+
+    #[tokio::test]
+    async fn test_get_documents_conditional_get() {
+        use crate::db::tests::insert_dummy_document;
+
+        let state = create_mock_app_state().await;
+        insert_dummy_document(&state.db, &state.storage, "First Document", None);
+
+        // First request returns the full list plus cache validators
+        let first = get_documents(
+            Query(DocumentListQuery::default()),
+            axum::extract::State(state.clone()),
+            HeaderMap::new(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(first.status(), StatusCode::OK);
+        let etag = first
+            .headers()
+            .get(header::ETAG)
+            .expect("list response should carry an ETag")
+            .clone();
+        assert!(first.headers().contains_key(header::LAST_MODIFIED));
+
+        // Sending the validator back yields 304 while nothing has changed
+        let mut conditional_headers = HeaderMap::new();
+        conditional_headers.insert(header::IF_NONE_MATCH, etag);
+        let second = get_documents(
+            Query(DocumentListQuery::default()),
+            axum::extract::State(state.clone()),
+            conditional_headers.clone(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(second.status(), StatusCode::NOT_MODIFIED);
+
+        // A new document invalidates the ETag (the document count changed)
+        insert_dummy_document(&state.db, &state.storage, "Second Document", None);
+        let third = get_documents(
+            Query(DocumentListQuery::default()),
+            axum::extract::State(state),
+            conditional_headers,
+        )
+        .await
+        .unwrap();
+        assert_eq!(third.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_get_documents_sort_echo_and_validation() {
+        let state = create_mock_app_state().await;
+
+        // The effective sort and window are echoed in the response body
+        let response = get_documents(
+            Query(DocumentListQuery {
+                sort: Some("top".to_string()),
+                window: Some("7d".to_string()),
+                ..Default::default()
+            }),
+            axum::extract::State(state.clone()),
+            HeaderMap::new(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let list: podnet_models::DocumentListResponse = serde_json::from_slice(&body).unwrap();
+        assert_eq!(list.sort, "top");
+        assert_eq!(list.window_days, Some(7));
+
+        // Unknown sorts are rejected rather than silently reinterpreted
+        let error = get_documents(
+            Query(DocumentListQuery {
+                sort: Some("best".to_string()),
+                window: None,
+                ..Default::default()
+            }),
+            axum::extract::State(state),
+            HeaderMap::new(),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(error, StatusCode::BAD_REQUEST);
+    }
 
-    // Mock AppState for testing
-    async fn create_mock_app_state() -> Arc<crate::AppState> {
-        let db = Arc::new(
-            Database::new(":memory:")
+    #[tokio::test]
+    async fn test_get_documents_pagination_metadata() {
+        use crate::db::tests::insert_threaded_document;
+
+        let state = create_mock_app_state().await;
+        for i in 0..3 {
+            insert_threaded_document(&state.db, &state.storage, &format!("Root {i}"), None);
+        }
+
+        let fetch_page = |limit: i64, offset: i64| {
+            let state = state.clone();
+            async move {
+                let response = get_documents(
+                    Query(DocumentListQuery {
+                        limit: Some(limit),
+                        offset: Some(offset),
+                        ..Default::default()
+                    }),
+                    axum::extract::State(state),
+                    HeaderMap::new(),
+                )
                 .await
-                .expect("Failed to create test database"),
-        );
+                .unwrap();
+                let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+                    .await
+                    .unwrap();
+                let list: podnet_models::DocumentListResponse =
+                    serde_json::from_slice(&body).unwrap();
+                list.page
+            }
+        };
+
+        // offset + items.len() < total, so another page exists
+        let first = fetch_page(2, 0).await;
+        assert_eq!(first.items.len(), 2);
+        assert_eq!(first.total, 3);
+        assert_eq!(first.limit, 2);
+        assert_eq!(first.offset, 0);
+        assert!(first.has_more);
+
+        // The final page reports no further rows
+        let last = fetch_page(2, 2).await;
+        assert_eq!(last.items.len(), 1);
+        assert_eq!(last.total, 3);
+        assert!(!last.has_more);
+
+        // Paging past the end yields an empty page, not an error
+        let past = fetch_page(2, 10).await;
+        assert!(past.items.is_empty());
+        assert!(!past.has_more);
+    }
 
-        // Create minimal storage and config for testing
-        let storage =
-            Arc::new(crate::storage::ContentAddressedStorage::new("/tmp/test_storage").unwrap());
-        let config = crate::config::ServerConfig::load();
-        let pod_config = crate::pod::PodConfig::new(true); // Use mock proofs
-
-        Arc::new(crate::AppState {
-            db,
-            storage,
-            config,
-            pod_config,
-        })
+    #[tokio::test]
+    async fn test_document_list_excerpts() {
+        use crate::db::tests::insert_threaded_document;
+
+        let state = create_mock_app_state().await;
+        // 250 two-byte characters force the cut to land inside a multi-byte
+        // sequence if truncation were byte-based
+        let long_title = "é".repeat(250);
+        insert_threaded_document(&state.db, &state.storage, &long_title, None);
+
+        let fetch_items = |include_excerpt: Option<bool>| {
+            let state = state.clone();
+            async move {
+                let response = get_documents(
+                    Query(DocumentListQuery {
+                        include_excerpt,
+                        ..Default::default()
+                    }),
+                    axum::extract::State(state),
+                    HeaderMap::new(),
+                )
+                .await
+                .unwrap();
+                let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+                    .await
+                    .unwrap();
+                let list: serde_json::Value = serde_json::from_slice(&body).unwrap();
+                list["items"].as_array().unwrap().clone()
+            }
+        };
+
+        // The cheap default path omits the field entirely
+        let items = fetch_items(None).await;
+        assert_eq!(items.len(), 1);
+        assert!(items[0].get("excerpt").is_none());
+
+        // The message is "Test content for é…é" (267 chars), so the excerpt
+        // truncates to 200 whole characters plus the ellipsis
+        let items = fetch_items(Some(true)).await;
+        let excerpt = items[0]["excerpt"].as_str().unwrap();
+        assert_eq!(excerpt.chars().count(), LIST_EXCERPT_MAX_CHARS + 1);
+        assert!(excerpt.starts_with("Test content for é"));
+        assert!(excerpt.ends_with('…'));
     }
 
     #[tokio::test]
@@ -745,7 +1689,12 @@ mod tests {
         let doc_id = insert_dummy_document(&state.db, &state.storage, "Test Document", None);
 
         // Call the handler
-        let result = get_document_reply_tree(Path(doc_id), axum::extract::State(state)).await;
+        let result = get_document_reply_tree(
+            Path(doc_id),
+            Query(ReplyTreeQuery::default()),
+            axum::extract::State(state),
+        )
+        .await;
 
         // Verify success response
         assert!(result.is_ok());
@@ -762,7 +1711,12 @@ mod tests {
         let state = create_mock_app_state().await;
 
         // Call the handler with a non-existent document ID
-        let result = get_document_reply_tree(Path(99999), axum::extract::State(state)).await;
+        let result = get_document_reply_tree(
+            Path(99999),
+            Query(ReplyTreeQuery::default()),
+            axum::extract::State(state),
+        )
+        .await;
 
         // Verify 404 response
         assert!(result.is_err());
@@ -811,4 +1765,803 @@ mod tests {
         let replies = response.0;
         assert_eq!(replies.len(), 0);
     }
+
+    #[test]
+    fn test_content_limits_boundaries() {
+        use std::collections::HashSet;
+
+        use podnet_models::{ContentLimits, InlineDocumentFile};
+
+        let limits = ContentLimits {
+            max_title_length: 5,
+            max_message_bytes: 10,
+            max_file_bytes: 8,
+            max_content_bytes: 15,
+            max_tags: 2,
+            max_tag_length: 3,
+        };
+        let content = |message: Option<&str>, file_bytes: Option<usize>, url: Option<&str>| {
+            DocumentContent {
+                message: message.map(str::to_string),
+                file: file_bytes.map(|n| InlineDocumentFile {
+                    name: "f.bin".to_string(),
+                    content: vec![0; n],
+                    mime_type: "application/octet-stream".to_string(),
+                }),
+                files: Vec::new(),
+                url: url.map(str::to_string),
+            }
+        };
+        let tags = |list: &[&str]| -> HashSet<String> {
+            list.iter().map(|t| t.to_string()).collect()
+        };
+
+        // Values exactly at each limit pass
+        limits
+            .validate("12345", &content(Some("0123456789"), None, None), &tags(&["abc", "de"]), &[])
+            .unwrap();
+        limits
+            .validate("t", &content(None, Some(8), None), &HashSet::new(), &[])
+            .unwrap();
+        limits
+            .validate("t", &content(Some("0123456789"), None, Some("12345")), &HashSet::new(), &[])
+            .unwrap();
+
+        // One past each limit fails with the offending field
+        let err = limits
+            .validate("123456", &content(Some("m"), None, None), &HashSet::new(), &[])
+            .unwrap_err();
+        assert_eq!(err.field, "title");
+        assert!(!err.oversize);
+
+        let err = limits
+            .validate("t", &content(Some("m"), None, None), &tags(&["a", "b", "c"]), &[])
+            .unwrap_err();
+        assert_eq!(err.field, "tags");
+        let err = limits
+            .validate("t", &content(Some("m"), None, None), &tags(&["abcd"]), &[])
+            .unwrap_err();
+        assert_eq!(err.field, "tags");
+        assert!(!err.oversize);
+
+        let err = limits
+            .validate("t", &content(Some("01234567890"), None, None), &HashSet::new(), &[])
+            .unwrap_err();
+        assert_eq!(err.field, "content.message");
+        assert!(err.oversize);
+
+        let err = limits
+            .validate("t", &content(None, Some(9), None), &HashSet::new(), &[])
+            .unwrap_err();
+        assert_eq!(err.field, "content.file");
+        assert!(err.oversize);
+
+        let err = limits
+            .validate(
+                "t",
+                &content(Some("0123456789"), None, Some("123456")),
+                &HashSet::new(),
+                &[],
+            )
+            .unwrap_err();
+        assert_eq!(err.field, "content");
+        assert!(err.oversize);
+
+        // Attachment blobs obey the per-file limit and count towards the total
+        limits
+            .validate("t", &content(None, None, None), &HashSet::new(), &[vec![0; 8]])
+            .unwrap();
+        let err = limits
+            .validate("t", &content(None, None, None), &HashSet::new(), &[vec![0; 9]])
+            .unwrap_err();
+        assert_eq!(err.field, "attachments");
+        assert!(err.oversize);
+        let err = limits
+            .validate(
+                "t",
+                &content(Some("0123456789"), None, None),
+                &HashSet::new(),
+                &[vec![0; 6]],
+            )
+            .unwrap_err();
+        assert_eq!(err.field, "content");
+        assert!(err.oversize);
+    }
+
+    #[tokio::test]
+    async fn test_over_limit_publish_writes_nothing() {
+        use std::collections::HashSet;
+
+        use pod2::{
+            backends::plonky2::{primitives::ec::schnorr::SecretKey, signer::Signer},
+            frontend::SignedDictBuilder,
+            middleware::Params,
+        };
+        use podnet_models::mainpod::publish::{
+            PublishProofParams, prove_publish_verification_with_solver,
+        };
+
+        let state = create_mock_app_state().await;
+
+        let content = DocumentContent {
+            message: Some("x".repeat(state.config.content_limits.max_message_bytes + 1)),
+            file: None,
+            files: Vec::new(),
+            url: None,
+        };
+        let content_hash =
+            crate::storage::ContentAddressedStorage::hash_document_content(&content).unwrap();
+
+        // A structurally valid publish main pod; the limit check rejects the
+        // request before this is ever verified
+        let params = Params::default();
+        let user_sk = SecretKey::new_rand();
+        let mut identity_builder = SignedDictBuilder::new(&params);
+        identity_builder.insert("username", "alice");
+        identity_builder.insert("user_public_key", user_sk.public_key());
+        let identity_pod = identity_builder.sign(&Signer(SecretKey::new_rand())).unwrap();
+
+        let mut data_map = HashMap::new();
+        data_map.insert(Key::from("content_hash"), Value::from(content_hash));
+        data_map.insert(
+            Key::from("tags"),
+            Value::from(Set::new(5, HashSet::new()).unwrap()),
+        );
+        data_map.insert(
+            Key::from("authors"),
+            Value::from(Set::new(5, HashSet::new()).unwrap()),
+        );
+        data_map.insert(Key::from("reply_to"), Value::from(-1i64));
+        data_map.insert(Key::from("post_id"), Value::from(-1i64));
+        let data = Dictionary::new(6, data_map).unwrap();
+        let mut document_builder = SignedDictBuilder::new(&params);
+        document_builder.insert("request_type", "publish");
+        document_builder.insert("data", data);
+        let document_pod = document_builder.sign(&Signer(user_sk)).unwrap();
+        let main_pod = prove_publish_verification_with_solver(PublishProofParams {
+            identity_pod: &identity_pod,
+            document_pod: &document_pod,
+            use_mock_proofs: true,
+        })
+        .unwrap();
+
+        let payload = PublishRequest {
+            title: "Too big".to_string(),
+            content: content.clone(),
+            tags: HashSet::new(),
+            authors: HashSet::new(),
+            reply_to: None,
+            post_id: None,
+            username: "alice".to_string(),
+            main_pod,
+            identity_pod_issued_at: None,
+            attachments: vec![],
+        };
+
+        let response = publish_document(
+            axum::extract::State(state.clone()),
+            Query(PublishQuery { subscribe: true }),
+            Json(payload),
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+
+        // The rejection happened before any write: no content blob, no post
+        // or document rows
+        let hash_hex: String = content_hash.encode_hex();
+        assert!(!state.storage.exists(&hash_hex));
+        let stats = state.db.get_stats().unwrap();
+        assert_eq!(stats.document_count, 0);
+        assert_eq!(stats.post_count, 0);
+    }
+
+    #[tokio::test]
+    async fn test_publish_with_mismatched_pod_content_rejected() {
+        use std::collections::HashSet;
+
+        use pod2::{
+            backends::plonky2::{primitives::ec::schnorr::SecretKey, signer::Signer},
+            frontend::SignedDictBuilder,
+            middleware::Params,
+        };
+        use podnet_models::mainpod::publish::{
+            PublishProofParams, prove_publish_verification_with_solver,
+        };
+
+        let state = create_mock_app_state().await;
+
+        // Prove a pod over one piece of content...
+        let proven_content = DocumentContent {
+            message: Some("the content the pod commits to".to_string()),
+            file: None,
+            files: Vec::new(),
+            url: None,
+        };
+        let proven_content_hash =
+            crate::storage::ContentAddressedStorage::hash_document_content(&proven_content)
+                .unwrap();
+
+        let params = Params::default();
+        let user_sk = SecretKey::new_rand();
+        let mut identity_builder = SignedDictBuilder::new(&params);
+        identity_builder.insert("username", "alice");
+        identity_builder.insert("user_public_key", user_sk.public_key());
+        let identity_pod = identity_builder.sign(&Signer(SecretKey::new_rand())).unwrap();
+
+        let mut data_map = HashMap::new();
+        data_map.insert(Key::from("content_hash"), Value::from(proven_content_hash));
+        data_map.insert(
+            Key::from("tags"),
+            Value::from(Set::new(5, HashSet::new()).unwrap()),
+        );
+        data_map.insert(
+            Key::from("authors"),
+            Value::from(Set::new(5, HashSet::new()).unwrap()),
+        );
+        data_map.insert(Key::from("reply_to"), Value::from(-1i64));
+        data_map.insert(Key::from("post_id"), Value::from(-1i64));
+        let data = Dictionary::new(6, data_map).unwrap();
+        let mut document_builder = SignedDictBuilder::new(&params);
+        document_builder.insert("request_type", "publish");
+        document_builder.insert("data", data);
+        let document_pod = document_builder.sign(&Signer(user_sk)).unwrap();
+        let main_pod = prove_publish_verification_with_solver(PublishProofParams {
+            identity_pod: &identity_pod,
+            document_pod: &document_pod,
+            use_mock_proofs: true,
+        })
+        .unwrap();
+
+        // ...but submit different content alongside it
+        let submitted_content = DocumentContent {
+            message: Some("entirely different content".to_string()),
+            file: None,
+            files: Vec::new(),
+            url: None,
+        };
+        let submitted_content_hash =
+            crate::storage::ContentAddressedStorage::hash_document_content(&submitted_content)
+                .unwrap();
+
+        let payload = PublishRequest {
+            title: "Mismatched".to_string(),
+            content: submitted_content,
+            tags: HashSet::new(),
+            authors: HashSet::new(),
+            reply_to: None,
+            post_id: None,
+            username: "alice".to_string(),
+            main_pod,
+            identity_pod_issued_at: None,
+            attachments: vec![],
+        };
+
+        let status = publish_document(
+            axum::extract::State(state.clone()),
+            Query(PublishQuery { subscribe: true }),
+            Json(payload),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        // Neither the proven nor the submitted content was stored
+        let proven_hex: String = proven_content_hash.encode_hex();
+        let submitted_hex: String = submitted_content_hash.encode_hex();
+        assert!(!state.storage.exists(&proven_hex));
+        assert!(!state.storage.exists(&submitted_hex));
+        let stats = state.db.get_stats().unwrap();
+        assert_eq!(stats.document_count, 0);
+        assert_eq!(stats.post_count, 0);
+    }
+
+    #[test]
+    fn test_markdown_export_renders_nested_thread() {
+        use std::collections::HashSet;
+
+        use hex::FromHex;
+        use pod2::middleware::Hash;
+        use podnet_models::InlineDocumentFile;
+
+        fn node(
+            id: i64,
+            title: &str,
+            author: &str,
+            content: DocumentContent,
+            replies: Vec<DocumentReplyTree>,
+        ) -> DocumentReplyTree {
+            DocumentReplyTree {
+                document: DocumentMetadata {
+                    id: Some(id),
+                    content_id: Hash::from_hex(
+                        "1234567890abcdef1234567890abcdef1234567890abcdef1234567890abcdef",
+                    )
+                    .unwrap(),
+                    post_id: id,
+                    revision: 1,
+                    created_at: Some("2025-01-01 12:00:00".to_string()),
+                    uploader_id: author.to_string(),
+                    upvote_count: id,
+                    tags: HashSet::new(),
+                    authors: HashSet::new(),
+                    reply_to: None,
+                    requested_post_id: None,
+                    title: title.to_string(),
+                    hidden: false,
+                    display_name: None,
+                    deleted_at: None,
+                    content_type: None,
+                },
+                content,
+                replies,
+                stubs: vec![],
+            }
+        }
+
+        let leaf = node(
+            3,
+            "Grandchild",
+            "carol",
+            DocumentContent {
+                message: None,
+                file: Some(InlineDocumentFile {
+                    name: "data.csv".to_string(),
+                    content: vec![1, 2, 3],
+                    mime_type: "text/csv".to_string(),
+                }),
+                files: Vec::new(),
+                url: None,
+            },
+            vec![],
+        );
+        let child = node(
+            2,
+            "Child",
+            "bob",
+            DocumentContent {
+                message: Some("First line\nSecond line".to_string()),
+                file: None,
+                files: Vec::new(),
+                url: None,
+            },
+            vec![leaf],
+        );
+        let root = node(
+            1,
+            "Root",
+            "alice",
+            DocumentContent {
+                message: Some("Hello".to_string()),
+                file: None,
+                files: Vec::new(),
+                url: Some("https://example.org".to_string()),
+            },
+            vec![child],
+        );
+
+        let mut chunks = Vec::new();
+        render_markdown_chunks(&root, 0, &mut chunks);
+
+        // One chunk per node, in reading order
+        assert_eq!(chunks.len(), 3);
+        let transcript = chunks.concat();
+        assert_eq!(
+            transcript,
+            "### Root\n\
+             *by alice at 2025-01-01 12:00:00 — 1 upvote*\n\
+             \n\
+             Hello\n\
+             \n\
+             URL: <https://example.org>\n\
+             \n\
+             > ### Child\n\
+             > *by bob at 2025-01-01 12:00:00 — 2 upvotes*\n\
+             > \n\
+             > First line\n\
+             > Second line\n\
+             > \n\
+             > > ### Grandchild\n\
+             > > *by carol at 2025-01-01 12:00:00 — 3 upvotes*\n\
+             > > \n\
+             > > Attachment: `data.csv` (text/csv, content \
+             1234567890abcdef1234567890abcdef1234567890abcdef1234567890abcdef)\n\
+             > > \n"
+        );
+
+        // The file bytes themselves are never inlined
+        assert!(!transcript.contains("\u{1}\u{2}\u{3}"));
+    }
+
+    #[test]
+    fn test_legacy_inline_file_content_deserializes() {
+        // Content blobs stored before attachments moved to `files` carry the
+        // file bytes inline and no `files` key at all
+        let legacy = r#"{
+            "message": null,
+            "file": {"name": "old.txt", "content": [104, 105], "mime_type": "text/plain"},
+            "url": null
+        }"#;
+        let content: DocumentContent = serde_json::from_str(legacy).unwrap();
+        let file = content.file.as_ref().unwrap();
+        assert_eq!(file.name, "old.txt");
+        assert_eq!(file.content, b"hi");
+        assert!(content.files.is_empty());
+        content.validate().unwrap();
+    }
+
+    #[test]
+    fn test_parse_byte_range() {
+        assert_eq!(parse_byte_range("bytes=0-3", 10), Some((0, 3)));
+        assert_eq!(parse_byte_range("bytes=4-", 10), Some((4, 9)));
+        assert_eq!(parse_byte_range("bytes=-3", 10), Some((7, 9)));
+        // A suffix longer than the blob covers the whole blob
+        assert_eq!(parse_byte_range("bytes=-20", 10), Some((0, 9)));
+        assert_eq!(parse_byte_range("bytes=3-2", 10), None);
+        assert_eq!(parse_byte_range("bytes=10-", 10), None);
+        assert_eq!(parse_byte_range("bytes=0-0", 0), None);
+        assert_eq!(parse_byte_range("bytes=0-1,3-4", 10), None);
+        assert_eq!(parse_byte_range("items=0-1", 10), None);
+    }
+
+    #[tokio::test]
+    async fn test_publish_with_attachments_and_fetch_by_hash() {
+        use std::collections::HashSet;
+
+        use pod2::{
+            backends::plonky2::{primitives::ec::schnorr::SecretKey, signer::Signer},
+            frontend::SignedDictBuilder,
+            middleware::Params,
+        };
+        use podnet_models::{
+            DocumentFile,
+            mainpod::publish::{PublishProofParams, prove_publish_verification_with_solver},
+        };
+
+        let state = create_mock_app_state().await;
+
+        // Register the identity server that signs the test identity pod
+        let identity_sk = SecretKey::new_rand();
+        state
+            .db
+            .create_identity_server(
+                "test-identity",
+                &serde_json::to_string(&identity_sk.public_key()).unwrap(),
+                "{}",
+                "{}",
+            )
+            .unwrap();
+
+        // Two attachments with unique bytes, so their hashes are fresh even in
+        // the shared test storage directory
+        let blob_png: Vec<u8> = (0..64).map(|_| rand::random::<u8>()).collect();
+        let blob_txt: Vec<u8> = format!("notes {}", rand::random::<u64>()).into_bytes();
+        let png_hash: String = DocumentFile::content_id_for(&blob_png).encode_hex();
+        let txt_hash: String = DocumentFile::content_id_for(&blob_txt).encode_hex();
+
+        let content = DocumentContent {
+            message: Some("see attachments".to_string()),
+            file: None,
+            files: vec![
+                DocumentFile {
+                    name: "photo.png".to_string(),
+                    mime: "image/png".to_string(),
+                    content_id: DocumentFile::content_id_for(&blob_png),
+                },
+                DocumentFile {
+                    name: "notes.txt".to_string(),
+                    mime: "text/plain".to_string(),
+                    content_id: DocumentFile::content_id_for(&blob_txt),
+                },
+            ],
+            url: None,
+        };
+        let content_hash =
+            crate::storage::ContentAddressedStorage::hash_document_content(&content).unwrap();
+
+        let params = Params::default();
+        let user_sk = SecretKey::new_rand();
+        let mut identity_builder = SignedDictBuilder::new(&params);
+        identity_builder.insert("username", "alice");
+        identity_builder.insert("user_public_key", user_sk.public_key());
+        let identity_pod = identity_builder.sign(&Signer(identity_sk)).unwrap();
+
+        let mut data_map = HashMap::new();
+        data_map.insert(Key::from("content_hash"), Value::from(content_hash));
+        data_map.insert(
+            Key::from("tags"),
+            Value::from(Set::new(5, HashSet::new()).unwrap()),
+        );
+        data_map.insert(
+            Key::from("authors"),
+            Value::from(Set::new(5, HashSet::new()).unwrap()),
+        );
+        data_map.insert(Key::from("reply_to"), Value::from(-1i64));
+        data_map.insert(Key::from("post_id"), Value::from(-1i64));
+        let data = Dictionary::new(6, data_map).unwrap();
+        let mut document_builder = SignedDictBuilder::new(&params);
+        document_builder.insert("request_type", "publish");
+        document_builder.insert("data", data);
+        let document_pod = document_builder.sign(&Signer(user_sk)).unwrap();
+        let main_pod = prove_publish_verification_with_solver(PublishProofParams {
+            identity_pod: &identity_pod,
+            document_pod: &document_pod,
+            use_mock_proofs: true,
+        })
+        .unwrap();
+
+        // Referencing an attachment without uploading its bytes is rejected
+        // before anything is stored
+        let missing_bytes = PublishRequest {
+            title: "With attachments".to_string(),
+            content: content.clone(),
+            tags: HashSet::new(),
+            authors: HashSet::new(),
+            reply_to: None,
+            post_id: None,
+            username: "alice".to_string(),
+            main_pod: main_pod.clone(),
+            identity_pod_issued_at: None,
+            attachments: vec![blob_png.clone()],
+        };
+        let response = publish_document(
+            axum::extract::State(state.clone()),
+            Query(PublishQuery { subscribe: true }),
+            Json(missing_bytes),
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        assert!(!state.storage.exists(&png_hash));
+
+        let payload = PublishRequest {
+            title: "With attachments".to_string(),
+            content: content.clone(),
+            tags: HashSet::new(),
+            authors: HashSet::new(),
+            reply_to: None,
+            post_id: None,
+            username: "alice".to_string(),
+            main_pod,
+            identity_pod_issued_at: None,
+            attachments: vec![blob_png.clone(), blob_txt.clone()],
+        };
+        let response = publish_document(
+            axum::extract::State(state.clone()),
+            Query(PublishQuery { subscribe: true }),
+            Json(payload),
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        // Each attachment is its own blob, fetchable by hash with the MIME
+        // type recorded at publish time
+        for (hash, bytes, mime) in [
+            (&png_hash, &blob_png, "image/png"),
+            (&txt_hash, &blob_txt, "text/plain"),
+        ] {
+            let response = get_content(
+                Path(hash.clone()),
+                axum::extract::State(state.clone()),
+                HeaderMap::new(),
+            )
+            .await
+            .unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+            assert_eq!(
+                response.headers().get(header::CONTENT_TYPE).unwrap(),
+                mime
+            );
+            assert_eq!(
+                response.headers().get(header::ACCEPT_RANGES).unwrap(),
+                "bytes"
+            );
+            let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+                .await
+                .unwrap();
+            assert_eq!(body.as_ref(), bytes.as_slice());
+        }
+
+        // A single byte range is served as a partial response
+        let mut range_headers = HeaderMap::new();
+        range_headers.insert(header::RANGE, HeaderValue::from_static("bytes=1-2"));
+        let response = get_content(
+            Path(png_hash.clone()),
+            axum::extract::State(state.clone()),
+            range_headers,
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.status(), StatusCode::PARTIAL_CONTENT);
+        assert_eq!(
+            response.headers().get(header::CONTENT_RANGE).unwrap(),
+            &format!("bytes 1-2/{}", blob_png.len())
+        );
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert_eq!(body.as_ref(), &blob_png[1..=2]);
+
+        // An unsatisfiable range reports the blob size
+        let mut range_headers = HeaderMap::new();
+        range_headers.insert(header::RANGE, HeaderValue::from_static("bytes=9999-"));
+        let response = get_content(
+            Path(png_hash.clone()),
+            axum::extract::State(state.clone()),
+            range_headers,
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.status(), StatusCode::RANGE_NOT_SATISFIABLE);
+
+        // Unknown blobs and malformed hashes are rejected
+        let missing = "0".repeat(64);
+        let err = get_content(
+            Path(missing),
+            axum::extract::State(state.clone()),
+            HeaderMap::new(),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(err, StatusCode::NOT_FOUND);
+        let err = get_content(
+            Path("../etc/passwd".to_string()),
+            axum::extract::State(state),
+            HeaderMap::new(),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(err, StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_reply_depth_limit() {
+        use std::collections::HashSet;
+
+        use pod2::{
+            backends::plonky2::{primitives::ec::schnorr::SecretKey, signer::Signer},
+            frontend::SignedDictBuilder,
+            middleware::Params,
+        };
+        use podnet_models::{
+            ReplyReference,
+            mainpod::publish::{PublishProofParams, prove_publish_verification_with_solver},
+        };
+
+        use crate::db::tests::insert_threaded_document;
+
+        let state = create_mock_app_state().await;
+        let max_depth = state.config.max_reply_depth as usize;
+
+        let identity_sk = SecretKey::new_rand();
+        state
+            .db
+            .create_identity_server(
+                "test-identity",
+                &serde_json::to_string(&identity_sk.public_key()).unwrap(),
+                "{}",
+                "{}",
+            )
+            .unwrap();
+
+        // A chain of posts where chain[i] sits at depth i, ending exactly at
+        // the configured limit
+        let mut chain = vec![insert_threaded_document(
+            &state.db,
+            &state.storage,
+            "Root",
+            None,
+        )];
+        for i in 1..=max_depth {
+            let parent = chain[i - 1];
+            chain.push(insert_threaded_document(
+                &state.db,
+                &state.storage,
+                &format!("Reply {i}"),
+                Some(parent),
+            ));
+        }
+        assert_eq!(
+            state.db.get_post_depth(chain[max_depth].0).unwrap(),
+            Some(max_depth as i64)
+        );
+
+        let params = Params::default();
+        let user_sk = SecretKey::new_rand();
+        let mut identity_builder = SignedDictBuilder::new(&params);
+        identity_builder.insert("username", "alice");
+        identity_builder.insert("user_public_key", user_sk.public_key());
+        let identity_pod = identity_builder.sign(&Signer(identity_sk)).unwrap();
+
+        let reply_payload = |(parent_post, parent_doc): (i64, i64)| {
+            let content = DocumentContent {
+                message: Some(format!("reply to document {parent_doc}")),
+                file: None,
+                files: Vec::new(),
+                url: None,
+            };
+            let content_hash =
+                crate::storage::ContentAddressedStorage::hash_document_content(&content).unwrap();
+
+            let mut reply_map = HashMap::new();
+            reply_map.insert(Key::from("post_id"), Value::from(parent_post));
+            reply_map.insert(Key::from("document_id"), Value::from(parent_doc));
+            let reply_dict = Dictionary::new(2, reply_map).unwrap();
+
+            let mut data_map = HashMap::new();
+            data_map.insert(Key::from("content_hash"), Value::from(content_hash));
+            data_map.insert(
+                Key::from("tags"),
+                Value::from(Set::new(5, HashSet::new()).unwrap()),
+            );
+            data_map.insert(
+                Key::from("authors"),
+                Value::from(Set::new(5, HashSet::new()).unwrap()),
+            );
+            data_map.insert(Key::from("reply_to"), Value::from(reply_dict));
+            data_map.insert(Key::from("post_id"), Value::from(-1i64));
+            let data = Dictionary::new(6, data_map).unwrap();
+            let mut document_builder = SignedDictBuilder::new(&params);
+            document_builder.insert("request_type", "publish");
+            document_builder.insert("data", data);
+            let document_pod = document_builder
+                .sign(&Signer(SecretKey(user_sk.0.clone())))
+                .unwrap();
+            let main_pod = prove_publish_verification_with_solver(PublishProofParams {
+                identity_pod: &identity_pod,
+                document_pod: &document_pod,
+                use_mock_proofs: true,
+            })
+            .unwrap();
+
+            PublishRequest {
+                title: "Reply".to_string(),
+                content,
+                tags: HashSet::new(),
+                authors: HashSet::new(),
+                reply_to: Some(ReplyReference {
+                    post_id: parent_post,
+                    document_id: parent_doc,
+                }),
+                post_id: None,
+                username: "alice".to_string(),
+                main_pod,
+                identity_pod_issued_at: None,
+                attachments: vec![],
+            }
+        };
+
+        // Replying one level above the limit is still allowed: the reply
+        // itself lands exactly at max_reply_depth
+        let response = publish_document(
+            axum::extract::State(state.clone()),
+            Query(PublishQuery { subscribe: true }),
+            Json(reply_payload(chain[max_depth - 1])),
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        // Replying to a post already at the limit is rejected, naming it
+        let response = publish_document(
+            axum::extract::State(state.clone()),
+            Query(PublishQuery { subscribe: true }),
+            Json(reply_payload(chain[max_depth])),
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let error: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(error["field"], "reply_to");
+        assert!(
+            error["error"]
+                .as_str()
+                .unwrap()
+                .contains(&format!("maximum reply depth is {max_depth}"))
+        );
+    }
 }
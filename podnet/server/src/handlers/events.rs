@@ -0,0 +1,194 @@
+//! Server-sent events stream broadcasting document, upvote, and deletion
+//! activity so clients can follow changes live instead of polling.
+
+use std::{convert::Infallible, sync::Arc};
+
+use axum::{
+    extract::{Query, State},
+    response::sse::{Event, KeepAlive, Sse},
+};
+use podnet_models::ReplyReference;
+use serde::{Deserialize, Serialize};
+use tokio_stream::{Stream, StreamExt, wrappers::BroadcastStream};
+
+/// Events broadcast to SSE subscribers. Every variant carries the thread root
+/// so subscribers can filter to a single thread.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ServerEvent {
+    DocumentCreated {
+        post_id: i64,
+        document_id: i64,
+        title: String,
+        reply_to: Option<ReplyReference>,
+        thread_root_post_id: i64,
+    },
+    Upvote {
+        document_id: i64,
+        new_count: i64,
+        thread_root_post_id: i64,
+    },
+    PostDeleted {
+        post_id: i64,
+        thread_root_post_id: i64,
+    },
+}
+
+impl ServerEvent {
+    fn name(&self) -> &'static str {
+        match self {
+            ServerEvent::DocumentCreated { .. } => "document_created",
+            ServerEvent::Upvote { .. } => "upvote",
+            ServerEvent::PostDeleted { .. } => "post_deleted",
+        }
+    }
+
+    fn thread_root_post_id(&self) -> i64 {
+        match self {
+            ServerEvent::DocumentCreated {
+                thread_root_post_id,
+                ..
+            }
+            | ServerEvent::Upvote {
+                thread_root_post_id,
+                ..
+            }
+            | ServerEvent::PostDeleted {
+                thread_root_post_id,
+                ..
+            } => *thread_root_post_id,
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct EventStreamQuery {
+    /// Only deliver events belonging to this thread
+    pub thread_root_post_id: Option<i64>,
+}
+
+impl EventStreamQuery {
+    fn matches(&self, event: &ServerEvent) -> bool {
+        self.thread_root_post_id
+            .is_none_or(|root| event.thread_root_post_id() == root)
+    }
+}
+
+/// Resolve the thread root for a post, falling back to the post itself for
+/// thread roots (which have no thread_root_post_id set)
+fn thread_root_for_post(state: &crate::AppState, post_id: i64) -> i64 {
+    state
+        .db
+        .get_post_thread_root_id(post_id)
+        .ok()
+        .flatten()
+        .unwrap_or(post_id)
+}
+
+pub fn emit_document_created(
+    state: &crate::AppState,
+    post_id: i64,
+    document_id: i64,
+    title: &str,
+    reply_to: Option<ReplyReference>,
+) {
+    // Send errors just mean nobody is subscribed right now
+    let _ = state.events.send(ServerEvent::DocumentCreated {
+        post_id,
+        document_id,
+        title: title.to_string(),
+        reply_to,
+        thread_root_post_id: thread_root_for_post(state, post_id),
+    });
+}
+
+pub fn emit_upvote(state: &crate::AppState, post_id: i64, document_id: i64, new_count: i64) {
+    let _ = state.events.send(ServerEvent::Upvote {
+        document_id,
+        new_count,
+        thread_root_post_id: thread_root_for_post(state, post_id),
+    });
+}
+
+pub fn emit_post_deleted(state: &crate::AppState, post_id: i64) {
+    let _ = state.events.send(ServerEvent::PostDeleted {
+        post_id,
+        thread_root_post_id: thread_root_for_post(state, post_id),
+    });
+}
+
+pub async fn event_stream(
+    Query(query): Query<EventStreamQuery>,
+    State(state): State<Arc<crate::AppState>>,
+) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    let rx = state.events.subscribe();
+    let stream = BroadcastStream::new(rx)
+        // A lagging receiver has fallen too far behind the broadcast channel;
+        // end its stream rather than letting it back up the channel
+        .map_while(|event| event.ok())
+        .filter_map(move |event| {
+            if !query.matches(&event) {
+                return None;
+            }
+            Event::default()
+                .event(event.name())
+                .json_data(&event)
+                .ok()
+                .map(Ok)
+        });
+
+    Sse::new(stream).keep_alive(KeepAlive::default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_document_created_event_reaches_subscriber() {
+        use crate::db::tests::insert_dummy_document;
+        use crate::handlers::tests::create_mock_app_state;
+
+        let state = create_mock_app_state().await;
+        let mut rx = state.events.subscribe();
+
+        let document_id = insert_dummy_document(&state.db, &state.storage, "Live Document", None);
+
+        emit_document_created(&state, 1, document_id, "Live Document", None);
+
+        let event = rx.recv().await.unwrap();
+        assert_eq!(
+            event,
+            ServerEvent::DocumentCreated {
+                post_id: 1,
+                document_id,
+                title: "Live Document".to_string(),
+                reply_to: None,
+                thread_root_post_id: 1,
+            }
+        );
+    }
+
+    #[test]
+    fn test_thread_filtering() {
+        let event = ServerEvent::Upvote {
+            document_id: 7,
+            new_count: 3,
+            thread_root_post_id: 42,
+        };
+
+        assert!(EventStreamQuery::default().matches(&event));
+        assert!(
+            EventStreamQuery {
+                thread_root_post_id: Some(42)
+            }
+            .matches(&event)
+        );
+        assert!(
+            !EventStreamQuery {
+                thread_root_post_id: Some(43)
+            }
+            .matches(&event)
+        );
+    }
+}
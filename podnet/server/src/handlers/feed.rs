@@ -0,0 +1,215 @@
+//! Atom feed of recent top-level documents so a podnet instance can be
+//! followed from a feed reader.
+
+use std::sync::Arc;
+
+use axum::{
+    extract::{Query, State},
+    http::{header, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+};
+use podnet_models::RawDocument;
+use pulldown_cmark::{Event, Parser};
+use serde::Deserialize;
+
+/// Number of entries included in the feed
+const FEED_ENTRY_LIMIT: usize = 20;
+/// Maximum length of a plain-text entry excerpt, in characters
+const EXCERPT_MAX_CHARS: usize = 300;
+
+#[derive(Debug, Default, Deserialize)]
+pub struct FeedQuery {
+    /// Restrict the feed to documents carrying this tag
+    pub tag: Option<String>,
+}
+
+/// Escape a string for inclusion in XML text or attribute content
+fn xml_escape(input: &str) -> String {
+    let mut escaped = String::with_capacity(input.len());
+    for c in input.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Extract a length-capped plain-text excerpt from markdown, dropping all
+/// markup and keeping only the rendered text
+pub(crate) fn plain_text_excerpt(markdown: &str, max_chars: usize) -> String {
+    let mut text = String::new();
+    for event in Parser::new(markdown) {
+        match event {
+            Event::Text(t) | Event::Code(t) => text.push_str(&t),
+            Event::SoftBreak | Event::HardBreak => text.push(' '),
+            Event::End(_) if !text.ends_with(' ') => text.push(' '),
+            _ => {}
+        }
+    }
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+
+    if text.chars().count() <= max_chars {
+        text
+    } else {
+        let mut excerpt: String = text.chars().take(max_chars).collect();
+        excerpt.push('…');
+        excerpt
+    }
+}
+
+/// Convert a SQLite `CURRENT_TIMESTAMP` string to RFC 3339 as required by Atom
+fn sqlite_timestamp_to_rfc3339(timestamp: &str) -> String {
+    format!("{}Z", timestamp.replacen(' ', "T", 1))
+}
+
+fn feed_entry(document: &RawDocument, excerpt: &str) -> String {
+    let document_id = document.id.unwrap_or(-1);
+    let updated = document
+        .created_at
+        .as_deref()
+        .map(sqlite_timestamp_to_rfc3339)
+        .unwrap_or_else(|| "1970-01-01T00:00:00Z".to_string());
+
+    format!(
+        r#"  <entry>
+    <id>urn:podnet:post:{post_id}:rev:{revision}</id>
+    <title>{title}</title>
+    <author><name>{author}</name></author>
+    <updated>{updated}</updated>
+    <link href="/documents/{document_id}"/>
+    <summary>{summary}</summary>
+  </entry>
+"#,
+        post_id = document.post_id,
+        revision = document.revision,
+        title = xml_escape(&document.title),
+        author = xml_escape(&document.uploader_id),
+        summary = xml_escape(excerpt),
+    )
+}
+
+pub async fn get_feed_atom(
+    Query(query): Query<FeedQuery>,
+    State(state): State<Arc<crate::AppState>>,
+) -> Result<Response, StatusCode> {
+    let documents = state
+        .db
+        .get_recent_top_level_documents(FEED_ENTRY_LIMIT, query.tag.as_deref())
+        .map_err(|e| {
+            tracing::error!("Failed to load documents for feed: {e}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    let updated = state
+        .db
+        .get_most_recent_modification_time()
+        .map_err(|e| {
+            tracing::error!("Failed to get feed modification time: {e}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .map(|ts| sqlite_timestamp_to_rfc3339(&ts))
+        .unwrap_or_else(|| "1970-01-01T00:00:00Z".to_string());
+
+    let mut entries = String::new();
+    for document in &documents {
+        let content_id = pod2::middleware::Hash::from_hex(document.content_id.clone())
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+        let excerpt = state
+            .storage
+            .retrieve_document_content_async(content_id)
+            .await
+            .ok()
+            .flatten()
+            .and_then(|content| {
+                content
+                    .message
+                    .map(|message| plain_text_excerpt(&message, EXCERPT_MAX_CHARS))
+                    .or(content.url)
+            })
+            .unwrap_or_default();
+        entries.push_str(&feed_entry(document, &excerpt));
+    }
+
+    let title = match &query.tag {
+        Some(tag) => format!("PodNet — {}", xml_escape(tag)),
+        None => "PodNet".to_string(),
+    };
+    let feed_id = match &query.tag {
+        Some(tag) => format!("urn:podnet:feed:tag:{}", xml_escape(tag)),
+        None => "urn:podnet:feed".to_string(),
+    };
+
+    let feed = format!(
+        r#"<?xml version="1.0" encoding="utf-8"?>
+<feed xmlns="http://www.w3.org/2005/Atom">
+  <id>{feed_id}</id>
+  <title>{title}</title>
+  <updated>{updated}</updated>
+  <link href="/feed.atom" rel="self"/>
+{entries}</feed>
+"#
+    );
+
+    let mut response = feed.into_response();
+    response.headers_mut().insert(
+        header::CONTENT_TYPE,
+        HeaderValue::from_static("application/atom+xml; charset=utf-8"),
+    );
+    Ok(response)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::handlers::tests::create_mock_app_state;
+
+    #[test]
+    fn test_plain_text_excerpt_strips_markdown() {
+        let markdown = "# Heading\n\nSome **bold** text with [a link](https://example.com) and `code`.";
+        let excerpt = plain_text_excerpt(markdown, EXCERPT_MAX_CHARS);
+        assert_eq!(excerpt, "Heading Some bold text with a link and code.");
+
+        let long = "word ".repeat(200);
+        let capped = plain_text_excerpt(&long, 50);
+        assert_eq!(capped.chars().count(), 51); // 50 chars plus ellipsis
+        assert!(capped.ends_with('…'));
+    }
+
+    #[tokio::test]
+    async fn test_feed_atom_structure() {
+        use crate::db::tests::insert_dummy_document;
+
+        let state = create_mock_app_state().await;
+        let doc_id = insert_dummy_document(&state.db, &state.storage, "Feed & Document", None);
+
+        let response = get_feed_atom(Query(FeedQuery::default()), axum::extract::State(state))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers().get(header::CONTENT_TYPE).unwrap(),
+            "application/atom+xml; charset=utf-8"
+        );
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let feed = String::from_utf8(body.to_vec()).unwrap();
+
+        // Required Atom elements per RFC 4287
+        assert!(feed.starts_with("<?xml version=\"1.0\" encoding=\"utf-8\"?>"));
+        assert!(feed.contains(r#"<feed xmlns="http://www.w3.org/2005/Atom">"#));
+        assert!(feed.contains("<id>urn:podnet:feed</id>"));
+        assert!(feed.contains("<updated>"));
+        assert!(feed.contains("<entry>"));
+        assert!(feed.contains("<title>Feed &amp; Document</title>"));
+        assert!(feed.contains(&format!(r#"<link href="/documents/{doc_id}"/>"#)));
+        // Entry ids are derived from post id and revision
+        assert!(feed.contains(":rev:1</id>"));
+    }
+}
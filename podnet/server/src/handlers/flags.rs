@@ -0,0 +1,471 @@
+//! Content flagging and the admin moderation queue.
+
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Json, Response},
+};
+use pod2::middleware::Value;
+use podnet_models::{
+    DocumentFlag, FlagAction, FlagRequest, FlaggedDocument, ResolveFlagRequest,
+    mainpod::flag::verify_flag_verification_with_solver,
+};
+
+pub async fn flag_document(
+    Path(document_id): Path<i64>,
+    State(state): State<Arc<crate::AppState>>,
+    Json(payload): Json<FlagRequest>,
+) -> Result<Response, StatusCode> {
+    tracing::info!("Processing flag for document {document_id} with main pod verification");
+
+    if payload.document_id != document_id {
+        tracing::error!(
+            "Document ID mismatch: path {} vs payload {}",
+            document_id,
+            payload.document_id
+        );
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    if payload.reason.trim().is_empty() {
+        tracing::error!("Flag reason cannot be empty");
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let (_vd_set, _prover) = state.pod_config.get_prover_setup()?;
+
+    // Verify main pod proof
+    tracing::info!("Verifying flag main pod proof");
+    payload.flag_main_pod.pod.verify().map_err(|e| {
+        tracing::error!("Failed to verify flag main pod: {e}");
+        StatusCode::UNAUTHORIZED
+    })?;
+    tracing::info!("✓ Flag main pod proof verified");
+
+    // Get the document to obtain its content hash for verification
+    let document = state
+        .db
+        .get_document_metadata(document_id)
+        .map_err(|e| {
+            tracing::error!("Database error retrieving document {document_id}: {e}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .ok_or_else(|| {
+            tracing::error!("Document {document_id} not found");
+            StatusCode::NOT_FOUND
+        })?;
+
+    // We need to verify with all registered identity servers, since we don't know which one was used
+    let identity_servers = state.db.get_all_identity_servers().map_err(|e| {
+        tracing::error!("Database error retrieving identity servers: {e}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    if identity_servers.is_empty() {
+        tracing::error!("No identity servers registered");
+        return Err(StatusCode::UNAUTHORIZED);
+    }
+
+    // Try verification with each registered identity server until one succeeds
+    let mut verification_succeeded = false;
+
+    'servers: for identity_server in &identity_servers {
+        // Skip revoked servers unless the identity pod predates the revocation
+        if !super::registration::identity_server_accepts_pod(
+            identity_server,
+            payload.identity_pod_issued_at.as_deref(),
+        ) {
+            tracing::info!(
+                "Skipping revoked identity server: {}",
+                identity_server.server_id
+            );
+            continue;
+        }
+
+        // Try every key that could have signed the identity pod (the current
+        // key, plus rotated-out keys valid at the claimed issuance time)
+        for key_json in super::registration::candidate_verification_keys(
+            &state.db,
+            identity_server,
+            payload.identity_pod_issued_at.as_deref(),
+        ) {
+            let server_pk: pod2::backends::plonky2::primitives::ec::curve::Point =
+                serde_json::from_str(&key_json).map_err(|e| {
+                    tracing::error!("Failed to parse identity server public key: {e}");
+                    StatusCode::INTERNAL_SERVER_ERROR
+                })?;
+
+            let server_pk_value = Value::from(server_pk);
+
+            tracing::info!(
+                "Trying flag verification with identity server: {}",
+                identity_server.server_id
+            );
+            match verify_flag_verification_with_solver(
+                &payload.flag_main_pod,
+                &payload.username,
+                &document.content_id,
+                &server_pk_value,
+            ) {
+                Ok(_) => {
+                    tracing::info!(
+                        "✓ Solver verification succeeded with identity server: {}",
+                        identity_server.server_id
+                    );
+                    verification_succeeded = true;
+                    break 'servers;
+                }
+                Err(_) => {
+                    tracing::debug!(
+                        "Verification failed with identity server: {}",
+                        identity_server.server_id
+                    );
+                }
+            }
+        }
+    }
+
+    if !verification_succeeded {
+        tracing::error!("Solver-based verification failed with all registered identity servers");
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    tracing::info!(
+        "✓ Solver verification passed: username={}, content_hash={}",
+        payload.username,
+        document.content_id
+    );
+
+    // One flag per verified identity per document
+    let already_flagged = state
+        .db
+        .user_has_flagged(document_id, &payload.username)
+        .map_err(|e| {
+            tracing::error!("Database error checking existing flag: {e}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    if already_flagged {
+        tracing::warn!(
+            "User {} has already flagged document {document_id}",
+            payload.username
+        );
+        return Err(StatusCode::CONFLICT);
+    }
+
+    let flag_id = state
+        .db
+        .create_flag(document_id, &payload.username, payload.reason.trim())
+        .map_err(|e| {
+            tracing::error!("Failed to store flag: {e}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    tracing::info!("✓ Flag stored with ID: {flag_id}");
+
+    apply_auto_hide_threshold(
+        &state.db,
+        state.config.flag_auto_hide_threshold,
+        document_id,
+        flag_id,
+    )?;
+
+    Ok(Json(serde_json::json!({
+        "success": true,
+        "flag_id": flag_id,
+        "document_id": document_id
+    }))
+    .into_response())
+}
+
+pub async fn get_flags(
+    State(state): State<Arc<crate::AppState>>,
+    headers: HeaderMap,
+) -> Result<Json<Vec<FlaggedDocument>>, StatusCode> {
+    super::admin::check_admin_token(state.config.admin_token.as_deref(), &headers)?;
+
+    let flagged = state.db.get_open_flagged_documents().map_err(|e| {
+        tracing::error!("Failed to list flagged documents: {e}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    Ok(Json(flagged))
+}
+
+pub async fn resolve_flag(
+    Path(flag_id): Path<i64>,
+    State(state): State<Arc<crate::AppState>>,
+    headers: HeaderMap,
+    Json(payload): Json<ResolveFlagRequest>,
+) -> Result<StatusCode, StatusCode> {
+    let admin_token = state.config.admin_token.as_deref();
+    super::admin::check_admin_token(admin_token, &headers)?;
+    // check_admin_token succeeded, so the token is configured
+    let admin_token_id = super::admin::admin_token_id(admin_token.unwrap());
+
+    let flag = state
+        .db
+        .get_flag(flag_id)
+        .map_err(|e| {
+            tracing::error!("Database error retrieving flag {flag_id}: {e}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .ok_or_else(|| {
+            tracing::error!("Flag {flag_id} not found");
+            StatusCode::NOT_FOUND
+        })?;
+
+    apply_flag_resolution(&state.db, &flag, payload.action, &admin_token_id)?;
+
+    tracing::info!(
+        "✓ Flag {flag_id} resolved with action '{}' for document {}",
+        payload.action.as_str(),
+        flag.document_id
+    );
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Resolve a flag and apply its side effects: a hide both hides the document
+/// and leaves an audit row naming the acting admin token. Factored out of the
+/// handler so the moderation flow can be exercised without HTTP plumbing.
+pub(crate) fn apply_flag_resolution(
+    db: &crate::db::Database,
+    flag: &DocumentFlag,
+    action: FlagAction,
+    admin_token_id: &str,
+) -> Result<(), StatusCode> {
+    let flag_id = flag.id.ok_or(StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    let resolved = db.resolve_flag(flag_id, action.as_str()).map_err(|e| {
+        tracing::error!("Failed to resolve flag {flag_id}: {e}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    if !resolved {
+        tracing::warn!("Flag {flag_id} is already resolved");
+        return Err(StatusCode::CONFLICT);
+    }
+
+    if action == FlagAction::Hide {
+        db.set_document_hidden(flag.document_id, true).map_err(|e| {
+            tracing::error!("Failed to hide document {}: {e}", flag.document_id);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+        db.record_moderation_action(flag_id, flag.document_id, action.as_str(), admin_token_id)
+            .map_err(|e| {
+                tracing::error!("Failed to record moderation action for flag {flag_id}: {e}");
+                StatusCode::INTERNAL_SERVER_ERROR
+            })?;
+    }
+
+    Ok(())
+}
+
+/// Actor recorded in the audit log when the server hides a document itself
+/// rather than an admin doing so.
+pub(crate) const AUTO_HIDE_ACTOR: &str = "auto";
+
+/// Hide a document once its unresolved flag count reaches the configured
+/// threshold. The flags stay open so an admin can still review them and
+/// dismiss the hide by resolving with a different action later. No-op when
+/// the threshold is unset or the document is already hidden.
+pub(crate) fn apply_auto_hide_threshold(
+    db: &crate::db::Database,
+    threshold: Option<u32>,
+    document_id: i64,
+    flag_id: i64,
+) -> Result<(), StatusCode> {
+    let Some(threshold) = threshold else {
+        return Ok(());
+    };
+
+    let already_hidden = db.document_is_hidden(document_id).map_err(|e| {
+        tracing::error!("Failed to check hidden state of document {document_id}: {e}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    if already_hidden {
+        return Ok(());
+    }
+
+    let unresolved = db.count_unresolved_flags(document_id).map_err(|e| {
+        tracing::error!("Failed to count unresolved flags for document {document_id}: {e}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    if unresolved < i64::from(threshold) {
+        return Ok(());
+    }
+
+    db.set_document_hidden(document_id, true).map_err(|e| {
+        tracing::error!("Failed to auto-hide document {document_id}: {e}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    db.record_moderation_action(flag_id, document_id, "auto_hide", AUTO_HIDE_ACTOR)
+        .map_err(|e| {
+            tracing::error!("Failed to record auto-hide for document {document_id}: {e}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    tracing::warn!(
+        "Auto-hid document {document_id}: {unresolved} unresolved flags (threshold {threshold})"
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{db::tests::insert_dummy_document, handlers::tests::create_mock_app_state};
+
+    #[tokio::test]
+    async fn test_flag_counting_groups_by_document() {
+        let state = create_mock_app_state().await;
+        let doc_a = insert_dummy_document(&state.db, &state.storage, "Document A", None);
+        let doc_b = insert_dummy_document(&state.db, &state.storage, "Document B", None);
+
+        state.db.create_flag(doc_a, "alice", "spam").unwrap();
+        state.db.create_flag(doc_a, "bob", "off-topic").unwrap();
+        let flag_b_id = state.db.create_flag(doc_b, "alice", "abuse").unwrap();
+
+        assert!(state.db.user_has_flagged(doc_a, "alice").unwrap());
+        assert!(!state.db.user_has_flagged(doc_b, "bob").unwrap());
+
+        let flagged = state.db.get_open_flagged_documents().unwrap();
+        assert_eq!(flagged.len(), 2);
+        let entry_a = flagged
+            .iter()
+            .find(|f| f.document_id == doc_a)
+            .expect("doc A should be in the queue");
+        assert_eq!(entry_a.flag_count, 2);
+        assert_eq!(entry_a.reasons, vec!["spam", "off-topic"]);
+        let entry_b = flagged
+            .iter()
+            .find(|f| f.document_id == doc_b)
+            .expect("doc B should be in the queue");
+        assert_eq!(entry_b.flag_count, 1);
+
+        // Resolved flags drop out of the queue
+        let flag_b = state.db.get_flag(flag_b_id).unwrap().unwrap();
+        apply_flag_resolution(&state.db, &flag_b, FlagAction::Dismiss, "unused").unwrap();
+        let flagged = state.db.get_open_flagged_documents().unwrap();
+        assert_eq!(flagged.len(), 1);
+        assert_eq!(flagged[0].document_id, doc_a);
+    }
+
+    #[tokio::test]
+    async fn test_hide_excludes_document_from_list_but_not_direct_get() {
+        let state = create_mock_app_state().await;
+        let visible = insert_dummy_document(&state.db, &state.storage, "Visible", None);
+        let flagged = insert_dummy_document(&state.db, &state.storage, "Flagged", None);
+
+        let flag_id = state.db.create_flag(flagged, "alice", "spam").unwrap();
+        let flag = state.db.get_flag(flag_id).unwrap().unwrap();
+        apply_flag_resolution(&state.db, &flag, FlagAction::Hide, "token-id").unwrap();
+
+        // The list query no longer returns the hidden document
+        let listed = state
+            .db
+            .get_top_level_documents_with_latest_reply(crate::db::DocumentSort::New, None, -1, 0)
+            .unwrap();
+        let listed_ids: Vec<i64> = listed.iter().filter_map(|d| d.metadata.id).collect();
+        assert!(listed_ids.contains(&visible));
+        assert!(!listed_ids.contains(&flagged));
+
+        // Direct retrieval still works, with the hidden marker set
+        let metadata = state.db.get_document_metadata(flagged).unwrap().unwrap();
+        assert!(metadata.hidden);
+        let metadata = state.db.get_document_metadata(visible).unwrap().unwrap();
+        assert!(!metadata.hidden);
+    }
+
+    #[tokio::test]
+    async fn test_auto_hide_at_unresolved_flag_threshold() {
+        let state = create_mock_app_state().await;
+        let doc_id = insert_dummy_document(&state.db, &state.storage, "Reported", None);
+
+        // One flag is below a threshold of two
+        let first = state.db.create_flag(doc_id, "alice", "spam").unwrap();
+        apply_auto_hide_threshold(&state.db, Some(2), doc_id, first).unwrap();
+        assert!(!state.db.document_is_hidden(doc_id).unwrap());
+
+        // With the threshold unset, nothing hides regardless of count
+        let second = state.db.create_flag(doc_id, "bob", "abuse").unwrap();
+        apply_auto_hide_threshold(&state.db, None, doc_id, second).unwrap();
+        assert!(!state.db.document_is_hidden(doc_id).unwrap());
+
+        // The second unresolved flag crosses the threshold
+        apply_auto_hide_threshold(&state.db, Some(2), doc_id, second).unwrap();
+        assert!(state.db.document_is_hidden(doc_id).unwrap());
+
+        let audit = state.db.get_moderation_audit_for_document(doc_id).unwrap();
+        assert_eq!(audit.len(), 1);
+        assert_eq!(audit[0].action, "auto_hide");
+        assert_eq!(audit[0].admin_token_id, AUTO_HIDE_ACTOR);
+
+        // The flags stay open for admin review
+        let flagged = state.db.get_open_flagged_documents().unwrap();
+        assert_eq!(flagged.len(), 1);
+        assert_eq!(flagged[0].flag_count, 2);
+
+        // A repeat call on an already-hidden document leaves a single audit row
+        let third = state.db.create_flag(doc_id, "carol", "spam").unwrap();
+        apply_auto_hide_threshold(&state.db, Some(2), doc_id, third).unwrap();
+        assert_eq!(
+            state
+                .db
+                .get_moderation_audit_for_document(doc_id)
+                .unwrap()
+                .len(),
+            1
+        );
+
+        // Resolved flags no longer count toward the threshold
+        let other = insert_dummy_document(&state.db, &state.storage, "Other", None);
+        let flag_id = state.db.create_flag(other, "alice", "spam").unwrap();
+        let flag = state.db.get_flag(flag_id).unwrap().unwrap();
+        apply_flag_resolution(&state.db, &flag, FlagAction::Dismiss, "token").unwrap();
+        let flag_id = state.db.create_flag(other, "bob", "spam").unwrap();
+        apply_auto_hide_threshold(&state.db, Some(2), other, flag_id).unwrap();
+        assert!(!state.db.document_is_hidden(other).unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_hide_records_audit_row() {
+        let state = create_mock_app_state().await;
+        let doc_id = insert_dummy_document(&state.db, &state.storage, "Audited", None);
+
+        let flag_id = state.db.create_flag(doc_id, "alice", "spam").unwrap();
+        let flag = state.db.get_flag(flag_id).unwrap().unwrap();
+
+        // A dismiss leaves no audit trail and does not hide the document
+        apply_flag_resolution(&state.db, &flag, FlagAction::Dismiss, "dismiss-token").unwrap();
+        assert!(
+            state
+                .db
+                .get_moderation_audit_for_document(doc_id)
+                .unwrap()
+                .is_empty()
+        );
+        assert!(!state.db.document_is_hidden(doc_id).unwrap());
+
+        // Resolving the same flag twice is refused
+        assert_eq!(
+            apply_flag_resolution(&state.db, &flag, FlagAction::Hide, "x").unwrap_err(),
+            StatusCode::CONFLICT
+        );
+
+        // A hide on a fresh flag writes the audit row with the acting token id
+        let flag_id = state.db.create_flag(doc_id, "bob", "abuse").unwrap();
+        let flag = state.db.get_flag(flag_id).unwrap().unwrap();
+        let token_id = super::super::admin::admin_token_id("hunter2");
+        apply_flag_resolution(&state.db, &flag, FlagAction::Hide, &token_id).unwrap();
+
+        let audit = state.db.get_moderation_audit_for_document(doc_id).unwrap();
+        assert_eq!(audit.len(), 1);
+        assert_eq!(audit[0].flag_id, flag_id);
+        assert_eq!(audit[0].document_id, doc_id);
+        assert_eq!(audit[0].action, "hide");
+        assert_eq!(audit[0].admin_token_id, token_id);
+        assert!(state.db.document_is_hidden(doc_id).unwrap());
+    }
+}
@@ -1,11 +1,56 @@
+pub mod admin;
 pub mod documents;
+pub mod events;
+pub mod feed;
+pub mod flags;
+pub mod notifications;
 pub mod posts;
 pub mod registration;
 pub mod server;
+pub mod tokens;
 pub mod upvotes;
 
+pub use admin::*;
 pub use documents::*;
+pub use events::*;
+pub use feed::*;
+pub use flags::*;
+pub use notifications::*;
 pub use posts::*;
 pub use registration::*;
 pub use server::*;
+pub use tokens::*;
 pub use upvotes::*;
+
+#[cfg(test)]
+pub mod tests {
+    use std::sync::Arc;
+
+    use crate::db::Database;
+
+    // Mock AppState for testing
+    pub async fn create_mock_app_state() -> Arc<crate::AppState> {
+        let db = Arc::new(
+            Database::new(":memory:")
+                .await
+                .expect("Failed to create test database"),
+        );
+
+        // Create minimal storage and config for testing
+        let storage =
+            Arc::new(crate::storage::ContentAddressedStorage::new("/tmp/test_storage").unwrap());
+        let config = crate::config::ServerConfig::load();
+        let pod_config = crate::pod::PodConfig::new(true); // Use mock proofs
+        let rate_limiters = crate::rate_limit::RateLimiters::from_config(&config);
+        let (events, _) = tokio::sync::broadcast::channel(crate::EVENT_CHANNEL_CAPACITY);
+
+        Arc::new(crate::AppState {
+            db,
+            storage,
+            config,
+            pod_config,
+            rate_limiters,
+            events,
+        })
+    }
+}
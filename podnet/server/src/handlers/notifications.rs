@@ -0,0 +1,127 @@
+use std::sync::Arc;
+
+use axum::{
+    Extension,
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::Json,
+};
+use podnet_models::{Notification, ThreadSubscribeRequest};
+
+use super::tokens::TokenUser;
+
+#[derive(Debug, serde::Deserialize)]
+pub struct NotificationsQuery {
+    pub username: String,
+    #[serde(default)]
+    pub unread_only: bool,
+}
+
+pub async fn get_notifications(
+    Query(params): Query<NotificationsQuery>,
+    State(state): State<Arc<crate::AppState>>,
+) -> Result<Json<Vec<Notification>>, StatusCode> {
+    let notifications = state
+        .db
+        .get_notifications(&params.username, params.unread_only)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(Json(notifications))
+}
+
+/// Resolve the subscription target for a thread endpoint: any document id in
+/// the thread maps to its root.
+fn resolve_thread_root(db: &crate::db::Database, document_id: i64) -> Result<i64, StatusCode> {
+    db.get_thread_root_id(document_id)
+        .map_err(|e| {
+            tracing::error!("Database error resolving thread root for {document_id}: {e}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .ok_or_else(|| {
+            tracing::error!("Document {document_id} not found");
+            StatusCode::NOT_FOUND
+        })
+}
+
+/// Resolve the requester's username from either a bearer token (attached as
+/// a [`TokenUser`] extension by the middleware) or an identity pod in the
+/// payload
+fn subscriber_username(
+    db: &crate::db::Database,
+    token_user: Option<Extension<TokenUser>>,
+    payload: &ThreadSubscribeRequest,
+) -> Result<String, StatusCode> {
+    if let Some(Extension(user)) = token_user {
+        return Ok(user.username);
+    }
+    let identity_pod = payload.identity_pod.as_ref().ok_or_else(|| {
+        tracing::error!("Subscription request carries neither a bearer token nor an identity pod");
+        StatusCode::UNAUTHORIZED
+    })?;
+    super::registration::verify_identity_pod_username(db, identity_pod)
+}
+
+pub async fn subscribe_to_thread(
+    Path(document_id): Path<i64>,
+    token_user: Option<Extension<TokenUser>>,
+    State(state): State<Arc<crate::AppState>>,
+    Json(payload): Json<ThreadSubscribeRequest>,
+) -> Result<Json<serde_json::Value>, StatusCode> {
+    let username = subscriber_username(&state.db, token_user, &payload)?;
+    let thread_root_id = resolve_thread_root(&state.db, document_id)?;
+
+    let created = state
+        .db
+        .subscribe_to_thread(&username, thread_root_id)
+        .map_err(|e| {
+            tracing::error!("Failed to subscribe {username} to thread {thread_root_id}: {e}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    tracing::info!("User {username} subscribed to thread {thread_root_id} (created: {created})");
+    Ok(Json(serde_json::json!({
+        "username": username,
+        "thread_root_id": thread_root_id,
+        "subscribed": true,
+    })))
+}
+
+pub async fn unsubscribe_from_thread(
+    Path(document_id): Path<i64>,
+    token_user: Option<Extension<TokenUser>>,
+    State(state): State<Arc<crate::AppState>>,
+    Json(payload): Json<ThreadSubscribeRequest>,
+) -> Result<Json<serde_json::Value>, StatusCode> {
+    let username = subscriber_username(&state.db, token_user, &payload)?;
+    let thread_root_id = resolve_thread_root(&state.db, document_id)?;
+
+    let removed = state
+        .db
+        .unsubscribe_from_thread(&username, thread_root_id)
+        .map_err(|e| {
+            tracing::error!("Failed to unsubscribe {username} from thread {thread_root_id}: {e}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    tracing::info!("User {username} unsubscribed from thread {thread_root_id} (removed: {removed})");
+    Ok(Json(serde_json::json!({
+        "username": username,
+        "thread_root_id": thread_root_id,
+        "subscribed": false,
+    })))
+}
+
+pub async fn mark_notification_read(
+    Path(id): Path<i64>,
+    State(state): State<Arc<crate::AppState>>,
+) -> Result<Json<serde_json::Value>, StatusCode> {
+    state
+        .db
+        .mark_notification_read(id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(Json(serde_json::json!({
+        "success": true,
+        "notification_id": id
+    })))
+}
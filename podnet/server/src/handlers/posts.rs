@@ -2,7 +2,7 @@ use std::sync::Arc;
 
 use axum::{
     extract::{Path, State},
-    http::StatusCode,
+    http::{HeaderMap, StatusCode},
     response::Json,
 };
 use podnet_models::PostWithDocuments;
@@ -67,3 +67,43 @@ pub async fn get_post_by_id(
     let post_with_documents = get_post_with_documents_from_db(id, state).await?;
     Ok(Json(post_with_documents))
 }
+
+fn set_pinned(
+    state: &crate::AppState,
+    headers: &HeaderMap,
+    post_id: i64,
+    pinned: bool,
+) -> Result<StatusCode, StatusCode> {
+    super::admin::check_admin_token(state.config.admin_token.as_deref(), headers)?;
+
+    let found = state.db.set_post_pinned(post_id, pinned).map_err(|e| {
+        tracing::error!("Failed to update pin state of post {post_id}: {e}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    if !found {
+        tracing::error!("Post {post_id} not found");
+        return Err(StatusCode::NOT_FOUND);
+    }
+
+    tracing::info!(
+        "✓ Post {post_id} {}",
+        if pinned { "pinned" } else { "unpinned" }
+    );
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub async fn pin_post(
+    Path(id): Path<i64>,
+    State(state): State<Arc<crate::AppState>>,
+    headers: HeaderMap,
+) -> Result<StatusCode, StatusCode> {
+    set_pinned(&state, &headers, id, true)
+}
+
+pub async fn unpin_post(
+    Path(id): Path<i64>,
+    State(state): State<Arc<crate::AppState>>,
+    headers: HeaderMap,
+) -> Result<StatusCode, StatusCode> {
+    set_pinned(&state, &headers, id, false)
+}
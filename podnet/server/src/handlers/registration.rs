@@ -1,10 +1,14 @@
 use std::sync::Arc;
 
-use axum::{extract::State, http::StatusCode, response::Json};
+use axum::{
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    response::Json,
+};
 use pod_utils::ValueExt;
 use podnet_models::{
-    IdentityServerChallengeRequest, IdentityServerChallengeResponse, IdentityServerRegistration,
-    ServerInfo,
+    IdentityServer, IdentityServerChallengeRequest, IdentityServerChallengeResponse,
+    IdentityServerKeyRotation, IdentityServerRegistration, IdentityServerRevocation, ServerInfo,
 };
 
 pub async fn request_identity_challenge(
@@ -21,14 +25,20 @@ pub async fn request_identity_challenge(
         payload.server_id
     );
 
-    // Generate a secure random challenge
-    let challenge: String = (0..32)
-        .map(|_| rand::rng().random::<u8>())
-        .map(|b| format!("{b:02x}"))
-        .collect();
+    // Generate a secure random challenge and a single-use nonce
+    let random_hex = || -> String {
+        (0..32)
+            .map(|_| rand::rng().random::<u8>())
+            .map(|b| format!("{b:02x}"))
+            .collect()
+    };
+    let challenge = random_hex();
+    let nonce = random_hex();
 
     // Create expiration timestamp (5 minutes from now)
-    let expires_at = chrono::Utc::now() + chrono::Duration::minutes(5);
+    let issued_at = chrono::Utc::now();
+    let issued_at_str = issued_at.to_rfc3339();
+    let expires_at = issued_at + chrono::Duration::minutes(5);
     let expires_at_str = expires_at.to_rfc3339();
 
     tracing::info!(
@@ -43,9 +53,14 @@ pub async fn request_identity_challenge(
     let mut challenge_builder = SignedDictBuilder::new(&params);
 
     challenge_builder.insert("challenge", challenge.as_str());
+    challenge_builder.insert("nonce", nonce.as_str());
+    challenge_builder.insert("issued_at", issued_at_str.as_str());
     challenge_builder.insert("expires_at", expires_at_str.as_str());
     challenge_builder.insert("identity_server_public_key", payload.public_key);
     challenge_builder.insert("server_id", payload.server_id.as_str());
+    // Bind the challenge to this instance so it cannot be replayed against
+    // another podnet server sharing the flow
+    challenge_builder.insert("podnet_server_public_key", crate::pod::get_server_public_key());
 
     // Sign with server's private key
     let server_secret_key = crate::pod::get_server_secret_key();
@@ -106,6 +121,59 @@ pub async fn register_identity_server(
         return Err(StatusCode::BAD_REQUEST);
     }
 
+    // 3b. The challenge must have been issued within the replay window
+    let issued_at_str = payload
+        .server_challenge_pod
+        .get("issued_at")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| {
+            tracing::error!("Server challenge pod missing issued_at");
+            StatusCode::BAD_REQUEST
+        })?;
+    let issued_at = chrono::DateTime::parse_from_rfc3339(issued_at_str).map_err(|e| {
+        tracing::error!("Invalid issued_at format: {e}");
+        StatusCode::BAD_REQUEST
+    })?;
+    let max_age = chrono::Duration::seconds(state.config.challenge_max_age_secs as i64);
+    if chrono::Utc::now().signed_duration_since(issued_at) > max_age {
+        tracing::error!("Challenge is older than the configured replay window");
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    // 3c. The challenge must be bound to this instance's key; a challenge
+    // issued by another podnet server is not acceptable here
+    let bound_server_key = payload
+        .server_challenge_pod
+        .get("podnet_server_public_key")
+        .and_then(|v| v.as_public_key())
+        .ok_or_else(|| {
+            tracing::error!("Server challenge pod missing podnet_server_public_key");
+            StatusCode::BAD_REQUEST
+        })?;
+    if *bound_server_key != server_public_key {
+        tracing::error!("Challenge pod is bound to a different podnet server instance");
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    // 3d. The nonce is single-use: consuming it up front means even a
+    // registration that fails later burns its challenge
+    let nonce = payload
+        .server_challenge_pod
+        .get("nonce")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| {
+            tracing::error!("Server challenge pod missing nonce");
+            StatusCode::BAD_REQUEST
+        })?;
+    let fresh = state.db.consume_challenge_nonce(nonce).map_err(|e| {
+        tracing::error!("Database error consuming challenge nonce: {e}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    if !fresh {
+        tracing::error!("Challenge nonce has already been used; rejecting replay");
+        return Err(StatusCode::UNAUTHORIZED);
+    }
+
     // 4. Extract challenge and identity server info from challenge pod
     let challenge = payload
         .server_challenge_pod
@@ -218,11 +286,851 @@ pub async fn register_identity_server(
             StatusCode::INTERNAL_SERVER_ERROR
         })?;
 
+    // The advertised endpoint is optional; without it the server is simply
+    // never health-polled.
+    let base_url = payload
+        .base_url
+        .as_deref()
+        .map(str::trim)
+        .filter(|url| !url.is_empty());
+    if let Some(base_url) = base_url {
+        state
+            .db
+            .set_identity_server_base_url(server_id, base_url)
+            .map_err(|e| {
+                tracing::error!("Failed to store base URL for identity server {server_id}: {e}");
+                StatusCode::INTERNAL_SERVER_ERROR
+            })?;
+    }
+
     tracing::info!("Identity server {server_id} registered successfully");
 
     // Return server info
     let server_pk = crate::pod::get_server_public_key();
     Ok(Json(ServerInfo {
         public_key: server_pk,
+        limits: state.config.content_limits.clone(),
     }))
 }
+
+/// Parse a SQLite CURRENT_TIMESTAMP value (always UTC)
+fn parse_sqlite_timestamp(ts: &str) -> Option<chrono::DateTime<chrono::Utc>> {
+    chrono::NaiveDateTime::parse_from_str(ts, "%Y-%m-%d %H:%M:%S")
+        .ok()
+        .map(|dt| dt.and_utc())
+}
+
+/// Decide whether an identity pod claimed to be issued at `claimed_issued_at`
+/// is acceptable given the server's revocation status. Pods from a revoked
+/// server are honored only when issued before the revocation timestamp.
+///
+/// The claimed issued-at is not bound into the MainPod's public statements, so
+/// this cutoff is only as strong as the claim; binding it cryptographically
+/// would require revising the verification predicates and is left as a TODO.
+pub(crate) fn identity_server_accepts_pod(
+    identity_server: &IdentityServer,
+    claimed_issued_at: Option<&str>,
+) -> bool {
+    let Some(revoked_at) = &identity_server.revoked_at else {
+        return true;
+    };
+    let Some(revoked_at) = parse_sqlite_timestamp(revoked_at) else {
+        return false;
+    };
+    match claimed_issued_at.and_then(|s| chrono::DateTime::parse_from_rfc3339(s).ok()) {
+        Some(issued_at) => issued_at < revoked_at,
+        None => false,
+    }
+}
+
+/// Public keys of `identity_server` that may have signed an identity pod
+/// claimed to be issued at `claimed_issued_at`: the currently valid key,
+/// plus any rotated-out key whose validity window contains the claimed
+/// issuance time. Without an issued-at claim only the current key is tried.
+pub(crate) fn candidate_verification_keys(
+    db: &crate::db::Database,
+    identity_server: &IdentityServer,
+    claimed_issued_at: Option<&str>,
+) -> Vec<String> {
+    let keys = db
+        .get_identity_server_keys(&identity_server.server_id)
+        .unwrap_or_default();
+    if keys.is_empty() {
+        // Server predates the key-history table; fall back to its current key
+        return vec![identity_server.public_key.clone()];
+    }
+
+    let issued_at =
+        claimed_issued_at.and_then(|s| chrono::DateTime::parse_from_rfc3339(s).ok());
+
+    keys.into_iter()
+        .filter(|key| match &key.valid_until {
+            // The current key is always a candidate
+            None => true,
+            // A rotated-out key only if the pod predates the window's close
+            Some(valid_until) => match (issued_at, parse_sqlite_timestamp(valid_until)) {
+                (Some(issued_at), Some(valid_until)) => issued_at < valid_until,
+                _ => false,
+            },
+        })
+        .map(|key| key.public_key)
+        .collect()
+}
+
+pub async fn rotate_identity_server(
+    State(state): State<Arc<crate::AppState>>,
+    Path(server_id): Path<String>,
+    Json(payload): Json<IdentityServerKeyRotation>,
+) -> Result<StatusCode, StatusCode> {
+    tracing::info!("Processing key rotation for identity server: {server_id}");
+
+    let identity_server = state
+        .db
+        .get_identity_server_by_id(&server_id)
+        .map_err(|e| {
+            tracing::error!("Database error retrieving identity server {server_id}: {e}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    if identity_server.revoked_at.is_some() {
+        tracing::warn!("Identity server {server_id} is revoked and cannot rotate keys");
+        return Err(StatusCode::CONFLICT);
+    }
+
+    let continuity_pod = &payload.continuity_pod;
+    continuity_pod.verify().map_err(|e| {
+        tracing::error!("Failed to verify continuity pod: {e}");
+        StatusCode::BAD_REQUEST
+    })?;
+
+    // The continuity pod must be signed by the server's current key
+    let current_pk: pod2::backends::plonky2::primitives::ec::curve::Point =
+        serde_json::from_str(&identity_server.public_key).map_err(|e| {
+            tracing::error!("Failed to parse identity server public key: {e}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+    if continuity_pod.public_key != current_pk {
+        tracing::error!("Continuity pod not signed by the current key of {server_id}");
+        return Err(StatusCode::UNAUTHORIZED);
+    }
+
+    let pod_server_id = continuity_pod
+        .get("server_id")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| {
+            tracing::error!("Continuity pod missing server_id");
+            StatusCode::BAD_REQUEST
+        })?;
+    if pod_server_id != server_id {
+        tracing::error!("Continuity pod server_id mismatch");
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let action = continuity_pod
+        .get("action")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| {
+            tracing::error!("Continuity pod missing action");
+            StatusCode::BAD_REQUEST
+        })?;
+    if action != "rotate" {
+        tracing::error!("Continuity pod action is not \"rotate\"");
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let new_public_key = continuity_pod
+        .get("new_public_key")
+        .and_then(|v| v.as_public_key())
+        .ok_or_else(|| {
+            tracing::error!("Continuity pod missing new_public_key");
+            StatusCode::BAD_REQUEST
+        })?;
+    let new_pk_string = serde_json::to_string(new_public_key).map_err(|e| {
+        tracing::error!("Unable to serialize new public key: {e}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    let rotated = state
+        .db
+        .rotate_identity_server_key(&server_id, &new_pk_string)
+        .map_err(|e| {
+            tracing::error!("Failed to rotate key for identity server {server_id}: {e}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+    if !rotated {
+        return Err(StatusCode::CONFLICT);
+    }
+
+    tracing::info!("Identity server {server_id} rotated to a new key");
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub async fn revoke_identity_server(
+    State(state): State<Arc<crate::AppState>>,
+    Path(server_id): Path<String>,
+    headers: HeaderMap,
+    Json(payload): Json<IdentityServerRevocation>,
+) -> Result<StatusCode, StatusCode> {
+    tracing::info!("Processing revocation request for identity server: {server_id}");
+
+    let identity_server = state
+        .db
+        .get_identity_server_by_id(&server_id)
+        .map_err(|e| {
+            tracing::error!("Database error retrieving identity server {server_id}: {e}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    if identity_server.revoked_at.is_some() {
+        tracing::warn!("Identity server {server_id} is already revoked");
+        return Err(StatusCode::CONFLICT);
+    }
+
+    // Operators may revoke with the admin token; otherwise the request must
+    // carry a revocation pod signed by the server's own key.
+    let admin_authorized =
+        super::admin::check_admin_token(state.config.admin_token.as_deref(), &headers).is_ok();
+
+    if !admin_authorized {
+        let revocation_pod = payload.revocation_pod.as_ref().ok_or_else(|| {
+            tracing::error!("Revocation request has neither admin token nor revocation pod");
+            StatusCode::UNAUTHORIZED
+        })?;
+
+        revocation_pod.verify().map_err(|e| {
+            tracing::error!("Failed to verify revocation pod: {e}");
+            StatusCode::BAD_REQUEST
+        })?;
+
+        let server_pk: pod2::backends::plonky2::primitives::ec::curve::Point =
+            serde_json::from_str(&identity_server.public_key).map_err(|e| {
+                tracing::error!("Failed to parse identity server public key: {e}");
+                StatusCode::INTERNAL_SERVER_ERROR
+            })?;
+
+        if revocation_pod.public_key != server_pk {
+            tracing::error!("Revocation pod not signed by identity server {server_id}");
+            return Err(StatusCode::UNAUTHORIZED);
+        }
+
+        let pod_server_id = revocation_pod
+            .get("server_id")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| {
+                tracing::error!("Revocation pod missing server_id");
+                StatusCode::BAD_REQUEST
+            })?;
+        if pod_server_id != server_id {
+            tracing::error!("Revocation pod server_id mismatch");
+            return Err(StatusCode::BAD_REQUEST);
+        }
+
+        let action = revocation_pod
+            .get("action")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| {
+                tracing::error!("Revocation pod missing action");
+                StatusCode::BAD_REQUEST
+            })?;
+        if action != "revoke" {
+            tracing::error!("Revocation pod action is not \"revoke\"");
+            return Err(StatusCode::BAD_REQUEST);
+        }
+    }
+
+    let revoked = state.db.revoke_identity_server(&server_id).map_err(|e| {
+        tracing::error!("Failed to revoke identity server {server_id}: {e}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    if !revoked {
+        return Err(StatusCode::CONFLICT);
+    }
+
+    tracing::info!("Identity server {server_id} revoked");
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// The identity server registry with the health-poll state for each entry
+pub async fn list_identity_servers(
+    State(state): State<Arc<crate::AppState>>,
+) -> Result<Json<Vec<IdentityServer>>, StatusCode> {
+    let identity_servers = state.db.get_all_identity_servers().map_err(|e| {
+        tracing::error!("Failed to fetch identity servers: {e}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    Ok(Json(identity_servers))
+}
+
+/// How long a health poll waits for an identity server's `GET /`
+const IDENTITY_POLL_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+
+/// The subset of an identity server's root `ServerInfo` the poller checks
+#[derive(serde::Deserialize)]
+struct AdvertisedServerInfo {
+    public_key: pod2::backends::plonky2::primitives::ec::curve::Point,
+}
+
+async fn fetch_advertised_key(client: &reqwest::Client, base_url: &str) -> anyhow::Result<String> {
+    let info: AdvertisedServerInfo = client
+        .get(format!("{}/", base_url.trim_end_matches('/')))
+        .timeout(IDENTITY_POLL_TIMEOUT)
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    Ok(serde_json::to_string(&info.public_key)?)
+}
+
+/// Poll every registered, unrevoked identity server that advertised an
+/// endpoint and record what was found there. A reachable server whose
+/// advertised key no longer matches its registered one is flagged; it may
+/// have rotated without telling us, or been replaced by an impostor.
+pub(crate) async fn poll_identity_servers(
+    state: &crate::AppState,
+    client: &reqwest::Client,
+) -> anyhow::Result<()> {
+    let identity_servers = state.db.get_all_identity_servers()?;
+    for server in identity_servers {
+        if server.revoked_at.is_some() {
+            continue;
+        }
+        let Some(base_url) = server.base_url.as_deref() else {
+            continue;
+        };
+        match fetch_advertised_key(client, base_url).await {
+            Ok(advertised_key) if advertised_key == server.public_key => {
+                state
+                    .db
+                    .record_identity_server_health(&server.server_id, "ok", true, Some(false))?;
+            }
+            Ok(_) => {
+                tracing::warn!(
+                    "Identity server {} advertises a public key that does not match its \
+                     registration",
+                    server.server_id
+                );
+                state.db.record_identity_server_health(
+                    &server.server_id,
+                    "key-mismatch",
+                    true,
+                    Some(true),
+                )?;
+            }
+            Err(e) => {
+                tracing::warn!("Identity server {} is unreachable: {e}", server.server_id);
+                state.db.record_identity_server_health(
+                    &server.server_id,
+                    "unreachable",
+                    false,
+                    None,
+                )?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Background task that periodically health-polls registered identity servers
+pub async fn identity_health_worker(state: Arc<crate::AppState>) {
+    let interval_secs = state.config.identity_poll_interval_secs;
+    if interval_secs == 0 {
+        return;
+    }
+    let client = reqwest::Client::new();
+    loop {
+        if let Err(e) = poll_identity_servers(&state, &client).await {
+            tracing::warn!("Identity server health poll failed: {e}");
+        }
+        tokio::time::sleep(std::time::Duration::from_secs(interval_secs)).await;
+    }
+}
+
+/// Log when an identity server issuing pods looks unhealthy according to the
+/// last poll. Advisory only: a stale poll is not grounds for rejection.
+pub(crate) fn warn_if_unhealthy(identity_server: &IdentityServer) {
+    if identity_server.key_mismatch {
+        tracing::warn!(
+            "Identity server {} last advertised a key that does not match its registration",
+            identity_server.server_id
+        );
+    } else if identity_server.last_status.as_deref() == Some("unreachable") {
+        tracing::warn!(
+            "Identity server {} was unreachable at its last health check",
+            identity_server.server_id
+        );
+    }
+}
+
+/// Verify an identity pod against the registered identity servers and return
+/// the username it binds. Shared by every endpoint that authorizes a request
+/// with a freshly issued identity pod.
+pub(crate) fn verify_identity_pod_username(
+    db: &crate::db::Database,
+    identity_pod: &pod2::frontend::SignedDict,
+) -> Result<String, StatusCode> {
+    identity_pod.verify().map_err(|e| {
+        tracing::error!("Failed to verify identity pod: {e}");
+        StatusCode::BAD_REQUEST
+    })?;
+
+    // The pod must come from a registered, unrevoked identity server
+    let signer_json = serde_json::to_string(&identity_pod.public_key).map_err(|e| {
+        tracing::error!("Failed to serialize identity pod signer: {e}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    let identity_server = db
+        .get_identity_server_by_public_key(&signer_json)
+        .map_err(|e| {
+            tracing::error!("Database error looking up identity server: {e}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .ok_or_else(|| {
+            tracing::error!("Identity pod not signed by a registered identity server");
+            StatusCode::UNAUTHORIZED
+        })?;
+    if !identity_server_accepts_pod(&identity_server, None) {
+        tracing::error!(
+            "Identity server {} is revoked; rejecting identity pod",
+            identity_server.server_id
+        );
+        return Err(StatusCode::UNAUTHORIZED);
+    }
+    warn_if_unhealthy(&identity_server);
+
+    identity_pod
+        .get("username")
+        .and_then(|v| v.as_str())
+        .map(str::to_string)
+        .ok_or_else(|| {
+            tracing::error!("Identity pod missing username");
+            StatusCode::BAD_REQUEST
+        })
+}
+
+/// Record the username a registered identity server currently binds to a
+/// user's public key. When the key was previously bound to a different
+/// username, the old name becomes an alias and historical documents start
+/// reporting the new display name.
+pub async fn update_user_identity(
+    State(state): State<Arc<crate::AppState>>,
+    Json(payload): Json<podnet_models::IdentityUpdateRequest>,
+) -> Result<Json<serde_json::Value>, StatusCode> {
+    let username = verify_identity_pod_username(&state.db, &payload.identity_pod)?;
+    let user_public_key = payload
+        .identity_pod
+        .get("user_public_key")
+        .and_then(|v| v.as_public_key())
+        .ok_or_else(|| {
+            tracing::error!("Identity pod missing user_public_key");
+            StatusCode::BAD_REQUEST
+        })?;
+    let user_pk_json = serde_json::to_string(&user_public_key).map_err(|e| {
+        tracing::error!("Failed to serialize user public key: {e}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    let previous_username = state
+        .db
+        .record_user_identity(&user_pk_json, &username)
+        .map_err(|e| {
+            tracing::error!("Failed to record user identity: {e}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+    match &previous_username {
+        Some(previous) => {
+            tracing::info!("User {previous} renamed to {username}");
+        }
+        None => tracing::info!("Recorded identity binding for {username}"),
+    }
+
+    Ok(Json(serde_json::json!({
+        "username": username,
+        "previous_username": previous_username,
+    })))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::Database;
+
+    fn identity_server(revoked_at: Option<&str>) -> IdentityServer {
+        IdentityServer {
+            id: Some(1),
+            server_id: "test-identity-server".to_string(),
+            public_key: "{}".to_string(),
+            challenge_pod: "{}".to_string(),
+            identity_pod: "{}".to_string(),
+            created_at: Some("2025-01-01 00:00:00".to_string()),
+            revoked_at: revoked_at.map(String::from),
+            base_url: None,
+            last_seen_at: None,
+            last_status: None,
+            key_mismatch: false,
+        }
+    }
+
+    #[test]
+    fn test_identity_server_accepts_pod_revocation_split() {
+        let active = identity_server(None);
+        assert!(identity_server_accepts_pod(&active, None));
+        assert!(identity_server_accepts_pod(
+            &active,
+            Some("2025-06-15T00:00:00+00:00")
+        ));
+
+        let revoked = identity_server(Some("2025-06-01 12:00:00"));
+        // Issued before the revocation timestamp: still honored
+        assert!(identity_server_accepts_pod(
+            &revoked,
+            Some("2025-05-31T23:59:59+00:00")
+        ));
+        // Issued after revocation, unparseable, or unclaimed: rejected
+        assert!(!identity_server_accepts_pod(
+            &revoked,
+            Some("2025-06-01T12:00:01+00:00")
+        ));
+        assert!(!identity_server_accepts_pod(&revoked, Some("not a date")));
+        assert!(!identity_server_accepts_pod(&revoked, None));
+    }
+
+    #[tokio::test]
+    async fn test_candidate_verification_keys_across_rotation() {
+        let db = Database::new(":memory:").await.unwrap();
+        db.create_identity_server("server-a", "\"old-key\"", "{}", "{}")
+            .unwrap();
+        assert!(
+            db.rotate_identity_server_key("server-a", "\"new-key\"")
+                .unwrap()
+        );
+
+        let server = db.get_identity_server_by_id("server-a").unwrap().unwrap();
+        assert_eq!(server.public_key, "\"new-key\"");
+
+        let keys = db.get_identity_server_keys("server-a").unwrap();
+        assert_eq!(keys.len(), 2);
+        assert!(keys[0].valid_until.is_some());
+        assert!(keys[1].valid_until.is_none());
+
+        // A pod issued before the rotation may verify against either key
+        let pre_rotation = candidate_verification_keys(
+            &db,
+            &server,
+            Some("2020-01-01T00:00:00+00:00"),
+        );
+        assert_eq!(pre_rotation, vec!["\"old-key\"", "\"new-key\""]);
+
+        // A pod issued after the old key's window closed must use the new key
+        let post_rotation = candidate_verification_keys(
+            &db,
+            &server,
+            Some("2099-01-01T00:00:00+00:00"),
+        );
+        assert_eq!(post_rotation, vec!["\"new-key\""]);
+
+        // Without an issued-at claim only the current key is tried
+        let unclaimed = candidate_verification_keys(&db, &server, None);
+        assert_eq!(unclaimed, vec!["\"new-key\""]);
+
+        // Rotation is refused for revoked servers
+        assert!(db.revoke_identity_server("server-a").unwrap());
+        assert!(
+            !db.rotate_identity_server_key("server-a", "\"newer-key\"")
+                .unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_revoke_identity_server_in_db() {
+        let db = Database::new(":memory:").await.unwrap();
+        db.create_identity_server("server-a", "{}", "{}", "{}")
+            .unwrap();
+
+        let before = db.get_identity_server_by_id("server-a").unwrap().unwrap();
+        assert!(before.revoked_at.is_none());
+
+        assert!(db.revoke_identity_server("server-a").unwrap());
+        // A second revocation is a no-op
+        assert!(!db.revoke_identity_server("server-a").unwrap());
+        assert!(!db.revoke_identity_server("missing").unwrap());
+
+        let after = db.get_identity_server_by_id("server-a").unwrap().unwrap();
+        assert!(after.revoked_at.is_some());
+
+        let all = db.get_all_identity_servers().unwrap();
+        assert_eq!(all.len(), 1);
+        assert!(all[0].revoked_at.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_update_user_identity_records_rename() {
+        use pod2::{
+            backends::plonky2::{primitives::ec::schnorr::SecretKey, signer::Signer},
+            frontend::SignedDictBuilder,
+            middleware::Params,
+        };
+        use podnet_models::IdentityUpdateRequest;
+
+        let state = crate::handlers::tests::create_mock_app_state().await;
+
+        let identity_sk = SecretKey::new_rand();
+        state
+            .db
+            .create_identity_server(
+                "test-identity",
+                &serde_json::to_string(&identity_sk.public_key()).unwrap(),
+                "{}",
+                "{}",
+            )
+            .unwrap();
+
+        let user_sk = SecretKey::new_rand();
+        let identity_pod = |username: &str| {
+            let mut builder = SignedDictBuilder::new(&Params::default());
+            builder.insert("username", username);
+            builder.insert("user_public_key", user_sk.public_key());
+            builder
+                .sign(&Signer(SecretKey(identity_sk.0.clone())))
+                .unwrap()
+        };
+
+        // First sighting of the key records the binding without a rename
+        let response = update_user_identity(
+            axum::extract::State(state.clone()),
+            Json(IdentityUpdateRequest {
+                identity_pod: identity_pod("alice"),
+            }),
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.0["previous_username"], serde_json::Value::Null);
+
+        // The same key with a new username is a rename
+        let response = update_user_identity(
+            axum::extract::State(state.clone()),
+            Json(IdentityUpdateRequest {
+                identity_pod: identity_pod("wonderland"),
+            }),
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.0["previous_username"], "alice");
+        assert_eq!(
+            state.db.resolve_display_name("alice").unwrap(),
+            "wonderland"
+        );
+
+        // Pods from unregistered signers are rejected
+        let mut builder = SignedDictBuilder::new(&Params::default());
+        builder.insert("username", "mallory");
+        builder.insert("user_public_key", user_sk.public_key());
+        let rogue_pod = builder.sign(&Signer(SecretKey::new_rand())).unwrap();
+        let error = update_user_identity(
+            axum::extract::State(state.clone()),
+            Json(IdentityUpdateRequest {
+                identity_pod: rogue_pod,
+            }),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(error, StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn test_registration_challenge_replay_protection() {
+        use pod2::{
+            backends::plonky2::{primitives::ec::schnorr::SecretKey, signer::Signer},
+            frontend::SignedDictBuilder,
+            middleware::Params,
+        };
+
+        let state = crate::handlers::tests::create_mock_app_state().await;
+        let identity_sk = SecretKey::new_rand();
+
+        // Shape a challenge pod the way request_identity_challenge does,
+        // with knobs for the replay-relevant entries
+        let challenge_pod = |server_id: &str,
+                             nonce: &str,
+                             issued_at: chrono::DateTime<chrono::Utc>,
+                             bound_key: pod2::backends::plonky2::primitives::ec::curve::Point| {
+            let mut builder = SignedDictBuilder::new(&Params::default());
+            builder.insert("challenge", "a-challenge");
+            builder.insert("nonce", nonce);
+            builder.insert("issued_at", issued_at.to_rfc3339().as_str());
+            builder.insert(
+                "expires_at",
+                (chrono::Utc::now() + chrono::Duration::minutes(5))
+                    .to_rfc3339()
+                    .as_str(),
+            );
+            builder.insert("identity_server_public_key", identity_sk.public_key());
+            builder.insert("server_id", server_id);
+            builder.insert("podnet_server_public_key", bound_key);
+            let server_sk = crate::pod::get_server_secret_key();
+            builder.sign(&Signer(SecretKey(server_sk.0.clone()))).unwrap()
+        };
+        let response_pod = |server_id: &str| {
+            let mut builder = SignedDictBuilder::new(&Params::default());
+            builder.insert("challenge", "a-challenge");
+            builder.insert("server_id", server_id);
+            builder.sign(&Signer(SecretKey(identity_sk.0.clone()))).unwrap()
+        };
+        let register = |server_id: &str, challenge_pod: pod2::frontend::SignedDict| {
+            register_identity_server(
+                axum::extract::State(state.clone()),
+                Json(IdentityServerRegistration {
+                    server_challenge_pod: challenge_pod,
+                    identity_response_pod: response_pod(server_id),
+                    base_url: None,
+                }),
+            )
+        };
+        let own_key = crate::pod::get_server_public_key();
+
+        // A fresh, correctly bound challenge registers successfully
+        let fresh = challenge_pod("ident-a", "nonce-1", chrono::Utc::now(), own_key);
+        register("ident-a", fresh.clone()).await.unwrap();
+        assert!(state.db.get_identity_server_by_id("ident-a").unwrap().is_some());
+
+        // Replaying the captured exchange is rejected before any conflict check
+        let error = register("ident-a", fresh).await.unwrap_err();
+        assert_eq!(error, StatusCode::UNAUTHORIZED);
+
+        // A challenge older than the replay window is rejected even though its
+        // nonce is unused and its expires_at claims otherwise
+        let stale_issued = chrono::Utc::now()
+            - chrono::Duration::seconds(state.config.challenge_max_age_secs as i64 + 60);
+        let stale = challenge_pod("ident-b", "nonce-2", stale_issued, own_key);
+        let error = register("ident-b", stale).await.unwrap_err();
+        assert_eq!(error, StatusCode::BAD_REQUEST);
+
+        // A challenge bound to another instance's key cannot be replayed here.
+        // It must still be signed by this server's key to get past signature
+        // checks, which is exactly the shared-key deployment the binding guards.
+        let foreign_key = SecretKey::new_rand().public_key();
+        let cross = challenge_pod("ident-c", "nonce-3", chrono::Utc::now(), foreign_key);
+        let error = register("ident-c", cross).await.unwrap_err();
+        assert_eq!(error, StatusCode::BAD_REQUEST);
+
+        // Neither rejected attempt registered anything
+        assert!(state.db.get_identity_server_by_id("ident-b").unwrap().is_none());
+        assert!(state.db.get_identity_server_by_id("ident-c").unwrap().is_none());
+    }
+
+    /// Serve a strawman-shaped `GET /` ServerInfo advertising `public_key`,
+    /// returning the base URL it is reachable at
+    async fn spawn_mock_identity_server(
+        public_key: pod2::backends::plonky2::primitives::ec::curve::Point,
+    ) -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let info = serde_json::json!({ "server_id": "mock", "public_key": public_key });
+        let app = axum::Router::new().route(
+            "/",
+            axum::routing::get(move || {
+                let info = info.clone();
+                async move { Json(info) }
+            }),
+        );
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        format!("http://{addr}")
+    }
+
+    /// An address nothing is listening on
+    async fn unreachable_url() -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        drop(listener);
+        format!("http://{addr}")
+    }
+
+    #[tokio::test]
+    async fn test_identity_server_health_polling() {
+        use pod2::backends::plonky2::primitives::ec::schnorr::SecretKey;
+
+        let state = crate::handlers::tests::create_mock_app_state().await;
+        let client = reqwest::Client::new();
+
+        let register = |server_id: &str, sk: &SecretKey| {
+            state
+                .db
+                .create_identity_server(
+                    server_id,
+                    &serde_json::to_string(&sk.public_key()).unwrap(),
+                    "{}",
+                    "{}",
+                )
+                .unwrap();
+        };
+
+        let healthy_sk = SecretKey::new_rand();
+        register("healthy", &healthy_sk);
+        let url = spawn_mock_identity_server(healthy_sk.public_key()).await;
+        state
+            .db
+            .set_identity_server_base_url("healthy", &url)
+            .unwrap();
+
+        // Registered under one key, but its endpoint advertises another
+        let shifty_sk = SecretKey::new_rand();
+        register("shifty", &shifty_sk);
+        let mismatch_url = spawn_mock_identity_server(SecretKey::new_rand().public_key()).await;
+        state
+            .db
+            .set_identity_server_base_url("shifty", &mismatch_url)
+            .unwrap();
+
+        register("offline", &SecretKey::new_rand());
+        state
+            .db
+            .set_identity_server_base_url("offline", &unreachable_url().await)
+            .unwrap();
+
+        // Never advertised an endpoint, so it is skipped entirely
+        register("silent", &SecretKey::new_rand());
+
+        poll_identity_servers(&state, &client).await.unwrap();
+
+        let healthy = state.db.get_identity_server_by_id("healthy").unwrap().unwrap();
+        assert_eq!(healthy.last_status.as_deref(), Some("ok"));
+        assert!(healthy.last_seen_at.is_some());
+        assert!(!healthy.key_mismatch);
+
+        let shifty = state.db.get_identity_server_by_id("shifty").unwrap().unwrap();
+        assert_eq!(shifty.last_status.as_deref(), Some("key-mismatch"));
+        assert!(shifty.last_seen_at.is_some());
+        assert!(shifty.key_mismatch);
+
+        let offline = state.db.get_identity_server_by_id("offline").unwrap().unwrap();
+        assert_eq!(offline.last_status.as_deref(), Some("unreachable"));
+        assert!(offline.last_seen_at.is_none());
+        assert!(!offline.key_mismatch);
+
+        let silent = state.db.get_identity_server_by_id("silent").unwrap().unwrap();
+        assert!(silent.last_status.is_none());
+
+        // Going dark keeps the mismatch flag: we could not re-compare keys
+        state
+            .db
+            .set_identity_server_base_url("shifty", &unreachable_url().await)
+            .unwrap();
+        poll_identity_servers(&state, &client).await.unwrap();
+        let shifty = state.db.get_identity_server_by_id("shifty").unwrap().unwrap();
+        assert_eq!(shifty.last_status.as_deref(), Some("unreachable"));
+        assert!(shifty.key_mismatch);
+
+        // Advertising the registered key again clears it
+        let honest_url = spawn_mock_identity_server(shifty_sk.public_key()).await;
+        state
+            .db
+            .set_identity_server_base_url("shifty", &honest_url)
+            .unwrap();
+        poll_identity_servers(&state, &client).await.unwrap();
+        let shifty = state.db.get_identity_server_by_id("shifty").unwrap().unwrap();
+        assert_eq!(shifty.last_status.as_deref(), Some("ok"));
+        assert!(!shifty.key_mismatch);
+    }
+}
@@ -1,7 +1,233 @@
-use axum::response::Json;
+use std::{
+    sync::Arc,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use axum::{
+    extract::State,
+    http::StatusCode,
+    response::{IntoResponse, Json, Response},
+};
+use hex::ToHex;
 use podnet_models::ServerInfo;
+use serde::Serialize;
 
-pub async fn root() -> Json<ServerInfo> {
+pub async fn root(State(state): State<Arc<crate::AppState>>) -> Json<ServerInfo> {
     let public_key = crate::pod::get_server_public_key();
-    Json(ServerInfo { public_key })
+    Json(ServerInfo {
+        public_key,
+        limits: state.config.content_limits.clone(),
+    })
+}
+
+/// Per-check timeout for the readiness probe. A hung dependency (e.g. a stuck
+/// storage volume) fails its check instead of hanging the probe.
+const HEALTH_CHECK_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Outcome of a single dependency check in the readiness probe
+#[derive(Debug, Serialize)]
+pub struct HealthCheck {
+    /// "ok", "warn", or "fail"
+    pub status: &'static str,
+    pub latency_ms: u128,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl HealthCheck {
+    fn is_fail(&self) -> bool {
+        self.status == "fail"
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct HealthReport {
+    /// "ok", "degraded" (warnings only), or "unhealthy"
+    pub status: &'static str,
+    pub database: HealthCheck,
+    pub storage: HealthCheck,
+    pub identity_servers: HealthCheck,
+}
+
+/// Run a blocking dependency check off the async runtime with a timeout.
+/// `Ok(None)` is healthy, `Ok(Some(msg))` is a warning, `Err(msg)` a failure.
+async fn run_check<F>(check: F) -> HealthCheck
+where
+    F: FnOnce() -> Result<Option<String>, String> + Send + 'static,
+{
+    let started = Instant::now();
+    let outcome = tokio::time::timeout(HEALTH_CHECK_TIMEOUT, tokio::task::spawn_blocking(check));
+    let (status, error) = match outcome.await {
+        Ok(Ok(Ok(None))) => ("ok", None),
+        Ok(Ok(Ok(Some(warning)))) => ("warn", Some(warning)),
+        Ok(Ok(Err(failure))) => ("fail", Some(failure)),
+        Ok(Err(e)) => ("fail", Some(format!("check panicked: {e}"))),
+        Err(_) => (
+            "fail",
+            Some(format!("timed out after {HEALTH_CHECK_TIMEOUT:?}")),
+        ),
+    };
+    HealthCheck {
+        status,
+        latency_ms: started.elapsed().as_millis(),
+        error,
+    }
+}
+
+pub(crate) async fn collect_health(state: &Arc<crate::AppState>) -> HealthReport {
+    let db = state.db.clone();
+    let database = run_check(move || {
+        db.ping()
+            .map(|_| None)
+            .map_err(|e| format!("database query failed: {e}"))
+    })
+    .await;
+
+    let storage_handle = state.storage.clone();
+    let storage = run_check(move || {
+        // Unique probe content so the round-trip actually writes a fresh blob
+        let nonce = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or(Duration::ZERO)
+            .as_nanos();
+        let probe = format!("healthz probe {nonce}");
+
+        let hash = storage_handle
+            .store(&probe)
+            .map_err(|e| format!("storage write failed: {e}"))?;
+        let read_back = storage_handle
+            .retrieve(&hash)
+            .map_err(|e| format!("storage read failed: {e}"))?;
+        if read_back.as_deref() != Some(probe.as_str()) {
+            return Err("storage probe read back different content".to_string());
+        }
+        storage_handle
+            .delete(&hash.encode_hex::<String>())
+            .map_err(|e| format!("storage delete failed: {e}"))?;
+        Ok(None)
+    })
+    .await;
+
+    let db = state.db.clone();
+    let identity_servers = run_check(move || {
+        let servers = db
+            .get_all_identity_servers()
+            .map_err(|e| format!("database query failed: {e}"))?;
+        if servers.is_empty() {
+            Ok(Some("no identity servers registered".to_string()))
+        } else {
+            Ok(None)
+        }
+    })
+    .await;
+
+    let status = if database.is_fail() || storage.is_fail() || identity_servers.is_fail() {
+        "unhealthy"
+    } else if [&database, &storage, &identity_servers]
+        .iter()
+        .any(|check| check.status == "warn")
+    {
+        "degraded"
+    } else {
+        "ok"
+    };
+
+    HealthReport {
+        status,
+        database,
+        storage,
+        identity_servers,
+    }
+}
+
+/// Liveness probe: answers 200 whenever the process is up. Load balancers use
+/// this to decide whether to restart the process, so it must not depend on
+/// the database or storage being reachable.
+pub async fn healthz() -> StatusCode {
+    StatusCode::OK
+}
+
+/// Readiness probe: exercises the database and content storage, and reports
+/// (without failing on) a missing identity server registration. Answers 503
+/// while a dependency is down so load balancers stop routing traffic here.
+pub async fn readyz(State(state): State<Arc<crate::AppState>>) -> Response {
+    let report = collect_health(&state).await;
+    let code = if report.status == "unhealthy" {
+        StatusCode::SERVICE_UNAVAILABLE
+    } else {
+        StatusCode::OK
+    };
+    (code, Json(report)).into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn state_with_storage_path(path: &str) -> Arc<crate::AppState> {
+        let db = Arc::new(
+            crate::db::Database::new(":memory:")
+                .await
+                .expect("Failed to create test database"),
+        );
+        let storage = Arc::new(crate::storage::ContentAddressedStorage::new(path).unwrap());
+        let config = crate::config::ServerConfig::load();
+        let pod_config = crate::pod::PodConfig::new(true);
+        let rate_limiters = crate::rate_limit::RateLimiters::from_config(&config);
+        let (events, _) = tokio::sync::broadcast::channel(crate::EVENT_CHANNEL_CAPACITY);
+        Arc::new(crate::AppState {
+            db,
+            storage,
+            config,
+            pod_config,
+            rate_limiters,
+            events,
+        })
+    }
+
+    #[tokio::test]
+    async fn test_healthz_always_reports_live() {
+        assert_eq!(healthz().await, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_readyz_reports_degraded_without_identity_servers() {
+        let path =
+            std::env::temp_dir().join(format!("podnet_health_test_{}", rand::random::<u64>()));
+        let state = state_with_storage_path(path.to_str().unwrap()).await;
+
+        let report = collect_health(&state).await;
+        assert_eq!(report.database.status, "ok");
+        assert_eq!(report.storage.status, "ok");
+        // Missing identity servers is a warning, not a failure
+        assert_eq!(report.identity_servers.status, "warn");
+        assert_eq!(report.status, "degraded");
+
+        let response = readyz(State(state)).await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let _ = std::fs::remove_dir_all(path);
+    }
+
+    #[tokio::test]
+    async fn test_readyz_fails_on_broken_storage() {
+        let path =
+            std::env::temp_dir().join(format!("podnet_health_test_{}", rand::random::<u64>()));
+        let state = state_with_storage_path(path.to_str().unwrap()).await;
+
+        // Replace the storage directory with a plain file so writes fail
+        std::fs::remove_dir_all(&path).unwrap();
+        std::fs::write(&path, "not a directory").unwrap();
+
+        let report = collect_health(&state).await;
+        assert_eq!(report.database.status, "ok");
+        assert_eq!(report.storage.status, "fail");
+        assert!(report.storage.error.is_some());
+        assert_eq!(report.status, "unhealthy");
+
+        let response = readyz(State(state)).await;
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+
+        let _ = std::fs::remove_file(path);
+    }
 }
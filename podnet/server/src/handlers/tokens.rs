@@ -0,0 +1,268 @@
+//! User-scoped API tokens for programmatic clients.
+//!
+//! Interactive clients authorize each request with a pod; bots and CI jobs
+//! instead exchange one identity pod for a long-lived bearer token via
+//! `POST /tokens`. The token is shown once and stored hashed. Routes opting
+//! into bearer auth run behind [`bearer_auth`], which resolves the token to a
+//! [`TokenUser`] extension the handler can use in place of pod verification;
+//! bearer tokens are only wired up for non-destructive operations.
+
+use std::sync::Arc;
+
+use axum::{
+    extract::{Request, State},
+    http::{HeaderMap, StatusCode, header},
+    middleware::Next,
+    response::{IntoResponse, Json, Response},
+};
+use podnet_models::{TokenIssueRequest, TokenIssueResponse};
+use sha2::{Digest, Sha256};
+
+/// The username a valid bearer token resolved to, inserted as a request
+/// extension by [`bearer_auth`]
+#[derive(Debug, Clone)]
+pub struct TokenUser {
+    pub username: String,
+}
+
+fn hash_token(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
+
+fn bearer_token(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get(header::AUTHORIZATION)?
+        .to_str()
+        .ok()?
+        .strip_prefix("Bearer ")
+}
+
+/// Issue a bearer token for the username bound by a verified identity pod
+pub async fn issue_token(
+    State(state): State<Arc<crate::AppState>>,
+    Json(payload): Json<TokenIssueRequest>,
+) -> Result<Json<TokenIssueResponse>, StatusCode> {
+    let username =
+        super::registration::verify_identity_pod_username(&state.db, &payload.identity_pod)?;
+
+    let token = format!("pnt_{}", hex::encode(rand::random::<[u8; 32]>()));
+    state
+        .db
+        .create_token(&hash_token(&token), &username)
+        .map_err(|e| {
+            tracing::error!("Failed to store API token: {e}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    tracing::info!("Issued API token for {username}");
+    Ok(Json(TokenIssueResponse { token, username }))
+}
+
+/// Revoke the bearer token presented in the Authorization header. Holding
+/// the token is sufficient authorization to retire it.
+pub async fn revoke_token(
+    State(state): State<Arc<crate::AppState>>,
+    headers: HeaderMap,
+) -> Result<Json<serde_json::Value>, StatusCode> {
+    let token = bearer_token(&headers).ok_or(StatusCode::UNAUTHORIZED)?;
+    let revoked = state.db.revoke_token(&hash_token(token)).map_err(|e| {
+        tracing::error!("Failed to revoke API token: {e}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    if !revoked {
+        return Err(StatusCode::UNAUTHORIZED);
+    }
+
+    tracing::info!("Revoked an API token");
+    Ok(Json(serde_json::json!({ "revoked": true })))
+}
+
+/// Middleware resolving `Authorization: Bearer` into a [`TokenUser`]
+/// extension. Requests without the header pass through untouched, so
+/// pod-based auth keeps working; an invalid or revoked token is rejected
+/// rather than silently downgraded.
+pub async fn bearer_auth(
+    State(state): State<Arc<crate::AppState>>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    if let Some(token) = bearer_token(request.headers()) {
+        match state.db.get_token_username(&hash_token(token)) {
+            Ok(Some(username)) => {
+                request.extensions_mut().insert(TokenUser { username });
+            }
+            Ok(None) => {
+                tracing::warn!("Request presented an unknown or revoked API token");
+                return StatusCode::UNAUTHORIZED.into_response();
+            }
+            Err(e) => {
+                tracing::error!("Database error resolving API token: {e}");
+                return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+            }
+        }
+    }
+    next.run(request).await
+}
+
+#[cfg(test)]
+mod tests {
+    use pod2::{
+        backends::plonky2::{primitives::ec::schnorr::SecretKey, signer::Signer},
+        frontend::SignedDictBuilder,
+        middleware::Params,
+    };
+
+    use super::*;
+    use crate::handlers::tests::create_mock_app_state;
+
+    fn auth_headers(token: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            header::AUTHORIZATION,
+            format!("Bearer {token}").parse().unwrap(),
+        );
+        headers
+    }
+
+    async fn issue_for(state: &Arc<crate::AppState>, username: &str) -> TokenIssueResponse {
+        let identity_sk = SecretKey::new_rand();
+        state
+            .db
+            .create_identity_server(
+                "test-identity",
+                &serde_json::to_string(&identity_sk.public_key()).unwrap(),
+                "{}",
+                "{}",
+            )
+            .unwrap();
+
+        let mut builder = SignedDictBuilder::new(&Params::default());
+        builder.insert("username", username);
+        builder.insert("user_public_key", SecretKey::new_rand().public_key());
+        let identity_pod = builder.sign(&Signer(identity_sk)).unwrap();
+
+        issue_token(
+            axum::extract::State(state.clone()),
+            Json(TokenIssueRequest { identity_pod }),
+        )
+        .await
+        .unwrap()
+        .0
+    }
+
+    #[tokio::test]
+    async fn test_issue_use_and_revoke_token() {
+        let state = create_mock_app_state().await;
+        let issued = issue_for(&state, "bot-alice").await;
+        assert_eq!(issued.username, "bot-alice");
+        assert!(issued.token.starts_with("pnt_"));
+
+        // The stored hash resolves back to the username; the raw token is
+        // never persisted
+        assert_eq!(
+            state
+                .db
+                .get_token_username(&hash_token(&issued.token))
+                .unwrap()
+                .as_deref(),
+            Some("bot-alice")
+        );
+        assert!(
+            state
+                .db
+                .get_token_username(&hash_token("pnt_unknown"))
+                .unwrap()
+                .is_none()
+        );
+
+        // Revocation requires the token itself and takes effect immediately
+        let response = revoke_token(
+            axum::extract::State(state.clone()),
+            auth_headers(&issued.token),
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.0["revoked"], true);
+        assert!(
+            state
+                .db
+                .get_token_username(&hash_token(&issued.token))
+                .unwrap()
+                .is_none()
+        );
+
+        // Revoking again, or with a bogus token, is unauthorized
+        let error = revoke_token(
+            axum::extract::State(state.clone()),
+            auth_headers(&issued.token),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(error, StatusCode::UNAUTHORIZED);
+        let error = revoke_token(axum::extract::State(state), HeaderMap::new())
+            .await
+            .unwrap_err();
+        assert_eq!(error, StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn test_issue_token_rejects_unregistered_signer() {
+        let state = create_mock_app_state().await;
+
+        let mut builder = SignedDictBuilder::new(&Params::default());
+        builder.insert("username", "mallory");
+        builder.insert("user_public_key", SecretKey::new_rand().public_key());
+        let rogue_pod = builder.sign(&Signer(SecretKey::new_rand())).unwrap();
+
+        let error = issue_token(
+            axum::extract::State(state),
+            Json(TokenIssueRequest {
+                identity_pod: rogue_pod,
+            }),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(error, StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn test_bearer_token_subscribes_without_identity_pod() {
+        use axum::Extension;
+
+        use crate::handlers::notifications::subscribe_to_thread;
+
+        let state = create_mock_app_state().await;
+        let issued = issue_for(&state, "bot-alice").await;
+        let doc_id =
+            crate::db::tests::insert_dummy_document(&state.db, &state.storage, "Thread root", None);
+
+        // With a TokenUser extension (as bearer_auth would attach) the
+        // payload needs no identity pod
+        let response = subscribe_to_thread(
+            axum::extract::Path(doc_id),
+            Some(Extension(TokenUser {
+                username: issued.username.clone(),
+            })),
+            axum::extract::State(state.clone()),
+            Json(podnet_models::ThreadSubscribeRequest { identity_pod: None }),
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.0["username"], "bot-alice");
+        assert_eq!(
+            state.db.get_thread_subscribers(doc_id).unwrap(),
+            vec!["bot-alice"]
+        );
+
+        // Without either credential the request is refused
+        let error = subscribe_to_thread(
+            axum::extract::Path(doc_id),
+            None,
+            axum::extract::State(state),
+            Json(podnet_models::ThreadSubscribeRequest { identity_pod: None }),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(error, StatusCode::UNAUTHORIZED);
+    }
+}
@@ -3,8 +3,9 @@ use std::sync::Arc;
 use axum::{
     extract::{Path, State},
     http::StatusCode,
-    response::Json,
+    response::{IntoResponse, Json, Response},
 };
+use hex::ToHex;
 use pod2::{
     frontend::MainPod,
     middleware::{Hash, Value},
@@ -16,12 +17,13 @@ use podnet_models::{
         prove_upvote_count_inductive_with_solver, verify_upvote_verification_with_solver,
     },
 };
+use serde::Serialize;
 
 pub async fn upvote_document(
     Path(document_id): Path<i64>,
     State(state): State<Arc<crate::AppState>>,
     Json(payload): Json<UpvoteRequest>,
-) -> Result<Json<serde_json::Value>, StatusCode> {
+) -> Result<Response, StatusCode> {
     tracing::info!("Processing upvote for document {document_id} with main pod verification");
 
     let (_vd_set, _prover) = state.pod_config.get_prover_setup()?;
@@ -63,41 +65,59 @@ pub async fn upvote_document(
     // Try verification with each registered identity server until one succeeds
     let mut verification_succeeded = false;
 
-    for identity_server in &identity_servers {
-        // Parse the identity server public key from database
-        let server_pk: pod2::backends::plonky2::primitives::ec::curve::Point =
-            serde_json::from_str(&identity_server.public_key).map_err(|e| {
-                tracing::error!("Failed to parse identity server public key: {e}");
-                StatusCode::INTERNAL_SERVER_ERROR
-            })?;
-
-        let server_pk_value = Value::from(server_pk);
-
-        // Try verification with this identity server using username from request
-        tracing::info!(
-            "Trying upvote verification with identity server: {}",
-            identity_server.server_id
-        );
-        match verify_upvote_verification_with_solver(
-            &payload.upvote_main_pod,
-            &payload.username,
-            &document.content_id,
-            &server_pk_value,
+    'servers: for identity_server in &identity_servers {
+        // Skip revoked servers unless the identity pod predates the revocation
+        if !super::registration::identity_server_accepts_pod(
+            identity_server,
+            payload.identity_pod_issued_at.as_deref(),
         ) {
-            Ok(_) => {
-                tracing::info!(
-                    "✓ Solver verification succeeded with identity server: {}",
-                    identity_server.server_id
-                );
-                verification_succeeded = true;
-                break;
-            }
-            Err(_) => {
-                tracing::debug!(
-                    "Verification failed with identity server: {}",
-                    identity_server.server_id
-                );
-                continue;
+            tracing::info!(
+                "Skipping revoked identity server: {}",
+                identity_server.server_id
+            );
+            continue;
+        }
+
+        // Try every key that could have signed the identity pod (the current
+        // key, plus rotated-out keys valid at the claimed issuance time)
+        for key_json in super::registration::candidate_verification_keys(
+            &state.db,
+            identity_server,
+            payload.identity_pod_issued_at.as_deref(),
+        ) {
+            let server_pk: pod2::backends::plonky2::primitives::ec::curve::Point =
+                serde_json::from_str(&key_json).map_err(|e| {
+                    tracing::error!("Failed to parse identity server public key: {e}");
+                    StatusCode::INTERNAL_SERVER_ERROR
+                })?;
+
+            let server_pk_value = Value::from(server_pk);
+
+            // Try verification with this identity server using username from request
+            tracing::info!(
+                "Trying upvote verification with identity server: {}",
+                identity_server.server_id
+            );
+            match verify_upvote_verification_with_solver(
+                &payload.upvote_main_pod,
+                &payload.username,
+                &document.content_id,
+                &server_pk_value,
+            ) {
+                Ok(_) => {
+                    tracing::info!(
+                        "✓ Solver verification succeeded with identity server: {}",
+                        identity_server.server_id
+                    );
+                    verification_succeeded = true;
+                    break 'servers;
+                }
+                Err(_) => {
+                    tracing::debug!(
+                        "Verification failed with identity server: {}",
+                        identity_server.server_id
+                    );
+                }
             }
         }
     }
@@ -113,6 +133,15 @@ pub async fn upvote_document(
         document.content_id
     );
 
+    // Rate limit by verified username now that we trust the claimed identity
+    if let Err(retry_after) = state.rate_limiters.upvote.check(&payload.username) {
+        tracing::warn!(
+            "Upvote rate limit exceeded for user {}, retry after {retry_after}s",
+            payload.username
+        );
+        return Ok(crate::rate_limit::too_many_requests(retry_after));
+    }
+
     // Content hash verification was already done during solver verification
 
     // Check if user has already upvoted this document (by username)
@@ -156,34 +185,202 @@ pub async fn upvote_document(
 
     tracing::info!("Document {document_id} now has {upvote_count} upvotes");
 
-    // Spawn background task to generate inductive upvote count pod
-    let state_clone = state.clone();
-    let doc_id = document_id;
-    let hash = document.content_id;
-    let current_count = upvote_count;
-
-    tokio::spawn(async move {
-        if let Err(e) = generate_inductive_upvote_pod(
-            state_clone,
-            doc_id,
-            &hash,
-            current_count,
-            &payload.upvote_main_pod,
-        )
-        .await
-        {
-            tracing::error!(
-                "Failed to generate inductive upvote count pod for document {doc_id}: {e}"
-            );
-        }
-    });
+    super::events::emit_upvote(&state, document.post_id, document_id, upvote_count);
+
+    // Queue the expensive recursive proof instead of proving in the request
+    // path; the worker coalesces a burst of upvotes into one proving pass.
+    let newly_queued = state.db.enqueue_upvote_recount(document_id).map_err(|e| {
+        tracing::error!("Failed to enqueue upvote recount: {e}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    if newly_queued {
+        tracing::info!("Queued upvote count pod recount for document {document_id}");
+    } else {
+        tracing::debug!("Upvote count pod recount for document {document_id} already queued");
+    }
 
     Ok(Json(serde_json::json!({
         "success": true,
         "upvote_id": upvote_id,
         "document_id": document_id,
-        "upvote_count": upvote_count
-    })))
+        "upvote_count": upvote_count,
+        "upvote_count_pod": "pending"
+    }))
+    .into_response())
+}
+
+/// Upper bound on upvote pods verified concurrently in one batch request.
+const UPVOTE_VERIFY_CONCURRENCY: usize = 4;
+
+#[derive(Debug, Serialize)]
+pub struct UpvoteVerification {
+    pub upvote_id: Option<i64>,
+    pub username: String,
+    /// Statements hash of the stored MainPod; absent when the stored JSON
+    /// does not even deserialize.
+    pub pod_id: Option<String>,
+    /// The pod's proof verifies cryptographically.
+    pub valid: bool,
+    /// The pod's public statements bind the stored username and the
+    /// document's content hash to a registered identity server key.
+    pub username_matches: bool,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct UpvoteVerificationReport {
+    pub document_id: i64,
+    pub upvotes: Vec<UpvoteVerification>,
+    /// Upvotes whose proof verifies and whose statements match.
+    pub valid_count: i64,
+    /// The count the stored upvote count pod proves.
+    pub proven_count: i64,
+    pub count_pod_matches: bool,
+}
+
+fn verify_stored_upvote(
+    upvote: &podnet_models::Upvote,
+    content_hash: &Hash,
+    identity_server_keys: &[Value],
+) -> UpvoteVerification {
+    let mut result = UpvoteVerification {
+        upvote_id: upvote.id,
+        username: upvote.username.clone(),
+        pod_id: None,
+        valid: false,
+        username_matches: false,
+        error: None,
+    };
+
+    let main_pod: MainPod = match serde_json::from_str(&upvote.pod_json) {
+        Ok(pod) => pod,
+        Err(e) => {
+            result.error = Some(format!("Stored pod does not deserialize: {e}"));
+            return result;
+        }
+    };
+    result.pod_id = Some(main_pod.statements_hash().encode_hex());
+
+    if let Err(e) = main_pod.pod.verify() {
+        result.error = Some(format!("Pod proof does not verify: {e}"));
+        return result;
+    }
+    result.valid = true;
+
+    result.username_matches = identity_server_keys.iter().any(|server_pk| {
+        verify_upvote_verification_with_solver(
+            &main_pod,
+            &upvote.username,
+            content_hash,
+            server_pk,
+        )
+        .is_ok()
+    });
+    if !result.username_matches {
+        result.error =
+            Some("Pod statements do not match the stored username and content hash".to_string());
+    }
+
+    result
+}
+
+/// Re-verify every stored upvote pod for a document in one pass.
+///
+/// Invalid upvotes are flagged in the report rather than dropped, and the
+/// count the stored upvote count pod proves is compared against the number
+/// of upvotes that still verify. Stored upvotes do not record the identity
+/// pod's issuance time, so each pod is checked against the keys identity
+/// servers currently accept.
+pub async fn verify_document_upvotes(
+    Path(document_id): Path<i64>,
+    State(state): State<Arc<crate::AppState>>,
+) -> Result<Json<UpvoteVerificationReport>, StatusCode> {
+    let document = state
+        .db
+        .get_document_metadata(document_id)
+        .map_err(|e| {
+            tracing::error!("Database error retrieving document {document_id}: {e}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .ok_or_else(|| {
+            tracing::error!("Document {document_id} not found");
+            StatusCode::NOT_FOUND
+        })?;
+    let content_hash = document.content_id;
+
+    let identity_servers = state.db.get_all_identity_servers().map_err(|e| {
+        tracing::error!("Database error retrieving identity servers: {e}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    let mut identity_server_keys = Vec::new();
+    for identity_server in &identity_servers {
+        if !super::registration::identity_server_accepts_pod(identity_server, None) {
+            continue;
+        }
+        for key_json in
+            super::registration::candidate_verification_keys(&state.db, identity_server, None)
+        {
+            let server_pk: pod2::backends::plonky2::primitives::ec::curve::Point =
+                serde_json::from_str(&key_json).map_err(|e| {
+                    tracing::error!("Failed to parse identity server public key: {e}");
+                    StatusCode::INTERNAL_SERVER_ERROR
+                })?;
+            identity_server_keys.push(Value::from(server_pk));
+        }
+    }
+    let identity_server_keys = Arc::new(identity_server_keys);
+
+    let upvotes = state.db.get_upvotes_by_document_id(document_id).map_err(|e| {
+        tracing::error!("Database error retrieving upvotes for document {document_id}: {e}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    let semaphore = Arc::new(tokio::sync::Semaphore::new(UPVOTE_VERIFY_CONCURRENCY));
+    let mut tasks = Vec::with_capacity(upvotes.len());
+    for upvote in upvotes {
+        // Holding the permit for the task's lifetime caps how many
+        // verifications run on the blocking pool at once
+        let permit = semaphore.clone().acquire_owned().await.map_err(|e| {
+            tracing::error!("Upvote verification semaphore closed: {e}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+        let keys = identity_server_keys.clone();
+        tasks.push(tokio::task::spawn_blocking(move || {
+            let _permit = permit;
+            verify_stored_upvote(&upvote, &content_hash, &keys)
+        }));
+    }
+
+    let mut results = Vec::with_capacity(tasks.len());
+    for task in tasks {
+        results.push(task.await.map_err(|e| {
+            tracing::error!("Upvote verification task failed: {e}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?);
+    }
+
+    let valid_count = results
+        .iter()
+        .filter(|r| r.valid && r.username_matches)
+        .count() as i64;
+    let proven_count = state.db.get_upvote_count_pod_count(document_id).map_err(|e| {
+        tracing::error!("Failed to get upvote count pod count: {e}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    tracing::info!(
+        "Verified {} stored upvotes for document {document_id}: {valid_count} valid",
+        results.len()
+    );
+
+    Ok(Json(UpvoteVerificationReport {
+        document_id,
+        upvotes: results,
+        valid_count,
+        proven_count,
+        count_pod_matches: proven_count == valid_count,
+    }))
 }
 
 pub async fn generate_base_case_upvote_pod(
@@ -218,7 +415,7 @@ pub async fn generate_base_case_upvote_pod(
 
     state
         .db
-        .update_upvote_count_pod(document_id, &pod_json)
+        .update_upvote_count_pod(document_id, &pod_json, 0)
         .map_err(|e| format!("Failed to store upvote count pod: {e}"))?;
 
     tracing::info!("✓ Stored base case upvote count pod for document {document_id}");
@@ -292,7 +489,7 @@ async fn generate_inductive_upvote_pod(
 
     state
         .db
-        .update_upvote_count_pod(document_id, &pod_json)
+        .update_upvote_count_pod(document_id, &pod_json, current_count)
         .map_err(|e| format!("Failed to store upvote count pod: {e}"))?;
 
     tracing::info!(
@@ -301,3 +498,331 @@ async fn generate_inductive_upvote_pod(
 
     Ok(())
 }
+
+/// How long the worker sleeps when the queue is empty.
+const JOB_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_millis(500);
+/// Base retry delay after a failed recount; doubles with every attempt.
+const JOB_RETRY_BASE_DELAY_SECS: i64 = 5;
+/// Attempts after which a recount job is failed for good.
+const JOB_MAX_ATTEMPTS: i64 = 5;
+
+/// Background worker that drains the upvote recount queue one job at a time.
+///
+/// Each job proves every upvote not yet covered by the stored upvote count
+/// pod, so ten quick upvotes on one document cost a single pass through the
+/// queue (or two, when some arrive while a pass is already running) rather
+/// than ten independently spawned proofs.
+pub async fn upvote_count_worker(state: Arc<crate::AppState>) {
+    loop {
+        let job = match state.db.claim_next_upvote_recount_job() {
+            Ok(job) => job,
+            Err(e) => {
+                tracing::error!("Failed to claim upvote recount job: {e}");
+                None
+            }
+        };
+        let Some(job) = job else {
+            tokio::time::sleep(JOB_POLL_INTERVAL).await;
+            continue;
+        };
+
+        match run_upvote_recount_job(&state, job.document_id).await {
+            Ok(count) => {
+                if let Err(e) = state.db.complete_upvote_recount_job(job.id) {
+                    tracing::error!("Failed to mark upvote recount job {} done: {e}", job.id);
+                }
+                tracing::info!(
+                    "✓ Upvote recount job {} proved count {count} for document {}",
+                    job.id,
+                    job.document_id
+                );
+            }
+            Err(e) => {
+                tracing::error!(
+                    "Upvote recount job {} for document {} failed (attempt {}): {e}",
+                    job.id,
+                    job.document_id,
+                    job.attempts
+                );
+                let retry = (job.attempts < JOB_MAX_ATTEMPTS)
+                    .then(|| JOB_RETRY_BASE_DELAY_SECS << (job.attempts - 1).clamp(0, 8));
+                if let Err(e) = state.db.fail_upvote_recount_job(job.id, &e.to_string(), retry) {
+                    tracing::error!("Failed to record upvote recount job failure: {e}");
+                }
+            }
+        }
+    }
+}
+
+/// The upvotes the stored upvote count pod does not cover yet, together with
+/// the count it currently proves. The recursive proof consumes upvotes in
+/// insertion order, one inductive step each.
+fn pending_upvote_steps(
+    db: &crate::db::Database,
+    document_id: i64,
+) -> Result<(i64, Vec<podnet_models::Upvote>), rusqlite::Error> {
+    let proven = db.get_upvote_count_pod_count(document_id)?;
+    let mut upvotes = db.get_upvotes_by_document_id(document_id)?;
+    upvotes.drain(..(proven as usize).min(upvotes.len()));
+    Ok((proven, upvotes))
+}
+
+/// Bring the document's upvote count pod up to date with the upvotes table,
+/// proving one inductive step per not-yet-counted upvote. Returns the count
+/// the final pod proves.
+async fn run_upvote_recount_job(
+    state: &Arc<crate::AppState>,
+    document_id: i64,
+) -> Result<i64, Box<dyn std::error::Error + Send + Sync>> {
+    let document = state
+        .db
+        .get_document_metadata(document_id)
+        .map_err(|e| format!("Failed to load document {document_id}: {e}"))?
+        .ok_or_else(|| format!("Document {document_id} not found"))?;
+    let content_hash = document.content_id;
+
+    if state
+        .db
+        .get_upvote_count_pod(document_id)
+        .map_err(|e| format!("Failed to load upvote count pod: {e}"))?
+        .is_none()
+    {
+        generate_base_case_upvote_pod(state.clone(), document_id, &content_hash).await?;
+    }
+
+    let (mut proven, remaining) = pending_upvote_steps(&state.db, document_id)
+        .map_err(|e| format!("Failed to determine pending upvote steps: {e}"))?;
+
+    for upvote in remaining {
+        let upvote_pod: MainPod = serde_json::from_str(&upvote.pod_json)
+            .map_err(|e| format!("Failed to parse upvote pod {:?}: {e}", upvote.id))?;
+        generate_inductive_upvote_pod(
+            state.clone(),
+            document_id,
+            &content_hash,
+            proven + 1,
+            &upvote_pod,
+        )
+        .await?;
+        proven += 1;
+    }
+
+    Ok(proven)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{db::tests::insert_dummy_document, handlers::tests::create_mock_app_state};
+
+    #[tokio::test]
+    async fn test_burst_of_upvotes_coalesces_into_at_most_two_passes() {
+        let state = create_mock_app_state().await;
+        let doc = insert_dummy_document(&state.db, &state.storage, "Popular", None);
+
+        // Ten quick upvotes each request a recount; only the first creates a job
+        for i in 0..10 {
+            state
+                .db
+                .create_upvote(doc, &format!("user{i}"), "{}")
+                .unwrap();
+            state.db.enqueue_upvote_recount(doc).unwrap();
+        }
+        let mut proving_passes = 0;
+
+        // The worker claims the single coalesced job
+        let job = state.db.claim_next_upvote_recount_job().unwrap().unwrap();
+        assert_eq!(job.document_id, doc);
+        assert!(state.db.claim_next_upvote_recount_job().unwrap().is_none());
+        proving_passes += 1;
+
+        // An upvote landing mid-pass queues exactly one follow-up job
+        state.db.create_upvote(doc, "latecomer", "{}").unwrap();
+        assert!(state.db.enqueue_upvote_recount(doc).unwrap());
+        assert!(!state.db.enqueue_upvote_recount(doc).unwrap());
+
+        // The pass proves every upvote visible at execution time
+        let (_, remaining) = pending_upvote_steps(&state.db, doc).unwrap();
+        state
+            .db
+            .update_upvote_count_pod(doc, r#"{"mock":"pod"}"#, remaining.len() as i64)
+            .unwrap();
+        state.db.complete_upvote_recount_job(job.id).unwrap();
+
+        // The follow-up pass finds nothing left to prove
+        let job = state.db.claim_next_upvote_recount_job().unwrap().unwrap();
+        proving_passes += 1;
+        let (proven, remaining) = pending_upvote_steps(&state.db, doc).unwrap();
+        assert_eq!(proven, 11);
+        assert!(remaining.is_empty());
+        state.db.complete_upvote_recount_job(job.id).unwrap();
+
+        assert!(proving_passes <= 2);
+        assert!(state.db.claim_next_upvote_recount_job().unwrap().is_none());
+        assert_eq!(
+            state.db.get_upvote_count_pod_count(doc).unwrap(),
+            state.db.get_upvote_count(doc).unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_batch_verification_flags_corrupted_pod_and_count_mismatch() {
+        use pod2::{
+            backends::plonky2::{primitives::ec::schnorr::SecretKey, signer::Signer},
+            frontend::SignedDictBuilder,
+        };
+        use pod_utils::prover_setup::PodNetProverSetup;
+        use podnet_models::mainpod::upvote::{
+            UpvoteProofParamsSolver, prove_upvote_verification_with_solver,
+        };
+
+        let state = create_mock_app_state().await;
+        let doc = insert_dummy_document(&state.db, &state.storage, "Audited", None);
+        let content_hash = state
+            .db
+            .get_document_metadata(doc)
+            .unwrap()
+            .unwrap()
+            .content_id;
+
+        let identity_sk = SecretKey::new_rand();
+        let identity_pk_json = serde_json::to_string(&identity_sk.public_key()).unwrap();
+        state
+            .db
+            .create_identity_server("test-ident", &identity_pk_json, "{}", "{}")
+            .unwrap();
+
+        // A genuine upvote: identity pod signed by the registered identity
+        // server, upvote pod signed by the user key the identity pod names
+        let user_sk = SecretKey::new_rand();
+        let params = PodNetProverSetup::get_params();
+        let mut identity_builder = SignedDictBuilder::new(&params);
+        identity_builder.insert("username", "alice");
+        identity_builder.insert("user_public_key", user_sk.public_key());
+        let identity_pod = identity_builder
+            .sign(&Signer(SecretKey(identity_sk.0.clone())))
+            .unwrap();
+
+        let mut upvote_builder = SignedDictBuilder::new(&params);
+        upvote_builder.insert("request_type", "upvote");
+        upvote_builder.insert("content_hash", content_hash);
+        upvote_builder.insert("timestamp", 1757398210_i64);
+        let upvote_pod = upvote_builder
+            .sign(&Signer(SecretKey(user_sk.0.clone())))
+            .unwrap();
+
+        let main_pod = prove_upvote_verification_with_solver(UpvoteProofParamsSolver {
+            identity_pod: &identity_pod,
+            upvote_pod: &upvote_pod,
+            use_mock_proofs: true,
+        })
+        .unwrap();
+        state
+            .db
+            .create_upvote(doc, "alice", &serde_json::to_string(&main_pod).unwrap())
+            .unwrap();
+
+        // A corrupted pod sits alongside the valid one
+        state.db.create_upvote(doc, "mallory", "{ not a pod").unwrap();
+
+        let report = verify_document_upvotes(Path(doc), State(state.clone()))
+            .await
+            .unwrap()
+            .0;
+
+        // Both upvotes appear in the report; the corrupted one is flagged,
+        // not dropped
+        assert_eq!(report.upvotes.len(), 2);
+        let alice = report.upvotes.iter().find(|r| r.username == "alice").unwrap();
+        assert!(alice.valid);
+        assert!(alice.username_matches);
+        assert!(alice.pod_id.is_some());
+        assert!(alice.error.is_none());
+        let mallory = report
+            .upvotes
+            .iter()
+            .find(|r| r.username == "mallory")
+            .unwrap();
+        assert!(!mallory.valid);
+        assert!(mallory.pod_id.is_none());
+        assert!(mallory.error.is_some());
+
+        // No recount has run yet, so the count pod still proves zero upvotes
+        // and the report says so
+        assert_eq!(report.valid_count, 1);
+        assert_eq!(report.proven_count, 0);
+        assert!(!report.count_pod_matches);
+    }
+
+    #[tokio::test]
+    async fn test_pending_steps_resume_from_proven_count() {
+        let state = create_mock_app_state().await;
+        let doc = insert_dummy_document(&state.db, &state.storage, "Resumable", None);
+
+        for i in 0..6 {
+            state
+                .db
+                .create_upvote(doc, &format!("user{i}"), "{}")
+                .unwrap();
+        }
+        state
+            .db
+            .update_upvote_count_pod(doc, r#"{"mock":"pod"}"#, 4)
+            .unwrap();
+
+        let (proven, remaining) = pending_upvote_steps(&state.db, doc).unwrap();
+        assert_eq!(proven, 4);
+        assert_eq!(
+            remaining
+                .iter()
+                .map(|u| u.username.as_str())
+                .collect::<Vec<_>>(),
+            vec!["user4", "user5"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_failed_job_backs_off_and_can_be_superseded() {
+        let state = create_mock_app_state().await;
+        let doc = insert_dummy_document(&state.db, &state.storage, "Flaky", None);
+
+        assert!(state.db.enqueue_upvote_recount(doc).unwrap());
+        let job = state.db.claim_next_upvote_recount_job().unwrap().unwrap();
+        assert_eq!(job.attempts, 1);
+
+        // A retryable failure returns the job to the queue with a backoff
+        // delay, so it is not immediately claimable again
+        state
+            .db
+            .fail_upvote_recount_job(job.id, "prover crashed", Some(30))
+            .unwrap();
+        let jobs = state.db.get_upvote_recount_jobs(10).unwrap();
+        assert_eq!(jobs[0].status, "pending");
+        assert!(jobs[0].not_before.is_some());
+        assert_eq!(jobs[0].last_error.as_deref(), Some("prover crashed"));
+        assert!(state.db.claim_next_upvote_recount_job().unwrap().is_none());
+
+        // Exhausted retries fail the job for good
+        state
+            .db
+            .fail_upvote_recount_job(job.id, "prover crashed again", None)
+            .unwrap();
+        let jobs = state.db.get_upvote_recount_jobs(10).unwrap();
+        assert_eq!(jobs[0].status, "failed");
+        assert!(jobs[0].completed_at.is_some());
+
+        // A failure while a newer job is already queued is superseded rather
+        // than re-queued next to it
+        assert!(state.db.enqueue_upvote_recount(doc).unwrap());
+        let running = state.db.claim_next_upvote_recount_job().unwrap().unwrap();
+        assert!(state.db.enqueue_upvote_recount(doc).unwrap());
+        state
+            .db
+            .fail_upvote_recount_job(running.id, "prover crashed", Some(30))
+            .unwrap();
+        let jobs = state.db.get_upvote_recount_jobs(10).unwrap();
+        let superseded = jobs.iter().find(|j| j.id == running.id).unwrap();
+        assert_eq!(superseded.status, "superseded");
+        assert!(state.db.claim_next_upvote_recount_job().unwrap().is_some());
+    }
+}
@@ -1,7 +1,9 @@
 mod config;
+mod cors;
 mod db;
 mod handlers;
 mod pod;
+mod rate_limit;
 mod storage;
 
 use std::sync::Arc;
@@ -10,14 +12,47 @@ use axum::{
     Router,
     routing::{delete, get, post},
 };
-use tower_http::cors::CorsLayer;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
+/// Capacity of the server event broadcast channel; slow SSE consumers that
+/// fall this far behind are disconnected rather than backing up the channel.
+pub const EVENT_CHANNEL_CAPACITY: usize = 256;
+
 pub struct AppState {
     pub db: Arc<db::Database>,
     pub storage: Arc<storage::ContentAddressedStorage>,
     pub config: config::ServerConfig,
     pub pod_config: pod::PodConfig,
+    pub rate_limiters: rate_limit::RateLimiters,
+    pub events: tokio::sync::broadcast::Sender<handlers::ServerEvent>,
+}
+
+/// Resolves on SIGINT or SIGTERM so the server can drain in-flight requests
+/// and close the database cleanly instead of dying mid-transaction.
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        tokio::signal::ctrl_c()
+            .await
+            .expect("failed to install Ctrl+C handler");
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+            .expect("failed to install SIGTERM handler")
+            .recv()
+            .await;
+    };
+
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {},
+        _ = terminate => {},
+    }
+
+    tracing::info!("Shutdown signal received, draining in-flight requests");
 }
 
 #[tokio::main]
@@ -44,28 +79,56 @@ async fn main() -> anyhow::Result<()> {
     tracing::info!("Database initialized successfully");
 
     tracing::info!("Initializing content storage...");
-    let storage = Arc::new(storage::ContentAddressedStorage::new(
-        &config.content_storage_path,
-    )?);
+    let storage = Arc::new(
+        storage::ContentAddressedStorage::new(&config.content_storage_path)?
+            .with_verify_on_read(config.verify_content_on_read),
+    );
     tracing::info!("Content storage initialized successfully");
 
+    if config.gc_on_startup {
+        tracing::info!("Running startup content garbage collection...");
+        if let Err(e) = handlers::run_content_gc(&db, &storage, handlers::GC_GRACE_PERIOD) {
+            tracing::error!("Startup content GC failed: {e}");
+        }
+    }
+
     let pod_config = pod::PodConfig::new(config.mock_proofs);
+    let rate_limiters = rate_limit::RateLimiters::from_config(&config);
+    let (events, _) = tokio::sync::broadcast::channel(EVENT_CHANNEL_CAPACITY);
     let state = Arc::new(AppState {
         db,
         storage,
         config,
         pod_config,
+        rate_limiters,
+        events,
     });
 
+    tracing::info!("Starting upvote count worker...");
+    tokio::spawn(handlers::upvote_count_worker(state.clone()));
+
+    tracing::info!("Starting identity server health poller...");
+    tokio::spawn(handlers::identity_health_worker(state.clone()));
+
+    tracing::info!("Starting backup scheduler...");
+    tokio::spawn(handlers::backup_worker(state.clone()));
+
     tracing::info!("Setting up routes...");
     let app = Router::new()
         .route("/", get(handlers::root))
+        // Readiness probe
+        .route("/healthz", get(handlers::healthz))
+        .route("/readyz", get(handlers::readyz))
         // Post routes
         .route("/posts", get(handlers::get_posts))
         .route("/posts/:id", get(handlers::get_post_by_id))
         // Document routes
         .route("/documents", get(handlers::get_documents))
         .route("/documents/:id", get(handlers::get_document_by_id))
+        .route(
+            "/documents/by-content/:hash",
+            get(handlers::get_document_by_content_id),
+        )
         .route(
             "/documents/:id/replies",
             get(handlers::get_document_replies),
@@ -74,21 +137,102 @@ async fn main() -> anyhow::Result<()> {
             "/documents/:id/reply-tree",
             get(handlers::get_document_reply_tree),
         )
+        .route(
+            "/documents/:id/reply-tree/expand",
+            get(handlers::expand_reply_tree_node),
+        )
+        .route(
+            "/documents/:id/export",
+            get(handlers::export_document_thread),
+        )
         .route("/documents/:id", delete(handlers::delete_document))
+        // Raw content blobs (document attachments)
+        .route("/content/:hash", get(handlers::get_content))
+        // Full-text search over documents
+        .route("/search", get(handlers::search_documents))
         // Publishing route
         .route("/publish", post(handlers::publish_document))
-        // Identity server routes
+        // Identity server routes (unauthenticated, rate limited by client IP)
+        .merge(
+            Router::new()
+                .route(
+                    "/identity/challenge",
+                    post(handlers::request_identity_challenge),
+                )
+                .route(
+                    "/identity/register",
+                    post(handlers::register_identity_server),
+                )
+                .layer(axum::middleware::from_fn_with_state(
+                    state.clone(),
+                    rate_limit::identity_rate_limit,
+                )),
+        )
+        // Username updates (authorized by a freshly issued identity pod)
+        .route(
+            "/identity/update",
+            post(handlers::update_user_identity),
+        )
+        // Identity server registry with health-poll status
+        .route(
+            "/identity-servers",
+            get(handlers::list_identity_servers),
+        )
+        // Identity server revocation (authorized by revocation pod or admin token)
         .route(
-            "/identity/challenge",
-            post(handlers::request_identity_challenge),
+            "/identity-servers/:server_id",
+            delete(handlers::revoke_identity_server),
         )
+        // Identity server key rotation (authorized by continuity pod)
         .route(
-            "/identity/register",
-            post(handlers::register_identity_server),
+            "/identity-servers/:server_id/rotate",
+            post(handlers::rotate_identity_server),
+        )
+        // API tokens for programmatic clients (issued against an identity pod)
+        .route(
+            "/tokens",
+            post(handlers::issue_token).delete(handlers::revoke_token),
+        )
+        // Thread subscriptions (authorized by a freshly issued identity pod
+        // or a bearer token)
+        .route(
+            "/threads/:id/subscribe",
+            post(handlers::subscribe_to_thread)
+                .delete(handlers::unsubscribe_from_thread)
+                .layer(axum::middleware::from_fn_with_state(
+                    state.clone(),
+                    handlers::bearer_auth,
+                )),
         )
         // Upvote routes
         .route("/documents/:id/upvote", post(handlers::upvote_document))
-        .layer(CorsLayer::permissive())
+        .route(
+            "/documents/:id/upvotes/verify",
+            get(handlers::verify_document_upvotes),
+        )
+        // Moderation routes
+        .route("/documents/:id/flag", post(handlers::flag_document))
+        .route("/admin/flags", get(handlers::get_flags))
+        .route("/admin/flags/:id/resolve", post(handlers::resolve_flag))
+        // Live event stream
+        .route("/events", get(handlers::event_stream))
+        // Syndication feed
+        .route("/feed.atom", get(handlers::get_feed_atom))
+        // Admin routes
+        .route("/admin/posts/:id/pin", post(handlers::pin_post))
+        .route("/admin/posts/:id/unpin", post(handlers::unpin_post))
+        .route("/admin/gc", post(handlers::gc_content))
+        .route("/admin/backup", post(handlers::create_backup))
+        .route("/admin/jobs", get(handlers::get_jobs))
+        .route("/admin/stats", get(handlers::get_stats))
+        .route("/metrics", get(handlers::get_metrics))
+        // Notification routes
+        .route("/notifications", get(handlers::get_notifications))
+        .route(
+            "/notifications/:id/read",
+            post(handlers::mark_notification_read),
+        )
+        .layer(cors::build_cors_layer(&state.config))
         .with_state(state);
 
     let bind_addr = format!("{host}:{port}");
@@ -97,18 +241,52 @@ async fn main() -> anyhow::Result<()> {
     tracing::info!("Server running on http://{}:{}", host, port);
     tracing::info!("Available endpoints:");
     tracing::info!("  GET  /                       - Root endpoint");
+    tracing::info!("  GET  /healthz                - Liveness probe");
+    tracing::info!("  GET  /readyz                 - Readiness probe with dependency checks");
     tracing::info!("  GET  /posts                  - List all posts");
     tracing::info!("  GET  /posts/:id              - Get post with documents");
     tracing::info!("  GET    /documents              - List all documents");
     tracing::info!("  GET    /documents/:id          - Get specific document");
+    tracing::info!("  GET    /documents/by-content/:hash - Look up a document by content hash");
     tracing::info!("  GET    /documents/:id/replies  - Get replies to a document");
     tracing::info!("  GET    /documents/:id/reply-tree - Get reply tree for a document");
+    tracing::info!(
+        "  GET    /documents/:id/reply-tree/expand - Expand a collapsed reply tree node"
+    );
+    tracing::info!("  GET    /documents/:id/export   - Export a thread as JSON or Markdown");
     tracing::info!("  DELETE /documents/:id          - Delete specific document");
+    tracing::info!("  GET    /content/:hash          - Fetch a content blob (attachment) by hash");
+    tracing::info!("  GET    /search?q=              - Full-text search over documents");
     tracing::info!("  POST   /publish                - Publish new document");
     tracing::info!("  POST /identity/challenge     - Request challenge for identity server");
     tracing::info!("  POST /identity/register      - Register identity server");
+    tracing::info!("  POST /identity/update        - Record a username change for a public key");
+    tracing::info!("  GET  /identity-servers       - List identity servers with health status");
+    tracing::info!("  DELETE /identity-servers/:server_id - Revoke an identity server");
+    tracing::info!("  POST /identity-servers/:server_id/rotate - Rotate an identity server key");
+    tracing::info!("  POST /tokens                 - Issue an API token against an identity pod");
+    tracing::info!("  DELETE /tokens               - Revoke the presented API token");
+    tracing::info!("  POST /threads/:id/subscribe  - Subscribe to reply notifications for a thread");
+    tracing::info!("  DELETE /threads/:id/subscribe - Unsubscribe from a thread");
     tracing::info!("  POST /documents/:id/upvote   - Upvote a document");
+    tracing::info!("  GET  /documents/:id/upvotes/verify - Re-verify a document's stored upvote pods");
+    tracing::info!("  POST /documents/:id/flag     - Flag a document for moderation");
+    tracing::info!("  GET  /admin/flags            - List flagged documents (requires admin token)");
+    tracing::info!("  POST /admin/flags/:id/resolve - Resolve a flag (requires admin token)");
+    tracing::info!("  GET  /events                 - Server-sent events stream");
+    tracing::info!("  GET  /feed.atom              - Atom feed of recent posts");
+    tracing::info!("  POST /admin/posts/:id/pin    - Pin a post to the top of the list (requires admin token)");
+    tracing::info!("  POST /admin/posts/:id/unpin  - Unpin a post (requires admin token)");
+    tracing::info!("  POST /admin/gc               - Garbage collect orphaned content");
+    tracing::info!("  POST /admin/backup           - Snapshot database and blob manifest (requires admin token)");
+    tracing::info!("  GET  /admin/jobs             - List upvote recount jobs (requires admin token)");
+    tracing::info!("  GET  /admin/stats            - Operator stats (requires admin token)");
+    tracing::info!("  GET  /metrics                - Prometheus metrics (requires admin token)");
+    tracing::info!("  GET  /notifications          - List notifications for a user");
+    tracing::info!("  POST /notifications/:id/read - Mark a notification as read");
 
-    axum::serve(listener, app).await?;
+    axum::serve(listener, app)
+        .with_graceful_shutdown(shutdown_signal())
+        .await?;
     Ok(())
 }
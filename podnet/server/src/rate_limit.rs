@@ -0,0 +1,168 @@
+//! Token-bucket rate limiting for publish, upvote, and identity endpoints.
+//!
+//! Authenticated routes are keyed by the verified username (the handlers call
+//! into the limiter after pod verification has extracted the username), while
+//! unauthenticated identity routes fall back to the client IP.
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::Instant,
+};
+
+use axum::{
+    extract::{Request, State},
+    http::{HeaderMap, HeaderValue, StatusCode, header},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+
+struct TokenBucket {
+    tokens: f64,
+    last_refill: Instant,
+}
+
+/// A token bucket rate limiter with one bucket per key. The bucket starts full
+/// with `budget` tokens and refills continuously over `window_secs`.
+pub struct RateLimiter {
+    capacity: f64,
+    refill_per_sec: f64,
+    buckets: Mutex<HashMap<String, TokenBucket>>,
+}
+
+impl RateLimiter {
+    pub fn new(budget: u32, window_secs: u64) -> Self {
+        let capacity = budget.max(1) as f64;
+        Self {
+            capacity,
+            refill_per_sec: capacity / window_secs.max(1) as f64,
+            buckets: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Try to take one token for `key`. On refusal, returns the number of
+    /// seconds after which a retry will succeed.
+    pub fn check(&self, key: &str) -> Result<(), u64> {
+        self.check_at(key, Instant::now())
+    }
+
+    fn check_at(&self, key: &str, now: Instant) -> Result<(), u64> {
+        let mut buckets = self.buckets.lock().unwrap();
+        let bucket = buckets.entry(key.to_string()).or_insert(TokenBucket {
+            tokens: self.capacity,
+            last_refill: now,
+        });
+
+        let elapsed = now.saturating_duration_since(bucket.last_refill).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * self.refill_per_sec).min(self.capacity);
+        bucket.last_refill = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(((1.0 - bucket.tokens) / self.refill_per_sec).ceil() as u64)
+        }
+    }
+}
+
+/// Per-route rate limiters, built from the server configuration
+pub struct RateLimiters {
+    pub publish: RateLimiter,
+    pub upvote: RateLimiter,
+    pub identity: RateLimiter,
+}
+
+const HOUR_SECS: u64 = 3600;
+
+impl RateLimiters {
+    pub fn from_config(config: &crate::config::ServerConfig) -> Self {
+        Self {
+            publish: RateLimiter::new(config.publish_per_hour, HOUR_SECS),
+            upvote: RateLimiter::new(config.upvote_per_hour, HOUR_SECS),
+            identity: RateLimiter::new(config.identity_per_hour, HOUR_SECS),
+        }
+    }
+}
+
+/// Build a 429 response with a Retry-After header
+pub fn too_many_requests(retry_after_secs: u64) -> Response {
+    let mut response = StatusCode::TOO_MANY_REQUESTS.into_response();
+    if let Ok(value) = HeaderValue::from_str(&retry_after_secs.to_string()) {
+        response.headers_mut().insert(header::RETRY_AFTER, value);
+    }
+    response
+}
+
+// Best-effort client IP for unauthenticated endpoints. Honors X-Forwarded-For
+// for deployments behind a proxy.
+fn client_ip(headers: &HeaderMap) -> String {
+    headers
+        .get("x-forwarded-for")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.split(',').next())
+        .map(|ip| ip.trim().to_string())
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+/// Middleware applying the identity-route budget keyed by client IP
+pub async fn identity_rate_limit(
+    State(state): State<Arc<crate::AppState>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let key = client_ip(request.headers());
+    match state.rate_limiters.identity.check(&key) {
+        Ok(()) => next.run(request).await,
+        Err(retry_after) => {
+            tracing::warn!("Rate limit exceeded on identity endpoint for {key}");
+            too_many_requests(retry_after)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[test]
+    fn test_budget_exhaustion_and_recovery() {
+        let limiter = RateLimiter::new(3, 60);
+        let start = Instant::now();
+
+        // The full budget is available up front
+        for _ in 0..3 {
+            assert!(limiter.check_at("alice", start).is_ok());
+        }
+
+        // The next request is refused with a sensible Retry-After
+        let retry_after = limiter.check_at("alice", start).unwrap_err();
+        assert!(retry_after > 0);
+        assert!(retry_after <= 20); // one token refills in window/budget = 20s
+
+        // Other keys are unaffected
+        assert!(limiter.check_at("bob", start).is_ok());
+
+        // After the advertised wait, the request succeeds again
+        let later = start + Duration::from_secs(retry_after);
+        assert!(limiter.check_at("alice", later).is_ok());
+
+        // After a full window the bucket is back to capacity
+        let recovered = start + Duration::from_secs(120);
+        for _ in 0..3 {
+            assert!(limiter.check_at("alice", recovered).is_ok());
+        }
+    }
+
+    #[test]
+    fn test_too_many_requests_response() {
+        let response = too_many_requests(42);
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(
+            response.headers().get(header::RETRY_AFTER).unwrap(),
+            &HeaderValue::from_static("42")
+        );
+    }
+}
@@ -1,19 +1,67 @@
-use std::{fs, path::PathBuf};
+use std::{fs, path::PathBuf, time::SystemTime};
 
 use anyhow::Result;
 use hex::ToHex;
 use pod2::middleware::{Hash, Value, hash_values};
 use podnet_models::DocumentContent;
 
+/// Aggregate size of the content store, for the operator stats endpoint
+#[derive(Debug, Clone, Copy)]
+pub struct StorageStats {
+    pub blob_count: usize,
+    pub total_bytes: u64,
+}
+
+/// A content blob on disk: its hex hash, size in bytes, and modification time
+#[derive(Debug, Clone)]
+pub struct StoredBlob {
+    pub hash: String,
+    pub size: u64,
+    pub modified: SystemTime,
+}
+
+/// A stored blob no longer hashes to the id it is filed under: the bytes on
+/// disk were corrupted or tampered with since they were written.
+#[derive(Debug)]
+pub struct ContentCorrupted {
+    pub expected: String,
+    pub actual: String,
+}
+
+impl std::fmt::Display for ContentCorrupted {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "stored content is corrupted: expected hash {}, found {}",
+            self.expected, self.actual
+        )
+    }
+}
+
+impl std::error::Error for ContentCorrupted {}
+
+#[derive(Clone)]
 pub struct ContentAddressedStorage {
     base_path: PathBuf,
+    verify_on_read: bool,
 }
 
 impl ContentAddressedStorage {
     pub fn new(base_path: &str) -> Result<Self> {
         let path = PathBuf::from(base_path);
         fs::create_dir_all(&path)?;
-        Ok(Self { base_path: path })
+        Ok(Self {
+            base_path: path,
+            verify_on_read: true,
+        })
+    }
+
+    /// Disable (or re-enable) re-hashing retrieved documents against their
+    /// requested hash. Verification is on by default; operators who measure
+    /// the re-hash as too costly for their read path can switch it off.
+    pub fn with_verify_on_read(mut self, verify_on_read: bool) -> Self {
+        self.verify_on_read = verify_on_read;
+        self
     }
 
     pub fn hash_content(content: &str) -> Hash {
@@ -64,6 +112,36 @@ impl ContentAddressedStorage {
         Ok(hash)
     }
 
+    /// Store raw attachment bytes under their [`DocumentFile::content_id_for`]
+    /// hash. The bytes are written verbatim, so range reads can serve slices
+    /// of the file directly.
+    pub fn store_bytes(&self, bytes: &[u8]) -> Result<Hash> {
+        let hash = podnet_models::DocumentFile::content_id_for(bytes);
+        let hash_string: String = hash.encode_hex();
+        let file_path = self.get_file_path(&hash_string);
+
+        if let Some(parent) = file_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        if !file_path.exists() {
+            fs::write(&file_path, bytes)?;
+        }
+
+        Ok(hash)
+    }
+
+    /// Read a blob back as raw bytes, for serving attachments
+    pub fn retrieve_bytes(&self, hash: &str) -> Result<Option<Vec<u8>>> {
+        let file_path = self.get_file_path(hash);
+
+        if file_path.exists() {
+            Ok(Some(fs::read(file_path)?))
+        } else {
+            Ok(None)
+        }
+    }
+
     pub fn retrieve(&self, hash: &Hash) -> Result<Option<String>> {
         let hash_string: String = hash.encode_hex();
         let file_path = self.get_file_path(&hash_string);
@@ -82,6 +160,18 @@ impl ContentAddressedStorage {
 
         if file_path.exists() {
             let json_string = fs::read_to_string(file_path)?;
+            // The document hash is the hash of its serialized JSON, so the
+            // raw file contents can be checked before parsing
+            if self.verify_on_read {
+                let actual = Self::hash_content(&json_string);
+                if actual != *hash {
+                    return Err(ContentCorrupted {
+                        expected: hash_string,
+                        actual: actual.encode_hex(),
+                    }
+                    .into());
+                }
+            }
             let content: DocumentContent = serde_json::from_str(&json_string)?;
             Ok(Some(content))
         } else {
@@ -89,7 +179,138 @@ impl ContentAddressedStorage {
         }
     }
 
+    /// Async variant of [`store_document_content`](Self::store_document_content)
+    /// that runs the filesystem write on the blocking pool, so request handlers
+    /// never stall the async executor on disk I/O.
+    pub async fn store_document_content_async(&self, content: DocumentContent) -> Result<Hash> {
+        let storage = self.clone();
+        tokio::task::spawn_blocking(move || storage.store_document_content(&content)).await?
+    }
+
+    /// Async variant of [`retrieve_document_content`](Self::retrieve_document_content)
+    /// that runs the filesystem read on the blocking pool.
+    pub async fn retrieve_document_content_async(
+        &self,
+        hash: Hash,
+    ) -> Result<Option<DocumentContent>> {
+        let storage = self.clone();
+        tokio::task::spawn_blocking(move || storage.retrieve_document_content(&hash)).await?
+    }
+
+    /// Async variant of [`store_bytes`](Self::store_bytes) that runs the
+    /// filesystem write on the blocking pool.
+    pub async fn store_bytes_async(&self, bytes: Vec<u8>) -> Result<Hash> {
+        let storage = self.clone();
+        tokio::task::spawn_blocking(move || storage.store_bytes(&bytes)).await?
+    }
+
+    /// Async variant of [`retrieve_bytes`](Self::retrieve_bytes) that runs the
+    /// filesystem read on the blocking pool.
+    pub async fn retrieve_bytes_async(&self, hash: String) -> Result<Option<Vec<u8>>> {
+        let storage = self.clone();
+        tokio::task::spawn_blocking(move || storage.retrieve_bytes(&hash)).await?
+    }
+
     pub fn exists(&self, hash: &str) -> bool {
         self.get_file_path(hash).exists()
     }
+
+    /// List every content blob currently on disk
+    pub fn list_blobs(&self) -> Result<Vec<StoredBlob>> {
+        let mut blobs = Vec::new();
+        for prefix_entry in fs::read_dir(&self.base_path)? {
+            let prefix_entry = prefix_entry?;
+            if !prefix_entry.file_type()?.is_dir() {
+                continue;
+            }
+            let prefix = prefix_entry.file_name().to_string_lossy().to_string();
+            for blob_entry in fs::read_dir(prefix_entry.path())? {
+                let blob_entry = blob_entry?;
+                if !blob_entry.file_type()?.is_file() {
+                    continue;
+                }
+                let metadata = blob_entry.metadata()?;
+                let suffix = blob_entry.file_name().to_string_lossy().to_string();
+                blobs.push(StoredBlob {
+                    hash: format!("{prefix}{suffix}"),
+                    size: metadata.len(),
+                    modified: metadata.modified()?,
+                });
+            }
+        }
+        Ok(blobs)
+    }
+
+    /// Count the stored blobs and their total size
+    pub fn stats(&self) -> Result<StorageStats> {
+        let blobs = self.list_blobs()?;
+        Ok(StorageStats {
+            blob_count: blobs.len(),
+            total_bytes: blobs.iter().map(|b| b.size).sum(),
+        })
+    }
+
+    /// Delete a blob by hash, cleaning up its prefix directory if it becomes empty
+    pub fn delete(&self, hash: &str) -> Result<()> {
+        let file_path = self.get_file_path(hash);
+        fs::remove_file(&file_path)?;
+        if let Some(parent) = file_path.parent() {
+            // Ignore the error: the directory may simply not be empty
+            let _ = fs::remove_dir(parent);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_storage() -> ContentAddressedStorage {
+        let path =
+            std::env::temp_dir().join(format!("podnet_storage_test_{}", rand::random::<u64>()));
+        ContentAddressedStorage::new(path.to_str().unwrap()).unwrap()
+    }
+
+    fn test_content() -> DocumentContent {
+        DocumentContent {
+            message: Some("stored content".to_string()),
+            file: None,
+            files: Vec::new(),
+            url: None,
+        }
+    }
+
+    #[test]
+    fn test_tampered_blob_is_reported_as_corrupted() {
+        let storage = test_storage();
+        let hash = storage.store_document_content(&test_content()).unwrap();
+
+        // Retrieval succeeds while the blob is intact
+        assert!(storage.retrieve_document_content(&hash).unwrap().is_some());
+
+        // Rewrite the stored file so it no longer matches its hash
+        let hash_string: String = hash.encode_hex();
+        let file_path = storage.get_file_path(&hash_string);
+        fs::write(
+            &file_path,
+            serde_json::to_string(&DocumentContent {
+                message: Some("tampered content".to_string()),
+                ..test_content()
+            })
+            .unwrap(),
+        )
+        .unwrap();
+
+        let err = storage.retrieve_document_content(&hash).unwrap_err();
+        assert!(err.downcast_ref::<ContentCorrupted>().is_some(), "{err}");
+
+        // With verification switched off the tampered blob is served as-is
+        let storage = storage.with_verify_on_read(false);
+        let content = storage
+            .retrieve_document_content(&hash)
+            .unwrap()
+            .unwrap();
+        assert_eq!(content.message.as_deref(), Some("tampered content"));
+    }
 }